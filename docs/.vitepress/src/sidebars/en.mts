@@ -73,6 +73,12 @@ export const sidebar = [
                     { text: "Wildcard Subscription", link: "" },
                     { text: "Session Persistence", link: "" },
                     { text: "Shared Subscription", link: "" },
+                    { text: "Hooks", link: "/RobustMQ-MQTT/Hook.md" },
+                    { text: "WebHook", link: "/RobustMQ-MQTT/WebHook.md" },
+                    { text: "WASM Plugins", link: "/RobustMQ-MQTT/WasmPlugin.md" },
+                    { text: "Scheduled Messages", link: "/RobustMQ-MQTT/ScheduledMessage.md" },
+                    { text: "Message Deduplication", link: "/RobustMQ-MQTT/IdempotentMessage.md" },
+                    { text: "Connectors", link: "/RobustMQ-MQTT/Connector.md" },
                 ],
             },
             {
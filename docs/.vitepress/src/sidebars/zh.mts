@@ -73,6 +73,12 @@ export const sidebar = [
                     { text: "通配符订阅", link: "" },
                     { text: "Session 持久化", link: "" },
                     { text: "共享订阅", link: "" },
+                    { text: "钩子", link: "/zh/RobustMQ-MQTT/Hook.md" },
+                    { text: "WebHook", link: "/zh/RobustMQ-MQTT/WebHook.md" },
+                    { text: "WASM 插件", link: "/zh/RobustMQ-MQTT/WasmPlugin.md" },
+                    { text: "定时消息", link: "/zh/RobustMQ-MQTT/ScheduledMessage.md" },
+                    { text: "消息去重", link: "/zh/RobustMQ-MQTT/IdempotentMessage.md" },
+                    { text: "连接器", link: "/zh/RobustMQ-MQTT/Connector.md" },
                 ],
             },
             {
@@ -17,17 +17,27 @@
 use common_base::enum_type::sort_type::SortType;
 use common_base::tools::unique_id;
 use grpc_clients::mqtt::admin::call::{
-    mqtt_broker_bind_schema, mqtt_broker_cluster_status, mqtt_broker_create_acl,
-    mqtt_broker_create_blacklist, mqtt_broker_create_connector, mqtt_broker_create_schema,
-    mqtt_broker_create_topic_rewrite_rule, mqtt_broker_create_user, mqtt_broker_delete_acl,
-    mqtt_broker_delete_auto_subscribe_rule, mqtt_broker_delete_blacklist,
-    mqtt_broker_delete_connector, mqtt_broker_delete_schema, mqtt_broker_delete_topic_rewrite_rule,
-    mqtt_broker_delete_user, mqtt_broker_enable_flapping_detect, mqtt_broker_enable_slow_subscribe,
-    mqtt_broker_list_acl, mqtt_broker_list_auto_subscribe_rule, mqtt_broker_list_bind_schema,
-    mqtt_broker_list_blacklist, mqtt_broker_list_connection, mqtt_broker_list_connector,
-    mqtt_broker_list_schema, mqtt_broker_list_slow_subscribe, mqtt_broker_list_topic,
-    mqtt_broker_list_user, mqtt_broker_set_auto_subscribe_rule, mqtt_broker_unbind_schema,
-    mqtt_broker_update_connector, mqtt_broker_update_schema,
+    mqtt_broker_bind_schema, mqtt_broker_cancel_delay_message, mqtt_broker_cluster_status,
+    mqtt_broker_create_acl, mqtt_broker_create_blacklist, mqtt_broker_create_connector,
+    mqtt_broker_create_rule, mqtt_broker_create_scheduled_message, mqtt_broker_create_schema,
+    mqtt_broker_create_tenant, mqtt_broker_create_topic_rewrite_rule, mqtt_broker_create_user,
+    mqtt_broker_create_wasm_plugin, mqtt_broker_delete_acl, mqtt_broker_delete_auto_subscribe_rule,
+    mqtt_broker_delete_blacklist, mqtt_broker_delete_connector, mqtt_broker_delete_rule,
+    mqtt_broker_delete_scheduled_message, mqtt_broker_delete_schema, mqtt_broker_delete_tenant,
+    mqtt_broker_delete_topic_rewrite_rule, mqtt_broker_delete_user, mqtt_broker_delete_wasm_plugin,
+    mqtt_broker_drain_node, mqtt_broker_enable_flapping_detect, mqtt_broker_enable_slow_subscribe,
+    mqtt_broker_get_delay_message, mqtt_broker_list_acl, mqtt_broker_list_auto_subscribe_rule,
+    mqtt_broker_list_bind_schema, mqtt_broker_list_blacklist, mqtt_broker_list_connection,
+    mqtt_broker_list_connector, mqtt_broker_list_dead_letter_message,
+    mqtt_broker_list_delay_message, mqtt_broker_list_listener, mqtt_broker_list_rule,
+    mqtt_broker_list_scheduled_message, mqtt_broker_list_schema, mqtt_broker_list_slow_subscribe,
+    mqtt_broker_list_tenant, mqtt_broker_list_topic, mqtt_broker_list_user,
+    mqtt_broker_list_wasm_plugin, mqtt_broker_pause_connector,
+    mqtt_broker_redrive_dead_letter_message, mqtt_broker_reload_listener_cert,
+    mqtt_broker_reset_connector_offset, mqtt_broker_resume_connector,
+    mqtt_broker_set_auto_subscribe_rule, mqtt_broker_start_listener, mqtt_broker_stop_listener,
+    mqtt_broker_unbind_schema, mqtt_broker_undrain_node, mqtt_broker_update_connector,
+    mqtt_broker_update_schema,
 };
 use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::auto_subscribe_rule::MqttAutoSubscribeRule;
@@ -37,15 +47,23 @@
 use paho_mqtt::{DisconnectOptionsBuilder, MessageBuilder, Properties, PropertyCode, ReasonCode};
 use prettytable::{row, Table};
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    ClusterStatusRequest, CreateAclRequest, CreateBlacklistRequest, CreateTopicRewriteRuleRequest,
-    CreateUserRequest, DeleteAclRequest, DeleteAutoSubscribeRuleRequest, DeleteBlacklistRequest,
-    DeleteTopicRewriteRuleRequest, DeleteUserRequest, EnableFlappingDetectRequest,
-    EnableSlowSubscribeRequest, ListAclRequest, ListAutoSubscribeRuleRequest, ListBlacklistRequest,
-    ListConnectionRequest, ListSlowSubscribeRequest, ListTopicRequest, ListUserRequest,
-    MqttBindSchemaRequest, MqttCreateConnectorRequest, MqttCreateSchemaRequest,
-    MqttDeleteConnectorRequest, MqttDeleteSchemaRequest, MqttListBindSchemaRequest,
-    MqttListConnectorRequest, MqttListSchemaRequest, MqttUnbindSchemaRequest,
-    MqttUpdateConnectorRequest, MqttUpdateSchemaRequest, SetAutoSubscribeRuleRequest,
+    CancelDelayMessageRequest, ClusterStatusRequest, CreateAclRequest, CreateBlacklistRequest,
+    CreateRuleRequest, CreateScheduledMessageRequest, CreateTenantRequest,
+    CreateTopicRewriteRuleRequest, CreateUserRequest, CreateWasmPluginRequest, DeleteAclRequest,
+    DeleteAutoSubscribeRuleRequest, DeleteBlacklistRequest, DeleteRuleRequest,
+    DeleteScheduledMessageRequest, DeleteTenantRequest, DeleteTopicRewriteRuleRequest,
+    DeleteUserRequest, DeleteWasmPluginRequest, DrainNodeRequest, EnableFlappingDetectRequest,
+    EnableSlowSubscribeRequest, GetDelayMessageRequest, ListAclRequest,
+    ListAutoSubscribeRuleRequest, ListBlacklistRequest, ListConnectionRequest,
+    ListDeadLetterMessageRequest, ListDelayMessageRequest, ListListenerRequest, ListRuleRequest,
+    ListScheduledMessageRequest, ListSlowSubscribeRequest, ListTenantRequest, ListTopicRequest,
+    ListUserRequest, ListWasmPluginRequest, MqttBindSchemaRequest, MqttCreateConnectorRequest,
+    MqttCreateSchemaRequest, MqttDeleteConnectorRequest, MqttDeleteSchemaRequest,
+    MqttListBindSchemaRequest, MqttListConnectorRequest, MqttListSchemaRequest,
+    MqttPauseConnectorRequest, MqttResetConnectorOffsetRequest, MqttResumeConnectorRequest,
+    MqttUnbindSchemaRequest, MqttUpdateConnectorRequest, MqttUpdateSchemaRequest,
+    RedriveDeadLetterMessageRequest, ReloadListenerCertRequest, SetAutoSubscribeRuleRequest,
+    StartListenerRequest, StopListenerRequest, UndrainNodeRequest,
 };
 use std::str::FromStr;
 use std::sync::Arc;
@@ -65,14 +83,42 @@ pub struct MqttCliCommandParam {
 pub enum MqttActionType {
     // cluster status
     Status,
+    DrainNode(DrainNodeRequest),
+    UndrainNode,
+
+    // listener
+    ListListener,
+    StartListener(StartListenerRequest),
+    StopListener(StopListenerRequest),
+    ReloadListenerCert(ReloadListenerCertRequest),
+
+    // tenant admin
+    ListTenant(ListTenantRequest),
+    CreateTenant(CreateTenantRequest),
+    DeleteTenant(DeleteTenantRequest),
+
+    // rule engine admin
+    ListRule(ListRuleRequest),
+    CreateRule(CreateRuleRequest),
+    DeleteRule(DeleteRuleRequest),
+
+    // scheduled message admin
+    ListScheduledMessage(ListScheduledMessageRequest),
+    CreateScheduledMessage(CreateScheduledMessageRequest),
+    DeleteScheduledMessage(DeleteScheduledMessageRequest),
+
+    // wasm plugin admin
+    ListWasmPlugin(ListWasmPluginRequest),
+    CreateWasmPlugin(CreateWasmPluginRequest),
+    DeleteWasmPlugin(DeleteWasmPluginRequest),
 
     // user admin
-    ListUser,
+    ListUser(ListUserRequest),
     CreateUser(CreateUserRequest),
     DeleteUser(DeleteUserRequest),
 
     // access control list admin
-    ListAcl,
+    ListAcl(ListAclRequest),
     CreateAcl(CreateAclRequest),
     DeleteAcl(DeleteAclRequest),
 
@@ -108,6 +154,9 @@ pub enum MqttActionType {
     CreateConnector(MqttCreateConnectorRequest),
     UpdateConnector(MqttUpdateConnectorRequest),
     DeleteConnector(MqttDeleteConnectorRequest),
+    PauseConnector(MqttPauseConnectorRequest),
+    ResumeConnector(MqttResumeConnectorRequest),
+    ResetConnectorOffset(MqttResetConnectorOffsetRequest),
 
     // schema
     ListSchema(MqttListSchemaRequest),
@@ -122,6 +171,15 @@ pub enum MqttActionType {
     ListAutoSubscribeRule(ListAutoSubscribeRuleRequest),
     SetAutoSubscribeRule(SetAutoSubscribeRuleRequest),
     DeleteAutoSubscribeRule(DeleteAutoSubscribeRuleRequest),
+
+    // dead letter
+    ListDeadLetterMessage(ListDeadLetterMessageRequest),
+    RedriveDeadLetterMessage(RedriveDeadLetterMessageRequest),
+
+    // delay message
+    ListDelayMessage(ListDelayMessageRequest),
+    GetDelayMessage(GetDelayMessageRequest),
+    CancelDelayMessage(CancelDelayMessageRequest),
 }
 
 pub struct MqttBrokerCommand {}
@@ -144,9 +202,85 @@ pub async fn start(&self, params: MqttCliCommandParam) {
             MqttActionType::Status => {
                 self.status(&client_pool, params.clone()).await;
             }
+            MqttActionType::DrainNode(ref request) => {
+                self.drain_node(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::UndrainNode => {
+                self.undrain_node(&client_pool, params.clone()).await;
+            }
+            // listener
+            MqttActionType::ListListener => {
+                self.list_listener(&client_pool, params.clone()).await;
+            }
+            MqttActionType::StartListener(ref request) => {
+                self.start_listener(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::StopListener(ref request) => {
+                self.stop_listener(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::ReloadListenerCert(ref request) => {
+                self.reload_listener_cert(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            // tenant admin
+            MqttActionType::ListTenant(ref request) => {
+                self.list_tenant(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::CreateTenant(ref request) => {
+                self.create_tenant(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::DeleteTenant(ref request) => {
+                self.delete_tenant(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            // rule engine admin
+            MqttActionType::ListRule(ref request) => {
+                self.list_rule(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::CreateRule(ref request) => {
+                self.create_rule(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::DeleteRule(ref request) => {
+                self.delete_rule(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            // scheduled message admin
+            MqttActionType::ListScheduledMessage(ref request) => {
+                self.list_scheduled_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::CreateScheduledMessage(ref request) => {
+                self.create_scheduled_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::DeleteScheduledMessage(ref request) => {
+                self.delete_scheduled_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            // wasm plugin admin
+            MqttActionType::ListWasmPlugin(ref request) => {
+                self.list_wasm_plugin(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::CreateWasmPlugin(ref request) => {
+                self.create_wasm_plugin(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::DeleteWasmPlugin(ref request) => {
+                self.delete_wasm_plugin(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
             // user admin
-            MqttActionType::ListUser => {
-                self.list_user(&client_pool, params.clone()).await;
+            MqttActionType::ListUser(ref request) => {
+                self.list_user(&client_pool, params.clone(), request.clone())
+                    .await;
             }
             MqttActionType::CreateUser(ref request) => {
                 self.create_user(&client_pool, params.clone(), request.clone())
@@ -157,8 +291,9 @@ pub async fn start(&self, params: MqttCliCommandParam) {
                     .await;
             }
             // access control list admin
-            MqttActionType::ListAcl => {
-                self.list_acl(&client_pool, params.clone()).await;
+            MqttActionType::ListAcl(ref request) => {
+                self.list_acl(&client_pool, params.clone(), request.clone())
+                    .await;
             }
             MqttActionType::CreateAcl(ref request) => {
                 self.create_acl(&client_pool, params.clone(), request.clone())
@@ -201,6 +336,18 @@ pub async fn start(&self, params: MqttCliCommandParam) {
                 self.update_connector(&client_pool, params.clone(), request.clone())
                     .await;
             }
+            MqttActionType::PauseConnector(ref request) => {
+                self.pause_connector(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::ResumeConnector(ref request) => {
+                self.resume_connector(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::ResetConnectorOffset(ref request) => {
+                self.reset_connector_offset(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
             // topic rewrite rule
             MqttActionType::CreateTopicRewriteRule(ref request) => {
                 self.create_topic_rewrite_rule(&client_pool, params.clone(), request.clone())
@@ -265,7 +412,7 @@ pub async fn start(&self, params: MqttCliCommandParam) {
 
             //auto subscribe
             MqttActionType::ListAutoSubscribeRule(ref request) => {
-                self.list_auto_subscribe_rule(&client_pool, params.clone(), *request)
+                self.list_auto_subscribe_rule(&client_pool, params.clone(), request.clone())
                     .await;
             }
             MqttActionType::SetAutoSubscribeRule(ref request) => {
@@ -276,6 +423,30 @@ pub async fn start(&self, params: MqttCliCommandParam) {
                 self.delete_auto_subscribe_rule(&client_pool, params.clone(), request.clone())
                     .await;
             }
+
+            // dead letter
+            MqttActionType::ListDeadLetterMessage(ref request) => {
+                self.list_dead_letter_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::RedriveDeadLetterMessage(ref request) => {
+                self.redrive_dead_letter_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+
+            // delay message
+            MqttActionType::ListDelayMessage(ref request) => {
+                self.list_delay_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::GetDelayMessage(ref request) => {
+                self.get_delay_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
+            MqttActionType::CancelDelayMessage(ref request) => {
+                self.cancel_delay_message(&client_pool, params.clone(), request.clone())
+                    .await;
+            }
         }
     }
     async fn publish(&self, params: MqttCliCommandParam, args: PublishArgsRequest) {
@@ -428,6 +599,143 @@ async fn status(&self, client_pool: &ClientPool, params: MqttCliCommandParam) {
             }
         }
     }
+
+    async fn drain_node(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: DrainNodeRequest,
+    ) {
+        match mqtt_broker_drain_node(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(data) => {
+                println!(
+                    "Start draining the node, {} connections will be disconnected",
+                    data.connection_num
+                )
+            }
+            Err(e) => {
+                println!("MQTT broker drain node exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn undrain_node(&self, client_pool: &ClientPool, params: MqttCliCommandParam) {
+        let request = UndrainNodeRequest {};
+        match mqtt_broker_undrain_node(client_pool, &grpc_addr(params.server), request).await {
+            Ok(_) => {
+                println!("Node drain was cancelled, new connections are accepted again")
+            }
+            Err(e) => {
+                println!("MQTT broker undrain node exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    // ------------ listener ------------
+    async fn list_listener(&self, client_pool: &ClientPool, params: MqttCliCommandParam) {
+        let request = ListListenerRequest {};
+        match mqtt_broker_list_listener(client_pool, &grpc_addr(params.server), request).await {
+            Ok(data) => {
+                let mut table = Table::new();
+
+                println!("listener list:");
+                table.add_row(row![
+                    "name",
+                    "protocol",
+                    "bind",
+                    "running",
+                    "connection_num",
+                    "max_connections",
+                    "authentication",
+                    "mountpoint",
+                    "proxy_protocol",
+                ]);
+
+                for raw in data.listeners {
+                    table.add_row(row![
+                        raw.name,
+                        raw.protocol,
+                        raw.bind,
+                        raw.running,
+                        raw.connection_num,
+                        raw.max_connections,
+                        raw.authentication.join(","),
+                        raw.mountpoint,
+                        raw.proxy_protocol,
+                    ]);
+                }
+                // output cmd
+                table.printstd();
+            }
+            Err(e) => {
+                println!("MQTT broker list listener exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn start_listener(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: StartListenerRequest,
+    ) {
+        match mqtt_broker_start_listener(client_pool, &grpc_addr(params.server), cli_request).await
+        {
+            Ok(_) => {
+                println!("Started successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker start listener exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn stop_listener(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: StopListenerRequest,
+    ) {
+        match mqtt_broker_stop_listener(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(data) => {
+                println!(
+                    "Stopped successfully, {} connections were disconnected",
+                    data.connection_num
+                )
+            }
+            Err(e) => {
+                println!("MQTT broker stop listener exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn reload_listener_cert(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ReloadListenerCertRequest,
+    ) {
+        match mqtt_broker_reload_listener_cert(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(data) => {
+                println!(
+                    "Reloaded the TLS certificates of listeners: {}",
+                    data.listeners.join(",")
+                )
+            }
+            Err(e) => {
+                println!("MQTT broker reload listener certificate exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
     // ------------ user admin ------------
 
     async fn create_user(
@@ -464,16 +772,24 @@ async fn delete_user(
         }
     }
 
-    async fn list_user(&self, client_pool: &ClientPool, params: MqttCliCommandParam) {
-        let request = ListUserRequest {};
-        match mqtt_broker_list_user(client_pool, &grpc_addr(params.server), request).await {
+    async fn list_user(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListUserRequest,
+    ) {
+        match mqtt_broker_list_user(client_pool, &grpc_addr(params.server), cli_request).await {
             Ok(data) => {
                 // format table
                 let mut table = Table::new();
-                table.add_row(row!["username", "is_superuser"]);
+                table.add_row(row!["username", "is_superuser", "tenant"]);
                 for user in data.users {
                     let mqtt_user = serde_json::from_slice::<MqttUser>(user.as_slice()).unwrap();
-                    table.add_row(row![mqtt_user.username.as_str(), mqtt_user.is_superuser]);
+                    table.add_row(row![
+                        mqtt_user.username.as_str(),
+                        mqtt_user.is_superuser,
+                        mqtt_user.tenant.as_str()
+                    ]);
                 }
                 // output cmd
                 table.printstd()
@@ -484,6 +800,346 @@ async fn list_user(&self, client_pool: &ClientPool, params: MqttCliCommandParam)
             }
         }
     }
+    // -------------- tenant admin --------------
+    async fn list_tenant(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListTenantRequest,
+    ) {
+        match mqtt_broker_list_tenant(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(data) => {
+                println!("tenant list:");
+                // format table
+                let mut table = Table::new();
+                table.add_row(row![
+                    "tenant_name",
+                    "desc",
+                    "connection_num",
+                    "max_connections",
+                    "topic_num",
+                    "max_topics",
+                    "max_publish_rate",
+                ]);
+                for raw in data.tenants {
+                    table.add_row(row![
+                        raw.tenant_name,
+                        raw.desc,
+                        raw.connection_num,
+                        raw.max_connections,
+                        raw.topic_num,
+                        raw.max_topics,
+                        raw.max_publish_rate,
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list tenant exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn create_tenant(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: CreateTenantRequest,
+    ) {
+        match mqtt_broker_create_tenant(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(_) => {
+                println!("Created successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker create tenant exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_tenant(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: DeleteTenantRequest,
+    ) {
+        match mqtt_broker_delete_tenant(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(_) => {
+                println!("Deleted successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker delete tenant exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    // -------------- rule engine admin --------------
+    async fn list_rule(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListRuleRequest,
+    ) {
+        match mqtt_broker_list_rule(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(data) => {
+                println!("rule list:");
+                // format table
+                let mut table = Table::new();
+                table.add_row(row![
+                    "rule_name",
+                    "sql",
+                    "actions",
+                    "desc",
+                    "matched_num",
+                    "passed_num",
+                    "failed_num",
+                ]);
+                for raw in data.rules {
+                    table.add_row(row![
+                        raw.rule_name,
+                        raw.sql,
+                        raw.actions,
+                        raw.desc,
+                        raw.matched_num,
+                        raw.passed_num,
+                        raw.failed_num,
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list rule exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn create_rule(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: CreateRuleRequest,
+    ) {
+        match mqtt_broker_create_rule(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(_) => {
+                println!("Created successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker create rule exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_rule(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: DeleteRuleRequest,
+    ) {
+        match mqtt_broker_delete_rule(client_pool, &grpc_addr(params.server), cli_request).await {
+            Ok(_) => {
+                println!("Deleted successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker delete rule exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    // -------------- scheduled message admin --------------
+    async fn list_scheduled_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListScheduledMessageRequest,
+    ) {
+        match mqtt_broker_list_scheduled_message(
+            client_pool,
+            &grpc_addr(params.server),
+            cli_request,
+        )
+        .await
+        {
+            Ok(data) => {
+                println!("scheduled message list:");
+                // format table
+                let mut table = Table::new();
+                table.add_row(row![
+                    "name",
+                    "topic",
+                    "qos",
+                    "retain",
+                    "cron",
+                    "interval_sec",
+                    "start_time",
+                    "end_time",
+                    "last_fire_time",
+                    "next_fire_time",
+                    "fire_count",
+                    "payload_size",
+                    "desc",
+                ]);
+                for raw in data.messages {
+                    table.add_row(row![
+                        raw.name,
+                        raw.topic,
+                        raw.qos,
+                        raw.retain,
+                        raw.cron,
+                        raw.interval_sec,
+                        raw.start_time,
+                        raw.end_time,
+                        raw.last_fire_time,
+                        raw.next_fire_time,
+                        raw.fire_count,
+                        raw.payload_size,
+                        raw.desc,
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list scheduled message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn create_scheduled_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: CreateScheduledMessageRequest,
+    ) {
+        match mqtt_broker_create_scheduled_message(
+            client_pool,
+            &grpc_addr(params.server),
+            cli_request,
+        )
+        .await
+        {
+            Ok(_) => {
+                println!("Created successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker create scheduled message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_scheduled_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: DeleteScheduledMessageRequest,
+    ) {
+        match mqtt_broker_delete_scheduled_message(
+            client_pool,
+            &grpc_addr(params.server),
+            cli_request,
+        )
+        .await
+        {
+            Ok(_) => {
+                println!("Deleted successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker delete scheduled message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    // -------------- wasm plugin admin --------------
+    async fn list_wasm_plugin(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListWasmPluginRequest,
+    ) {
+        match mqtt_broker_list_wasm_plugin(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(data) => {
+                println!("wasm plugin list:");
+                // format table
+                let mut table = Table::new();
+                table.add_row(row![
+                    "plugin_name",
+                    "topics",
+                    "tenant",
+                    "module_size",
+                    "max_fuel",
+                    "max_memory_bytes",
+                    "desc",
+                ]);
+                for raw in data.plugins {
+                    table.add_row(row![
+                        raw.plugin_name,
+                        raw.topics.join(","),
+                        raw.tenant,
+                        raw.module_size,
+                        raw.max_fuel,
+                        raw.max_memory_bytes,
+                        raw.desc,
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list wasm plugin exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn create_wasm_plugin(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: CreateWasmPluginRequest,
+    ) {
+        match mqtt_broker_create_wasm_plugin(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(_) => {
+                println!("Created successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker create wasm plugin exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn delete_wasm_plugin(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: DeleteWasmPluginRequest,
+    ) {
+        match mqtt_broker_delete_wasm_plugin(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(_) => {
+                println!("Deleted successfully!");
+            }
+            Err(e) => {
+                println!("MQTT broker delete wasm plugin exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
     // -------------- acl admin --------------
 
     async fn create_acl(
@@ -520,8 +1176,16 @@ async fn delete_acl(
         }
     }
 
-    async fn list_acl(&self, client_pool: &ClientPool, params: MqttCliCommandParam) {
-        let request = ListAclRequest::default();
+    async fn list_acl(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListAclRequest,
+    ) {
+        let request = ListAclRequest {
+            tenant: cli_request.tenant,
+            ..Default::default()
+        };
         match mqtt_broker_list_acl(client_pool, &grpc_addr(params.server), request).await {
             Ok(data) => {
                 // format table
@@ -532,7 +1196,8 @@ async fn list_acl(&self, client_pool: &ClientPool, params: MqttCliCommandParam)
                     "topic",
                     "ip",
                     "action",
-                    "permission"
+                    "permission",
+                    "tenant"
                 ]);
                 for acl in data.acls {
                     let mqtt_acl = serde_json::from_slice::<MqttAcl>(acl.as_slice()).unwrap();
@@ -542,7 +1207,8 @@ async fn list_acl(&self, client_pool: &ClientPool, params: MqttCliCommandParam)
                         mqtt_acl.topic,
                         mqtt_acl.ip,
                         mqtt_acl.action,
-                        mqtt_acl.permission
+                        mqtt_acl.permission,
+                        mqtt_acl.tenant
                     ]);
                 }
                 // output cmd
@@ -791,6 +1457,7 @@ async fn list_topic(
                     "topic_name",
                     "cluster_name",
                     "is_contain_retain_message",
+                    "tenant",
                 ]);
                 let topics = data.topics;
                 for topic in topics {
@@ -798,7 +1465,8 @@ async fn list_topic(
                         topic.topic_id,
                         topic.topic_name,
                         topic.cluster_name,
-                        topic.is_contain_retain_message
+                        topic.is_contain_retain_message,
+                        topic.tenant
                     ]);
                 }
                 // output cmd
@@ -834,10 +1502,18 @@ async fn list_connectors(
                     "broker id",
                     "create time",
                     "update time",
+                    "success num",
+                    "error num",
+                    "dead letter num",
+                    "lag",
+                    "delivery delay sec",
+                    "last success time",
+                    "last error",
                 ]);
 
                 for mqtt_connector in data.connectors {
                     let connector = MQTTConnector::decode(&mqtt_connector);
+                    let runtime_status = connector.runtime_status.unwrap_or_default();
                     table.add_row(row![
                         connector.cluster_name,
                         connector.connector_name,
@@ -847,7 +1523,14 @@ async fn list_connectors(
                         connector.status,
                         connector.broker_id.unwrap_or(0),
                         connector.create_time,
-                        connector.update_time
+                        connector.update_time,
+                        runtime_status.success_num,
+                        runtime_status.error_num,
+                        runtime_status.dead_letter_num,
+                        runtime_status.lag,
+                        runtime_status.delivery_delay_sec,
+                        runtime_status.last_success_time,
+                        runtime_status.last_error
                     ]);
                 }
 
@@ -918,6 +1601,66 @@ async fn update_connector(
         }
     }
 
+    async fn pause_connector(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: MqttPauseConnectorRequest,
+    ) {
+        match mqtt_broker_pause_connector(client_pool, &grpc_addr(params.server), cli_request).await
+        {
+            Ok(_) => {
+                println!("Paused successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker pause connector exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn resume_connector(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: MqttResumeConnectorRequest,
+    ) {
+        match mqtt_broker_resume_connector(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(_) => {
+                println!("Resumed successfully!")
+            }
+            Err(e) => {
+                println!("MQTT broker resume connector exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn reset_connector_offset(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: MqttResetConnectorOffsetRequest,
+    ) {
+        match mqtt_broker_reset_connector_offset(
+            client_pool,
+            &grpc_addr(params.server),
+            cli_request,
+        )
+        .await
+        {
+            Ok(data) => {
+                println!("Offset was reset to {} successfully!", data.offset)
+            }
+            Err(e) => {
+                println!("MQTT broker reset connector offset exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
     // ------------------ topic rewrite rule ----------------
     async fn create_topic_rewrite_rule(
         &self,
@@ -1174,8 +1917,9 @@ async fn list_auto_subscribe_rule(
         params: MqttCliCommandParam,
         cli_request: ListAutoSubscribeRuleRequest,
     ) {
-        let _ = cli_request;
-        let request = ListAutoSubscribeRuleRequest {};
+        let request = ListAutoSubscribeRuleRequest {
+            tenant: cli_request.tenant,
+        };
         match mqtt_broker_list_auto_subscribe_rule(client_pool, &grpc_addr(params.server), request)
             .await
         {
@@ -1188,6 +1932,7 @@ async fn list_auto_subscribe_rule(
                     "no_local",
                     "retain_as_published",
                     "retained_handling",
+                    "tenant",
                 ]);
                 for rule in data.auto_subscribe_rules {
                     let mqtt_auto_subscribe_rule =
@@ -1203,7 +1948,8 @@ async fn list_auto_subscribe_rule(
                         Into::<u8>::into(mqtt_auto_subscribe_rule.qos),
                         mqtt_auto_subscribe_rule.no_local,
                         mqtt_auto_subscribe_rule.retain_as_published,
-                        Into::<u8>::into(mqtt_auto_subscribe_rule.retained_handling)
+                        Into::<u8>::into(mqtt_auto_subscribe_rule.retained_handling),
+                        mqtt_auto_subscribe_rule.tenant
                     ]);
                 }
                 // output cmd
@@ -1215,6 +1961,171 @@ async fn list_auto_subscribe_rule(
             }
         }
     }
+
+    // ------------------ dead letter ----------------
+    async fn list_dead_letter_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListDeadLetterMessageRequest,
+    ) {
+        match mqtt_broker_list_dead_letter_message(
+            client_pool,
+            &grpc_addr(params.server),
+            cli_request,
+        )
+        .await
+        {
+            Ok(data) => {
+                println!("dead letter message list result:");
+                let mut table = Table::new();
+                table.add_row(row![
+                    "offset",
+                    "original_topic",
+                    "client_id",
+                    "reason",
+                    "attempts",
+                    "payload",
+                    "create_time",
+                ]);
+                for message in data.messages {
+                    table.add_row(row![
+                        message.offset,
+                        message.original_topic,
+                        message.client_id,
+                        message.reason,
+                        message.attempts,
+                        String::from_utf8_lossy(&message.payload),
+                        message.create_time
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list dead letter message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn redrive_dead_letter_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: RedriveDeadLetterMessageRequest,
+    ) {
+        match mqtt_broker_redrive_dead_letter_message(
+            client_pool,
+            &grpc_addr(params.server),
+            cli_request,
+        )
+        .await
+        {
+            Ok(data) => {
+                if data.error.is_empty() {
+                    println!("Redrive {} messages successfully!", data.redrive_num)
+                } else {
+                    println!("Redrive {} messages, then stopped", data.redrive_num);
+                    error_info(data.error);
+                }
+            }
+            Err(e) => {
+                println!("MQTT broker redrive dead letter message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    // ------------ delay message ------------
+    async fn list_delay_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: ListDelayMessageRequest,
+    ) {
+        match mqtt_broker_list_delay_message(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(data) => {
+                println!("delay message list result, total: {}", data.total);
+                let mut table = Table::new();
+                table.add_row(row![
+                    "id",
+                    "topic_name",
+                    "client_id",
+                    "delay_timestamp",
+                    "create_time",
+                ]);
+                for message in data.messages {
+                    table.add_row(row![
+                        message.id,
+                        message.topic_name,
+                        message.client_id,
+                        message.delay_timestamp,
+                        message.create_time
+                    ]);
+                }
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker list delay message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn get_delay_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: GetDelayMessageRequest,
+    ) {
+        match mqtt_broker_get_delay_message(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(data) => {
+                let mut table = Table::new();
+                if let Some(message) = data.message {
+                    table.add_row(row!["id", message.id]);
+                    table.add_row(row!["topic_name", message.topic_name]);
+                    table.add_row(row!["client_id", message.client_id]);
+                    table.add_row(row!["delay_timestamp", message.delay_timestamp]);
+                    table.add_row(row!["create_time", message.create_time]);
+                }
+                table.add_row(row!["qos", data.qos]);
+                table.add_row(row!["retain", data.retain]);
+                table.add_row(row!["user_properties", data.user_properties.join(",")]);
+                table.add_row(row!["payload", String::from_utf8_lossy(&data.payload)]);
+                // output cmd
+                table.printstd()
+            }
+            Err(e) => {
+                println!("MQTT broker get delay message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
+
+    async fn cancel_delay_message(
+        &self,
+        client_pool: &ClientPool,
+        params: MqttCliCommandParam,
+        cli_request: CancelDelayMessageRequest,
+    ) {
+        match mqtt_broker_cancel_delay_message(client_pool, &grpc_addr(params.server), cli_request)
+            .await
+        {
+            Ok(data) => {
+                println!("Cancel {} messages successfully!", data.cancel_num)
+            }
+            Err(e) => {
+                println!("MQTT broker cancel delay message exception");
+                error_info(e.to_string());
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -35,9 +35,13 @@
 };
 
 use crate::mqtt::admin::{
-    process_acl_args, process_blacklist_args, process_connector_args, process_slow_sub_args,
-    process_topic_rewrite_args, process_user_args, AclArgs, BlacklistArgs, ConnectorArgs,
-    FlappingDetectArgs, SlowSubArgs, TopicRewriteArgs, UserArgs,
+    process_acl_args, process_blacklist_args, process_connector_args, process_dead_letter_args,
+    process_delay_message_args, process_drain_node_args, process_listener_args, process_rule_args,
+    process_scheduled_message_args, process_slow_sub_args, process_tenant_args,
+    process_topic_rewrite_args, process_user_args, process_wasm_plugin_args, AclArgs,
+    BlacklistArgs, ConnectorArgs, DeadLetterArgs, DelayMessageArgs, DrainNodeArgs,
+    FlappingDetectArgs, ListenerArgs, RuleArgs, ScheduledMessageArgs, SlowSubArgs, TenantArgs,
+    TopicRewriteArgs, UserArgs, WasmPluginArgs,
 };
 use crate::mqtt::publish::{process_publish_args, PubSubArgs};
 
@@ -83,8 +87,22 @@ struct MqttArgs {
 enum MQTTAction {
     // cluster status
     Status,
+    // drain the broker node
+    DrainNode(DrainNodeArgs),
+    // cancel draining the broker node
+    UndrainNode,
+    // listener admin
+    Listener(ListenerArgs),
     // user admin
     User(UserArgs),
+    // tenant admin
+    Tenant(TenantArgs),
+    // rule engine admin
+    Rule(RuleArgs),
+    // wasm plugin admin
+    WasmPlugin(WasmPluginArgs),
+    // scheduled message admin
+    ScheduledMessage(ScheduledMessageArgs),
     // access control list admin
     Acl(AclArgs),
     // blacklist admin
@@ -101,6 +119,10 @@ enum MQTTAction {
     TopicRewriteRule(TopicRewriteArgs),
     // connector
     Connector(ConnectorArgs),
+    // dead letter
+    DeadLetter(DeadLetterArgs),
+    // delay message
+    DelayMessage(DelayMessageArgs),
 
     // schema
     ListSchema(ListSchemaArgs),
@@ -134,6 +156,9 @@ struct ListTopicArgs {
 
     #[arg(short, long, default_value = "e")]
     match_option: MatchOption,
+
+    #[arg(long, default_value = "")]
+    tenant: String,
 }
 
 #[derive(clap::Args, Debug)]
@@ -210,6 +235,20 @@ async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
             MQTTAction::Status => MqttActionType::Status,
             // user admin
             MQTTAction::User(args) => process_user_args(args),
+            // tenant admin
+            MQTTAction::Tenant(args) => process_tenant_args(args),
+            // rule engine admin
+            MQTTAction::Rule(args) => process_rule_args(args),
+            // wasm plugin admin
+            MQTTAction::WasmPlugin(args) => process_wasm_plugin_args(args),
+            // scheduled message admin
+            MQTTAction::ScheduledMessage(args) => process_scheduled_message_args(args),
+            // drain the broker node
+            MQTTAction::DrainNode(args) => process_drain_node_args(args),
+            // cancel draining the broker node
+            MQTTAction::UndrainNode => MqttActionType::UndrainNode,
+            // listener admin
+            MQTTAction::Listener(args) => process_listener_args(args),
             // access control list admin
             MQTTAction::Acl(args) => process_acl_args(args),
             // blacklist admin
@@ -227,6 +266,10 @@ async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
             MQTTAction::ListConnection => MqttActionType::ListConnection,
             // connector
             MQTTAction::Connector(args) => process_connector_args(args),
+            // dead letter
+            MQTTAction::DeadLetter(args) => process_dead_letter_args(args),
+            // delay message
+            MQTTAction::DelayMessage(args) => process_delay_message_args(args),
             // list topic
             MQTTAction::ListTopic(args) => MqttActionType::ListTopic(ListTopicRequest {
                 topic_name: args.topic_name,
@@ -235,6 +278,7 @@ async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
                     MatchOption::P => 1,
                     MatchOption::S => 2,
                 },
+                tenant: args.tenant,
             }),
             // topic rewrite rule
             MQTTAction::TopicRewriteRule(args) => process_topic_rewrite_args(args),
@@ -245,6 +289,7 @@ async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
             // schema
             MQTTAction::ListSchema(args) => MqttActionType::ListSchema(MqttListSchemaRequest {
                 schema_name: args.schema_name,
+                tenant: args.tenant,
             }),
             MQTTAction::CreateSchema(args) => {
                 MqttActionType::CreateSchema(MqttCreateSchemaRequest {
@@ -252,6 +297,7 @@ async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
                     schema_type: args.schema_type,
                     schema: args.schema,
                     desc: args.desc,
+                    tenant: args.tenant,
                 })
             }
             MQTTAction::UpdateSchema(args) => {
@@ -260,11 +306,13 @@ async fn handle_mqtt(args: MqttArgs, cmd: MqttBrokerCommand) {
                     schema_type: args.schema_type,
                     schema: args.schema,
                     desc: args.desc,
+                    tenant: args.tenant,
                 })
             }
             MQTTAction::DeleteSchema(args) => {
                 MqttActionType::DeleteSchema(MqttDeleteSchemaRequest {
                     schema_name: args.schema_name,
+                    tenant: args.tenant,
                 })
             }
             MQTTAction::ListBindSchema(args) => {
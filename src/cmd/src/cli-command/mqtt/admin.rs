@@ -20,11 +20,19 @@
 use common_base::enum_type::sort_type::SortType;
 use core::option::Option::Some;
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    CreateAclRequest, CreateBlacklistRequest, CreateTopicRewriteRuleRequest, CreateUserRequest,
-    DeleteAclRequest, DeleteAutoSubscribeRuleRequest, DeleteBlacklistRequest,
-    DeleteTopicRewriteRuleRequest, DeleteUserRequest, ListAutoSubscribeRuleRequest,
-    MqttCreateConnectorRequest, MqttDeleteConnectorRequest, MqttListConnectorRequest,
-    MqttUpdateConnectorRequest, SetAutoSubscribeRuleRequest,
+    CancelDelayMessageRequest, CreateAclRequest, CreateBlacklistRequest, CreateRuleRequest,
+    CreateScheduledMessageRequest, CreateTenantRequest, CreateTopicRewriteRuleRequest,
+    CreateUserRequest, CreateWasmPluginRequest, DeleteAclRequest, DeleteAutoSubscribeRuleRequest,
+    DeleteBlacklistRequest, DeleteRuleRequest, DeleteScheduledMessageRequest, DeleteTenantRequest,
+    DeleteTopicRewriteRuleRequest, DeleteUserRequest, DeleteWasmPluginRequest, DrainNodeRequest,
+    GetDelayMessageRequest, ListAclRequest, ListAutoSubscribeRuleRequest,
+    ListDeadLetterMessageRequest, ListDelayMessageRequest, ListRuleRequest,
+    ListScheduledMessageRequest, ListTenantRequest, ListUserRequest, ListWasmPluginRequest,
+    MqttConnectorOffsetResetType, MqttCreateConnectorRequest, MqttDeleteConnectorRequest,
+    MqttListConnectorRequest, MqttPauseConnectorRequest, MqttResetConnectorOffsetRequest,
+    MqttResumeConnectorRequest, MqttUpdateConnectorRequest, RedriveDeadLetterMessageRequest,
+    ReloadListenerCertRequest, SetAutoSubscribeRuleRequest, StartListenerRequest,
+    StopListenerRequest,
 };
 use protocol::broker_mqtt::broker_mqtt_admin::{
     EnableSlowSubscribeRequest, ListSlowSubscribeRequest,
@@ -42,7 +50,7 @@ pub(crate) struct UserArgs {
 #[derive(Debug, clap::Subcommand)]
 pub enum UserActionType {
     #[command(author = "RobustMQ", about = "action: list users", long_about = None)]
-    List,
+    List(ListUserArgs),
     #[command(author = "RobustMQ", about = "action: create user", long_about = None)]
     Create(CreateUserArgs),
     #[command(author = "RobustMQ", about = "action: delete user", long_about = None)]
@@ -59,6 +67,23 @@ pub(crate) struct CreateUserArgs {
     pub(crate) password: String,
     #[arg(short, long, default_value_t = false)]
     pub(crate) is_superuser: bool,
+    #[arg(short, long, default_value = "")]
+    pub(crate) mountpoint: String,
+    #[arg(short, long, default_value = "")]
+    pub(crate) tenant: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list users", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListUserArgs {
+    #[arg(
+        short,
+        long,
+        default_value = "",
+        help = "Only list the users of the tenant, all users are listed if empty"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(clap::Args, Debug)]
@@ -67,6 +92,13 @@ pub(crate) struct CreateUserArgs {
 pub(crate) struct DeleteUserArgs {
     #[arg(short, long, required = true)]
     pub(crate) username: String,
+    #[arg(
+        short,
+        long,
+        default_value = "",
+        help = "The tenant of the user, empty for the default tenant"
+    )]
+    pub(crate) tenant: String,
 }
 
 // acl feat
@@ -81,13 +113,25 @@ pub(crate) struct AclArgs {
 #[derive(Debug, clap::Subcommand)]
 pub enum AclActionType {
     #[command(author = "RobustMQ", about = "action: acl list", long_about = None)]
-    List,
+    List(ListAclArgs),
     #[command(author = "RobustMQ", about = "action: create acl", long_about = None)]
     Create(CreateAclArgs),
     #[command(author = "RobustMQ", about = "action: delete acl", long_about = None)]
     Delete(DeleteAclArgs),
 }
 
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: acl list", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListAclArgs {
+    #[arg(
+        long,
+        default_value = "",
+        help = "Only list the acls of the tenant, all acls are listed if empty"
+    )]
+    pub(crate) tenant: String,
+}
+
 #[derive(clap::Args, Debug)]
 #[command(author = "RobustMQ", about = "action: create acl", long_about = None)]
 #[command(next_line_help = true)]
@@ -317,7 +361,7 @@ pub(crate) struct DeleteTopicRewriteArgs {
 
 // connector feat
 #[derive(clap::Args, Debug)]
-#[command(author = "RobustMQ", about = "related operations of connector, such as listing, creating, updating and deleting", long_about = None)]
+#[command(author = "RobustMQ", about = "related operations of connector, such as listing, creating, updating, deleting, pausing, resuming and resetting the offset", long_about = None)]
 #[command(next_line_help = true)]
 pub(crate) struct ConnectorArgs {
     #[command(subcommand)]
@@ -334,6 +378,12 @@ pub enum ConnectorActionType {
     Delete(DeleteConnectorArgs),
     #[command(author = "RobustMQ", about = "action: update connector", long_about = None)]
     Update(UpdateConnectorArgs),
+    #[command(author = "RobustMQ", about = "action: pause connector", long_about = None)]
+    Pause(PauseConnectorArgs),
+    #[command(author = "RobustMQ", about = "action: resume a paused connector", long_about = None)]
+    Resume(ResumeConnectorArgs),
+    #[command(author = "RobustMQ", about = "action: reset the offset of a paused connector", long_about = None)]
+    ResetOffset(ResetConnectorOffsetArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -348,6 +398,8 @@ pub(crate) struct CreateConnectorArgs {
     pub(crate) config: String,
     #[arg(short, long, required = true)]
     pub(crate) topic_id: String,
+    #[arg(long, default_value = "", help = "The tenant the connector belongs to")]
+    pub(crate) tenant: String,
 }
 
 #[derive(clap::Args, Debug)]
@@ -356,6 +408,12 @@ pub(crate) struct CreateConnectorArgs {
 pub(crate) struct ListConnectorArgs {
     #[arg(short, long, required = true)]
     pub(crate) connector_name: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "Only list the connectors of the tenant, all connectors are listed if empty"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(clap::Args, Debug)]
@@ -364,6 +422,12 @@ pub(crate) struct ListConnectorArgs {
 pub(crate) struct DeleteConnectorArgs {
     #[arg(short, long, required = true)]
     pub(crate) connector_name: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "The connector is only deleted if it belongs to the tenant"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(clap::Args, Debug)]
@@ -374,12 +438,57 @@ pub(crate) struct UpdateConnectorArgs {
     pub(crate) connector: String,
 }
 
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: pause connector", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct PauseConnectorArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) connector_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: resume connector", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ResumeConnectorArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) connector_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: reset connector offset", long_about = None)]
+#[command(next_line_help = true)]
+#[command(group(clap::ArgGroup::new("position").required(true).args(["earliest", "latest", "timestamp", "offset"])))]
+pub(crate) struct ResetConnectorOffsetArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) connector_name: String,
+    #[arg(long, help = "Reset to the oldest message of the topic")]
+    pub(crate) earliest: bool,
+    #[arg(
+        long,
+        help = "Reset to the end of the topic, skipping the pending messages"
+    )]
+    pub(crate) latest: bool,
+    #[arg(
+        long,
+        help = "Reset to the first message written at or after the unix timestamp in seconds"
+    )]
+    pub(crate) timestamp: Option<u64>,
+    #[arg(long, help = "Reset to the offset")]
+    pub(crate) offset: Option<u64>,
+}
+
 // schema
 #[derive(Debug, Parser)]
 #[command(author="RobustMQ", about="", long_about = None)]
 #[command(next_line_help = true)]
 pub(crate) struct ListSchemaArgs {
     pub(crate) schema_name: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "Only list the schemas of the tenant, all schemas are listed if empty"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(Debug, Parser)]
@@ -390,6 +499,8 @@ pub(crate) struct CreateSchemaArgs {
     pub(crate) schema_type: String,
     pub(crate) schema: String,
     pub(crate) desc: String,
+    #[arg(long, default_value = "", help = "The tenant the schema belongs to")]
+    pub(crate) tenant: String,
 }
 
 #[derive(Debug, Parser)]
@@ -400,6 +511,12 @@ pub(crate) struct UpdateSchemaArgs {
     pub(crate) schema_type: String,
     pub(crate) schema: String,
     pub(crate) desc: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "The schema is only updated if it belongs to the tenant"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(Debug, Parser)]
@@ -407,6 +524,12 @@ pub(crate) struct UpdateSchemaArgs {
 #[command(next_line_help = true)]
 pub(crate) struct DeleteSchemaArgs {
     pub(crate) schema_name: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "The schema is only deleted if it belongs to the tenant"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(Debug, Parser)]
@@ -456,14 +579,19 @@ pub fn process_slow_sub_args(args: SlowSubArgs) -> MqttActionType {
 pub fn process_user_args(args: UserArgs) -> MqttActionType {
     match args.action {
         Some(user_action) => match user_action {
-            UserActionType::List => MqttActionType::ListUser,
+            UserActionType::List(arg) => {
+                MqttActionType::ListUser(ListUserRequest { tenant: arg.tenant })
+            }
             UserActionType::Create(arg) => MqttActionType::CreateUser(CreateUserRequest {
                 username: arg.username,
                 password: arg.password,
                 is_superuser: arg.is_superuser,
+                mountpoint: arg.mountpoint,
+                tenant: arg.tenant,
             }),
             UserActionType::Delete(arg) => MqttActionType::DeleteUser(DeleteUserRequest {
                 username: arg.username,
+                tenant: arg.tenant,
             }),
         },
         None => unreachable!(),
@@ -473,7 +601,10 @@ pub fn process_user_args(args: UserArgs) -> MqttActionType {
 pub fn process_acl_args(args: AclArgs) -> MqttActionType {
     match args.action {
         Some(acl_action) => match acl_action {
-            AclActionType::List => MqttActionType::ListAcl,
+            AclActionType::List(arg) => MqttActionType::ListAcl(ListAclRequest {
+                tenant: arg.tenant,
+                ..Default::default()
+            }),
             AclActionType::Create(arg) => MqttActionType::CreateAcl(CreateAclRequest {
                 cluster_name: arg.cluster_name,
                 acl: Vec::from(arg.acl),
@@ -515,6 +646,7 @@ pub fn process_connector_args(args: ConnectorArgs) -> MqttActionType {
             ConnectorActionType::List(arg) => {
                 MqttActionType::ListConnector(MqttListConnectorRequest {
                     connector_name: arg.connector_name,
+                    tenant: arg.tenant,
                 })
             }
             ConnectorActionType::Create(arg) => {
@@ -523,11 +655,13 @@ pub fn process_connector_args(args: ConnectorArgs) -> MqttActionType {
                     connector_type: arg.connector_type.parse().unwrap(),
                     config: arg.config,
                     topic_id: arg.topic_id,
+                    tenant: arg.tenant,
                 })
             }
             ConnectorActionType::Delete(arg) => {
                 MqttActionType::DeleteConnector(MqttDeleteConnectorRequest {
                     connector_name: arg.connector_name,
+                    tenant: arg.tenant,
                 })
             }
             ConnectorActionType::Update(arg) => {
@@ -535,6 +669,645 @@ pub fn process_connector_args(args: ConnectorArgs) -> MqttActionType {
                     connector: Vec::from(arg.connector),
                 })
             }
+            ConnectorActionType::Pause(arg) => {
+                MqttActionType::PauseConnector(MqttPauseConnectorRequest {
+                    connector_name: arg.connector_name,
+                })
+            }
+            ConnectorActionType::Resume(arg) => {
+                MqttActionType::ResumeConnector(MqttResumeConnectorRequest {
+                    connector_name: arg.connector_name,
+                })
+            }
+            ConnectorActionType::ResetOffset(arg) => {
+                let reset_type = if arg.earliest {
+                    MqttConnectorOffsetResetType::Earliest
+                } else if arg.latest {
+                    MqttConnectorOffsetResetType::Latest
+                } else if arg.timestamp.is_some() {
+                    MqttConnectorOffsetResetType::Timestamp
+                } else {
+                    MqttConnectorOffsetResetType::Offset
+                };
+                MqttActionType::ResetConnectorOffset(MqttResetConnectorOffsetRequest {
+                    connector_name: arg.connector_name,
+                    reset_type: reset_type as i32,
+                    timestamp: arg.timestamp.unwrap_or_default(),
+                    offset: arg.offset.unwrap_or_default(),
+                })
+            }
+        },
+        None => unreachable!(),
+    }
+}
+
+// dead letter feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of dead letter messages, such as listing and redriving", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DeadLetterArgs {
+    #[command(subcommand)]
+    pub action: Option<DeadLetterActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum DeadLetterActionType {
+    #[command(author = "RobustMQ", about = "action: list dead letter messages", long_about = None)]
+    List(ListDeadLetterArgs),
+    #[command(author = "RobustMQ", about = "action: redrive dead letter messages to the original topic", long_about = None)]
+    Redrive(RedriveDeadLetterArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list dead letter messages", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListDeadLetterArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) topic_name: String,
+    #[arg(short, long, default_value_t = 0)]
+    pub(crate) offset: u64,
+    #[arg(short, long, default_value_t = 10)]
+    pub(crate) num: u64,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: redrive dead letter messages", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct RedriveDeadLetterArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) topic_name: String,
+    #[arg(short, long, default_value_t = 0)]
+    pub(crate) offset: u64,
+    #[arg(short, long, default_value_t = 10)]
+    pub(crate) num: u64,
+}
+
+pub fn process_dead_letter_args(args: DeadLetterArgs) -> MqttActionType {
+    match args.action {
+        Some(dead_letter_action) => match dead_letter_action {
+            DeadLetterActionType::List(arg) => {
+                MqttActionType::ListDeadLetterMessage(ListDeadLetterMessageRequest {
+                    topic_name: arg.topic_name,
+                    offset: arg.offset,
+                    num: arg.num,
+                })
+            }
+            DeadLetterActionType::Redrive(arg) => {
+                MqttActionType::RedriveDeadLetterMessage(RedriveDeadLetterMessageRequest {
+                    topic_name: arg.topic_name,
+                    offset: arg.offset,
+                    num: arg.num,
+                })
+            }
+        },
+        None => unreachable!(),
+    }
+}
+
+// delay message feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of pending delay messages, such as listing, inspecting and cancelling", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DelayMessageArgs {
+    #[command(subcommand)]
+    pub action: Option<DelayMessageActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum DelayMessageActionType {
+    #[command(author = "RobustMQ", about = "action: list pending delay messages", long_about = None)]
+    List(ListDelayMessageArgs),
+    #[command(author = "RobustMQ", about = "action: inspect a pending delay message", long_about = None)]
+    Inspect(InspectDelayMessageArgs),
+    #[command(author = "RobustMQ", about = "action: cancel pending delay messages", long_about = None)]
+    Cancel(CancelDelayMessageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list pending delay messages", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListDelayMessageArgs {
+    #[arg(short, long, default_value = "")]
+    pub(crate) topic_name: String,
+    #[arg(short, long, default_value = "")]
+    pub(crate) client_id: String,
+    #[arg(short, long, default_value_t = 0)]
+    pub(crate) offset: u64,
+    #[arg(short, long, default_value_t = 10)]
+    pub(crate) num: u64,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: inspect a pending delay message", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct InspectDelayMessageArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) id: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: cancel pending delay messages", long_about = None)]
+#[command(next_line_help = true)]
+#[command(group(clap::ArgGroup::new("target").required(true).multiple(true).args(["id", "topic_name", "client_id"])))]
+pub(crate) struct CancelDelayMessageArgs {
+    #[arg(short, long, conflicts_with_all = ["topic_name", "client_id"])]
+    pub(crate) id: Option<String>,
+    #[arg(short, long)]
+    pub(crate) topic_name: Option<String>,
+    #[arg(short, long)]
+    pub(crate) client_id: Option<String>,
+}
+
+pub fn process_delay_message_args(args: DelayMessageArgs) -> MqttActionType {
+    match args.action {
+        Some(delay_message_action) => match delay_message_action {
+            DelayMessageActionType::List(arg) => {
+                MqttActionType::ListDelayMessage(ListDelayMessageRequest {
+                    topic_name: arg.topic_name,
+                    client_id: arg.client_id,
+                    offset: arg.offset,
+                    num: arg.num,
+                })
+            }
+            DelayMessageActionType::Inspect(arg) => {
+                MqttActionType::GetDelayMessage(GetDelayMessageRequest { id: arg.id })
+            }
+            DelayMessageActionType::Cancel(arg) => {
+                MqttActionType::CancelDelayMessage(CancelDelayMessageRequest {
+                    id: arg.id.unwrap_or_default(),
+                    topic_name: arg.topic_name.unwrap_or_default(),
+                    client_id: arg.client_id.unwrap_or_default(),
+                })
+            }
+        },
+        None => unreachable!(),
+    }
+}
+
+// drain node feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: drain the broker node, redirecting its clients to other nodes", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DrainNodeArgs {
+    #[arg(
+        long,
+        default_value = "",
+        help = "Comma separated server references, the other nodes of the cluster are used if empty"
+    )]
+    pub(crate) server_reference: String,
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Disconnect with ServerMoved instead of UseAnotherServer"
+    )]
+    pub(crate) server_moved: bool,
+    #[arg(long, default_value_t = 100)]
+    pub(crate) batch_size: u32,
+    #[arg(long, default_value_t = 1000)]
+    pub(crate) batch_interval_ms: u64,
+}
+
+pub fn process_drain_node_args(args: DrainNodeArgs) -> MqttActionType {
+    MqttActionType::DrainNode(DrainNodeRequest {
+        server_reference: args.server_reference,
+        server_moved: args.server_moved,
+        batch_size: args.batch_size,
+        batch_interval_ms: args.batch_interval_ms,
+    })
+}
+
+// listener feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of listeners, such as listing, starting and stopping", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListenerArgs {
+    #[command(subcommand)]
+    pub action: Option<ListenerActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ListenerActionType {
+    #[command(author = "RobustMQ", about = "action: list listeners", long_about = None)]
+    List,
+    #[command(author = "RobustMQ", about = "action: start a stopped listener", long_about = None)]
+    Start(StartListenerArgs),
+    #[command(author = "RobustMQ", about = "action: stop a listener and disconnect its clients", long_about = None)]
+    Stop(StopListenerArgs),
+    #[command(author = "RobustMQ", about = "action: reload the TLS certificates of listeners", long_about = None)]
+    ReloadCert(ReloadListenerCertArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: start a stopped listener", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct StartListenerArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: stop a listener", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct StopListenerArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: reload the TLS certificates of listeners", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ReloadListenerCertArgs {
+    #[arg(
+        short,
+        long,
+        default_value = "",
+        help = "Listener name, all running TLS listeners are reloaded if empty"
+    )]
+    pub(crate) name: String,
+}
+
+pub fn process_listener_args(args: ListenerArgs) -> MqttActionType {
+    match args.action {
+        Some(listener_action) => match listener_action {
+            ListenerActionType::List => MqttActionType::ListListener,
+            ListenerActionType::Start(arg) => {
+                MqttActionType::StartListener(StartListenerRequest { name: arg.name })
+            }
+            ListenerActionType::Stop(arg) => {
+                MqttActionType::StopListener(StopListenerRequest { name: arg.name })
+            }
+            ListenerActionType::ReloadCert(arg) => {
+                MqttActionType::ReloadListenerCert(ReloadListenerCertRequest { name: arg.name })
+            }
+        },
+        None => unreachable!(),
+    }
+}
+
+// tenant feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of tenants, such as listing, creating, and deleting", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct TenantArgs {
+    #[command(subcommand)]
+    pub action: Option<TenantActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum TenantActionType {
+    #[command(author = "RobustMQ", about = "action: list tenants", long_about = None)]
+    List(ListTenantArgs),
+    #[command(author = "RobustMQ", about = "action: create tenant", long_about = None)]
+    Create(CreateTenantArgs),
+    #[command(author = "RobustMQ", about = "action: delete tenant", long_about = None)]
+    Delete(DeleteTenantArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list tenants", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListTenantArgs {
+    #[arg(short, long, default_value = "")]
+    pub(crate) tenant_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: create tenant", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct CreateTenantArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) tenant_name: String,
+    #[arg(short, long, default_value = "")]
+    pub(crate) desc: String,
+    #[arg(long, default_value_t = 0, help = "0 means no limit")]
+    pub(crate) max_connections: u64,
+    #[arg(long, default_value_t = 0, help = "0 means no limit")]
+    pub(crate) max_topics: u64,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Messages published per second, 0 means no limit"
+    )]
+    pub(crate) max_publish_rate: u64,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: delete tenant", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DeleteTenantArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) tenant_name: String,
+}
+
+pub fn process_tenant_args(args: TenantArgs) -> MqttActionType {
+    match args.action {
+        Some(tenant_action) => match tenant_action {
+            TenantActionType::List(arg) => MqttActionType::ListTenant(ListTenantRequest {
+                tenant_name: arg.tenant_name,
+            }),
+            TenantActionType::Create(arg) => MqttActionType::CreateTenant(CreateTenantRequest {
+                tenant_name: arg.tenant_name,
+                desc: arg.desc,
+                max_connections: arg.max_connections,
+                max_topics: arg.max_topics,
+                max_publish_rate: arg.max_publish_rate,
+            }),
+            TenantActionType::Delete(arg) => MqttActionType::DeleteTenant(DeleteTenantRequest {
+                tenant_name: arg.tenant_name,
+            }),
+        },
+        None => unreachable!(),
+    }
+}
+
+// rule engine feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of rule engine rules, such as listing, creating, and deleting", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct RuleArgs {
+    #[command(subcommand)]
+    pub action: Option<RuleActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum RuleActionType {
+    #[command(author = "RobustMQ", about = "action: list rules", long_about = None)]
+    List(ListRuleArgs),
+    #[command(author = "RobustMQ", about = "action: create rule", long_about = None)]
+    Create(CreateRuleArgs),
+    #[command(author = "RobustMQ", about = "action: delete rule", long_about = None)]
+    Delete(DeleteRuleArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list rules", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListRuleArgs {
+    #[arg(short, long, default_value = "")]
+    pub(crate) rule_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: create rule", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct CreateRuleArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) rule_name: String,
+    #[arg(
+        short,
+        long,
+        required = true,
+        help = "SELECT ... FROM \"topic filter\" [WHERE ...]"
+    )]
+    pub(crate) sql: String,
+    #[arg(
+        short,
+        long,
+        required = true,
+        help = "JSON array of actions, e.g. [{\"action_type\":\"Republish\",\"target\":\"alarm/${clientid}\",\"qos\":1}]"
+    )]
+    pub(crate) actions: String,
+    #[arg(short, long, default_value = "")]
+    pub(crate) desc: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: delete rule", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DeleteRuleArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) rule_name: String,
+}
+
+pub fn process_rule_args(args: RuleArgs) -> MqttActionType {
+    match args.action {
+        Some(rule_action) => match rule_action {
+            RuleActionType::List(arg) => MqttActionType::ListRule(ListRuleRequest {
+                rule_name: arg.rule_name,
+            }),
+            RuleActionType::Create(arg) => MqttActionType::CreateRule(CreateRuleRequest {
+                rule_name: arg.rule_name,
+                sql: arg.sql,
+                actions: arg.actions,
+                desc: arg.desc,
+            }),
+            RuleActionType::Delete(arg) => MqttActionType::DeleteRule(DeleteRuleRequest {
+                rule_name: arg.rule_name,
+            }),
+        },
+        None => unreachable!(),
+    }
+}
+
+// wasm plugin feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of WASM plugins, such as listing, creating, and deleting", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct WasmPluginArgs {
+    #[command(subcommand)]
+    pub action: Option<WasmPluginActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum WasmPluginActionType {
+    #[command(author = "RobustMQ", about = "action: list WASM plugins", long_about = None)]
+    List(ListWasmPluginArgs),
+    #[command(author = "RobustMQ", about = "action: create WASM plugin", long_about = None)]
+    Create(CreateWasmPluginArgs),
+    #[command(author = "RobustMQ", about = "action: delete WASM plugin", long_about = None)]
+    Delete(DeleteWasmPluginArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list WASM plugins", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListWasmPluginArgs {
+    #[arg(short, long, default_value = "")]
+    pub(crate) plugin_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: create WASM plugin", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct CreateWasmPluginArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) plugin_name: String,
+    #[arg(
+        short,
+        long,
+        required = true,
+        value_delimiter = ',',
+        help = "topic filters of the messages the plugin runs on, separated by commas"
+    )]
+    pub(crate) topics: Vec<String>,
+    #[arg(short, long, required = true, help = "path of the .wasm module file")]
+    pub(crate) module_path: String,
+    #[arg(long, default_value = "")]
+    pub(crate) tenant: String,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "fuel per message, 0 uses the default"
+    )]
+    pub(crate) max_fuel: u64,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "memory limit in bytes, 0 uses the default"
+    )]
+    pub(crate) max_memory_bytes: u64,
+    #[arg(short, long, default_value = "")]
+    pub(crate) desc: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: delete WASM plugin", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DeleteWasmPluginArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) plugin_name: String,
+}
+
+pub fn process_wasm_plugin_args(args: WasmPluginArgs) -> MqttActionType {
+    match args.action {
+        Some(wasm_plugin_action) => match wasm_plugin_action {
+            WasmPluginActionType::List(arg) => {
+                MqttActionType::ListWasmPlugin(ListWasmPluginRequest {
+                    plugin_name: arg.plugin_name,
+                })
+            }
+            WasmPluginActionType::Create(arg) => {
+                let module = match std::fs::read(&arg.module_path) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        eprintln!("Failed to read the WASM module {}: {}", arg.module_path, e);
+                        std::process::exit(1);
+                    }
+                };
+                MqttActionType::CreateWasmPlugin(CreateWasmPluginRequest {
+                    plugin_name: arg.plugin_name,
+                    topics: arg.topics,
+                    tenant: arg.tenant,
+                    module,
+                    max_fuel: arg.max_fuel,
+                    max_memory_bytes: arg.max_memory_bytes,
+                    desc: arg.desc,
+                })
+            }
+            WasmPluginActionType::Delete(arg) => {
+                MqttActionType::DeleteWasmPlugin(DeleteWasmPluginRequest {
+                    plugin_name: arg.plugin_name,
+                })
+            }
+        },
+        None => unreachable!(),
+    }
+}
+
+// scheduled message feat
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "related operations of scheduled messages, such as listing, creating, and deleting", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ScheduledMessageArgs {
+    #[command(subcommand)]
+    pub action: Option<ScheduledMessageActionType>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ScheduledMessageActionType {
+    #[command(author = "RobustMQ", about = "action: list scheduled messages", long_about = None)]
+    List(ListScheduledMessageArgs),
+    #[command(author = "RobustMQ", about = "action: create scheduled message", long_about = None)]
+    Create(CreateScheduledMessageArgs),
+    #[command(author = "RobustMQ", about = "action: delete scheduled message", long_about = None)]
+    Delete(DeleteScheduledMessageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: list scheduled messages", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListScheduledMessageArgs {
+    #[arg(short, long, default_value = "")]
+    pub(crate) name: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: create scheduled message", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct CreateScheduledMessageArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) name: String,
+    #[arg(short, long, required = true)]
+    pub(crate) topic: String,
+    #[arg(short, long, default_value = "")]
+    pub(crate) payload: String,
+    #[arg(short, long, default_value_t = 0, value_parser = RangedU64ValueParser::<u32>::new().range(0..=2))]
+    pub(crate) qos: u32,
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) retain: bool,
+    #[arg(
+        short,
+        long,
+        default_value = "",
+        help = "cron expression with a seconds field, evaluated in UTC, e.g. \"0 0 * * * *\""
+    )]
+    pub(crate) cron: String,
+    #[arg(
+        short,
+        long,
+        default_value_t = 0,
+        help = "fixed interval in seconds, used when no cron expression is given"
+    )]
+    pub(crate) interval_sec: u64,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "unix time in seconds of the first possible publish, 0 means now"
+    )]
+    pub(crate) start_time: u64,
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "unix time in seconds after which nothing is published, 0 means never"
+    )]
+    pub(crate) end_time: u64,
+    #[arg(short, long, default_value = "")]
+    pub(crate) desc: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author = "RobustMQ", about = "action: delete scheduled message", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct DeleteScheduledMessageArgs {
+    #[arg(short, long, required = true)]
+    pub(crate) name: String,
+}
+
+pub fn process_scheduled_message_args(args: ScheduledMessageArgs) -> MqttActionType {
+    match args.action {
+        Some(scheduled_message_action) => match scheduled_message_action {
+            ScheduledMessageActionType::List(arg) => {
+                MqttActionType::ListScheduledMessage(ListScheduledMessageRequest { name: arg.name })
+            }
+            ScheduledMessageActionType::Create(arg) => {
+                MqttActionType::CreateScheduledMessage(CreateScheduledMessageRequest {
+                    name: arg.name,
+                    topic: arg.topic,
+                    payload: arg.payload.into_bytes(),
+                    qos: arg.qos,
+                    retain: arg.retain,
+                    cron: arg.cron,
+                    interval_sec: arg.interval_sec,
+                    start_time: arg.start_time,
+                    end_time: arg.end_time,
+                    desc: arg.desc,
+                })
+            }
+            ScheduledMessageActionType::Delete(arg) => {
+                MqttActionType::DeleteScheduledMessage(DeleteScheduledMessageRequest {
+                    name: arg.name,
+                })
+            }
         },
         None => unreachable!(),
     }
@@ -573,7 +1346,7 @@ pub(crate) struct AutoSubscribeRuleCommand {
 #[derive(Debug, clap::Subcommand)]
 pub enum AutoSubscribeRuleActionType {
     #[command(author = "RobustMQ", about = "action: auto subscribe rule list", long_about = None)]
-    List,
+    List(ListAutoSubscribeRuleArgs),
     #[command(author = "RobustMQ", about = "action: delete auto subscribe rule", long_about = None)]
     Delete(DeleteAutoSubscribeRuleArgs),
     #[command(author = "RobustMQ", about = "action: set auto subscribe rule", long_about = None)]
@@ -594,6 +1367,24 @@ pub(crate) struct SetAutoSubscribeRuleArgs {
     pub(crate) retain_as_published: bool,
     #[arg(short = 'R', long, default_value_t = 0)]
     pub(crate) retained_handling: u8,
+    #[arg(
+        long,
+        default_value = "",
+        help = "The rule only applies to the clients of the tenant"
+    )]
+    pub(crate) tenant: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(author="RobustMQ", about="action: auto subscribe rule list", long_about = None)]
+#[command(next_line_help = true)]
+pub(crate) struct ListAutoSubscribeRuleArgs {
+    #[arg(
+        long,
+        default_value = "",
+        help = "Only list the rules of the tenant, all rules are listed if empty"
+    )]
+    pub(crate) tenant: String,
 }
 
 #[derive(clap::Args, Debug)]
@@ -602,13 +1393,21 @@ pub(crate) struct SetAutoSubscribeRuleArgs {
 pub(crate) struct DeleteAutoSubscribeRuleArgs {
     #[arg(short, long, required = true)]
     pub(crate) topic: String,
+    #[arg(
+        long,
+        default_value = "",
+        help = "The rule is only deleted if it belongs to the tenant"
+    )]
+    pub(crate) tenant: String,
 }
 
 pub fn process_auto_subscribe_args(args: AutoSubscribeRuleCommand) -> MqttActionType {
     match args.action {
         Some(auto_subscribe_action) => match auto_subscribe_action {
-            AutoSubscribeRuleActionType::List => {
-                MqttActionType::ListAutoSubscribeRule(ListAutoSubscribeRuleRequest::default())
+            AutoSubscribeRuleActionType::List(arg) => {
+                MqttActionType::ListAutoSubscribeRule(ListAutoSubscribeRuleRequest {
+                    tenant: arg.tenant,
+                })
             }
             AutoSubscribeRuleActionType::Set(arg) => {
                 MqttActionType::SetAutoSubscribeRule(SetAutoSubscribeRuleRequest {
@@ -617,11 +1416,13 @@ pub fn process_auto_subscribe_args(args: AutoSubscribeRuleCommand) -> MqttAction
                     no_local: arg.no_local,
                     retain_as_published: arg.retain_as_published,
                     retained_handling: arg.retained_handling as u32,
+                    tenant: arg.tenant,
                 })
             }
             AutoSubscribeRuleActionType::Delete(arg) => {
                 MqttActionType::DeleteAutoSubscribeRule(DeleteAutoSubscribeRuleRequest {
                     topic: arg.topic,
+                    tenant: arg.tenant,
                 })
             }
         },
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
@@ -20,13 +21,18 @@
     default_prometheus, override_default_by_env, Auth, Log, Prometheus, Storage, Telemetry,
 };
 use super::default_mqtt::{
-    default_auth, default_grpc_port, default_log, default_mqtt_cluster_dynamic_feature,
-    default_mqtt_cluster_dynamic_flapping_detect, default_mqtt_cluster_dynamic_network,
-    default_mqtt_cluster_dynamic_protocol, default_mqtt_cluster_dynamic_security,
-    default_mqtt_cluster_dynamic_slow_sub, default_network, default_network_quic_port,
-    default_network_tcp_port, default_network_tcps_port, default_network_websocket_port,
+    default_auth, default_dead_letter, default_drain, default_exhook_failure_policy,
+    default_exhook_reload_interval_ms, default_exhook_timeout_ms, default_grpc_port, default_hook,
+    default_idempotent_message, default_listener_enable, default_log,
+    default_mqtt_cluster_dynamic_feature, default_mqtt_cluster_dynamic_flapping_detect,
+    default_mqtt_cluster_dynamic_network, default_mqtt_cluster_dynamic_protocol,
+    default_mqtt_cluster_dynamic_security, default_mqtt_cluster_dynamic_slow_sub, default_network,
+    default_network_quic_port, default_network_tcp_port, default_network_tcps_port,
+    default_network_tls_reload_interval_sec, default_network_websocket_port,
     default_network_websockets_port, default_offline_message, default_placement_center,
     default_storage, default_system, default_tcp_thread, default_telemetry,
+    default_webhook_batch_interval_ms, default_webhook_batch_size, default_webhook_max_retries,
+    default_webhook_queue_size, default_webhook_retry_backoff_ms, default_webhook_timeout_ms,
 };
 use crate::tools::{read_file, try_create_fold};
 
@@ -40,6 +46,8 @@ pub struct BrokerMqttConfig {
     pub placement_center: Vec<String>,
     #[serde(default = "default_network")]
     pub network: Network,
+    #[serde(default)]
+    pub listeners: Vec<Listener>,
     #[serde(default = "default_tcp_thread")]
     pub tcp_thread: TcpThread,
     #[serde(default = "default_system")]
@@ -52,6 +60,14 @@ pub struct BrokerMqttConfig {
     pub log: Log,
     #[serde(default = "default_offline_message")]
     pub offline_messages: OfflineMessage,
+    #[serde(default = "default_dead_letter")]
+    pub dead_letter: DeadLetter,
+    #[serde(default = "default_drain")]
+    pub drain: Drain,
+    #[serde(default = "default_idempotent_message")]
+    pub idempotent_message: IdempotentMessage,
+    #[serde(default = "default_hook")]
+    pub hook: Hook,
     #[serde(default = "default_telemetry")]
     pub telemetry: Telemetry,
     #[serde(default = "default_prometheus")]
@@ -180,6 +196,52 @@ pub struct Network {
     pub tls_cert: String,
     #[serde(default)]
     pub tls_key: String,
+    // How often the certificate files are checked for changes, 0 disables the hot reload
+    #[serde(default = "default_network_tls_reload_interval_sec")]
+    pub tls_reload_interval_sec: u64,
+}
+
+// A named MQTT listener. When no listener is configured, the ports of [network] are used as the
+// "tcp", "tls", "ws", "wss" and "quic" listeners.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct Listener {
+    pub name: String,
+    // tcp, tls, ws, wss or quic
+    pub protocol: String,
+    // Socket address to bind, such as 0.0.0.0:1883
+    pub bind: String,
+    #[serde(default = "default_listener_enable")]
+    pub enable: bool,
+    #[serde(default)]
+    pub tls_cert: String,
+    #[serde(default)]
+    pub tls_key: String,
+    // When set, clients must present a certificate signed by this CA (mTLS)
+    #[serde(default)]
+    pub ca_cert: String,
+    // Authentication methods tried in order: anonymous, password, x509. Empty uses the cluster settings.
+    #[serde(default)]
+    pub authentication: Vec<String>,
+    // 0 means no limit
+    #[serde(default)]
+    pub max_connections: usize,
+    // Topic prefix for the clients of the listener, supports ${clientid} and ${username}
+    #[serde(default)]
+    pub mountpoint: String,
+    // Whether the connections start with a PROXY protocol v1/v2 header
+    #[serde(default)]
+    pub proxy_protocol: bool,
+    // Extra certificates selected by the SNI of the client, tls_cert/tls_key is used when none matches
+    #[serde(default)]
+    pub sni_certs: Vec<SniCert>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct SniCert {
+    // Exact host name, or a wildcard such as *.example.com
+    pub server_name: String,
+    pub tls_cert: String,
+    pub tls_key: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -222,6 +284,102 @@ pub struct OfflineMessage {
     pub max_messages_num: u32,
 }
 
+// Retry policy for pushing QoS1/QoS2 messages to subscribers. Messages that are still
+// not acknowledged after max_retry_times are published to the dead letter topic.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct DeadLetter {
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default)]
+    pub max_retry_times: u32,
+    #[serde(default)]
+    pub retry_backoff_ms: u64,
+    #[serde(default)]
+    pub max_retry_backoff_ms: u64,
+    #[serde(default)]
+    pub topic_prefix: String,
+}
+
+// Controls how connections are closed when the node is drained or stopped. Connections are
+// disconnected batch by batch, and MQTT 5 clients are redirected to server_reference, or to the
+// other broker nodes of the cluster when it is empty. The interval between the batches is
+// shortened so that the whole drain takes at most max_drain_time_ms, 0 means no limit.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Drain {
+    #[serde(default)]
+    pub batch_size: u32,
+    #[serde(default)]
+    pub batch_interval_ms: u64,
+    #[serde(default)]
+    pub server_reference: String,
+    #[serde(default)]
+    pub max_drain_time_ms: u64,
+}
+
+// How long the QoS 2 packet identifiers and the message ids of a client are remembered, and how
+// often the entries saved by the other broker nodes are read from the shared storage. The entries
+// are kept in memory, so the memory used grows with the message rate times ttl_sec.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct IdempotentMessage {
+    #[serde(default)]
+    pub ttl_sec: u64,
+    #[serde(default)]
+    pub sync_interval_ms: u64,
+}
+
+// The order in which the registered broker hooks run. Hooks listed in order run first, in the
+// listed order, the other hooks run after them in the order they are registered.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Hook {
+    #[serde(default)]
+    pub order: Vec<String>,
+    #[serde(default)]
+    pub exhook: Vec<ExHookServer>,
+    #[serde(default)]
+    pub webhook: Vec<WebHookServer>,
+}
+
+// An external hook service implementing the HookProviderService gRPC service. When a call fails
+// or times out, failure_policy decides whether the request is denied ("deny") or allowed
+// ("ignore"). The declared hooks are reloaded every reload_interval_ms.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ExHookServer {
+    pub name: String,
+    pub addr: String,
+    #[serde(default = "default_exhook_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_exhook_failure_policy")]
+    pub failure_policy: String,
+    #[serde(default = "default_exhook_reload_interval_ms")]
+    pub reload_interval_ms: u64,
+}
+
+// An HTTP endpoint the client and message lifecycle events are POSTed to as a JSON array.
+// An empty events list sends all events. A batch is sent when batch_size events are buffered
+// or batch_interval_ms has elapsed, a failed request is retried max_retries times with an
+// exponential backoff starting at retry_backoff_ms.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct WebHookServer {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_webhook_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_webhook_batch_interval_ms")]
+    pub batch_interval_ms: u64,
+    #[serde(default = "default_webhook_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_webhook_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    #[serde(default = "default_webhook_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_webhook_queue_size")]
+    pub queue_size: usize,
+}
+
 static BROKER_MQTT_CONF: OnceLock<BrokerMqttConfig> = OnceLock::new();
 
 pub fn init_broker_mqtt_conf_by_path(config_path: &str) -> &'static BrokerMqttConfig {
@@ -300,6 +458,7 @@ fn config_default_test() {
         assert_eq!(config.network.quic_port, 9083);
         assert!(!config.network.tls_cert.is_empty());
         assert!(!config.network.tls_key.is_empty());
+        assert_eq!(config.network.tls_reload_interval_sec, 60);
 
         assert_eq!(config.tcp_thread.accept_thread_num, 1);
         assert_eq!(config.tcp_thread.handler_thread_num, 10);
@@ -331,6 +490,107 @@ fn config_default_test() {
         assert_eq!(config.auth.storage_type, "placement".to_string());
         assert_eq!(config.auth.journal_addr, "".to_string());
         assert_eq!(config.auth.mysql_addr, "".to_string());
+
+        assert!(config.dead_letter.enable);
+        assert_eq!(config.dead_letter.max_retry_times, 3);
+        assert_eq!(config.dead_letter.retry_backoff_ms, 1000);
+        assert_eq!(config.dead_letter.max_retry_backoff_ms, 30000);
+        assert_eq!(config.dead_letter.topic_prefix, "$DLQ".to_string());
+        assert_eq!(config.drain.batch_size, 100);
+        assert_eq!(config.drain.batch_interval_ms, 1000);
+        assert!(config.drain.server_reference.is_empty());
+        assert_eq!(config.drain.max_drain_time_ms, 30000);
+        assert_eq!(config.idempotent_message.ttl_sec, 3600);
+        assert_eq!(config.idempotent_message.sync_interval_ms, 1000);
+        assert!(config.hook.order.is_empty());
+        assert!(config.hook.exhook.is_empty());
+        assert!(config.hook.webhook.is_empty());
+        assert!(config.listeners.is_empty());
+    }
+
+    #[test]
+    fn listener_config_test() {
+        let content = r#"
+            cluster_name = "mqtt-broker"
+            broker_id = 1
+
+            [[listeners]]
+            name = "internal"
+            protocol = "tcp"
+            bind = "127.0.0.1:1883"
+
+            [[listeners]]
+            name = "external"
+            protocol = "tls"
+            bind = "0.0.0.0:8883"
+            tls_cert = "cert.pem"
+            tls_key = "key.pem"
+            ca_cert = "ca.pem"
+            authentication = ["x509", "password"]
+            max_connections = 100
+            mountpoint = "tenant-a/"
+            proxy_protocol = true
+
+            [[listeners.sni_certs]]
+            server_name = "*.example.com"
+            tls_cert = "example.pem"
+            tls_key = "example.key"
+        "#;
+        let config: BrokerMqttConfig = toml::from_str(content).unwrap();
+        assert_eq!(config.listeners.len(), 2);
+
+        let internal = &config.listeners[0];
+        assert_eq!(internal.name, "internal");
+        assert_eq!(internal.protocol, "tcp");
+        assert!(internal.enable);
+        assert!(internal.authentication.is_empty());
+        assert_eq!(internal.max_connections, 0);
+        assert!(!internal.proxy_protocol);
+
+        let external = &config.listeners[1];
+        assert_eq!(external.protocol, "tls");
+        assert_eq!(external.ca_cert, "ca.pem");
+        assert_eq!(
+            external.authentication,
+            vec!["x509".to_string(), "password".to_string()]
+        );
+        assert_eq!(external.max_connections, 100);
+        assert_eq!(external.mountpoint, "tenant-a/");
+        assert!(external.proxy_protocol);
+        assert_eq!(external.sni_certs.len(), 1);
+        assert_eq!(external.sni_certs[0].server_name, "*.example.com");
+        assert_eq!(external.sni_certs[0].tls_cert, "example.pem");
+    }
+
+    #[test]
+    fn webhook_config_test() {
+        let content = r#"
+            cluster_name = "mqtt-broker"
+            broker_id = 1
+
+            [[hook.webhook]]
+            name = "registry"
+            url = "http://127.0.0.1:8080/events"
+            events = ["client.connected", "client.disconnected"]
+            headers = { Authorization = "Bearer token" }
+            batch_size = 10
+        "#;
+        let config: BrokerMqttConfig = toml::from_str(content).unwrap();
+        assert_eq!(config.hook.webhook.len(), 1);
+
+        let webhook = &config.hook.webhook[0];
+        assert_eq!(webhook.name, "registry");
+        assert_eq!(webhook.events.len(), 2);
+        assert_eq!(
+            webhook.headers.get("Authorization").unwrap(),
+            "Bearer token"
+        );
+        assert_eq!(webhook.batch_size, 10);
+        assert_eq!(webhook.batch_interval_ms, 1000);
+        assert_eq!(webhook.max_retries, 3);
+        assert_eq!(webhook.retry_backoff_ms, 1000);
+        assert_eq!(webhook.timeout_ms, 5000);
+        assert_eq!(webhook.queue_size, 10000);
     }
 
     #[test]
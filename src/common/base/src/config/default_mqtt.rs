@@ -13,7 +13,8 @@
 // limitations under the License.
 
 use super::broker_mqtt::{
-    ConfigAvailableFlag, MqttClusterDynamicConfigFeature, MqttClusterDynamicConfigNetwork,
+    ConfigAvailableFlag, DeadLetter, Drain, Hook, IdempotentMessage,
+    MqttClusterDynamicConfigFeature, MqttClusterDynamicConfigNetwork,
     MqttClusterDynamicConfigProtocol, MqttClusterDynamicConfigSecurity,
     MqttClusterDynamicFlappingDetect, MqttClusterDynamicSlowSub, Network, OfflineMessage, System,
     TcpThread,
@@ -37,8 +38,13 @@ pub fn default_network() -> Network {
         quic_port: default_network_quic_port(),
         tls_cert: "".to_string(),
         tls_key: "".to_string(),
+        tls_reload_interval_sec: default_network_tls_reload_interval_sec(),
     }
 }
+pub fn default_listener_enable() -> bool {
+    true
+}
+
 pub fn default_network_tcp_port() -> u32 {
     1883
 }
@@ -54,6 +60,9 @@ pub fn default_network_websockets_port() -> u32 {
 pub fn default_network_quic_port() -> u32 {
     9083
 }
+pub fn default_network_tls_reload_interval_sec() -> u64 {
+    60
+}
 
 pub fn default_tcp_thread() -> TcpThread {
     TcpThread {
@@ -101,6 +110,76 @@ pub fn default_offline_message() -> OfflineMessage {
     }
 }
 
+pub fn default_dead_letter() -> DeadLetter {
+    DeadLetter {
+        enable: false,
+        max_retry_times: 3,
+        retry_backoff_ms: 1000,
+        max_retry_backoff_ms: 30000,
+        topic_prefix: "$DLQ".to_string(),
+    }
+}
+
+pub fn default_drain() -> Drain {
+    Drain {
+        batch_size: 100,
+        batch_interval_ms: 1000,
+        server_reference: "".to_string(),
+        max_drain_time_ms: 30000,
+    }
+}
+
+pub fn default_idempotent_message() -> IdempotentMessage {
+    IdempotentMessage {
+        ttl_sec: 3600,
+        sync_interval_ms: 1000,
+    }
+}
+
+pub fn default_hook() -> Hook {
+    Hook {
+        order: Vec::new(),
+        exhook: Vec::new(),
+        webhook: Vec::new(),
+    }
+}
+
+pub fn default_exhook_timeout_ms() -> u64 {
+    5000
+}
+
+pub fn default_exhook_failure_policy() -> String {
+    "ignore".to_string()
+}
+
+pub fn default_exhook_reload_interval_ms() -> u64 {
+    60000
+}
+
+pub fn default_webhook_batch_size() -> usize {
+    100
+}
+
+pub fn default_webhook_batch_interval_ms() -> u64 {
+    1000
+}
+
+pub fn default_webhook_max_retries() -> u32 {
+    3
+}
+
+pub fn default_webhook_retry_backoff_ms() -> u64 {
+    1000
+}
+
+pub fn default_webhook_timeout_ms() -> u64 {
+    5000
+}
+
+pub fn default_webhook_queue_size() -> usize {
+    10000
+}
+
 pub fn default_auth() -> Auth {
     Auth {
         storage_type: "memory".to_string(),
@@ -110,6 +110,44 @@ macro_rules! gauge_metric_inc_by {
     }};
 }
 
+#[macro_export]
+macro_rules! counter_metric_inc_by {
+    ($family:ident,$label:ident,$v:expr) => {{
+        let family = $family.clone();
+        let mut found = false;
+        {
+            let family_r = family.read().unwrap();
+            if let Some(counter) = family_r.get(&$label) {
+                counter.inc_by($v);
+                found = true;
+            };
+        }
+        if !found {
+            let family_w = family.write().unwrap();
+            family_w.get_or_create(&$label).inc_by($v);
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! gauge_metric_set {
+    ($family:ident,$label:ident,$v:expr) => {{
+        let family = $family.clone();
+        let mut found = false;
+        {
+            let family_r = family.read().unwrap();
+            if let Some(gauge) = family_r.get(&$label) {
+                gauge.set($v);
+                found = true;
+            };
+        }
+        if !found {
+            let family_w = family.write().unwrap();
+            family_w.get_or_create(&$label).set($v);
+        }
+    }};
+}
+
 #[macro_export]
 macro_rules! gauge_metric_get {
     ($family:ident,$label:ident, $res:ident) => {{
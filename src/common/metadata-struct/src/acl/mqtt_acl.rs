@@ -25,6 +25,9 @@ pub struct MqttAcl {
     pub ip: String,
     pub action: MqttAclAction,
     pub permission: MqttAclPermission,
+    // The tenant the acl belongs to, empty for the default tenant
+    #[serde(default)]
+    pub tenant: String,
 }
 
 impl MqttAcl {
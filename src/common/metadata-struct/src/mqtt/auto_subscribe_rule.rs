@@ -23,6 +23,10 @@ pub struct MqttAutoSubscribeRule {
     pub no_local: bool,
     pub retain_as_published: bool,
     pub retained_handling: RetainForwardRule,
+    // The tenant the rule belongs to, empty for the default tenant. The rule only applies to the
+    // clients of its tenant.
+    #[serde(default)]
+    pub tenant: String,
 }
 
 impl MqttAutoSubscribeRule {
@@ -0,0 +1,124 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+// The settings shared by all connector types. They are read from the same JSON as the
+// config of the connector type, e.g.
+// {"url": "...", "retry": {"max_retry_times": 5}, "dead_letter_topic": "dlq/http"}.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ConnectorCommonConfig {
+    #[serde(default)]
+    pub retry: ConnectorRetryConfig,
+    // The topic the records that still fail after the retries are written to. When it is
+    // empty, a failed batch is retried until it succeeds and the connector is Failed meanwhile.
+    #[serde(default)]
+    pub dead_letter_topic: String,
+    // Applied in order to the payload of each message before it is sent.
+    #[serde(default)]
+    pub transforms: Vec<ConnectorTransform>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConnectorRetryConfig {
+    // The number of attempts of a batch before it is dead-lettered.
+    #[serde(default = "default_max_retry_times")]
+    pub max_retry_times: u32,
+    // The backoff doubles after each attempt, up to max_retry_backoff_ms.
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    #[serde(default = "default_max_retry_backoff_ms")]
+    pub max_retry_backoff_ms: u64,
+}
+
+impl Default for ConnectorRetryConfig {
+    fn default() -> Self {
+        ConnectorRetryConfig {
+            max_retry_times: default_max_retry_times(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            max_retry_backoff_ms: default_max_retry_backoff_ms(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum ConnectorTransform {
+    // Replaces the JSON payload with the value of the field, e.g. "data.temperature".
+    Extract { field: String },
+    // Moves the value of a field of the JSON payload to another field.
+    Rename { from: String, to: String },
+    // Converts the payload to the format.
+    Format { format: ConnectorPayloadFormat },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum ConnectorPayloadFormat {
+    // A payload that is not JSON is converted to a JSON string.
+    #[default]
+    Json,
+    // A payload that is a JSON string is unquoted.
+    Raw,
+    // The payload is encoded with standard base64.
+    Base64,
+}
+
+fn default_max_retry_times() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_retry_backoff_ms() -> u64 {
+    60000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectorCommonConfig, ConnectorPayloadFormat, ConnectorTransform};
+
+    #[test]
+    fn connector_common_config_test() {
+        let config: ConnectorCommonConfig =
+            serde_json::from_str(r#"{"url":"http://127.0.0.1:8080"}"#).unwrap();
+        assert_eq!(config, ConnectorCommonConfig::default());
+        assert_eq!(config.retry.max_retry_times, 3);
+        assert!(config.dead_letter_topic.is_empty());
+
+        let config: ConnectorCommonConfig = serde_json::from_str(
+            r#"{"retry":{"max_retry_times":5},"dead_letter_topic":"dlq/http","transforms":[{"type":"Extract","field":"data"},{"type":"Rename","from":"t","to":"temperature"},{"type":"Format","format":"Base64"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.retry.max_retry_times, 5);
+        assert_eq!(config.retry.retry_backoff_ms, 1000);
+        assert_eq!(config.dead_letter_topic, "dlq/http");
+        assert_eq!(
+            config.transforms,
+            vec![
+                ConnectorTransform::Extract {
+                    field: "data".to_string()
+                },
+                ConnectorTransform::Rename {
+                    from: "t".to_string(),
+                    to: "temperature".to_string()
+                },
+                ConnectorTransform::Format {
+                    format: ConnectorPayloadFormat::Base64
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HttpConnectorConfig {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    // The request body of each message, placeholders such as ${payload}, ${topic} and
+    // ${clientid} are replaced with the fields of the message.
+    #[serde(default = "default_body")]
+    pub body: String,
+    // The number of messages sent in one request, the body of a request with more than one
+    // message is a JSON array of the rendered bodies.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u64,
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: u64,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for HttpConnectorConfig {
+    fn default() -> Self {
+        HttpConnectorConfig {
+            url: String::new(),
+            method: default_method(),
+            headers: HashMap::new(),
+            body: default_body(),
+            batch_size: default_batch_size(),
+            max_concurrency: default_max_concurrency(),
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_body() -> String {
+    "${payload}".to_string()
+}
+
+fn default_batch_size() -> u64 {
+    1
+}
+
+fn default_max_concurrency() -> u64 {
+    4
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
@@ -14,9 +14,98 @@
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct KafkaConnectorConfig {
     pub bootstrap_servers: String,
     pub topic: String,
+    // The message key, placeholders such as ${topic}, ${clientid} and ${payload.device}
+    // are replaced with the fields of each message.
     pub key: String,
+    #[serde(default)]
+    pub payload_format: KafkaPayloadFormat,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u64,
+    #[serde(default = "default_linger_ms")]
+    pub linger_ms: u64,
+    #[serde(default)]
+    pub compression_type: KafkaCompressionType,
+}
+
+impl Default for KafkaConnectorConfig {
+    fn default() -> Self {
+        KafkaConnectorConfig {
+            bootstrap_servers: String::new(),
+            topic: String::new(),
+            key: String::new(),
+            payload_format: KafkaPayloadFormat::default(),
+            batch_size: default_batch_size(),
+            linger_ms: default_linger_ms(),
+            compression_type: KafkaCompressionType::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub enum KafkaPayloadFormat {
+    // Only the payload of the MQTT message
+    Raw,
+    // The MQTT message as JSON, including the topic, client id, qos and user properties
+    #[default]
+    Envelope,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub enum KafkaCompressionType {
+    #[default]
+    None,
+    Gzip,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl KafkaCompressionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KafkaCompressionType::None => "none",
+            KafkaCompressionType::Gzip => "gzip",
+            KafkaCompressionType::Snappy => "snappy",
+            KafkaCompressionType::Lz4 => "lz4",
+            KafkaCompressionType::Zstd => "zstd",
+        }
+    }
+}
+
+fn default_batch_size() -> u64 {
+    100
+}
+
+fn default_linger_ms() -> u64 {
+    5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KafkaCompressionType, KafkaConnectorConfig, KafkaPayloadFormat};
+
+    #[test]
+    fn kafka_connector_config_default_test() {
+        let config: KafkaConnectorConfig = serde_json::from_str(
+            r#"{"bootstrap_servers":"localhost:9092","topic":"t1","key":"k1"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.payload_format, KafkaPayloadFormat::Envelope);
+        assert_eq!(config.batch_size, 100);
+        assert_eq!(config.linger_ms, 5);
+        assert_eq!(config.compression_type, KafkaCompressionType::None);
+
+        let config: KafkaConnectorConfig = serde_json::from_str(
+            r#"{"bootstrap_servers":"localhost:9092","topic":"t1","key":"${clientid}","payload_format":"Raw","batch_size":500,"linger_ms":20,"compression_type":"Lz4"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.payload_format, KafkaPayloadFormat::Raw);
+        assert_eq!(config.batch_size, 500);
+        assert_eq!(config.linger_ms, 20);
+        assert_eq!(config.compression_type.as_str(), "lz4");
+    }
 }
@@ -0,0 +1,112 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MqttConnectorConfig {
+    // The address of the remote broker, tcp://host:1883 or ssl://host:8883
+    pub server: String,
+    #[serde(default)]
+    pub protocol_version: MqttConnectorProtocolVersion,
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub tls: Option<MqttConnectorTlsConfig>,
+    #[serde(default)]
+    pub direction: MqttConnectorDirection,
+    // Egress: the remote topic the messages are published to, e.g. cloud/${topic}.
+    // Ingress: the topic filter subscribed on the remote broker.
+    pub remote_topic: String,
+    // Ingress: the local topic the messages are published to, e.g. edge/${topic}.
+    #[serde(default = "default_local_topic")]
+    pub local_topic: String,
+    // The QoS used for the forwarded messages, the QoS of each message is kept if not set.
+    #[serde(default)]
+    pub qos: Option<u8>,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u64,
+    #[serde(default = "default_reconnect_min_interval_ms")]
+    pub reconnect_min_interval_ms: u64,
+    #[serde(default = "default_reconnect_max_interval_ms")]
+    pub reconnect_max_interval_ms: u64,
+}
+
+impl Default for MqttConnectorConfig {
+    fn default() -> Self {
+        MqttConnectorConfig {
+            server: String::new(),
+            protocol_version: MqttConnectorProtocolVersion::default(),
+            client_id: String::new(),
+            username: String::new(),
+            password: String::new(),
+            tls: None,
+            direction: MqttConnectorDirection::default(),
+            remote_topic: String::new(),
+            local_topic: default_local_topic(),
+            qos: None,
+            batch_size: default_batch_size(),
+            reconnect_min_interval_ms: default_reconnect_min_interval_ms(),
+            reconnect_max_interval_ms: default_reconnect_max_interval_ms(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub enum MqttConnectorProtocolVersion {
+    V311,
+    #[default]
+    V5,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub enum MqttConnectorDirection {
+    // Forwards the messages of the local topic to the remote broker
+    #[default]
+    Egress,
+    // Subscribes to the remote broker and publishes the messages locally
+    Ingress,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct MqttConnectorTlsConfig {
+    #[serde(default)]
+    pub ca_path: String,
+    #[serde(default)]
+    pub cert_path: String,
+    #[serde(default)]
+    pub key_path: String,
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+}
+
+fn default_local_topic() -> String {
+    "${topic}".to_string()
+}
+
+fn default_batch_size() -> u64 {
+    100
+}
+
+fn default_reconnect_min_interval_ms() -> u64 {
+    1000
+}
+
+fn default_reconnect_max_interval_ms() -> u64 {
+    60000
+}
@@ -0,0 +1,77 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum RedisConnectorMode {
+    // XADD the message to a stream.
+    #[default]
+    Xadd,
+    // LPUSH the message to a list.
+    Lpush,
+    // PUBLISH the message to a channel.
+    Publish,
+    // SET the message as the value of the key, so the key holds the latest message.
+    Set,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RedisConnectorConfig {
+    // e.g. redis://:password@127.0.0.1:6379/0
+    pub url: String,
+    #[serde(default)]
+    pub mode: RedisConnectorMode,
+    // The stream, list, channel or key the message is written to, placeholders such as
+    // ${topic}, ${clientid} and ${payload.device} are replaced with the fields of the message.
+    #[serde(default = "default_key")]
+    pub key: String,
+    // The value written for each message, rendered in the same way as the key.
+    #[serde(default = "default_value")]
+    pub value: String,
+    // The expiration of the key in seconds, set after each write. Not supported by PUBLISH.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    // Trims the stream to about this many entries on each XADD.
+    #[serde(default)]
+    pub stream_max_len: Option<u64>,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u64,
+}
+
+impl Default for RedisConnectorConfig {
+    fn default() -> Self {
+        RedisConnectorConfig {
+            url: String::new(),
+            mode: RedisConnectorMode::default(),
+            key: default_key(),
+            value: default_value(),
+            ttl_secs: None,
+            stream_max_len: None,
+            batch_size: default_batch_size(),
+        }
+    }
+}
+
+fn default_key() -> String {
+    "${topic}".to_string()
+}
+
+fn default_value() -> String {
+    "${payload}".to_string()
+}
+
+fn default_batch_size() -> u64 {
+    100
+}
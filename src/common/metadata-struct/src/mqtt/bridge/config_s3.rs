@@ -0,0 +1,97 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct S3ConnectorConfig {
+    // The address of the S3 compatible storage, e.g. http://127.0.0.1:9000 for MinIO
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default)]
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    // The directory of the bucket the files are written to
+    #[serde(default = "default_root")]
+    pub root: String,
+    #[serde(default)]
+    pub format: S3FileFormat,
+    // The directory of each file, ${topic}, ${date}, ${hour} and the other time fields are
+    // replaced with the values of the first message of the file.
+    #[serde(default = "default_path")]
+    pub path: String,
+    // A file is written when its messages reach max_file_size bytes, or roll_interval_secs
+    // after its first message was read.
+    #[serde(default = "default_max_file_size")]
+    pub max_file_size: u64,
+    #[serde(default = "default_roll_interval_secs")]
+    pub roll_interval_secs: u64,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u64,
+}
+
+impl Default for S3ConnectorConfig {
+    fn default() -> Self {
+        S3ConnectorConfig {
+            endpoint: String::new(),
+            bucket: String::new(),
+            region: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            root: default_root(),
+            format: S3FileFormat::default(),
+            path: default_path(),
+            max_file_size: default_max_file_size(),
+            roll_interval_secs: default_roll_interval_secs(),
+            batch_size: default_batch_size(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub enum S3FileFormat {
+    #[default]
+    Parquet,
+    Jsonl,
+}
+
+impl S3FileFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            S3FileFormat::Parquet => "parquet",
+            S3FileFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+fn default_root() -> String {
+    "/".to_string()
+}
+
+fn default_path() -> String {
+    "${topic}/${date}/${hour}".to_string()
+}
+
+fn default_max_file_size() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_roll_interval_secs() -> u64 {
+    3600
+}
+
+fn default_batch_size() -> u64 {
+    1000
+}
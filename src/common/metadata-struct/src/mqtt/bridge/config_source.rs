@@ -0,0 +1,117 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+// Where a source starts reading when it has no stored source offset yet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum SourceStartFrom {
+    Earliest,
+    #[default]
+    Latest,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KafkaSourceConnectorConfig {
+    pub bootstrap_servers: String,
+    // The Kafka topic consumed by the connector.
+    pub topic: String,
+    // The group.id of the consumer, defaults to robustmq-<connector name>. The offsets are
+    // stored by the connector and not committed to the group.
+    #[serde(default)]
+    pub group_id: String,
+    // The MQTT topic the records are published to, placeholders such as ${topic}, ${key},
+    // ${partition} and ${payload.device} are replaced with the fields of the record.
+    pub mqtt_topic: String,
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    #[serde(default)]
+    pub start_from: SourceStartFrom,
+    // The user the messages are published as, the ACL of the user applies to them.
+    #[serde(default)]
+    pub mqtt_username: String,
+    #[serde(default = "default_offset_commit_interval_ms")]
+    pub offset_commit_interval_ms: u64,
+}
+
+impl Default for KafkaSourceConnectorConfig {
+    fn default() -> Self {
+        KafkaSourceConnectorConfig {
+            bootstrap_servers: String::new(),
+            topic: String::new(),
+            group_id: String::new(),
+            mqtt_topic: String::new(),
+            qos: default_qos(),
+            retain: false,
+            start_from: SourceStartFrom::default(),
+            mqtt_username: String::new(),
+            offset_commit_interval_ms: default_offset_commit_interval_ms(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LocalFileSourceConnectorConfig {
+    // The file that is tailed, each line is published as one message.
+    pub path: String,
+    // The MQTT topic the lines are published to, placeholders such as ${payload.device} are
+    // replaced with the fields of the line when it is JSON.
+    pub mqtt_topic: String,
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    #[serde(default)]
+    pub start_from: SourceStartFrom,
+    #[serde(default)]
+    pub mqtt_username: String,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    // The maximum number of lines published per poll.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u64,
+}
+
+impl Default for LocalFileSourceConnectorConfig {
+    fn default() -> Self {
+        LocalFileSourceConnectorConfig {
+            path: String::new(),
+            mqtt_topic: String::new(),
+            qos: default_qos(),
+            retain: false,
+            start_from: SourceStartFrom::default(),
+            mqtt_username: String::new(),
+            poll_interval_ms: default_poll_interval_ms(),
+            batch_size: default_batch_size(),
+        }
+    }
+}
+
+fn default_qos() -> u8 {
+    1
+}
+
+fn default_offset_commit_interval_ms() -> u64 {
+    1000
+}
+
+fn default_poll_interval_ms() -> u64 {
+    500
+}
+
+fn default_batch_size() -> u64 {
+    100
+}
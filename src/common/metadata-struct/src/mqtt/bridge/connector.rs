@@ -14,7 +14,10 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::{connector_type::ConnectorType, status::MQTTStatus};
+use super::{
+    connector_type::ConnectorType,
+    status::{ConnectorRuntimeStatus, MQTTStatus},
+};
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct MQTTConnector {
@@ -27,6 +30,12 @@ pub struct MQTTConnector {
     pub broker_id: Option<u64>,
     pub create_time: u64,
     pub update_time: u64,
+    // The tenant the connector belongs to, empty for the default tenant
+    #[serde(default)]
+    pub tenant: String,
+    // Only filled in when the connectors are listed through the admin interface.
+    #[serde(default)]
+    pub runtime_status: Option<ConnectorRuntimeStatus>,
 }
 
 impl MQTTConnector {
@@ -21,6 +21,24 @@ pub enum ConnectorType {
     #[default]
     Kafka,
     LocalFile,
+    Mqtt,
+    Http,
+    MySQL,
+    PostgreSQL,
+    S3,
+    Redis,
+    KafkaSource,
+    LocalFileSource,
+}
+
+impl ConnectorType {
+    // A source publishes into MQTT topics, it does not read the topic of the connector.
+    pub fn is_source(&self) -> bool {
+        matches!(
+            self,
+            ConnectorType::KafkaSource | ConnectorType::LocalFileSource
+        )
+    }
 }
 
 impl Display for ConnectorType {
@@ -12,8 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod config_common;
+pub mod config_http;
 pub mod config_kafka;
 pub mod config_local_file;
+pub mod config_mqtt;
+pub mod config_redis;
+pub mod config_s3;
+pub mod config_source;
+pub mod config_sql;
 pub mod connector;
 pub mod connector_type;
 pub mod status;
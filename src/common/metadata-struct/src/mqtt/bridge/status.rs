@@ -21,6 +21,9 @@ pub enum MQTTStatus {
     #[default]
     Idle,
     Running,
+    Paused,
+    // The connector keeps failing, the error is in the last_error of its runtime status.
+    Failed,
 }
 
 impl Display for MQTTStatus {
@@ -28,3 +31,29 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
     }
 }
+
+// The status of a connector as reported by the broker running it.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct ConnectorRuntimeStatus {
+    pub status: MQTTStatus,
+    pub broker_id: u64,
+    pub last_error: String,
+    pub last_error_time: u64,
+    pub last_success_time: u64,
+    // The number of records delivered, failed attempts and dead-lettered records, counted
+    // since the broker running the connector was started.
+    pub success_num: u64,
+    pub error_num: u64,
+    pub dead_letter_num: u64,
+    // The seconds between the newest delivered record being written and being delivered. It is
+    // not the number of records waiting to be delivered.
+    #[serde(default)]
+    pub delivery_delay_sec: u64,
+    // The number of records of the topic of the connector after its committed offset.
+    #[serde(default)]
+    pub lag: u64,
+    // The thread of the connector on the broker exited after the connector was paused or
+    // scheduled to another broker, it no longer delivers records or commits offsets.
+    #[serde(default)]
+    pub stopped: bool,
+}
@@ -41,6 +41,8 @@ pub struct MQTTConnection {
     pub max_packet_size: u32,
     // Record the maximum number of connection dimensions and topic aliases. The default value ranges from 0 to 65535
     pub topic_alias_max: u16,
+    // The maximum topic alias the client accepts from the server. 0 means the server must not send topic aliases.
+    pub client_topic_alias_max: u16,
     // Flags whether to return a detailed error message to the client when an error occurs.
     pub request_problem_info: u8,
     // Flow control part keeps track of how many QOS 1 and QOS 2 messages are still pending on the connection
@@ -51,6 +53,12 @@ pub struct MQTTConnection {
     pub sender_qos_message: Arc<AtomicIsize>,
     // Time when the connection was created
     pub create_time: u64,
+    // Prefix transparently added to the topics of the connection, empty when not mounted
+    #[serde(default)]
+    pub mountpoint: String,
+    // The tenant of the login user, empty for the default tenant
+    #[serde(default)]
+    pub tenant: String,
 }
 
 pub struct ConnectionConfig {
@@ -59,6 +67,7 @@ pub struct ConnectionConfig {
     pub receive_maximum: u16,
     pub max_packet_size: u32,
     pub topic_alias_max: u16,
+    pub client_topic_alias_max: u16,
     pub request_problem_info: u8,
     pub keep_alive: u16,
     pub source_ip_addr: String,
@@ -75,6 +84,7 @@ pub fn new(config: ConnectionConfig) -> MQTTConnection {
             max_packet_size: config.max_packet_size,
             topic_alias: DashMap::with_capacity(2),
             topic_alias_max: config.topic_alias_max,
+            client_topic_alias_max: config.client_topic_alias_max,
             request_problem_info: config.request_problem_info,
             receive_qos_message: Arc::new(AtomicIsize::new(0)),
             sender_qos_message: Arc::new(AtomicIsize::new(0)),
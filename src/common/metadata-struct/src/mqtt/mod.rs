@@ -19,8 +19,12 @@
 pub mod lastwill;
 pub mod message;
 pub mod node_extend;
+pub mod rule;
+pub mod scheduled_message;
 pub mod session;
 pub mod subscribe_data;
+pub mod tenant;
 pub mod topic;
 pub mod topic_rewrite_rule;
 pub mod user;
+pub mod wasm_plugin;
@@ -0,0 +1,53 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct MqttRule {
+    pub cluster: String,
+    pub rule_name: String,
+    pub sql: String,
+    pub actions: Vec<MqttRuleAction>,
+    pub desc: String,
+    pub create_time: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct MqttRuleAction {
+    pub action_type: MqttRuleActionType,
+    // The topic for Republish, it may contain ${field} placeholders of the rule output.
+    // The connector name for Connector.
+    #[serde(default)]
+    pub target: String,
+    #[serde(default)]
+    pub qos: u8,
+    // Whether the message of Republish is retained.
+    #[serde(default)]
+    pub retain: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub enum MqttRuleActionType {
+    #[default]
+    Republish,
+    Connector,
+    Drop,
+}
+
+impl MqttRule {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
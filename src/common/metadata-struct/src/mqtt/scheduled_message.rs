@@ -0,0 +1,208 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use common_base::error::common::CommonError;
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+
+// A message published by the scheduler at each tick of its schedule. The schedule is either a
+// cron expression or a fixed interval, starting at start_time and ending at end_time. Times are
+// unix timestamps in seconds, an end_time of 0 never ends.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct MqttScheduledMessage {
+    pub cluster_name: String,
+    pub name: String,
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub qos: u8,
+    pub retain: bool,
+    // Cron expression with a seconds field, evaluated in UTC
+    pub cron: String,
+    pub interval_sec: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    // The time of the last fired tick, 0 before the first tick
+    pub last_fire_time: u64,
+    pub fire_count: u64,
+    // The time of the last tick the broker acknowledged. A fired tick that is not acknowledged
+    // is sent again, the broker drops the ticks it has already stored.
+    #[serde(default)]
+    pub delivered_fire_time: u64,
+    pub desc: String,
+    pub create_time: u64,
+}
+
+// The fire state of a scheduled message, written by the scheduler without touching the rest
+// of the message.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct MqttScheduledMessageFireState {
+    pub cluster_name: String,
+    pub name: String,
+    pub last_fire_time: u64,
+    pub fire_count: u64,
+    pub delivered_fire_time: u64,
+}
+
+impl MqttScheduledMessageFireState {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
+
+impl MqttScheduledMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+
+    pub fn validate(&self) -> Result<(), CommonError> {
+        if self.cron.is_empty() && self.interval_sec == 0 {
+            return Err(CommonError::CommonError(
+                "either cron or interval_sec is required".to_string(),
+            ));
+        }
+        if !self.cron.is_empty() && self.interval_sec > 0 {
+            return Err(CommonError::CommonError(
+                "cron and interval_sec cannot be set at the same time".to_string(),
+            ));
+        }
+        if !self.cron.is_empty() {
+            parse_cron(&self.cron)?;
+        }
+        if self.end_time > 0 && self.end_time < self.start_time {
+            return Err(CommonError::CommonError(
+                "end_time cannot be earlier than start_time".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // Returns the first tick later than the given time, None when the schedule has ended.
+    pub fn next_fire_time(&self, after: u64) -> Result<Option<u64>, CommonError> {
+        let after = after.max(self.start_time.saturating_sub(1));
+        let next = if !self.cron.is_empty() {
+            let schedule = parse_cron(&self.cron)?;
+            let after = DateTime::<Utc>::from_timestamp(after as i64, 0)
+                .ok_or_else(|| CommonError::CommonError(format!("invalid timestamp {}", after)))?;
+            match schedule.after(&after).next() {
+                Some(time) => time.timestamp() as u64,
+                None => return Ok(None),
+            }
+        } else if self.interval_sec > 0 {
+            if after < self.start_time {
+                self.start_time
+            } else {
+                let ticks = (after - self.start_time) / self.interval_sec + 1;
+                self.start_time + ticks * self.interval_sec
+            }
+        } else {
+            return Ok(None);
+        };
+
+        if self.end_time > 0 && next > self.end_time {
+            return Ok(None);
+        }
+        Ok(Some(next))
+    }
+
+    // The tick that fires next. Ticks missed while no scheduler was running are not caught up.
+    pub fn pending_fire_time(&self) -> Result<Option<u64>, CommonError> {
+        self.next_fire_time(self.last_fire_time)
+    }
+
+    // The last tick was fired but the broker has not acknowledged it yet.
+    pub fn is_delivery_pending(&self) -> bool {
+        self.last_fire_time > self.delivered_fire_time
+    }
+
+    pub fn fire_state(&self) -> MqttScheduledMessageFireState {
+        MqttScheduledMessageFireState {
+            cluster_name: self.cluster_name.clone(),
+            name: self.name.clone(),
+            last_fire_time: self.last_fire_time,
+            fire_count: self.fire_count,
+            delivered_fire_time: self.delivered_fire_time,
+        }
+    }
+}
+
+fn parse_cron(expression: &str) -> Result<Schedule, CommonError> {
+    Schedule::from_str(expression).map_err(|e| {
+        CommonError::CommonError(format!("invalid cron expression {}: {}", expression, e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MqttScheduledMessage;
+
+    #[test]
+    fn interval_fire_time_test() {
+        let message = MqttScheduledMessage {
+            interval_sec: 60,
+            start_time: 1000,
+            end_time: 1200,
+            ..Default::default()
+        };
+        assert!(message.validate().is_ok());
+        assert_eq!(message.pending_fire_time().unwrap(), Some(1000));
+        assert_eq!(message.next_fire_time(1000).unwrap(), Some(1060));
+        assert_eq!(message.next_fire_time(1130).unwrap(), Some(1180));
+        assert_eq!(message.next_fire_time(1180).unwrap(), None);
+    }
+
+    #[test]
+    fn cron_fire_time_test() {
+        // 2024-01-01T00:00:00Z
+        let start_time = 1704067200;
+        let message = MqttScheduledMessage {
+            cron: "0 30 8 * * *".to_string(),
+            start_time,
+            ..Default::default()
+        };
+        assert!(message.validate().is_ok());
+        let first = start_time + 8 * 3600 + 30 * 60;
+        assert_eq!(message.pending_fire_time().unwrap(), Some(first));
+        assert_eq!(message.next_fire_time(first).unwrap(), Some(first + 86400));
+    }
+
+    #[test]
+    fn validate_test() {
+        let message = MqttScheduledMessage::default();
+        assert!(message.validate().is_err());
+
+        let message = MqttScheduledMessage {
+            cron: "0 * * * * *".to_string(),
+            interval_sec: 10,
+            ..Default::default()
+        };
+        assert!(message.validate().is_err());
+
+        let message = MqttScheduledMessage {
+            cron: "every day".to_string(),
+            ..Default::default()
+        };
+        assert!(message.validate().is_err());
+
+        let message = MqttScheduledMessage {
+            interval_sec: 10,
+            start_time: 100,
+            end_time: 50,
+            ..Default::default()
+        };
+        assert!(message.validate().is_err());
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+// Separates the tenant from the name of a user or an acl resource of the tenant
+pub const TENANT_NAME_SEPARATOR: char = ':';
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct MqttTenant {
+    pub tenant_name: String,
+    pub desc: String,
+    // Quotas of the tenant, 0 means no limit
+    pub max_connections: u64,
+    pub max_topics: u64,
+    // Maximum number of messages published by the tenant per second
+    pub max_publish_rate: u64,
+    pub create_time: u64,
+}
+
+impl MqttTenant {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
+
+// Users and acls are scoped by their tenant, so two tenants can have a user of the same name. The
+// clients of a tenant log in as `<tenant>:<username>`, the default tenant keeps the plain name.
+pub fn tenant_scoped_name(tenant: &str, name: &str) -> String {
+    if tenant.is_empty() {
+        return name.to_string();
+    }
+    format!("{}{}{}", tenant, TENANT_NAME_SEPARATOR, name)
+}
+
+// A resource owned by a tenant, the placement center keeps one under the tenant keys for every
+// user, acl, topic, schema, connector and auto subscribe rule of the tenant.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct MqttTenantResource {
+    pub resource_type: String,
+    pub resource_name: String,
+}
+
+// The usage of the tenants on one broker node. Every node reports its usage to the placement
+// center, so the connection and publish rate quotas are enforced on the whole cluster.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct MqttTenantUsage {
+    pub broker_id: u64,
+    // (tenant_name, connection num)
+    pub connection_num: HashMap<String, u64>,
+    // (tenant_name, messages published per second)
+    pub publish_rate: HashMap<String, u64>,
+    pub report_time: u64,
+}
@@ -23,6 +23,9 @@ pub struct MqttTopic {
     pub retain_message: Option<Vec<u8>>,
     pub retain_message_expired_at: Option<u64>,
     pub create_time: u64,
+    // The tenant the topic belongs to, empty for the default tenant
+    #[serde(default)]
+    pub tenant: String,
 }
 
 impl MqttTopic {
@@ -34,6 +37,7 @@ pub fn new(topic_id: String, cluster_name: String, topic_name: String) -> Self {
             retain_message: None,
             retain_message_expired_at: None,
             create_time: now_second(),
+            tenant: "".to_string(),
         }
     }
 
@@ -14,15 +14,28 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::tenant::tenant_scoped_name;
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct MqttUser {
     pub username: String,
     pub password: String,
     pub is_superuser: bool,
+    // Prefix added to the topics of the user, overrides the mountpoint of the listener
+    #[serde(default)]
+    pub mountpoint: String,
+    // The tenant the user belongs to, empty for the default tenant
+    #[serde(default)]
+    pub tenant: String,
 }
 
 impl MqttUser {
     pub fn encode(&self) -> Vec<u8> {
         serde_json::to_vec(&self).unwrap()
     }
+
+    // The name the clients of the user log in with
+    pub fn login_name(&self) -> String {
+        tenant_scoped_name(&self.tenant, &self.username)
+    }
 }
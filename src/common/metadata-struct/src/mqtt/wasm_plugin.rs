@@ -0,0 +1,38 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct MqttWasmPlugin {
+    pub cluster: String,
+    pub plugin_name: String,
+    // The plugin runs on the messages published to topics matching one of the topic filters.
+    pub topics: Vec<String>,
+    // Limits the plugin to the messages published by the clients of the tenant, all tenants
+    // when it is empty.
+    #[serde(default)]
+    pub tenant: String,
+    pub module: Vec<u8>,
+    pub max_fuel: u64,
+    pub max_memory_bytes: u64,
+    pub desc: String,
+    pub create_time: u64,
+}
+
+impl MqttWasmPlugin {
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(&self).unwrap()
+    }
+}
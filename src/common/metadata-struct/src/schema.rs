@@ -23,6 +23,9 @@ pub struct SchemaData {
     pub schema_type: SchemaType,
     pub desc: String,
     pub schema: String,
+    // The tenant the schema belongs to, empty for the default tenant
+    #[serde(default)]
+    pub tenant: String,
 }
 
 impl SchemaData {
@@ -12,15 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_base::tools::now_second;
 use log::error;
 use metadata_struct::adapter::read_config::ReadConfig;
 use std::{sync::Arc, time::Duration};
 use storage_adapter::storage::StorageAdapter;
 use tokio::time::sleep;
 
-use crate::{DelayMessageManager, DelayMessageRecord};
+use crate::cancel::cancelled_offset;
+use crate::metrics::{
+    incr_rebuild_read_counter, incr_rebuild_restored_counter, record_checkpoint_offset,
+    record_rebuild_finished, record_rebuild_offset,
+};
+use crate::pop::delivering_offset;
+use crate::{delay_message_id, DelayMessageManager, DelayMessageRecord};
 
+// Rebuilds the delay queue of the shard from the records after its checkpoint, up to the
+// first message this broker wrote itself.
 pub async fn build_delay_queue<S>(
     message_storage_adapter: Arc<S>,
     delay_message_manager: Arc<DelayMessageManager<S>>,
@@ -31,9 +38,21 @@ pub async fn build_delay_queue<S>(
 ) where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
-    let mut offset = 0;
+    record_rebuild_finished(&shard_name, false);
+    let mut offset = loop {
+        match delay_message_manager.read_checkpoint(shard_no).await {
+            Ok(offset) => break offset,
+            Err(e) => {
+                error!("Reading the checkpoint of the shard {} failed with error {:?} while building the deferred message index", shard_name, e);
+                sleep(Duration::from_secs(1)).await;
+            }
+        }
+    };
+    delay_message_manager.start_rebuild(shard_no, offset);
+    record_checkpoint_offset(&shard_name, offset);
 
-    loop {
+    'read: loop {
+        record_rebuild_offset(&shard_name, offset);
         let data = match message_storage_adapter
             .read_by_offset(
                 namespace.to_owned(),
@@ -53,24 +72,87 @@ pub async fn build_delay_queue<S>(
         if data.is_empty() {
             break;
         }
+        let first_write_offset = delay_message_manager
+            .shard_state
+            .get(&shard_no)
+            .and_then(|raw| raw.first_write_offset);
         for record in data {
-            offset = record.offset.unwrap();
+            let record_offset = record.offset.unwrap();
+            // The messages from here on were added to the queue when they were sent.
+            if first_write_offset
+                .is_some_and(|first_write_offset| record_offset >= first_write_offset)
+            {
+                break 'read;
+            }
+            offset = record_offset + 1;
+            incr_rebuild_read_counter(&shard_name);
 
-            if record.delay_timestamp < now_second() {
+            // The cancellation is always written after the message it cancels.
+            if let Some(cancelled_offset) = cancelled_offset(&record) {
+                delay_message_manager.remove_from_delay_queue(
+                    shard_no,
+                    &delay_message_id(shard_no, cancelled_offset),
+                );
                 continue;
             }
 
-            let delay_message_record = DelayMessageRecord {
-                shard_name: shard_name.to_owned(),
-                offset,
-                delay_timestamp: record.delay_timestamp,
-            };
+            // The delivery was started before the restart, it may have completed or not.
+            if let Some(delivering_offset) = delivering_offset(&record) {
+                delay_message_manager.mark_delivering(
+                    shard_no,
+                    &delay_message_id(shard_no, delivering_offset),
+                    record.timestamp,
+                );
+                continue;
+            }
+
+            if record.delay_timestamp == 0 {
+                continue;
+            }
+
+            // Messages that became due while the broker was down are delivered right away.
+            let delay_message_record =
+                DelayMessageRecord::build(shard_no, shard_name.to_owned(), record_offset, &record);
 
             if let Err(e) = delay_message_manager
                 .send_to_delay_queue(shard_no, delay_message_record)
                 .await
             {
                 error!("While building the deferred message index, sending a message to the DelayQueue failed with error message :{:?}", e);
+                continue;
+            }
+            incr_rebuild_restored_counter(&shard_name);
+        }
+    }
+
+    record_rebuild_offset(&shard_name, offset);
+    delay_message_manager.finish_rebuild(shard_no, offset);
+    record_rebuild_finished(&shard_name, true);
+}
+
+impl<S> DelayMessageManager<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    fn start_rebuild(&self, shard_no: u64, checkpoint_offset: u64) {
+        let mut state = self.shard_state.entry(shard_no).or_default();
+        state.rebuilt = false;
+        state.checkpoint_offset = checkpoint_offset;
+        state.end_offset = state.end_offset.max(checkpoint_offset);
+    }
+
+    fn finish_rebuild(&self, shard_no: u64, end_offset: u64) {
+        let mut state = self.shard_state.entry(shard_no).or_default();
+        state.rebuilt = true;
+        state.end_offset = state.end_offset.max(end_offset);
+    }
+
+    fn mark_delivering(&self, shard_no: u64, id: &str, deliver_time: u64) {
+        let _delay_queue = self.delay_queue_list.get_mut(&shard_no);
+        if let Some(mut raw) = self.delay_message_index.get_mut(id) {
+            if !raw.0.delivering {
+                raw.0.delivering = true;
+                raw.0.deliver_time = deliver_time;
             }
         }
     }
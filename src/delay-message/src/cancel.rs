@@ -0,0 +1,178 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use metadata_struct::adapter::record::{Header, Record};
+use storage_adapter::storage::StorageAdapter;
+
+use crate::{parse_delay_message_id, record_header, DelayMessageManager, DelayMessageRecord};
+
+// A cancelled delay message is recorded by writing a record with this header to its shard, so
+// the rebuild of the delay queue after a restart drops the message again.
+const DELAY_MESSAGE_HEADER_CANCEL_OFFSET: &str = "delay_cancel_offset";
+
+impl<S> DelayMessageManager<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    // Cancels a delay message that has not been published yet. Returns the cancelled message,
+    // or None when no delay message has this id.
+    pub async fn cancel_delay_message(
+        &self,
+        id: &str,
+    ) -> Result<Option<DelayMessageRecord>, CommonError> {
+        let Some((shard_no, _)) = parse_delay_message_id(id) else {
+            return Ok(None);
+        };
+        let Some(delay_message_record) = self.remove_from_delay_queue(shard_no, id) else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self
+            .message_storage_adapter
+            .write(
+                self.namespace.clone(),
+                delay_message_record.shard_name.clone(),
+                build_cancel_record(delay_message_record.offset),
+            )
+            .await
+        {
+            // The cancellation could not be persisted, so the message stays pending.
+            self.send_to_delay_queue(shard_no, delay_message_record)
+                .await?;
+            return Err(e);
+        }
+        Ok(Some(delay_message_record))
+    }
+
+    pub(crate) fn remove_from_delay_queue(
+        &self,
+        shard_no: u64,
+        id: &str,
+    ) -> Option<DelayMessageRecord> {
+        let mut delay_queue = self.delay_queue_list.get_mut(&shard_no)?;
+        let (_, (delay_message_record, key)) = self.delay_message_index.remove(id)?;
+        delay_queue.remove(&key);
+        Some(delay_message_record)
+    }
+}
+
+pub fn build_cancel_record(offset: u64) -> Record {
+    let mut record = Record::build_byte(Vec::new());
+    record.set_header(vec![Header {
+        name: DELAY_MESSAGE_HEADER_CANCEL_OFFSET.to_string(),
+        value: offset.to_string(),
+    }]);
+    record
+}
+
+// Returns the offset of the cancelled message when the record is a cancellation.
+pub fn cancelled_offset(record: &Record) -> Option<u64> {
+    record_header(record, DELAY_MESSAGE_HEADER_CANCEL_OFFSET)?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_base::tools::now_second;
+    use metadata_struct::adapter::read_config::ReadConfig;
+    use metadata_struct::adapter::record::Record;
+    use storage_adapter::memory::MemoryStorageAdapter;
+
+    use super::{build_cancel_record, cancelled_offset};
+    use crate::build::build_delay_queue;
+    use crate::{delay_message_id, parse_delay_message_id, DelayMessageManager};
+
+    #[tokio::test]
+    async fn cancel_delay_message_after_restart_test() {
+        let namespace = "test".to_string();
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let delay_message_manager =
+            DelayMessageManager::new(namespace.clone(), 1, storage_adapter.clone());
+        delay_message_manager.init().await.unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..2 {
+            let mut record = Record::build_str(format!("data{}", i));
+            record.delay_timestamp = now_second() + 100;
+            let id = delay_message_manager
+                .send_delay_message("topic-1", "/a/b", "c1", record)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        assert_eq!(delay_message_manager.list_delay_message().len(), 2);
+        let record = delay_message_manager.get_delay_message(&ids[0]).unwrap();
+        assert_eq!(record.topic, "/a/b");
+        assert_eq!(record.client_id, "c1");
+
+        let cancelled = delay_message_manager
+            .cancel_delay_message(&ids[0])
+            .await
+            .unwrap();
+        assert!(cancelled.is_some());
+        assert!(delay_message_manager
+            .cancel_delay_message(&ids[0])
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(delay_message_manager.list_delay_message().len(), 1);
+
+        // rebuild the delay queue from the shard, as after a restart
+        let delay_message_manager = Arc::new(DelayMessageManager::new(
+            namespace.clone(),
+            1,
+            storage_adapter.clone(),
+        ));
+        delay_message_manager.init().await.unwrap();
+        build_delay_queue(
+            storage_adapter,
+            delay_message_manager.clone(),
+            namespace,
+            0,
+            delay_message_manager.get_delay_message_shard_name(0),
+            ReadConfig {
+                max_record_num: 100,
+                max_size: 1024 * 1024 * 1024,
+            },
+        )
+        .await;
+
+        let results = delay_message_manager.list_delay_message();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), ids[1]);
+    }
+
+    #[test]
+    fn cancel_record_test() {
+        let record = build_cancel_record(1024);
+        assert_eq!(cancelled_offset(&record), Some(1024));
+        assert_eq!(record.delay_timestamp, 0);
+
+        let record = Record::build_str("data".to_string());
+        assert_eq!(cancelled_offset(&record), None);
+    }
+
+    #[test]
+    fn delay_message_id_test() {
+        let id = delay_message_id(2, 1024);
+        assert_eq!(id, "2-1024");
+        assert_eq!(parse_delay_message_id(&id), Some((2, 1024)));
+        assert_eq!(parse_delay_message_id("2"), None);
+        assert_eq!(parse_delay_message_id("a-1"), None);
+    }
+}
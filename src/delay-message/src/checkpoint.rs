@@ -0,0 +1,232 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use log::error;
+use storage_adapter::storage::StorageAdapter;
+
+use crate::metrics::record_checkpoint_offset;
+use crate::DelayMessageManager;
+
+// The checkpoint of a shard is taken at most once per interval, in seconds.
+const DELAY_MESSAGE_CHECKPOINT_INTERVAL: u64 = 10;
+
+pub fn delay_message_checkpoint_group(namespace: &str, shard_no: u64) -> String {
+    format!("$delay-message-checkpoint-{}-{}", namespace, shard_no)
+}
+
+impl<S> DelayMessageManager<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    // Reads the checkpoint of the shard. Every message below it has been delivered or
+    // cancelled, so the rebuild of the delay queue starts there.
+    pub async fn read_checkpoint(&self, shard_no: u64) -> Result<u64, CommonError> {
+        let results = self
+            .message_storage_adapter
+            .get_offset_by_group(delay_message_checkpoint_group(&self.namespace, shard_no))
+            .await?;
+        Ok(results.first().map(|raw| raw.offset).unwrap_or(0))
+    }
+
+    pub(crate) async fn try_checkpoint(&self, shard_no: u64) {
+        let Some(state) = self.shard_state.get(&shard_no).map(|raw| raw.clone()) else {
+            return;
+        };
+        if now_second().saturating_sub(state.checkpoint_time) < DELAY_MESSAGE_CHECKPOINT_INTERVAL {
+            return;
+        }
+
+        if let Err(e) = self.checkpoint(shard_no).await {
+            error!(
+                "Saving the checkpoint of delay message shard {} failed with error {:?}",
+                shard_no, e
+            );
+        }
+    }
+
+    // Saves the offset of the oldest message of the shard that is still waiting or being
+    // delivered, and deletes the records below it. Returns the saved offset.
+    pub async fn checkpoint(&self, shard_no: u64) -> Result<u64, CommonError> {
+        let state = self
+            .shard_state
+            .get(&shard_no)
+            .map(|raw| raw.clone())
+            .unwrap_or_default();
+        // Before the rebuild has finished, the messages below the end of the shard are not
+        // all known yet.
+        if !state.rebuilt {
+            return Ok(state.checkpoint_offset);
+        }
+
+        let offset = {
+            let write_lock = self.shard_write_lock(shard_no);
+            let _guard = write_lock.lock().await;
+            self.low_watermark(shard_no)
+        };
+
+        if offset <= state.checkpoint_offset {
+            self.update_checkpoint_state(shard_no, state.checkpoint_offset);
+            return Ok(state.checkpoint_offset);
+        }
+
+        let shard_name = self.get_delay_message_shard_name(shard_no);
+        self.message_storage_adapter
+            .commit_offset(
+                delay_message_checkpoint_group(&self.namespace, shard_no),
+                self.namespace.clone(),
+                HashMap::from([(shard_name.clone(), offset)]),
+            )
+            .await?;
+        self.update_checkpoint_state(shard_no, offset);
+        record_checkpoint_offset(&shard_name, offset);
+
+        if let Err(e) = self
+            .message_storage_adapter
+            .trim_shard(self.namespace.clone(), shard_name.clone(), offset)
+            .await
+        {
+            error!(
+                "Trimming delay message shard {} below offset {} failed with error {:?}",
+                shard_name, offset, e
+            );
+        }
+        Ok(offset)
+    }
+
+    // The offset of the oldest message of the shard in the delay queue or being delivered,
+    // or the end of the shard when there is none.
+    fn low_watermark(&self, shard_no: u64) -> u64 {
+        // Holding the queue keeps messages from moving between the queue and the delivering
+        // messages while they are read.
+        let _delay_queue = self.delay_queue_list.get_mut(&shard_no);
+        let waiting = self
+            .delay_message_index
+            .iter()
+            .filter(|raw| raw.value().0.shard_no == shard_no)
+            .map(|raw| raw.value().0.offset)
+            .min();
+        let delivering = self
+            .delay_message_delivering
+            .iter()
+            .filter(|raw| raw.value().shard_no == shard_no)
+            .map(|raw| raw.value().offset)
+            .min();
+
+        match waiting.into_iter().chain(delivering).min() {
+            Some(offset) => offset,
+            None => self
+                .shard_state
+                .get(&shard_no)
+                .map(|raw| raw.end_offset)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn update_checkpoint_state(&self, shard_no: u64, offset: u64) {
+        let mut state = self.shard_state.entry(shard_no).or_default();
+        state.checkpoint_offset = offset;
+        state.checkpoint_time = now_second();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_base::tools::now_second;
+    use metadata_struct::adapter::read_config::ReadConfig;
+    use metadata_struct::adapter::record::Record;
+    use storage_adapter::memory::MemoryStorageAdapter;
+
+    use crate::build::build_delay_queue;
+    use crate::DelayMessageManager;
+
+    async fn rebuild(
+        namespace: &str,
+        storage_adapter: &Arc<MemoryStorageAdapter>,
+    ) -> Arc<DelayMessageManager<MemoryStorageAdapter>> {
+        let delay_message_manager = Arc::new(DelayMessageManager::new(
+            namespace.to_string(),
+            1,
+            storage_adapter.clone(),
+        ));
+        delay_message_manager.init().await.unwrap();
+        build_delay_queue(
+            storage_adapter.clone(),
+            delay_message_manager.clone(),
+            namespace.to_string(),
+            0,
+            delay_message_manager.get_delay_message_shard_name(0),
+            ReadConfig {
+                max_record_num: 100,
+                max_size: 1024 * 1024 * 1024,
+            },
+        )
+        .await;
+        delay_message_manager
+    }
+
+    #[tokio::test]
+    async fn checkpoint_rebuild_test() {
+        let namespace = "test";
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let delay_message_manager = rebuild(namespace, &storage_adapter).await;
+        assert_eq!(delay_message_manager.checkpoint(0).await.unwrap(), 0);
+
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let mut record = Record::build_str(format!("data{}", i));
+            record.delay_timestamp = now_second() + 100;
+            let id = delay_message_manager
+                .send_delay_message("topic-1", "/a/b", "c1", record)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        // the checkpoint stays at the oldest waiting message
+        delay_message_manager
+            .cancel_delay_message(&ids[1])
+            .await
+            .unwrap();
+        assert_eq!(delay_message_manager.checkpoint(0).await.unwrap(), 0);
+
+        delay_message_manager
+            .cancel_delay_message(&ids[0])
+            .await
+            .unwrap();
+        assert_eq!(delay_message_manager.checkpoint(0).await.unwrap(), 2);
+        assert_eq!(delay_message_manager.read_checkpoint(0).await.unwrap(), 2);
+
+        // the rebuild after a restart starts from the checkpoint
+        let delay_message_manager = rebuild(namespace, &storage_adapter).await;
+        let results = delay_message_manager.list_delay_message();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), ids[2]);
+        assert_eq!(results[0].target_shard, "topic-1");
+
+        // without waiting messages the checkpoint moves to the end of the shard
+        delay_message_manager
+            .cancel_delay_message(&ids[2])
+            .await
+            .unwrap();
+        assert_eq!(delay_message_manager.checkpoint(0).await.unwrap(), 5);
+        let delay_message_manager = rebuild(namespace, &storage_adapter).await;
+        assert!(delay_message_manager.list_delay_message().is_empty());
+    }
+}
@@ -14,9 +14,14 @@
 
 use build::build_delay_queue;
 use common_base::error::common::CommonError;
+use common_base::tools::now_second;
 use dashmap::DashMap;
 use log::{debug, info};
-use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
+use metadata_struct::adapter::{
+    read_config::ReadConfig,
+    record::{Header, Record},
+};
+use pop::read_offset_data;
 use std::{
     sync::{atomic::AtomicU64, Arc},
     time::Duration,
@@ -24,19 +29,55 @@
 use storage_adapter::storage::{ShardInfo, StorageAdapter};
 use tokio::{
     select,
-    sync::broadcast,
+    sync::{broadcast, Mutex},
     time::{sleep, Instant},
 };
-use tokio_util::time::DelayQueue;
+use tokio_util::time::{delay_queue::Key, DelayQueue};
 
 pub mod build;
+pub mod cancel;
+pub mod checkpoint;
+pub mod metrics;
 pub mod pop;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Default)]
 pub struct DelayMessageRecord {
-    shard_name: String,
-    offset: u64,
-    delay_timestamp: u64,
+    pub shard_no: u64,
+    pub shard_name: String,
+    pub offset: u64,
+    pub topic: String,
+    pub client_id: String,
+    pub target_shard: String,
+    pub delay_timestamp: u64,
+    pub create_time: u64,
+    // The delivery was started before the restart, so the message may already be stored in
+    // the target shard.
+    pub delivering: bool,
+    // When the first delivery was started. The records the target shard got before are not
+    // read to find the message.
+    pub deliver_time: u64,
+}
+
+impl DelayMessageRecord {
+    pub fn build(shard_no: u64, shard_name: String, offset: u64, record: &Record) -> Self {
+        DelayMessageRecord {
+            shard_no,
+            shard_name,
+            offset,
+            topic: record_header(record, DELAY_MESSAGE_HEADER_TOPIC).unwrap_or_default(),
+            client_id: record_header(record, DELAY_MESSAGE_HEADER_CLIENT_ID).unwrap_or_default(),
+            target_shard: record_header(record, DELAY_MESSAGE_HEADER_TARGET_SHARD)
+                .unwrap_or_default(),
+            delay_timestamp: record.delay_timestamp,
+            create_time: record.timestamp,
+            delivering: false,
+            deliver_time: 0,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        delay_message_id(self.shard_no, self.offset)
+    }
 }
 
 pub struct DelayMessageManager<S> {
@@ -46,9 +87,34 @@ pub struct DelayMessageManager<S> {
     incr_no: AtomicU64,
     delay_queue_list: DashMap<u64, DelayQueue<DelayMessageRecord>>,
     delay_queue_pop_thread: DashMap<u64, broadcast::Sender<bool>>,
+    // The messages waiting in the delay queues by id, with the key of their queue entry.
+    // It is only changed while the delay queue of the shard is locked.
+    delay_message_index: DashMap<String, (DelayMessageRecord, Key)>,
+    // The messages taken from the delay queues that are being written to their target shard.
+    delay_message_delivering: DashMap<String, DelayMessageRecord>,
+    shard_state: DashMap<u64, ShardState>,
+    // Held while a message is written to the shard and added to the queue, and while the
+    // checkpoint of the shard is taken, so the checkpoint never passes a message in between.
+    shard_write_lock: DashMap<u64, Arc<Mutex<()>>>,
+}
+
+#[derive(Default, Clone)]
+struct ShardState {
+    // The offset after the last record written or read by this broker.
+    end_offset: u64,
+    // The offset of the first message written by this broker, the rebuild stops there.
+    first_write_offset: Option<u64>,
+    rebuilt: bool,
+    checkpoint_offset: u64,
+    checkpoint_time: u64,
 }
 
 const DELAY_MESSAGE_SHARD_NAME_PREFIX: &str = "$delay-message-shard-";
+const DELAY_MESSAGE_HEADER_PREFIX: &str = "delay_";
+const DELAY_MESSAGE_HEADER_TOPIC: &str = "delay_topic";
+const DELAY_MESSAGE_HEADER_CLIENT_ID: &str = "delay_client_id";
+const DELAY_MESSAGE_HEADER_TARGET_SHARD: &str = "delay_target_shard";
+
 impl<S> DelayMessageManager<S>
 where
     S: StorageAdapter + Sync + Send + 'static + Clone,
@@ -61,6 +127,10 @@ pub fn new(namespace: String, shard_num: u64, message_storage_adapter: Arc<S>) -
             incr_no: AtomicU64::new(0),
             delay_queue_list: DashMap::with_capacity(2),
             delay_queue_pop_thread: DashMap::with_capacity(2),
+            delay_message_index: DashMap::with_capacity(8),
+            delay_message_delivering: DashMap::with_capacity(8),
+            shard_state: DashMap::with_capacity(2),
+            shard_write_lock: DashMap::with_capacity(2),
         }
     }
 
@@ -100,23 +170,80 @@ async fn init_delay_queue(&self) -> Result<(), CommonError> {
         Ok(())
     }
 
-    pub async fn send_delay_message(&self, data: Record) -> Result<(), CommonError> {
+    // Stores a message that is written to the target shard at data.delay_timestamp, and returns
+    // the id of the delay message. The topic and client id are only used to find the message.
+    pub async fn send_delay_message(
+        &self,
+        target_shard: &str,
+        topic: &str,
+        client_id: &str,
+        mut data: Record,
+    ) -> Result<String, CommonError> {
+        data.header.push(Header {
+            name: DELAY_MESSAGE_HEADER_TARGET_SHARD.to_string(),
+            value: target_shard.to_string(),
+        });
+        data.header.push(Header {
+            name: DELAY_MESSAGE_HEADER_TOPIC.to_string(),
+            value: topic.to_string(),
+        });
+        data.header.push(Header {
+            name: DELAY_MESSAGE_HEADER_CLIENT_ID.to_string(),
+            value: client_id.to_string(),
+        });
+
         let shard_no = self.get_target_shard_no();
         let namespace = self.namespace.clone();
         let shard_name = self.get_delay_message_shard_name(shard_no);
+        let write_lock = self.shard_write_lock(shard_no);
+        let _guard = write_lock.lock().await;
         let offset = self
             .message_storage_adapter
             .write(namespace, shard_name.clone(), data.clone())
             .await?;
+        self.shard_state
+            .entry(shard_no)
+            .or_default()
+            .first_write_offset
+            .get_or_insert(offset);
+        self.update_shard_end_offset(shard_no, offset + 1);
 
-        let delay_message_record = DelayMessageRecord {
-            shard_name,
-            offset,
-            delay_timestamp: data.delay_timestamp,
-        };
+        let delay_message_record = DelayMessageRecord::build(shard_no, shard_name, offset, &data);
+        let id = delay_message_record.id();
         self.send_to_delay_queue(shard_no, delay_message_record)
             .await?;
-        Ok(())
+        Ok(id)
+    }
+
+    // Lists the messages waiting in the delay queues, the earliest first.
+    pub fn list_delay_message(&self) -> Vec<DelayMessageRecord> {
+        let mut results: Vec<DelayMessageRecord> = self
+            .delay_message_index
+            .iter()
+            .map(|raw| raw.value().0.clone())
+            .collect();
+        results.sort_by_key(|raw| (raw.delay_timestamp, raw.shard_no, raw.offset));
+        results
+    }
+
+    pub fn get_delay_message(&self, id: &str) -> Option<DelayMessageRecord> {
+        self.delay_message_index
+            .get(id)
+            .map(|raw| raw.value().0.clone())
+    }
+
+    // Reads the stored record of a delay message.
+    pub async fn read_delay_message(
+        &self,
+        delay_message_record: &DelayMessageRecord,
+    ) -> Result<Option<Record>, CommonError> {
+        read_offset_data(
+            &self.message_storage_adapter,
+            &self.namespace,
+            &delay_message_record.shard_name,
+            delay_message_record.offset,
+        )
+        .await
     }
 
     fn add_delay_queue_pop_thread(&self, shard_no: u64, stop_send: broadcast::Sender<bool>) {
@@ -138,14 +265,36 @@ async fn send_to_delay_queue(
         delay_message_record: DelayMessageRecord,
     ) -> Result<(), CommonError> {
         if let Some(mut delay_queue) = self.delay_queue_list.get_mut(&shard_no) {
-            delay_queue.insert_at(
+            let id = delay_message_record.id();
+            // The rebuild of the queue may read a message that was already added when it was sent.
+            if self.delay_message_index.contains_key(&id) {
+                return Ok(());
+            }
+            let delay = delay_message_record
+                .delay_timestamp
+                .saturating_sub(now_second());
+            let key = delay_queue.insert_at(
                 delay_message_record.clone(),
-                Instant::now() + Duration::from_secs(delay_message_record.delay_timestamp),
+                Instant::now() + Duration::from_secs(delay),
             );
+            self.delay_message_index
+                .insert(id, (delay_message_record, key));
         }
         Ok(())
     }
 
+    fn shard_write_lock(&self, shard_no: u64) -> Arc<Mutex<()>> {
+        self.shard_write_lock
+            .entry(shard_no)
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone()
+    }
+
+    fn update_shard_end_offset(&self, shard_no: u64, end_offset: u64) {
+        let mut state = self.shard_state.entry(shard_no).or_default();
+        state.end_offset = state.end_offset.max(end_offset);
+    }
+
     fn get_target_shard_no(&self) -> u64 {
         self.incr_no
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
@@ -157,6 +306,23 @@ fn get_delay_message_shard_name(&self, no: u64) -> String {
     }
 }
 
+pub fn delay_message_id(shard_no: u64, offset: u64) -> String {
+    format!("{}-{}", shard_no, offset)
+}
+
+pub fn parse_delay_message_id(id: &str) -> Option<(u64, u64)> {
+    let (shard_no, offset) = id.split_once('-')?;
+    Some((shard_no.parse().ok()?, offset.parse().ok()?))
+}
+
+fn record_header(record: &Record, name: &str) -> Option<String> {
+    record
+        .header
+        .iter()
+        .find(|header| header.name == name)
+        .map(|header| header.value.clone())
+}
+
 pub async fn start_build_delay_queue<S>(
     namespace: String,
     delay_message_manager: Arc<DelayMessageManager<S>>,
@@ -223,6 +389,7 @@ pub async fn start_delay_message_pop<S>(
                         &new_delay_message_manager,
                         shard_no,
                     ) => {
+                        new_delay_message_manager.try_checkpoint(shard_no).await;
                         sleep(Duration::from_millis(100)).await;
                     }
                 }
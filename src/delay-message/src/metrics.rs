@@ -0,0 +1,103 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct DelayShardLabels {
+    shard_name: String,
+}
+
+common_base::register_counter_metric!(
+    DELAY_MESSAGE_REBUILD_READ_COUNTER,
+    "delay_message_rebuild_read",
+    "The number of records read from the delay shard while rebuilding the delay queue.",
+    DelayShardLabels
+);
+
+common_base::register_counter_metric!(
+    DELAY_MESSAGE_REBUILD_RESTORED_COUNTER,
+    "delay_message_rebuild_restored",
+    "The number of delay messages restored into the delay queue by the rebuild.",
+    DelayShardLabels
+);
+
+common_base::register_gauge_metric!(
+    DELAY_MESSAGE_REBUILD_OFFSET,
+    "delay_message_rebuild_offset",
+    "The offset of the delay shard the rebuild of the delay queue has reached.",
+    DelayShardLabels
+);
+
+common_base::register_gauge_metric!(
+    DELAY_MESSAGE_REBUILD_FINISHED,
+    "delay_message_rebuild_finished",
+    "Whether the rebuild of the delay queue of the shard has finished (1) or not (0).",
+    DelayShardLabels
+);
+
+common_base::register_gauge_metric!(
+    DELAY_MESSAGE_CHECKPOINT_OFFSET,
+    "delay_message_checkpoint_offset",
+    "The offset of the delay shard below which all messages have been delivered or cancelled.",
+    DelayShardLabels
+);
+
+pub fn incr_rebuild_read_counter(shard_name: &str) {
+    let labels = DelayShardLabels {
+        shard_name: shard_name.to_string(),
+    };
+    common_base::counter_metric_inc!(DELAY_MESSAGE_REBUILD_READ_COUNTER, labels)
+}
+
+pub fn incr_rebuild_restored_counter(shard_name: &str) {
+    let labels = DelayShardLabels {
+        shard_name: shard_name.to_string(),
+    };
+    common_base::counter_metric_inc!(DELAY_MESSAGE_REBUILD_RESTORED_COUNTER, labels)
+}
+
+pub fn record_rebuild_offset(shard_name: &str, offset: u64) {
+    set_gauge(&DELAY_MESSAGE_REBUILD_OFFSET, shard_name, offset as i64);
+}
+
+pub fn record_rebuild_finished(shard_name: &str, finished: bool) {
+    set_gauge(&DELAY_MESSAGE_REBUILD_FINISHED, shard_name, finished as i64);
+}
+
+pub fn record_checkpoint_offset(shard_name: &str, offset: u64) {
+    set_gauge(&DELAY_MESSAGE_CHECKPOINT_OFFSET, shard_name, offset as i64);
+}
+
+pub fn get_checkpoint_offset(shard_name: &str) -> i64 {
+    let labels = DelayShardLabels {
+        shard_name: shard_name.to_string(),
+    };
+    DELAY_MESSAGE_CHECKPOINT_OFFSET
+        .read()
+        .unwrap()
+        .get_or_create(&labels)
+        .get()
+}
+
+fn set_gauge(
+    family: &common_base::metrics::registry::FamilyGauge<DelayShardLabels>,
+    shard_name: &str,
+    value: i64,
+) {
+    let labels = DelayShardLabels {
+        shard_name: shard_name.to_string(),
+    };
+    family.write().unwrap().get_or_create(&labels).set(value);
+}
@@ -14,13 +14,22 @@
 
 use std::{sync::Arc, time::Duration};
 
-use crate::DelayMessageManager;
+use crate::{record_header, DelayMessageManager, DelayMessageRecord, DELAY_MESSAGE_HEADER_PREFIX};
 use common_base::error::common::CommonError;
-use futures::StreamExt;
+use common_base::tools::now_second;
+use futures::{FutureExt, StreamExt};
 use log::error;
-use metadata_struct::adapter::{read_config::ReadConfig, record::Record};
+use metadata_struct::adapter::{
+    read_config::ReadConfig,
+    record::{Header, Record},
+};
 use storage_adapter::storage::StorageAdapter;
 
+// Before a delay message is written to its target shard, a record with this header is written
+// to its delay shard. A message found with it by the rebuild after a restart may already be
+// in the target shard, which is checked by its key before it is written again.
+const DELAY_MESSAGE_HEADER_DELIVER_OFFSET: &str = "delay_deliver_offset";
+
 pub async fn pop_delay_queue<S>(
     namespace: &str,
     message_storage_adapter: &Arc<S>,
@@ -29,68 +38,214 @@ pub async fn pop_delay_queue<S>(
 ) where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
+    // The queue is drained without waiting, so the lock of the queue is not held across an
+    // await and the queue can be changed while nothing is due.
     if let Some(mut delay_queue) = delay_message_manager.delay_queue_list.get_mut(&shard_no) {
-        while let Some(expired) = delay_queue.next().await {
-            let delay_message = expired.into_inner();
+        while let Some(Some(expired)) = delay_queue.next().now_or_never() {
+            let mut delay_message = expired.into_inner();
+            let id = delay_message.id();
+            // The message is delivering before it leaves the queue, so the checkpoint of the
+            // shard never passes it.
+            if let Some(raw) = delay_message_manager.delay_message_index.get(&id) {
+                delay_message = raw.value().0.clone();
+            }
+            delay_message_manager
+                .delay_message_delivering
+                .insert(id.clone(), delay_message.clone());
+            delay_message_manager.delay_message_index.remove(&id);
+
             let raw_message_storage_adapter = message_storage_adapter.clone();
+            let raw_delay_message_manager = delay_message_manager.clone();
             let raw_namespace = namespace.to_owned();
             tokio::spawn(async move {
-                send_delay_message_to_shard(
+                if send_delay_message_to_shard(
                     &raw_message_storage_adapter,
                     &raw_namespace,
-                    &delay_message.shard_name,
-                    delay_message.offset,
+                    &delay_message,
                 )
-                .await;
+                .await
+                {
+                    raw_delay_message_manager
+                        .delay_message_delivering
+                        .remove(&delay_message.id());
+                }
             });
         }
     }
 }
 
+// Writes the delay message to its target shard. Returns false when it could not be written,
+// the message is then kept as delivering and written again after a restart.
 async fn send_delay_message_to_shard<S>(
     message_storage_adapter: &Arc<S>,
     namespace: &str,
-    shard_name: &str,
-    offset: u64,
-) where
+    delay_message: &DelayMessageRecord,
+) -> bool
+where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
+    let shard_name = &delay_message.shard_name;
+    let offset = delay_message.offset;
+    if delay_message.target_shard.is_empty() {
+        error!(
+            "Delay message {} has no target shard and is dropped",
+            delay_message.id()
+        );
+        return true;
+    }
+
+    let key = delay_message_target_key(&delay_message.id());
+    let mut deliver_marked = false;
+    let mut check_target = delay_message.delivering;
+    let mut deliver_time = delay_message.deliver_time;
     let mut times = 0;
     loop {
         if times > 1000 {
             error!("send_delay_message_to_shard failed, times: {},namespace:{},shard_name:{},offset:{}", times, namespace, shard_name, offset);
-            break;
+            return false;
         }
 
+        if times > 0 {
+            tokio::time::sleep(Duration::from_millis(1000)).await;
+        }
         times += 1;
+
         let record =
             match read_offset_data(message_storage_adapter, namespace, shard_name, offset).await {
                 Ok(Some(record)) => record,
-                Ok(None) => break,
+                Ok(None) => return true,
                 Err(e) => {
                     error!("read_offset_data failed, err: {:?}", e);
-                    tokio::time::sleep(Duration::from_millis(1000)).await;
                     continue;
                 }
             };
 
+        if !deliver_marked {
+            let deliver_record = build_deliver_record(offset);
+            let timestamp = deliver_record.timestamp;
+            if let Err(e) = message_storage_adapter
+                .write(namespace.to_owned(), shard_name.to_owned(), deliver_record)
+                .await
+            {
+                error!("write failed, err: {:?}", e);
+                continue;
+            }
+            deliver_marked = true;
+            if !delay_message.delivering {
+                deliver_time = timestamp;
+            }
+        }
+
+        if check_target {
+            match target_contains_key(
+                message_storage_adapter,
+                namespace,
+                &delay_message.target_shard,
+                deliver_time,
+                &key,
+            )
+            .await
+            {
+                Ok(true) => return true,
+                Ok(false) => {}
+                Err(e) => {
+                    error!(
+                        "Finding the delay message in its target shard failed, err: {:?}",
+                        e
+                    );
+                    continue;
+                }
+            }
+        }
+
         match message_storage_adapter
-            .write(namespace.to_owned(), shard_name.to_owned(), record)
+            .write(
+                namespace.to_owned(),
+                delay_message.target_shard.clone(),
+                build_target_record(record, key.clone()),
+            )
             .await
         {
-            Ok(_) => {
-                break;
-            }
+            Ok(_) => return true,
             Err(e) => {
                 error!("write failed, err: {:?}", e);
-                tokio::time::sleep(Duration::from_millis(1000)).await;
-                continue;
+                // The write may have been stored even though it failed.
+                check_target = true;
             }
         }
     }
 }
 
-async fn read_offset_data<S>(
+// Whether the delay message was already written to its target shard. It is written after its
+// delivery was started, so the key is only looked up from the offset of that time.
+async fn target_contains_key<S>(
+    message_storage_adapter: &Arc<S>,
+    namespace: &str,
+    target_shard: &str,
+    deliver_time: u64,
+    key: &str,
+) -> Result<bool, CommonError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let Some(start) = message_storage_adapter
+        .get_offset_by_timestamp(namespace.to_owned(), target_shard.to_owned(), deliver_time)
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    let results = message_storage_adapter
+        .read_by_key(
+            namespace.to_owned(),
+            target_shard.to_owned(),
+            start.offset,
+            key.to_owned(),
+            ReadConfig {
+                max_record_num: u32::MAX as u64,
+                max_size: 1024 * 1024 * 1024,
+            },
+        )
+        .await?;
+    Ok(!results.is_empty())
+}
+
+// The key of a delay message in its target shard.
+pub fn delay_message_target_key(id: &str) -> String {
+    format!("$delay-message/{}", id)
+}
+
+fn build_target_record(mut record: Record, key: String) -> Record {
+    record.offset = None;
+    // the message is published when it is delivered, which is also where the lookup of its
+    // key starts
+    record.timestamp = now_second();
+    record.delay_timestamp = 0;
+    record
+        .header
+        .retain(|header| !header.name.starts_with(DELAY_MESSAGE_HEADER_PREFIX));
+    record.set_key(key);
+    record
+}
+
+pub fn build_deliver_record(offset: u64) -> Record {
+    let mut record = Record::build_byte(Vec::new());
+    record.set_header(vec![Header {
+        name: DELAY_MESSAGE_HEADER_DELIVER_OFFSET.to_string(),
+        value: offset.to_string(),
+    }]);
+    record
+}
+
+// Returns the offset of the message whose delivery was started when the record is a
+// delivery marker.
+pub fn delivering_offset(record: &Record) -> Option<u64> {
+    record_header(record, DELAY_MESSAGE_HEADER_DELIVER_OFFSET)?
+        .parse()
+        .ok()
+}
+
+pub(crate) async fn read_offset_data<S>(
     message_storage_adapter: &Arc<S>,
     namespace: &str,
     shard_name: &str,
@@ -122,6 +277,115 @@ async fn read_offset_data<S>(
 
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
+    use common_base::tools::now_second;
+    use metadata_struct::adapter::read_config::ReadConfig;
+    use metadata_struct::adapter::record::Record;
+    use storage_adapter::memory::MemoryStorageAdapter;
+    use storage_adapter::storage::StorageAdapter;
+
+    use super::{
+        build_deliver_record, delay_message_target_key, delivering_offset,
+        send_delay_message_to_shard,
+    };
+    use crate::build::build_delay_queue;
+    use crate::DelayMessageManager;
+
     #[test]
-    pub fn read_offset_data_test() {}
+    fn deliver_record_test() {
+        let record = build_deliver_record(1024);
+        assert_eq!(delivering_offset(&record), Some(1024));
+        assert_eq!(record.delay_timestamp, 0);
+
+        let record = Record::build_str("data".to_string());
+        assert_eq!(delivering_offset(&record), None);
+    }
+
+    #[tokio::test]
+    async fn deliver_once_after_restart_test() {
+        let namespace = "test".to_string();
+        let target_shard = "topic-1".to_string();
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let delay_message_manager =
+            DelayMessageManager::new(namespace.clone(), 1, storage_adapter.clone());
+        delay_message_manager.init().await.unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..2 {
+            let mut record = Record::build_str(format!("data{}", i));
+            record.delay_timestamp = now_second() + 1;
+            let id = delay_message_manager
+                .send_delay_message(&target_shard, "/a/b", "c1", record)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        // the broker stopped after both deliveries were started, but only the first one was
+        // written to the target shard
+        for (offset, id) in ids.iter().enumerate() {
+            storage_adapter
+                .write(
+                    namespace.clone(),
+                    delay_message_manager.get_delay_message_shard_name(0),
+                    build_deliver_record(offset as u64),
+                )
+                .await
+                .unwrap();
+            if offset == 0 {
+                let mut record = Record::build_str("data0".to_string());
+                record.set_key(delay_message_target_key(id));
+                storage_adapter
+                    .write(namespace.clone(), target_shard.clone(), record)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let delay_message_manager = Arc::new(DelayMessageManager::new(
+            namespace.clone(),
+            1,
+            storage_adapter.clone(),
+        ));
+        delay_message_manager.init().await.unwrap();
+        build_delay_queue(
+            storage_adapter.clone(),
+            delay_message_manager.clone(),
+            namespace.clone(),
+            0,
+            delay_message_manager.get_delay_message_shard_name(0),
+            ReadConfig {
+                max_record_num: 100,
+                max_size: 1024 * 1024 * 1024,
+            },
+        )
+        .await;
+
+        for id in ids.iter() {
+            let delay_message = delay_message_manager.get_delay_message(id).unwrap();
+            assert!(delay_message.delivering);
+            assert!(
+                send_delay_message_to_shard(&storage_adapter, &namespace, &delay_message).await
+            );
+        }
+
+        let results = storage_adapter
+            .read_by_offset(
+                namespace.clone(),
+                target_shard,
+                0,
+                ReadConfig {
+                    max_record_num: 100,
+                    max_size: 1024 * 1024 * 1024,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].key, delay_message_target_key(&ids[0]));
+        assert_eq!(results[1].key, delay_message_target_key(&ids[1]));
+        assert_eq!(results[1].delay_timestamp, 0);
+        assert!(results[1].header.is_empty());
+    }
 }
@@ -14,24 +14,40 @@
 
 use common_base::error::common::CommonError;
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    ClusterStatusReply, ClusterStatusRequest, CreateAclReply, CreateAclRequest,
-    CreateBlacklistReply, CreateBlacklistRequest, CreateTopicRewriteRuleReply,
-    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, DeleteAclReply,
-    DeleteAclRequest, DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest,
-    DeleteBlacklistReply, DeleteBlacklistRequest, DeleteTopicRewriteRuleReply,
-    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, EnableFlappingDetectReply,
+    CancelDelayMessageReply, CancelDelayMessageRequest, ClusterStatusReply, ClusterStatusRequest,
+    CreateAclReply, CreateAclRequest, CreateBlacklistReply, CreateBlacklistRequest,
+    CreateScheduledMessageReply, CreateScheduledMessageRequest, CreateTopicRewriteRuleReply,
+    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply,
+    DeleteAutoSubscribeRuleRequest, DeleteBlacklistReply, DeleteBlacklistRequest,
+    DeleteScheduledMessageReply, DeleteScheduledMessageRequest, DeleteTopicRewriteRuleReply,
+    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, DeleteWasmPluginReply,
+    DeleteWasmPluginRequest, DrainNodeReply, DrainNodeRequest, EnableFlappingDetectReply,
     EnableFlappingDetectRequest, EnableSlowSubScribeReply, EnableSlowSubscribeRequest,
-    ListAclReply, ListAclRequest, ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest,
-    ListBlacklistReply, ListBlacklistRequest, ListConnectionReply, ListConnectionRequest,
+    GetDelayMessageReply, GetDelayMessageRequest, ListAclReply, ListAclRequest,
+    ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest, ListBlacklistReply,
+    ListBlacklistRequest, ListConnectionReply, ListConnectionRequest, ListDeadLetterMessageReply,
+    ListDeadLetterMessageRequest, ListDelayMessageReply, ListDelayMessageRequest,
+    ListListenerReply, ListListenerRequest, ListScheduledMessageReply, ListScheduledMessageRequest,
     ListSlowSubscribeReply, ListSlowSubscribeRequest, ListTopicReply, ListTopicRequest,
-    ListUserReply, ListUserRequest, MqttBindSchemaReply, MqttBindSchemaRequest,
-    MqttCreateConnectorReply, MqttCreateConnectorRequest, MqttCreateSchemaReply,
-    MqttCreateSchemaRequest, MqttDeleteConnectorReply, MqttDeleteConnectorRequest,
-    MqttDeleteSchemaReply, MqttDeleteSchemaRequest, MqttListBindSchemaReply,
-    MqttListBindSchemaRequest, MqttListConnectorReply, MqttListConnectorRequest,
-    MqttListSchemaReply, MqttListSchemaRequest, MqttUnbindSchemaReply, MqttUnbindSchemaRequest,
-    MqttUpdateConnectorReply, MqttUpdateConnectorRequest, MqttUpdateSchemaReply,
-    MqttUpdateSchemaRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
+    ListUserReply, ListUserRequest, ListWasmPluginReply, ListWasmPluginRequest,
+    MqttBindSchemaReply, MqttBindSchemaRequest, MqttCreateConnectorReply,
+    MqttCreateConnectorRequest, MqttCreateSchemaReply, MqttCreateSchemaRequest,
+    MqttDeleteConnectorReply, MqttDeleteConnectorRequest, MqttDeleteSchemaReply,
+    MqttDeleteSchemaRequest, MqttListBindSchemaReply, MqttListBindSchemaRequest,
+    MqttListConnectorReply, MqttListConnectorRequest, MqttListSchemaReply, MqttListSchemaRequest,
+    MqttUnbindSchemaReply, MqttUnbindSchemaRequest, MqttUpdateConnectorReply,
+    MqttUpdateConnectorRequest, MqttUpdateSchemaReply, MqttUpdateSchemaRequest,
+    RedriveDeadLetterMessageReply, RedriveDeadLetterMessageRequest, ReloadListenerCertReply,
+    ReloadListenerCertRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
+    StartListenerReply, StartListenerRequest, StopListenerReply, StopListenerRequest,
+};
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CreateRuleReply, CreateRuleRequest, CreateTenantReply, CreateTenantRequest, DeleteRuleReply,
+    DeleteRuleRequest, DeleteTenantReply, DeleteTenantRequest, ListRuleReply, ListRuleRequest,
+    ListTenantReply, ListTenantRequest, MqttPauseConnectorReply, MqttPauseConnectorRequest,
+    MqttResetConnectorOffsetReply, MqttResetConnectorOffsetRequest, MqttResumeConnectorReply,
+    MqttResumeConnectorRequest, UndrainNodeReply, UndrainNodeRequest,
 };
 
 use crate::pool::ClientPool;
@@ -56,6 +72,94 @@ pub async fn $fn_name(
     ClusterStatus
 );
 
+// ------ tenant -------
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_tenant,
+    ListTenantRequest,
+    ListTenantReply,
+    ListTenant
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_create_tenant,
+    CreateTenantRequest,
+    CreateTenantReply,
+    CreateTenant
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_delete_tenant,
+    DeleteTenantRequest,
+    DeleteTenantReply,
+    DeleteTenant
+);
+
+// ------ rule engine -------
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_rule,
+    ListRuleRequest,
+    ListRuleReply,
+    ListRule
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_create_rule,
+    CreateRuleRequest,
+    CreateRuleReply,
+    CreateRule
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_delete_rule,
+    DeleteRuleRequest,
+    DeleteRuleReply,
+    DeleteRule
+);
+
+// ------ wasm plugin -------
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_wasm_plugin,
+    ListWasmPluginRequest,
+    ListWasmPluginReply,
+    ListWasmPlugin
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_create_wasm_plugin,
+    CreateWasmPluginRequest,
+    CreateWasmPluginReply,
+    CreateWasmPlugin
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_delete_wasm_plugin,
+    DeleteWasmPluginRequest,
+    DeleteWasmPluginReply,
+    DeleteWasmPlugin
+);
+
+// ------ scheduled message -------
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_scheduled_message,
+    ListScheduledMessageRequest,
+    ListScheduledMessageReply,
+    ListScheduledMessage
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_create_scheduled_message,
+    CreateScheduledMessageRequest,
+    CreateScheduledMessageReply,
+    CreateScheduledMessage
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_delete_scheduled_message,
+    DeleteScheduledMessageRequest,
+    DeleteScheduledMessageReply,
+    DeleteScheduledMessage
+);
+
 // ------ user -------
 generate_mqtt_admin_service_call!(
     mqtt_broker_list_user,
@@ -197,6 +301,27 @@ pub async fn $fn_name(
     MqttDeleteConnector
 );
 
+generate_mqtt_admin_service_call!(
+    mqtt_broker_pause_connector,
+    MqttPauseConnectorRequest,
+    MqttPauseConnectorReply,
+    MqttPauseConnector
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_resume_connector,
+    MqttResumeConnectorRequest,
+    MqttResumeConnectorReply,
+    MqttResumeConnector
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_reset_connector_offset,
+    MqttResetConnectorOffsetRequest,
+    MqttResetConnectorOffsetReply,
+    MqttResetConnectorOffset
+);
+
 // schema command line CRUD
 generate_mqtt_admin_service_call!(
     mqtt_broker_list_schema,
@@ -267,3 +392,84 @@ pub async fn $fn_name(
     ListAutoSubscribeRuleReply,
     ListAutoSubscribeRule
 );
+
+// --- dead letter ---
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_dead_letter_message,
+    ListDeadLetterMessageRequest,
+    ListDeadLetterMessageReply,
+    ListDeadLetterMessage
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_redrive_dead_letter_message,
+    RedriveDeadLetterMessageRequest,
+    RedriveDeadLetterMessageReply,
+    RedriveDeadLetterMessage
+);
+
+// ------ delay message -------
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_delay_message,
+    ListDelayMessageRequest,
+    ListDelayMessageReply,
+    ListDelayMessage
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_get_delay_message,
+    GetDelayMessageRequest,
+    GetDelayMessageReply,
+    GetDelayMessage
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_cancel_delay_message,
+    CancelDelayMessageRequest,
+    CancelDelayMessageReply,
+    CancelDelayMessage
+);
+
+// --- drain ---
+generate_mqtt_admin_service_call!(
+    mqtt_broker_drain_node,
+    DrainNodeRequest,
+    DrainNodeReply,
+    DrainNode
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_undrain_node,
+    UndrainNodeRequest,
+    UndrainNodeReply,
+    UndrainNode
+);
+
+// --- listener ---
+generate_mqtt_admin_service_call!(
+    mqtt_broker_list_listener,
+    ListListenerRequest,
+    ListListenerReply,
+    ListListener
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_start_listener,
+    StartListenerRequest,
+    StartListenerReply,
+    StartListener
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_stop_listener,
+    StopListenerRequest,
+    StopListenerReply,
+    StopListener
+);
+
+generate_mqtt_admin_service_call!(
+    mqtt_broker_reload_listener_cert,
+    ReloadListenerCertRequest,
+    ReloadListenerCertReply,
+    ReloadListenerCert
+);
@@ -16,12 +16,17 @@
 use mobc::Manager;
 use protocol::broker_mqtt::broker_mqtt_admin::mqtt_broker_admin_service_client::MqttBrokerAdminServiceClient;
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    ClusterStatusReply, ClusterStatusRequest, DeleteAutoSubscribeRuleReply,
-    DeleteAutoSubscribeRuleRequest, ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest,
-    MqttCreateConnectorReply, MqttCreateConnectorRequest, MqttDeleteConnectorReply,
-    MqttDeleteConnectorRequest, MqttListConnectorReply, MqttListConnectorRequest,
-    MqttUpdateConnectorReply, MqttUpdateConnectorRequest, SetAutoSubscribeRuleReply,
-    SetAutoSubscribeRuleRequest,
+    CancelDelayMessageReply, CancelDelayMessageRequest, ClusterStatusReply, ClusterStatusRequest,
+    CreateScheduledMessageReply, CreateScheduledMessageRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest,
+    DeleteScheduledMessageReply, DeleteScheduledMessageRequest, DeleteWasmPluginReply,
+    DeleteWasmPluginRequest, GetDelayMessageReply, GetDelayMessageRequest,
+    ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest, ListDelayMessageReply,
+    ListDelayMessageRequest, ListScheduledMessageReply, ListScheduledMessageRequest,
+    ListWasmPluginReply, ListWasmPluginRequest, MqttCreateConnectorReply,
+    MqttCreateConnectorRequest, MqttDeleteConnectorReply, MqttDeleteConnectorRequest,
+    MqttListConnectorReply, MqttListConnectorRequest, MqttUpdateConnectorReply,
+    MqttUpdateConnectorRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
 };
 use protocol::broker_mqtt::broker_mqtt_admin::{
     CreateAclReply, CreateAclRequest, CreateBlacklistReply, CreateBlacklistRequest,
@@ -37,6 +42,20 @@
     MqttListBindSchemaRequest, MqttListSchemaReply, MqttListSchemaRequest, MqttUnbindSchemaReply,
     MqttUnbindSchemaRequest, MqttUpdateSchemaReply, MqttUpdateSchemaRequest,
 };
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CreateRuleReply, CreateRuleRequest, CreateTenantReply, CreateTenantRequest, DeleteRuleReply,
+    DeleteRuleRequest, DeleteTenantReply, DeleteTenantRequest, DrainNodeReply, DrainNodeRequest,
+    ListDeadLetterMessageReply, ListDeadLetterMessageRequest, ListListenerReply,
+    ListListenerRequest, ListRuleReply, ListRuleRequest, ListTenantReply, ListTenantRequest,
+    RedriveDeadLetterMessageReply, RedriveDeadLetterMessageRequest, ReloadListenerCertReply,
+    ReloadListenerCertRequest, StartListenerReply, StartListenerRequest, StopListenerReply,
+    StopListenerRequest,
+};
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    MqttPauseConnectorReply, MqttPauseConnectorRequest, MqttResetConnectorOffsetReply,
+    MqttResetConnectorOffsetRequest, MqttResumeConnectorReply, MqttResumeConnectorRequest,
+    UndrainNodeReply, UndrainNodeRequest,
+};
 use tonic::transport::Channel;
 
 use crate::macros::impl_retriable_request;
@@ -247,6 +266,30 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     mqtt_broker_delete_connector
 );
 
+impl_retriable_request!(
+    MqttPauseConnectorRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    MqttPauseConnectorReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_pause_connector
+);
+
+impl_retriable_request!(
+    MqttResumeConnectorRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    MqttResumeConnectorReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_resume_connector
+);
+
+impl_retriable_request!(
+    MqttResetConnectorOffsetRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    MqttResetConnectorOffsetReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_reset_connector_offset
+);
+
 // schema command line CRUD
 impl_retriable_request!(
     MqttListSchemaRequest,
@@ -327,3 +370,187 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     mqtt_broker_admin_services_client,
     mqtt_broker_delete_auto_subscribe_rule
 );
+
+impl_retriable_request!(
+    ListDeadLetterMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListDeadLetterMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_dead_letter_message
+);
+
+impl_retriable_request!(
+    RedriveDeadLetterMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    RedriveDeadLetterMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_redrive_dead_letter_message
+);
+
+impl_retriable_request!(
+    ListDelayMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListDelayMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_delay_message
+);
+
+impl_retriable_request!(
+    GetDelayMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    GetDelayMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_get_delay_message
+);
+
+impl_retriable_request!(
+    CancelDelayMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    CancelDelayMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_cancel_delay_message
+);
+
+impl_retriable_request!(
+    DrainNodeRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DrainNodeReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_drain_node
+);
+
+impl_retriable_request!(
+    UndrainNodeRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    UndrainNodeReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_undrain_node
+);
+
+impl_retriable_request!(
+    ListListenerRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListListenerReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_listener
+);
+
+impl_retriable_request!(
+    StartListenerRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    StartListenerReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_start_listener
+);
+
+impl_retriable_request!(
+    StopListenerRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    StopListenerReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_stop_listener
+);
+
+impl_retriable_request!(
+    ReloadListenerCertRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ReloadListenerCertReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_reload_listener_cert
+);
+
+impl_retriable_request!(
+    ListTenantRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListTenantReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_tenant
+);
+
+impl_retriable_request!(
+    CreateTenantRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    CreateTenantReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_create_tenant
+);
+
+impl_retriable_request!(
+    DeleteTenantRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DeleteTenantReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_delete_tenant
+);
+
+impl_retriable_request!(
+    ListRuleRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListRuleReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_rule
+);
+
+impl_retriable_request!(
+    CreateRuleRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    CreateRuleReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_create_rule
+);
+
+impl_retriable_request!(
+    DeleteRuleRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DeleteRuleReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_delete_rule
+);
+
+impl_retriable_request!(
+    ListWasmPluginRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListWasmPluginReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_wasm_plugin
+);
+
+impl_retriable_request!(
+    CreateWasmPluginRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    CreateWasmPluginReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_create_wasm_plugin
+);
+
+impl_retriable_request!(
+    DeleteWasmPluginRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DeleteWasmPluginReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_delete_wasm_plugin
+);
+
+impl_retriable_request!(
+    ListScheduledMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    ListScheduledMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_list_scheduled_message
+);
+
+impl_retriable_request!(
+    CreateScheduledMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    CreateScheduledMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_create_scheduled_message
+);
+
+impl_retriable_request!(
+    DeleteScheduledMessageRequest,
+    MqttBrokerAdminServiceClient<Channel>,
+    DeleteScheduledMessageReply,
+    mqtt_broker_admin_services_client,
+    mqtt_broker_delete_scheduled_message
+);
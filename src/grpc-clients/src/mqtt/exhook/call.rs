@@ -0,0 +1,105 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use protocol::broker_mqtt::broker_mqtt_exhook::{
+    ClientAuthenticateRequest, ClientConnectRequest, ClientConnectedRequest,
+    ClientDisconnectedRequest, ClientSubscribeRequest, ClientUnsubscribeRequest, EmptyReply,
+    HookReply, MessageAckedRequest, MessageDeliveredRequest, MessagePublishRequest,
+    ProviderLoadedReply, ProviderLoadedRequest,
+};
+
+use crate::pool::ClientPool;
+
+macro_rules! generate_mqtt_exhook_service_call {
+    ($fn_name:ident, $req_ty:ty, $rep_ty:ty, $variant:ident) => {
+        pub async fn $fn_name(
+            client_pool: &ClientPool,
+            addrs: &[impl AsRef<str>],
+            request: $req_ty,
+        ) -> Result<$rep_ty, CommonError> {
+            $crate::utils::retry_call(client_pool, addrs, request).await
+        }
+    };
+}
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_provider_loaded,
+    ProviderLoadedRequest,
+    ProviderLoadedReply,
+    ProviderLoaded
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_client_connect,
+    ClientConnectRequest,
+    HookReply,
+    ClientConnect
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_client_authenticate,
+    ClientAuthenticateRequest,
+    HookReply,
+    ClientAuthenticate
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_client_connected,
+    ClientConnectedRequest,
+    EmptyReply,
+    ClientConnected
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_client_disconnected,
+    ClientDisconnectedRequest,
+    EmptyReply,
+    ClientDisconnected
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_client_subscribe,
+    ClientSubscribeRequest,
+    HookReply,
+    ClientSubscribe
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_client_unsubscribe,
+    ClientUnsubscribeRequest,
+    HookReply,
+    ClientUnsubscribe
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_message_publish,
+    MessagePublishRequest,
+    HookReply,
+    MessagePublish
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_message_delivered,
+    MessageDeliveredRequest,
+    EmptyReply,
+    MessageDelivered
+);
+
+generate_mqtt_exhook_service_call!(
+    mqtt_exhook_on_message_acked,
+    MessageAckedRequest,
+    EmptyReply,
+    MessageAcked
+);
@@ -0,0 +1,144 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use mobc::Manager;
+use protocol::broker_mqtt::broker_mqtt_exhook::hook_provider_service_client::HookProviderServiceClient;
+use protocol::broker_mqtt::broker_mqtt_exhook::{
+    ClientAuthenticateRequest, ClientConnectRequest, ClientConnectedRequest,
+    ClientDisconnectedRequest, ClientSubscribeRequest, ClientUnsubscribeRequest, EmptyReply,
+    HookReply, MessageAckedRequest, MessageDeliveredRequest, MessagePublishRequest,
+    ProviderLoadedReply, ProviderLoadedRequest,
+};
+use tonic::transport::Channel;
+
+use crate::macros::impl_retriable_request;
+
+pub mod call;
+
+#[derive(Clone)]
+pub struct MqttExHookServiceManager {
+    pub addr: String,
+}
+
+impl MqttExHookServiceManager {
+    pub fn new(addr: String) -> Self {
+        Self { addr }
+    }
+}
+
+#[tonic::async_trait]
+impl Manager for MqttExHookServiceManager {
+    type Connection = HookProviderServiceClient<Channel>;
+    type Error = CommonError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        match HookProviderServiceClient::connect(format!("http://{}", self.addr.clone())).await {
+            Ok(client) => {
+                return Ok(client);
+            }
+            Err(err) => {
+                return Err(CommonError::CommonError(format!(
+                    "{},{}",
+                    err,
+                    self.addr.clone()
+                )))
+            }
+        };
+    }
+
+    async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        Ok(conn)
+    }
+}
+
+impl_retriable_request!(
+    ProviderLoadedRequest,
+    HookProviderServiceClient<Channel>,
+    ProviderLoadedReply,
+    mqtt_exhook_services_client,
+    on_provider_loaded
+);
+
+impl_retriable_request!(
+    ClientConnectRequest,
+    HookProviderServiceClient<Channel>,
+    HookReply,
+    mqtt_exhook_services_client,
+    on_client_connect
+);
+
+impl_retriable_request!(
+    ClientAuthenticateRequest,
+    HookProviderServiceClient<Channel>,
+    HookReply,
+    mqtt_exhook_services_client,
+    on_client_authenticate
+);
+
+impl_retriable_request!(
+    ClientConnectedRequest,
+    HookProviderServiceClient<Channel>,
+    EmptyReply,
+    mqtt_exhook_services_client,
+    on_client_connected
+);
+
+impl_retriable_request!(
+    ClientDisconnectedRequest,
+    HookProviderServiceClient<Channel>,
+    EmptyReply,
+    mqtt_exhook_services_client,
+    on_client_disconnected
+);
+
+impl_retriable_request!(
+    ClientSubscribeRequest,
+    HookProviderServiceClient<Channel>,
+    HookReply,
+    mqtt_exhook_services_client,
+    on_client_subscribe
+);
+
+impl_retriable_request!(
+    ClientUnsubscribeRequest,
+    HookProviderServiceClient<Channel>,
+    HookReply,
+    mqtt_exhook_services_client,
+    on_client_unsubscribe
+);
+
+impl_retriable_request!(
+    MessagePublishRequest,
+    HookProviderServiceClient<Channel>,
+    HookReply,
+    mqtt_exhook_services_client,
+    on_message_publish
+);
+
+impl_retriable_request!(
+    MessageDeliveredRequest,
+    HookProviderServiceClient<Channel>,
+    EmptyReply,
+    mqtt_exhook_services_client,
+    on_message_delivered
+);
+
+impl_retriable_request!(
+    MessageAckedRequest,
+    HookProviderServiceClient<Channel>,
+    EmptyReply,
+    mqtt_exhook_services_client,
+    on_message_acked
+);
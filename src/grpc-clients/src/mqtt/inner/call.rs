@@ -15,7 +15,8 @@
 use common_base::error::common::CommonError;
 use protocol::broker_mqtt::broker_mqtt_inner::{
     DeleteSessionReply, DeleteSessionRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
-    UpdateMqttCacheReply, UpdateMqttCacheRequest,
+    SendScheduledMessageReply, SendScheduledMessageRequest, UpdateMqttCacheReply,
+    UpdateMqttCacheRequest,
 };
 
 use crate::pool::ClientPool;
@@ -52,3 +53,10 @@ pub async fn $fn_name(
     SendLastWillMessageReply,
     SendLastWillMessage
 );
+
+generate_mqtt_inner_service_call!(
+    send_scheduled_message,
+    SendScheduledMessageRequest,
+    SendScheduledMessageReply,
+    SendScheduledMessage
+);
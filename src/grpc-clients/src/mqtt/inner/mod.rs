@@ -17,7 +17,8 @@
 use protocol::broker_mqtt::broker_mqtt_inner::mqtt_broker_inner_service_client::MqttBrokerInnerServiceClient;
 use protocol::broker_mqtt::broker_mqtt_inner::{
     DeleteSessionReply, DeleteSessionRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
-    UpdateMqttCacheReply, UpdateMqttCacheRequest,
+    SendScheduledMessageReply, SendScheduledMessageRequest, UpdateMqttCacheReply,
+    UpdateMqttCacheRequest,
 };
 use tonic::transport::Channel;
 
@@ -83,3 +84,11 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     mqtt_broker_mqtt_services_client,
     send_last_will_message
 );
+
+impl_retriable_request!(
+    SendScheduledMessageRequest,
+    MqttBrokerInnerServiceClient<Channel>,
+    SendScheduledMessageReply,
+    mqtt_broker_mqtt_services_client,
+    send_scheduled_message
+);
@@ -16,18 +16,24 @@
 use protocol::placement_center::placement_center_mqtt::{
     ConnectorHeartbeatReply, ConnectorHeartbeatRequest, CreateAclReply, CreateAclRequest,
     CreateBlacklistReply, CreateBlacklistRequest, CreateConnectorReply, CreateConnectorRequest,
-    CreateSessionReply, CreateSessionRequest, CreateTopicReply, CreateTopicRequest,
-    CreateTopicRewriteRuleReply, CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest,
-    DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest,
-    DeleteBlacklistReply, DeleteBlacklistRequest, DeleteConnectorReply, DeleteConnectorRequest,
-    DeleteSessionReply, DeleteSessionRequest, DeleteSubscribeReply, DeleteSubscribeRequest,
-    DeleteTopicReply, DeleteTopicRequest, DeleteTopicRewriteRuleReply,
-    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, GetShareSubLeaderReply,
-    GetShareSubLeaderRequest, ListAclReply, ListAclRequest, ListAutoSubscribeRuleReply,
-    ListAutoSubscribeRuleRequest, ListBlacklistReply, ListBlacklistRequest, ListConnectorReply,
-    ListConnectorRequest, ListSessionReply, ListSessionRequest, ListSubscribeReply,
-    ListSubscribeRequest, ListTopicReply, ListTopicRequest, ListTopicRewriteRuleReply,
-    ListTopicRewriteRuleRequest, ListUserReply, ListUserRequest, SaveLastWillMessageReply,
+    CreateRuleReply, CreateRuleRequest, CreateScheduledMessageReply, CreateScheduledMessageRequest,
+    CreateSessionReply, CreateSessionRequest, CreateTenantReply, CreateTenantRequest,
+    CreateTopicReply, CreateTopicRequest, CreateTopicRewriteRuleReply,
+    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply,
+    DeleteAutoSubscribeRuleRequest, DeleteBlacklistReply, DeleteBlacklistRequest,
+    DeleteConnectorReply, DeleteConnectorRequest, DeleteRuleReply, DeleteRuleRequest,
+    DeleteScheduledMessageReply, DeleteScheduledMessageRequest, DeleteSessionReply,
+    DeleteSessionRequest, DeleteSubscribeReply, DeleteSubscribeRequest, DeleteTenantReply,
+    DeleteTenantRequest, DeleteTopicReply, DeleteTopicRequest, DeleteTopicRewriteRuleReply,
+    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, DeleteWasmPluginReply,
+    DeleteWasmPluginRequest, GetShareSubLeaderReply, GetShareSubLeaderRequest, ListAclReply,
+    ListAclRequest, ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest, ListBlacklistReply,
+    ListBlacklistRequest, ListConnectorReply, ListConnectorRequest, ListRuleReply, ListRuleRequest,
+    ListScheduledMessageReply, ListScheduledMessageRequest, ListSessionReply, ListSessionRequest,
+    ListSubscribeReply, ListSubscribeRequest, ListTenantReply, ListTenantRequest, ListTopicReply,
+    ListTopicRequest, ListTopicRewriteRuleReply, ListTopicRewriteRuleRequest, ListUserReply,
+    ListUserRequest, ListWasmPluginReply, ListWasmPluginRequest, SaveLastWillMessageReply,
     SaveLastWillMessageRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
     SetSubscribeReply, SetSubscribeRequest, SetTopicRetainMessageReply,
     SetTopicRetainMessageRequest, UpdateConnectorReply, UpdateConnectorRequest, UpdateSessionReply,
@@ -72,6 +78,24 @@ pub async fn $fn_name(
     ListUserReply,
     ListUser
 );
+generate_mqtt_service_call!(
+    placement_create_tenant,
+    CreateTenantRequest,
+    CreateTenantReply,
+    CreateTenant
+);
+generate_mqtt_service_call!(
+    placement_delete_tenant,
+    DeleteTenantRequest,
+    DeleteTenantReply,
+    DeleteTenant
+);
+generate_mqtt_service_call!(
+    placement_list_tenant,
+    ListTenantRequest,
+    ListTenantReply,
+    ListTenant
+);
 generate_mqtt_service_call!(
     placement_create_topic,
     CreateTopicRequest,
@@ -166,6 +190,63 @@ pub async fn $fn_name(
     DeleteTopicRewriteRule
 );
 
+generate_mqtt_service_call!(
+    placement_list_rule,
+    ListRuleRequest,
+    ListRuleReply,
+    ListRule
+);
+generate_mqtt_service_call!(
+    placement_create_rule,
+    CreateRuleRequest,
+    CreateRuleReply,
+    CreateRule
+);
+generate_mqtt_service_call!(
+    placement_delete_rule,
+    DeleteRuleRequest,
+    DeleteRuleReply,
+    DeleteRule
+);
+
+generate_mqtt_service_call!(
+    placement_list_wasm_plugin,
+    ListWasmPluginRequest,
+    ListWasmPluginReply,
+    ListWasmPlugin
+);
+generate_mqtt_service_call!(
+    placement_create_wasm_plugin,
+    CreateWasmPluginRequest,
+    CreateWasmPluginReply,
+    CreateWasmPlugin
+);
+generate_mqtt_service_call!(
+    placement_delete_wasm_plugin,
+    DeleteWasmPluginRequest,
+    DeleteWasmPluginReply,
+    DeleteWasmPlugin
+);
+
+generate_mqtt_service_call!(
+    placement_list_scheduled_message,
+    ListScheduledMessageRequest,
+    ListScheduledMessageReply,
+    ListScheduledMessage
+);
+generate_mqtt_service_call!(
+    placement_create_scheduled_message,
+    CreateScheduledMessageRequest,
+    CreateScheduledMessageReply,
+    CreateScheduledMessage
+);
+generate_mqtt_service_call!(
+    placement_delete_scheduled_message,
+    DeleteScheduledMessageRequest,
+    DeleteScheduledMessageReply,
+    DeleteScheduledMessage
+);
+
 generate_mqtt_service_call!(
     placement_set_subscribe,
     SetSubscribeRequest,
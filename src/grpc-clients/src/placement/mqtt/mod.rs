@@ -18,18 +18,24 @@
 use protocol::placement_center::placement_center_mqtt::{
     ConnectorHeartbeatReply, ConnectorHeartbeatRequest, CreateAclReply, CreateAclRequest,
     CreateBlacklistReply, CreateBlacklistRequest, CreateConnectorReply, CreateConnectorRequest,
-    CreateSessionReply, CreateSessionRequest, CreateTopicReply, CreateTopicRequest,
-    CreateTopicRewriteRuleReply, CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest,
-    DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest,
-    DeleteBlacklistReply, DeleteBlacklistRequest, DeleteConnectorReply, DeleteConnectorRequest,
-    DeleteSessionReply, DeleteSessionRequest, DeleteSubscribeReply, DeleteSubscribeRequest,
-    DeleteTopicReply, DeleteTopicRequest, DeleteTopicRewriteRuleReply,
-    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, GetShareSubLeaderReply,
-    GetShareSubLeaderRequest, ListAclReply, ListAclRequest, ListAutoSubscribeRuleReply,
-    ListAutoSubscribeRuleRequest, ListBlacklistReply, ListBlacklistRequest, ListConnectorReply,
-    ListConnectorRequest, ListSessionReply, ListSessionRequest, ListSubscribeReply,
-    ListSubscribeRequest, ListTopicReply, ListTopicRequest, ListTopicRewriteRuleReply,
-    ListTopicRewriteRuleRequest, ListUserReply, ListUserRequest, SaveLastWillMessageReply,
+    CreateRuleReply, CreateRuleRequest, CreateScheduledMessageReply, CreateScheduledMessageRequest,
+    CreateSessionReply, CreateSessionRequest, CreateTenantReply, CreateTenantRequest,
+    CreateTopicReply, CreateTopicRequest, CreateTopicRewriteRuleReply,
+    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply,
+    DeleteAutoSubscribeRuleRequest, DeleteBlacklistReply, DeleteBlacklistRequest,
+    DeleteConnectorReply, DeleteConnectorRequest, DeleteRuleReply, DeleteRuleRequest,
+    DeleteScheduledMessageReply, DeleteScheduledMessageRequest, DeleteSessionReply,
+    DeleteSessionRequest, DeleteSubscribeReply, DeleteSubscribeRequest, DeleteTenantReply,
+    DeleteTenantRequest, DeleteTopicReply, DeleteTopicRequest, DeleteTopicRewriteRuleReply,
+    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, DeleteWasmPluginReply,
+    DeleteWasmPluginRequest, GetShareSubLeaderReply, GetShareSubLeaderRequest, ListAclReply,
+    ListAclRequest, ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest, ListBlacklistReply,
+    ListBlacklistRequest, ListConnectorReply, ListConnectorRequest, ListRuleReply, ListRuleRequest,
+    ListScheduledMessageReply, ListScheduledMessageRequest, ListSessionReply, ListSessionRequest,
+    ListSubscribeReply, ListSubscribeRequest, ListTenantReply, ListTenantRequest, ListTopicReply,
+    ListTopicRequest, ListTopicRewriteRuleReply, ListTopicRewriteRuleRequest, ListUserReply,
+    ListUserRequest, ListWasmPluginReply, ListWasmPluginRequest, SaveLastWillMessageReply,
     SaveLastWillMessageRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
     SetSubscribeReply, SetSubscribeRequest, SetTopicRetainMessageReply,
     SetTopicRetainMessageRequest, UpdateConnectorReply, UpdateConnectorRequest, UpdateSessionReply,
@@ -113,6 +119,33 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     true
 );
 
+impl_retriable_request!(
+    CreateTenantRequest,
+    MqttServiceClient<Channel>,
+    CreateTenantReply,
+    placement_center_mqtt_services_client,
+    create_tenant,
+    true
+);
+
+impl_retriable_request!(
+    DeleteTenantRequest,
+    MqttServiceClient<Channel>,
+    DeleteTenantReply,
+    placement_center_mqtt_services_client,
+    delete_tenant,
+    true
+);
+
+impl_retriable_request!(
+    ListTenantRequest,
+    MqttServiceClient<Channel>,
+    ListTenantReply,
+    placement_center_mqtt_services_client,
+    list_tenant,
+    true
+);
+
 impl_retriable_request!(
     CreateTopicRequest,
     MqttServiceClient<Channel>,
@@ -275,6 +308,87 @@ async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::
     true
 );
 
+impl_retriable_request!(
+    ListRuleRequest,
+    MqttServiceClient<Channel>,
+    ListRuleReply,
+    placement_center_mqtt_services_client,
+    list_rule,
+    true
+);
+
+impl_retriable_request!(
+    CreateRuleRequest,
+    MqttServiceClient<Channel>,
+    CreateRuleReply,
+    placement_center_mqtt_services_client,
+    create_rule,
+    true
+);
+
+impl_retriable_request!(
+    DeleteRuleRequest,
+    MqttServiceClient<Channel>,
+    DeleteRuleReply,
+    placement_center_mqtt_services_client,
+    delete_rule,
+    true
+);
+
+impl_retriable_request!(
+    ListWasmPluginRequest,
+    MqttServiceClient<Channel>,
+    ListWasmPluginReply,
+    placement_center_mqtt_services_client,
+    list_wasm_plugin,
+    true
+);
+
+impl_retriable_request!(
+    CreateWasmPluginRequest,
+    MqttServiceClient<Channel>,
+    CreateWasmPluginReply,
+    placement_center_mqtt_services_client,
+    create_wasm_plugin,
+    true
+);
+
+impl_retriable_request!(
+    DeleteWasmPluginRequest,
+    MqttServiceClient<Channel>,
+    DeleteWasmPluginReply,
+    placement_center_mqtt_services_client,
+    delete_wasm_plugin,
+    true
+);
+
+impl_retriable_request!(
+    ListScheduledMessageRequest,
+    MqttServiceClient<Channel>,
+    ListScheduledMessageReply,
+    placement_center_mqtt_services_client,
+    list_scheduled_message,
+    true
+);
+
+impl_retriable_request!(
+    CreateScheduledMessageRequest,
+    MqttServiceClient<Channel>,
+    CreateScheduledMessageReply,
+    placement_center_mqtt_services_client,
+    create_scheduled_message,
+    true
+);
+
+impl_retriable_request!(
+    DeleteScheduledMessageRequest,
+    MqttServiceClient<Channel>,
+    DeleteScheduledMessageReply,
+    placement_center_mqtt_services_client,
+    delete_scheduled_message,
+    true
+);
+
 impl_retriable_request!(
     SetSubscribeRequest,
     MqttServiceClient<Channel>,
@@ -21,6 +21,7 @@
 use crate::journal::admin::JournalAdminServiceManager;
 use crate::journal::inner::JournalInnerServiceManager;
 use crate::mqtt::admin::MqttBrokerAdminServiceManager;
+use crate::mqtt::exhook::MqttExHookServiceManager;
 use crate::mqtt::inner::MqttBrokerPlacementServiceManager;
 use crate::placement::inner::PlacementServiceManager;
 use crate::placement::journal::JournalServiceManager;
@@ -43,6 +44,7 @@ pub struct ClientPool {
     // modules: mqtt broker
     mqtt_broker_placement_service_pools: DashMap<String, Pool<MqttBrokerPlacementServiceManager>>,
     mqtt_broker_admin_service_pools: DashMap<String, Pool<MqttBrokerAdminServiceManager>>,
+    mqtt_exhook_service_pools: DashMap<String, Pool<MqttExHookServiceManager>>,
 
     // modules: journal engine
     journal_admin_service_pools: DashMap<String, Pool<JournalAdminServiceManager>>,
@@ -63,6 +65,7 @@ pub fn new(max_open_connection: u64) -> Self {
             // modules: mqtt_broker
             mqtt_broker_placement_service_pools: DashMap::with_capacity(2),
             mqtt_broker_admin_service_pools: DashMap::with_capacity(2),
+            mqtt_exhook_service_pools: DashMap::with_capacity(2),
             // modules: journal_engine
             journal_admin_service_pools: DashMap::with_capacity(2),
             journal_inner_service_pools: DashMap::with_capacity(2),
@@ -298,6 +301,37 @@ pub async fn mqtt_broker_admin_services_client(
         ))
     }
 
+    pub async fn mqtt_exhook_services_client(
+        &self,
+        addr: &str,
+    ) -> Result<Connection<MqttExHookServiceManager>, CommonError> {
+        if !self.mqtt_exhook_service_pools.contains_key(addr) {
+            let manager = MqttExHookServiceManager::new(addr.to_owned());
+            let pool = Pool::builder()
+                .max_open(self.max_open_connection)
+                .build(manager);
+            self.mqtt_exhook_service_pools.insert(addr.to_owned(), pool);
+        }
+
+        if let Some(pool) = self.mqtt_exhook_service_pools.get(addr) {
+            match pool.get().await {
+                Ok(conn) => {
+                    return Ok(conn);
+                }
+                Err(e) => {
+                    return Err(CommonError::NoAvailableGrpcConnection(
+                        "ExHookServices".to_string(),
+                        e.to_string(),
+                    ));
+                }
+            };
+        }
+        Err(CommonError::NoAvailableGrpcConnection(
+            "ExHookServices".to_string(),
+            "connection pool is not initialized".to_string(),
+        ))
+    }
+
     // ----------modules: journal engine -------------
     pub async fn journal_inner_services_client(
         &self,
@@ -65,6 +65,8 @@ async fn user_test() {
             username: user_name.clone(),
             password: password.clone(),
             is_superuser: false,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_create_user(&client_pool, &addrs, user.clone()).await {
@@ -74,7 +76,7 @@ async fn user_test() {
             }
         }
 
-        match mqtt_broker_list_user(&client_pool, &addrs, ListUserRequest {}).await {
+        match mqtt_broker_list_user(&client_pool, &addrs, ListUserRequest::default()).await {
             Ok(data) => {
                 let mut flag = false;
                 for raw in data.users {
@@ -95,6 +97,7 @@ async fn user_test() {
             &addrs,
             DeleteUserRequest {
                 username: user.username.clone(),
+                tenant: user.tenant.clone(),
             },
         )
         .await
@@ -105,7 +108,7 @@ async fn user_test() {
             }
         }
 
-        match mqtt_broker_list_user(&client_pool, &addrs, ListUserRequest {}).await {
+        match mqtt_broker_list_user(&client_pool, &addrs, ListUserRequest::default()).await {
             Ok(data) => {
                 let mut flag = true;
                 for raw in data.users {
@@ -148,6 +151,7 @@ async fn schema_test() {
             schema_type: "json".to_string(),
             schema: schema_data.clone(),
             desc: "Old schema".to_string(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_create_schema(&client_pool, &addrs, create_request).await {
@@ -159,6 +163,7 @@ async fn schema_test() {
 
         let list_request = MqttListSchemaRequest {
             schema_name: schema_name.clone(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_list_schema(&client_pool, &addrs, list_request.clone()).await {
@@ -194,6 +199,7 @@ async fn schema_test() {
             schema_type: "avro".to_string(),
             schema: schema_data.clone(),
             desc: "New schema".to_string(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_update_schema(&client_pool, &addrs, update_request).await {
@@ -224,6 +230,7 @@ async fn schema_test() {
         // delete schema
         let delete_request = MqttDeleteSchemaRequest {
             schema_name: schema_name.clone(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_delete_schema(&client_pool, &addrs, delete_request).await {
@@ -260,6 +267,7 @@ async fn connector_test() {
             })
             .unwrap(),
             topic_id: "test-topic-1".to_string(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_create_connector(&client_pool, &addrs, create_request).await {
@@ -272,6 +280,7 @@ async fn connector_test() {
         // list connector we just created
         let list_request = MqttListConnectorRequest {
             connector_name: connector_name.clone(),
+            tenant: "".to_string(),
         };
 
         let mut connector =
@@ -304,6 +313,7 @@ async fn connector_test() {
             bootstrap_servers: "localhost:9092".to_string(),
             topic: "test-topic".to_string(),
             key: "test-key".to_string(),
+            ..Default::default()
         })
         .unwrap();
         connector.topic_id = "test-topic-2".to_string();
@@ -342,6 +352,7 @@ async fn connector_test() {
                 bootstrap_servers: "localhost:9092".to_string(),
                 topic: "test-topic".to_string(),
                 key: "test-key".to_string(),
+                ..Default::default()
             })
             .unwrap()
         );
@@ -350,6 +361,7 @@ async fn connector_test() {
         // delete connector
         let delete_request = MqttDeleteConnectorRequest {
             connector_name: connector_name.clone(),
+            tenant: "".to_string(),
         };
 
         match mqtt_broker_delete_connector(&client_pool, &addrs, delete_request).await {
@@ -50,6 +50,7 @@ async fn mqtt_topic_test() {
             retain_message: None,
             retain_message_expired_at: None,
             create_time: now_second(),
+            tenant: "".to_string(),
         };
 
         let request = CreateTopicRequest {
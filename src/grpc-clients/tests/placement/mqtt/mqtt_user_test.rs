@@ -40,6 +40,8 @@ async fn mqtt_user_test() {
             username: user_name.clone(),
             password: password.clone(),
             is_superuser: false,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         let request: CreateUserRequest = CreateUserRequest {
@@ -11,3 +11,535 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use log::{error, info};
+use metadata_struct::adapter::read_config::ReadConfig;
+use persist::PersistRequest;
+use record::{IdempotentAction, IdempotentRecord};
+use storage_adapter::storage::{ShardInfo, StorageAdapter};
+use tokio::sync::{mpsc, oneshot};
+
+pub mod persist;
+pub mod record;
+
+const IDEMPOTENT_SHARD_NAME: &str = "$idempotent-message";
+
+// How long a reserved id blocks its duplicates while the message is being stored. A reservation
+// that is neither committed nor released in time, for example because the task was cancelled,
+// is given up so the producer can send the message again.
+const RESERVE_TIMEOUT_SEC: u64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdempotentStatus {
+    // The id was not seen before and is now reserved by the caller
+    New,
+    // The id was saved and has not expired
+    Duplicate,
+    // The id is reserved by another caller whose message is still being stored
+    Pending,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct IdempotentEntry {
+    expire_time: u64,
+    pending: bool,
+}
+
+// Remembers the messages a producer has already sent, by a message id or by an increasing
+// sequence number, for ttl seconds. The data is kept in memory and appended to a shard of
+// the storage in batches, so it survives a restart. The records appended by the other nodes
+// sharing the storage are read back by sync, so a producer that moves to another node keeps
+// its data. The storage is chosen when the service starts, it must be shared by the nodes for
+// the data to follow the producer.
+pub struct IdempotentManager {
+    namespace: String,
+    node_id: String,
+    ttl: u64,
+    storage_adapter: Arc<dyn StorageAdapter + Sync + Send>,
+    // producer id -> message id -> entry
+    idempotent_data: DashMap<String, DashMap<String, IdempotentEntry>>,
+    // producer id -> (last sequence number, expire time)
+    producer_sequence: DashMap<String, (u64, u64)>,
+    // the next offset of the shard sync reads from
+    sync_offset: AtomicU64,
+    sync_lock: tokio::sync::Mutex<()>,
+    persist_sender: mpsc::Sender<PersistRequest>,
+    persist_receiver: Mutex<Option<mpsc::Receiver<PersistRequest>>>,
+}
+
+impl IdempotentManager {
+    pub fn new(
+        namespace: String,
+        node_id: String,
+        ttl: u64,
+        storage_adapter: Arc<dyn StorageAdapter + Sync + Send>,
+    ) -> Self {
+        let (persist_sender, persist_receiver) = mpsc::channel(10000);
+        IdempotentManager {
+            namespace,
+            node_id,
+            ttl,
+            storage_adapter,
+            idempotent_data: DashMap::with_capacity(8),
+            producer_sequence: DashMap::with_capacity(8),
+            sync_offset: AtomicU64::new(0),
+            sync_lock: tokio::sync::Mutex::new(()),
+            persist_sender,
+            persist_receiver: Mutex::new(Some(persist_receiver)),
+        }
+    }
+
+    // Creates the shard of the idempotent data and loads the entries that have not expired.
+    pub async fn init(&self) -> Result<(), CommonError> {
+        self.try_init_shard().await?;
+        self.load_idempotent_data().await?;
+        info!("Idempotent message service start");
+        Ok(())
+    }
+
+    async fn try_init_shard(&self) -> Result<(), CommonError> {
+        let results = self
+            .storage_adapter
+            .list_shard(self.namespace.clone(), IDEMPOTENT_SHARD_NAME.to_string())
+            .await?;
+        if results.is_empty() {
+            let shard = ShardInfo {
+                namespace: self.namespace.clone(),
+                shard_name: IDEMPOTENT_SHARD_NAME.to_string(),
+                replica_num: 1,
+            };
+            self.storage_adapter.create_shard(shard).await?;
+            info!("init shard:{}, {}", self.namespace, IDEMPOTENT_SHARD_NAME);
+        }
+        Ok(())
+    }
+
+    async fn load_idempotent_data(&self) -> Result<(), CommonError> {
+        // The records written before the ttl window only hold expired entries.
+        if let Some(start) = self
+            .storage_adapter
+            .get_offset_by_timestamp(
+                self.namespace.clone(),
+                IDEMPOTENT_SHARD_NAME.to_string(),
+                now_second().saturating_sub(self.ttl),
+            )
+            .await?
+        {
+            self.sync_offset.store(start.offset, Ordering::SeqCst);
+        }
+        // The memory is empty after a restart, so the records of this node are applied too.
+        self.read_records(true).await
+    }
+
+    // Applies the records the other nodes appended to the shard since the last sync. It runs
+    // periodically, and before a session that may come from another node is resumed.
+    pub async fn sync(&self) -> Result<(), CommonError> {
+        self.read_records(false).await
+    }
+
+    async fn read_records(&self, include_own: bool) -> Result<(), CommonError> {
+        let _guard = self.sync_lock.lock().await;
+        let read_config = ReadConfig {
+            max_record_num: 1000,
+            max_size: 1024 * 1024 * 1024,
+        };
+        let mut offset = self.sync_offset.load(Ordering::SeqCst);
+        loop {
+            let data = self
+                .storage_adapter
+                .read_by_offset(
+                    self.namespace.clone(),
+                    IDEMPOTENT_SHARD_NAME.to_string(),
+                    offset,
+                    read_config.clone(),
+                )
+                .await?;
+            if data.is_empty() {
+                break;
+            }
+            for record in data {
+                offset = record.offset.unwrap_or(offset) + 1;
+                match IdempotentRecord::decode(&record) {
+                    Ok(idempotent_record) => {
+                        if include_own || idempotent_record.node_id != self.node_id {
+                            self.apply(&idempotent_record);
+                        }
+                    }
+                    Err(e) => {
+                        error!(
+                            "Decoding the idempotent data at offset {} failed with error {:?}",
+                            offset - 1,
+                            e
+                        );
+                    }
+                }
+            }
+            self.sync_offset.store(offset, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    fn apply(&self, record: &IdempotentRecord) {
+        let now = now_second();
+        if record.expire_time <= now {
+            return;
+        }
+        match record.action {
+            IdempotentAction::Save => {
+                let data = self
+                    .idempotent_data
+                    .entry(record.producer_id.clone())
+                    .or_default();
+                let mut entry = data.entry(record.id.clone()).or_insert(IdempotentEntry {
+                    expire_time: 0,
+                    pending: false,
+                });
+                // a message that is being stored on this node decides the entry itself
+                if !(entry.pending && entry.expire_time > now) {
+                    *entry = IdempotentEntry {
+                        expire_time: record.expire_time,
+                        pending: false,
+                    };
+                }
+            }
+            IdempotentAction::Delete => {
+                if let Some(data) = self.idempotent_data.get(&record.producer_id) {
+                    data.remove_if(&record.id, |_, entry| !entry.pending);
+                }
+            }
+            IdempotentAction::Sequence => {
+                let mut entry = self
+                    .producer_sequence
+                    .entry(record.producer_id.clone())
+                    .or_insert((0, 0));
+                if entry.1 <= now || record.seq > entry.0 {
+                    *entry = (record.seq, record.expire_time);
+                }
+            }
+        }
+    }
+
+    // Reserves the message id of the producer before its message is stored. The caller must
+    // commit the id once the message is stored, or release it when storing fails, so that a
+    // message that was not stored is never taken for a duplicate.
+    pub fn reserve(&self, producer_id: &str, id: &str) -> IdempotentStatus {
+        let now = now_second();
+        match self
+            .idempotent_data
+            .entry(producer_id.to_owned())
+            .or_default()
+            .entry(id.to_owned())
+        {
+            Entry::Occupied(mut entry) => {
+                let current = *entry.get();
+                if current.expire_time > now {
+                    if current.pending {
+                        IdempotentStatus::Pending
+                    } else {
+                        IdempotentStatus::Duplicate
+                    }
+                } else {
+                    entry.insert(IdempotentEntry {
+                        expire_time: now + RESERVE_TIMEOUT_SEC,
+                        pending: true,
+                    });
+                    IdempotentStatus::New
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(IdempotentEntry {
+                    expire_time: now + RESERVE_TIMEOUT_SEC,
+                    pending: true,
+                });
+                IdempotentStatus::New
+            }
+        }
+    }
+
+    // Saves a reserved id. The id counts as a duplicate from now on, even when it cannot be
+    // persisted, because its message is already stored.
+    pub async fn commit(&self, producer_id: &str, id: &str) -> Result<(), CommonError> {
+        let expire_time = now_second() + self.ttl;
+        let record =
+            IdempotentRecord::build(IdempotentAction::Save, producer_id, id, 0, expire_time);
+        let result = self.persist(record).await;
+        self.mark_saved(producer_id, id, expire_time);
+        result
+    }
+
+    fn mark_saved(&self, producer_id: &str, id: &str, expire_time: u64) {
+        self.idempotent_data
+            .entry(producer_id.to_owned())
+            .or_default()
+            .insert(
+                id.to_owned(),
+                IdempotentEntry {
+                    expire_time,
+                    pending: false,
+                },
+            );
+    }
+
+    // Gives up a reserved id whose message could not be stored.
+    pub fn release(&self, producer_id: &str, id: &str) {
+        if let Some(data) = self.idempotent_data.get(producer_id) {
+            data.remove_if(id, |_, entry| entry.pending);
+        }
+    }
+
+    // Saves the message id of the producer. Returns false when it was already saved and has
+    // not expired, the message is then a duplicate. The id only becomes visible to the other
+    // callers once it is persisted.
+    pub async fn save(&self, producer_id: &str, id: &str) -> Result<bool, CommonError> {
+        match self.reserve(producer_id, id) {
+            IdempotentStatus::Duplicate => return Ok(false),
+            IdempotentStatus::Pending => {
+                return Err(CommonError::CommonError(format!(
+                    "{} of producer {} is being saved",
+                    id, producer_id
+                )));
+            }
+            IdempotentStatus::New => {}
+        }
+
+        let expire_time = now_second() + self.ttl;
+        let record =
+            IdempotentRecord::build(IdempotentAction::Save, producer_id, id, 0, expire_time);
+        if let Err(e) = self.persist(record).await {
+            self.release(producer_id, id);
+            return Err(e);
+        }
+        self.mark_saved(producer_id, id, expire_time);
+        Ok(true)
+    }
+
+    pub fn exists(&self, producer_id: &str, id: &str) -> bool {
+        let now = now_second();
+        self.idempotent_data
+            .get(producer_id)
+            .and_then(|data| {
+                data.get(id)
+                    .map(|entry| !entry.pending && entry.expire_time > now)
+            })
+            .unwrap_or(false)
+    }
+
+    pub async fn delete(&self, producer_id: &str, id: &str) -> Result<(), CommonError> {
+        let removed = self
+            .idempotent_data
+            .get(producer_id)
+            .and_then(|data| data.remove(id));
+        if removed.is_none() {
+            return Ok(());
+        }
+        let record = IdempotentRecord::build(
+            IdempotentAction::Delete,
+            producer_id,
+            id,
+            0,
+            now_second() + self.ttl,
+        );
+        self.persist(record).await
+    }
+
+    // Saves the sequence number of the producer. Returns false when it is not above the last
+    // saved one, the message is then a duplicate.
+    pub async fn save_sequence(&self, producer_id: &str, seq: u64) -> Result<bool, CommonError> {
+        let now = now_second();
+        let expire_time = now + self.ttl;
+        let previous = {
+            let mut entry = self
+                .producer_sequence
+                .entry(producer_id.to_owned())
+                .or_insert((0, 0));
+            let (last_seq, last_expire_time) = *entry;
+            if last_expire_time > now && seq <= last_seq {
+                return Ok(false);
+            }
+            *entry = (seq, expire_time);
+            (last_seq, last_expire_time)
+        };
+
+        let record = IdempotentRecord::build(
+            IdempotentAction::Sequence,
+            producer_id,
+            "",
+            seq,
+            expire_time,
+        );
+        if let Err(e) = self.persist(record).await {
+            if let Some(mut entry) = self.producer_sequence.get_mut(producer_id) {
+                if entry.0 == seq {
+                    *entry = previous;
+                }
+            }
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    // Drops the entries whose ttl has passed from memory.
+    pub fn remove_expired(&self) {
+        let now = now_second();
+        self.idempotent_data.retain(|_, data| {
+            data.retain(|_, entry| entry.expire_time > now);
+            !data.is_empty()
+        });
+        self.producer_sequence
+            .retain(|_, (_, expire_time)| *expire_time > now);
+    }
+
+    async fn persist(&self, mut record: IdempotentRecord) -> Result<(), CommonError> {
+        record.node_id = self.node_id.clone();
+        let (resp_sx, resp_rx) = oneshot::channel();
+        self.persist_sender
+            .send(PersistRequest { record, resp_sx })
+            .await
+            .map_err(|e| CommonError::CommonError(e.to_string()))?;
+        resp_rx
+            .await
+            .map_err(|e| CommonError::CommonError(e.to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use storage_adapter::memory::MemoryStorageAdapter;
+    use tokio::sync::broadcast;
+
+    use crate::persist::start_idempotent_persist_thread;
+    use crate::{IdempotentManager, IdempotentStatus};
+
+    async fn start(
+        storage_adapter: &Arc<MemoryStorageAdapter>,
+        node_id: &str,
+        ttl: u64,
+        stop_send: &broadcast::Sender<bool>,
+    ) -> Arc<IdempotentManager> {
+        let idempotent_manager = Arc::new(IdempotentManager::new(
+            "test".to_string(),
+            node_id.to_string(),
+            ttl,
+            storage_adapter.clone(),
+        ));
+        idempotent_manager.init().await.unwrap();
+        start_idempotent_persist_thread(idempotent_manager.clone(), stop_send.clone());
+        idempotent_manager
+    }
+
+    #[tokio::test]
+    async fn save_and_restart_test() {
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let (stop_send, _) = broadcast::channel(2);
+        let idempotent_manager = start(&storage_adapter, "1", 100, &stop_send).await;
+
+        assert!(!idempotent_manager.exists("c1", "15"));
+        assert!(idempotent_manager.save("c1", "15").await.unwrap());
+        assert!(!idempotent_manager.save("c1", "15").await.unwrap());
+        assert!(idempotent_manager.save("c2", "15").await.unwrap());
+        assert!(idempotent_manager.save("c1", "16").await.unwrap());
+        idempotent_manager.delete("c1", "16").await.unwrap();
+        assert!(!idempotent_manager.exists("c1", "16"));
+
+        assert!(idempotent_manager.save_sequence("p1", 1).await.unwrap());
+        assert!(idempotent_manager.save_sequence("p1", 2).await.unwrap());
+        assert!(!idempotent_manager.save_sequence("p1", 2).await.unwrap());
+        assert!(!idempotent_manager.save_sequence("p1", 1).await.unwrap());
+
+        // the saved data is loaded again after a restart
+        stop_send.send(true).unwrap();
+        let (stop_send, _) = broadcast::channel(2);
+        let idempotent_manager = start(&storage_adapter, "1", 100, &stop_send).await;
+        assert!(idempotent_manager.exists("c1", "15"));
+        assert!(idempotent_manager.exists("c2", "15"));
+        assert!(!idempotent_manager.exists("c1", "16"));
+        assert!(!idempotent_manager.save_sequence("p1", 2).await.unwrap());
+        assert!(idempotent_manager.save_sequence("p1", 3).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn expire_test() {
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let (stop_send, _) = broadcast::channel(2);
+        let idempotent_manager = start(&storage_adapter, "1", 0, &stop_send).await;
+
+        assert!(idempotent_manager.save("c1", "15").await.unwrap());
+        assert!(!idempotent_manager.exists("c1", "15"));
+        assert!(idempotent_manager.save("c1", "15").await.unwrap());
+
+        idempotent_manager.remove_expired();
+        assert!(idempotent_manager.idempotent_data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reserve_commit_release_test() {
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let (stop_send, _) = broadcast::channel(2);
+        let idempotent_manager = start(&storage_adapter, "1", 100, &stop_send).await;
+
+        // a reserved id is neither saved nor a duplicate until its message is stored
+        assert_eq!(
+            idempotent_manager.reserve("c1", "m1"),
+            IdempotentStatus::New
+        );
+        assert!(!idempotent_manager.exists("c1", "m1"));
+        assert_eq!(
+            idempotent_manager.reserve("c1", "m1"),
+            IdempotentStatus::Pending
+        );
+        assert!(idempotent_manager.save("c1", "m1").await.is_err());
+
+        // storing the message failed, the retry is stored
+        idempotent_manager.release("c1", "m1");
+        assert_eq!(
+            idempotent_manager.reserve("c1", "m1"),
+            IdempotentStatus::New
+        );
+        idempotent_manager.commit("c1", "m1").await.unwrap();
+        assert!(idempotent_manager.exists("c1", "m1"));
+        assert_eq!(
+            idempotent_manager.reserve("c1", "m1"),
+            IdempotentStatus::Duplicate
+        );
+
+        // releasing a committed id keeps it
+        idempotent_manager.release("c1", "m1");
+        assert!(idempotent_manager.exists("c1", "m1"));
+    }
+
+    #[tokio::test]
+    async fn sync_test() {
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let (stop_send, _) = broadcast::channel(2);
+        let node1 = start(&storage_adapter, "1", 100, &stop_send).await;
+        let node2 = start(&storage_adapter, "2", 100, &stop_send).await;
+
+        assert!(node1.save("c1", "pkid-15").await.unwrap());
+        assert!(node1.save("c1", "pkid-16").await.unwrap());
+        node1.delete("c1", "pkid-16").await.unwrap();
+        assert!(node1.save_sequence("p1", 5).await.unwrap());
+        assert!(!node2.exists("c1", "pkid-15"));
+
+        // the client moves to node 2
+        node2.sync().await.unwrap();
+        assert!(node2.exists("c1", "pkid-15"));
+        assert!(!node2.exists("c1", "pkid-16"));
+        assert!(!node2.save("c1", "pkid-15").await.unwrap());
+        assert!(!node2.save_sequence("p1", 5).await.unwrap());
+
+        // the changes made on node 2 are seen by node 1
+        node2.delete("c1", "pkid-15").await.unwrap();
+        node1.sync().await.unwrap();
+        assert!(!node1.exists("c1", "pkid-15"));
+        assert!(node1.save("c1", "pkid-15").await.unwrap());
+    }
+}
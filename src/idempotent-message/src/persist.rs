@@ -0,0 +1,199 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{sync::Arc, time::Duration};
+
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use log::{debug, error};
+use metadata_struct::adapter::record::Record;
+use tokio::{
+    select,
+    sync::{broadcast, oneshot},
+    time::sleep,
+};
+
+use crate::record::IdempotentRecord;
+use crate::{IdempotentManager, IDEMPOTENT_SHARD_NAME};
+
+// The most entries appended to the shard in one write.
+const PERSIST_BATCH_SIZE: usize = 1000;
+
+pub(crate) struct PersistRequest {
+    pub record: IdempotentRecord,
+    pub resp_sx: oneshot::Sender<Result<(), CommonError>>,
+}
+
+// Appends the entries to the shard. The entries saved while a write is running are written
+// together in the next one, and each caller is answered once its entry is stored.
+pub fn start_idempotent_persist_thread(
+    idempotent_manager: Arc<IdempotentManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let Some(mut persist_receiver) = idempotent_manager.persist_receiver.lock().unwrap().take()
+    else {
+        error!("The persist thread of the idempotent data is already running");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut stop_recv = stop_send.subscribe();
+        loop {
+            select! {
+                val = stop_recv.recv() => {
+                    if let Ok(flag) = val {
+                        if flag {
+                            debug!("{}","Idempotent data persist thread exited successfully");
+                            break;
+                        }
+                    }
+                }
+                val = persist_receiver.recv() => {
+                    let Some(request) = val else {
+                        break;
+                    };
+                    let mut requests = vec![request];
+                    while requests.len() < PERSIST_BATCH_SIZE {
+                        match persist_receiver.try_recv() {
+                            Ok(request) => requests.push(request),
+                            Err(_) => break,
+                        }
+                    }
+                    persist_batch(&idempotent_manager, requests).await;
+                }
+            }
+        }
+    });
+}
+
+async fn persist_batch(idempotent_manager: &IdempotentManager, requests: Vec<PersistRequest>) {
+    let mut records = Vec::with_capacity(requests.len());
+    let mut senders = Vec::with_capacity(requests.len());
+    for request in requests {
+        match request.record.encode() {
+            Ok(record) => {
+                records.push(record);
+                senders.push(request.resp_sx);
+            }
+            Err(e) => {
+                let _ = request.resp_sx.send(Err(e));
+            }
+        }
+    }
+
+    let result = write_records(idempotent_manager, records).await;
+    for resp_sx in senders {
+        let resp = match &result {
+            Ok(()) => Ok(()),
+            Err(e) => Err(CommonError::CommonError(e.to_string())),
+        };
+        let _ = resp_sx.send(resp);
+    }
+}
+
+async fn write_records(
+    idempotent_manager: &IdempotentManager,
+    records: Vec<Record>,
+) -> Result<(), CommonError> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    idempotent_manager
+        .storage_adapter
+        .batch_write(
+            idempotent_manager.namespace.clone(),
+            IDEMPOTENT_SHARD_NAME.to_string(),
+            records,
+        )
+        .await?;
+    Ok(())
+}
+
+// Reads the records appended by the other nodes every sync_interval_ms.
+pub fn start_idempotent_sync_thread(
+    idempotent_manager: Arc<IdempotentManager>,
+    sync_interval_ms: u64,
+    stop_send: broadcast::Sender<bool>,
+) {
+    tokio::spawn(async move {
+        let mut stop_recv = stop_send.subscribe();
+        loop {
+            select! {
+                val = stop_recv.recv() => {
+                    if let Ok(flag) = val {
+                        if flag {
+                            debug!("{}","Idempotent data sync thread exited successfully");
+                            break;
+                        }
+                    }
+                }
+                _ = sleep(Duration::from_millis(sync_interval_ms)) => {
+                    if let Err(e) = idempotent_manager.sync().await {
+                        error!("Syncing the idempotent data failed with error {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+// Drops the expired entries from memory and trims the records written before the ttl window
+// from the shard.
+pub fn start_idempotent_expire_thread(
+    idempotent_manager: Arc<IdempotentManager>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    tokio::spawn(async move {
+        let mut stop_recv = stop_send.subscribe();
+        loop {
+            select! {
+                val = stop_recv.recv() => {
+                    if let Ok(flag) = val {
+                        if flag {
+                            debug!("{}","Idempotent data expire thread exited successfully");
+                            break;
+                        }
+                    }
+                }
+                _ = sleep(Duration::from_secs(60)) => {
+                    idempotent_manager.remove_expired();
+                    if let Err(e) = trim_expired_records(&idempotent_manager).await {
+                        error!("Trimming the expired idempotent data failed with error {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn trim_expired_records(idempotent_manager: &IdempotentManager) -> Result<(), CommonError> {
+    let storage_adapter = &idempotent_manager.storage_adapter;
+    let offset = storage_adapter
+        .get_offset_by_timestamp(
+            idempotent_manager.namespace.clone(),
+            IDEMPOTENT_SHARD_NAME.to_string(),
+            now_second().saturating_sub(idempotent_manager.ttl),
+        )
+        .await?;
+    if let Some(offset) = offset {
+        storage_adapter
+            .trim_shard(
+                idempotent_manager.namespace.clone(),
+                IDEMPOTENT_SHARD_NAME.to_string(),
+                offset.offset,
+            )
+            .await?;
+    }
+    Ok(())
+}
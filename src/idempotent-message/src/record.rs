@@ -0,0 +1,79 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_base::error::common::CommonError;
+use metadata_struct::adapter::record::Record;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum IdempotentAction {
+    Save,
+    Delete,
+    Sequence,
+}
+
+// An entry of the idempotent data as it is appended to the shard.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdempotentRecord {
+    pub action: IdempotentAction,
+    pub producer_id: String,
+    pub id: String,
+    pub seq: u64,
+    pub expire_time: u64,
+    // The node that appended the record
+    #[serde(default)]
+    pub node_id: String,
+}
+
+impl IdempotentRecord {
+    pub fn build(
+        action: IdempotentAction,
+        producer_id: &str,
+        id: &str,
+        seq: u64,
+        expire_time: u64,
+    ) -> Self {
+        IdempotentRecord {
+            action,
+            producer_id: producer_id.to_owned(),
+            id: id.to_owned(),
+            seq,
+            expire_time,
+            node_id: String::new(),
+        }
+    }
+
+    pub fn encode(&self) -> Result<Record, CommonError> {
+        Ok(Record::build_byte(serde_json::to_vec(self)?))
+    }
+
+    pub fn decode(record: &Record) -> Result<Self, CommonError> {
+        Ok(serde_json::from_slice(&record.data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdempotentAction, IdempotentRecord};
+
+    #[test]
+    fn encode_decode_test() {
+        let record = IdempotentRecord::build(IdempotentAction::Save, "c1", "15", 0, 100);
+        let data = IdempotentRecord::decode(&record.encode().unwrap()).unwrap();
+        assert_eq!(data.action, IdempotentAction::Save);
+        assert_eq!(data.producer_id, "c1");
+        assert_eq!(data.id, "15");
+        assert_eq!(data.expire_time, 100);
+    }
+}
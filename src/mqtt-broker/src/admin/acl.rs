@@ -13,13 +13,16 @@
 // limitations under the License.
 
 use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::tenant::{check_tenant_exists, tenant_scope_match};
 use crate::security::AuthDriver;
 use grpc_clients::pool::ClientPool;
-use metadata_struct::acl::mqtt_acl::MqttAcl;
+use metadata_struct::acl::mqtt_acl::{MqttAcl, MqttAclResourceType};
 use metadata_struct::acl::mqtt_blacklist::{MqttAclBlackList, MqttAclBlackListType};
+use metadata_struct::mqtt::tenant::tenant_scoped_name;
 use protocol::broker_mqtt::broker_mqtt_admin::{
     CreateAclReply, CreateAclRequest, CreateBlacklistReply, CreateBlacklistRequest, DeleteAclReply,
-    DeleteAclRequest, DeleteBlacklistReply, DeleteBlacklistRequest, ListAclReply,
+    DeleteAclRequest, DeleteBlacklistReply, DeleteBlacklistRequest, ListAclReply, ListAclRequest,
     ListBlacklistReply,
 };
 use std::sync::Arc;
@@ -28,7 +31,9 @@
 pub async fn list_acl_by_req(
     cache_manager: &Arc<CacheManager>,
     client_pool: &Arc<ClientPool>,
+    request: Request<ListAclRequest>,
 ) -> Result<Response<ListAclReply>, Status> {
+    let req = request.into_inner();
     let mut reply = ListAclReply::default();
 
     let auth_driver = AuthDriver::new(cache_manager.clone(), client_pool.clone());
@@ -36,7 +41,10 @@ pub async fn list_acl_by_req(
         Ok(data) => {
             let mut acls_list = Vec::new();
             // todo finish get_items
-            for ele in data {
+            for ele in data
+                .iter()
+                .filter(|acl| tenant_scope_match(&req.tenant, &acl.tenant))
+            {
                 match ele.encode() {
                     Ok(acl) => acls_list.push(acl),
                     Err(e) => return Err(Status::cancelled(e.to_string())),
@@ -61,6 +69,8 @@ pub async fn create_acl_by_req(
         Err(e) => return Err(Status::cancelled(e.to_string())),
     };
 
+    check_acl_tenant(cache_manager, &mqtt_acl)?;
+
     let auth_driver = AuthDriver::new(cache_manager.clone(), client_pool.clone());
     match auth_driver.save_acl(mqtt_acl).await {
         Ok(_) => Ok(Response::new(CreateAclReply::default())),
@@ -79,6 +89,7 @@ pub async fn delete_acl_by_req(
         Err(e) => return Err(Status::cancelled(e.to_string())),
     };
 
+    // The acls are kept per tenant, so only the acl of the tenant in the request is deleted
     let auth_driver = AuthDriver::new(cache_manager.clone(), client_pool.clone());
     match auth_driver.delete_acl(mqtt_acl).await {
         Ok(_) => Ok(Response::new(DeleteAclReply::default())),
@@ -86,6 +97,28 @@ pub async fn delete_acl_by_req(
     }
 }
 
+// A tenant acl can only be granted to a user of the same tenant
+fn check_acl_tenant(
+    cache_manager: &Arc<CacheManager>,
+    acl: &MqttAcl,
+) -> Result<(), MqttBrokerError> {
+    check_tenant_exists(cache_manager, &acl.tenant)?;
+    if acl.tenant.is_empty() || acl.resource_type != MqttAclResourceType::User {
+        return Ok(());
+    }
+
+    if cache_manager
+        .user_info
+        .contains_key(&tenant_scoped_name(&acl.tenant, &acl.resource_name))
+    {
+        return Ok(());
+    }
+    Err(MqttBrokerError::TenantResourceNotOwned(
+        format!("User {}", acl.resource_name),
+        acl.tenant.clone(),
+    ))
+}
+
 pub async fn list_blacklist_by_req(
     cache_manager: &Arc<CacheManager>,
     client_pool: &Arc<ClientPool>,
@@ -12,24 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::handler::cache::CacheManager;
 use crate::handler::error::MqttBrokerError;
+use crate::handler::tenant::{check_tenant_exists, check_tenant_owner, tenant_scope_match};
+use crate::handler::topic::topic_name_validator;
+use crate::storage::cluster::ClusterStorage;
 use crate::storage::connector::ConnectorStorage;
+use crate::storage::message::MessageStorage;
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use common_base::tools::now_second;
 use grpc_clients::placement::mqtt::call::placement_list_connector;
 use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::bridge::config_common::{ConnectorCommonConfig, ConnectorTransform};
+use metadata_struct::mqtt::bridge::config_http::HttpConnectorConfig;
 use metadata_struct::mqtt::bridge::config_kafka::KafkaConnectorConfig;
 use metadata_struct::mqtt::bridge::config_local_file::LocalFileConnectorConfig;
+use metadata_struct::mqtt::bridge::config_mqtt::{MqttConnectorConfig, MqttConnectorDirection};
+use metadata_struct::mqtt::bridge::config_redis::RedisConnectorConfig;
+use metadata_struct::mqtt::bridge::config_s3::S3ConnectorConfig;
+use metadata_struct::mqtt::bridge::config_source::{
+    KafkaSourceConnectorConfig, LocalFileSourceConnectorConfig,
+};
+use metadata_struct::mqtt::bridge::config_sql::SqlConnectorConfig;
 use metadata_struct::mqtt::bridge::connector::MQTTConnector;
 use metadata_struct::mqtt::bridge::connector_type::ConnectorType;
 use metadata_struct::mqtt::bridge::status::MQTTStatus;
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    MqttConnectorType, MqttCreateConnectorReply, MqttCreateConnectorRequest,
-    MqttDeleteConnectorReply, MqttDeleteConnectorRequest, MqttListConnectorReply,
-    MqttListConnectorRequest, MqttUpdateConnectorReply, MqttUpdateConnectorRequest,
+    MqttConnectorOffsetResetType, MqttConnectorType, MqttCreateConnectorReply,
+    MqttCreateConnectorRequest, MqttDeleteConnectorReply, MqttDeleteConnectorRequest,
+    MqttListConnectorReply, MqttListConnectorRequest, MqttPauseConnectorReply,
+    MqttPauseConnectorRequest, MqttResetConnectorOffsetReply, MqttResetConnectorOffsetRequest,
+    MqttResumeConnectorReply, MqttResumeConnectorRequest, MqttUpdateConnectorReply,
+    MqttUpdateConnectorRequest,
 };
 use protocol::placement_center::placement_center_mqtt::ListConnectorRequest;
 use std::sync::Arc;
+use storage_adapter::storage::StorageAdapter;
 use tonic::{Request, Response, Status};
 
 pub async fn list_connector_by_req(
@@ -43,17 +61,46 @@ pub async fn list_connector_by_req(
         connector_name: req.connector_name.clone(),
     };
 
-    let connectors = placement_list_connector(client_pool, &config.placement_center, request)
-        .await?
-        .connectors;
+    let reply = placement_list_connector(client_pool, &config.placement_center, request).await?;
+
+    // The status of a running connector is the one reported by the broker running it.
+    let storage = ConnectorStorage::new(client_pool.clone());
+    let mut connectors = Vec::with_capacity(reply.connectors.len());
+    for raw in reply.connectors {
+        let mut connector = match serde_json::from_slice::<MQTTConnector>(&raw) {
+            Ok(connector) => connector,
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        };
+        if !tenant_scope_match(&req.tenant, &connector.tenant) {
+            continue;
+        }
+        let runtime_status = match storage
+            .get_runtime_status(&config.cluster_name, &connector.connector_name)
+            .await
+        {
+            Ok(runtime_status) => runtime_status,
+            Err(e) => return Err(Status::cancelled(e.to_string())),
+        };
+        if let Some(runtime_status) = &runtime_status {
+            if connector.status == MQTTStatus::Running
+                && connector.broker_id == Some(runtime_status.broker_id)
+            {
+                connector.status = runtime_status.status.clone();
+            }
+        }
+        connector.runtime_status = runtime_status;
+        connectors.push(connector.encode());
+    }
 
     Ok(Response::new(MqttListConnectorReply { connectors }))
 }
 pub async fn create_connector_by_req(
+    cache_manager: &Arc<CacheManager>,
     client_pool: &Arc<ClientPool>,
     request: Request<MqttCreateConnectorRequest>,
 ) -> Result<Response<MqttCreateConnectorReply>, Status> {
     let req = request.into_inner();
+    check_tenant_exists(cache_manager, &req.tenant)?;
     let connector_type = parse_mqtt_connector_type(req.connector_type());
     if let Err(e) = connector_config_validator(&connector_type, &req.config) {
         return Err(Status::cancelled(e.to_string()));
@@ -71,6 +118,8 @@ pub async fn create_connector_by_req(
         broker_id: None,
         create_time: now_second(),
         update_time: now_second(),
+        runtime_status: None,
+        tenant: req.tenant.clone(),
     };
     if let Err(e) = storage.create_connector(connector).await {
         return Err(Status::cancelled(e.to_string()));
@@ -82,14 +131,20 @@ pub async fn update_connector_by_req(
     request: Request<MqttUpdateConnectorRequest>,
 ) -> Result<Response<MqttUpdateConnectorReply>, Status> {
     let req = request.into_inner();
-    let connector = match serde_json::from_slice::<MQTTConnector>(&req.connector) {
+    let mut connector = match serde_json::from_slice::<MQTTConnector>(&req.connector) {
         Ok(connector) => connector,
         Err(e) => return Err(Status::cancelled(e.to_string())),
     };
+    // The runtime status is reported by the broker, it is not part of the stored connector.
+    connector.runtime_status = None;
     if let Err(e) = connector_config_validator(&connector.connector_type, &connector.config) {
         return Err(Status::cancelled(e.to_string()));
     };
     let storage = ConnectorStorage::new(client_pool.clone());
+    // The connector keeps the tenant it was created with
+    connector.tenant = get_connector(&storage, &connector.connector_name)
+        .await?
+        .tenant;
     if let Err(e) = storage.update_connector(connector).await {
         return Err(Status::cancelled(e.to_string()));
     };
@@ -103,25 +158,424 @@ pub async fn delete_connector_by_req(
     let req = request.into_inner();
     let config = broker_mqtt_conf();
     let storage = ConnectorStorage::new(client_pool.clone());
+    let connector = get_connector(&storage, &req.connector_name).await?;
+    check_tenant_owner(
+        &req.tenant,
+        &connector.tenant,
+        &format!("Connector {}", req.connector_name),
+    )?;
     if let Err(e) = storage
         .delete_connector(&config.cluster_name, &req.connector_name)
         .await
     {
         return Err(Status::cancelled(e.to_string()));
     };
+    // A connector created later with the same name starts without the offset of a source.
+    if let Err(e) = storage
+        .delete_source_offset(&config.cluster_name, &req.connector_name)
+        .await
+    {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    if let Err(e) = storage
+        .delete_runtime_status(&config.cluster_name, &req.connector_name)
+        .await
+    {
+        return Err(Status::cancelled(e.to_string()));
+    };
     Ok(Response::new(MqttDeleteConnectorReply::default()))
 }
 
+pub async fn pause_connector_by_req(
+    client_pool: &Arc<ClientPool>,
+    request: Request<MqttPauseConnectorRequest>,
+) -> Result<Response<MqttPauseConnectorReply>, Status> {
+    let req = request.into_inner();
+    let storage = ConnectorStorage::new(client_pool.clone());
+    let mut connector = match get_connector(&storage, &req.connector_name).await {
+        Ok(connector) => connector,
+        Err(e) => return Err(Status::cancelled(e.to_string())),
+    };
+
+    // A paused connector is not assigned to any broker, so the broker running it stops it and
+    // the placement center does not schedule it until it is resumed.
+    connector.status = MQTTStatus::Paused;
+    connector.broker_id = None;
+    connector.update_time = now_second();
+    if let Err(e) = storage.update_connector(connector).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(MqttPauseConnectorReply::default()))
+}
+
+pub async fn resume_connector_by_req(
+    client_pool: &Arc<ClientPool>,
+    request: Request<MqttResumeConnectorRequest>,
+) -> Result<Response<MqttResumeConnectorReply>, Status> {
+    let req = request.into_inner();
+    let storage = ConnectorStorage::new(client_pool.clone());
+    let mut connector = match get_connector(&storage, &req.connector_name).await {
+        Ok(connector) => connector,
+        Err(e) => return Err(Status::cancelled(e.to_string())),
+    };
+    if connector.status != MQTTStatus::Paused {
+        return Err(Status::cancelled(
+            MqttBrokerError::ConnectorNotPaused(req.connector_name).to_string(),
+        ));
+    }
+
+    // The connector is scheduled again and continues from its committed offset.
+    connector.status = MQTTStatus::Idle;
+    connector.broker_id = None;
+    connector.update_time = now_second();
+    if let Err(e) = storage.update_connector(connector).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(MqttResumeConnectorReply::default()))
+}
+
+pub async fn reset_connector_offset_by_req<S>(
+    client_pool: &Arc<ClientPool>,
+    message_storage_adapter: &Arc<S>,
+    request: Request<MqttResetConnectorOffsetRequest>,
+) -> Result<Response<MqttResetConnectorOffsetReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    match reset_connector_offset(client_pool, message_storage_adapter, &req).await {
+        Ok(offset) => Ok(Response::new(MqttResetConnectorOffsetReply { offset })),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+// The connector has to be paused and its thread has to have exited, otherwise it would commit
+// the offset of the batch it is delivering over the reset offset.
+async fn reset_connector_offset<S>(
+    client_pool: &Arc<ClientPool>,
+    message_storage_adapter: &Arc<S>,
+    req: &MqttResetConnectorOffsetRequest,
+) -> Result<u64, MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let storage = ConnectorStorage::new(client_pool.clone());
+    let connector = get_connector(&storage, &req.connector_name).await?;
+    if connector.status != MQTTStatus::Paused {
+        return Err(MqttBrokerError::ConnectorNotPaused(
+            connector.connector_name,
+        ));
+    }
+    if !is_connector_reading_topic(&connector)? {
+        return Err(MqttBrokerError::ConnectorOffsetNotResettable(
+            connector.connector_name,
+        ));
+    }
+    if !is_connector_stopped(client_pool, &storage, &connector.connector_name).await? {
+        return Err(MqttBrokerError::ConnectorNotStopped(
+            connector.connector_name,
+        ));
+    }
+
+    // The topic of a sink connector is read with the connector name as the group name.
+    let message_storage = MessageStorage::new(message_storage_adapter.clone());
+    let offset = resolve_connector_offset(
+        &message_storage,
+        &connector.topic_id,
+        &connector.connector_name,
+        req,
+    )
+    .await?;
+    message_storage
+        .commit_group_offset(&connector.connector_name, &connector.topic_id, offset)
+        .await?;
+    Ok(offset)
+}
+
+// The offset the group is reset to. A timestamp after the newest record resets to the end of
+// the topic, the same as Latest.
+async fn resolve_connector_offset<S>(
+    message_storage: &MessageStorage<S>,
+    topic_id: &str,
+    group_name: &str,
+    req: &MqttResetConnectorOffsetRequest,
+) -> Result<u64, MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let timestamp = match req.reset_type() {
+        MqttConnectorOffsetResetType::Earliest => Some(0),
+        MqttConnectorOffsetResetType::Latest => None,
+        MqttConnectorOffsetResetType::Timestamp => Some(req.timestamp),
+        MqttConnectorOffsetResetType::Offset => {
+            let end_offset = message_storage
+                .get_end_offset(
+                    topic_id,
+                    message_storage.get_group_offset(group_name).await?,
+                )
+                .await?;
+            if req.offset > end_offset {
+                return Err(MqttBrokerError::ConnectorOffsetOutOfRange(
+                    req.offset, end_offset,
+                ));
+            }
+            return Ok(req.offset);
+        }
+    };
+
+    if let Some(timestamp) = timestamp {
+        if let Some(offset) = message_storage
+            .get_offset_by_timestamp(topic_id, timestamp)
+            .await?
+        {
+            return Ok(offset);
+        }
+    }
+
+    let committed_offset = message_storage.get_group_offset(group_name).await?;
+    Ok(message_storage
+        .get_end_offset(topic_id, committed_offset)
+        .await?)
+}
+
+// Source connectors and MQTT ingress connectors write into the topics, they do not read the
+// topic of the connector.
+fn is_connector_reading_topic(connector: &MQTTConnector) -> Result<bool, MqttBrokerError> {
+    if connector.connector_type.is_source() {
+        return Ok(false);
+    }
+    if connector.connector_type == ConnectorType::Mqtt {
+        let mqtt_config: MqttConnectorConfig = serde_json::from_str(&connector.config)?;
+        return Ok(mqtt_config.direction == MqttConnectorDirection::Egress);
+    }
+    Ok(true)
+}
+
+// The thread of the connector reports that it exited in the runtime status. A thread on a
+// broker that is no longer in the cluster has exited as well.
+async fn is_connector_stopped(
+    client_pool: &Arc<ClientPool>,
+    storage: &ConnectorStorage,
+    connector_name: &str,
+) -> Result<bool, MqttBrokerError> {
+    let config = broker_mqtt_conf();
+    let Some(runtime_status) = storage
+        .get_runtime_status(&config.cluster_name, connector_name)
+        .await?
+    else {
+        return Ok(true);
+    };
+    if runtime_status.stopped {
+        return Ok(true);
+    }
+
+    let cluster_storage = ClusterStorage::new(client_pool.clone());
+    let is_broker_alive = cluster_storage
+        .node_list()
+        .await?
+        .iter()
+        .any(|node| node.node_id == runtime_status.broker_id);
+    Ok(!is_broker_alive)
+}
+
+async fn get_connector(
+    storage: &ConnectorStorage,
+    connector_name: &str,
+) -> Result<MQTTConnector, MqttBrokerError> {
+    storage
+        .list_connector(connector_name)
+        .await?
+        .into_iter()
+        .find(|connector| connector.connector_name == connector_name)
+        .ok_or(MqttBrokerError::ConnectorNotExists(
+            connector_name.to_owned(),
+        ))
+}
+
 fn connector_config_validator(
     connector_type: &ConnectorType,
     config: &str,
 ) -> Result<(), MqttBrokerError> {
+    connector_common_config_validator(config)?;
     match connector_type {
         ConnectorType::LocalFile => {
             let _file_config: LocalFileConnectorConfig = serde_json::from_str(config)?;
         }
         ConnectorType::Kafka => {
-            let _kafka_config: KafkaConnectorConfig = serde_json::from_str(config)?;
+            let kafka_config: KafkaConnectorConfig = serde_json::from_str(config)?;
+            if kafka_config.batch_size == 0 {
+                return Err(MqttBrokerError::CommonError(
+                    "batch_size of the kafka connector must be greater than 0".to_string(),
+                ));
+            }
+        }
+        ConnectorType::Mqtt => {
+            let mqtt_config: MqttConnectorConfig = serde_json::from_str(config)?;
+            if mqtt_config.server.is_empty() || mqtt_config.remote_topic.is_empty() {
+                return Err(MqttBrokerError::CommonError(
+                    "server and remote_topic of the mqtt connector must not be empty".to_string(),
+                ));
+            }
+            if mqtt_config.qos.is_some_and(|qos| qos > 2) {
+                return Err(MqttBrokerError::CommonError(
+                    "qos of the mqtt connector must be 0, 1 or 2".to_string(),
+                ));
+            }
+            if mqtt_config.batch_size == 0
+                || mqtt_config.reconnect_min_interval_ms == 0
+                || mqtt_config.reconnect_min_interval_ms > mqtt_config.reconnect_max_interval_ms
+            {
+                return Err(MqttBrokerError::CommonError(
+                    "batch_size and reconnect intervals of the mqtt connector are invalid"
+                        .to_string(),
+                ));
+            }
+        }
+        ConnectorType::Http => {
+            let http_config: HttpConnectorConfig = serde_json::from_str(config)?;
+            if !http_config.url.starts_with("http://") && !http_config.url.starts_with("https://") {
+                return Err(MqttBrokerError::CommonError(
+                    "url of the http connector must start with http:// or https://".to_string(),
+                ));
+            }
+            if reqwest::Method::from_bytes(http_config.method.to_uppercase().as_bytes()).is_err() {
+                return Err(MqttBrokerError::CommonError(format!(
+                    "invalid method {} of the http connector",
+                    http_config.method
+                )));
+            }
+            if http_config.batch_size == 0 || http_config.max_concurrency == 0 {
+                return Err(MqttBrokerError::CommonError(
+                    "batch_size and max_concurrency of the http connector must be greater than 0"
+                        .to_string(),
+                ));
+            }
+        }
+        ConnectorType::MySQL | ConnectorType::PostgreSQL => {
+            let sql_config: SqlConnectorConfig = serde_json::from_str(config)?;
+            let schemes: &[&str] = if *connector_type == ConnectorType::MySQL {
+                &["mysql://"]
+            } else {
+                &["postgres://", "postgresql://"]
+            };
+            if !schemes
+                .iter()
+                .any(|scheme| sql_config.url.starts_with(scheme))
+            {
+                return Err(MqttBrokerError::CommonError(format!(
+                    "url of the {} connector must start with {}",
+                    connector_type,
+                    schemes.join(" or ")
+                )));
+            }
+            if sql_config.sql.is_empty() || sql_config.batch_size == 0 || sql_config.pool_size == 0
+            {
+                return Err(MqttBrokerError::CommonError(format!(
+                    "sql, batch_size and pool_size of the {} connector are invalid",
+                    connector_type
+                )));
+            }
+        }
+        ConnectorType::S3 => {
+            let s3_config: S3ConnectorConfig = serde_json::from_str(config)?;
+            if s3_config.endpoint.is_empty() || s3_config.bucket.is_empty() {
+                return Err(MqttBrokerError::CommonError(
+                    "endpoint and bucket of the s3 connector must not be empty".to_string(),
+                ));
+            }
+            if s3_config.batch_size == 0
+                || s3_config.max_file_size == 0
+                || s3_config.roll_interval_secs == 0
+            {
+                return Err(MqttBrokerError::CommonError(
+                    "batch_size, max_file_size and roll_interval_secs of the s3 connector must be greater than 0"
+                        .to_string(),
+                ));
+            }
+        }
+        ConnectorType::Redis => {
+            let redis_config: RedisConnectorConfig = serde_json::from_str(config)?;
+            if !redis_config.url.starts_with("redis://")
+                && !redis_config.url.starts_with("rediss://")
+            {
+                return Err(MqttBrokerError::CommonError(
+                    "url of the redis connector must start with redis:// or rediss://".to_string(),
+                ));
+            }
+            if redis_config.key.is_empty() {
+                return Err(MqttBrokerError::CommonError(
+                    "key of the redis connector must not be empty".to_string(),
+                ));
+            }
+            if redis_config.batch_size == 0 || redis_config.ttl_secs == Some(0) {
+                return Err(MqttBrokerError::CommonError(
+                    "batch_size and ttl_secs of the redis connector must be greater than 0"
+                        .to_string(),
+                ));
+            }
+        }
+        ConnectorType::KafkaSource => {
+            let source_config: KafkaSourceConnectorConfig = serde_json::from_str(config)?;
+            if source_config.bootstrap_servers.is_empty()
+                || source_config.topic.is_empty()
+                || source_config.mqtt_topic.is_empty()
+            {
+                return Err(MqttBrokerError::CommonError(
+                    "bootstrap_servers, topic and mqtt_topic of the kafka source connector must not be empty"
+                        .to_string(),
+                ));
+            }
+            if source_config.qos > 2 || source_config.offset_commit_interval_ms == 0 {
+                return Err(MqttBrokerError::CommonError(
+                    "qos of the kafka source connector must be 0, 1 or 2, and offset_commit_interval_ms must be greater than 0"
+                        .to_string(),
+                ));
+            }
+        }
+        ConnectorType::LocalFileSource => {
+            let source_config: LocalFileSourceConnectorConfig = serde_json::from_str(config)?;
+            if source_config.path.is_empty() || source_config.mqtt_topic.is_empty() {
+                return Err(MqttBrokerError::CommonError(
+                    "path and mqtt_topic of the file source connector must not be empty"
+                        .to_string(),
+                ));
+            }
+            if source_config.qos > 2 || source_config.batch_size == 0 {
+                return Err(MqttBrokerError::CommonError(
+                    "qos of the file source connector must be 0, 1 or 2, and batch_size must be greater than 0"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn connector_common_config_validator(config: &str) -> Result<(), MqttBrokerError> {
+    let common_config: ConnectorCommonConfig = serde_json::from_str(config)?;
+    let retry = &common_config.retry;
+    if retry.max_retry_times == 0
+        || retry.retry_backoff_ms == 0
+        || retry.retry_backoff_ms > retry.max_retry_backoff_ms
+    {
+        return Err(MqttBrokerError::CommonError(
+            "max_retry_times and retry_backoff_ms of the connector must be greater than 0, and retry_backoff_ms must not be greater than max_retry_backoff_ms"
+                .to_string(),
+        ));
+    }
+    if !common_config.dead_letter_topic.is_empty() {
+        topic_name_validator(&common_config.dead_letter_topic)?;
+    }
+    for transform in common_config.transforms.iter() {
+        let is_valid = match transform {
+            ConnectorTransform::Extract { field } => !field.is_empty(),
+            ConnectorTransform::Rename { from, to } => !from.is_empty() && !to.is_empty(),
+            ConnectorTransform::Format { .. } => true,
+        };
+        if !is_valid {
+            return Err(MqttBrokerError::CommonError(
+                "the fields of the transforms of the connector must not be empty".to_string(),
+            ));
         }
     }
     Ok(())
@@ -131,5 +585,95 @@ fn parse_mqtt_connector_type(connector_type: MqttConnectorType) -> ConnectorType
     match connector_type {
         MqttConnectorType::File => ConnectorType::LocalFile,
         MqttConnectorType::Kafka => ConnectorType::Kafka,
+        MqttConnectorType::Mqtt => ConnectorType::Mqtt,
+        MqttConnectorType::Http => ConnectorType::Http,
+        MqttConnectorType::Mysql => ConnectorType::MySQL,
+        MqttConnectorType::Postgresql => ConnectorType::PostgreSQL,
+        MqttConnectorType::S3 => ConnectorType::S3,
+        MqttConnectorType::Redis => ConnectorType::Redis,
+        MqttConnectorType::KafkaSource => ConnectorType::KafkaSource,
+        MqttConnectorType::FileSource => ConnectorType::LocalFileSource,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_base::{
+        config::broker_mqtt::{init_broker_mqtt_conf_by_config, BrokerMqttConfig},
+        tools::unique_id,
+    };
+    use metadata_struct::adapter::record::Record;
+    use protocol::broker_mqtt::broker_mqtt_admin::{
+        MqttConnectorOffsetResetType, MqttResetConnectorOffsetRequest,
+    };
+    use storage_adapter::memory::MemoryStorageAdapter;
+
+    use super::resolve_connector_offset;
+    use crate::storage::message::MessageStorage;
+
+    async fn resolve(
+        message_storage: &MessageStorage<MemoryStorageAdapter>,
+        reset_type: MqttConnectorOffsetResetType,
+        timestamp: u64,
+        offset: u64,
+    ) -> u64 {
+        let req = MqttResetConnectorOffsetRequest {
+            connector_name: "test_connector".to_string(),
+            reset_type: reset_type as i32,
+            timestamp,
+            offset,
+        };
+        resolve_connector_offset(message_storage, "test_topic", "test_connector", &req)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn resolve_connector_offset_test() {
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: unique_id(),
+            ..Default::default()
+        });
+
+        let message_storage = MessageStorage::new(Arc::new(MemoryStorageAdapter::new()));
+        let mut records = Vec::new();
+        for i in 0..5 {
+            let mut record = Record::build_str(format!("data-{}", i));
+            record.timestamp = 100 + i;
+            records.push(record);
+        }
+        message_storage
+            .append_topic_message("test_topic", records)
+            .await
+            .unwrap();
+        message_storage
+            .commit_group_offset("test_connector", "test_topic", 1)
+            .await
+            .unwrap();
+
+        let storage = &message_storage;
+        assert_eq!(
+            resolve(storage, MqttConnectorOffsetResetType::Earliest, 0, 0).await,
+            0
+        );
+        assert_eq!(
+            resolve(storage, MqttConnectorOffsetResetType::Latest, 0, 0).await,
+            5
+        );
+        assert_eq!(
+            resolve(storage, MqttConnectorOffsetResetType::Timestamp, 102, 0).await,
+            2
+        );
+        // No record was written after the timestamp.
+        assert_eq!(
+            resolve(storage, MqttConnectorOffsetResetType::Timestamp, 200, 0).await,
+            5
+        );
+        assert_eq!(
+            resolve(storage, MqttConnectorOffsetResetType::Offset, 0, 3).await,
+            3
+        );
     }
 }
@@ -0,0 +1,301 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::max;
+use std::sync::Arc;
+
+use common_base::config::broker_mqtt::broker_mqtt_conf;
+use metadata_struct::mqtt::topic::MqttTopic;
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    DeadLetterMessageRaw, ListDeadLetterMessageReply, ListDeadLetterMessageRequest,
+    RedriveDeadLetterMessageReply, RedriveDeadLetterMessageRequest,
+};
+use storage_adapter::storage::StorageAdapter;
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::server::connection_manager::ConnectionManager;
+use crate::storage::message::MessageStorage;
+use crate::subscribe::dead_letter::{dead_letter_topic_name, DeadLetterMessage};
+use crate::subscribe::exclusive_push::exclusive_publish_message;
+use crate::subscribe::sub_common::{get_pkid, min_qos};
+use crate::subscribe::subscriber::{SubPublishParam, Subscriber};
+
+pub async fn list_dead_letter_message_by_req<S>(
+    cache_manager: &Arc<CacheManager>,
+    message_storage_adapter: &Arc<S>,
+    request: Request<ListDeadLetterMessageRequest>,
+) -> Result<Response<ListDeadLetterMessageReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    let topic = get_dead_letter_topic(cache_manager, &req.topic_name)?;
+    let message_storage = MessageStorage::new(message_storage_adapter.clone());
+    let messages =
+        read_dead_letter_message(&message_storage, &topic.topic_id, req.offset, req.num).await?;
+
+    let messages = messages
+        .into_iter()
+        .map(|dead_letter| DeadLetterMessageRaw {
+            offset: dead_letter.offset,
+            original_topic: dead_letter.original_topic,
+            client_id: dead_letter.client_id,
+            reason: dead_letter.reason,
+            attempts: dead_letter.attempts,
+            payload: dead_letter.message.payload.to_vec(),
+            create_time: dead_letter.message.create_time,
+        })
+        .collect();
+
+    Ok(Response::new(ListDeadLetterMessageReply { messages }))
+}
+
+// Each message is sent again only to the client its delivery failed for, which has to be
+// connected to this broker. The offset after the last redriven message is committed for the
+// dead letter topic and redrive starts from it at the earliest, so a message is not redriven
+// twice. Redrive stops at the first failure, the reply carries the number of messages
+// redriven before it and the error.
+pub async fn redrive_dead_letter_message_by_req<S>(
+    cache_manager: &Arc<CacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    message_storage_adapter: &Arc<S>,
+    request: Request<RedriveDeadLetterMessageRequest>,
+) -> Result<Response<RedriveDeadLetterMessageReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    let topic = get_dead_letter_topic(cache_manager, &req.topic_name)?;
+    let message_storage = MessageStorage::new(message_storage_adapter.clone());
+    let group_name = redrive_group_name(&topic.topic_name);
+    let redrive_offset = message_storage
+        .get_group_offset(&group_name)
+        .await
+        .map_err(|e| Status::cancelled(e.to_string()))?;
+    let messages = read_dead_letter_message(
+        &message_storage,
+        &topic.topic_id,
+        max(req.offset, redrive_offset),
+        req.num,
+    )
+    .await?;
+
+    let (stop_sx, _) = broadcast::channel(1);
+    let mut redrive_num = 0;
+    for dead_letter in messages {
+        if let Err(e) =
+            redrive_message(cache_manager, connection_manager, &stop_sx, &dead_letter).await
+        {
+            return Ok(Response::new(RedriveDeadLetterMessageReply {
+                redrive_num,
+                error: e.to_string(),
+            }));
+        }
+        redrive_num += 1;
+
+        if let Err(e) = message_storage
+            .commit_group_offset(&group_name, &topic.topic_id, dead_letter.offset + 1)
+            .await
+        {
+            return Ok(Response::new(RedriveDeadLetterMessageReply {
+                redrive_num,
+                error: e.to_string(),
+            }));
+        }
+    }
+
+    Ok(Response::new(RedriveDeadLetterMessageReply {
+        redrive_num,
+        error: "".to_string(),
+    }))
+}
+
+async fn redrive_message(
+    cache_manager: &Arc<CacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    stop_sx: &broadcast::Sender<bool>,
+    dead_letter: &DeadLetterMessage,
+) -> Result<(), MqttBrokerError> {
+    let client_id = &dead_letter.client_id;
+    let protocol = cache_manager
+        .get_connect_id(client_id)
+        .and_then(|connect_id| connection_manager.get_connect_protocol(connect_id))
+        .ok_or_else(|| MqttBrokerError::ClientNoAvailableCOnnection(client_id.to_owned()))?;
+
+    let (mut publish, properties) = dead_letter.build_redrive_publish();
+    let cluster = cache_manager.get_cluster_info();
+    let pkid = get_pkid();
+    publish.qos = min_qos(cluster.protocol.max_qos, publish.qos);
+    publish.pkid = pkid;
+
+    let subscriber = Subscriber {
+        protocol,
+        client_id: client_id.to_owned(),
+        topic_name: dead_letter.original_topic.clone(),
+        ..Default::default()
+    };
+    let sub_pub_param = SubPublishParam::new(
+        subscriber,
+        publish,
+        properties,
+        dead_letter.message.create_time as u128,
+        "".to_string(),
+        pkid,
+    );
+    exclusive_publish_message(cache_manager, connection_manager, &sub_pub_param, stop_sx).await
+}
+
+fn redrive_group_name(dead_letter_topic: &str) -> String {
+    format!("$dead-letter-redrive/{}", dead_letter_topic)
+}
+
+fn get_dead_letter_topic(
+    cache_manager: &Arc<CacheManager>,
+    topic_name: &str,
+) -> Result<MqttTopic, MqttBrokerError> {
+    let conf = broker_mqtt_conf();
+    let dead_letter_topic = dead_letter_topic_name(&conf.dead_letter.topic_prefix, topic_name);
+    cache_manager
+        .get_topic_by_name(&dead_letter_topic)
+        .ok_or(MqttBrokerError::TopicDoesNotExist(dead_letter_topic))
+}
+
+async fn read_dead_letter_message<S>(
+    message_storage: &MessageStorage<S>,
+    topic_id: &str,
+    offset: u64,
+    num: u64,
+) -> Result<Vec<DeadLetterMessage>, MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let records = message_storage
+        .read_topic_message(topic_id, offset, num)
+        .await?;
+
+    let mut results = Vec::new();
+    for record in records {
+        results.push(DeadLetterMessage::decode(record)?);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use common_base::config::broker_mqtt::{
+        broker_mqtt_conf, init_broker_mqtt_conf_by_config, BrokerMqttConfig,
+    };
+    use common_base::tools::{now_second, unique_id};
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::mqtt::topic::MqttTopic;
+    use protocol::broker_mqtt::broker_mqtt_admin::RedriveDeadLetterMessageRequest;
+    use protocol::mqtt::common::{Publish, QoS};
+    use storage_adapter::memory::MemoryStorageAdapter;
+    use tonic::Request;
+
+    use super::{redrive_dead_letter_message_by_req, redrive_group_name};
+    use crate::handler::cache::CacheManager;
+    use crate::server::connection_manager::ConnectionManager;
+    use crate::storage::message::MessageStorage;
+    use crate::subscribe::dead_letter::{build_dead_letter_record, dead_letter_topic_name};
+    use crate::subscribe::subscriber::{SubPublishParam, Subscriber};
+
+    #[tokio::test]
+    async fn redrive_dead_letter_message_test() {
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: unique_id(),
+            ..Default::default()
+        });
+        let conf = broker_mqtt_conf();
+        let cache_manager = Arc::new(CacheManager::new(
+            Arc::new(ClientPool::new(1)),
+            "test".to_string(),
+        ));
+        let connection_manager = Arc::new(ConnectionManager::new(cache_manager.clone()));
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let message_storage = MessageStorage::new(storage_adapter.clone());
+
+        let topic_name = dead_letter_topic_name(&conf.dead_letter.topic_prefix, "/a/b");
+        let topic = MqttTopic::new(unique_id(), "test".to_string(), topic_name.clone());
+        cache_manager.add_topic(&topic_name, &topic);
+
+        let sub_pub_param = SubPublishParam {
+            subscribe: Subscriber {
+                client_id: "client-1".to_string(),
+                topic_name: "/a/b".to_string(),
+                ..Default::default()
+            },
+            publish: Publish {
+                qos: QoS::AtLeastOnce,
+                topic: Bytes::from("/a/b"),
+                payload: Bytes::from("payload"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut records = Vec::new();
+        for _ in 0..2 {
+            records.push(
+                build_dead_letter_record(
+                    &sub_pub_param,
+                    &topic_name,
+                    "timeout",
+                    3,
+                    now_second() + 10,
+                )
+                .unwrap(),
+            );
+        }
+        message_storage
+            .append_topic_message(&topic.topic_id, records)
+            .await
+            .unwrap();
+
+        // The first message was redriven before, redrive starts after it.
+        let group_name = redrive_group_name(&topic_name);
+        message_storage
+            .commit_group_offset(&group_name, &topic.topic_id, 1)
+            .await
+            .unwrap();
+
+        let request = Request::new(RedriveDeadLetterMessageRequest {
+            topic_name: "/a/b".to_string(),
+            offset: 0,
+            num: 10,
+        });
+        let reply = redrive_dead_letter_message_by_req(
+            &cache_manager,
+            &connection_manager,
+            &storage_adapter,
+            request,
+        )
+        .await
+        .unwrap()
+        .into_inner();
+
+        // client-1 is not connected, the redrive stops at the second message.
+        assert_eq!(reply.redrive_num, 0);
+        assert!(reply.error.contains("client-1"));
+        assert_eq!(
+            message_storage.get_group_offset(&group_name).await.unwrap(),
+            1
+        );
+    }
+}
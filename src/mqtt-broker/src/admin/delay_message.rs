@@ -0,0 +1,204 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use delay_message::{DelayMessageManager, DelayMessageRecord};
+use metadata_struct::mqtt::message::MqttMessage;
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CancelDelayMessageReply, CancelDelayMessageRequest, DelayMessageRaw, GetDelayMessageReply,
+    GetDelayMessageRequest, ListDelayMessageReply, ListDelayMessageRequest,
+};
+use storage_adapter::storage::StorageAdapter;
+use tonic::{Request, Response, Status};
+
+use crate::handler::error::MqttBrokerError;
+
+pub async fn list_delay_message_by_req<S>(
+    delay_message_manager: &Arc<DelayMessageManager<S>>,
+    request: Request<ListDelayMessageRequest>,
+) -> Result<Response<ListDelayMessageReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    let messages = filter_delay_message(
+        delay_message_manager.list_delay_message(),
+        &req.topic_name,
+        &req.client_id,
+    );
+
+    let total = messages.len() as u64;
+    let num = if req.num == 0 { total } else { req.num };
+    let messages = messages
+        .into_iter()
+        .skip(req.offset as usize)
+        .take(num as usize)
+        .map(delay_message_raw)
+        .collect();
+
+    Ok(Response::new(ListDelayMessageReply { messages, total }))
+}
+
+pub async fn get_delay_message_by_req<S>(
+    delay_message_manager: &Arc<DelayMessageManager<S>>,
+    request: Request<GetDelayMessageRequest>,
+) -> Result<Response<GetDelayMessageReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    let delay_message_record =
+        if let Some(record) = delay_message_manager.get_delay_message(&req.id) {
+            record
+        } else {
+            return Err(Status::cancelled(
+                MqttBrokerError::DelayMessageNotExists(req.id).to_string(),
+            ));
+        };
+
+    let record = match delay_message_manager
+        .read_delay_message(&delay_message_record)
+        .await
+    {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            return Err(Status::cancelled(
+                MqttBrokerError::DelayMessageNotExists(req.id).to_string(),
+            ));
+        }
+        Err(e) => return Err(Status::cancelled(e.to_string())),
+    };
+    let message =
+        MqttMessage::decode_record(record).map_err(|e| Status::cancelled(e.to_string()))?;
+
+    Ok(Response::new(GetDelayMessageReply {
+        message: Some(delay_message_raw(delay_message_record)),
+        qos: message.qos as u32,
+        retain: message.retain,
+        payload: message.payload.to_vec(),
+        user_properties: message
+            .user_properties
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect(),
+    }))
+}
+
+pub async fn cancel_delay_message_by_req<S>(
+    delay_message_manager: &Arc<DelayMessageManager<S>>,
+    request: Request<CancelDelayMessageRequest>,
+) -> Result<Response<CancelDelayMessageReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    let ids = if !req.id.is_empty() {
+        if delay_message_manager.get_delay_message(&req.id).is_none() {
+            return Err(Status::cancelled(
+                MqttBrokerError::DelayMessageNotExists(req.id).to_string(),
+            ));
+        }
+        vec![req.id]
+    } else if !req.topic_name.is_empty() || !req.client_id.is_empty() {
+        filter_delay_message(
+            delay_message_manager.list_delay_message(),
+            &req.topic_name,
+            &req.client_id,
+        )
+        .into_iter()
+        .map(|record| record.id())
+        .collect()
+    } else {
+        return Err(Status::cancelled(
+            CommonError::ParameterCannotBeNull("id, topic_name or client_id".to_string())
+                .to_string(),
+        ));
+    };
+
+    let mut cancel_num = 0;
+    for id in ids {
+        // A message may be published while it is being cancelled.
+        if delay_message_manager
+            .cancel_delay_message(&id)
+            .await
+            .map_err(|e| Status::cancelled(e.to_string()))?
+            .is_some()
+        {
+            cancel_num += 1;
+        }
+    }
+    Ok(Response::new(CancelDelayMessageReply { cancel_num }))
+}
+
+fn filter_delay_message(
+    records: Vec<DelayMessageRecord>,
+    topic_name: &str,
+    client_id: &str,
+) -> Vec<DelayMessageRecord> {
+    records
+        .into_iter()
+        .filter(|record| topic_name.is_empty() || record.topic == topic_name)
+        .filter(|record| client_id.is_empty() || record.client_id == client_id)
+        .collect()
+}
+
+fn delay_message_raw(record: DelayMessageRecord) -> DelayMessageRaw {
+    DelayMessageRaw {
+        id: record.id(),
+        topic_name: record.topic,
+        client_id: record.client_id,
+        delay_timestamp: record.delay_timestamp,
+        create_time: record.create_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use delay_message::DelayMessageRecord;
+
+    use super::filter_delay_message;
+
+    #[test]
+    fn filter_delay_message_test() {
+        let records = vec![
+            DelayMessageRecord {
+                offset: 1,
+                topic: "/a/b".to_string(),
+                client_id: "c1".to_string(),
+                ..Default::default()
+            },
+            DelayMessageRecord {
+                offset: 2,
+                topic: "/a/b".to_string(),
+                client_id: "c2".to_string(),
+                ..Default::default()
+            },
+            DelayMessageRecord {
+                offset: 3,
+                topic: "/x".to_string(),
+                client_id: "c1".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(filter_delay_message(records.clone(), "", "").len(), 3);
+        assert_eq!(filter_delay_message(records.clone(), "/a/b", "").len(), 2);
+        assert_eq!(filter_delay_message(records.clone(), "", "c1").len(), 2);
+
+        let results = filter_delay_message(records, "/a/b", "c1");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].offset, 1);
+    }
+}
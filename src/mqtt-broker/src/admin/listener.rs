@@ -0,0 +1,95 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    ListListenerReply, ListListenerRequest, ListenerRaw, StartListenerReply, StartListenerRequest,
+    StopListenerReply, StopListenerRequest,
+};
+use storage_adapter::storage::StorageAdapter;
+use tonic::{Request, Response, Status};
+
+use crate::server::listener::ListenerManager;
+
+pub async fn list_listener_by_req<S>(
+    listener_manager: &Arc<ListenerManager<S>>,
+    _: Request<ListListenerRequest>,
+) -> Result<Response<ListListenerReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let listeners = listener_manager
+        .list_listener()
+        .into_iter()
+        .map(|state| ListenerRaw {
+            name: state.listener.name,
+            protocol: state.listener.protocol,
+            bind: state.listener.bind,
+            enable: state.listener.enable,
+            running: state.running,
+            connection_num: state.connection_num as u64,
+            max_connections: state.listener.max_connections as u64,
+            authentication: state.listener.authentication,
+            mountpoint: state.listener.mountpoint,
+            proxy_protocol: state.listener.proxy_protocol,
+        })
+        .collect();
+
+    Ok(Response::new(ListListenerReply { listeners }))
+}
+
+pub async fn start_listener_by_req<S>(
+    listener_manager: &Arc<ListenerManager<S>>,
+    request: Request<StartListenerRequest>,
+) -> Result<Response<StartListenerReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    match listener_manager.start_listener(&req.name).await {
+        Ok(()) => Ok(Response::new(StartListenerReply::default())),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn stop_listener_by_req<S>(
+    listener_manager: &Arc<ListenerManager<S>>,
+    request: Request<StopListenerRequest>,
+) -> Result<Response<StopListenerReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    match listener_manager.stop_listener(&req.name).await {
+        Ok(connection_num) => Ok(Response::new(StopListenerReply {
+            connection_num: connection_num as u64,
+        })),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn reload_listener_cert_by_req<S>(
+    listener_manager: &Arc<ListenerManager<S>>,
+    request: Request<ReloadListenerCertRequest>,
+) -> Result<Response<ReloadListenerCertReply>, Status>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let req = request.into_inner();
+    match listener_manager.reload_tls_cert(&req.name) {
+        Ok(listeners) => Ok(Response::new(ReloadListenerCertReply { listeners })),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
@@ -14,24 +14,34 @@
 
 pub mod acl;
 pub mod connector;
+pub mod dead_letter;
+pub mod delay_message;
+pub mod listener;
+pub mod rule;
+pub mod scheduled_message;
 pub mod schema;
 pub mod subscribe;
+pub mod tenant;
 pub mod topic;
 pub mod user;
+pub mod wasm_plugin;
 
 use crate::handler::cache::CacheManager;
+use crate::handler::drain::{drain_node, undrain_node, NodeDrain};
 use crate::handler::flapping_detect::enable_flapping_detect;
 use crate::observability::slow::sub::{enable_slow_sub, read_slow_sub_record, SlowSubData};
 use crate::server::connection_manager::ConnectionManager;
+use crate::subscribe::subscribe_manager::SubscribeManager;
 use crate::{handler::error::MqttBrokerError, storage::cluster::ClusterStorage};
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use common_base::tools::serialize_value;
 use common_base::utils::file_utils::get_project_root;
 use grpc_clients::pool::ClientPool;
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    ClusterStatusReply, EnableFlappingDetectReply, EnableFlappingDetectRequest,
-    EnableSlowSubScribeReply, EnableSlowSubscribeRequest, ListConnectionRaw, ListConnectionReply,
-    ListSlowSubScribeRaw, ListSlowSubscribeReply, ListSlowSubscribeRequest,
+    ClusterStatusReply, DrainNodeReply, DrainNodeRequest, EnableFlappingDetectReply,
+    EnableFlappingDetectRequest, EnableSlowSubScribeReply, EnableSlowSubscribeRequest,
+    ListConnectionRaw, ListConnectionReply, ListSlowSubScribeRaw, ListSlowSubscribeReply,
+    ListSlowSubscribeRequest, UndrainNodeReply, UndrainNodeRequest,
 };
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
@@ -53,6 +63,63 @@ pub async fn cluster_status_by_req(
     })
 }
 
+// The node is drained in the background, the reply returns the number of connections to be disconnected.
+pub async fn drain_node_by_req(
+    cache_manager: &Arc<CacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<DrainNodeRequest>,
+) -> Result<Response<DrainNodeReply>, Status> {
+    let req = request.into_inner();
+    let drain = match NodeDrain::build(
+        client_pool,
+        req.server_moved,
+        &req.server_reference,
+        req.batch_size,
+        req.batch_interval_ms,
+    )
+    .await
+    {
+        Ok(drain) => drain,
+        Err(e) => return Err(Status::cancelled(e.to_string())),
+    };
+    // The node stops accepting new connections before the reply is returned.
+    if !cache_manager.try_set_node_drain(drain.clone()) {
+        return Err(Status::cancelled(
+            MqttBrokerError::NodeIsDraining(broker_mqtt_conf().broker_id).to_string(),
+        ));
+    }
+
+    let connection_num = cache_manager.connection_info.len() as u64;
+    let cache_manager = cache_manager.clone();
+    let connection_manager = connection_manager.clone();
+    let subscribe_manager = subscribe_manager.clone();
+    let client_pool = client_pool.clone();
+    tokio::spawn(async move {
+        drain_node(
+            &cache_manager,
+            &connection_manager,
+            &subscribe_manager,
+            &client_pool,
+            drain,
+        )
+        .await;
+    });
+
+    Ok(Response::new(DrainNodeReply { connection_num }))
+}
+
+pub fn undrain_node_by_req(
+    cache_manager: &Arc<CacheManager>,
+    _request: Request<UndrainNodeRequest>,
+) -> Result<Response<UndrainNodeReply>, Status> {
+    match undrain_node(cache_manager) {
+        Ok(()) => Ok(Response::new(UndrainNodeReply::default())),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
 pub async fn enable_flapping_detect_by_req(
     cache_manager: &Arc<CacheManager>,
     request: Request<EnableFlappingDetectRequest>,
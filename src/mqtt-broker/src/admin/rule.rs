@@ -0,0 +1,153 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::rule::{MqttRule, MqttRuleAction, MqttRuleActionType};
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CreateRuleReply, CreateRuleRequest, DeleteRuleReply, DeleteRuleRequest, ListRuleReply,
+    ListRuleRequest, RuleRaw,
+};
+use tonic::{Request, Response, Status};
+
+use crate::bridge::manager::ConnectorManager;
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::topic::topic_name_validator;
+use crate::observability::metrics::rule::get_rule_metrics;
+use crate::rule_engine::Rule;
+use crate::storage::topic::TopicStorage;
+
+pub async fn create_rule_by_req(
+    cache_manager: &Arc<CacheManager>,
+    connector_manager: &Arc<ConnectorManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateRuleRequest>,
+) -> Result<Response<CreateRuleReply>, Status> {
+    let req = request.into_inner();
+    if cache_manager.get_rule(&req.rule_name).is_some() {
+        return Err(Status::cancelled(
+            MqttBrokerError::RuleAlreadyExists(req.rule_name).to_string(),
+        ));
+    }
+
+    let actions = serde_json::from_str::<Vec<MqttRuleAction>>(&req.actions)
+        .map_err(|e| Status::cancelled(e.to_string()))?;
+    validate_rule_actions(connector_manager, &actions)
+        .map_err(|e| Status::cancelled(e.to_string()))?;
+
+    let rule = MqttRule {
+        cluster: cache_manager.cluster_name.clone(),
+        rule_name: req.rule_name,
+        sql: req.sql,
+        actions,
+        desc: req.desc,
+        create_time: now_second(),
+    };
+    // Reject a rule with an invalid SQL before it is stored.
+    Rule::new(rule.clone()).map_err(|e| Status::cancelled(e.to_string()))?;
+
+    let topic_storage = TopicStorage::new(client_pool.clone());
+    match topic_storage.create_rule(rule.clone()).await {
+        Ok(_) => {
+            if let Err(e) = cache_manager.add_rule(rule) {
+                return Err(Status::cancelled(e.to_string()));
+            }
+            Ok(Response::new(CreateRuleReply::default()))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn delete_rule_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<DeleteRuleRequest>,
+) -> Result<Response<DeleteRuleReply>, Status> {
+    let req = request.into_inner();
+    if cache_manager.get_rule(&req.rule_name).is_none() {
+        return Err(Status::cancelled(
+            MqttBrokerError::RuleNotExists(req.rule_name).to_string(),
+        ));
+    }
+
+    let topic_storage = TopicStorage::new(client_pool.clone());
+    match topic_storage.delete_rule(req.rule_name.clone()).await {
+        Ok(_) => {
+            cache_manager.delete_rule(&req.rule_name);
+            Ok(Response::new(DeleteRuleReply::default()))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub fn list_rule_by_req(
+    cache_manager: &Arc<CacheManager>,
+    request: Request<ListRuleRequest>,
+) -> Result<Response<ListRuleReply>, Status> {
+    let req = request.into_inner();
+    let mut rules = Vec::new();
+    for rule in cache_manager.rule_info.iter() {
+        let info = &rule.info;
+        if !req.rule_name.is_empty() && info.rule_name != req.rule_name {
+            continue;
+        }
+        let actions =
+            serde_json::to_string(&info.actions).map_err(|e| Status::cancelled(e.to_string()))?;
+        let (matched_num, passed_num, failed_num) = get_rule_metrics(&info.rule_name);
+        rules.push(RuleRaw {
+            rule_name: info.rule_name.clone(),
+            sql: info.sql.clone(),
+            actions,
+            desc: info.desc.clone(),
+            matched_num,
+            passed_num,
+            failed_num,
+            create_time: info.create_time,
+        });
+    }
+
+    Ok(Response::new(ListRuleReply { rules }))
+}
+
+fn validate_rule_actions(
+    connector_manager: &Arc<ConnectorManager>,
+    actions: &[MqttRuleAction],
+) -> Result<(), MqttBrokerError> {
+    for action in actions.iter() {
+        match action.action_type {
+            MqttRuleActionType::Republish => {
+                // Topics with ${field} placeholders can only be checked once they are rendered.
+                if !action.target.contains("${") {
+                    topic_name_validator(&action.target)?;
+                }
+                if action.qos > 2 {
+                    return Err(MqttBrokerError::RuleActionInvalid(format!(
+                        "invalid qos {} of the republish action",
+                        action.qos
+                    )));
+                }
+            }
+            MqttRuleActionType::Connector => {
+                if connector_manager.get_connector(&action.target).is_none() {
+                    return Err(MqttBrokerError::ConnectorNotExists(action.target.clone()));
+                }
+            }
+            MqttRuleActionType::Drop => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CreateScheduledMessageReply, CreateScheduledMessageRequest, DeleteScheduledMessageReply,
+    DeleteScheduledMessageRequest, ListScheduledMessageReply, ListScheduledMessageRequest,
+    ScheduledMessageRaw,
+};
+use protocol::mqtt::common::qos;
+use tonic::{Request, Response, Status};
+
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::topic::topic_name_validator;
+use crate::storage::scheduled_message::ScheduledMessageStorage;
+
+pub async fn create_scheduled_message_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateScheduledMessageRequest>,
+) -> Result<Response<CreateScheduledMessageReply>, Status> {
+    let req = request.into_inner();
+    let storage = ScheduledMessageStorage::new(client_pool.clone());
+    let existing = storage
+        .list_scheduled_message(req.name.clone())
+        .await
+        .map_err(|e| Status::cancelled(e.to_string()))?;
+    if !existing.is_empty() {
+        return Err(Status::cancelled(
+            MqttBrokerError::ScheduledMessageAlreadyExists(req.name).to_string(),
+        ));
+    }
+
+    topic_name_validator(&req.topic).map_err(|e| Status::cancelled(e.to_string()))?;
+    if qos(req.qos as u8).is_none() {
+        return Err(Status::cancelled(
+            MqttBrokerError::ScheduledMessageError(req.name, format!("invalid qos {}", req.qos))
+                .to_string(),
+        ));
+    }
+
+    let message = MqttScheduledMessage {
+        cluster_name: cache_manager.cluster_name.clone(),
+        name: req.name,
+        topic: req.topic,
+        payload: req.payload,
+        qos: req.qos as u8,
+        retain: req.retain,
+        cron: req.cron,
+        interval_sec: req.interval_sec,
+        start_time: if req.start_time == 0 {
+            now_second()
+        } else {
+            req.start_time
+        },
+        end_time: req.end_time,
+        last_fire_time: 0,
+        fire_count: 0,
+        desc: req.desc,
+        create_time: now_second(),
+    };
+    if let Err(e) = message.validate() {
+        return Err(Status::cancelled(
+            MqttBrokerError::ScheduledMessageError(message.name, e.to_string()).to_string(),
+        ));
+    }
+
+    match storage.save_scheduled_message(message).await {
+        Ok(_) => Ok(Response::new(CreateScheduledMessageReply::default())),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn delete_scheduled_message_by_req(
+    client_pool: &Arc<ClientPool>,
+    request: Request<DeleteScheduledMessageRequest>,
+) -> Result<Response<DeleteScheduledMessageReply>, Status> {
+    let req = request.into_inner();
+    let storage = ScheduledMessageStorage::new(client_pool.clone());
+    let existing = storage
+        .list_scheduled_message(req.name.clone())
+        .await
+        .map_err(|e| Status::cancelled(e.to_string()))?;
+    if existing.is_empty() {
+        return Err(Status::cancelled(
+            MqttBrokerError::ScheduledMessageNotExists(req.name).to_string(),
+        ));
+    }
+
+    match storage.delete_scheduled_message(req.name).await {
+        Ok(_) => Ok(Response::new(DeleteScheduledMessageReply::default())),
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn list_scheduled_message_by_req(
+    client_pool: &Arc<ClientPool>,
+    request: Request<ListScheduledMessageRequest>,
+) -> Result<Response<ListScheduledMessageReply>, Status> {
+    let req = request.into_inner();
+    let storage = ScheduledMessageStorage::new(client_pool.clone());
+    let data = storage
+        .list_scheduled_message(req.name)
+        .await
+        .map_err(|e| Status::cancelled(e.to_string()))?;
+
+    let mut messages = Vec::new();
+    for message in data {
+        let next_fire_time = message
+            .pending_fire_time()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        messages.push(ScheduledMessageRaw {
+            name: message.name,
+            topic: message.topic,
+            qos: message.qos as u32,
+            retain: message.retain,
+            cron: message.cron,
+            interval_sec: message.interval_sec,
+            start_time: message.start_time,
+            end_time: message.end_time,
+            last_fire_time: message.last_fire_time,
+            next_fire_time,
+            fire_count: message.fire_count,
+            payload_size: message.payload.len() as u64,
+            desc: message.desc,
+            create_time: message.create_time,
+        });
+    }
+    Ok(Response::new(ListScheduledMessageReply { messages }))
+}
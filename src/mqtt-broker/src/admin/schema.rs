@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::handler::cache::CacheManager;
 use crate::handler::error::MqttBrokerError;
+use crate::handler::tenant::{check_tenant_exists, check_tenant_owner, tenant_scope_match};
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use grpc_clients::{
     placement::inner::call::{
@@ -50,18 +52,50 @@ pub async fn list_schema_by_req(
         schema_name: req.schema_name.clone(),
     };
 
-    let schemas = list_schema(client_pool, &config.placement_center, request)
+    let mut schemas = Vec::new();
+    for raw in list_schema(client_pool, &config.placement_center, request)
         .await?
-        .schemas;
+        .schemas
+    {
+        let schema = serde_json::from_slice::<SchemaData>(&raw)
+            .map_err(|e| Status::cancelled(e.to_string()))?;
+        if tenant_scope_match(&req.tenant, &schema.tenant) {
+            schemas.push(raw);
+        }
+    }
     Ok(Response::new(MqttListSchemaReply { schemas }))
 }
 
+async fn get_schema(
+    client_pool: &Arc<ClientPool>,
+    schema_name: &str,
+) -> Result<SchemaData, Status> {
+    let config = broker_mqtt_conf();
+    let request = ListSchemaRequest {
+        cluster_name: config.cluster_name.clone(),
+        schema_name: schema_name.to_string(),
+    };
+
+    let Some(raw) = list_schema(client_pool, &config.placement_center, request)
+        .await?
+        .schemas
+        .pop()
+    else {
+        return Err(Status::cancelled(
+            MqttBrokerError::SchemaNotExists(schema_name.to_string()).to_string(),
+        ));
+    };
+    serde_json::from_slice::<SchemaData>(&raw).map_err(|e| Status::cancelled(e.to_string()))
+}
+
 pub async fn create_schema_by_req(
+    cache_manager: &Arc<CacheManager>,
     client_pool: &Arc<ClientPool>,
     request: Request<MqttCreateSchemaRequest>,
 ) -> Result<Response<MqttCreateSchemaReply>, Status> {
     let req = request.into_inner();
     let config = broker_mqtt_conf();
+    check_tenant_exists(cache_manager, &req.tenant)?;
 
     let schema_type = match req.schema_type.as_str() {
         "" | "json" => SchemaType::JSON,
@@ -80,6 +114,7 @@ pub async fn create_schema_by_req(
         schema_type,
         schema: req.schema.clone(),
         desc: req.desc.clone(),
+        tenant: req.tenant.clone(),
     };
 
     let request = CreateSchemaRequest {
@@ -110,12 +145,21 @@ pub async fn update_schema_by_req(
         }
     };
 
+    // The schema keeps the tenant it was created with
+    let schema = get_schema(client_pool, &req.schema_name).await?;
+    check_tenant_owner(
+        &req.tenant,
+        &schema.tenant,
+        &format!("Schema {}", req.schema_name),
+    )?;
+
     let schema_data = SchemaData {
         cluster_name: config.cluster_name.clone(),
         name: req.schema_name.clone(),
         schema_type,
         schema: req.schema.clone(),
         desc: req.desc.clone(),
+        tenant: schema.tenant,
     };
 
     let request = UpdateSchemaRequest {
@@ -134,6 +178,13 @@ pub async fn delete_schema_by_req(
 ) -> Result<Response<MqttDeleteSchemaReply>, Status> {
     let req = request.into_inner();
     let config = broker_mqtt_conf();
+    let schema = get_schema(client_pool, &req.schema_name).await?;
+    check_tenant_owner(
+        &req.tenant,
+        &schema.tenant,
+        &format!("Schema {}", req.schema_name),
+    )?;
+
     let request = DeleteSchemaRequest {
         cluster_name: config.cluster_name.clone(),
         schema_name: req.schema_name.clone(),
@@ -13,13 +13,14 @@
 // limitations under the License.
 
 use crate::handler::cache::CacheManager;
+use crate::handler::tenant::{check_tenant_exists, check_tenant_owner, tenant_scope_match};
 use crate::storage::auto_subscribe::AutoSubscribeStorage;
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::auto_subscribe_rule::MqttAutoSubscribeRule;
 use protocol::broker_mqtt::broker_mqtt_admin::{
     DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest, ListAutoSubscribeRuleReply,
-    SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
+    ListAutoSubscribeRuleRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
 };
 use protocol::mqtt::common::{qos, retain_forward_rule, Error, QoS, RetainForwardRule};
 use std::sync::Arc;
@@ -32,6 +33,17 @@ pub async fn set_auto_subscribe_rule(
 ) -> Result<Response<SetAutoSubscribeRuleReply>, Status> {
     let req = request.into_inner();
     let config = broker_mqtt_conf();
+    check_tenant_exists(cache_manager, &req.tenant)?;
+
+    // The rules are keyed by topic, a tenant can not replace the rule of another tenant
+    let key = cache_manager.auto_subscribe_rule_key(&config.cluster_name, &req.topic);
+    if let Some(rule) = cache_manager.auto_subscribe_rule.get(&key) {
+        check_tenant_owner(
+            &req.tenant,
+            &rule.tenant,
+            &format!("Auto subscribe rule {}", req.topic),
+        )?;
+    }
 
     let mut _qos: Option<QoS> = None;
     if req.qos <= u8::MAX as u32 {
@@ -59,6 +71,7 @@ pub async fn set_auto_subscribe_rule(
         retained_handling: _retained_handling.ok_or_else(|| {
             Status::cancelled(Error::InvalidQoS(req.retained_handling as u8).to_string())
         })?,
+        tenant: req.tenant.clone(),
     };
     let auto_subscribe_storage = AutoSubscribeStorage::new(client_pool.clone());
     match auto_subscribe_storage
@@ -66,7 +79,6 @@ pub async fn set_auto_subscribe_rule(
         .await
     {
         Ok(_) => {
-            let key = cache_manager.auto_subscribe_rule_key(&config.cluster_name, &req.topic);
             cache_manager
                 .auto_subscribe_rule
                 .insert(key, auto_subscribe_rule);
@@ -82,14 +94,22 @@ pub async fn delete_auto_subscribe_rule(
     request: Request<DeleteAutoSubscribeRuleRequest>,
 ) -> Result<Response<DeleteAutoSubscribeRuleReply>, Status> {
     let req = request.into_inner();
+    let config = broker_mqtt_conf();
+    let key = cache_manager.auto_subscribe_rule_key(&config.cluster_name, &req.topic);
+    if let Some(rule) = cache_manager.auto_subscribe_rule.get(&key) {
+        check_tenant_owner(
+            &req.tenant,
+            &rule.tenant,
+            &format!("Auto subscribe rule {}", req.topic),
+        )?;
+    }
+
     let auto_subscribe_storage = AutoSubscribeStorage::new(client_pool.clone());
     match auto_subscribe_storage
         .delete_auto_subscribe_rule(req.topic.clone())
         .await
     {
         Ok(_) => {
-            let config = broker_mqtt_conf();
-            let key = cache_manager.auto_subscribe_rule_key(&config.cluster_name, &req.topic);
             cache_manager.auto_subscribe_rule.remove(&key);
             Ok(Response::new(DeleteAutoSubscribeRuleReply::default()))
         }
@@ -99,11 +119,14 @@ pub async fn delete_auto_subscribe_rule(
 
 pub fn list_auto_subscribe_rule_by_req(
     cache_manager: &Arc<CacheManager>,
+    request: Request<ListAutoSubscribeRuleRequest>,
 ) -> Result<Response<ListAutoSubscribeRuleReply>, Status> {
+    let req = request.into_inner();
     Ok(Response::new(ListAutoSubscribeRuleReply {
         auto_subscribe_rules: cache_manager
             .auto_subscribe_rule
             .iter()
+            .filter(|entry| tenant_scope_match(&req.tenant, &entry.value().tenant))
             .map(|entry| entry.value().clone().encode())
             .collect(),
     }))
@@ -0,0 +1,119 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::tenant::MqttTenant;
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CreateTenantReply, CreateTenantRequest, DeleteTenantReply, DeleteTenantRequest,
+    ListTenantReply, ListTenantRequest, TenantRaw,
+};
+use tonic::{Request, Response, Status};
+
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::tenant::{tenant_connection_num, tenant_name_validator, tenant_topic_num};
+use crate::storage::tenant::TenantStorage;
+
+pub async fn create_tenant_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateTenantRequest>,
+) -> Result<Response<CreateTenantReply>, Status> {
+    let req = request.into_inner();
+    if let Err(e) = tenant_name_validator(&req.tenant_name) {
+        return Err(Status::cancelled(e.to_string()));
+    }
+    if cache_manager.get_tenant(&req.tenant_name).is_some() {
+        return Err(Status::cancelled(
+            MqttBrokerError::TenantAlreadyExists(req.tenant_name).to_string(),
+        ));
+    }
+
+    let tenant = MqttTenant {
+        tenant_name: req.tenant_name,
+        desc: req.desc,
+        max_connections: req.max_connections,
+        max_topics: req.max_topics,
+        max_publish_rate: req.max_publish_rate,
+        create_time: now_second(),
+    };
+
+    let tenant_storage = TenantStorage::new(client_pool.clone());
+    match tenant_storage.save_tenant(tenant.clone()).await {
+        Ok(_) => {
+            cache_manager.add_tenant(tenant);
+            Ok(Response::new(CreateTenantReply::default()))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn delete_tenant_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<DeleteTenantRequest>,
+) -> Result<Response<DeleteTenantReply>, Status> {
+    let req = request.into_inner();
+    if cache_manager.get_tenant(&req.tenant_name).is_none() {
+        return Err(Status::cancelled(
+            MqttBrokerError::TenantNotExists(req.tenant_name).to_string(),
+        ));
+    }
+
+    if cache_manager
+        .user_info
+        .iter()
+        .any(|user| user.tenant == req.tenant_name)
+    {
+        return Err(Status::cancelled(
+            MqttBrokerError::TenantHasUsers(req.tenant_name).to_string(),
+        ));
+    }
+
+    let tenant_storage = TenantStorage::new(client_pool.clone());
+    match tenant_storage.delete_tenant(req.tenant_name.clone()).await {
+        Ok(_) => {
+            cache_manager.del_tenant(&req.tenant_name);
+            Ok(Response::new(DeleteTenantReply::default()))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub fn list_tenant_by_req(
+    cache_manager: &Arc<CacheManager>,
+    request: Request<ListTenantRequest>,
+) -> Result<Response<ListTenantReply>, Status> {
+    let req = request.into_inner();
+    let tenants = cache_manager
+        .tenant_info
+        .iter()
+        .filter(|tenant| req.tenant_name.is_empty() || tenant.tenant_name == req.tenant_name)
+        .map(|tenant| TenantRaw {
+            tenant_name: tenant.tenant_name.clone(),
+            desc: tenant.desc.clone(),
+            max_connections: tenant.max_connections,
+            max_topics: tenant.max_topics,
+            max_publish_rate: tenant.max_publish_rate,
+            connection_num: tenant_connection_num(cache_manager, &tenant.tenant_name),
+            topic_num: tenant_topic_num(cache_manager, &tenant.tenant_name),
+            create_time: tenant.create_time,
+        })
+        .collect();
+
+    Ok(Response::new(ListTenantReply { tenants }))
+}
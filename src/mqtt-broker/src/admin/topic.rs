@@ -33,17 +33,20 @@ pub fn list_topic_by_req(
         0 => cache_manager
             .get_topic_by_name(&req.topic_name)
             .into_iter()
+            .filter(|topic| req.tenant.is_empty() || topic.tenant == req.tenant)
             .take(10)
             .map(|entry| MqttTopic {
                 topic_id: entry.topic_id.clone(),
                 topic_name: entry.topic_name.clone(),
                 cluster_name: entry.cluster_name.clone(),
                 is_contain_retain_message: entry.retain_message.is_some(),
+                tenant: entry.tenant.clone(),
             })
             .collect(),
         option => cache_manager
             .topic_info
             .iter()
+            .filter(|entry| req.tenant.is_empty() || entry.value().tenant == req.tenant)
             .filter(|entry| match option {
                 1 => entry.value().topic_name.starts_with(&req.topic_name),
                 2 => entry.value().topic_name.contains(&req.topic_name),
@@ -55,6 +58,7 @@ pub fn list_topic_by_req(
                 topic_name: entry.value().topic_name.clone(),
                 cluster_name: entry.value().cluster_name.clone(),
                 is_contain_retain_message: entry.value().retain_message.is_some(),
+                tenant: entry.value().tenant.clone(),
             })
             .collect(),
     };
@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
 use crate::security::AuthDriver;
 use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::tenant::{tenant_scoped_name, TENANT_NAME_SEPARATOR};
 use metadata_struct::mqtt::user::MqttUser;
 use protocol::broker_mqtt::broker_mqtt_admin::{
     CreateUserReply, CreateUserRequest, DeleteUserReply, DeleteUserRequest, ListUserReply,
+    ListUserRequest,
 };
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
@@ -28,10 +31,23 @@ pub async fn create_user_by_req(
     request: Request<CreateUserRequest>,
 ) -> Result<Response<CreateUserReply>, Status> {
     let req = request.into_inner();
+    if req.username.contains(TENANT_NAME_SEPARATOR) {
+        return Err(Status::cancelled(
+            MqttBrokerError::UserNameInvalid(req.username, TENANT_NAME_SEPARATOR).to_string(),
+        ));
+    }
+    if !req.tenant.is_empty() && cache_manager.get_tenant(&req.tenant).is_none() {
+        return Err(Status::cancelled(
+            MqttBrokerError::TenantNotExists(req.tenant).to_string(),
+        ));
+    }
+
     let mqtt_user = MqttUser {
         username: req.username,
         password: req.password,
         is_superuser: req.is_superuser,
+        mountpoint: req.mountpoint,
+        tenant: req.tenant,
     };
 
     let auth_driver = AuthDriver::new(cache_manager.clone(), client_pool.clone());
@@ -49,7 +65,10 @@ pub async fn delete_user_by_req(
     let req = request.into_inner();
 
     let auth_driver = AuthDriver::new(cache_manager.clone(), client_pool.clone());
-    match auth_driver.delete_user(req.username).await {
+    match auth_driver
+        .delete_user(tenant_scoped_name(&req.tenant, &req.username))
+        .await
+    {
         Ok(_) => Ok(Response::new(DeleteUserReply::default())),
         Err(e) => Err(Status::cancelled(e.to_string())),
     }
@@ -58,13 +77,19 @@ pub async fn delete_user_by_req(
 pub async fn list_user_by_req(
     cache_manager: &Arc<CacheManager>,
     client_pool: &Arc<ClientPool>,
+    request: Request<ListUserRequest>,
 ) -> Result<Response<ListUserReply>, Status> {
+    let req = request.into_inner();
     let mut reply = ListUserReply::default();
     let auth_driver = AuthDriver::new(cache_manager.clone(), client_pool.clone());
     match auth_driver.read_all_user().await {
         Ok(data) => {
             let mut users = Vec::new();
             for ele in data {
+                // an empty tenant lists the users of all tenants
+                if !req.tenant.is_empty() && ele.1.tenant != req.tenant {
+                    continue;
+                }
                 users.push(ele.1.encode());
             }
             reply.users = users;
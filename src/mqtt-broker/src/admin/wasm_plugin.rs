@@ -0,0 +1,154 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
+use protocol::broker_mqtt::broker_mqtt_admin::{
+    CreateWasmPluginReply, CreateWasmPluginRequest, DeleteWasmPluginReply, DeleteWasmPluginRequest,
+    ListWasmPluginReply, ListWasmPluginRequest, WasmPluginRaw,
+};
+use tonic::{Request, Response, Status};
+
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::storage::topic::TopicStorage;
+use crate::subscribe::sub_common::sub_path_validator;
+use crate::wasm_plugin::{WasmPlugin, DEFAULT_WASM_MAX_FUEL, DEFAULT_WASM_MAX_MEMORY_BYTES};
+
+pub async fn create_wasm_plugin_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateWasmPluginRequest>,
+) -> Result<Response<CreateWasmPluginReply>, Status> {
+    let req = request.into_inner();
+    if cache_manager.get_wasm_plugin(&req.plugin_name).is_some() {
+        return Err(Status::cancelled(
+            MqttBrokerError::WasmPluginAlreadyExists(req.plugin_name).to_string(),
+        ));
+    }
+
+    if req.topics.is_empty() {
+        return Err(Status::cancelled(
+            MqttBrokerError::WasmPluginError(
+                req.plugin_name,
+                "at least one topic filter is required".to_string(),
+            )
+            .to_string(),
+        ));
+    }
+    for topic in req.topics.iter() {
+        if !sub_path_validator(topic.clone()) {
+            return Err(Status::cancelled(
+                MqttBrokerError::WasmPluginError(
+                    req.plugin_name,
+                    format!("invalid topic filter {}", topic),
+                )
+                .to_string(),
+            ));
+        }
+    }
+
+    if !req.tenant.is_empty() && cache_manager.get_tenant(&req.tenant).is_none() {
+        return Err(Status::cancelled(
+            MqttBrokerError::TenantNotExists(req.tenant).to_string(),
+        ));
+    }
+
+    let plugin = MqttWasmPlugin {
+        cluster: cache_manager.cluster_name.clone(),
+        plugin_name: req.plugin_name,
+        topics: req.topics,
+        tenant: req.tenant,
+        module: req.module,
+        max_fuel: if req.max_fuel == 0 {
+            DEFAULT_WASM_MAX_FUEL
+        } else {
+            req.max_fuel
+        },
+        max_memory_bytes: if req.max_memory_bytes == 0 {
+            DEFAULT_WASM_MAX_MEMORY_BYTES
+        } else {
+            req.max_memory_bytes
+        },
+        desc: req.desc,
+        create_time: now_second(),
+    };
+    // Reject a module that cannot be compiled before it is stored.
+    WasmPlugin::new(plugin.clone()).map_err(|e| Status::cancelled(e.to_string()))?;
+
+    let topic_storage = TopicStorage::new(client_pool.clone());
+    match topic_storage.create_wasm_plugin(plugin.clone()).await {
+        Ok(_) => {
+            if let Err(e) = cache_manager.add_wasm_plugin(plugin) {
+                return Err(Status::cancelled(e.to_string()));
+            }
+            Ok(Response::new(CreateWasmPluginReply::default()))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn delete_wasm_plugin_by_req(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<DeleteWasmPluginRequest>,
+) -> Result<Response<DeleteWasmPluginReply>, Status> {
+    let req = request.into_inner();
+    if cache_manager.get_wasm_plugin(&req.plugin_name).is_none() {
+        return Err(Status::cancelled(
+            MqttBrokerError::WasmPluginNotExists(req.plugin_name).to_string(),
+        ));
+    }
+
+    let topic_storage = TopicStorage::new(client_pool.clone());
+    match topic_storage
+        .delete_wasm_plugin(req.plugin_name.clone())
+        .await
+    {
+        Ok(_) => {
+            cache_manager.delete_wasm_plugin(&req.plugin_name);
+            Ok(Response::new(DeleteWasmPluginReply::default()))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub fn list_wasm_plugin_by_req(
+    cache_manager: &Arc<CacheManager>,
+    request: Request<ListWasmPluginRequest>,
+) -> Result<Response<ListWasmPluginReply>, Status> {
+    let req = request.into_inner();
+    let mut plugins = Vec::new();
+    for plugin in cache_manager.wasm_plugin_info.iter() {
+        let info = &plugin.info;
+        if !req.plugin_name.is_empty() && info.plugin_name != req.plugin_name {
+            continue;
+        }
+        plugins.push(WasmPluginRaw {
+            plugin_name: info.plugin_name.clone(),
+            topics: info.topics.clone(),
+            tenant: info.tenant.clone(),
+            module_size: info.module.len() as u64,
+            max_fuel: info.max_fuel,
+            max_memory_bytes: info.max_memory_bytes,
+            desc: info.desc.clone(),
+            create_time: info.create_time,
+        });
+    }
+
+    Ok(Response::new(ListWasmPluginReply { plugins }))
+}
@@ -12,19 +12,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::handler::cache::CacheManager;
 use crate::handler::error::MqttBrokerError;
+use crate::handler::publish::MessagePublisher;
 use axum::async_trait;
 use common_base::config::broker_mqtt::broker_mqtt_conf;
+use grpc_clients::pool::ClientPool;
 use log::{error, info};
 use metadata_struct::mqtt::bridge::{
-    config_local_file::LocalFileConnectorConfig, connector::MQTTConnector,
-    connector_type::ConnectorType, status::MQTTStatus,
+    config_common::ConnectorCommonConfig,
+    config_http::HttpConnectorConfig,
+    config_kafka::KafkaConnectorConfig,
+    config_local_file::LocalFileConnectorConfig,
+    config_mqtt::MqttConnectorConfig,
+    config_redis::RedisConnectorConfig,
+    config_s3::S3ConnectorConfig,
+    config_source::{KafkaSourceConnectorConfig, LocalFileSourceConnectorConfig},
+    config_sql::SqlConnectorConfig,
+    connector::MQTTConnector,
+    connector_type::ConnectorType,
+    status::MQTTStatus,
 };
 use std::{sync::Arc, time::Duration};
 use storage_adapter::storage::StorageAdapter;
 use tokio::{select, sync::broadcast, time::sleep};
 
-use super::{file::FileBridgePlugin, manager::ConnectorManager};
+use super::{
+    file::{source::FileSourceBridgePlugin, FileBridgePlugin},
+    http::HttpBridgePlugin,
+    kafka::{source::KafkaSourceBridgePlugin, KafkaBridgePlugin},
+    manager::ConnectorManager,
+    mqtt::MqttBridgePlugin,
+    redis::RedisBridgePlugin,
+    runtime::ConnectorRuntime,
+    s3::S3BridgePlugin,
+    source::{source_client_id, SourcePublisher},
+    sql::{SqlBridgePlugin, SqlDriver},
+};
 
 #[derive(Clone)]
 pub struct BridgePluginReadConfig {
@@ -44,8 +68,11 @@ pub trait BridgePlugin {
 }
 
 pub async fn start_connector_thread<S>(
+    cache_manager: Arc<CacheManager>,
+    client_pool: Arc<ClientPool>,
     message_storage: Arc<S>,
     connector_manager: Arc<ConnectorManager>,
+    message_publisher: MessagePublisher<S>,
     stop_send: broadcast::Sender<bool>,
 ) where
     S: StorageAdapter + Sync + Send + 'static + Clone,
@@ -62,8 +89,11 @@ pub async fn start_connector_thread<S>(
                 }
             }
             _ = check_connector(
+                &cache_manager,
+                &client_pool,
                 &message_storage,
                 &connector_manager,
+                &message_publisher,
             ) => {
                 sleep(Duration::from_secs(1)).await;
             }
@@ -71,8 +101,13 @@ pub async fn start_connector_thread<S>(
     }
 }
 
-async fn check_connector<S>(message_storage: &Arc<S>, connector_manager: &Arc<ConnectorManager>)
-where
+async fn check_connector<S>(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    message_storage: &Arc<S>,
+    connector_manager: &Arc<ConnectorManager>,
+    message_publisher: &MessagePublisher<S>,
+) where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
     let config = broker_mqtt_conf();
@@ -103,8 +138,11 @@ async fn check_connector<S>(message_storage: &Arc<S>, connector_manager: &Arc<Co
         };
 
         start_thread(
+            cache_manager.clone(),
+            client_pool.clone(),
             connector_manager.clone(),
             message_storage.clone(),
+            message_publisher.clone(),
             raw.clone(),
             thread,
         );
@@ -133,58 +171,422 @@ async fn check_connector<S>(message_storage: &Arc<S>, connector_manager: &Arc<Co
             if let Some(mut connector) = connector_manager.get_connector(&raw.connector_name) {
                 connector.status = MQTTStatus::Idle;
             }
+            // A deleted connector has no runtime status any more, the status of any other
+            // connector is kept until its thread reported that it exited.
+            if connector_manager
+                .get_connector(&raw.connector_name)
+                .is_none()
+            {
+                connector_manager.remove_connector_status(&raw.connector_name);
+            }
         }
     }
 }
 
 fn start_thread<S>(
+    cache_manager: Arc<CacheManager>,
+    client_pool: Arc<ClientPool>,
     connector_manager: Arc<ConnectorManager>,
     message_storage: Arc<S>,
+    message_publisher: MessagePublisher<S>,
     connector: MQTTConnector,
     thread: BridgePluginThread,
 ) where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
     tokio::spawn(async move {
-        match connector.connector_type {
-            ConnectorType::LocalFile => {
-                let local_file_config = match serde_json::from_str::<LocalFileConnectorConfig>(
-                    &connector.config,
-                ) {
-                    Ok(config) => config,
-                    Err(e) => {
-                        error!("Failed to parse LocalFileConnectorConfig file with error message :{}, configuration contents: {}", e, connector.config);
-                        return;
-                    }
-                };
-
-                let bridge = FileBridgePlugin::new(
-                    connector_manager.clone(),
-                    message_storage.clone(),
-                    connector.connector_name.clone(),
-                    local_file_config,
-                    thread.stop_send.clone(),
+        let connector_name = connector.connector_name.clone();
+        run_thread(
+            cache_manager,
+            client_pool,
+            connector_manager.clone(),
+            message_storage,
+            message_publisher,
+            connector,
+            thread,
+        )
+        .await;
+        connector_manager.remove_connector_thread(&connector_name);
+
+        // The thread of a connector taken away from this broker, i.e. paused or scheduled to
+        // another broker, has exited, so it no longer commits offsets. This is reported in the
+        // runtime status so that the offset of a paused connector can be reset.
+        let config = broker_mqtt_conf();
+        let is_assigned = connector_manager
+            .get_connector(&connector_name)
+            .is_some_and(|connector| connector.broker_id == Some(config.broker_id));
+        if !is_assigned {
+            connector_manager.set_connector_stopped(&connector_name);
+        }
+    });
+}
+
+async fn run_thread<S>(
+    cache_manager: Arc<CacheManager>,
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    message_storage: Arc<S>,
+    message_publisher: MessagePublisher<S>,
+    connector: MQTTConnector,
+    thread: BridgePluginThread,
+) where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let common_config = match serde_json::from_str::<ConnectorCommonConfig>(&connector.config) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Failed to parse ConnectorCommonConfig with error message :{}, configuration contents: {}", e, connector.config);
+            connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+            return;
+        }
+    };
+    let runtime = ConnectorRuntime::new(
+        cache_manager.clone(),
+        client_pool.clone(),
+        connector_manager.clone(),
+        message_storage.clone(),
+        connector.connector_name.clone(),
+        common_config,
+    );
+
+    match connector.connector_type {
+        ConnectorType::LocalFile => {
+            let local_file_config = match serde_json::from_str::<LocalFileConnectorConfig>(
+                &connector.config,
+            ) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse LocalFileConnectorConfig file with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            let bridge =
+                FileBridgePlugin::new(runtime.clone(), local_file_config, thread.stop_send.clone());
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num: 100,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start FileBridgePlugin with error message: {:?}",
+                    e
                 );
+            }
+        }
+        ConnectorType::Kafka => {
+            let kafka_config = match serde_json::from_str::<KafkaConnectorConfig>(&connector.config)
+            {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse KafkaConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            let record_num = kafka_config.batch_size;
+            let bridge =
+                KafkaBridgePlugin::new(runtime.clone(), kafka_config, thread.stop_send.clone());
 
-                connector_manager.add_connector_thread(&connector.connector_name, thread);
-
-                if let Err(e) = bridge
-                    .exec(BridgePluginReadConfig {
-                        topic_id: connector.topic_id,
-                        record_num: 100,
-                    })
-                    .await
-                {
-                    connector_manager.remove_connector_thread(&connector.connector_name);
-                    error!(
-                        "Failed to start FileBridgePlugin with error message: {:?}",
-                        e
-                    );
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start KafkaBridgePlugin with error message: {:?}",
+                    e
+                );
+            }
+        }
+        ConnectorType::Mqtt => {
+            let mqtt_config = match serde_json::from_str::<MqttConnectorConfig>(&connector.config) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse MqttConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
                 }
+            };
+
+            let record_num = mqtt_config.batch_size;
+            let publisher = SourcePublisher::new(
+                runtime.clone(),
+                message_publisher.clone(),
+                source_client_id(&connector.connector_name),
+                String::new(),
+            );
+            let bridge = MqttBridgePlugin::new(
+                runtime.clone(),
+                publisher,
+                mqtt_config,
+                thread.stop_send.clone(),
+            );
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start MqttBridgePlugin with error message: {:?}",
+                    e
+                );
             }
-            ConnectorType::Kafka => {}
         }
-    });
+        ConnectorType::Http => {
+            let http_config = match serde_json::from_str::<HttpConnectorConfig>(&connector.config) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse HttpConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            let record_num = http_config.batch_size * http_config.max_concurrency;
+            let bridge =
+                HttpBridgePlugin::new(runtime.clone(), http_config, thread.stop_send.clone());
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start HttpBridgePlugin with error message: {:?}",
+                    e
+                );
+            }
+        }
+        ConnectorType::MySQL | ConnectorType::PostgreSQL => {
+            let sql_config = match serde_json::from_str::<SqlConnectorConfig>(&connector.config) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse SqlConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            let driver = if connector.connector_type == ConnectorType::MySQL {
+                SqlDriver::MySQL
+            } else {
+                SqlDriver::PostgreSQL
+            };
+            let record_num = sql_config.batch_size;
+            let bridge = SqlBridgePlugin::new(
+                runtime.clone(),
+                driver,
+                sql_config,
+                thread.stop_send.clone(),
+            );
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start SqlBridgePlugin with error message: {:?}",
+                    e
+                );
+            }
+        }
+        ConnectorType::S3 => {
+            let s3_config = match serde_json::from_str::<S3ConnectorConfig>(&connector.config) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse S3ConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            let record_num = s3_config.batch_size;
+            let bridge = S3BridgePlugin::new(runtime.clone(), s3_config, thread.stop_send.clone());
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!("Failed to start S3BridgePlugin with error message: {:?}", e);
+            }
+        }
+        ConnectorType::Redis => {
+            let redis_config = match serde_json::from_str::<RedisConnectorConfig>(&connector.config)
+            {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse RedisConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            let record_num = redis_config.batch_size;
+            let bridge =
+                RedisBridgePlugin::new(runtime.clone(), redis_config, thread.stop_send.clone());
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start RedisBridgePlugin with error message: {:?}",
+                    e
+                );
+            }
+        }
+        ConnectorType::KafkaSource => {
+            let kafka_source_config = match serde_json::from_str::<KafkaSourceConnectorConfig>(
+                &connector.config,
+            ) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse KafkaSourceConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            // A source publishes into MQTT topics, so it does not read from the topic of
+            // the connector.
+            let publisher = SourcePublisher::new(
+                runtime.clone(),
+                message_publisher.clone(),
+                source_client_id(&connector.connector_name),
+                kafka_source_config.mqtt_username.clone(),
+            );
+            let bridge = KafkaSourceBridgePlugin::new(
+                client_pool.clone(),
+                connector_manager.clone(),
+                publisher,
+                connector.connector_name.clone(),
+                kafka_source_config,
+                thread.stop_send.clone(),
+            );
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num: 0,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start KafkaSourceBridgePlugin with error message: {:?}",
+                    e
+                );
+            }
+        }
+        ConnectorType::LocalFileSource => {
+            let file_source_config = match serde_json::from_str::<LocalFileSourceConnectorConfig>(
+                &connector.config,
+            ) {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to parse LocalFileSourceConnectorConfig with error message :{}, configuration contents: {}", e, connector.config);
+                    connector_manager.report_error(&connector.connector_name, e.to_string(), true);
+                    return;
+                }
+            };
+
+            // A source publishes into MQTT topics, so it does not read from the topic of
+            // the connector.
+            let publisher = SourcePublisher::new(
+                runtime.clone(),
+                message_publisher.clone(),
+                source_client_id(&connector.connector_name),
+                file_source_config.mqtt_username.clone(),
+            );
+            let bridge = FileSourceBridgePlugin::new(
+                client_pool.clone(),
+                connector_manager.clone(),
+                publisher,
+                connector.connector_name.clone(),
+                file_source_config,
+                thread.stop_send.clone(),
+            );
+
+            connector_manager.add_connector_thread(&connector.connector_name, thread);
+            runtime.set_running();
+
+            if let Err(e) = bridge
+                .exec(BridgePluginReadConfig {
+                    topic_id: connector.topic_id,
+                    record_num: 0,
+                })
+                .await
+            {
+                connector_manager.remove_connector_thread(&connector.connector_name);
+                runtime.report_error(&e, true);
+                error!(
+                    "Failed to start FileSourceBridgePlugin with error message: {:?}",
+                    e
+                );
+            }
+        }
+    }
 }
 
 fn stop_thread(thread: BridgePluginThread) -> Result<(), MqttBrokerError> {
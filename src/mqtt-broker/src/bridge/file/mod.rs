@@ -12,25 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
-
 use super::core::{BridgePlugin, BridgePluginReadConfig};
-use super::manager::ConnectorManager;
-use crate::{handler::error::MqttBrokerError, storage::message::MessageStorage};
+use super::runtime::ConnectorRuntime;
+use crate::handler::error::MqttBrokerError;
 use axum::async_trait;
-use log::error;
 use metadata_struct::{
     adapter::record::Record, mqtt::bridge::config_local_file::LocalFileConnectorConfig,
 };
 use storage_adapter::storage::StorageAdapter;
 use tokio::fs::File;
 use tokio::io::{AsyncWriteExt, BufWriter};
-use tokio::{fs::OpenOptions, select, sync::broadcast, time::sleep};
+use tokio::{
+    fs::OpenOptions,
+    sync::{broadcast, Mutex},
+};
+
+pub mod source;
 
 pub struct FileBridgePlugin<S> {
-    connector_manager: Arc<ConnectorManager>,
-    message_storage: Arc<S>,
-    connector_name: String,
+    runtime: ConnectorRuntime<S>,
     config: LocalFileConnectorConfig,
     stop_send: broadcast::Sender<bool>,
 }
@@ -40,16 +40,12 @@ impl<S> FileBridgePlugin<S>
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
     pub fn new(
-        connector_manager: Arc<ConnectorManager>,
-        message_storage: Arc<S>,
-        connector_name: String,
+        runtime: ConnectorRuntime<S>,
         config: LocalFileConnectorConfig,
         stop_send: broadcast::Sender<bool>,
     ) -> Self {
         FileBridgePlugin {
-            connector_manager,
-            message_storage,
-            connector_name,
+            runtime,
             config,
             stop_send,
         }
@@ -75,53 +71,20 @@ impl<S> BridgePlugin for FileBridgePlugin<S>
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
     async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
-        let message_storage = MessageStorage::new(self.message_storage.clone());
-        let group_name = self.connector_name.clone();
         let mut recv = self.stop_send.subscribe();
         let file = OpenOptions::new()
             .append(true)
             .open(self.config.local_file_path.clone())
             .await?;
-        let mut writer = tokio::io::BufWriter::new(file);
-
-        loop {
-            let offset = message_storage.get_group_offset(&group_name).await?;
-
-            select! {
-                val = recv.recv() =>{
-                    if let Ok(flag) = val {
-                        if flag {
-                            break;
-                        }
-                    }
-                },
-
-                val = message_storage.read_topic_message(&config.topic_id, offset, config.record_num) => {
-                    match val {
-                        Ok(data) => {
-                            self.connector_manager.report_heartbeat(&self.connector_name);
-                            if data.is_empty() {
-                                sleep(Duration::from_millis(100)).await;
-                                continue;
-                            }
-
-                            if let Err(e) = self.append(&data,&mut writer).await{
-                                error!("Connector {} failed to write data to {}, error message :{}", self.connector_name,self.config.local_file_path, e);
-                                sleep(Duration::from_millis(100)).await;
-                            }
-
-                            // commit offset
-                            message_storage.commit_group_offset(&group_name, &config.topic_id, offset + data.len() as u64).await?;
-                        },
-                        Err(e) => {
-                            error!("Connector {} failed to read Topic {} data with error message :{}", self.connector_name,config.topic_id,e);
-                            sleep(Duration::from_millis(100)).await;
-                        }
-                    }
-                }
-            }
-        }
-        Ok(())
+        let writer = Mutex::new(BufWriter::new(file));
+        let writer = &writer;
+
+        self.runtime
+            .run_sink(&config, &mut recv, move |records| async move {
+                let mut writer = writer.lock().await;
+                self.append(&records, &mut writer).await
+            })
+            .await
     }
 }
 
@@ -134,6 +97,7 @@ mod tests {
         tools::{now_second, unique_id},
         utils::crc::calc_crc32,
     };
+    use grpc_clients::pool::ClientPool;
     use metadata_struct::{
         adapter::record::{Header, Record},
         mqtt::bridge::config_local_file::LocalFileConnectorConfig,
@@ -148,7 +112,9 @@ mod tests {
         core::{BridgePlugin, BridgePluginReadConfig},
         file::FileBridgePlugin,
         manager::ConnectorManager,
+        runtime::ConnectorRuntime,
     };
+    use crate::handler::cache::CacheManager;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -222,13 +188,16 @@ async fn file_bridge_plugin_test() {
 
         let (stop_send, _) = broadcast::channel(1);
 
-        let file_bridge_plugin = FileBridgePlugin::new(
+        let client_pool = Arc::new(ClientPool::new(1));
+        let runtime = ConnectorRuntime::new(
+            Arc::new(CacheManager::new(client_pool.clone(), namespace.clone())),
+            client_pool,
             connector_manager.clone(),
             storage_adapter.clone(),
             connector_name.clone(),
-            config.clone(),
-            stop_send.clone(),
+            Default::default(),
         );
+        let file_bridge_plugin = FileBridgePlugin::new(runtime, config.clone(), stop_send.clone());
 
         let read_config = BridgePluginReadConfig {
             topic_id: shard_name.clone(),
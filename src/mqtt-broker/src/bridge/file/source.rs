@@ -0,0 +1,306 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, io::SeekFrom, sync::Arc, time::Duration};
+
+use axum::async_trait;
+use bytes::Bytes;
+use grpc_clients::pool::ClientPool;
+use log::{error, info};
+use metadata_struct::mqtt::bridge::config_source::{
+    LocalFileSourceConnectorConfig, SourceStartFrom,
+};
+use protocol::mqtt::common::{qos, Publish, PublishProperties, QoS};
+use serde_json::json;
+use storage_adapter::storage::StorageAdapter;
+use tokio::{
+    fs::{self, File},
+    io::{AsyncBufReadExt, AsyncSeekExt, BufReader},
+    select,
+    sync::broadcast,
+    time::sleep,
+};
+
+use crate::{
+    bridge::{
+        core::{BridgePlugin, BridgePluginReadConfig},
+        manager::ConnectorManager,
+        message::parse_payload,
+        source::SourcePublisher,
+    },
+    handler::error::MqttBrokerError,
+    rule_engine::action::render_template,
+    storage::connector::ConnectorStorage,
+};
+
+// The byte position in the file up to which the lines were published.
+const FILE_SOURCE_POSITION: &str = "position";
+
+// Tails a local file and publishes each line to an MQTT topic.
+pub struct FileSourceBridgePlugin<S> {
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    publisher: SourcePublisher<S>,
+    connector_name: String,
+    config: LocalFileSourceConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> FileSourceBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        client_pool: Arc<ClientPool>,
+        connector_manager: Arc<ConnectorManager>,
+        publisher: SourcePublisher<S>,
+        connector_name: String,
+        config: LocalFileSourceConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        FileSourceBridgePlugin {
+            client_pool,
+            connector_manager,
+            publisher,
+            connector_name,
+            config,
+            stop_send,
+        }
+    }
+
+    async fn start_position(&self, offsets: &HashMap<String, u64>) -> u64 {
+        if let Some(position) = offsets.get(FILE_SOURCE_POSITION) {
+            return *position;
+        }
+        match self.config.start_from {
+            SourceStartFrom::Earliest => 0,
+            SourceStartFrom::Latest => fs::metadata(&self.config.path)
+                .await
+                .map(|metadata| metadata.len())
+                .unwrap_or(0),
+        }
+    }
+
+    // The lines are published again after a failover if the position could not be stored.
+    async fn store_position(&self, connector_storage: &ConnectorStorage, position: u64) {
+        let offsets = HashMap::from([(FILE_SOURCE_POSITION.to_string(), position)]);
+        if let Err(e) = connector_storage
+            .set_source_offset(&self.connector_name, &offsets)
+            .await
+        {
+            error!(
+                "Connector {} failed to store the source offset, error message: {}",
+                self.connector_name, e
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for FileSourceBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, _: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let connector_storage = ConnectorStorage::new(self.client_pool.clone());
+        let mut recv = self.stop_send.subscribe();
+        let offsets = connector_storage
+            .get_source_offset(&self.connector_name)
+            .await?;
+        let mut position = self.start_position(&offsets).await;
+        let batch_size = self.config.batch_size.max(1) as usize;
+
+        loop {
+            self.connector_manager
+                .report_heartbeat(&self.connector_name);
+
+            let idle = match read_lines(&self.config.path, position, batch_size).await {
+                Ok((lines, next_position)) => {
+                    let idle = lines.len() < batch_size;
+                    for line in lines {
+                        let (topic_name, publish, publish_properties) =
+                            build_file_source_publish(&self.config, &line);
+                        if !self
+                            .publisher
+                            .publish_with_retry(
+                                &topic_name,
+                                &publish,
+                                &publish_properties,
+                                &mut recv,
+                            )
+                            .await
+                        {
+                            info!("{}", "Connector thread exited successfully");
+                            return Ok(());
+                        }
+                    }
+
+                    if next_position != position {
+                        position = next_position;
+                        self.store_position(&connector_storage, position).await;
+                    }
+                    idle
+                }
+                Err(e) => {
+                    error!(
+                        "Connector {} failed to read file {}, error message: {}",
+                        self.connector_name, self.config.path, e
+                    );
+                    true
+                }
+            };
+
+            if idle {
+                select! {
+                    val = recv.recv() => {
+                        if let Ok(true) = val {
+                            break;
+                        }
+                    }
+                    _ = sleep(Duration::from_millis(self.config.poll_interval_ms)) => {}
+                }
+            } else if let Ok(true) = recv.try_recv() {
+                break;
+            }
+        }
+
+        info!("{}", "Connector thread exited successfully");
+        Ok(())
+    }
+}
+
+// Reads up to max_lines complete lines from the position, a trailing line without a line
+// break is read once it is completed. Returns the lines and the position after them. If the
+// file is shorter than the position it was truncated, and is read from the beginning.
+pub async fn read_lines(
+    path: &str,
+    position: u64,
+    max_lines: usize,
+) -> Result<(Vec<String>, u64), MqttBrokerError> {
+    let mut file = File::open(path).await?;
+    let mut position = position;
+    if file.metadata().await?.len() < position {
+        info!("File {} was truncated, reading it from the beginning", path);
+        position = 0;
+    }
+    file.seek(SeekFrom::Start(position)).await?;
+
+    let mut reader = BufReader::new(file);
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    while lines.len() < max_lines {
+        buf.clear();
+        let len = reader.read_until(b'\n', &mut buf).await?;
+        if len == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        position += len as u64;
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches(['\n', '\r']);
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    Ok((lines, position))
+}
+
+// Builds the MQTT publish of a line, the topic is rendered with the line when it is JSON.
+pub fn build_file_source_publish(
+    config: &LocalFileSourceConnectorConfig,
+    line: &str,
+) -> (String, Publish, Option<PublishProperties>) {
+    let context = json!({
+        "path": config.path,
+        "payload": parse_payload(line.as_bytes()),
+    });
+    let topic_name = render_template(&config.mqtt_topic, &context);
+    let publish = Publish {
+        dup: false,
+        qos: qos(config.qos).unwrap_or(QoS::AtLeastOnce),
+        pkid: 0,
+        retain: config.retain,
+        topic: Bytes::from(topic_name.clone()),
+        payload: Bytes::from(line.to_string()),
+    };
+    (topic_name, publish, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use metadata_struct::mqtt::bridge::config_source::LocalFileSourceConnectorConfig;
+    use tempfile::tempdir;
+    use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+    use super::{build_file_source_publish, read_lines};
+
+    #[tokio::test]
+    async fn read_lines_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("source.log");
+        let path = path.to_str().unwrap();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .unwrap();
+        file.write_all(b"l1\r\n\nl2\nl3\npart").await.unwrap();
+        file.flush().await.unwrap();
+
+        let (lines, position) = read_lines(path, 0, 1).await.unwrap();
+        assert_eq!(lines, vec!["l1".to_string()]);
+        assert_eq!(position, 4);
+
+        let (lines, position) = read_lines(path, position, 10).await.unwrap();
+        assert_eq!(lines, vec!["l2".to_string(), "l3".to_string()]);
+        assert_eq!(position, 11);
+
+        // The partial line is read once it is completed.
+        let (lines, _) = read_lines(path, position, 10).await.unwrap();
+        assert!(lines.is_empty());
+        file.write_all(b"ial\n").await.unwrap();
+        file.flush().await.unwrap();
+        let (lines, position) = read_lines(path, position, 10).await.unwrap();
+        assert_eq!(lines, vec!["partial".to_string()]);
+        assert_eq!(position, 19);
+
+        // A truncated file is read from the beginning.
+        file.set_len(0).await.unwrap();
+        file.write_all(b"new\n").await.unwrap();
+        file.flush().await.unwrap();
+        let (lines, position) = read_lines(path, position, 10).await.unwrap();
+        assert_eq!(lines, vec!["new".to_string()]);
+        assert_eq!(position, 4);
+    }
+
+    #[test]
+    fn build_file_source_publish_test() {
+        let config = LocalFileSourceConnectorConfig {
+            path: "/var/log/device.log".to_string(),
+            mqtt_topic: "devices/${payload.device}/log".to_string(),
+            ..Default::default()
+        };
+        let (topic_name, publish, _) =
+            build_file_source_publish(&config, r#"{"device":"d1","level":"warn"}"#);
+        assert_eq!(topic_name, "devices/d1/log");
+        assert_eq!(
+            publish.payload,
+            Bytes::from(r#"{"device":"d1","level":"warn"}"#)
+        );
+
+        let (topic_name, _, _) = build_file_source_publish(&config, "plain text");
+        assert_eq!(topic_name, "devices//log");
+    }
+}
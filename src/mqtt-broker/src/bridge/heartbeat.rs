@@ -12,15 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use grpc_clients::pool::ClientPool;
 use log::{error, info};
+use metadata_struct::mqtt::bridge::status::ConnectorRuntimeStatus;
 use protocol::placement_center::placement_center_mqtt::ConnectorHeartbeatRaw;
 use tokio::{select, sync::broadcast, time::sleep};
 
-use crate::storage::connector::ConnectorStorage;
+use crate::{
+    observability::metrics::connector::get_connector_metrics, storage::connector::ConnectorStorage,
+};
 
 use super::manager::ConnectorManager;
 
@@ -30,6 +33,9 @@ pub async fn start_connector_report_heartbeat_thread(
     stop_send: broadcast::Sender<bool>,
 ) {
     let mut recv = stop_send.subscribe();
+    // The runtime status last reported of each connector, it is only reported again when it
+    // changed.
+    let mut reported_status = HashMap::new();
     loop {
         select! {
             val = recv.recv() =>{
@@ -42,7 +48,8 @@ pub async fn start_connector_report_heartbeat_thread(
             }
             _ = report_heartbeat(
                 &client_pool,
-                &connector_manager
+                &connector_manager,
+                &mut reported_status
             ) => {
                 sleep(Duration::from_secs(1)).await;
             }
@@ -53,6 +60,7 @@ pub async fn start_connector_report_heartbeat_thread(
 async fn report_heartbeat(
     client_pool: &Arc<ClientPool>,
     connector_manager: &Arc<ConnectorManager>,
+    reported_status: &mut HashMap<String, ConnectorRuntimeStatus>,
 ) {
     let storage = ConnectorStorage::new(client_pool.clone());
     let conf = broker_mqtt_conf();
@@ -69,4 +77,46 @@ async fn report_heartbeat(
     if let Err(e) = storage.connector_heartbeat(heatbeats).await {
         error!("report connector heartbeat error:{}", e);
     }
+
+    reported_status.retain(|connector_name, _| {
+        connector_manager
+            .connector_status
+            .contains_key(connector_name)
+    });
+    for (connector_name, status) in connector_manager.connector_status.clone() {
+        let (success_num, error_num, dead_letter_num, delivery_delay_sec, lag) =
+            get_connector_metrics(&connector_name);
+        let runtime_status = ConnectorRuntimeStatus {
+            broker_id: conf.broker_id,
+            success_num,
+            error_num,
+            dead_letter_num,
+            delivery_delay_sec,
+            lag,
+            ..status
+        };
+        if reported_status.get(&connector_name) == Some(&runtime_status) {
+            continue;
+        }
+
+        match storage
+            .set_runtime_status(&connector_name, &runtime_status)
+            .await
+        {
+            Ok(()) => {
+                if runtime_status.stopped {
+                    connector_manager.remove_stopped_connector_status(&connector_name);
+                    reported_status.remove(&connector_name);
+                } else {
+                    reported_status.insert(connector_name, runtime_status);
+                }
+            }
+            Err(e) => {
+                error!(
+                    "report the runtime status of connector {} error:{}",
+                    connector_name, e
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,349 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use axum::async_trait;
+use futures::{stream, StreamExt, TryStreamExt};
+use metadata_struct::{adapter::record::Record, mqtt::bridge::config_http::HttpConnectorConfig};
+use reqwest::{header::CONTENT_TYPE, Method};
+use serde_json::Value;
+use storage_adapter::storage::StorageAdapter;
+use tokio::sync::broadcast;
+
+use crate::{
+    handler::error::MqttBrokerError,
+    rule_engine::action::{render_json_template, render_template},
+};
+
+use super::{
+    core::{BridgePlugin, BridgePluginReadConfig},
+    message::{build_record_context, parse_payload},
+    runtime::ConnectorRuntime,
+};
+
+pub struct HttpBridgePlugin<S> {
+    runtime: ConnectorRuntime<S>,
+    config: HttpConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> HttpBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        runtime: ConnectorRuntime<S>,
+        config: HttpConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        HttpBridgePlugin {
+            runtime,
+            config,
+            stop_send,
+        }
+    }
+
+    fn request_failed(&self, message: String) -> MqttBrokerError {
+        MqttBrokerError::ConnectorRequestFailed(self.runtime.connector_name.clone(), message)
+    }
+
+    // Splits the records into requests of batch_size messages and sends up to
+    // max_concurrency requests at the same time. Returns once every request succeeded, or
+    // with the error of the first request that failed, the requests that are not sent yet are
+    // dropped. The records are then retried with the retry policy of the connector.
+    pub async fn append(
+        &self,
+        client: &reqwest::Client,
+        records: &[Record],
+    ) -> Result<(), MqttBrokerError> {
+        let bodies: Vec<String> = records
+            .iter()
+            .map(|record| render_body(&self.config, record))
+            .collect();
+
+        let batch_size = self.config.batch_size.max(1) as usize;
+        stream::iter(bodies.chunks(batch_size))
+            .map(|chunk| async move {
+                self.request(client, &build_request_body(&self.config, chunk))
+                    .await
+            })
+            .buffer_unordered(self.config.max_concurrency.max(1) as usize)
+            .try_collect::<Vec<()>>()
+            .await?;
+        Ok(())
+    }
+
+    async fn request(&self, client: &reqwest::Client, body: &str) -> Result<(), MqttBrokerError> {
+        let method = Method::from_bytes(self.config.method.to_uppercase().as_bytes())
+            .map_err(|e| self.request_failed(e.to_string()))?;
+
+        let mut request = client.request(method, &self.config.url);
+        if !self
+            .config
+            .headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case(CONTENT_TYPE.as_str()))
+        {
+            let content_type = if serde_json::from_str::<Value>(body).is_ok() {
+                "application/json"
+            } else {
+                "text/plain"
+            };
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+        for (key, value) in self.config.headers.iter() {
+            request = request.header(key, value);
+        }
+
+        let response = request
+            .body(body.to_owned())
+            .send()
+            .await
+            .map_err(|e| self.request_failed(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(self.request_failed(format!("response status {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for HttpBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let mut recv = self.stop_send.subscribe();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(self.config.timeout_ms))
+            .build()
+            .map_err(|e| self.request_failed(e.to_string()))?;
+        let client = &client;
+
+        // The offset is only committed after every request succeeded.
+        self.runtime
+            .run_sink(&config, &mut recv, move |records| async move {
+                self.append(client, &records).await
+            })
+            .await
+    }
+}
+
+// Renders the body template with the fields of the message. A template that is a JSON object
+// or array is rendered with render_json_template, so quotes in the fields are escaped.
+pub fn render_body(config: &HttpConnectorConfig, record: &Record) -> String {
+    let context = build_record_context(record);
+    let body = config.body.trim_start();
+    if body.starts_with('{') || body.starts_with('[') {
+        return render_json_template(&config.body, &context);
+    }
+    render_template(&config.body, &context)
+}
+
+// With a batch_size of 1 the body of the message is sent as it is, otherwise the request
+// body is a JSON array of the bodies, each parsed as JSON if possible.
+pub fn build_request_body(config: &HttpConnectorConfig, bodies: &[String]) -> String {
+    if config.batch_size <= 1 && bodies.len() == 1 {
+        return bodies[0].clone();
+    }
+    let values: Vec<Value> = bodies
+        .iter()
+        .map(|body| parse_payload(body.as_bytes()))
+        .collect();
+    Value::Array(values).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use axum::{extract::State, http::StatusCode, routing::post, Router};
+    use common_base::{
+        config::broker_mqtt::{init_broker_mqtt_conf_by_config, BrokerMqttConfig},
+        tools::unique_id,
+    };
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::{
+        adapter::record::Record,
+        mqtt::bridge::{
+            config_common::{ConnectorCommonConfig, ConnectorRetryConfig},
+            config_http::HttpConnectorConfig,
+        },
+    };
+    use serde_json::Value;
+    use storage_adapter::{memory::MemoryStorageAdapter, storage::StorageAdapter};
+    use tokio::{net::TcpListener, sync::broadcast, time::sleep};
+
+    use super::{build_request_body, render_body, HttpBridgePlugin};
+    use crate::{
+        bridge::{
+            core::{BridgePlugin, BridgePluginReadConfig},
+            manager::ConnectorManager,
+            message::build_record,
+            runtime::ConnectorRuntime,
+        },
+        handler::cache::CacheManager,
+        storage::message::MessageStorage,
+    };
+
+    #[derive(Clone, Default)]
+    struct Endpoint {
+        requests: Arc<AtomicU32>,
+        bodies: Arc<Mutex<Vec<String>>>,
+    }
+
+    // The first request fails, so its messages are only received after the connector retried.
+    async fn receive(State(endpoint): State<Endpoint>, body: String) -> StatusCode {
+        if endpoint.requests.fetch_add(1, Ordering::SeqCst) == 0 {
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+        endpoint.bodies.lock().unwrap().push(body);
+        StatusCode::OK
+    }
+
+    #[test]
+    fn render_body_test() {
+        let record = build_record("c1", r#"{"temp":30}"#);
+        let config = HttpConnectorConfig {
+            body: r#"{"device":"${clientid}","topic":"${topic}","data":${payload}}"#.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_body(&config, &record),
+            r#"{"device":"c1","topic":"sensor/1/temp","data":{"temp":30}}"#
+        );
+
+        let config = HttpConnectorConfig::default();
+        let bodies = vec![render_body(&config, &record)];
+        assert_eq!(build_request_body(&config, &bodies), r#"{"temp":30}"#);
+
+        let config = HttpConnectorConfig {
+            batch_size: 10,
+            ..Default::default()
+        };
+        let bodies = vec![r#"{"temp":30}"#.to_string(), "text".to_string()];
+        assert_eq!(
+            build_request_body(&config, &bodies),
+            r#"[{"temp":30},"text"]"#
+        );
+    }
+
+    #[test]
+    fn render_body_escape_test() {
+        let record = build_record(r#"c"1"#, r#"say "hi""#);
+        let config = HttpConnectorConfig {
+            body: r#"{"device":"${clientid}","msg":"${payload}","data":${payload}}"#.to_string(),
+            ..Default::default()
+        };
+        let body: Value = serde_json::from_str(&render_body(&config, &record)).unwrap();
+        assert_eq!(body["device"], r#"c"1"#);
+        assert_eq!(body["msg"], r#"say "hi""#);
+        assert_eq!(body["data"], r#"say "hi""#);
+
+        let config = HttpConnectorConfig {
+            body: "device ${clientid}".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(render_body(&config, &record), r#"device c"1"#);
+    }
+
+    #[tokio::test]
+    async fn http_bridge_plugin_test() {
+        let namespace = unique_id();
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: namespace.clone(),
+            ..Default::default()
+        });
+
+        let endpoint = Endpoint::default();
+        let app = Router::new()
+            .route("/messages", post(receive))
+            .with_state(endpoint.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let topic_id = "test_topic".to_string();
+        let records: Vec<Record> = (0..4)
+            .map(|i| build_record(&format!("c{}", i), &format!("{}", i)))
+            .collect();
+        storage_adapter
+            .batch_write(namespace.clone(), topic_id.clone(), records)
+            .await
+            .unwrap();
+
+        let connector_name = unique_id();
+        let client_pool = Arc::new(ClientPool::new(1));
+        let (stop_send, _) = broadcast::channel(1);
+        let plugin = HttpBridgePlugin::new(
+            ConnectorRuntime::new(
+                Arc::new(CacheManager::new(client_pool.clone(), namespace.clone())),
+                client_pool.clone(),
+                Arc::new(ConnectorManager::new()),
+                storage_adapter.clone(),
+                connector_name.clone(),
+                ConnectorCommonConfig {
+                    retry: ConnectorRetryConfig {
+                        retry_backoff_ms: 10,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ),
+            HttpConnectorConfig {
+                url: format!("http://{}/messages", addr),
+                body: "${payload}".to_string(),
+                batch_size: 2,
+                max_concurrency: 1,
+                ..Default::default()
+            },
+            stop_send.clone(),
+        );
+        let read_config = BridgePluginReadConfig {
+            topic_id: topic_id.clone(),
+            record_num: 4,
+        };
+        let handle = tokio::spawn(async move {
+            plugin.exec(read_config).await.unwrap();
+        });
+
+        sleep(Duration::from_secs(2)).await;
+        stop_send.send(true).unwrap();
+        handle.await.unwrap();
+
+        assert_eq!(
+            endpoint.bodies.lock().unwrap().clone(),
+            vec!["[0,1]".to_string(), "[2,3]".to_string()]
+        );
+
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&connector_name)
+                .await
+                .unwrap(),
+            4
+        );
+    }
+}
@@ -12,26 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{sync::Arc, time::Duration};
+use std::time::Duration;
 
 use axum::async_trait;
-use log::{error, info};
-use metadata_struct::{adapter::record::Record, mqtt::bridge::config_kafka::KafkaConnectorConfig};
-use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use futures::future::join_all;
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::bridge::config_kafka::{KafkaConnectorConfig, KafkaPayloadFormat},
+};
+use rdkafka::producer::{FutureProducer, FutureRecord};
 use storage_adapter::storage::StorageAdapter;
-use tokio::{select, sync::broadcast, time::sleep};
+use tokio::sync::broadcast;
 
-use crate::{handler::error::MqttBrokerError, storage::message::MessageStorage};
+use crate::{handler::error::MqttBrokerError, rule_engine::action::render_template};
 
 use super::{
     core::{BridgePlugin, BridgePluginReadConfig},
-    manager::ConnectorManager,
+    message::build_record_context_and_payload,
+    runtime::ConnectorRuntime,
 };
 
+pub mod source;
+
 pub struct KafkaBridgePlugin<S> {
-    connector_manager: Arc<ConnectorManager>,
-    message_storage: Arc<S>,
-    connector_name: String,
+    runtime: ConnectorRuntime<S>,
     config: KafkaConnectorConfig,
     stop_send: broadcast::Sender<bool>,
 }
@@ -41,40 +45,54 @@ impl<S> KafkaBridgePlugin<S>
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
     pub fn new(
-        connector_manager: Arc<ConnectorManager>,
-        message_storage: Arc<S>,
-        connector_name: String,
+        runtime: ConnectorRuntime<S>,
         config: KafkaConnectorConfig,
         stop_send: broadcast::Sender<bool>,
     ) -> Self {
         KafkaBridgePlugin {
-            connector_manager,
-            message_storage,
-            connector_name,
+            runtime,
             config,
             stop_send,
         }
     }
 
+    fn build_producer(&self) -> Result<FutureProducer, MqttBrokerError> {
+        // acks=all together with idempotence makes sure a message is only acknowledged
+        // once it is persisted by all in-sync replicas, and retries do not duplicate it.
+        Ok(rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", self.config.bootstrap_servers.as_str())
+            .set("message.timeout.ms", "5000")
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .set("batch.num.messages", self.config.batch_size.to_string())
+            .set("linger.ms", self.config.linger_ms.to_string())
+            .set("compression.type", self.config.compression_type.as_str())
+            .create()?)
+    }
+
+    // Sends the records and waits until all of them are acknowledged by Kafka.
     pub async fn append(
         &self,
-        records: &Vec<Record>,
-        producer: FutureProducer,
+        records: &[Record],
+        producer: &FutureProducer,
     ) -> Result<(), MqttBrokerError> {
+        let mut messages = Vec::with_capacity(records.len());
         for record in records {
-            let data = serde_json::to_string(record)?;
-            producer
-                .send(
-                    FutureRecord::to(self.config.topic.as_str())
-                        .key(self.config.key.as_str())
-                        .payload(&data),
-                    Duration::from_secs(0),
-                )
-                .await
-                .map_err(|(e, _)| e)?;
+            messages.push(build_kafka_message(&self.config, record)?);
         }
 
-        producer.flush(Duration::from_secs(0))?;
+        let deliveries = messages.iter().map(|(key, payload)| {
+            producer.send(
+                FutureRecord::to(self.config.topic.as_str())
+                    .key(key)
+                    .payload(payload),
+                Duration::from_secs(0),
+            )
+        });
+
+        for result in join_all(deliveries).await {
+            result.map_err(|(e, _)| e)?;
+        }
         Ok(())
     }
 }
@@ -85,49 +103,188 @@ impl<S> BridgePlugin for KafkaBridgePlugin<S>
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
     async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
-        let message_storage = MessageStorage::new(self.message_storage.clone());
-        let group_name = self.connector_name.clone();
-        let offset = message_storage.get_group_offset(&group_name).await?;
         let mut recv = self.stop_send.subscribe();
-        let producer: FutureProducer = rdkafka::ClientConfig::new()
-            .set("bootstrap.servers", self.config.bootstrap_servers.as_str())
-            .set("message.timeout.ms", "5000")
-            .create()?;
-
-        loop {
-            select! {
-                val = recv.recv() =>{
-                    if let Ok(flag) = val {
-                        if flag {
-                            info!("{}","Connector thread exited successfully");
-                            break;
-                        }
-                    }
-                }
-
-                val = message_storage.read_topic_message(&config.topic_id, offset, config.record_num) => {
-                    match val {
-                        Ok(data) => {
-                            self.connector_manager.report_heartbeat(&self.connector_name);
-                            if data.is_empty() {
-                                tokio::time::sleep(Duration::from_millis(100)).await;
-                                continue;
-                            }
-
-                            if let Err(e) = self.append(&data, producer.clone()).await{
-                                error!("Connector {} failed to write data to kafka topic {}, error message: {}", self.connector_name, self.config.topic, e);
-                                sleep(Duration::from_millis(100)).await;
-                            }
-                        },
-                        Err(e) => {
-                            error!("Connector {} failed to read Topic {} data with error message :{}", self.connector_name,config.topic_id,e);
-                            sleep(Duration::from_millis(100)).await;
-                        }
-                    }
-                }
-            }
+        let producer = self.build_producer()?;
+        let producer = &producer;
+
+        // The offset is only committed after all records are acknowledged.
+        self.runtime
+            .run_sink(&config, &mut recv, move |records| async move {
+                self.append(&records, producer).await
+            })
+            .await
+    }
+}
+
+// Builds the key and the payload of the Kafka message. Records that are not MQTT
+// messages are sent as they are, with only ${payload} available to the key.
+pub fn build_kafka_message(
+    config: &KafkaConnectorConfig,
+    record: &Record,
+) -> Result<(String, Vec<u8>), MqttBrokerError> {
+    let (context, raw_payload) = build_record_context_and_payload(record);
+
+    let key = render_template(&config.key, &context);
+    let payload = match config.payload_format {
+        KafkaPayloadFormat::Raw => raw_payload,
+        KafkaPayloadFormat::Envelope => serde_json::to_vec(&context)?,
+    };
+    Ok((key, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use common_base::{
+        config::broker_mqtt::{init_broker_mqtt_conf_by_config, BrokerMqttConfig},
+        tools::unique_id,
+    };
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::{
+        adapter::record::Record,
+        mqtt::bridge::config_kafka::{KafkaConnectorConfig, KafkaPayloadFormat},
+    };
+    use rdkafka::{
+        consumer::{Consumer, StreamConsumer},
+        mocking::MockCluster,
+        Message,
+    };
+    use serde_json::Value;
+    use storage_adapter::{memory::MemoryStorageAdapter, storage::StorageAdapter};
+    use tokio::{sync::broadcast, time::timeout};
+
+    use super::{build_kafka_message, KafkaBridgePlugin};
+    use crate::{
+        bridge::{
+            core::{BridgePlugin, BridgePluginReadConfig},
+            manager::ConnectorManager,
+            message::build_record,
+            runtime::ConnectorRuntime,
+        },
+        handler::cache::CacheManager,
+        storage::message::MessageStorage,
+    };
+
+    #[test]
+    fn build_kafka_message_test() {
+        let record = build_record("c1", r#"{"device":"d1","temp":30}"#);
+
+        let config = KafkaConnectorConfig {
+            key: "${topic}/${clientid}/${payload.device}".to_string(),
+            payload_format: KafkaPayloadFormat::Raw,
+            ..Default::default()
+        };
+        let (key, payload) = build_kafka_message(&config, &record).unwrap();
+        assert_eq!(key, "sensor/1/temp/c1/d1");
+        assert_eq!(payload, br#"{"device":"d1","temp":30}"#.to_vec());
+
+        let config = KafkaConnectorConfig {
+            key: "fixed-key".to_string(),
+            payload_format: KafkaPayloadFormat::Envelope,
+            ..Default::default()
+        };
+        let (key, payload) = build_kafka_message(&config, &record).unwrap();
+        assert_eq!(key, "fixed-key");
+        let envelope: Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(envelope["clientid"], "c1");
+        assert_eq!(envelope["topic"], "sensor/1/temp");
+        assert_eq!(envelope["payload"]["temp"], 30);
+
+        // records that are not MQTT messages are sent as they are
+        let record = Record::build_str("plain text".to_string());
+        let config = KafkaConnectorConfig {
+            key: "${clientid}".to_string(),
+            payload_format: KafkaPayloadFormat::Raw,
+            ..Default::default()
+        };
+        let (key, payload) = build_kafka_message(&config, &record).unwrap();
+        assert_eq!(key, "");
+        assert_eq!(payload, b"plain text".to_vec());
+    }
+
+    #[tokio::test]
+    async fn kafka_bridge_plugin_test() {
+        let namespace = unique_id();
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: namespace.clone(),
+            ..Default::default()
+        });
+
+        // the mock cluster of librdkafka stands in for a real Kafka cluster
+        let cluster = MockCluster::new(1).unwrap();
+        let kafka_topic = "robustmq-bridge";
+        cluster.create_topic(kafka_topic, 1, 1).unwrap();
+
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let topic_id = "test_topic".to_string();
+        let records: Vec<Record> = (0..10)
+            .map(|i| build_record(&format!("c{}", i), &format!("data-{}", i)))
+            .collect();
+        storage_adapter
+            .batch_write(namespace.clone(), topic_id.clone(), records)
+            .await
+            .unwrap();
+
+        let connector_name = unique_id();
+        let client_pool = Arc::new(ClientPool::new(1));
+        let (stop_send, _) = broadcast::channel(1);
+        let plugin = KafkaBridgePlugin::new(
+            ConnectorRuntime::new(
+                Arc::new(CacheManager::new(client_pool.clone(), namespace.clone())),
+                client_pool.clone(),
+                Arc::new(ConnectorManager::new()),
+                storage_adapter.clone(),
+                connector_name.clone(),
+                Default::default(),
+            ),
+            KafkaConnectorConfig {
+                bootstrap_servers: cluster.bootstrap_servers(),
+                topic: kafka_topic.to_string(),
+                key: "${clientid}".to_string(),
+                payload_format: KafkaPayloadFormat::Raw,
+                batch_size: 4,
+                ..Default::default()
+            },
+            stop_send.clone(),
+        );
+
+        let read_config = BridgePluginReadConfig {
+            topic_id: topic_id.clone(),
+            record_num: 4,
+        };
+        let handle = tokio::spawn(async move {
+            plugin.exec(read_config).await.unwrap();
+        });
+
+        let consumer: StreamConsumer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", cluster.bootstrap_servers())
+            .set("group.id", "kafka_bridge_plugin_test")
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .unwrap();
+        consumer.subscribe(&[kafka_topic]).unwrap();
+
+        for i in 0..10 {
+            let message = timeout(Duration::from_secs(30), consumer.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(message.key(), Some(format!("c{}", i).as_bytes()));
+            assert_eq!(message.payload(), Some(format!("data-{}", i).as_bytes()));
         }
 
-        Ok(())
+        stop_send.send(true).unwrap();
+        handle.await.unwrap();
+
+        // all records are acknowledged, so the offset of the connector is committed
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&connector_name)
+                .await
+                .unwrap(),
+            10
+        );
     }
 }
@@ -0,0 +1,306 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use axum::async_trait;
+use bytes::Bytes;
+use grpc_clients::pool::ClientPool;
+use log::{error, info};
+use metadata_struct::mqtt::bridge::config_source::{KafkaSourceConnectorConfig, SourceStartFrom};
+use protocol::mqtt::common::{qos, Publish, PublishProperties, QoS};
+use rdkafka::{
+    consumer::{Consumer, StreamConsumer},
+    message::Headers,
+    ClientConfig, Message, Offset, TopicPartitionList,
+};
+use serde_json::json;
+use storage_adapter::storage::StorageAdapter;
+use tokio::{
+    select,
+    sync::broadcast,
+    time::{sleep, Instant},
+};
+
+use crate::{
+    bridge::{
+        core::{BridgePlugin, BridgePluginReadConfig},
+        manager::ConnectorManager,
+        message::parse_payload,
+        source::SourcePublisher,
+    },
+    handler::error::MqttBrokerError,
+    rule_engine::action::render_template,
+    storage::connector::ConnectorStorage,
+};
+
+// Consumes a Kafka topic and publishes the records to MQTT topics. The next offset of each
+// partition is stored as the source offset of the connector.
+pub struct KafkaSourceBridgePlugin<S> {
+    client_pool: Arc<ClientPool>,
+    connector_manager: Arc<ConnectorManager>,
+    publisher: SourcePublisher<S>,
+    connector_name: String,
+    config: KafkaSourceConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> KafkaSourceBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        client_pool: Arc<ClientPool>,
+        connector_manager: Arc<ConnectorManager>,
+        publisher: SourcePublisher<S>,
+        connector_name: String,
+        config: KafkaSourceConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        KafkaSourceBridgePlugin {
+            client_pool,
+            connector_manager,
+            publisher,
+            connector_name,
+            config,
+            stop_send,
+        }
+    }
+
+    // The partitions are assigned to the consumer directly instead of joining a consumer
+    // group, a partition without a source offset starts from start_from.
+    fn build_consumer(
+        &self,
+        offsets: &HashMap<String, u64>,
+    ) -> Result<StreamConsumer, MqttBrokerError> {
+        let group_id = if self.config.group_id.is_empty() {
+            format!("robustmq-{}", self.connector_name)
+        } else {
+            self.config.group_id.clone()
+        };
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", self.config.bootstrap_servers.as_str())
+            .set("group.id", group_id)
+            .set("enable.auto.commit", "false")
+            .set("enable.auto.offset.store", "false")
+            .create()?;
+
+        let metadata =
+            consumer.fetch_metadata(Some(&self.config.topic), Duration::from_secs(10))?;
+        let mut assignment = TopicPartitionList::new();
+        for topic in metadata.topics() {
+            for partition in topic.partitions() {
+                let offset = match offsets.get(&partition.id().to_string()) {
+                    Some(offset) => Offset::Offset(*offset as i64),
+                    None => match self.config.start_from {
+                        SourceStartFrom::Earliest => Offset::Beginning,
+                        SourceStartFrom::Latest => Offset::End,
+                    },
+                };
+                assignment.add_partition_offset(topic.name(), partition.id(), offset)?;
+            }
+        }
+        if assignment.count() == 0 {
+            return Err(MqttBrokerError::CommonError(format!(
+                "kafka topic {} has no partitions",
+                self.config.topic
+            )));
+        }
+        consumer.assign(&assignment)?;
+        Ok(consumer)
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for KafkaSourceBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, _: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let connector_storage = ConnectorStorage::new(self.client_pool.clone());
+        let mut recv = self.stop_send.subscribe();
+        let mut offsets = connector_storage
+            .get_source_offset(&self.connector_name)
+            .await?;
+        let consumer = self.build_consumer(&offsets)?;
+
+        // The offsets are stored at most once per interval, after a failover the records
+        // published since the last store are published again.
+        let commit_interval = Duration::from_millis(self.config.offset_commit_interval_ms);
+        let mut committed = offsets.clone();
+        let mut last_commit = Instant::now();
+
+        loop {
+            select! {
+                val = recv.recv() =>{
+                    if let Ok(true) = val {
+                        break;
+                    }
+                }
+
+                val = consumer.recv() => {
+                    match val {
+                        Ok(message) => {
+                            let (topic_name, publish, publish_properties) =
+                                build_kafka_source_publish(&self.config, &message);
+                            if !self
+                                .publisher
+                                .publish_with_retry(&topic_name, &publish, &publish_properties, &mut recv)
+                                .await
+                            {
+                                break;
+                            }
+                            offsets.insert(
+                                message.partition().to_string(),
+                                (message.offset() + 1) as u64,
+                            );
+                        }
+                        Err(e) => {
+                            error!("Connector {} failed to consume Kafka topic {} with error message :{}", self.connector_name, self.config.topic, e);
+                            sleep(Duration::from_secs(1)).await;
+                        }
+                    }
+                }
+
+                _ = sleep(commit_interval) => {}
+            }
+
+            self.connector_manager
+                .report_heartbeat(&self.connector_name);
+            if offsets != committed && last_commit.elapsed() >= commit_interval {
+                match connector_storage
+                    .set_source_offset(&self.connector_name, &offsets)
+                    .await
+                {
+                    Ok(()) => {
+                        committed = offsets.clone();
+                        last_commit = Instant::now();
+                    }
+                    Err(e) => {
+                        error!(
+                            "Connector {} failed to store the source offset, error message: {}",
+                            self.connector_name, e
+                        );
+                    }
+                }
+            }
+        }
+
+        if offsets != committed {
+            connector_storage
+                .set_source_offset(&self.connector_name, &offsets)
+                .await?;
+        }
+        info!("{}", "Connector thread exited successfully");
+        Ok(())
+    }
+}
+
+// Builds the MQTT publish of a Kafka record. The topic is rendered with the fields of the
+// record, and the headers of the record become user properties.
+pub fn build_kafka_source_publish<M: Message>(
+    config: &KafkaSourceConnectorConfig,
+    message: &M,
+) -> (String, Publish, Option<PublishProperties>) {
+    let payload = message.payload().unwrap_or_default();
+    let context = json!({
+        "topic": message.topic(),
+        "key": message.key().map(|key| String::from_utf8_lossy(key).to_string()),
+        "partition": message.partition(),
+        "offset": message.offset(),
+        "payload": parse_payload(payload),
+    });
+    let topic_name = render_template(&config.mqtt_topic, &context);
+
+    let mut user_properties = Vec::new();
+    if let Some(headers) = message.headers() {
+        for header in headers.iter() {
+            let value = header
+                .value
+                .map(|value| String::from_utf8_lossy(value).to_string())
+                .unwrap_or_default();
+            user_properties.push((header.key.to_string(), value));
+        }
+    }
+
+    let publish = Publish {
+        dup: false,
+        qos: qos(config.qos).unwrap_or(QoS::AtLeastOnce),
+        pkid: 0,
+        retain: config.retain,
+        topic: Bytes::from(topic_name.clone()),
+        payload: Bytes::copy_from_slice(payload),
+    };
+    let publish_properties = PublishProperties {
+        user_properties,
+        ..Default::default()
+    };
+    (topic_name, publish, Some(publish_properties))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use metadata_struct::mqtt::bridge::config_source::KafkaSourceConnectorConfig;
+    use protocol::mqtt::common::QoS;
+    use rdkafka::{
+        message::{Header, OwnedHeaders, OwnedMessage},
+        Timestamp,
+    };
+
+    use super::build_kafka_source_publish;
+
+    #[test]
+    fn build_kafka_source_publish_test() {
+        let message = OwnedMessage::new(
+            Some(br#"{"cmd":"reboot"}"#.to_vec()),
+            Some(b"device-1".to_vec()),
+            "commands".to_string(),
+            Timestamp::NotAvailable,
+            2,
+            41,
+            Some(OwnedHeaders::new().insert(Header {
+                key: "trace-id",
+                value: Some("t1"),
+            })),
+        );
+        let config = KafkaSourceConnectorConfig {
+            mqtt_topic: "devices/${key}/${payload.cmd}".to_string(),
+            qos: 2,
+            retain: true,
+            ..Default::default()
+        };
+
+        let (topic_name, publish, publish_properties) =
+            build_kafka_source_publish(&config, &message);
+        assert_eq!(topic_name, "devices/device-1/reboot");
+        assert_eq!(publish.topic, Bytes::from("devices/device-1/reboot"));
+        assert_eq!(publish.payload, Bytes::from(r#"{"cmd":"reboot"}"#));
+        assert_eq!(publish.qos, QoS::ExactlyOnce);
+        assert!(publish.retain);
+        assert_eq!(
+            publish_properties.unwrap().user_properties,
+            vec![("trace-id".to_string(), "t1".to_string())]
+        );
+
+        let config = KafkaSourceConnectorConfig {
+            mqtt_topic: "kafka/${topic}/${partition}".to_string(),
+            ..Default::default()
+        };
+        let (topic_name, publish, _) = build_kafka_source_publish(&config, &message);
+        assert_eq!(topic_name, "kafka/commands/2");
+        assert_eq!(publish.qos, QoS::AtLeastOnce);
+        assert!(!publish.retain);
+    }
+}
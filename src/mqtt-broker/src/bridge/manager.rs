@@ -14,7 +14,10 @@
 
 use common_base::tools::now_second;
 use dashmap::DashMap;
-use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+use metadata_struct::mqtt::bridge::{
+    connector::MQTTConnector,
+    status::{ConnectorRuntimeStatus, MQTTStatus},
+};
 
 use super::core::BridgePluginThread;
 
@@ -28,6 +31,9 @@ pub struct ConnectorManager {
 
     // (connector_name, u64)
     pub connector_heartbeat: DashMap<String, u64>,
+
+    // (connector_name, ConnectorRuntimeStatus)
+    pub connector_status: DashMap<String, ConnectorRuntimeStatus>,
 }
 
 impl ConnectorManager {
@@ -36,6 +42,7 @@ pub fn new() -> Self {
             connector_list: DashMap::with_capacity(8),
             connector_thread: DashMap::with_capacity(8),
             connector_heartbeat: DashMap::with_capacity(8),
+            connector_status: DashMap::with_capacity(8),
         }
     }
 
@@ -96,4 +103,64 @@ pub fn report_heartbeat(&self, connector_name: &str) {
         self.connector_heartbeat
             .insert(connector_name.to_owned(), now_second());
     }
+
+    // Connector Status
+    pub fn set_connector_status(&self, connector_name: &str, status: MQTTStatus) {
+        let mut entry = self
+            .connector_status
+            .entry(connector_name.to_owned())
+            .or_default();
+        entry.status = status;
+        entry.stopped = false;
+    }
+
+    // The thread of the connector exited, the status is removed once it has been reported.
+    pub fn set_connector_stopped(&self, connector_name: &str) {
+        self.connector_heartbeat.remove(connector_name);
+        let mut entry = self
+            .connector_status
+            .entry(connector_name.to_owned())
+            .or_default();
+        entry.status = MQTTStatus::Idle;
+        entry.stopped = true;
+    }
+
+    pub fn report_success(&self, connector_name: &str) {
+        let mut entry = self
+            .connector_status
+            .entry(connector_name.to_owned())
+            .or_default();
+        entry.status = MQTTStatus::Running;
+        entry.last_success_time = now_second();
+    }
+
+    // A failed connector stays Failed until the next success.
+    pub fn report_error(&self, connector_name: &str, error: String, failed: bool) {
+        let mut entry = self
+            .connector_status
+            .entry(connector_name.to_owned())
+            .or_default();
+        if failed {
+            entry.status = MQTTStatus::Failed;
+        }
+        entry.last_error = error;
+        entry.last_error_time = now_second();
+    }
+
+    pub fn get_connector_status(&self, connector_name: &str) -> Option<ConnectorRuntimeStatus> {
+        if let Some(status) = self.connector_status.get(connector_name) {
+            return Some(status.clone());
+        }
+
+        None
+    }
+
+    pub fn remove_connector_status(&self, connector_name: &str) {
+        self.connector_status.remove(connector_name);
+    }
+
+    pub fn remove_stopped_connector_status(&self, connector_name: &str) {
+        self.connector_status
+            .remove_if(connector_name, |_, status| status.stopped);
+    }
 }
@@ -0,0 +1,71 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use metadata_struct::{adapter::record::Record, mqtt::message::MqttMessage};
+use serde_json::{json, Map, Value};
+
+// The payload as JSON if it can be parsed, otherwise as a string.
+pub fn parse_payload(payload: &[u8]) -> Value {
+    serde_json::from_slice::<Value>(payload)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(payload).to_string()))
+}
+
+// The fields of a message that are available to the templates of the connectors,
+// e.g. ${topic}, ${clientid} and ${payload.device}.
+pub fn build_message_context(message: &MqttMessage) -> Value {
+    let mut user_properties = Map::new();
+    for (key, value) in message.user_properties.iter() {
+        user_properties.insert(key.clone(), Value::String(value.clone()));
+    }
+
+    json!({
+        "clientid": message.client_id,
+        "topic": String::from_utf8_lossy(&message.topic),
+        "qos": message.qos as u8,
+        "retain": message.retain,
+        "payload": parse_payload(&message.payload),
+        "create_time": message.create_time,
+        "user_properties": user_properties,
+    })
+}
+
+// The template context of a record, see build_record_context_and_payload.
+pub fn build_record_context(record: &Record) -> Value {
+    build_record_context_and_payload(record).0
+}
+
+// The template context and the payload of a record. Records that are not MQTT messages only
+// have ${payload}, and their payload is the data of the record.
+pub fn build_record_context_and_payload(record: &Record) -> (Value, Vec<u8>) {
+    match MqttMessage::decode_record(record.clone()) {
+        Ok(message) => (build_message_context(&message), message.payload.to_vec()),
+        Err(_) => (
+            json!({ "payload": parse_payload(&record.data) }),
+            record.data.clone(),
+        ),
+    }
+}
+
+// The MQTT message record written to the topic of a connector in the tests of the connectors.
+#[cfg(test)]
+pub fn build_record(client_id: &str, payload: &str) -> Record {
+    let message = MqttMessage {
+        client_id: client_id.to_string(),
+        topic: bytes::Bytes::from("sensor/1/temp"),
+        payload: bytes::Bytes::from(payload.to_string()),
+        create_time: 1740817800,
+        ..Default::default()
+    };
+    Record::build_byte(message.encode())
+}
@@ -15,5 +15,14 @@
 pub mod core;
 pub mod file;
 pub mod heartbeat;
+pub mod http;
 pub mod kafka;
 pub mod manager;
+pub mod message;
+pub mod mqtt;
+pub mod redis;
+pub mod runtime;
+pub mod s3;
+pub mod source;
+pub mod sql;
+pub mod transform;
@@ -0,0 +1,368 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Display, path::Path, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use metadata_struct::mqtt::bridge::config_mqtt::{
+    MqttConnectorConfig, MqttConnectorProtocolVersion, MqttConnectorTlsConfig,
+};
+use rumqttc::{
+    tokio_rustls::rustls::{
+        self,
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    },
+    v5::{
+        self,
+        mqttbytes::v5::{ConnectProperties, Packet as PacketV5, Publish as PublishV5},
+    },
+    Event, Packet, Publish, TlsConfiguration, Transport,
+};
+
+use crate::{
+    handler::error::MqttBrokerError,
+    server::tcp::tls_server::{load_certs, load_key},
+};
+
+use super::MQTT_CONNECTOR_SESSION_EXPIRY;
+
+// The requests queued to the event loop of the client, the messages are written locally and
+// acknowledged one by one, so only the subscription and an acknowledgement are queued.
+const INGRESS_REQUEST_CAPACITY: usize = 10;
+
+// The client of an ingress connector. The received messages are acknowledged to the remote
+// broker only by ack, after they are written locally, and the remote session keeps the
+// messages that are not acknowledged yet while the connector is disconnected.
+pub enum IngressClient {
+    V311(rumqttc::AsyncClient, rumqttc::EventLoop),
+    V5(v5::AsyncClient, v5::EventLoop),
+}
+
+// A message received from the remote broker.
+pub struct IngressMessage {
+    pub topic: String,
+    pub payload: Bytes,
+    pub qos: u8,
+    pub retain: bool,
+    pub user_properties: Vec<(String, String)>,
+    pub content_type: Option<String>,
+    publish: IngressPublish,
+}
+
+enum IngressPublish {
+    V311(Publish),
+    V5(PublishV5),
+}
+
+impl From<Publish> for IngressMessage {
+    fn from(publish: Publish) -> Self {
+        IngressMessage {
+            topic: publish.topic.clone(),
+            payload: publish.payload.clone(),
+            qos: publish.qos as u8,
+            retain: publish.retain,
+            user_properties: Vec::new(),
+            content_type: None,
+            publish: IngressPublish::V311(publish),
+        }
+    }
+}
+
+impl From<PublishV5> for IngressMessage {
+    fn from(publish: PublishV5) -> Self {
+        let properties = publish.properties.clone().unwrap_or_default();
+        IngressMessage {
+            topic: String::from_utf8_lossy(&publish.topic).to_string(),
+            payload: publish.payload.clone(),
+            qos: publish.qos as u8,
+            retain: publish.retain,
+            user_properties: properties.user_properties,
+            content_type: properties.content_type,
+            publish: IngressPublish::V5(publish),
+        }
+    }
+}
+
+impl IngressClient {
+    // Connects to the remote broker and waits for the CONNACK.
+    pub async fn connect(
+        config: &MqttConnectorConfig,
+        client_id: String,
+    ) -> Result<Self, MqttBrokerError> {
+        let (host, port, is_tls) = parse_server(&config.server)?;
+        let transport = match &config.tls {
+            Some(tls) => Transport::tls_with_config(TlsConfiguration::Rustls(Arc::new(
+                build_tls_config(tls)?,
+            ))),
+            None if is_tls => Transport::tls_with_config(TlsConfiguration::Rustls(Arc::new(
+                build_tls_config(&MqttConnectorTlsConfig::default())?,
+            ))),
+            None => Transport::Tcp,
+        };
+
+        let mut client = match config.protocol_version {
+            MqttConnectorProtocolVersion::V311 => {
+                let mut options = rumqttc::MqttOptions::new(client_id, host, port);
+                options
+                    .set_transport(transport)
+                    .set_keep_alive(Duration::from_secs(30))
+                    .set_clean_session(false)
+                    .set_manual_acks(true);
+                if !config.username.is_empty() {
+                    options.set_credentials(config.username.as_str(), config.password.as_str());
+                }
+                let (client, eventloop) =
+                    rumqttc::AsyncClient::new(options, INGRESS_REQUEST_CAPACITY);
+                IngressClient::V311(client, eventloop)
+            }
+            MqttConnectorProtocolVersion::V5 => {
+                let mut options = v5::MqttOptions::new(client_id, host, port);
+                let mut properties = ConnectProperties::new();
+                properties.session_expiry_interval = Some(MQTT_CONNECTOR_SESSION_EXPIRY);
+                options
+                    .set_transport(transport)
+                    .set_keep_alive(Duration::from_secs(30))
+                    .set_connection_timeout(10)
+                    .set_clean_start(false)
+                    .set_connect_properties(properties)
+                    .set_manual_acks(true);
+                if !config.username.is_empty() {
+                    options.set_credentials(config.username.as_str(), config.password.as_str());
+                }
+                let (client, eventloop) = v5::AsyncClient::new(options, INGRESS_REQUEST_CAPACITY);
+                IngressClient::V5(client, eventloop)
+            }
+        };
+
+        loop {
+            let connected = match &mut client {
+                IngressClient::V311(_, eventloop) => matches!(
+                    eventloop.poll().await.map_err(client_error)?,
+                    Event::Incoming(Packet::ConnAck(_))
+                ),
+                IngressClient::V5(_, eventloop) => matches!(
+                    eventloop.poll().await.map_err(client_error)?,
+                    v5::Event::Incoming(PacketV5::ConnAck(_))
+                ),
+            };
+            if connected {
+                return Ok(client);
+            }
+        }
+    }
+
+    pub async fn subscribe(&self, topic: &str, qos: u8) -> Result<(), MqttBrokerError> {
+        match self {
+            IngressClient::V311(client, _) => {
+                let qos = rumqttc::qos(qos).map_err(client_error)?;
+                client.subscribe(topic, qos).await.map_err(client_error)
+            }
+            IngressClient::V5(client, _) => {
+                let qos = v5::mqttbytes::qos(qos)
+                    .ok_or_else(|| client_error(format!("invalid QoS {}", qos)))?;
+                client.subscribe(topic, qos).await.map_err(client_error)
+            }
+        }
+    }
+
+    // Drives the connection, returns the next message received from the remote broker or
+    // None for the other packets.
+    pub async fn poll(&mut self) -> Result<Option<IngressMessage>, MqttBrokerError> {
+        match self {
+            IngressClient::V311(_, eventloop) => match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::Publish(publish))) => Ok(Some(publish.into())),
+                Ok(_) => Ok(None),
+                Err(e) => Err(client_error(e)),
+            },
+            IngressClient::V5(_, eventloop) => match eventloop.poll().await {
+                Ok(v5::Event::Incoming(PacketV5::Publish(publish))) => Ok(Some(publish.into())),
+                Ok(_) => Ok(None),
+                Err(e) => Err(client_error(e)),
+            },
+        }
+    }
+
+    // Acknowledges the message to the remote broker, the acknowledgement is sent by the next
+    // poll.
+    pub async fn ack(&self, message: &IngressMessage) -> Result<(), MqttBrokerError> {
+        match (self, &message.publish) {
+            (IngressClient::V311(client, _), IngressPublish::V311(publish)) => {
+                client.ack(publish).await.map_err(client_error)
+            }
+            (IngressClient::V5(client, _), IngressPublish::V5(publish)) => {
+                client.ack(publish).await.map_err(client_error)
+            }
+            _ => Err(client_error("the message was received by another client")),
+        }
+    }
+
+    pub async fn disconnect(&mut self) {
+        let result = match self {
+            IngressClient::V311(client, _) => client.disconnect().await.map_err(client_error),
+            IngressClient::V5(client, _) => client.disconnect().await.map_err(client_error),
+        };
+        if result.is_ok() {
+            // The DISCONNECT is sent by the event loop.
+            let _ = tokio::time::timeout(Duration::from_secs(1), self.poll()).await;
+        }
+    }
+}
+
+fn client_error(e: impl Display) -> MqttBrokerError {
+    MqttBrokerError::CommonError(format!("mqtt client error: {}", e))
+}
+
+// tcp://host:1883 or ssl://host:8883, mqtt:// and mqtts:// are accepted as well. Returns the
+// host, the port and whether the scheme is a TLS one.
+pub fn parse_server(server: &str) -> Result<(String, u16, bool), MqttBrokerError> {
+    let (scheme, address) = server.split_once("://").unwrap_or(("tcp", server));
+    let is_tls = match scheme {
+        "tcp" | "mqtt" => false,
+        "ssl" | "mqtts" => true,
+        _ => {
+            return Err(client_error(format!(
+                "unsupported scheme {} of server {}",
+                scheme, server
+            )))
+        }
+    };
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>()
+                .map_err(|e| client_error(format!("invalid port of server {}: {}", server, e)))?,
+        ),
+        None => (address, if is_tls { 8883 } else { 1883 }),
+    };
+    if host.is_empty() {
+        return Err(client_error(format!("invalid server {}", server)));
+    }
+    Ok((host.to_string(), port, is_tls))
+}
+
+fn build_tls_config(tls: &MqttConnectorTlsConfig) -> Result<ClientConfig, MqttBrokerError> {
+    let builder = if tls.insecure_skip_verify {
+        ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(SkipServerVerification::new())
+    } else {
+        let mut roots = RootCertStore::empty();
+        let certs = if tls.ca_path.is_empty() {
+            rustls_native_certs::load_native_certs()?
+        } else {
+            load_certs(Path::new(&tls.ca_path))?
+        };
+        roots.add_parsable_certificates(certs);
+        ClientConfig::builder().with_root_certificates(roots)
+    };
+
+    if tls.cert_path.is_empty() {
+        return Ok(builder.with_no_client_auth());
+    }
+    let certs = load_certs(Path::new(&tls.cert_path))?;
+    let key_path = if tls.key_path.is_empty() {
+        &tls.cert_path
+    } else {
+        &tls.key_path
+    };
+    let key = load_key(Path::new(key_path))?;
+    builder
+        .with_client_auth_cert(certs, key)
+        .map_err(client_error)
+}
+
+// The TLS of rumqttc is built on another version of rustls than the QUIC server, so it has
+// its own verifier for insecure_skip_verify.
+#[derive(Debug)]
+struct SkipServerVerification(Arc<rustls::crypto::CryptoProvider>);
+
+impl SkipServerVerification {
+    fn new() -> Arc<Self> {
+        Arc::new(Self(Arc::new(rustls::crypto::ring::default_provider())))
+    }
+}
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_server;
+
+    #[test]
+    fn parse_server_test() {
+        assert_eq!(
+            parse_server("tcp://127.0.0.1:1883").unwrap(),
+            ("127.0.0.1".to_string(), 1883, false)
+        );
+        assert_eq!(
+            parse_server("ssl://cloud.example.com:8884").unwrap(),
+            ("cloud.example.com".to_string(), 8884, true)
+        );
+        assert_eq!(
+            parse_server("mqtts://cloud.example.com").unwrap(),
+            ("cloud.example.com".to_string(), 8883, true)
+        );
+        assert_eq!(
+            parse_server("localhost:1884").unwrap(),
+            ("localhost".to_string(), 1884, false)
+        );
+        assert!(parse_server("ws://localhost:8083").is_err());
+        assert!(parse_server("tcp://localhost:port").is_err());
+    }
+}
@@ -0,0 +1,479 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use axum::async_trait;
+use bytes::Bytes;
+use futures::future::join_all;
+use log::{error, info, warn};
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::{
+        bridge::config_mqtt::{
+            MqttConnectorConfig, MqttConnectorDirection, MqttConnectorProtocolVersion,
+        },
+        message::MqttMessage,
+    },
+};
+use paho_mqtt::{
+    AsyncClient, ConnectOptionsBuilder, CreateOptionsBuilder, Message, MessageBuilder, Properties,
+    PropertyCode, SslOptionsBuilder, MQTT_VERSION_3_1_1, MQTT_VERSION_5,
+};
+use protocol::mqtt::common::{qos, Publish, PublishProperties, QoS};
+use serde_json::{json, Map, Value};
+use storage_adapter::storage::StorageAdapter;
+use tokio::{
+    select,
+    sync::broadcast::{self, Receiver},
+    time::sleep,
+};
+
+use crate::{handler::error::MqttBrokerError, rule_engine::action::render_template};
+
+use self::ingress::{IngressClient, IngressMessage};
+use super::{
+    core::{BridgePlugin, BridgePluginReadConfig},
+    message::{build_message_context, parse_payload},
+    runtime::ConnectorRuntime,
+    source::SourcePublisher,
+};
+
+pub mod ingress;
+
+// The remote broker keeps the session of the connector for one day, so the messages
+// subscribed by an ingress connector are queued remotely while it is disconnected.
+const MQTT_CONNECTOR_SESSION_EXPIRY: u32 = 86400;
+
+pub struct MqttBridgePlugin<S> {
+    runtime: ConnectorRuntime<S>,
+    // Writes the messages received by an ingress connector into the local topics.
+    publisher: SourcePublisher<S>,
+    config: MqttConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> MqttBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        runtime: ConnectorRuntime<S>,
+        publisher: SourcePublisher<S>,
+        config: MqttConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        MqttBridgePlugin {
+            runtime,
+            publisher,
+            config,
+            stop_send,
+        }
+    }
+
+    fn client_id(&self) -> String {
+        if self.config.client_id.is_empty() {
+            format!("robustmq-bridge-{}", self.runtime.connector_name)
+        } else {
+            self.config.client_id.clone()
+        }
+    }
+
+    // Connects the client of an egress connector.
+    async fn connect(&self) -> Result<AsyncClient, MqttBrokerError> {
+        let mqtt_version = match self.config.protocol_version {
+            MqttConnectorProtocolVersion::V311 => MQTT_VERSION_3_1_1,
+            MqttConnectorProtocolVersion::V5 => MQTT_VERSION_5,
+        };
+        let create_opts = CreateOptionsBuilder::new()
+            .server_uri(self.config.server.as_str())
+            .client_id(self.client_id())
+            .mqtt_version(mqtt_version)
+            .finalize();
+        let client = AsyncClient::new(create_opts)?;
+
+        let mut conn_opts = match self.config.protocol_version {
+            MqttConnectorProtocolVersion::V311 => {
+                let mut builder = ConnectOptionsBuilder::new();
+                builder
+                    .mqtt_version(MQTT_VERSION_3_1_1)
+                    .clean_session(false);
+                builder
+            }
+            MqttConnectorProtocolVersion::V5 => {
+                let mut props = Properties::new();
+                props.push_u32(
+                    PropertyCode::SessionExpiryInterval,
+                    MQTT_CONNECTOR_SESSION_EXPIRY,
+                )?;
+                let mut builder = ConnectOptionsBuilder::new_v5();
+                builder.clean_start(false).properties(props);
+                builder
+            }
+        };
+        conn_opts
+            .keep_alive_interval(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10));
+        if !self.config.username.is_empty() {
+            conn_opts
+                .user_name(self.config.username.as_str())
+                .password(self.config.password.as_str());
+        }
+        if let Some(tls) = &self.config.tls {
+            let mut ssl_opts = SslOptionsBuilder::new();
+            if !tls.ca_path.is_empty() {
+                ssl_opts.trust_store(tls.ca_path.as_str())?;
+            }
+            if !tls.cert_path.is_empty() {
+                ssl_opts.key_store(tls.cert_path.as_str())?;
+            }
+            if !tls.key_path.is_empty() {
+                ssl_opts.private_key(tls.key_path.as_str())?;
+            }
+            ssl_opts
+                .enable_server_cert_auth(!tls.insecure_skip_verify)
+                .verify(!tls.insecure_skip_verify);
+            conn_opts.ssl_options(ssl_opts.finalize());
+        }
+
+        client.connect(conn_opts.finalize()).await?;
+        Ok(client)
+    }
+
+    // Forwards the messages of the local topic to the remote broker until the connector is
+    // stopped. The offset is only committed after the remote broker acknowledged the
+    // messages, so the local topic buffers the messages while the remote broker is down.
+    async fn run_egress(
+        &self,
+        client: &AsyncClient,
+        read_config: &BridgePluginReadConfig,
+        recv: &mut Receiver<bool>,
+    ) -> Result<(), MqttBrokerError> {
+        self.runtime
+            .run_sink(read_config, recv, move |records| async move {
+                if !client.is_connected() {
+                    client.reconnect().await?;
+                }
+                self.publish_remote(client, &records).await
+            })
+            .await
+    }
+
+    async fn publish_remote(
+        &self,
+        client: &AsyncClient,
+        records: &[Record],
+    ) -> Result<(), MqttBrokerError> {
+        let mut messages = Vec::with_capacity(records.len());
+        for record in records {
+            let message = match MqttMessage::decode_record(record.clone()) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!(
+                        "Connector {} skipped a record that is not an MQTT message, error message: {}",
+                        self.runtime.connector_name, e
+                    );
+                    continue;
+                }
+            };
+            messages.push(build_egress_message(&self.config, &message)?);
+        }
+
+        let deliveries = messages.into_iter().map(|message| client.publish(message));
+        for result in join_all(deliveries).await {
+            result?;
+        }
+        Ok(())
+    }
+
+    // Subscribes to the remote broker and writes the received messages into the local topics
+    // until the connector is stopped. A message is only acknowledged to the remote broker after
+    // it is written locally, the messages that are not acknowledged when the connector stops or
+    // the broker node fails are sent again by the remote broker after reconnecting.
+    async fn run_ingress(
+        &self,
+        client: &mut IngressClient,
+        recv: &mut Receiver<bool>,
+    ) -> Result<(), MqttBrokerError> {
+        client
+            .subscribe(&self.config.remote_topic, self.config.qos.unwrap_or(1))
+            .await?;
+
+        loop {
+            select! {
+                val = recv.recv() =>{
+                    if let Ok(flag) = val {
+                        if flag {
+                            return Ok(());
+                        }
+                    }
+                }
+
+                val = client.poll() => {
+                    self.runtime.report_heartbeat();
+                    let Some(message) = val? else {
+                        continue;
+                    };
+
+                    // The message goes through the same checks as a client publish. It is
+                    // retried until it is written, while waiting the next messages are held by
+                    // the remote broker.
+                    let (topic_name, publish, publish_properties) =
+                        build_ingress_publish(&self.config, &message);
+                    if !self
+                        .publisher
+                        .publish_with_retry(&topic_name, &publish, &publish_properties, recv)
+                        .await
+                    {
+                        return Ok(());
+                    }
+                    client.ack(&message).await?;
+                }
+            }
+        }
+    }
+
+    fn connected(&self) {
+        info!(
+            "Connector {} connected to remote broker {}",
+            self.runtime.connector_name, self.config.server
+        );
+    }
+
+    async fn wait_reconnect(&self, backoff: Duration, recv: &mut Receiver<bool>) -> bool {
+        select! {
+            val = recv.recv() => matches!(val, Ok(true)),
+            _ = sleep(backoff) => false,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for MqttBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let mut recv = self.stop_send.subscribe();
+        let min_backoff = Duration::from_millis(self.config.reconnect_min_interval_ms);
+        let max_backoff = Duration::from_millis(self.config.reconnect_max_interval_ms);
+        let mut backoff = min_backoff;
+
+        loop {
+            let result = match self.config.direction {
+                MqttConnectorDirection::Egress => match self.connect().await {
+                    Ok(client) => {
+                        self.connected();
+                        backoff = min_backoff;
+                        let result = self.run_egress(&client, &config, &mut recv).await;
+                        if client.is_connected() {
+                            let _ = client.disconnect(None).await;
+                        }
+                        result
+                    }
+                    Err(e) => Err(e),
+                },
+                MqttConnectorDirection::Ingress => {
+                    match IngressClient::connect(&self.config, self.client_id()).await {
+                        Ok(mut client) => {
+                            self.connected();
+                            backoff = min_backoff;
+                            let result = self.run_ingress(&mut client, &mut recv).await;
+                            // After an error the event loop would only connect again.
+                            if result.is_ok() {
+                                client.disconnect().await;
+                            }
+                            result
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            match result {
+                Ok(()) => break,
+                Err(e) => {
+                    error!(
+                        "Connector {} failed to forward messages with remote broker {}, reconnecting in {}ms, error message: {}",
+                        self.runtime.connector_name,
+                        self.config.server,
+                        backoff.as_millis(),
+                        e
+                    );
+                    if self.wait_reconnect(backoff, &mut recv).await {
+                        break;
+                    }
+                    backoff = (backoff * 2).min(max_backoff);
+                }
+            }
+        }
+
+        info!("{}", "Connector thread exited successfully");
+        Ok(())
+    }
+}
+
+fn map_qos(config: &MqttConnectorConfig, message_qos: u8) -> u8 {
+    config.qos.unwrap_or(message_qos).min(2)
+}
+
+// Builds the message published to the remote broker, the remote topic is rendered with
+// the fields of the local message.
+pub fn build_egress_message(
+    config: &MqttConnectorConfig,
+    message: &MqttMessage,
+) -> Result<Message, MqttBrokerError> {
+    let context = build_message_context(message);
+    let topic = render_template(&config.remote_topic, &context);
+
+    let mut builder = MessageBuilder::new()
+        .topic(topic)
+        .payload(message.payload.to_vec())
+        .qos(map_qos(config, message.qos as u8) as i32)
+        .retained(message.retain);
+
+    if config.protocol_version == MqttConnectorProtocolVersion::V5 {
+        let mut props = Properties::new();
+        for (key, value) in message.user_properties.iter() {
+            props.push_string_pair(PropertyCode::UserProperty, key, value)?;
+        }
+        if let Some(content_type) = &message.content_type {
+            props.push_string(PropertyCode::ContentType, content_type)?;
+        }
+        builder = builder.properties(props);
+    }
+    Ok(builder.finalize())
+}
+
+// Builds the local publish of a message received from the remote broker, the local topic
+// is rendered with the fields of the remote message.
+pub fn build_ingress_publish(
+    config: &MqttConnectorConfig,
+    message: &IngressMessage,
+) -> (String, Publish, Option<PublishProperties>) {
+    let mut user_properties_context = Map::new();
+    for (key, value) in message.user_properties.iter() {
+        user_properties_context.insert(key.clone(), Value::String(value.clone()));
+    }
+    let context = json!({
+        "topic": message.topic,
+        "qos": message.qos,
+        "retain": message.retain,
+        "payload": parse_payload(&message.payload),
+        "user_properties": user_properties_context,
+    });
+    let topic_name = render_template(&config.local_topic, &context);
+
+    let publish = Publish {
+        dup: false,
+        qos: qos(map_qos(config, message.qos)).unwrap_or(QoS::AtLeastOnce),
+        pkid: 0,
+        retain: false,
+        topic: Bytes::from(topic_name.clone()),
+        payload: message.payload.clone(),
+    };
+    let publish_properties = PublishProperties {
+        user_properties: message.user_properties.clone(),
+        content_type: message.content_type.clone(),
+        ..Default::default()
+    };
+    (topic_name, publish, Some(publish_properties))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use metadata_struct::mqtt::{
+        bridge::config_mqtt::{MqttConnectorConfig, MqttConnectorProtocolVersion},
+        message::MqttMessage,
+    };
+    use paho_mqtt::PropertyCode;
+    use protocol::mqtt::common::QoS;
+    use rumqttc::v5::mqttbytes::{
+        v5::{Publish, PublishProperties},
+        QoS as RemoteQoS,
+    };
+
+    use super::{build_egress_message, build_ingress_publish, ingress::IngressMessage};
+
+    #[test]
+    fn build_egress_message_test() {
+        let message = MqttMessage {
+            client_id: "c1".to_string(),
+            qos: QoS::AtLeastOnce,
+            retain: true,
+            topic: Bytes::from("factory/1/temp"),
+            payload: Bytes::from(r#"{"line":"l2"}"#),
+            user_properties: vec![("k".to_string(), "v".to_string())],
+            ..Default::default()
+        };
+
+        let config = MqttConnectorConfig {
+            remote_topic: "cloud/${payload.line}/${topic}".to_string(),
+            ..Default::default()
+        };
+        let remote = build_egress_message(&config, &message).unwrap();
+        assert_eq!(remote.topic(), "cloud/l2/factory/1/temp");
+        assert_eq!(remote.payload(), br#"{"line":"l2"}"#);
+        assert_eq!(remote.qos(), 1);
+        assert!(remote.retained());
+        assert_eq!(
+            remote
+                .properties()
+                .get_string_pair(PropertyCode::UserProperty),
+            Some(("k".to_string(), "v".to_string()))
+        );
+
+        let config = MqttConnectorConfig {
+            remote_topic: "cloud/data".to_string(),
+            protocol_version: MqttConnectorProtocolVersion::V311,
+            qos: Some(0),
+            ..Default::default()
+        };
+        let remote = build_egress_message(&config, &message).unwrap();
+        assert_eq!(remote.topic(), "cloud/data");
+        assert_eq!(remote.qos(), 0);
+        assert!(remote.properties().is_empty());
+    }
+
+    #[test]
+    fn build_ingress_publish_test() {
+        let properties = PublishProperties {
+            user_properties: vec![("k".to_string(), "v".to_string())],
+            ..Default::default()
+        };
+        let message = IngressMessage::from(Publish::new(
+            "cmd/device-1",
+            RemoteQoS::ExactlyOnce,
+            "reboot",
+            Some(properties),
+        ));
+
+        let config = MqttConnectorConfig {
+            remote_topic: "cmd/#".to_string(),
+            local_topic: "edge/${topic}".to_string(),
+            qos: Some(1),
+            ..Default::default()
+        };
+        let (topic_name, publish, publish_properties) = build_ingress_publish(&config, &message);
+        assert_eq!(topic_name, "edge/cmd/device-1");
+        assert_eq!(publish.topic, Bytes::from("edge/cmd/device-1"));
+        assert_eq!(publish.payload, Bytes::from("reboot"));
+        assert_eq!(publish.qos, QoS::AtLeastOnce);
+        assert_eq!(
+            publish_properties.unwrap().user_properties,
+            vec![("k".to_string(), "v".to_string())]
+        );
+    }
+}
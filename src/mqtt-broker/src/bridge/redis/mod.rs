@@ -0,0 +1,358 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::async_trait;
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::bridge::config_redis::{RedisConnectorConfig, RedisConnectorMode},
+};
+use redis::{aio::ConnectionManager, Cmd};
+use storage_adapter::storage::StorageAdapter;
+use tokio::sync::broadcast;
+
+use crate::{
+    handler::error::MqttBrokerError,
+    rule_engine::{action::render_template, eval::value_to_string},
+};
+
+use super::{
+    core::{BridgePlugin, BridgePluginReadConfig},
+    message::build_record_context,
+    runtime::ConnectorRuntime,
+};
+
+pub struct RedisBridgePlugin<S> {
+    runtime: ConnectorRuntime<S>,
+    config: RedisConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> RedisBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        runtime: ConnectorRuntime<S>,
+        config: RedisConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        RedisBridgePlugin {
+            runtime,
+            config,
+            stop_send,
+        }
+    }
+
+    // The commands of all records are sent in one MULTI/EXEC pipeline, so they run one after
+    // another without commands of other clients in between. Redis does not roll back a
+    // transaction: when a command fails the others are still applied, and when the connection
+    // is lost after EXEC was sent the result is unknown. In both cases the batch is written
+    // again, so the records that were already written are duplicated.
+    pub async fn append(
+        &self,
+        conn: &mut ConnectionManager,
+        records: &[Record],
+    ) -> Result<(), MqttBrokerError> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for record in records {
+            for cmd in build_redis_commands(&self.config, record) {
+                pipe.add_command(cmd).ignore();
+            }
+        }
+        let _: () = pipe.query_async(conn).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for RedisBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let mut recv = self.stop_send.subscribe();
+        let client = redis::Client::open(self.config.url.as_str())?;
+        // The connection manager reconnects by itself once the connection is lost.
+        let mut conn = ConnectionManager::new(client).await?;
+        // MULTI/EXEC fails with CROSSSLOT on Redis Cluster when the keys of a batch are in
+        // different slots, and the connection does not follow MOVED redirects.
+        if is_cluster_enabled(&mut conn).await? {
+            return Err(MqttBrokerError::RedisClusterNotSupported(
+                self.config.url.clone(),
+            ));
+        }
+        let conn = &conn;
+
+        // The offset is only committed after the writes succeeded.
+        self.runtime
+            .run_sink(&config, &mut recv, move |records| async move {
+                let mut conn = conn.clone();
+                self.append(&mut conn, &records).await
+            })
+            .await
+    }
+}
+
+async fn is_cluster_enabled(conn: &mut ConnectionManager) -> Result<bool, MqttBrokerError> {
+    let info: String = redis::cmd("INFO").arg("cluster").query_async(conn).await?;
+    Ok(parse_cluster_enabled(&info))
+}
+
+fn parse_cluster_enabled(info: &str) -> bool {
+    info.lines().any(|line| line.trim() == "cluster_enabled:1")
+}
+
+// The commands that write one record, followed by an EXPIRE of the key when a ttl is
+// configured. An XADD entry has the fields clientid, topic, qos, create_time and payload,
+// where payload is the rendered value.
+pub fn build_redis_commands(config: &RedisConnectorConfig, record: &Record) -> Vec<Cmd> {
+    let context = build_record_context(record);
+    let key = render_template(&config.key, &context);
+    let value = render_template(&config.value, &context);
+
+    let mut commands = Vec::new();
+    let mut expire = config.ttl_secs;
+    match config.mode {
+        RedisConnectorMode::Xadd => {
+            let mut cmd = redis::cmd("XADD");
+            cmd.arg(&key);
+            if let Some(max_len) = config.stream_max_len {
+                cmd.arg("MAXLEN").arg("~").arg(max_len);
+            }
+            cmd.arg("*");
+            for field in ["clientid", "topic", "qos", "create_time"] {
+                cmd.arg(field).arg(value_to_string(&context[field]));
+            }
+            cmd.arg("payload").arg(&value);
+            commands.push(cmd);
+        }
+        RedisConnectorMode::Lpush => {
+            let mut cmd = redis::cmd("LPUSH");
+            cmd.arg(&key).arg(&value);
+            commands.push(cmd);
+        }
+        RedisConnectorMode::Publish => {
+            let mut cmd = redis::cmd("PUBLISH");
+            cmd.arg(&key).arg(&value);
+            commands.push(cmd);
+            expire = None;
+        }
+        RedisConnectorMode::Set => {
+            let mut cmd = redis::cmd("SET");
+            cmd.arg(&key).arg(&value);
+            if let Some(ttl) = expire.take() {
+                cmd.arg("EX").arg(ttl);
+            }
+            commands.push(cmd);
+        }
+    }
+
+    if let Some(ttl) = expire {
+        let mut cmd = redis::cmd("EXPIRE");
+        cmd.arg(&key).arg(ttl);
+        commands.push(cmd);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use common_base::{
+        config::broker_mqtt::{init_broker_mqtt_conf_by_config, BrokerMqttConfig},
+        tools::unique_id,
+    };
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::{
+        adapter::record::Record,
+        mqtt::bridge::config_redis::{RedisConnectorConfig, RedisConnectorMode},
+    };
+    use redis::AsyncCommands;
+    use storage_adapter::{memory::MemoryStorageAdapter, storage::StorageAdapter};
+    use tokio::{sync::broadcast, time::sleep};
+
+    use super::{build_redis_commands, parse_cluster_enabled, RedisBridgePlugin};
+    use crate::{
+        bridge::{
+            core::{BridgePlugin, BridgePluginReadConfig},
+            manager::ConnectorManager,
+            message::build_record,
+            runtime::ConnectorRuntime,
+        },
+        handler::cache::CacheManager,
+        storage::message::MessageStorage,
+    };
+
+    fn packed(commands: Vec<redis::Cmd>) -> Vec<Vec<u8>> {
+        commands
+            .iter()
+            .map(|cmd| cmd.get_packed_command())
+            .collect()
+    }
+
+    #[test]
+    fn build_redis_commands_test() {
+        let record = build_record("c1", r#"{"temp":30}"#);
+
+        let config = RedisConnectorConfig {
+            mode: RedisConnectorMode::Set,
+            key: "state:${clientid}".to_string(),
+            ttl_secs: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            packed(build_redis_commands(&config, &record)),
+            packed(vec![redis::cmd("SET")
+                .arg("state:c1")
+                .arg(r#"{"temp":30}"#)
+                .arg("EX")
+                .arg(60)
+                .clone()])
+        );
+
+        let config = RedisConnectorConfig {
+            mode: RedisConnectorMode::Lpush,
+            value: "${payload.temp}".to_string(),
+            ttl_secs: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            packed(build_redis_commands(&config, &record)),
+            packed(vec![
+                redis::cmd("LPUSH").arg("sensor/1/temp").arg("30").clone(),
+                redis::cmd("EXPIRE").arg("sensor/1/temp").arg(60).clone(),
+            ])
+        );
+
+        let config = RedisConnectorConfig {
+            mode: RedisConnectorMode::Publish,
+            ttl_secs: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            packed(build_redis_commands(&config, &record)),
+            packed(vec![redis::cmd("PUBLISH")
+                .arg("sensor/1/temp")
+                .arg(r#"{"temp":30}"#)
+                .clone()])
+        );
+
+        let config = RedisConnectorConfig {
+            stream_max_len: Some(1000),
+            ..Default::default()
+        };
+        assert_eq!(
+            packed(build_redis_commands(&config, &record)),
+            packed(vec![redis::cmd("XADD")
+                .arg("sensor/1/temp")
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(1000)
+                .arg("*")
+                .arg("clientid")
+                .arg("c1")
+                .arg("topic")
+                .arg("sensor/1/temp")
+                .arg("qos")
+                .arg("0")
+                .arg("create_time")
+                .arg("1740817800")
+                .arg("payload")
+                .arg(r#"{"temp":30}"#)
+                .clone()])
+        );
+    }
+
+    #[test]
+    fn parse_cluster_enabled_test() {
+        assert!(parse_cluster_enabled("# Cluster\r\ncluster_enabled:1\r\n"));
+        assert!(!parse_cluster_enabled("# Cluster\r\ncluster_enabled:0\r\n"));
+        assert!(!parse_cluster_enabled(""));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn redis_bridge_plugin_test() {
+        let namespace = unique_id();
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: namespace.clone(),
+            ..Default::default()
+        });
+
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let topic_id = "test_topic".to_string();
+        let records: Vec<Record> = (0..3)
+            .map(|i| build_record(&format!("c{}", i), &format!("{}", i)))
+            .collect();
+        storage_adapter
+            .batch_write(namespace.clone(), topic_id.clone(), records)
+            .await
+            .unwrap();
+
+        let url = "redis://127.0.0.1:6379".to_string();
+        let key = format!("robustmq:{}", unique_id());
+        let connector_name = unique_id();
+        let client_pool = Arc::new(ClientPool::new(1));
+        let (stop_send, _) = broadcast::channel(1);
+        let plugin = RedisBridgePlugin::new(
+            ConnectorRuntime::new(
+                Arc::new(CacheManager::new(client_pool.clone(), namespace.clone())),
+                client_pool.clone(),
+                Arc::new(ConnectorManager::new()),
+                storage_adapter.clone(),
+                connector_name.clone(),
+                Default::default(),
+            ),
+            RedisConnectorConfig {
+                url: url.clone(),
+                mode: RedisConnectorMode::Lpush,
+                key: key.clone(),
+                ttl_secs: Some(60),
+                batch_size: 2,
+                ..Default::default()
+            },
+            stop_send.clone(),
+        );
+        let read_config = BridgePluginReadConfig {
+            topic_id: topic_id.clone(),
+            record_num: 2,
+        };
+        let handle = tokio::spawn(async move {
+            plugin.exec(read_config).await.unwrap();
+        });
+
+        sleep(Duration::from_secs(2)).await;
+        stop_send.send(true).unwrap();
+        handle.await.unwrap();
+
+        let client = redis::Client::open(url).unwrap();
+        let mut conn = client.get_multiplexed_async_connection().await.unwrap();
+        let values: Vec<String> = conn.lrange(&key, 0, -1).await.unwrap();
+        assert_eq!(values, vec!["2", "1", "0"]);
+        let ttl: i64 = conn.ttl(&key).await.unwrap();
+        assert!(ttl > 0 && ttl <= 60);
+        let _: () = conn.del(&key).await.unwrap();
+
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&connector_name)
+                .await
+                .unwrap(),
+            3
+        );
+    }
+}
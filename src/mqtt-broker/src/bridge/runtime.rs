@@ -0,0 +1,661 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use log::{error, info, warn};
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::{
+        bridge::{config_common::ConnectorCommonConfig, status::MQTTStatus},
+        message::MqttMessage,
+    },
+};
+use storage_adapter::storage::{ShardInfo, StorageAdapter};
+use tokio::{select, sync::broadcast::Receiver, time::sleep};
+
+use crate::{
+    handler::{
+        cache::CacheManager, error::MqttBrokerError, message::build_message_expire,
+        topic::try_init_topic,
+    },
+    observability::metrics::connector::{
+        metrics_connector_dead_letter_inc, metrics_connector_delivery_delay_set,
+        metrics_connector_error_inc, metrics_connector_lag_set, metrics_connector_success_inc,
+    },
+    storage::message::{cluster_name, MessageStorage},
+    subscribe::dead_letter::{
+        RetryPolicy, DEAD_LETTER_ATTEMPTS, DEAD_LETTER_CLIENT_ID, DEAD_LETTER_ORIGINAL_TOPIC,
+        DEAD_LETTER_REASON,
+    },
+};
+
+use super::{
+    core::BridgePluginReadConfig, manager::ConnectorManager, source::source_client_id,
+    transform::transform_record,
+};
+
+// The retries, dead-letter topic, transforms and status of a connector, shared by all the
+// bridge plugins.
+#[derive(Clone)]
+pub struct ConnectorRuntime<S> {
+    pub cache_manager: Arc<CacheManager>,
+    pub client_pool: Arc<ClientPool>,
+    pub connector_manager: Arc<ConnectorManager>,
+    pub message_storage: Arc<S>,
+    pub connector_name: String,
+    pub config: ConnectorCommonConfig,
+}
+
+impl<S> ConnectorRuntime<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        cache_manager: Arc<CacheManager>,
+        client_pool: Arc<ClientPool>,
+        connector_manager: Arc<ConnectorManager>,
+        message_storage: Arc<S>,
+        connector_name: String,
+        config: ConnectorCommonConfig,
+    ) -> Self {
+        ConnectorRuntime {
+            cache_manager,
+            client_pool,
+            connector_manager,
+            message_storage,
+            connector_name,
+            config,
+        }
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            enable: true,
+            max_retry_times: self.config.retry.max_retry_times,
+            retry_backoff_ms: self.config.retry.retry_backoff_ms,
+            max_retry_backoff_ms: self.config.retry.max_retry_backoff_ms,
+        }
+    }
+
+    pub fn report_heartbeat(&self) {
+        self.connector_manager
+            .report_heartbeat(&self.connector_name);
+    }
+
+    pub fn set_running(&self) {
+        self.connector_manager
+            .set_connector_status(&self.connector_name, MQTTStatus::Running);
+    }
+
+    pub fn report_success(&self, num: u64, delivery_delay: u64) {
+        metrics_connector_success_inc(&self.connector_name, num);
+        metrics_connector_delivery_delay_set(&self.connector_name, delivery_delay);
+        self.connector_manager.report_success(&self.connector_name);
+    }
+
+    pub fn report_lag(&self, lag: u64) {
+        metrics_connector_lag_set(&self.connector_name, lag);
+    }
+
+    pub fn report_error(&self, e: &MqttBrokerError, failed: bool) {
+        metrics_connector_error_inc(&self.connector_name);
+        self.connector_manager
+            .report_error(&self.connector_name, e.to_string(), failed);
+    }
+
+    // Writes the record to the dead letter topic, returns false if the connector has none.
+    pub async fn dead_letter(
+        &self,
+        record: Record,
+        reason: &str,
+        attempts: u32,
+    ) -> Result<bool, MqttBrokerError> {
+        let topic_name = &self.config.dead_letter_topic;
+        if topic_name.is_empty() {
+            return Ok(false);
+        }
+
+        let topic = try_init_topic(
+            topic_name,
+            "",
+            &self.cache_manager,
+            &self.message_storage,
+            &self.client_pool,
+        )
+        .await?;
+        let message_expire = build_message_expire(&self.cache_manager, &None);
+        let record = build_connector_dead_letter_record(
+            &self.connector_name,
+            topic_name,
+            record,
+            reason,
+            attempts,
+            message_expire,
+        );
+
+        let message_storage = MessageStorage::new(self.message_storage.clone());
+        message_storage
+            .append_topic_message(&topic.topic_id, vec![record])
+            .await?;
+
+        metrics_connector_dead_letter_inc(&self.connector_name);
+        warn!(
+            "Connector {} moved a record to dead letter topic {} after {} attempts, reason: {}",
+            self.connector_name, topic_name, attempts, reason
+        );
+        Ok(true)
+    }
+
+    // Applies the transforms, the records that can not be transformed are dead-lettered.
+    pub async fn transform_records(
+        &self,
+        records: Vec<Record>,
+    ) -> Result<Vec<Record>, MqttBrokerError> {
+        let mut results = Vec::with_capacity(records.len());
+        for record in records {
+            match transform_record(&self.config.transforms, record.clone()) {
+                Ok(record) => results.push(record),
+                Err(e) => {
+                    self.report_error(&e, false);
+                    if !self.dead_letter(record, &e.to_string(), 0).await? {
+                        warn!(
+                            "Connector {} dropped a record that can not be transformed, error message: {}",
+                            self.connector_name, e
+                        );
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    // Sends the records until they are delivered. When the retries are exhausted, the records
+    // are sent one by one and those that still fail are dead-lettered. Without a dead letter
+    // topic the connector is Failed and the records are retried until they are delivered.
+    // Returns false if the connector was stopped while retrying.
+    pub async fn deliver<F, Fut>(
+        &self,
+        records: Vec<Record>,
+        send: &F,
+        recv: &mut Receiver<bool>,
+    ) -> Result<bool, MqttBrokerError>
+    where
+        F: Fn(Vec<Record>) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<(), MqttBrokerError>> + Send,
+    {
+        if records.is_empty() {
+            return Ok(true);
+        }
+
+        let policy = self.retry_policy();
+        let has_dead_letter = !self.config.dead_letter_topic.is_empty();
+        let mut attempts = 0;
+        loop {
+            let e = match send(records.clone()).await {
+                Ok(()) => {
+                    self.report_success(records.len() as u64, record_delivery_delay(&records));
+                    return Ok(true);
+                }
+                Err(e) => e,
+            };
+
+            attempts += 1;
+            let exhausted = policy.is_exhausted(attempts);
+            self.report_error(&e, exhausted && !has_dead_letter);
+            error!(
+                "Connector {} failed to deliver {} records, attempts: {}, error message: {}",
+                self.connector_name,
+                records.len(),
+                attempts,
+                e
+            );
+
+            if exhausted && has_dead_letter {
+                for record in records {
+                    match send(vec![record.clone()]).await {
+                        Ok(()) => self.report_success(1, record_delivery_delay(&[record])),
+                        Err(e) => {
+                            self.report_error(&e, false);
+                            self.dead_letter(record, &e.to_string(), attempts + 1)
+                                .await?;
+                        }
+                    }
+                }
+                return Ok(true);
+            }
+
+            if self.wait(policy.backoff(attempts), recv).await {
+                return Ok(false);
+            }
+        }
+    }
+
+    // Reads the topic of the connector and the output of the rules sent to the connector, and
+    // delivers the records with send. The offset is only committed after the records are
+    // delivered or dead-lettered.
+    pub async fn run_sink<F, Fut>(
+        &self,
+        read_config: &BridgePluginReadConfig,
+        recv: &mut Receiver<bool>,
+        send: F,
+    ) -> Result<(), MqttBrokerError>
+    where
+        F: Fn(Vec<Record>) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<(), MqttBrokerError>> + Send,
+    {
+        let message_storage = MessageStorage::new(self.message_storage.clone());
+        init_rule_output_shard(&self.message_storage, &self.connector_name).await?;
+        let sources = [
+            (read_config.topic_id.clone(), self.connector_name.clone()),
+            (
+                rule_output_shard_name(&self.connector_name),
+                rule_output_group_name(&self.connector_name),
+            ),
+        ];
+
+        let mut lags = [TopicLag::default(), TopicLag::default()];
+
+        loop {
+            let mut is_idle = true;
+            for ((topic_id, group_name), lag) in sources.iter().zip(lags.iter_mut()) {
+                let offset = message_storage.get_group_offset(group_name).await?;
+
+                let data = select! {
+                    val = recv.recv() =>{
+                        if let Ok(true) = val {
+                            info!("{}","Connector thread exited successfully");
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    val = message_storage.read_topic_message(topic_id, offset, read_config.record_num) => val,
+                };
+
+                let data = match data {
+                    Ok(data) => data,
+                    Err(e) => {
+                        error!(
+                            "Connector {} failed to read Topic {} data with error message :{}",
+                            self.connector_name, topic_id, e
+                        );
+                        continue;
+                    }
+                };
+                self.report_heartbeat();
+                if data.is_empty() {
+                    lag.update(&message_storage, topic_id, offset).await;
+                    continue;
+                }
+                is_idle = false;
+
+                let next_offset = data
+                    .last()
+                    .and_then(|record| record.offset)
+                    .map(|last| last + 1)
+                    .unwrap_or(offset + data.len() as u64);
+
+                let records = self.transform_records(data).await?;
+                if !self.deliver(records, &send, recv).await? {
+                    info!("{}", "Connector thread exited successfully");
+                    return Ok(());
+                }
+                message_storage
+                    .commit_group_offset(group_name, topic_id, next_offset)
+                    .await?;
+                lag.update(&message_storage, topic_id, next_offset).await;
+            }
+            self.report_lag(lags.iter().map(|lag| lag.lag).sum());
+
+            if is_idle {
+                metrics_connector_delivery_delay_set(&self.connector_name, 0);
+                sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+
+    // Waits for the backoff, the heartbeat is reported meanwhile so that a long backoff does
+    // not reschedule the connector. Returns true if the connector was stopped.
+    pub async fn wait(&self, backoff: Duration, recv: &mut Receiver<bool>) -> bool {
+        let deadline = Instant::now() + backoff;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            self.report_heartbeat();
+            select! {
+                val = recv.recv() => {
+                    if let Ok(true) = val {
+                        return true;
+                    }
+                }
+                _ = sleep((deadline - now).min(Duration::from_secs(1))) => {}
+            }
+        }
+    }
+}
+
+// The number of records of a topic after the committed offset of a connector. The end of the
+// topic is only found by reading up to it, so it is read on from the end found last time, at
+// most once every LAG_CHECK_INTERVAL. In between, the lag only goes down as the records are
+// committed.
+#[derive(Default)]
+pub struct TopicLag {
+    pub lag: u64,
+    end_offset: u64,
+    checked_at: Option<Instant>,
+}
+
+const LAG_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+impl TopicLag {
+    pub async fn update<S>(
+        &mut self,
+        message_storage: &MessageStorage<S>,
+        topic_id: &str,
+        offset: u64,
+    ) where
+        S: StorageAdapter + Sync + Send + 'static + Clone,
+    {
+        let expired = self
+            .checked_at
+            .map(|checked_at| checked_at.elapsed() >= LAG_CHECK_INTERVAL)
+            .unwrap_or(true);
+        if expired || offset > self.end_offset {
+            match message_storage
+                .get_end_offset(topic_id, offset.max(self.end_offset))
+                .await
+            {
+                Ok(end_offset) => {
+                    self.end_offset = end_offset;
+                    self.checked_at = Some(Instant::now());
+                }
+                Err(e) => {
+                    warn!("Failed to read the end offset of Topic {}: {}", topic_id, e);
+                }
+            }
+        }
+        self.lag = self.end_offset.saturating_sub(offset);
+    }
+}
+
+// The output of the rules sent to a connector is written to a shard of the connector that is
+// not an MQTT topic, so the subscribers of the topic of the connector do not receive it.
+pub fn rule_output_shard_name(connector_name: &str) -> String {
+    format!("$rule-output/{}", connector_name)
+}
+
+fn rule_output_group_name(connector_name: &str) -> String {
+    format!("{}/$rule-output", connector_name)
+}
+
+pub async fn init_rule_output_shard<S>(
+    message_storage: &Arc<S>,
+    connector_name: &str,
+) -> Result<(), MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let namespace = cluster_name();
+    let shard_name = rule_output_shard_name(connector_name);
+    let list = message_storage
+        .list_shard(namespace.clone(), shard_name.clone())
+        .await?;
+    if list.is_empty() {
+        message_storage
+            .create_shard(ShardInfo {
+                namespace,
+                shard_name,
+                replica_num: 1,
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+// The seconds between the newest record being written and being delivered.
+pub fn record_delivery_delay(records: &[Record]) -> u64 {
+    records
+        .iter()
+        .map(|record| record.timestamp)
+        .max()
+        .map(|timestamp| now_second().saturating_sub(timestamp))
+        .unwrap_or(0)
+}
+
+// The dead letter message keeps the MQTT message of the record, records that are not MQTT
+// messages are kept as the payload. The user properties are the same as the ones of the dead
+// letter messages of the subscriptions, so they can be re-driven the same way.
+pub fn build_connector_dead_letter_record(
+    connector_name: &str,
+    topic_name: &str,
+    record: Record,
+    reason: &str,
+    attempts: u32,
+    message_expire: u64,
+) -> Record {
+    let mut message = match MqttMessage::decode_record(record.clone()) {
+        Ok(message) => message,
+        Err(_) => MqttMessage {
+            client_id: source_client_id(connector_name),
+            payload: Bytes::from(record.data),
+            create_time: record.timestamp,
+            ..Default::default()
+        },
+    };
+    let original_topic = String::from_utf8_lossy(&message.topic).to_string();
+
+    message.topic = Bytes::from(topic_name.to_owned());
+    message.retain = false;
+    message.expiry_interval = message_expire;
+    message
+        .user_properties
+        .push((DEAD_LETTER_REASON.to_string(), reason.to_string()));
+    message.user_properties.push((
+        DEAD_LETTER_CLIENT_ID.to_string(),
+        source_client_id(connector_name),
+    ));
+    message
+        .user_properties
+        .push((DEAD_LETTER_ATTEMPTS.to_string(), attempts.to_string()));
+    message
+        .user_properties
+        .push((DEAD_LETTER_ORIGINAL_TOPIC.to_string(), original_topic));
+    Record::build_byte(message.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use bytes::Bytes;
+    use common_base::{
+        config::broker_mqtt::{init_broker_mqtt_conf_by_config, BrokerMqttConfig},
+        tools::{now_second, unique_id},
+    };
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::{
+        adapter::record::Record,
+        mqtt::{
+            bridge::{
+                config_common::{ConnectorCommonConfig, ConnectorRetryConfig},
+                status::MQTTStatus,
+            },
+            message::MqttMessage,
+        },
+    };
+    use storage_adapter::memory::MemoryStorageAdapter;
+    use tokio::sync::broadcast;
+
+    use super::{
+        build_connector_dead_letter_record, record_delivery_delay, ConnectorRuntime, TopicLag,
+    };
+    use crate::{
+        bridge::manager::ConnectorManager, handler::cache::CacheManager,
+        handler::error::MqttBrokerError, observability::metrics::connector::get_connector_metrics,
+        storage::message::MessageStorage, subscribe::dead_letter::DeadLetterMessage,
+    };
+
+    #[test]
+    fn record_delivery_delay_test() {
+        let mut record = Record::build_str("data".to_string());
+        record.timestamp = now_second() - 5;
+        assert!(record_delivery_delay(&[record]) >= 5);
+        assert_eq!(record_delivery_delay(&[]), 0);
+    }
+
+    #[tokio::test]
+    async fn topic_lag_test() {
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: unique_id(),
+            ..Default::default()
+        });
+        let message_storage = MessageStorage::new(Arc::new(MemoryStorageAdapter::new()));
+        let records = (0..5)
+            .map(|i| Record::build_str(format!("data-{}", i)))
+            .collect();
+        message_storage
+            .append_topic_message("test_topic", records)
+            .await
+            .unwrap();
+
+        let mut lag = TopicLag::default();
+        lag.update(&message_storage, "test_topic", 1).await;
+        assert_eq!(lag.lag, 4);
+        lag.update(&message_storage, "test_topic", 3).await;
+        assert_eq!(lag.lag, 2);
+
+        // Committing past the end found last time reads the end again.
+        let records = (5..8)
+            .map(|i| Record::build_str(format!("data-{}", i)))
+            .collect();
+        message_storage
+            .append_topic_message("test_topic", records)
+            .await
+            .unwrap();
+        lag.update(&message_storage, "test_topic", 6).await;
+        assert_eq!(lag.lag, 2);
+        lag.update(&message_storage, "test_topic", 8).await;
+        assert_eq!(lag.lag, 0);
+    }
+
+    #[test]
+    fn connector_dead_letter_record_test() {
+        let message = MqttMessage {
+            client_id: "c1".to_string(),
+            topic: Bytes::from("/sensor/1"),
+            payload: Bytes::from("payload"),
+            ..Default::default()
+        };
+        let mut record = build_connector_dead_letter_record(
+            "http-1",
+            "dlq/http",
+            Record::build_byte(message.encode()),
+            "response status 400",
+            3,
+            0,
+        );
+        record.offset = Some(1);
+
+        let dead_letter = DeadLetterMessage::decode(record).unwrap();
+        assert_eq!(dead_letter.original_topic, "/sensor/1");
+        assert_eq!(dead_letter.client_id, "$connector/http-1");
+        assert_eq!(dead_letter.reason, "response status 400");
+        assert_eq!(dead_letter.attempts, 3);
+        assert_eq!(dead_letter.message.topic, Bytes::from("dlq/http"));
+        assert_eq!(dead_letter.message.payload, Bytes::from("payload"));
+
+        let record = build_connector_dead_letter_record(
+            "file-1",
+            "dlq/file",
+            Record::build_str("raw".to_string()),
+            "reason",
+            1,
+            0,
+        );
+        let message = MqttMessage::decode_record(record).unwrap();
+        assert_eq!(message.payload, Bytes::from("raw"));
+        assert_eq!(message.client_id, "$connector/file-1");
+    }
+
+    #[tokio::test]
+    async fn deliver_retry_test() {
+        let connector_name = "deliver_retry_test".to_string();
+        let client_pool = Arc::new(ClientPool::new(1));
+        let connector_manager = Arc::new(ConnectorManager::new());
+        let runtime = ConnectorRuntime::new(
+            Arc::new(CacheManager::new(client_pool.clone(), "test".to_string())),
+            client_pool,
+            connector_manager.clone(),
+            Arc::new(MemoryStorageAdapter::new()),
+            connector_name.clone(),
+            ConnectorCommonConfig {
+                retry: ConnectorRetryConfig {
+                    max_retry_times: 2,
+                    retry_backoff_ms: 1,
+                    max_retry_backoff_ms: 2,
+                },
+                ..Default::default()
+            },
+        );
+        let (stop_send, _) = broadcast::channel::<bool>(1);
+        let mut recv = stop_send.subscribe();
+
+        // Without a dead letter topic the records are retried until they are delivered, the
+        // connector is Failed after the retries are exhausted.
+        let attempts = AtomicU32::new(0);
+        let attempts_ref = &attempts;
+        let manager = connector_manager.clone();
+        let name = connector_name.clone();
+        let send = move |_: Vec<Record>| {
+            let manager = manager.clone();
+            let name = name.clone();
+            async move {
+                if attempts_ref.fetch_add(1, Ordering::SeqCst) < 3 {
+                    return Err(MqttBrokerError::CommonError("unavailable".to_string()));
+                }
+                let status = manager.get_connector_status(&name).unwrap();
+                assert_eq!(status.status, MQTTStatus::Failed);
+                assert!(status.last_error.contains("unavailable"));
+                Ok(())
+            }
+        };
+
+        let records = vec![Record::build_str("a".to_string())];
+        assert!(runtime.deliver(records, &send, &mut recv).await.unwrap());
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+        let status = connector_manager
+            .get_connector_status(&connector_name)
+            .unwrap();
+        assert_eq!(status.status, MQTTStatus::Running);
+        assert!(status.last_success_time > 0);
+
+        let (success, error, dead_letter, _, _) = get_connector_metrics(&connector_name);
+        assert_eq!(success, 1);
+        assert_eq!(error, 3);
+        assert_eq!(dead_letter, 0);
+    }
+}
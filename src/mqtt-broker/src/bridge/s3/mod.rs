@@ -0,0 +1,544 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use axum::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use common_base::error::common::CommonError;
+use log::{error, info};
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::{
+        bridge::config_s3::{S3ConnectorConfig, S3FileFormat},
+        message::MqttMessage,
+    },
+};
+use opendal::Operator;
+use serde_json::json;
+use storage_adapter::{minio::build_s3_operator, storage::StorageAdapter};
+use tokio::{select, sync::broadcast, time::sleep};
+
+use crate::{
+    handler::error::MqttBrokerError, rule_engine::action::render_template,
+    storage::message::MessageStorage,
+};
+
+use super::{
+    core::{BridgePlugin, BridgePluginReadConfig},
+    message::build_message_context,
+    runtime::{ConnectorRuntime, TopicLag},
+};
+
+mod parquet;
+
+// The messages read since the last file was written.
+#[derive(Default)]
+struct FileBuffer {
+    records: Vec<Record>,
+    start_offset: u64,
+    next_offset: u64,
+    size: u64,
+    first_read: Option<Instant>,
+}
+
+impl FileBuffer {
+    fn push(&mut self, records: Vec<Record>, next_offset: u64) {
+        if self.records.is_empty() && !records.is_empty() {
+            self.first_read = Some(Instant::now());
+        }
+        self.size += records
+            .iter()
+            .map(|record| record.data.len() as u64)
+            .sum::<u64>();
+        self.records.extend(records);
+        self.next_offset = next_offset;
+    }
+
+    fn should_roll(&self, config: &S3ConnectorConfig) -> bool {
+        let Some(first_read) = self.first_read else {
+            return false;
+        };
+        self.size >= config.max_file_size
+            || first_read.elapsed() >= Duration::from_secs(config.roll_interval_secs)
+    }
+
+    fn clear(&mut self) {
+        self.records.clear();
+        self.start_offset = self.next_offset;
+        self.size = 0;
+        self.first_read = None;
+    }
+}
+
+pub struct S3BridgePlugin<S> {
+    runtime: ConnectorRuntime<S>,
+    config: S3ConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> S3BridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        runtime: ConnectorRuntime<S>,
+        config: S3ConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        S3BridgePlugin {
+            runtime,
+            config,
+            stop_send,
+        }
+    }
+
+    // Buffers the messages of the topic and writes them as one file when the buffer rolls.
+    // The offset is only committed after the file is written or its messages are
+    // dead-lettered, a failed file is retried with the retry policy of the connector.
+    pub async fn run(
+        &self,
+        operator: &Operator,
+        config: &BridgePluginReadConfig,
+    ) -> Result<(), MqttBrokerError> {
+        let message_storage = MessageStorage::new(self.runtime.message_storage.clone());
+        let group_name = self.runtime.connector_name.clone();
+        let mut recv = self.stop_send.subscribe();
+
+        let offset = message_storage.get_group_offset(&group_name).await?;
+        let mut buffer = FileBuffer {
+            start_offset: offset,
+            next_offset: offset,
+            ..Default::default()
+        };
+        let mut lag = TopicLag::default();
+
+        loop {
+            if buffer.should_roll(&self.config) {
+                let records = std::mem::take(&mut buffer.records);
+                let start_offset = buffer.start_offset;
+                let write = move |records: Vec<Record>| async move {
+                    self.write_file(operator, &config.topic_id, start_offset, &records)
+                        .await
+                };
+                if !self.runtime.deliver(records, &write, &mut recv).await? {
+                    break;
+                }
+                message_storage
+                    .commit_group_offset(&group_name, &config.topic_id, buffer.next_offset)
+                    .await?;
+                buffer.clear();
+            }
+            // The buffered records are not committed yet, they count as lag.
+            lag.update(&message_storage, &config.topic_id, buffer.start_offset)
+                .await;
+            self.runtime.report_lag(lag.lag);
+
+            select! {
+                val = recv.recv() =>{
+                    if let Ok(flag) = val {
+                        if flag {
+                            break;
+                        }
+                    }
+                }
+
+                val = message_storage.read_topic_message(&config.topic_id, buffer.next_offset, config.record_num) => {
+                    match val {
+                        Ok(data) => {
+                            self.runtime.report_heartbeat();
+                            if data.is_empty() {
+                                sleep(Duration::from_millis(100)).await;
+                                continue;
+                            }
+
+                            let next_offset = data
+                                .last()
+                                .and_then(|record| record.offset)
+                                .map(|last| last + 1)
+                                .unwrap_or(buffer.next_offset + data.len() as u64);
+                            let records = self.runtime.transform_records(data).await?;
+                            buffer.push(records, next_offset);
+                        },
+                        Err(e) => {
+                            error!("Connector {} failed to read Topic {} data with error message :{}", self.runtime.connector_name,config.topic_id,e);
+                            sleep(Duration::from_millis(100)).await;
+                        }
+                    }
+                }
+            }
+        }
+
+        // The buffered messages are written once more when the connector stops, if that
+        // fails they are read again by the next run.
+        if !buffer.records.is_empty()
+            && self
+                .write_file(
+                    operator,
+                    &config.topic_id,
+                    buffer.start_offset,
+                    &buffer.records,
+                )
+                .await
+                .is_ok()
+        {
+            message_storage
+                .commit_group_offset(&group_name, &config.topic_id, buffer.next_offset)
+                .await?;
+        }
+        info!("{}", "Connector thread exited successfully");
+        Ok(())
+    }
+
+    // The records are written as one file per partition directory, a new file is started
+    // whenever the directory of the next record differs. Each file is named by the offset of
+    // its first record, so the records that are written one by one after the retries are
+    // exhausted do not replace each other.
+    async fn write_file(
+        &self,
+        operator: &Operator,
+        topic_id: &str,
+        start_offset: u64,
+        records: &[Record],
+    ) -> Result<(), MqttBrokerError> {
+        let messages: Vec<MqttMessage> = records.iter().map(decode_message).collect();
+        for (dir, range) in split_by_partition(&self.config, topic_id, &messages) {
+            let file_offset = records[range.start]
+                .offset
+                .unwrap_or(start_offset + range.start as u64);
+            let path = build_file_path(
+                &self.config,
+                &self.runtime.connector_name,
+                &dir,
+                file_offset,
+            );
+            let data = encode_file(&self.config.format, &messages[range.clone()])?;
+            operator
+                .write(&path, data)
+                .await
+                .map_err(CommonError::from)?;
+            info!(
+                "Connector {} wrote {} messages to {}",
+                self.runtime.connector_name,
+                range.len(),
+                path
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for S3BridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let operator = build_s3_operator(
+            &self.config.endpoint,
+            &self.config.bucket,
+            &self.config.root,
+            &self.config.access_key_id,
+            &self.config.secret_access_key,
+            &self.config.region,
+        )?;
+        self.run(&operator, &config).await
+    }
+}
+
+// Records that are not MQTT messages are written with their data as the payload.
+fn decode_message(record: &Record) -> MqttMessage {
+    MqttMessage::decode_record(record.clone()).unwrap_or_else(|_| MqttMessage {
+        payload: Bytes::from(record.data.clone()),
+        create_time: record.timestamp,
+        ..Default::default()
+    })
+}
+
+// Splits the messages into runs of consecutive messages with the same partition directory.
+pub fn split_by_partition(
+    config: &S3ConnectorConfig,
+    topic_id: &str,
+    messages: &[MqttMessage],
+) -> Vec<(String, Range<usize>)> {
+    let mut results: Vec<(String, Range<usize>)> = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        let dir = build_partition_dir(config, topic_id, message);
+        match results.last_mut() {
+            Some((last_dir, range)) if *last_dir == dir => range.end = i + 1,
+            _ => results.push((dir, i..i + 1)),
+        }
+    }
+    results
+}
+
+// The directory of a message, the path template rendered with its topic and time.
+pub fn build_partition_dir(
+    config: &S3ConnectorConfig,
+    topic_id: &str,
+    message: &MqttMessage,
+) -> String {
+    let time = DateTime::<Utc>::from_timestamp(message.create_time as i64, 0).unwrap_or_default();
+    let topic = if message.topic.is_empty() {
+        topic_id.to_string()
+    } else {
+        String::from_utf8_lossy(&message.topic).to_string()
+    };
+    let context = json!({
+        "topic": topic,
+        "date": time.format("%Y-%m-%d").to_string(),
+        "year": time.format("%Y").to_string(),
+        "month": time.format("%m").to_string(),
+        "day": time.format("%d").to_string(),
+        "hour": time.format("%H").to_string(),
+        "minute": time.format("%M").to_string(),
+    });
+    render_template(&config.path, &context)
+        .trim_matches('/')
+        .to_string()
+}
+
+// The file is named by the offset of its first message, so a file written again after a
+// restart replaces the old one.
+pub fn build_file_path(
+    config: &S3ConnectorConfig,
+    connector_name: &str,
+    dir: &str,
+    start_offset: u64,
+) -> String {
+    let name = format!(
+        "{}-{:020}.{}",
+        connector_name,
+        start_offset,
+        config.format.extension()
+    );
+    if dir.is_empty() {
+        name
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+pub fn encode_file(
+    format: &S3FileFormat,
+    messages: &[MqttMessage],
+) -> Result<Vec<u8>, MqttBrokerError> {
+    match format {
+        S3FileFormat::Parquet => parquet::encode_parquet(messages),
+        S3FileFormat::Jsonl => {
+            let mut data = Vec::new();
+            for message in messages {
+                serde_json::to_writer(&mut data, &build_message_context(message))?;
+                data.push(b'\n');
+            }
+            Ok(data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bytes::Bytes;
+    use common_base::{
+        config::broker_mqtt::{init_broker_mqtt_conf_by_config, BrokerMqttConfig},
+        tools::unique_id,
+    };
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::{
+        adapter::record::Record,
+        mqtt::{
+            bridge::config_s3::{S3ConnectorConfig, S3FileFormat},
+            message::MqttMessage,
+        },
+    };
+    use opendal::{services::Memory, Operator};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use storage_adapter::{memory::MemoryStorageAdapter, storage::StorageAdapter};
+    use tokio::{sync::broadcast, time::sleep};
+
+    use super::{
+        build_file_path, build_partition_dir, encode_file, split_by_partition, S3BridgePlugin,
+    };
+    use crate::{
+        bridge::{
+            core::BridgePluginReadConfig, manager::ConnectorManager, runtime::ConnectorRuntime,
+        },
+        handler::cache::CacheManager,
+        storage::message::MessageStorage,
+    };
+
+    fn build_message(client_id: &str, payload: &str) -> MqttMessage {
+        MqttMessage {
+            client_id: client_id.to_string(),
+            topic: Bytes::from("factory/temp"),
+            payload: Bytes::from(payload.to_string()),
+            // 2025-03-01 08:30:00 UTC
+            create_time: 1740817800,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn build_file_path_test() {
+        let message = build_message("c1", "1");
+        let config = S3ConnectorConfig::default();
+        let dir = build_partition_dir(&config, "topic-id", &message);
+        assert_eq!(dir, "factory/temp/2025-03-01/08");
+        assert_eq!(
+            build_file_path(&config, "c-1", &dir, 42),
+            "factory/temp/2025-03-01/08/c-1-00000000000000000042.parquet"
+        );
+
+        let config = S3ConnectorConfig {
+            path: "/data/${year}/${month}/${day}/".to_string(),
+            format: S3FileFormat::Jsonl,
+            ..Default::default()
+        };
+        let dir = build_partition_dir(&config, "topic-id", &message);
+        assert_eq!(
+            build_file_path(&config, "c-1", &dir, 0),
+            "data/2025/03/01/c-1-00000000000000000000.jsonl"
+        );
+
+        let config = S3ConnectorConfig {
+            path: "".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_file_path(&config, "c-1", "", 7),
+            "c-1-00000000000000000007.parquet"
+        );
+    }
+
+    #[test]
+    fn split_by_partition_test() {
+        let config = S3ConnectorConfig::default();
+        let mut next_hour = build_message("c1", "3");
+        next_hour.create_time += 3600;
+        let mut other_topic = build_message("c1", "4");
+        other_topic.topic = Bytes::from("factory/humidity");
+        let messages = vec![
+            build_message("c1", "1"),
+            build_message("c1", "2"),
+            next_hour,
+            other_topic,
+            build_message("c1", "5"),
+        ];
+
+        let files = split_by_partition(&config, "topic-id", &messages);
+        assert_eq!(
+            files,
+            vec![
+                ("factory/temp/2025-03-01/08".to_string(), 0..2),
+                ("factory/temp/2025-03-01/09".to_string(), 2..3),
+                ("factory/humidity/2025-03-01/08".to_string(), 3..4),
+                ("factory/temp/2025-03-01/08".to_string(), 4..5),
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_file_test() {
+        let messages = vec![build_message("c1", "1"), build_message("c2", "2")];
+
+        let data = encode_file(&S3FileFormat::Jsonl, &messages).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&data).unwrap().lines().collect();
+        assert_eq!(lines.len(), 2);
+        let line: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(line["clientid"], "c2");
+        assert_eq!(line["payload"], 2);
+
+        let data = encode_file(&S3FileFormat::Parquet, &messages).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(data))
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 2);
+    }
+
+    #[tokio::test]
+    async fn s3_bridge_plugin_test() {
+        let namespace = unique_id();
+        init_broker_mqtt_conf_by_config(BrokerMqttConfig {
+            cluster_name: namespace.clone(),
+            ..Default::default()
+        });
+
+        let storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let topic_id = "test_topic".to_string();
+        let records: Vec<Record> = (0..5)
+            .map(|i| Record::build_byte(build_message(&format!("c{}", i), "data").encode()))
+            .collect();
+        storage_adapter
+            .batch_write(namespace.clone(), topic_id.clone(), records)
+            .await
+            .unwrap();
+
+        let connector_name = unique_id();
+        let client_pool = Arc::new(ClientPool::new(1));
+        let (stop_send, _) = broadcast::channel(1);
+        // every read batch fills a file
+        let plugin = S3BridgePlugin::new(
+            ConnectorRuntime::new(
+                Arc::new(CacheManager::new(client_pool.clone(), namespace.clone())),
+                client_pool.clone(),
+                Arc::new(ConnectorManager::new()),
+                storage_adapter.clone(),
+                connector_name.clone(),
+                Default::default(),
+            ),
+            S3ConnectorConfig {
+                format: S3FileFormat::Jsonl,
+                path: "${topic}".to_string(),
+                max_file_size: 1,
+                ..Default::default()
+            },
+            stop_send.clone(),
+        );
+
+        let operator = Operator::new(Memory::default()).unwrap().finish();
+        let op = operator.clone();
+        let read_config = BridgePluginReadConfig {
+            topic_id: topic_id.clone(),
+            record_num: 2,
+        };
+        let handle = tokio::spawn(async move {
+            plugin.run(&op, &read_config).await.unwrap();
+        });
+
+        sleep(Duration::from_secs(2)).await;
+        stop_send.send(true).unwrap();
+        handle.await.unwrap();
+
+        for (offset, lines) in [(0, 2), (2, 2), (4, 1)] {
+            let path = format!("factory/temp/{}-{:020}.jsonl", connector_name, offset);
+            let data = operator.read(&path).await.unwrap().to_vec();
+            assert_eq!(std::str::from_utf8(&data).unwrap().lines().count(), lines);
+        }
+
+        let message_storage = MessageStorage::new(storage_adapter);
+        assert_eq!(
+            message_storage
+                .get_group_offset(&connector_name)
+                .await
+                .unwrap(),
+            5
+        );
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, UInt64Array, UInt8Array};
+use arrow_schema::{DataType, Field, Schema};
+use metadata_struct::mqtt::message::MqttMessage;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+
+use crate::handler::error::MqttBrokerError;
+
+// The columns of the parquet files, the payload is written as a UTF-8 string and the user
+// properties as a JSON object.
+pub fn parquet_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("clientid", DataType::Utf8, false),
+        Field::new("topic", DataType::Utf8, false),
+        Field::new("qos", DataType::UInt8, false),
+        Field::new("retain", DataType::Boolean, false),
+        Field::new("payload", DataType::Utf8, false),
+        Field::new("user_properties", DataType::Utf8, false),
+        Field::new("create_time", DataType::UInt64, false),
+    ])
+}
+
+pub fn encode_parquet(messages: &[MqttMessage]) -> Result<Vec<u8>, MqttBrokerError> {
+    let schema = Arc::new(parquet_schema());
+    let user_properties: Vec<String> = messages
+        .iter()
+        .map(|message| {
+            let properties: serde_json::Map<String, serde_json::Value> = message
+                .user_properties
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+                .collect();
+            serde_json::Value::Object(properties).to_string()
+        })
+        .collect();
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            messages.iter().map(|message| message.client_id.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            messages
+                .iter()
+                .map(|message| String::from_utf8_lossy(&message.topic)),
+        )),
+        Arc::new(UInt8Array::from_iter_values(
+            messages.iter().map(|message| message.qos as u8),
+        )),
+        Arc::new(BooleanArray::from(
+            messages
+                .iter()
+                .map(|message| message.retain)
+                .collect::<Vec<bool>>(),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            messages
+                .iter()
+                .map(|message| String::from_utf8_lossy(&message.payload)),
+        )),
+        Arc::new(StringArray::from_iter_values(user_properties.iter())),
+        Arc::new(UInt64Array::from_iter_values(
+            messages.iter().map(|message| message.create_time),
+        )),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(Vec::new(), schema, Some(props))?;
+    writer.write(&batch)?;
+    Ok(writer.into_inner()?)
+}
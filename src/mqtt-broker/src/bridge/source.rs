@@ -0,0 +1,213 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use log::{error, warn};
+use metadata_struct::mqtt::{connection::MQTTConnection, message::MqttMessage};
+use protocol::mqtt::common::{Publish, PublishProperties};
+use storage_adapter::storage::StorageAdapter;
+use tokio::sync::broadcast::Receiver;
+
+use crate::handler::{
+    error::MqttBrokerError,
+    message::build_message_expire,
+    mountpoint::{mount_topic, resolve_mountpoint},
+    publish::MessagePublisher,
+    tenant::tenant_mountpoint,
+    topic::mount_publish_topic,
+};
+
+use super::{runtime::ConnectorRuntime, transform::transform_payload};
+
+// The client id the messages of a source connector are published with.
+pub fn source_client_id(connector_name: &str) -> String {
+    format!("$connector/{}", connector_name)
+}
+
+// Publishes the messages ingested by a source connector through the publish path of the
+// clients, as the user the connector is configured with: the mountpoint of the user, the
+// ACL, the tenant quotas, the hooks, the WASM plugins, the schema of the topic, retained
+// and delay messages and the rule engine apply the same way.
+pub struct SourcePublisher<S> {
+    runtime: ConnectorRuntime<S>,
+    message_publisher: MessagePublisher<S>,
+    client_id: String,
+    username: String,
+}
+
+impl<S> SourcePublisher<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        runtime: ConnectorRuntime<S>,
+        message_publisher: MessagePublisher<S>,
+        client_id: String,
+        username: String,
+    ) -> Self {
+        SourcePublisher {
+            runtime,
+            message_publisher,
+            client_id,
+            username,
+        }
+    }
+
+    fn rejected(&self, message: String) -> MqttBrokerError {
+        MqttBrokerError::SourceMessageRejected(self.runtime.connector_name.clone(), message)
+    }
+
+    // The connection the message is published as.
+    fn connection(&self) -> Result<MQTTConnection, MqttBrokerError> {
+        let (tenant, mountpoint) = match self.runtime.cache_manager.user_info.get(&self.username) {
+            Some(user) => (
+                user.tenant.clone(),
+                mount_topic(
+                    &tenant_mountpoint(&user.tenant),
+                    &resolve_mountpoint(&user.mountpoint, &self.client_id, &user.username)?,
+                ),
+            ),
+            None => (String::new(), String::new()),
+        };
+        Ok(MQTTConnection {
+            client_id: self.client_id.clone(),
+            is_login: true,
+            login_user: self.username.clone(),
+            tenant,
+            mountpoint,
+            ..Default::default()
+        })
+    }
+
+    // A message that will never be accepted, e.g. because it is denied by the ACL, returns
+    // SourceMessageRejected so that the source can skip it. Other errors are transient and
+    // the message should be published again.
+    pub async fn publish(
+        &self,
+        topic_name: &str,
+        publish: &Publish,
+        publish_properties: &Option<PublishProperties>,
+    ) -> Result<(), MqttBrokerError> {
+        let connection = self
+            .connection()
+            .map_err(|e| self.rejected(e.to_string()))?;
+
+        let topic_name = mount_publish_topic(
+            &self.runtime.cache_manager,
+            &connection.mountpoint,
+            topic_name.to_owned(),
+        )
+        .map_err(|e| self.rejected(e.to_string()))?;
+
+        match self
+            .message_publisher
+            .publish(
+                &connection,
+                topic_name,
+                publish.clone(),
+                publish_properties.clone(),
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) if e.is_rejected() => Err(self.rejected(e.error.to_string())),
+            Err(e) => Err(e.error),
+        }
+    }
+
+    // Publishes the message until it is written, the transforms of the connector are applied
+    // to the payload first. A rejected message is dead-lettered or skipped, so the source can
+    // move on to the next one. Returns false if the connector was stopped while retrying.
+    pub async fn publish_with_retry(
+        &self,
+        topic_name: &str,
+        publish: &Publish,
+        publish_properties: &Option<PublishProperties>,
+        recv: &mut Receiver<bool>,
+    ) -> bool {
+        let publish = match transform_payload(&self.runtime.config.transforms, &publish.payload) {
+            Ok(payload) => Publish {
+                payload: Bytes::from(payload),
+                ..publish.clone()
+            },
+            Err(e) => {
+                self.reject(topic_name, publish, publish_properties, e.to_string())
+                    .await;
+                return true;
+            }
+        };
+
+        let policy = self.runtime.retry_policy();
+        let mut attempts = 0;
+        loop {
+            match self.publish(topic_name, &publish, publish_properties).await {
+                Ok(()) => {
+                    self.runtime.report_success(1, 0);
+                    return true;
+                }
+                Err(MqttBrokerError::SourceMessageRejected(_, reason)) => {
+                    self.reject(topic_name, &publish, publish_properties, reason)
+                        .await;
+                    return true;
+                }
+                Err(e) => {
+                    attempts += 1;
+                    self.runtime.report_error(&e, policy.is_exhausted(attempts));
+                    error!(
+                        "Connector {} failed to publish a message to topic {}, attempts: {}, error message: {}",
+                        self.runtime.connector_name, topic_name, attempts, e
+                    );
+                    if self.runtime.wait(policy.backoff(attempts), recv).await {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn reject(
+        &self,
+        topic_name: &str,
+        publish: &Publish,
+        publish_properties: &Option<PublishProperties>,
+        reason: String,
+    ) {
+        self.runtime
+            .report_error(&self.rejected(reason.clone()), false);
+
+        let message_expire = build_message_expire(&self.runtime.cache_manager, publish_properties);
+        let dead_lettered = match MqttMessage::build_record(
+            &self.client_id,
+            publish,
+            publish_properties,
+            message_expire,
+        ) {
+            Some(record) => self.runtime.dead_letter(record, &reason, 0).await,
+            None => Ok(false),
+        };
+        match dead_lettered {
+            Ok(true) => {}
+            Ok(false) => warn!(
+                "Connector {} skipped a message of topic {}, reason: {}",
+                self.runtime.connector_name, topic_name, reason
+            ),
+            Err(e) => error!(
+                "Connector {} failed to dead-letter a message of topic {}, reason: {}, error message: {}",
+                self.runtime.connector_name, topic_name, reason, e
+            ),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::async_trait;
+use metadata_struct::{adapter::record::Record, mqtt::bridge::config_sql::SqlConnectorConfig};
+use serde_json::Value;
+use storage_adapter::storage::StorageAdapter;
+use third_driver::{
+    mysql::build_mysql_conn_pool,
+    postgres::{build_postgres_conn_pool, PostgresPool},
+};
+use tokio::sync::broadcast;
+
+use crate::{
+    handler::error::MqttBrokerError,
+    rule_engine::{eval::get_path, sql::PathSegment},
+};
+
+use super::{
+    core::{BridgePlugin, BridgePluginReadConfig},
+    message::build_record_context,
+    runtime::ConnectorRuntime,
+};
+
+mod mysql;
+mod postgres;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SqlDriver {
+    MySQL,
+    PostgreSQL,
+}
+
+#[derive(Clone)]
+enum SqlPool {
+    MySQL(::mysql::Pool),
+    PostgreSQL(PostgresPool),
+}
+
+// The statement of the driver, with the placeholders of the template replaced by
+// parameters, and the fields of the message bound to each parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqlTemplate {
+    pub statement: String,
+    pub fields: Vec<Vec<PathSegment>>,
+}
+
+impl SqlTemplate {
+    // A quoted placeholder such as '${clientid}' is bound as a parameter too, so the
+    // fields never need to be escaped.
+    pub fn parse(template: &str, driver: SqlDriver) -> Self {
+        let mut statement = String::with_capacity(template.len());
+        let mut fields = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let end = start + len + 1;
+            let quoted =
+                start > 0 && rest[..start].ends_with('\'') && rest[end..].starts_with('\'');
+            let (prefix_end, suffix_start) = if quoted {
+                (start - 1, end + 1)
+            } else {
+                (start, end)
+            };
+
+            statement.push_str(&rest[..prefix_end]);
+            fields.push(
+                rest[start + 2..end - 1]
+                    .split('.')
+                    .map(|key| PathSegment::Key(key.to_string()))
+                    .collect(),
+            );
+            match driver {
+                SqlDriver::MySQL => statement.push('?'),
+                SqlDriver::PostgreSQL => statement.push_str(&format!("${}", fields.len())),
+            }
+            rest = &rest[suffix_start..];
+        }
+        statement.push_str(rest);
+        SqlTemplate { statement, fields }
+    }
+
+    // The parameters of a record, a field the message does not have is bound as NULL.
+    pub fn bind(&self, record: &Record) -> Vec<Value> {
+        let context = build_record_context(record);
+        self.fields
+            .iter()
+            .map(|path| get_path(&context, path).cloned().unwrap_or(Value::Null))
+            .collect()
+    }
+}
+
+pub struct SqlBridgePlugin<S> {
+    runtime: ConnectorRuntime<S>,
+    driver: SqlDriver,
+    config: SqlConnectorConfig,
+    stop_send: broadcast::Sender<bool>,
+}
+
+impl<S> SqlBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        runtime: ConnectorRuntime<S>,
+        driver: SqlDriver,
+        config: SqlConnectorConfig,
+        stop_send: broadcast::Sender<bool>,
+    ) -> Self {
+        SqlBridgePlugin {
+            runtime,
+            driver,
+            config,
+            stop_send,
+        }
+    }
+
+    // The drivers are blocking, so the pool is built and used on the blocking threads.
+    async fn build_pool(&self) -> Result<SqlPool, MqttBrokerError> {
+        let driver = self.driver;
+        let url = self.config.url.clone();
+        let pool_size = self.config.pool_size;
+        tokio::task::spawn_blocking(move || match driver {
+            SqlDriver::MySQL => Ok(SqlPool::MySQL(build_mysql_conn_pool(&mysql_pool_url(
+                &url, pool_size,
+            ))?)),
+            SqlDriver::PostgreSQL => Ok(SqlPool::PostgreSQL(build_postgres_conn_pool(
+                &url, pool_size,
+            )?)),
+        })
+        .await
+        .map_err(|e| MqttBrokerError::CommonError(e.to_string()))?
+    }
+
+    // Inserts the records in one transaction, so a batch is either written completely or
+    // not at all.
+    async fn insert(
+        &self,
+        pool: &SqlPool,
+        template: &SqlTemplate,
+        records: &[Record],
+    ) -> Result<(), MqttBrokerError> {
+        let rows: Vec<Vec<Value>> = records.iter().map(|record| template.bind(record)).collect();
+        let statement = template.statement.clone();
+        let pool = pool.clone();
+        tokio::task::spawn_blocking(move || match pool {
+            SqlPool::MySQL(pool) => mysql::insert_rows(&pool, &statement, rows),
+            SqlPool::PostgreSQL(pool) => postgres::insert_rows(&pool, &statement, rows),
+        })
+        .await
+        .map_err(|e| MqttBrokerError::CommonError(e.to_string()))?
+    }
+}
+
+#[async_trait]
+impl<S> BridgePlugin for SqlBridgePlugin<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    async fn exec(&self, config: BridgePluginReadConfig) -> Result<(), MqttBrokerError> {
+        let mut recv = self.stop_send.subscribe();
+        let template = SqlTemplate::parse(&self.config.sql, self.driver);
+        let template = &template;
+        let pool = self.build_pool().await?;
+        let pool = &pool;
+
+        // The offset is only committed after the transaction is committed.
+        self.runtime
+            .run_sink(&config, &mut recv, move |records| async move {
+                self.insert(pool, template, &records).await
+            })
+            .await
+    }
+}
+
+// The pool size of the mysql driver is set by the url, a size already in the url is kept.
+fn mysql_pool_url(url: &str, pool_size: u32) -> String {
+    if url.contains("pool_max=") {
+        return url.to_string();
+    }
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}pool_min=1&pool_max={}", url, separator, pool_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{mysql_pool_url, SqlDriver, SqlTemplate};
+    use crate::{bridge::message::build_record, rule_engine::sql::PathSegment};
+
+    #[test]
+    fn sql_template_parse_test() {
+        let sql =
+            "INSERT INTO t (device, topic, temp) VALUES ('${clientid}', ${topic}, ${payload.temp})";
+
+        let template = SqlTemplate::parse(sql, SqlDriver::MySQL);
+        assert_eq!(
+            template.statement,
+            "INSERT INTO t (device, topic, temp) VALUES (?, ?, ?)"
+        );
+        assert_eq!(
+            template.fields[2],
+            vec![
+                PathSegment::Key("payload".to_string()),
+                PathSegment::Key("temp".to_string())
+            ]
+        );
+
+        let template = SqlTemplate::parse(sql, SqlDriver::PostgreSQL);
+        assert_eq!(
+            template.statement,
+            "INSERT INTO t (device, topic, temp) VALUES ($1, $2, $3)"
+        );
+    }
+
+    #[test]
+    fn sql_template_bind_test() {
+        let record = build_record("c1", r#"{"temp":30.5}"#);
+
+        let template = SqlTemplate::parse(
+            "INSERT INTO t VALUES (${clientid}, ${payload.temp}, ${payload.humidity})",
+            SqlDriver::MySQL,
+        );
+        assert_eq!(
+            template.bind(&record),
+            vec![json!("c1"), json!(30.5), Value::Null]
+        );
+    }
+
+    #[test]
+    fn mysql_pool_url_test() {
+        assert_eq!(
+            mysql_pool_url("mysql://root@127.0.0.1:3306/mqtt", 4),
+            "mysql://root@127.0.0.1:3306/mqtt?pool_min=1&pool_max=4"
+        );
+        assert_eq!(
+            mysql_pool_url("mysql://root@127.0.0.1:3306/mqtt?pool_max=8", 4),
+            "mysql://root@127.0.0.1:3306/mqtt?pool_max=8"
+        );
+    }
+}
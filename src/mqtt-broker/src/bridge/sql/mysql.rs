@@ -0,0 +1,76 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mysql::prelude::Queryable;
+use mysql::{Pool, TxOpts};
+use serde_json::Value;
+
+use crate::handler::error::MqttBrokerError;
+
+pub fn insert_rows(
+    pool: &Pool,
+    statement: &str,
+    rows: Vec<Vec<Value>>,
+) -> Result<(), MqttBrokerError> {
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    tx.exec_batch(
+        statement,
+        rows.iter()
+            .map(|row| row.iter().map(mysql_value).collect::<Vec<mysql::Value>>()),
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn mysql_value(value: &Value) -> mysql::Value {
+    match value {
+        Value::Null => mysql::Value::NULL,
+        Value::Bool(flag) => mysql::Value::Int(*flag as i64),
+        Value::Number(number) => {
+            if let Some(v) = number.as_i64() {
+                mysql::Value::Int(v)
+            } else if let Some(v) = number.as_u64() {
+                mysql::Value::UInt(v)
+            } else {
+                mysql::Value::Double(number.as_f64().unwrap_or_default())
+            }
+        }
+        Value::String(text) => mysql::Value::Bytes(text.as_bytes().to_vec()),
+        value => mysql::Value::Bytes(value.to_string().into_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::mysql_value;
+
+    #[test]
+    fn mysql_value_test() {
+        assert_eq!(mysql_value(&json!(null)), mysql::Value::NULL);
+        assert_eq!(mysql_value(&json!(true)), mysql::Value::Int(1));
+        assert_eq!(mysql_value(&json!(-3)), mysql::Value::Int(-3));
+        assert_eq!(mysql_value(&json!(1.5)), mysql::Value::Double(1.5));
+        assert_eq!(
+            mysql_value(&json!("c1")),
+            mysql::Value::Bytes(b"c1".to_vec())
+        );
+        assert_eq!(
+            mysql_value(&json!({"a": 1})),
+            mysql::Value::Bytes(br#"{"a":1}"#.to_vec())
+        );
+    }
+}
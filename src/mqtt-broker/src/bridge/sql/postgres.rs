@@ -0,0 +1,111 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use postgres::types::{ToSql, Type};
+use serde_json::Value;
+use third_driver::postgres::PostgresPool;
+
+use crate::{handler::error::MqttBrokerError, rule_engine::eval::value_to_string};
+
+pub fn insert_rows(
+    pool: &PostgresPool,
+    statement: &str,
+    rows: Vec<Vec<Value>>,
+) -> Result<(), MqttBrokerError> {
+    let mut client = pool
+        .get()
+        .map_err(|e| MqttBrokerError::CommonError(e.to_string()))?;
+    let mut tx = client.transaction()?;
+    let stmt = tx.prepare(statement)?;
+    for row in rows.iter() {
+        let params: Vec<Box<dyn ToSql + Sync>> = stmt
+            .params()
+            .iter()
+            .zip(row.iter())
+            .map(|(ty, value)| postgres_value(ty, value))
+            .collect();
+        let refs: Vec<&(dyn ToSql + Sync)> = params.iter().map(|param| param.as_ref()).collect();
+        tx.execute(&stmt, &refs)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+// PostgreSQL checks the type of each parameter, so the field is converted to the type of
+// its column. A field that cannot be converted is bound as NULL.
+fn postgres_value(ty: &Type, value: &Value) -> Box<dyn ToSql + Sync> {
+    if *ty == Type::BOOL {
+        Box::new(as_bool(value))
+    } else if *ty == Type::INT2 {
+        Box::new(as_i64(value).and_then(|v| i16::try_from(v).ok()))
+    } else if *ty == Type::INT4 {
+        Box::new(as_i64(value).and_then(|v| i32::try_from(v).ok()))
+    } else if *ty == Type::INT8 {
+        Box::new(as_i64(value))
+    } else if *ty == Type::FLOAT4 {
+        Box::new(as_f64(value).map(|v| v as f32))
+    } else if *ty == Type::FLOAT8 {
+        Box::new(as_f64(value))
+    } else if *ty == Type::JSON || *ty == Type::JSONB {
+        Box::new((!value.is_null()).then(|| value.clone()))
+    } else {
+        Box::new((!value.is_null()).then(|| value_to_string(value)))
+    }
+}
+
+fn as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(flag) => Some(*flag),
+        Value::Number(number) => number.as_i64().map(|v| v != 0),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Bool(flag) => Some(*flag as i64),
+        Value::Number(number) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|v| v as i64)),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{as_bool, as_f64, as_i64};
+
+    #[test]
+    fn postgres_value_test() {
+        assert_eq!(as_bool(&json!(1)), Some(true));
+        assert_eq!(as_bool(&json!("false")), Some(false));
+        assert_eq!(as_i64(&json!(2.7)), Some(2));
+        assert_eq!(as_i64(&json!("42")), Some(42));
+        assert_eq!(as_i64(&json!({"a": 1})), None);
+        assert_eq!(as_f64(&json!("1.5")), Some(1.5));
+        assert_eq!(as_f64(&json!(null)), None);
+    }
+}
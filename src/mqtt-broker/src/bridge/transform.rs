@@ -0,0 +1,243 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use common_base::utils::crc::calc_crc32;
+use metadata_struct::{
+    adapter::record::Record,
+    mqtt::{
+        bridge::config_common::{ConnectorPayloadFormat, ConnectorTransform},
+        message::MqttMessage,
+    },
+};
+use serde_json::{Map, Value};
+
+use crate::{handler::error::MqttBrokerError, rule_engine::eval::value_to_string};
+
+// Applies the transforms to the payload of the MQTT message in the record. A record that can
+// not be transformed will never be accepted, so it is a poison record of the connector.
+pub fn transform_record(
+    transforms: &[ConnectorTransform],
+    mut record: Record,
+) -> Result<Record, MqttBrokerError> {
+    if transforms.is_empty() {
+        return Ok(record);
+    }
+
+    let mut message = MqttMessage::decode_record(record.clone())
+        .map_err(|e| MqttBrokerError::ConnectorTransformFailed(e.to_string()))?;
+    message.payload = Bytes::from(transform_payload(transforms, &message.payload)?);
+    record.data = message.encode();
+    record.crc_num = calc_crc32(&record.data);
+    Ok(record)
+}
+
+pub fn transform_payload(
+    transforms: &[ConnectorTransform],
+    payload: &[u8],
+) -> Result<Vec<u8>, MqttBrokerError> {
+    let mut payload = payload.to_vec();
+    for transform in transforms {
+        payload = match transform {
+            ConnectorTransform::Extract { field } => {
+                let value = parse_json(&payload)?;
+                let Some(value) = get_field(&value, field) else {
+                    return Err(MqttBrokerError::ConnectorTransformFailed(format!(
+                        "field {} does not exist in the payload",
+                        field
+                    )));
+                };
+                value_to_string(value).into_bytes()
+            }
+            ConnectorTransform::Rename { from, to } => {
+                let mut value = parse_json(&payload)?;
+                if let Some(field) = remove_field(&mut value, from) {
+                    insert_field(&mut value, to, field)?;
+                }
+                serde_json::to_vec(&value)?
+            }
+            ConnectorTransform::Format { format } => format_payload(format, &payload)?,
+        };
+    }
+    Ok(payload)
+}
+
+fn format_payload(
+    format: &ConnectorPayloadFormat,
+    payload: &[u8],
+) -> Result<Vec<u8>, MqttBrokerError> {
+    Ok(match format {
+        ConnectorPayloadFormat::Json => match serde_json::from_slice::<Value>(payload) {
+            Ok(value) => serde_json::to_vec(&value)?,
+            Err(_) => {
+                serde_json::to_vec(&Value::String(String::from_utf8_lossy(payload).to_string()))?
+            }
+        },
+        ConnectorPayloadFormat::Raw => match serde_json::from_slice::<Value>(payload) {
+            Ok(Value::String(value)) => value.into_bytes(),
+            _ => payload.to_vec(),
+        },
+        ConnectorPayloadFormat::Base64 => STANDARD.encode(payload).into_bytes(),
+    })
+}
+
+fn parse_json(payload: &[u8]) -> Result<Value, MqttBrokerError> {
+    serde_json::from_slice(payload).map_err(|e| {
+        MqttBrokerError::ConnectorTransformFailed(format!("the payload is not JSON, {}", e))
+    })
+}
+
+// The field is a path such as "data.temperature".
+fn get_field<'a>(value: &'a Value, field: &str) -> Option<&'a Value> {
+    field
+        .split('.')
+        .try_fold(value, |value, key| value.as_object()?.get(key))
+}
+
+fn remove_field(value: &mut Value, field: &str) -> Option<Value> {
+    let (parent, key) = match field.rsplit_once('.') {
+        Some((parent, key)) => (get_field_mut(value, parent)?, key),
+        None => (value, field),
+    };
+    parent.as_object_mut()?.remove(key)
+}
+
+fn get_field_mut<'a>(value: &'a mut Value, field: &str) -> Option<&'a mut Value> {
+    field
+        .split('.')
+        .try_fold(value, |value, key| value.as_object_mut()?.get_mut(key))
+}
+
+// Missing objects on the path are created.
+fn insert_field(value: &mut Value, field: &str, field_value: Value) -> Result<(), MqttBrokerError> {
+    let mut current = value;
+    let mut keys = field.split('.').peekable();
+    while let Some(key) = keys.next() {
+        let Some(object) = current.as_object_mut() else {
+            return Err(MqttBrokerError::ConnectorTransformFailed(format!(
+                "field {} can not be set in the payload",
+                field
+            )));
+        };
+        if keys.peek().is_none() {
+            object.insert(key.to_string(), field_value);
+            return Ok(());
+        }
+        current = object
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use metadata_struct::{
+        adapter::record::Record,
+        mqtt::{
+            bridge::config_common::{ConnectorPayloadFormat, ConnectorTransform},
+            message::MqttMessage,
+        },
+    };
+
+    use super::{transform_payload, transform_record};
+
+    fn extract(field: &str) -> ConnectorTransform {
+        ConnectorTransform::Extract {
+            field: field.to_string(),
+        }
+    }
+
+    fn rename(from: &str, to: &str) -> ConnectorTransform {
+        ConnectorTransform::Rename {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn format(format: ConnectorPayloadFormat) -> ConnectorTransform {
+        ConnectorTransform::Format { format }
+    }
+
+    #[test]
+    fn transform_payload_test() {
+        let payload = br#"{"data":{"t":21.5,"unit":"C"},"device":"d1"}"#;
+
+        let result = transform_payload(&[extract("data")], payload).unwrap();
+        assert_eq!(result, br#"{"t":21.5,"unit":"C"}"#);
+        let result = transform_payload(&[extract("data.unit")], payload).unwrap();
+        assert_eq!(result, b"C");
+        assert!(transform_payload(&[extract("data.humidity")], payload).is_err());
+        assert!(transform_payload(&[extract("data")], b"not json").is_err());
+
+        let result = transform_payload(
+            &[rename("data.t", "temperature"), rename("missing", "x")],
+            payload,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            br#"{"data":{"unit":"C"},"device":"d1","temperature":21.5}"#
+        );
+        let result = transform_payload(&[rename("device", "meta.id")], payload).unwrap();
+        assert_eq!(
+            result,
+            br#"{"data":{"t":21.5,"unit":"C"},"meta":{"id":"d1"}}"#
+        );
+
+        let result = transform_payload(&[format(ConnectorPayloadFormat::Json)], b"hello").unwrap();
+        assert_eq!(result, br#""hello""#);
+        let result =
+            transform_payload(&[format(ConnectorPayloadFormat::Json)], b"{ \"a\": 1 }").unwrap();
+        assert_eq!(result, br#"{"a":1}"#);
+        let result =
+            transform_payload(&[format(ConnectorPayloadFormat::Raw)], br#""hello""#).unwrap();
+        assert_eq!(result, b"hello");
+        let result =
+            transform_payload(&[format(ConnectorPayloadFormat::Base64)], b"hello").unwrap();
+        assert_eq!(result, b"aGVsbG8=");
+
+        let result = transform_payload(
+            &[extract("device"), format(ConnectorPayloadFormat::Base64)],
+            payload,
+        )
+        .unwrap();
+        assert_eq!(result, b"ZDE=");
+    }
+
+    #[test]
+    fn transform_record_test() {
+        let message = MqttMessage {
+            client_id: "c1".to_string(),
+            topic: Bytes::from("/sensor/1"),
+            payload: Bytes::from(r#"{"data":{"t":21.5}}"#),
+            ..Default::default()
+        };
+        let record = Record::build_byte(message.encode());
+
+        let result = transform_record(&[], record.clone()).unwrap();
+        assert_eq!(result.data, record.data);
+
+        let result = transform_record(&[extract("data.t")], record).unwrap();
+        assert!(result.crc32_check());
+        let message = MqttMessage::decode_record(result).unwrap();
+        assert_eq!(message.payload, Bytes::from("21.5"));
+        assert_eq!(message.client_id, "c1");
+
+        let record = Record::build_str("not a message".to_string());
+        assert!(transform_record(&[extract("data")], record).is_err());
+    }
+}
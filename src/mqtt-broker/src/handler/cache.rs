@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_base::config::broker_mqtt::Listener;
 use common_base::tools::now_second;
 use dashmap::DashMap;
 use grpc_clients::pool::ClientPool;
@@ -20,17 +21,25 @@
 use metadata_struct::mqtt::auto_subscribe_rule::MqttAutoSubscribeRule;
 use metadata_struct::mqtt::cluster::MqttClusterDynamicConfig;
 use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::rule::MqttRule;
 use metadata_struct::mqtt::session::MqttSession;
+use metadata_struct::mqtt::tenant::{MqttTenant, MqttTenantUsage};
 use metadata_struct::mqtt::topic::MqttTopic;
 use metadata_struct::mqtt::topic_rewrite_rule::MqttTopicRewriteRule;
 use metadata_struct::mqtt::user::MqttUser;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 use protocol::mqtt::common::{MqttProtocol, PublishProperties};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::sync::broadcast::Sender;
 
+use super::drain::NodeDrain;
+use super::error::MqttBrokerError;
+use crate::observability::metrics::tenant::metrics_tenant_connection_num;
+use crate::rule_engine::Rule;
 use crate::security::acl::metadata::AclMetadata;
+use crate::wasm_plugin::WasmPlugin;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub enum MetadataCacheAction {
@@ -127,6 +136,27 @@ pub struct CacheManager {
 
     // All auto subscribe rule
     pub auto_subscribe_rule: DashMap<String, MqttAutoSubscribeRule>,
+
+    // Set when the node is being drained, new connections are redirected to the other nodes
+    pub node_drain: Arc<RwLock<Option<NodeDrain>>>,
+
+    // (listener name, Listener)
+    pub listener_info: DashMap<String, Listener>,
+
+    // (tenant_name, Tenant)
+    pub tenant_info: DashMap<String, MqttTenant>,
+
+    // (tenant_name, (second, message num)), messages published by the tenant in the current second
+    pub tenant_publish_rate: DashMap<String, (u64, u64)>,
+
+    // (broker_id, TenantUsage), the tenant usage reported by the other broker nodes
+    pub tenant_node_usage: DashMap<u64, MqttTenantUsage>,
+
+    // (rule_name, Rule), the rules of the rule engine with their parsed sql
+    pub rule_info: DashMap<String, Arc<Rule>>,
+
+    // (plugin_name, WasmPlugin), the WASM plugins with their compiled module
+    pub wasm_plugin_info: DashMap<String, Arc<WasmPlugin>>,
 }
 
 impl CacheManager {
@@ -146,7 +176,46 @@ pub fn new(client_pool: Arc<ClientPool>, cluster_name: String) -> Self {
             acl_metadata: AclMetadata::new(),
             topic_rewrite_rule: DashMap::with_capacity(8),
             auto_subscribe_rule: DashMap::with_capacity(8),
+            node_drain: Arc::new(RwLock::new(None)),
+            listener_info: DashMap::with_capacity(2),
+            tenant_info: DashMap::with_capacity(2),
+            tenant_publish_rate: DashMap::with_capacity(2),
+            tenant_node_usage: DashMap::with_capacity(2),
+            rule_info: DashMap::with_capacity(2),
+            wasm_plugin_info: DashMap::with_capacity(2),
+        }
+    }
+
+    // node drain
+    // Returns false if the node is already draining, checked and set under the same lock so
+    // that only one of two concurrent drain requests starts a drain.
+    pub fn try_set_node_drain(&self, drain: NodeDrain) -> bool {
+        let mut node_drain = self.node_drain.write().unwrap();
+        if node_drain.is_some() {
+            return false;
+        }
+        *node_drain = Some(drain);
+        true
+    }
+
+    pub fn get_node_drain(&self) -> Option<NodeDrain> {
+        self.node_drain.read().unwrap().clone()
+    }
+
+    pub fn remove_node_drain(&self) -> Option<NodeDrain> {
+        self.node_drain.write().unwrap().take()
+    }
+
+    // listener
+    pub fn add_listener(&self, listener: Listener) {
+        self.listener_info.insert(listener.name.clone(), listener);
+    }
+
+    pub fn get_listener(&self, name: &str) -> Option<Listener> {
+        if let Some(listener) = self.listener_info.get(name) {
+            return Some(listener.clone());
         }
+        None
     }
 
     // session
@@ -187,9 +256,27 @@ pub fn remove_session(&self, client_id: &str) {
         }
     }
 
+    // tenant
+    pub fn add_tenant(&self, tenant: MqttTenant) {
+        self.tenant_info.insert(tenant.tenant_name.clone(), tenant);
+    }
+
+    pub fn del_tenant(&self, tenant_name: &str) {
+        self.tenant_info.remove(tenant_name);
+        self.tenant_publish_rate.remove(tenant_name);
+    }
+
+    pub fn get_tenant(&self, tenant_name: &str) -> Option<MqttTenant> {
+        if let Some(tenant) = self.tenant_info.get(tenant_name) {
+            return Some(tenant.clone());
+        }
+        None
+    }
+
     // user
+    // The users are keyed by the name their clients log in with
     pub fn add_user(&self, user: MqttUser) {
-        self.user_info.insert(user.username.clone(), user);
+        self.user_info.insert(user.login_name(), user);
     }
 
     pub fn del_user(&self, username: String) {
@@ -205,12 +292,19 @@ pub fn retain_users(&self, usernames: HashSet<String>) {
     pub fn add_connection(&self, connect_id: u64, conn: MQTTConnection) {
         if let Some(mut session) = self.session_info.get_mut(&conn.client_id) {
             session.connection_id = Some(connect_id);
+            if !conn.tenant.is_empty() {
+                metrics_tenant_connection_num(&conn.tenant, 1);
+            }
             self.connection_info.insert(connect_id, conn);
         }
     }
 
     pub fn remove_connection(&self, connect_id: u64) {
-        self.connection_info.remove(&connect_id);
+        if let Some((_, conn)) = self.connection_info.remove(&connect_id) {
+            if !conn.tenant.is_empty() {
+                metrics_tenant_connection_num(&conn.tenant, -1);
+            }
+        }
     }
 
     pub fn get_connect_id(&self, client_id: &str) -> Option<u64> {
@@ -280,6 +374,40 @@ pub fn delete_topic_rewrite_rule(&self, cluster: &str, action: &str, source_topi
         self.topic_rewrite_rule.remove(&key);
     }
 
+    // rule engine
+    pub fn add_rule(&self, rule: MqttRule) -> Result<(), MqttBrokerError> {
+        let rule = Rule::new(rule)?;
+        self.rule_info
+            .insert(rule.info.rule_name.clone(), Arc::new(rule));
+        Ok(())
+    }
+
+    pub fn delete_rule(&self, rule_name: &str) {
+        self.rule_info.remove(rule_name);
+    }
+
+    pub fn get_rule(&self, rule_name: &str) -> Option<Arc<Rule>> {
+        self.rule_info.get(rule_name).map(|rule| rule.clone())
+    }
+
+    // wasm plugin
+    pub fn add_wasm_plugin(&self, plugin: MqttWasmPlugin) -> Result<(), MqttBrokerError> {
+        let plugin = WasmPlugin::new(plugin)?;
+        self.wasm_plugin_info
+            .insert(plugin.info.plugin_name.clone(), Arc::new(plugin));
+        Ok(())
+    }
+
+    pub fn delete_wasm_plugin(&self, plugin_name: &str) {
+        self.wasm_plugin_info.remove(plugin_name);
+    }
+
+    pub fn get_wasm_plugin(&self, plugin_name: &str) -> Option<Arc<WasmPlugin>> {
+        self.wasm_plugin_info
+            .get(plugin_name)
+            .map(|plugin| plugin.clone())
+    }
+
     pub fn login_success(&self, connect_id: u64, user_name: String) {
         if let Some(mut conn) = self.connection_info.get_mut(&connect_id) {
             conn.login_success(user_name)
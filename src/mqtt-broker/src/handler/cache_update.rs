@@ -15,6 +15,7 @@
 use crate::bridge::manager::ConnectorManager;
 use crate::storage::auto_subscribe::AutoSubscribeStorage;
 use crate::storage::connector::ConnectorStorage;
+use crate::storage::tenant::TenantStorage;
 use crate::storage::topic::TopicStorage;
 use crate::{security::AuthDriver, subscribe::subscribe_manager::SubscribeManager};
 use common_base::config::broker_mqtt::broker_mqtt_conf;
@@ -22,10 +23,13 @@
 use grpc_clients::pool::ClientPool;
 use log::error;
 use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+use metadata_struct::mqtt::rule::MqttRule;
 use metadata_struct::mqtt::session::MqttSession;
 use metadata_struct::mqtt::subscribe_data::MqttSubscribe;
+use metadata_struct::mqtt::tenant::MqttTenant;
 use metadata_struct::mqtt::topic::MqttTopic;
 use metadata_struct::mqtt::user::MqttUser;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 use metadata_struct::schema::{SchemaData, SchemaResourceBind};
 use protocol::broker_mqtt::broker_mqtt_inner::{
     MqttBrokerUpdateCacheActionType, MqttBrokerUpdateCacheResourceType, UpdateMqttCacheRequest,
@@ -118,6 +122,40 @@ pub async fn load_metadata_cache(
         cache_manager.add_topic_rewrite_rule(topic_rewrite_rule);
     }
 
+    // load all rule engine rules
+    let rules = match topic_storage.list_rule().await {
+        Ok(list) => list,
+        Err(e) => {
+            panic!("Failed to load the rule list with error message:{}", e);
+        }
+    };
+    for rule in rules {
+        let rule_name = rule.rule_name.clone();
+        if let Err(e) = cache_manager.add_rule(rule) {
+            error!("Failed to load rule {} with error message:{}", rule_name, e);
+        }
+    }
+
+    // load all wasm plugins
+    let plugins = match topic_storage.list_wasm_plugin().await {
+        Ok(list) => list,
+        Err(e) => {
+            panic!(
+                "Failed to load the wasm plugin list with error message:{}",
+                e
+            );
+        }
+    };
+    for plugin in plugins {
+        let plugin_name = plugin.plugin_name.clone();
+        if let Err(e) = cache_manager.add_wasm_plugin(plugin) {
+            error!(
+                "Failed to load wasm plugin {} with error message:{}",
+                plugin_name, e
+            );
+        }
+    }
+
     // load all connectors
     let connector_storage = ConnectorStorage::new(client_pool.clone());
     let connectors = match connector_storage.list_all_connectors().await {
@@ -169,6 +207,18 @@ pub async fn load_metadata_cache(
     for auto_subscribe_rule in auto_subscribe_rules {
         cache_manager.add_auto_subscribe_rule(auto_subscribe_rule);
     }
+
+    // load all tenant
+    let tenant_storage = TenantStorage::new(client_pool.clone());
+    let tenants = match tenant_storage.list_tenant().await {
+        Ok(list) => list,
+        Err(e) => {
+            panic!("Failed to load the tenant list with error message:{}", e);
+        }
+    };
+    for tenant in tenants {
+        cache_manager.add_tenant(tenant);
+    }
 }
 
 pub async fn update_cache_metadata(
@@ -215,7 +265,77 @@ pub async fn update_cache_metadata(
             MqttBrokerUpdateCacheActionType::Delete => {
                 match serde_json::from_str::<MqttUser>(&request.data) {
                     Ok(user) => {
-                        cache_manager.del_user(user.username);
+                        cache_manager.del_user(user.login_name());
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+        },
+        MqttBrokerUpdateCacheResourceType::Tenant => match request.action_type() {
+            MqttBrokerUpdateCacheActionType::Set => {
+                match serde_json::from_str::<MqttTenant>(&request.data) {
+                    Ok(tenant) => {
+                        cache_manager.add_tenant(tenant);
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+            MqttBrokerUpdateCacheActionType::Delete => {
+                match serde_json::from_str::<MqttTenant>(&request.data) {
+                    Ok(tenant) => {
+                        cache_manager.del_tenant(&tenant.tenant_name);
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+        },
+        MqttBrokerUpdateCacheResourceType::Rule => match request.action_type() {
+            MqttBrokerUpdateCacheActionType::Set => {
+                match serde_json::from_str::<MqttRule>(&request.data) {
+                    Ok(rule) => {
+                        if let Err(e) = cache_manager.add_rule(rule) {
+                            error!("{}", e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+            MqttBrokerUpdateCacheActionType::Delete => {
+                match serde_json::from_str::<MqttRule>(&request.data) {
+                    Ok(rule) => {
+                        cache_manager.delete_rule(&rule.rule_name);
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+        },
+        MqttBrokerUpdateCacheResourceType::WasmPlugin => match request.action_type() {
+            MqttBrokerUpdateCacheActionType::Set => {
+                match serde_json::from_str::<MqttWasmPlugin>(&request.data) {
+                    Ok(plugin) => {
+                        if let Err(e) = cache_manager.add_wasm_plugin(plugin) {
+                            error!("{}", e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("{}", e);
+                    }
+                }
+            }
+            MqttBrokerUpdateCacheActionType::Delete => {
+                match serde_json::from_str::<MqttWasmPlugin>(&request.data) {
+                    Ok(plugin) => {
+                        cache_manager.delete_wasm_plugin(&plugin.plugin_name);
                     }
                     Err(e) => {
                         error!("{}", e);
@@ -17,10 +17,13 @@
 
 use super::flow_control::is_qos_message;
 use super::mqtt::MqttService;
+use super::publish::MessagePublisher;
+use crate::bridge::manager::ConnectorManager;
 use crate::handler::cache::CacheManager;
 use crate::handler::response::{
     response_packet_mqtt_connect_fail, response_packet_mqtt_distinct_by_reason,
 };
+use crate::hook::hook_manager;
 use crate::security::AuthDriver;
 use crate::server::connection::NetworkConnection;
 use crate::server::connection_manager::ConnectionManager;
@@ -28,6 +31,7 @@
 use common_base::telemetry::trace::CustomContext;
 use delay_message::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
+use idempotent_message::IdempotentManager;
 use log::info;
 use opentelemetry::global;
 use opentelemetry::trace::{Span, SpanKind, Tracer};
@@ -55,44 +59,56 @@ pub fn new(
         cache_manager: Arc<CacheManager>,
         message_storage_adapter: Arc<S>,
         delay_message_manager: Arc<DelayMessageManager<S>>,
+        idempotent_manager: Arc<IdempotentManager>,
         subscribe_manager: Arc<SubscribeManager>,
         client_pool: Arc<ClientPool>,
         connection_manager: Arc<ConnectionManager>,
         schema_manager: Arc<SchemaRegisterManager>,
         auth_driver: Arc<AuthDriver>,
+        connector_manager: Arc<ConnectorManager>,
     ) -> Self {
+        let message_publisher = MessagePublisher::new(
+            cache_manager.clone(),
+            message_storage_adapter.clone(),
+            delay_message_manager,
+            idempotent_manager.clone(),
+            subscribe_manager.clone(),
+            schema_manager,
+            client_pool.clone(),
+            connector_manager,
+        );
         let mqtt3_service = MqttService::new(
             MqttProtocol::Mqtt3,
             cache_manager.clone(),
             connection_manager.clone(),
             message_storage_adapter.clone(),
-            delay_message_manager.clone(),
+            idempotent_manager.clone(),
             subscribe_manager.clone(),
-            schema_manager.clone(),
             client_pool.clone(),
             auth_driver.clone(),
+            message_publisher.clone(),
         );
         let mqtt4_service = MqttService::new(
             MqttProtocol::Mqtt4,
             cache_manager.clone(),
             connection_manager.clone(),
             message_storage_adapter.clone(),
-            delay_message_manager.clone(),
+            idempotent_manager.clone(),
             subscribe_manager.clone(),
-            schema_manager.clone(),
             client_pool.clone(),
             auth_driver.clone(),
+            message_publisher.clone(),
         );
         let mqtt5_service = MqttService::new(
             MqttProtocol::Mqtt5,
             cache_manager.clone(),
             connection_manager.clone(),
             message_storage_adapter.clone(),
-            delay_message_manager.clone(),
+            idempotent_manager.clone(),
             subscribe_manager.clone(),
-            schema_manager.clone(),
             client_pool.clone(),
             auth_driver.clone(),
+            message_publisher.clone(),
         );
         Command {
             mqtt3_service,
@@ -195,6 +211,13 @@ pub async fn apply(
                         self.metadata_cache
                             .login_success(tcp_connection.connection_id, username);
                         info!("connect [{}] login success", tcp_connection.connection_id);
+
+                        if let Some(connection) = self
+                            .metadata_cache
+                            .get_connection(tcp_connection.connection_id)
+                        {
+                            hook_manager().on_client_connected(&connection).await;
+                        }
                     }
                 }
                 return Some(ack_pkg);
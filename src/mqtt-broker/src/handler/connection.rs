@@ -24,6 +24,7 @@
 use super::cache::CacheManager;
 use super::error::MqttBrokerError;
 use super::keep_alive::client_keep_live_time;
+use crate::hook::hook_manager;
 use crate::server::connection_manager::ConnectionManager;
 use crate::storage::session::SessionStorage;
 use crate::subscribe::subscribe_manager::SubscribeManager;
@@ -41,49 +42,63 @@ pub fn build_connection(
 ) -> MQTTConnection {
     let keep_alive = client_keep_live_time(cluster, connect.keep_alive);
 
-    let (client_receive_maximum, max_packet_size, topic_alias_max, request_problem_info) =
-        if let Some(properties) = connect_properties {
-            let client_receive_maximum = if let Some(value) = properties.receive_maximum {
-                value
-            } else {
-                cluster.protocol.receive_max
-            };
-
-            let max_packet_size = if let Some(value) = properties.max_packet_size {
-                std::cmp::min(value, cluster.protocol.max_packet_size)
-            } else {
-                cluster.protocol.max_packet_size
-            };
-
-            let topic_alias_max = if let Some(value) = properties.topic_alias_max {
-                std::cmp::min(value, cluster.protocol.topic_alias_max)
-            } else {
-                cluster.protocol.topic_alias_max
-            };
-
-            let request_problem_info = properties.request_problem_info.unwrap_or_default();
-
-            (
-                client_receive_maximum,
-                max_packet_size,
-                topic_alias_max,
-                request_problem_info,
-            )
+    let (
+        client_receive_maximum,
+        max_packet_size,
+        topic_alias_max,
+        client_topic_alias_max,
+        request_problem_info,
+    ) = if let Some(properties) = connect_properties {
+        let client_receive_maximum = if let Some(value) = properties.receive_maximum {
+            value
+        } else {
+            cluster.protocol.receive_max
+        };
+
+        let max_packet_size = if let Some(value) = properties.max_packet_size {
+            std::cmp::min(value, cluster.protocol.max_packet_size)
+        } else {
+            cluster.protocol.max_packet_size
+        };
+
+        let topic_alias_max = if let Some(value) = properties.topic_alias_max {
+            std::cmp::min(value, cluster.protocol.topic_alias_max)
         } else {
-            (
-                cluster.protocol.receive_max,
-                cluster.protocol.max_packet_size,
-                cluster.protocol.topic_alias_max,
-                0,
-            )
+            cluster.protocol.topic_alias_max
         };
 
+        // If the client does not carry the Topic Alias Maximum, the server must not send topic aliases to it.
+        let client_topic_alias_max = std::cmp::min(
+            properties.topic_alias_max.unwrap_or_default(),
+            cluster.protocol.topic_alias_max,
+        );
+
+        let request_problem_info = properties.request_problem_info.unwrap_or_default();
+
+        (
+            client_receive_maximum,
+            max_packet_size,
+            topic_alias_max,
+            client_topic_alias_max,
+            request_problem_info,
+        )
+    } else {
+        (
+            cluster.protocol.receive_max,
+            cluster.protocol.max_packet_size,
+            cluster.protocol.topic_alias_max,
+            0,
+            0,
+        )
+    };
+
     let config = ConnectionConfig {
         connect_id,
         client_id: client_id.clone(),
         receive_maximum: client_receive_maximum,
         max_packet_size,
         topic_alias_max,
+        client_topic_alias_max,
         request_problem_info,
         keep_alive,
         source_ip_addr: addr.to_string(),
@@ -140,6 +155,10 @@ pub async fn disconnect_connection(
             .await?;
     }
 
+    if let Some(connection) = cache_manager.get_connection(connect_id) {
+        hook_manager().on_client_disconnected(&connection).await;
+    }
+
     connection_manager.close_connect(connect_id).await;
     cache_manager.remove_connection(connect_id);
     Ok(())
@@ -195,6 +214,7 @@ pub async fn build_connection_test() {
         assert_eq!(conn.client_max_receive_maximum, 100);
         assert_eq!(conn.max_packet_size, 100);
         assert_eq!(conn.topic_alias_max, 100);
+        assert_eq!(conn.client_topic_alias_max, 100);
         assert_eq!(conn.request_problem_info, 0);
     }
 
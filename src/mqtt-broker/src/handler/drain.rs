@@ -0,0 +1,402 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ws::Message;
+use bytes::BytesMut;
+use common_base::config::broker_mqtt::broker_mqtt_conf;
+use grpc_clients::pool::ClientPool;
+use log::{error, info, warn};
+use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::node_extend::MqttNodeExtend;
+use protocol::mqtt::codec::{MqttCodec, MqttPacketWrapper};
+use protocol::mqtt::common::{ConnectReturnCode, DisconnectReasonCode};
+use tokio::time::sleep;
+
+use super::cache::CacheManager;
+use super::connection::disconnect_connection;
+use super::error::MqttBrokerError;
+use super::response::response_packet_mqtt_distinct_by_server_reference;
+use crate::server::connection::NetworkConnectionType;
+use crate::server::connection_manager::ConnectionManager;
+use crate::storage::cluster::ClusterStorage;
+use crate::subscribe::subscribe_manager::SubscribeManager;
+
+#[derive(Clone)]
+enum ServerReference {
+    // Server references specified by the administrator
+    Fixed(Vec<String>),
+    // The other broker nodes of the cluster, the address is chosen by the connection type
+    Nodes(Vec<MqttNodeExtend>),
+}
+
+#[derive(Clone)]
+pub struct NodeDrain {
+    // ServerMoved tells the client to use the other server permanently, UseAnotherServer temporarily
+    pub server_moved: bool,
+    pub batch_size: u32,
+    pub batch_interval_ms: u64,
+    server_reference: ServerReference,
+    next_reference: Arc<AtomicUsize>,
+    // Set when the node is undrained, the connections not disconnected yet are kept
+    cancelled: Arc<AtomicBool>,
+}
+
+impl NodeDrain {
+    pub fn new(
+        server_moved: bool,
+        server_references: Vec<String>,
+        batch_size: u32,
+        batch_interval_ms: u64,
+    ) -> Self {
+        NodeDrain {
+            server_moved,
+            batch_size,
+            batch_interval_ms,
+            server_reference: ServerReference::Fixed(server_references),
+            next_reference: Arc::new(AtomicUsize::new(0)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub async fn build(
+        client_pool: &Arc<ClientPool>,
+        server_moved: bool,
+        server_reference: &str,
+        batch_size: u32,
+        batch_interval_ms: u64,
+    ) -> Result<Self, MqttBrokerError> {
+        if server_reference.trim().is_empty() {
+            let mut drain = NodeDrain::new(server_moved, Vec::new(), batch_size, batch_interval_ms);
+            drain.server_reference = ServerReference::Nodes(list_peer_nodes(client_pool).await?);
+            return Ok(drain);
+        }
+
+        let server_references = server_reference
+            .split(',')
+            .map(|addr| addr.trim().to_string())
+            .filter(|addr| !addr.is_empty())
+            .collect();
+        Ok(NodeDrain::new(
+            server_moved,
+            server_references,
+            batch_size,
+            batch_interval_ms,
+        ))
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn disconnect_reason(&self) -> DisconnectReasonCode {
+        if self.server_moved {
+            DisconnectReasonCode::ServerMoved
+        } else {
+            DisconnectReasonCode::UseAnotherServer
+        }
+    }
+
+    pub fn connect_return_code(&self) -> ConnectReturnCode {
+        if self.server_moved {
+            ConnectReturnCode::ServerMoved
+        } else {
+            ConnectReturnCode::UseAnotherServer
+        }
+    }
+
+    // Clients are spread over the server references in turn, so that they do not all reconnect to the same node.
+    pub fn server_reference(&self, connection_type: &NetworkConnectionType) -> Option<String> {
+        let index = self.next_reference.fetch_add(1, Ordering::Relaxed);
+        match &self.server_reference {
+            ServerReference::Fixed(list) => {
+                if list.is_empty() {
+                    return None;
+                }
+                Some(list[index % list.len()].clone())
+            }
+            ServerReference::Nodes(nodes) => {
+                if nodes.is_empty() {
+                    return None;
+                }
+                let node = &nodes[index % nodes.len()];
+                let addr = match connection_type {
+                    NetworkConnectionType::Tcp => node.mqtt_addr.clone(),
+                    NetworkConnectionType::Tls => node.mqtts_addr.clone(),
+                    NetworkConnectionType::WebSocket => node.websocket_addr.clone(),
+                    NetworkConnectionType::WebSockets => node.websockets_addr.clone(),
+                    NetworkConnectionType::Quic => node.quic_addr.clone(),
+                };
+                Some(addr)
+            }
+        }
+    }
+}
+
+async fn list_peer_nodes(
+    client_pool: &Arc<ClientPool>,
+) -> Result<Vec<MqttNodeExtend>, MqttBrokerError> {
+    let config = broker_mqtt_conf();
+    let cluster_storage = ClusterStorage::new(client_pool.clone());
+    let mut results = Vec::new();
+    for node in cluster_storage.node_list().await? {
+        if node.node_id == config.broker_id {
+            continue;
+        }
+        match serde_json::from_str::<MqttNodeExtend>(&node.extend) {
+            Ok(extend) => results.push(extend),
+            Err(e) => {
+                warn!(
+                    "Failed to parse the extend info of broker node {}, error message: {}",
+                    node.node_id, e
+                );
+            }
+        }
+    }
+    Ok(results)
+}
+
+// Disconnects the existing connections batch by batch, starting with the oldest ones. The drain
+// must already be set with try_set_node_drain, so that no new connection is accepted on this
+// node. Sessions are released in the placement center, so that the clients can resume them on
+// the broker node they reconnect to.
+pub async fn drain_node(
+    cache_manager: &Arc<CacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    client_pool: &Arc<ClientPool>,
+    drain: NodeDrain,
+) {
+    let mut connections: Vec<MQTTConnection> = cache_manager
+        .connection_info
+        .iter()
+        .map(|connection| connection.clone())
+        .collect();
+    connections.sort_by_key(|connection| connection.create_time);
+
+    let batch_size = std::cmp::max(drain.batch_size, 1) as usize;
+    let total = connections.len();
+    let batch_interval_ms = drain_batch_interval(
+        total.div_ceil(batch_size),
+        drain.batch_interval_ms,
+        broker_mqtt_conf().drain.max_drain_time_ms,
+    );
+    info!(
+        "Start draining the node, {} connections will be disconnected in batches of {} every {}ms",
+        total, batch_size, batch_interval_ms
+    );
+
+    for (i, batch) in connections.chunks(batch_size).enumerate() {
+        if drain.is_cancelled() {
+            info!(
+                "Node drain was cancelled, {} connections were disconnected",
+                i * batch_size
+            );
+            return;
+        }
+
+        for connection in batch {
+            if let Err(e) = drain_connection(
+                cache_manager,
+                connection_manager,
+                subscribe_manager,
+                client_pool,
+                &drain,
+                connection,
+            )
+            .await
+            {
+                error!(
+                    "Failed to drain connection {}, error message: {}",
+                    connection.connect_id, e
+                );
+            }
+        }
+
+        if (i + 1) * batch_size < total {
+            sleep(Duration::from_millis(batch_interval_ms)).await;
+        }
+    }
+
+    // Network connections that have not completed CONNECT yet
+    for (connect_id, _) in connection_manager.list_connect() {
+        connection_manager.close_connect(connect_id).await;
+    }
+
+    info!("Node drained, {} connections were disconnected", total);
+}
+
+// Stops draining the node, new connections are accepted again and the connections that were
+// not disconnected yet are kept.
+pub fn undrain_node(cache_manager: &Arc<CacheManager>) -> Result<(), MqttBrokerError> {
+    let Some(drain) = cache_manager.remove_node_drain() else {
+        return Err(MqttBrokerError::NodeIsNotDraining(
+            broker_mqtt_conf().broker_id,
+        ));
+    };
+    drain.cancel();
+    info!("Node drain was cancelled, new connections are accepted again");
+    Ok(())
+}
+
+// The interval between two batches, shortened so that all batches are disconnected within
+// max_drain_time_ms. 0 means no limit.
+fn drain_batch_interval(batch_num: usize, batch_interval_ms: u64, max_drain_time_ms: u64) -> u64 {
+    if max_drain_time_ms == 0 || batch_num <= 1 {
+        return batch_interval_ms;
+    }
+    batch_interval_ms.min(max_drain_time_ms / (batch_num as u64 - 1))
+}
+
+async fn drain_connection(
+    cache_manager: &Arc<CacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    subscribe_manager: &Arc<SubscribeManager>,
+    client_pool: &Arc<ClientPool>,
+    drain: &NodeDrain,
+    connection: &MQTTConnection,
+) -> Result<(), MqttBrokerError> {
+    // Only MQTT 5 supports DISCONNECT sent by the server, older clients are just closed.
+    if let Some(network) = connection_manager.get_connect(connection.connect_id) {
+        if let Some(protocol) = network.protocol.clone() {
+            if protocol.is_mqtt5() {
+                let packet = response_packet_mqtt_distinct_by_server_reference(
+                    &protocol,
+                    drain.disconnect_reason(),
+                    drain.server_reference(&network.connection_type),
+                );
+                let wrap = MqttPacketWrapper {
+                    protocol_version: protocol.clone().into(),
+                    packet,
+                };
+
+                let res = if connection_manager.is_websocket(connection.connect_id) {
+                    let mut codec = MqttCodec::new(Some(protocol.into()));
+                    let mut buff = BytesMut::new();
+                    match codec.encode_data(wrap.clone(), &mut buff) {
+                        Ok(()) => {}
+                        Err(e) => {
+                            error!("Websocket encode back packet failed with error message: {e:?}");
+                        }
+                    }
+                    connection_manager
+                        .write_websocket_frame(
+                            connection.connect_id,
+                            wrap,
+                            Message::Binary(buff.to_vec()),
+                        )
+                        .await
+                } else {
+                    connection_manager
+                        .write_tcp_frame(connection.connect_id, wrap)
+                        .await
+                };
+
+                if let Err(e) = res {
+                    warn!(
+                        "Failed to send DISCONNECT to client {} when draining the node, error message: {}",
+                        connection.client_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    disconnect_connection(
+        &connection.client_id,
+        connection.connect_id,
+        cache_manager,
+        client_pool,
+        connection_manager,
+        subscribe_manager,
+        false,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::mqtt::node_extend::MqttNodeExtend;
+    use protocol::mqtt::common::{ConnectReturnCode, DisconnectReasonCode};
+
+    use super::{drain_batch_interval, NodeDrain, ServerReference};
+    use crate::{handler::cache::CacheManager, server::connection::NetworkConnectionType};
+
+    #[test]
+    fn try_set_node_drain_test() {
+        let cache_manager = CacheManager::new(Arc::new(ClientPool::new(1)), "test".to_string());
+        let drain = NodeDrain::new(false, Vec::new(), 10, 100);
+        assert!(cache_manager.try_set_node_drain(drain.clone()));
+        assert!(!cache_manager.try_set_node_drain(drain.clone()));
+        assert!(cache_manager.get_node_drain().is_some());
+
+        assert!(cache_manager.remove_node_drain().is_some());
+        assert!(cache_manager.try_set_node_drain(drain));
+    }
+
+    #[test]
+    fn server_reference_test() {
+        let drain = NodeDrain::new(
+            false,
+            vec!["node1:1883".to_string(), "node2:1883".to_string()],
+            10,
+            100,
+        );
+        assert_eq!(
+            drain.disconnect_reason(),
+            DisconnectReasonCode::UseAnotherServer
+        );
+        assert_eq!(
+            drain.connect_return_code(),
+            ConnectReturnCode::UseAnotherServer
+        );
+        let tcp = NetworkConnectionType::Tcp;
+        assert_eq!(drain.server_reference(&tcp), Some("node1:1883".to_string()));
+        assert_eq!(drain.server_reference(&tcp), Some("node2:1883".to_string()));
+        assert_eq!(drain.server_reference(&tcp), Some("node1:1883".to_string()));
+
+        let node = MqttNodeExtend {
+            mqtt_addr: "node1:1883".to_string(),
+            websocket_addr: "node1:8083".to_string(),
+            ..Default::default()
+        };
+        let mut drain = NodeDrain::new(true, Vec::new(), 10, 100);
+        assert_eq!(drain.server_reference(&tcp), None);
+        drain.server_reference = ServerReference::Nodes(vec![node]);
+        assert_eq!(drain.disconnect_reason(), DisconnectReasonCode::ServerMoved);
+        assert_eq!(
+            drain.server_reference(&NetworkConnectionType::WebSocket),
+            Some("node1:8083".to_string())
+        );
+    }
+
+    #[test]
+    fn drain_batch_interval_test() {
+        assert_eq!(drain_batch_interval(10, 1000, 0), 1000);
+        assert_eq!(drain_batch_interval(10, 1000, 30000), 1000);
+        assert_eq!(drain_batch_interval(1000, 1000, 30000), 30);
+        assert_eq!(drain_batch_interval(1, 1000, 10), 1000);
+        assert_eq!(drain_batch_interval(0, 1000, 10), 1000);
+    }
+}
@@ -45,6 +45,18 @@ pub enum MqttBrokerError {
     #[error("{0}")]
     FromMysqlError(#[from] mysql::Error),
 
+    #[error("{0}")]
+    FromPostgresError(#[from] postgres::Error),
+
+    #[error("{0}")]
+    FromArrowError(#[from] arrow_schema::ArrowError),
+
+    #[error("{0}")]
+    FromParquetError(#[from] parquet::errors::ParquetError),
+
+    #[error("{0}")]
+    FromRedisError(#[from] redis::RedisError),
+
     #[error("Topic alias is too long. alias is {0}")]
     TopicAliasTooLong(u16),
 
@@ -57,6 +69,15 @@ pub enum MqttBrokerError {
     #[error("Topic {0} is incorrectly formatted")]
     TopicNameIncorrectlyFormatted(String),
 
+    #[error("Topic {0} is not under the mountpoint {1} of the client")]
+    TopicNameOutOfMountpoint(String, String),
+
+    #[error("The message with {0} is still being stored, publish it again later")]
+    MessageIdIsBeingStored(String),
+
+    #[error("Mountpoint {0} is invalid, the client id and username it contains must not contain +, #, / or NUL")]
+    MountpointInvalid(String),
+
     #[error("Connection ID [0] information not found in cache.")]
     NotFoundConnectionInCache(u64),
 
@@ -74,6 +95,86 @@ pub enum MqttBrokerError {
     )]
     SubPublishWaitPubRecTimeout(String),
 
+    #[error("Subscribe to push, client {0} did not return {1} within the timeout period.")]
+    SubPublishWaitAckTimeout(String, String),
+
+    #[error(
+        "Subscribe to push, message to client {0} was still not acknowledged after {1} attempts."
+    )]
+    SubPublishRetryExhausted(String, u32),
+
+    #[error("Broker node {0} is already being drained")]
+    NodeIsDraining(u64),
+
+    #[error("Broker node {0} is not being drained")]
+    NodeIsNotDraining(u64),
+
+    #[error("Invalid PROXY protocol header: {0}")]
+    ProxyProtocolHeaderInvalid(String),
+
+    #[error("Listener {0} does not exist")]
+    ListenerNotExists(String),
+
+    #[error("Listener {0} is already running")]
+    ListenerAlreadyRunning(String),
+
+    #[error("Listener {0} is not running")]
+    ListenerNotRunning(String),
+
+    #[error("Listener {0} protocol {1} is not supported, the protocol must be one of tcp, tls, ws, wss and quic")]
+    ListenerProtocolNotSupported(String, String),
+
+    #[error("Listener {0} protocol {1} does not support the PROXY protocol, only tcp and tls listeners do")]
+    ListenerProxyProtocolNotSupported(String, String),
+
+    #[error("Listener {0} protocol {1} does not support x509 authentication, only tls listeners with a ca_cert do")]
+    ListenerX509AuthNotSupported(String, String),
+
+    #[error("Listener name {0} is used by more than one listener")]
+    ListenerNameDuplicated(String),
+
+    #[error("Listener {0} is not a TLS listener")]
+    ListenerNotTls(String),
+
+    #[error("Failed to reload the TLS certificates of {0}, reloaded listeners: [{1}]")]
+    TlsCertReloadFailed(String, String),
+
+    #[error("Tenant {0} does not exist")]
+    TenantNotExists(String),
+
+    #[error("Tenant {0} already exists")]
+    TenantAlreadyExists(String),
+
+    #[error("Tenant {0} still has users and cannot be deleted")]
+    TenantHasUsers(String),
+
+    #[error("Tenant {0} exceeded the connection quota {1}")]
+    TenantConnectionQuotaExceeded(String, u64),
+
+    #[error("Tenant {0} exceeded the topic quota {1}")]
+    TenantTopicQuotaExceeded(String, u64),
+
+    #[error("Tenant {0} exceeded the publish rate quota")]
+    TenantPublishRateExceeded(String),
+
+    #[error("{0} does not belong to tenant {1}")]
+    TenantResourceNotOwned(String, String),
+
+    #[error("Topic {0} does not belong to tenant {1}")]
+    TenantTopicNotOwned(String, String),
+
+    #[error("Tenant name {0} can only contain letters, digits and underscores")]
+    TenantNameInvalid(String),
+
+    #[error("User name {0} cannot contain the tenant separator {1}")]
+    UserNameInvalid(String, char),
+
+    #[error("Publishing to topic {0} is not authorized")]
+    PublishNotAuthorized(String),
+
+    #[error("The payload does not match the schema of topic {0}")]
+    PayloadNotMatchSchema(String),
+
     #[error("Bad subscription Path [{0}] does not exist")]
     SubscriptionPathNotExists(String),
 
@@ -104,17 +205,92 @@ pub enum MqttBrokerError {
     #[error("topicRewriteRule has been existed")]
     TopicRewriteRuleAlreadyExist,
 
+    #[error("Rule SQL syntax error: {0}")]
+    RuleSqlSyntaxError(String),
+
+    #[error("Rule evaluation error: {0}")]
+    RuleEvalError(String),
+
+    #[error("Rule {0} does not exist")]
+    RuleNotExists(String),
+
+    #[error("Rule {0} already exists")]
+    RuleAlreadyExists(String),
+
+    #[error("Invalid rule action: {0}")]
+    RuleActionInvalid(String),
+
+    #[error("Denied by hook {0}: {1}")]
+    HookDenied(String, String),
+
+    #[error("WASM plugin {0} does not exist")]
+    WasmPluginNotExists(String),
+
+    #[error("WASM plugin {0} already exists")]
+    WasmPluginAlreadyExists(String),
+
+    #[error("WASM plugin {0} error: {1}")]
+    WasmPluginError(String, String),
+
+    #[error("WebHook {0} request failed: {1}")]
+    WebHookRequestFailed(String, String),
+
+    #[error("Connector {0} request failed: {1}")]
+    ConnectorRequestFailed(String, String),
+
+    #[error("Scheduled message {0} does not exist")]
+    ScheduledMessageNotExists(String),
+
+    #[error("Scheduled message {0} already exists")]
+    ScheduledMessageAlreadyExists(String),
+
+    #[error("Scheduled message {0} error: {1}")]
+    ScheduledMessageError(String, String),
+
+    #[error("Source connector {0} rejected the message: {1}")]
+    SourceMessageRejected(String, String),
+
+    #[error("Failed to transform the message of the connector: {0}")]
+    ConnectorTransformFailed(String),
+
+    #[error("Connector {0} does not exist")]
+    ConnectorNotExists(String),
+
+    #[error("Connector {0} is not paused")]
+    ConnectorNotPaused(String),
+
+    #[error("Connector {0} does not read the messages of a topic, its offset can not be reset")]
+    ConnectorOffsetNotResettable(String),
+
+    #[error("Connector {0} is still stopping, try again later")]
+    ConnectorNotStopped(String),
+
+    #[error("Offset {0} is beyond the end offset {1} of the topic")]
+    ConnectorOffsetOutOfRange(u64, u64),
+
+    #[error("Redis {0} runs in cluster mode, which is not supported by the Redis connector")]
+    RedisClusterNotSupported(String),
+
     #[error("Failed to build Message")]
     FailedToBuildMessage,
 
     #[error("Publish message was delayed, the target Topic failed to resolve, Topic name {0}")]
     DelayPublishDecodeTopicNameFail(String),
 
+    #[error("Delay message {0} does not exist")]
+    DelayMessageNotExists(String),
+
     #[error("Invalid schema type {0}")]
     InvalidSchemaType(String),
 
+    #[error("Schema {0} does not exist")]
+    SchemaNotExists(String),
+
     #[error("kafka error: {0}")]
     KafkaError(#[from] KafkaError),
+
+    #[error("mqtt client error: {0}")]
+    PahoMqttError(#[from] paho_mqtt::Error),
 }
 
 impl From<MqttBrokerError> for Status {
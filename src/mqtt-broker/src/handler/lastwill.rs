@@ -53,6 +53,7 @@ pub async fn send_last_will_message<S>(
 
     let topic = try_init_topic(
         &topic_name,
+        "",
         cache_manager,
         &message_storage_adapter,
         client_pool,
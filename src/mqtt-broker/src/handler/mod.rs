@@ -20,6 +20,7 @@
 pub mod connection;
 pub mod constant;
 pub mod delay_message;
+pub mod drain;
 pub mod error;
 pub mod flapping_detect;
 pub mod flow_control;
@@ -27,17 +28,22 @@
 pub mod keep_alive;
 pub mod lastwill;
 pub mod message;
+pub mod mountpoint;
 pub mod mqtt;
 pub mod offline_message;
 pub mod pkid;
+pub mod publish;
 pub mod response;
 pub mod retain;
+pub mod scheduled_message;
 pub mod session;
 pub mod sub_auto;
 pub mod sub_exclusive;
 pub mod sub_parse_topic;
 pub mod subscribe;
+pub mod tenant;
 pub mod topic;
+pub mod topic_alias;
 mod topic_rewrite;
 pub mod unsubscribe;
 pub mod user;
@@ -0,0 +1,280 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common_base::config::broker_mqtt::Listener;
+use protocol::mqtt::common::{LastWill, Login, Publish, Subscribe, Unsubscribe};
+
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::tenant::tenant_mountpoint;
+use crate::handler::topic::topic_name_validator;
+use crate::subscribe::sub_common::{
+    decode_queue_info, decode_share_info, is_queue_sub, is_share_sub,
+};
+
+const MOUNTPOINT_CLIENT_ID: &str = "${clientid}";
+const MOUNTPOINT_USERNAME: &str = "${username}";
+
+// A substituted value must stay inside one topic level, otherwise a client id such as `+`
+// would turn the mountpoint into a filter that reaches the namespaces of other clients
+const MOUNTPOINT_FORBIDDEN_CHARS: [char; 4] = ['+', '#', '/', '\0'];
+
+// The mountpoint of the user takes precedence over the mountpoint of the listener. The clients
+// of a tenant are always mounted under the tenant, the mountpoint of the user or the listener
+// is mounted inside it.
+pub fn connection_mountpoint(
+    cache_manager: &Arc<CacheManager>,
+    listener: &Option<Listener>,
+    client_id: &str,
+    login: &Option<Login>,
+) -> Result<String, MqttBrokerError> {
+    let login_name = login
+        .as_ref()
+        .map(|login| login.username.clone())
+        .unwrap_or_default();
+
+    let (tenant, username, template) = if let Some(user) = cache_manager.user_info.get(&login_name)
+    {
+        let template = if !user.mountpoint.is_empty() {
+            user.mountpoint.clone()
+        } else {
+            listener_mountpoint(listener)
+        };
+        (user.tenant.clone(), user.username.clone(), template)
+    } else {
+        (String::new(), login_name, listener_mountpoint(listener))
+    };
+
+    let mountpoint = resolve_mountpoint(&template, client_id, &username)?;
+    Ok(mount_topic(&tenant_mountpoint(&tenant), &mountpoint))
+}
+
+fn listener_mountpoint(listener: &Option<Listener>) -> String {
+    listener
+        .as_ref()
+        .map(|listener| listener.mountpoint.clone())
+        .unwrap_or_default()
+}
+
+pub fn resolve_mountpoint(
+    template: &str,
+    client_id: &str,
+    username: &str,
+) -> Result<String, MqttBrokerError> {
+    if template.is_empty() {
+        return Ok(String::new());
+    }
+
+    if template.contains(MOUNTPOINT_CLIENT_ID) {
+        check_mountpoint_value(client_id)?;
+    }
+    if template.contains(MOUNTPOINT_USERNAME) {
+        check_mountpoint_value(username)?;
+    }
+
+    let mountpoint = template
+        .replace(MOUNTPOINT_CLIENT_ID, client_id)
+        .replace(MOUNTPOINT_USERNAME, username);
+
+    if mountpoint.contains(['+', '#']) {
+        return Err(MqttBrokerError::MountpointInvalid(mountpoint));
+    }
+    topic_name_validator(&mountpoint)
+        .map_err(|_| MqttBrokerError::MountpointInvalid(mountpoint.clone()))?;
+    Ok(mountpoint)
+}
+
+fn check_mountpoint_value(value: &str) -> Result<(), MqttBrokerError> {
+    if value.is_empty() || value.contains(MOUNTPOINT_FORBIDDEN_CHARS) {
+        return Err(MqttBrokerError::MountpointInvalid(value.to_string()));
+    }
+    Ok(())
+}
+
+pub fn mount_topic(mountpoint: &str, topic: &str) -> String {
+    if mountpoint.is_empty() {
+        return topic.to_string();
+    }
+    format!("{}{}", mountpoint, topic)
+}
+
+pub fn unmount_topic(mountpoint: &str, topic: &str) -> String {
+    if mountpoint.is_empty() {
+        return topic.to_string();
+    }
+    topic.strip_prefix(mountpoint).unwrap_or(topic).to_string()
+}
+
+// Shared and queue subscriptions keep their prefix, only the topic filter is mounted
+pub fn mount_sub_path(mountpoint: &str, path: &str) -> String {
+    if mountpoint.is_empty() {
+        return path.to_string();
+    }
+
+    if is_share_sub(path) {
+        let (group_name, sub_name) = decode_share_info(path);
+        let prefix = path.split("/").next().unwrap_or_default();
+        return format!(
+            "{}/{}/{}",
+            prefix,
+            group_name,
+            mount_topic(mountpoint, sub_name.trim_start_matches("/"))
+        );
+    }
+
+    if is_queue_sub(path) {
+        let sub_name = decode_queue_info(path);
+        let prefix = path.split("/").next().unwrap_or_default();
+        return format!(
+            "{}/{}",
+            prefix,
+            mount_topic(mountpoint, sub_name.trim_start_matches("/"))
+        );
+    }
+
+    mount_topic(mountpoint, path)
+}
+
+pub fn mount_subscribe(mountpoint: &str, subscribe: &mut Subscribe) {
+    for filter in subscribe.filters.iter_mut() {
+        filter.path = mount_sub_path(mountpoint, &filter.path);
+    }
+}
+
+pub fn mount_unsubscribe(mountpoint: &str, un_subscribe: &mut Unsubscribe) {
+    for path in un_subscribe.filters.iter_mut() {
+        *path = mount_sub_path(mountpoint, path);
+    }
+}
+
+pub fn mount_last_will(mountpoint: &str, last_will: &mut Option<LastWill>) {
+    if mountpoint.is_empty() {
+        return;
+    }
+    if let Some(will) = last_will.as_mut() {
+        let topic = String::from_utf8_lossy(&will.topic).to_string();
+        will.topic = Bytes::from(mount_topic(mountpoint, &topic));
+    }
+}
+
+pub fn unmount_publish(mountpoint: &str, publish: &mut Publish) {
+    if mountpoint.is_empty() {
+        return;
+    }
+    let topic = String::from_utf8_lossy(&publish.topic).to_string();
+    publish.topic = Bytes::from(unmount_topic(mountpoint, &topic));
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use protocol::mqtt::common::{Filter, LastWill, Publish, QoS, Subscribe, Unsubscribe};
+
+    use super::{
+        mount_last_will, mount_sub_path, mount_subscribe, mount_topic, mount_unsubscribe,
+        resolve_mountpoint, unmount_publish, unmount_topic,
+    };
+
+    #[test]
+    fn resolve_mountpoint_test() {
+        assert_eq!(
+            resolve_mountpoint("tenant_a/", "c1", "u1").unwrap(),
+            "tenant_a/"
+        );
+        assert_eq!(
+            resolve_mountpoint("${username}/${clientid}/", "c1", "u1").unwrap(),
+            "u1/c1/"
+        );
+        assert_eq!(resolve_mountpoint("", "c1", "u1").unwrap(), "");
+        // the client id is only checked when the template uses it
+        assert_eq!(
+            resolve_mountpoint("${username}/", "+", "u1").unwrap(),
+            "u1/"
+        );
+    }
+
+    #[test]
+    fn resolve_mountpoint_hostile_test() {
+        for client_id in ["+", "#", "a/b", "a+", "x\0y", ""] {
+            assert!(resolve_mountpoint("${clientid}/", client_id, "u1").is_err());
+        }
+        assert!(resolve_mountpoint("${username}/", "c1", "#").is_err());
+        assert!(resolve_mountpoint("tenant/+/", "c1", "u1").is_err());
+        assert!(resolve_mountpoint("tenant a/", "c1", "u1").is_err());
+    }
+
+    #[test]
+    fn mount_topic_test() {
+        assert_eq!(mount_topic("tenant-a/", "a/b"), "tenant-a/a/b");
+        assert_eq!(mount_topic("", "a/b"), "a/b");
+        assert_eq!(unmount_topic("tenant-a/", "tenant-a/a/b"), "a/b");
+        assert_eq!(unmount_topic("tenant-a/", "tenant-b/a/b"), "tenant-b/a/b");
+        assert_eq!(unmount_topic("", "a/b"), "a/b");
+    }
+
+    #[test]
+    fn mount_sub_path_test() {
+        assert_eq!(mount_sub_path("tenant-a/", "a/+"), "tenant-a/a/+");
+        assert_eq!(mount_sub_path("tenant-a/", "#"), "tenant-a/#");
+        assert_eq!(
+            mount_sub_path("tenant-a/", "$share/g1/a/b"),
+            "$share/g1/tenant-a/a/b"
+        );
+        assert_eq!(
+            mount_sub_path("tenant-a/", "$queue/a/b"),
+            "$queue/tenant-a/a/b"
+        );
+        assert_eq!(mount_sub_path("", "$share/g1/a/b"), "$share/g1/a/b");
+    }
+
+    #[test]
+    fn mount_packet_test() {
+        let mut subscribe = Subscribe {
+            packet_identifier: 1,
+            filters: vec![Filter {
+                path: "a/b".to_string(),
+                qos: QoS::AtLeastOnce,
+                ..Default::default()
+            }],
+        };
+        mount_subscribe("tenant-a/", &mut subscribe);
+        assert_eq!(subscribe.filters[0].path, "tenant-a/a/b");
+
+        let mut un_subscribe = Unsubscribe {
+            pkid: 1,
+            filters: vec!["a/b".to_string()],
+        };
+        mount_unsubscribe("tenant-a/", &mut un_subscribe);
+        assert_eq!(un_subscribe.filters[0], "tenant-a/a/b");
+
+        let mut last_will = Some(LastWill {
+            topic: Bytes::from("will"),
+            message: Bytes::from("bye"),
+            qos: QoS::AtMostOnce,
+            retain: false,
+        });
+        mount_last_will("tenant-a/", &mut last_will);
+        assert_eq!(last_will.unwrap().topic, Bytes::from("tenant-a/will"));
+
+        let mut publish = Publish {
+            topic: Bytes::from("tenant-a/a/b"),
+            ..Default::default()
+        };
+        unmount_publish("tenant-a/", &mut publish);
+        assert_eq!(publish.topic, Bytes::from("a/b"));
+    }
+}
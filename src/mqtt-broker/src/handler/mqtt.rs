@@ -16,9 +16,10 @@
 use std::sync::Arc;
 
 use common_base::tools::now_second;
-use delay_message::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
+use idempotent_message::IdempotentManager;
 use log::{error, warn};
+use metadata_struct::mqtt::connection::MQTTConnection;
 use protocol::mqtt::common::{
     Connect, ConnectProperties, ConnectReturnCode, Disconnect, DisconnectProperties,
     DisconnectReasonCode, LastWill, LastWillProperties, Login, MqttPacket, MqttProtocol, PingReq,
@@ -27,11 +28,9 @@
     PublishProperties, QoS, Subscribe, SubscribeProperties, SubscribeReasonCode, UnsubAckReason,
     Unsubscribe, UnsubscribeProperties,
 };
-use schema_register::schema::SchemaRegisterManager;
 use storage_adapter::storage::StorageAdapter;
 
 use super::connection::{disconnect_connection, is_delete_session};
-use super::offline_message::save_message;
 use super::retain::{is_new_sub, try_send_retain_message};
 use super::sub_auto::start_auto_subscribe;
 use super::subscribe::save_subscribe;
@@ -40,30 +39,38 @@
     CacheManager, ConnectionLiveTime, QosAckPackageData, QosAckPackageType,
 };
 use crate::handler::connection::{build_connection, get_client_id};
+use crate::handler::error::MqttBrokerError;
 use crate::handler::flapping_detect::check_flapping_detect;
 use crate::handler::lastwill::save_last_will_message;
+use crate::handler::mountpoint::{
+    connection_mountpoint, mount_last_will, mount_subscribe, mount_unsubscribe,
+};
 use crate::handler::pkid::{pkid_delete, pkid_exists, pkid_save};
+use crate::handler::publish::{MessagePublisher, PublishError, PublishErrorKind};
 use crate::handler::response::{
-    response_packet_mqtt_connect_fail, response_packet_mqtt_connect_success,
-    response_packet_mqtt_distinct_by_reason, response_packet_mqtt_ping_resp,
-    response_packet_mqtt_puback_fail, response_packet_mqtt_puback_success,
-    response_packet_mqtt_pubcomp_fail, response_packet_mqtt_pubcomp_success,
-    response_packet_mqtt_pubrec_fail, response_packet_mqtt_pubrec_success,
-    response_packet_mqtt_pubrel_success, response_packet_mqtt_suback,
-    response_packet_mqtt_unsuback,
+    response_packet_mqtt_connect_fail, response_packet_mqtt_connect_redirect,
+    response_packet_mqtt_connect_success, response_packet_mqtt_distinct_by_reason,
+    response_packet_mqtt_ping_resp, response_packet_mqtt_puback_fail,
+    response_packet_mqtt_puback_success, response_packet_mqtt_pubcomp_fail,
+    response_packet_mqtt_pubcomp_success, response_packet_mqtt_pubrec_fail,
+    response_packet_mqtt_pubrec_success, response_packet_mqtt_pubrel_success,
+    response_packet_mqtt_suback, response_packet_mqtt_unsuback,
 };
-use crate::handler::retain::save_retain_message;
 use crate::handler::session::{build_session, save_session};
-use crate::handler::topic::{get_topic_name, try_init_topic};
+use crate::handler::tenant::{check_tenant_connection_quota, login_tenant};
+use crate::handler::topic::get_topic_name;
 use crate::handler::topic_rewrite::{process_sub_topic_rewrite, process_unsub_topic_rewrite};
 use crate::handler::validator::{
     connect_validator, publish_validator, subscribe_validator, un_subscribe_validator,
 };
+use crate::hook::hook_manager;
 use crate::observability::system_topic::event::{
     st_report_connected_event, st_report_disconnected_event, st_report_subscribed_event,
     st_report_unsubscribed_event,
 };
+use crate::security::login::x509::x509_login;
 use crate::security::AuthDriver;
+use crate::server::connection::NetworkConnectionType;
 use crate::server::connection_manager::ConnectionManager;
 use crate::subscribe::sub_common::{min_qos, path_contain_sub};
 use crate::subscribe::subscribe_manager::SubscribeManager;
@@ -74,11 +81,11 @@ pub struct MqttService<S> {
     cache_manager: Arc<CacheManager>,
     connection_manager: Arc<ConnectionManager>,
     message_storage_adapter: Arc<S>,
-    delay_message_manager: Arc<DelayMessageManager<S>>,
+    idempotent_manager: Arc<IdempotentManager>,
     subscribe_manager: Arc<SubscribeManager>,
-    schema_manager: Arc<SchemaRegisterManager>,
     client_pool: Arc<ClientPool>,
     auth_driver: Arc<AuthDriver>,
+    message_publisher: MessagePublisher<S>,
 }
 
 impl<S> MqttService<S>
@@ -91,22 +98,22 @@ pub fn new(
         cache_manager: Arc<CacheManager>,
         connection_manager: Arc<ConnectionManager>,
         message_storage_adapter: Arc<S>,
-        delay_message_manager: Arc<DelayMessageManager<S>>,
+        idempotent_manager: Arc<IdempotentManager>,
         subscribe_manager: Arc<SubscribeManager>,
-        schema_manager: Arc<SchemaRegisterManager>,
         client_pool: Arc<ClientPool>,
         auth_driver: Arc<AuthDriver>,
+        message_publisher: MessagePublisher<S>,
     ) -> Self {
         MqttService {
             protocol,
             cache_manager,
             connection_manager,
             message_storage_adapter,
-            delay_message_manager,
+            idempotent_manager,
             subscribe_manager,
             client_pool,
             auth_driver,
-            schema_manager,
+            message_publisher,
         }
     }
 
@@ -116,13 +123,27 @@ pub async fn connect(
         connect_id: u64,
         connect: Connect,
         connect_properties: Option<ConnectProperties>,
-        last_will: Option<LastWill>,
+        mut last_will: Option<LastWill>,
         last_will_properties: Option<LastWillProperties>,
         login: &Option<Login>,
         addr: SocketAddr,
     ) -> MqttPacket {
         let cluster = self.cache_manager.get_cluster_info();
 
+        // the node is being drained, redirect the client to the other nodes
+        if let Some(drain) = self.cache_manager.get_node_drain() {
+            let connection_type = self
+                .connection_manager
+                .get_connect(connect_id)
+                .map(|network| network.connection_type)
+                .unwrap_or(NetworkConnectionType::Tcp);
+            return response_packet_mqtt_connect_redirect(
+                &self.protocol,
+                drain.connect_return_code(),
+                drain.server_reference(&connection_type),
+            );
+        }
+
         // connect params validator
         if let Some(res) = connect_validator(
             &self.protocol,
@@ -138,7 +159,7 @@ pub async fn connect(
 
         // blacklist check
         let (client_id, new_client_id) = get_client_id(&connect.client_id);
-        let connection = build_connection(
+        let mut connection = build_connection(
             connect_id,
             client_id.clone(),
             &cluster,
@@ -147,6 +168,18 @@ pub async fn connect(
             &addr,
         );
 
+        if let Err(e) = hook_manager()
+            .on_client_connect(&connection, &connect)
+            .await
+        {
+            return response_packet_mqtt_connect_fail(
+                &self.protocol,
+                ConnectReturnCode::NotAuthorized,
+                &connect_properties,
+                Some(e.to_string()),
+            );
+        }
+
         if self.auth_driver.allow_connect(&connection).await {
             return response_packet_mqtt_connect_fail(
                 &self.protocol,
@@ -157,29 +190,91 @@ pub async fn connect(
         }
 
         // login check
-        match self
-            .auth_driver
-            .check_login_auth(login, &connect_properties, &addr)
+        let network = self.connection_manager.get_connect(connect_id);
+        let listener = network
+            .as_ref()
+            .and_then(|network| network.listener.clone())
+            .and_then(|name| self.cache_manager.get_listener(&name));
+        let cert_identities = network
+            .map(|network| network.cert_identities)
+            .unwrap_or_default();
+        let login = &x509_login(login, &listener, &cert_identities);
+        let hook_authenticated = match hook_manager()
+            .on_client_authenticate(&connection, login)
             .await
         {
-            Ok(flag) => {
-                if !flag {
-                    return response_packet_mqtt_connect_fail(
-                        &self.protocol,
-                        ConnectReturnCode::NotAuthorized,
-                        &connect_properties,
-                        None,
-                    );
-                }
-            }
+            Ok(flag) => flag,
             Err(e) => {
                 return response_packet_mqtt_connect_fail(
                     &self.protocol,
-                    ConnectReturnCode::UnspecifiedError,
+                    ConnectReturnCode::NotAuthorized,
                     &connect_properties,
                     Some(e.to_string()),
                 );
             }
+        };
+        if !hook_authenticated {
+            match self
+                .auth_driver
+                .check_login_auth(
+                    login,
+                    &connect_properties,
+                    &addr,
+                    &listener,
+                    &cert_identities,
+                )
+                .await
+            {
+                Ok(flag) => {
+                    if !flag {
+                        return response_packet_mqtt_connect_fail(
+                            &self.protocol,
+                            ConnectReturnCode::NotAuthorized,
+                            &connect_properties,
+                            None,
+                        );
+                    }
+                }
+                Err(e) => {
+                    return response_packet_mqtt_connect_fail(
+                        &self.protocol,
+                        ConnectReturnCode::UnspecifiedError,
+                        &connect_properties,
+                        Some(e.to_string()),
+                    );
+                }
+            }
+        }
+
+        // mountpoint
+        connection.mountpoint =
+            match connection_mountpoint(&self.cache_manager, &listener, &client_id, login) {
+                Ok(mountpoint) => mountpoint,
+                Err(e) => {
+                    return response_packet_mqtt_connect_fail(
+                        &self.protocol,
+                        ConnectReturnCode::ClientIdentifierNotValid,
+                        &connect_properties,
+                        Some(e.to_string()),
+                    );
+                }
+            };
+        mount_last_will(&connection.mountpoint, &mut last_will);
+
+        // tenant quota check
+        connection.tenant = login_tenant(&self.cache_manager, login);
+        if let Err(e) = check_tenant_connection_quota(&self.cache_manager, &connection.tenant) {
+            let code = if let MqttBrokerError::TenantNotExists(_) = e {
+                ConnectReturnCode::NotAuthorized
+            } else {
+                ConnectReturnCode::QuotaExceeded
+            };
+            return response_packet_mqtt_connect_fail(
+                &self.protocol,
+                code,
+                &connect_properties,
+                Some(e.to_string()),
+            );
         }
 
         // flapping detect check
@@ -210,6 +305,17 @@ pub async fn connect(
             }
         };
 
+        // A session that was connected to another node may have QoS 2 packet identifiers and
+        // message ids saved there, read them before the client sends again.
+        if !new_session {
+            if let Err(e) = self.idempotent_manager.sync().await {
+                warn!(
+                    "Failed to read the idempotent data of client {}, error message: {}",
+                    client_id, e
+                );
+            }
+        }
+
         if let Err(e) = save_session(
             connect_id,
             session.clone(),
@@ -315,7 +421,7 @@ pub async fn publish(
         if let Some(pkg) = publish_validator(
             &self.protocol,
             &self.cache_manager,
-            &self.client_pool,
+            &self.idempotent_manager,
             &connection,
             &publish,
             &publish_properties,
@@ -339,183 +445,107 @@ pub async fn publish(
         ) {
             Ok(da) => da,
             Err(e) => {
-                if is_puback {
-                    return Some(response_packet_mqtt_puback_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubAckReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                } else {
-                    return Some(response_packet_mqtt_pubrec_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubRecReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                }
-            }
-        };
-
-        if !self
-            .auth_driver
-            .allow_publish(&connection, &topic_name, publish.retain, publish.qos)
-            .await
-        {
-            if is_puback {
-                return Some(response_packet_mqtt_puback_fail(
-                    &self.protocol,
-                    &connection,
-                    publish.pkid,
-                    PubAckReason::NotAuthorized,
-                    None,
-                ));
-            } else {
-                return Some(response_packet_mqtt_pubrec_fail(
-                    &self.protocol,
+                return Some(self.publish_fail_response(
                     &connection,
                     publish.pkid,
-                    PubRecReason::NotAuthorized,
-                    None,
+                    is_puback,
+                    &PublishError::new(PublishErrorKind::Invalid, e),
                 ));
             }
-        }
+        };
 
-        let topic = match try_init_topic(
-            &topic_name,
-            &self.cache_manager,
-            &self.message_storage_adapter,
-            &self.client_pool,
-        )
-        .await
+        let pkid = publish.pkid;
+        let result = match self
+            .message_publisher
+            .publish(&connection, topic_name, publish, publish_properties)
+            .await
         {
-            Ok(tp) => tp,
+            Ok(result) => result,
             Err(e) => {
-                if is_puback {
-                    return Some(response_packet_mqtt_puback_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubAckReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                } else {
-                    return Some(response_packet_mqtt_pubrec_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubRecReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                }
+                return Some(self.publish_fail_response(&connection, pkid, is_puback, &e));
             }
         };
 
-        if self.schema_manager.is_check_schema(&topic_name) {
-            if let Err(e) = self.schema_manager.validate(&topic_name, &publish.payload) {
-                if is_puback {
-                    return Some(response_packet_mqtt_puback_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubAckReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                } else {
-                    return Some(response_packet_mqtt_pubrec_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubRecReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                }
-            }
-        }
-
-        let client_id = connection.client_id.clone();
-
-        // Persisting retain message data
-        match save_retain_message(
-            &self.cache_manager,
-            &self.client_pool,
-            topic_name.clone(),
-            &client_id,
-            &publish,
-            &publish_properties,
+        // Duplicates and messages dropped by a WASM plugin are acknowledged as successful
+        // without being stored, so that the publisher does not send them again.
+        self.publish_success_response(
+            connect_id,
+            &connection,
+            &result.topic_name,
+            &result.publish,
+            &result.publish_properties,
+            format!("{:?}", result.offset),
         )
         .await
-        {
-            Ok(()) => {}
-            Err(e) => {
-                if is_puback {
-                    return Some(response_packet_mqtt_puback_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubAckReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                } else {
-                    return Some(response_packet_mqtt_pubrec_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubRecReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                }
-            }
-        }
+    }
 
-        // Persisting stores message data
-        let offset = match save_message(
-            &self.message_storage_adapter,
-            &self.delay_message_manager,
-            &self.cache_manager,
-            &publish,
-            &publish_properties,
-            &self.subscribe_manager,
-            &client_id,
-            &topic,
-        )
-        .await
-        {
-            Ok(da) => {
-                format!("{:?}", da)
-            }
-            Err(e) => {
-                if is_puback {
-                    return Some(response_packet_mqtt_puback_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubAckReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                } else {
-                    return Some(response_packet_mqtt_pubrec_fail(
-                        &self.protocol,
-                        &connection,
-                        publish.pkid,
-                        PubRecReason::UnspecifiedError,
-                        Some(e.to_string()),
-                    ));
-                }
-            }
+    fn publish_fail_response(
+        &self,
+        connection: &MQTTConnection,
+        pkid: u16,
+        is_puback: bool,
+        error: &PublishError,
+    ) -> MqttPacket {
+        let reason_string = match error.kind {
+            PublishErrorKind::NotAuthorized => None,
+            _ => Some(error.error.to_string()),
         };
+        if is_puback {
+            let reason = match error.kind {
+                PublishErrorKind::NotAuthorized => PubAckReason::NotAuthorized,
+                PublishErrorKind::QuotaExceeded => PubAckReason::QuotaExceeded,
+                PublishErrorKind::Plugin => PubAckReason::ImplementationSpecificError,
+                PublishErrorKind::Invalid | PublishErrorKind::Failed => {
+                    PubAckReason::UnspecifiedError
+                }
+            };
+            response_packet_mqtt_puback_fail(
+                &self.protocol,
+                connection,
+                pkid,
+                reason,
+                reason_string,
+            )
+        } else {
+            let reason = match error.kind {
+                PublishErrorKind::NotAuthorized => PubRecReason::NotAuthorized,
+                PublishErrorKind::QuotaExceeded => PubRecReason::QuotaExceeded,
+                PublishErrorKind::Plugin => PubRecReason::ImplementationSpecificError,
+                PublishErrorKind::Invalid | PublishErrorKind::Failed => {
+                    PubRecReason::UnspecifiedError
+                }
+            };
+            response_packet_mqtt_pubrec_fail(
+                &self.protocol,
+                connection,
+                pkid,
+                reason,
+                reason_string,
+            )
+        }
+    }
 
+    // Acknowledges a publish that was stored, or intentionally not stored because it is a
+    // duplicate or a WASM plugin dropped it.
+    async fn publish_success_response(
+        &self,
+        connect_id: u64,
+        connection: &MQTTConnection,
+        topic_name: &str,
+        publish: &Publish,
+        publish_properties: &Option<PublishProperties>,
+        offset: String,
+    ) -> Option<MqttPacket> {
+        let client_id = connection.client_id.clone();
         let user_properties: Vec<(String, String)> = vec![("offset".to_string(), offset)];
 
         self.cache_manager
-            .add_topic_alias(connect_id, &topic_name, &publish_properties);
+            .add_topic_alias(connect_id, topic_name, publish_properties);
 
         match publish.qos {
             QoS::AtMostOnce => None,
             QoS::AtLeastOnce => {
-                let reason_code = if path_contain_sub(&topic_name) {
+                let reason_code = if path_contain_sub(topic_name) {
                     PubAckReason::Success
                 } else {
                     PubAckReason::NoMatchingSubscribers
@@ -530,7 +560,7 @@ pub async fn publish(
             QoS::ExactlyOnce => {
                 match pkid_save(
                     &self.cache_manager,
-                    &self.client_pool,
+                    &self.idempotent_manager,
                     &client_id,
                     publish.pkid,
                 )
@@ -538,26 +568,16 @@ pub async fn publish(
                 {
                     Ok(()) => {}
                     Err(e) => {
-                        if is_puback {
-                            return Some(response_packet_mqtt_puback_fail(
-                                &self.protocol,
-                                &connection,
-                                publish.pkid,
-                                PubAckReason::UnspecifiedError,
-                                Some(e.to_string()),
-                            ));
-                        } else {
-                            return Some(response_packet_mqtt_pubrec_fail(
-                                &self.protocol,
-                                &connection,
-                                publish.pkid,
-                                PubRecReason::UnspecifiedError,
-                                Some(e.to_string()),
-                            ));
-                        }
+                        return Some(response_packet_mqtt_pubrec_fail(
+                            &self.protocol,
+                            connection,
+                            publish.pkid,
+                            PubRecReason::UnspecifiedError,
+                            Some(e.to_string()),
+                        ));
                     }
                 }
-                let reason_code = if path_contain_sub(&topic_name) {
+                let reason_code = if path_contain_sub(topic_name) {
                     PubRecReason::Success
                 } else {
                     PubRecReason::NoMatchingSubscribers
@@ -598,6 +618,13 @@ pub async fn publish_ack(
             }
         }
 
+        if !hook_manager().is_empty() {
+            if let Some(connection) = self.cache_manager.get_connection(connect_id) {
+                hook_manager()
+                    .on_message_acked(&connection, pub_ack.pkid)
+                    .await;
+            }
+        }
         None
     }
 
@@ -639,6 +666,14 @@ pub async fn publish_comp(
         pub_comp: PubComp,
         _: Option<PubCompProperties>,
     ) -> Option<MqttPacket> {
+        if !hook_manager().is_empty() {
+            if let Some(connection) = self.cache_manager.get_connection(connect_id) {
+                hook_manager()
+                    .on_message_acked(&connection, pub_comp.pkid)
+                    .await;
+            }
+        }
+
         if let Some(conn) = self.cache_manager.connection_info.get(&connect_id) {
             let client_id = conn.client_id.clone();
             let pkid = pub_comp.pkid;
@@ -679,7 +714,7 @@ pub async fn publish_rel(
 
         match pkid_exists(
             &self.cache_manager,
-            &self.client_pool,
+            &self.idempotent_manager,
             &client_id,
             pub_rel.pkid,
         )
@@ -709,7 +744,7 @@ pub async fn publish_rel(
 
         match pkid_delete(
             &self.cache_manager,
-            &self.client_pool,
+            &self.idempotent_manager,
             &client_id,
             pub_rel.pkid,
         )
@@ -737,7 +772,7 @@ pub async fn publish_rel(
     pub async fn subscribe(
         &self,
         connect_id: u64,
-        mut subscribe: Subscribe,
+        subscribe: Subscribe,
         subscribe_properties: Option<SubscribeProperties>,
     ) -> MqttPacket {
         let connection = if let Some(se) = self.cache_manager.connection_info.get(&connect_id) {
@@ -762,6 +797,25 @@ pub async fn subscribe(
             return packet;
         }
 
+        let packet_identifier = subscribe.packet_identifier;
+        let filter_num = subscribe.filters.len();
+        let mut subscribe = match hook_manager()
+            .on_client_subscribe(&connection, subscribe)
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                return response_packet_mqtt_suback(
+                    &self.protocol,
+                    &connection,
+                    packet_identifier,
+                    vec![SubscribeReasonCode::NotAuthorized; filter_num],
+                    Some(e.to_string()),
+                );
+            }
+        };
+
+        mount_subscribe(&connection.mountpoint, &mut subscribe);
         let new_subs = is_new_sub(&connection.client_id, &subscribe, &self.subscribe_manager).await;
         process_sub_topic_rewrite(&mut subscribe, &self.cache_manager.topic_rewrite_rule);
         if let Err(e) = save_subscribe(
@@ -795,6 +849,10 @@ pub async fn subscribe(
         )
         .await;
 
+        hook_manager()
+            .on_client_subscribed(&connection, &subscribe)
+            .await;
+
         try_send_retain_message(
             self.protocol.clone(),
             connection.client_id.clone(),
@@ -854,7 +912,7 @@ pub async fn ping(&self, connect_id: u64, _: PingReq) -> MqttPacket {
     pub async fn un_subscribe(
         &self,
         connect_id: u64,
-        mut un_subscribe: Unsubscribe,
+        un_subscribe: Unsubscribe,
         _: Option<UnsubscribeProperties>,
     ) -> MqttPacket {
         let connection = if let Some(se) = self.cache_manager.connection_info.get(&connect_id) {
@@ -877,6 +935,23 @@ pub async fn un_subscribe(
             return packet;
         }
 
+        let pkid = un_subscribe.pkid;
+        let mut un_subscribe = match hook_manager()
+            .on_client_unsubscribe(&connection, un_subscribe)
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                return response_packet_mqtt_unsuback(
+                    &connection,
+                    pkid,
+                    vec![UnsubAckReason::NotAuthorized],
+                    Some(e.to_string()),
+                );
+            }
+        };
+
+        mount_unsubscribe(&connection.mountpoint, &mut un_subscribe);
         process_unsub_topic_rewrite(&mut un_subscribe, &self.cache_manager.topic_rewrite_rule);
 
         if let Err(e) = remove_subscribe(
@@ -907,6 +982,10 @@ pub async fn un_subscribe(
         )
         .await;
 
+        hook_manager()
+            .on_client_unsubscribed(&connection, &un_subscribe)
+            .await;
+
         response_packet_mqtt_unsuback(
             &connection,
             un_subscribe.pkid,
@@ -19,12 +19,16 @@
     delay_message::{decode_delay_topic, is_delay_message},
     error::MqttBrokerError,
     message::build_message_expire,
+    topic::try_init_topic,
 };
 use crate::{
     observability::metrics::packets::record_messages_dropped_discard_metrics,
     storage::message::MessageStorage, subscribe::subscribe_manager::SubscribeManager,
 };
+use bytes::Bytes;
+use common_base::tools::now_second;
 use delay_message::DelayMessageManager;
+use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::{message::MqttMessage, topic::MqttTopic};
 use protocol::mqtt::common::{Publish, PublishProperties};
 use storage_adapter::storage::StorageAdapter;
@@ -38,6 +42,7 @@ pub async fn save_message<S>(
     message_storage_adapter: &Arc<S>,
     delay_message_manager: &Arc<DelayMessageManager<S>>,
     cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
     publish: &Publish,
     publish_properties: &Option<PublishProperties>,
     subscribe_manager: &Arc<SubscribeManager>,
@@ -47,6 +52,22 @@ pub async fn save_message<S>(
 where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
+    // The subscribers of a delay message are only known when it is published.
+    if is_delay_message(&topic.topic_name) {
+        save_delay_message(
+            message_storage_adapter,
+            delay_message_manager,
+            cache_manager,
+            client_pool,
+            publish,
+            publish_properties,
+            client_id,
+            topic,
+        )
+        .await?;
+        return Ok(None);
+    }
+
     let offline_message_disabled = !cache_manager.get_cluster_info().offline_message.enable;
     let not_exist_subscribe = !is_exist_subscribe(subscribe_manager, &topic.topic_name);
     if offline_message_disabled && not_exist_subscribe {
@@ -59,23 +80,69 @@ pub async fn save_message<S>(
     let offset = if let Some(record) =
         MqttMessage::build_record(client_id, publish, publish_properties, message_expire)
     {
-        if is_delay_message(&topic.topic_name) {
-            let delay_topic = decode_delay_topic(&topic.topic_name)?;
-            if delay_topic.is_none() {
-                return Err(MqttBrokerError::DelayPublishDecodeTopicNameFail(
-                    topic.topic_name.clone(),
-                ));
-            }
-            delay_message_manager.send_delay_message(record).await?;
-            return Ok(None);
-        } else {
-            let offsets = message_storage
-                .append_topic_message(&topic.topic_id, vec![record])
-                .await?;
-            Some(format!("{:?}", offsets))
-        }
+        let offsets = message_storage
+            .append_topic_message(&topic.topic_id, vec![record])
+            .await?;
+        Some(format!("{:?}", offsets))
     } else {
         return Err(MqttBrokerError::FailedToBuildMessage);
     };
     Ok(offset)
 }
+
+// Stores the message in the delay message shards, it is written to the shard of the target
+// topic when it is due.
+#[allow(clippy::too_many_arguments)]
+async fn save_delay_message<S>(
+    message_storage_adapter: &Arc<S>,
+    delay_message_manager: &Arc<DelayMessageManager<S>>,
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    publish: &Publish,
+    publish_properties: &Option<PublishProperties>,
+    client_id: &str,
+    topic: &MqttTopic,
+) -> Result<String, MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let delay_topic = if let Some(delay_topic) = decode_delay_topic(&topic.topic_name)? {
+        delay_topic
+    } else {
+        return Err(MqttBrokerError::DelayPublishDecodeTopicNameFail(
+            topic.topic_name.clone(),
+        ));
+    };
+
+    let target_topic = try_init_topic(
+        &delay_topic.topic,
+        &topic.tenant,
+        cache_manager,
+        message_storage_adapter,
+        client_pool,
+    )
+    .await?;
+
+    let mut target_publish = publish.clone();
+    target_publish.topic = Bytes::from(delay_topic.topic.clone());
+    let message_expire = build_message_expire(cache_manager, publish_properties);
+    let Some(mut record) = MqttMessage::build_record(
+        client_id,
+        &target_publish,
+        publish_properties,
+        message_expire,
+    ) else {
+        return Err(MqttBrokerError::FailedToBuildMessage);
+    };
+    record.delay_timestamp = now_second() + delay_topic.delay_timestamp;
+
+    let id = delay_message_manager
+        .send_delay_message(
+            &target_topic.topic_id,
+            &delay_topic.topic,
+            client_id,
+            record,
+        )
+        .await?;
+    Ok(id)
+}
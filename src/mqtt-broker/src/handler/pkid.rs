@@ -14,21 +14,53 @@
 
 use std::sync::Arc;
 
-use common_base::config::broker_mqtt::broker_mqtt_conf;
-use grpc_clients::placement::inner::call::{
-    delete_idempotent_data, exists_idempotent_data, set_idempotent_data,
-};
 use grpc_clients::pool::ClientPool;
-use protocol::placement_center::placement_center_inner::{
-    DeleteIdempotentDataRequest, ExistsIdempotentDataRequest, SetIdempotentDataRequest,
-};
+use idempotent_message::{IdempotentManager, IdempotentStatus};
+use log::warn;
+use protocol::mqtt::common::PublishProperties;
+use storage_adapter::placement::PlacementStorageAdapter;
+use storage_adapter::storage::StorageAdapter;
+use storage_adapter::StorageType;
 
 use super::cache::CacheManager;
 use super::error::MqttBrokerError;
 
+// A publisher that sets this user property gets each message with the same value stored
+// only once within the ttl.
+pub const MESSAGE_ID_USER_PROPERTY: &str = "message-id";
+
+// The storage of the idempotent state. A client that reconnects to another node must still
+// find its pkids and message ids, so the state stays in the placement center when the message
+// storage is local to this node.
+pub fn idempotent_storage_adapter<S>(
+    storage_type: &StorageType,
+    message_storage_adapter: &Arc<S>,
+    client_pool: &Arc<ClientPool>,
+    placement_center_addrs: Vec<String>,
+) -> Arc<dyn StorageAdapter + Sync + Send>
+where
+    S: StorageAdapter + Sync + Send + 'static,
+{
+    if storage_type.is_shared() {
+        return message_storage_adapter.clone();
+    }
+    Arc::new(PlacementStorageAdapter::new(
+        client_pool.clone(),
+        placement_center_addrs,
+    ))
+}
+
+fn pkid_idempotent_id(pkid: u16) -> String {
+    format!("pkid-{}", pkid)
+}
+
+fn message_idempotent_id(message_id: &str) -> String {
+    format!("message-id-{}", message_id)
+}
+
 pub async fn pkid_save(
     cache_manager: &Arc<CacheManager>,
-    client_pool: &Arc<ClientPool>,
+    idempotent_manager: &Arc<IdempotentManager>,
     client_id: &str,
     pkid: u16,
 ) -> Result<(), MqttBrokerError> {
@@ -37,20 +69,9 @@ pub async fn pkid_save(
         .protocol
         .client_pkid_persistent
     {
-        let conf = broker_mqtt_conf();
-        let request = SetIdempotentDataRequest {
-            cluster_name: conf.cluster_name.clone(),
-            producer_id: client_id.to_owned(),
-            seq_num: pkid as u64,
-        };
-        match set_idempotent_data(client_pool, &conf.placement_center, request).await {
-            Ok(_) => {
-                return Ok(());
-            }
-            Err(e) => {
-                return Err(MqttBrokerError::CommonError(e.to_string()));
-            }
-        }
+        idempotent_manager
+            .save(client_id, &pkid_idempotent_id(pkid))
+            .await?;
     } else {
         cache_manager.add_client_pkid(client_id, pkid);
     }
@@ -59,7 +80,7 @@ pub async fn pkid_save(
 
 pub async fn pkid_exists(
     cache_manager: &Arc<CacheManager>,
-    client_pool: &Arc<ClientPool>,
+    idempotent_manager: &Arc<IdempotentManager>,
     client_id: &str,
     pkid: u16,
 ) -> Result<bool, MqttBrokerError> {
@@ -68,16 +89,7 @@ pub async fn pkid_exists(
         .protocol
         .client_pkid_persistent
     {
-        let conf = broker_mqtt_conf();
-        let request = ExistsIdempotentDataRequest {
-            cluster_name: conf.cluster_name.clone(),
-            producer_id: client_id.to_owned(),
-            seq_num: pkid as u64,
-        };
-        match exists_idempotent_data(client_pool, &conf.placement_center, request).await {
-            Ok(reply) => Ok(reply.exists),
-            Err(e) => Err(MqttBrokerError::CommonError(e.to_string())),
-        }
+        Ok(idempotent_manager.exists(client_id, &pkid_idempotent_id(pkid)))
     } else {
         Ok(cache_manager.get_client_pkid(client_id, pkid).is_some())
     }
@@ -85,7 +97,7 @@ pub async fn pkid_exists(
 
 pub async fn pkid_delete(
     cache_manager: &Arc<CacheManager>,
-    client_pool: &Arc<ClientPool>,
+    idempotent_manager: &Arc<IdempotentManager>,
     client_id: &str,
     pkid: u16,
 ) -> Result<(), MqttBrokerError> {
@@ -94,36 +106,157 @@ pub async fn pkid_delete(
         .protocol
         .client_pkid_persistent
     {
-        let conf = broker_mqtt_conf();
-        let request = DeleteIdempotentDataRequest {
-            cluster_name: conf.cluster_name.clone(),
-            producer_id: client_id.to_owned(),
-            seq_num: pkid as u64,
-        };
-        match delete_idempotent_data(client_pool, &conf.placement_center, request).await {
-            Ok(_) => {
-                return Ok(());
-            }
-            Err(e) => {
-                return Err(MqttBrokerError::CommonError(e.to_string()));
-            }
-        }
+        idempotent_manager
+            .delete(client_id, &pkid_idempotent_id(pkid))
+            .await?;
     } else {
         cache_manager.delete_client_pkid(client_id, pkid);
     }
     Ok(())
 }
 
+// The message id a client set in the user properties of a publish.
+pub fn publish_message_id(publish_properties: &Option<PublishProperties>) -> Option<String> {
+    publish_properties.as_ref().and_then(|properties| {
+        properties
+            .user_properties
+            .iter()
+            .find(|(key, _)| key == MESSAGE_ID_USER_PROPERTY)
+            .map(|(_, message_id)| message_id.clone())
+    })
+}
+
+// Returns true when the client has already published a message with the message id.
+// Otherwise the message id is reserved, and must be committed with commit_message_id once the
+// message is stored, or released with release_message_id when storing it fails.
+pub fn is_duplicate_message_id(
+    idempotent_manager: &Arc<IdempotentManager>,
+    client_id: &str,
+    message_id: &Option<String>,
+) -> Result<bool, MqttBrokerError> {
+    let Some(id) = message_id.as_deref().map(message_idempotent_id) else {
+        return Ok(false);
+    };
+    match idempotent_manager.reserve(client_id, &id) {
+        IdempotentStatus::New => Ok(false),
+        IdempotentStatus::Duplicate => Ok(true),
+        // acknowledging it would lose the message if storing the first one fails
+        IdempotentStatus::Pending => Err(MqttBrokerError::MessageIdIsBeingStored(id)),
+    }
+}
+
+pub async fn commit_message_id(
+    idempotent_manager: &Arc<IdempotentManager>,
+    client_id: &str,
+    message_id: &Option<String>,
+) {
+    let Some(id) = message_id.as_deref().map(message_idempotent_id) else {
+        return;
+    };
+    // The message is already stored, so it is acknowledged even when the id is not persisted.
+    // This broker still drops its duplicates, after a restart they may be stored again.
+    if let Err(e) = idempotent_manager.commit(client_id, &id).await {
+        warn!(
+            "Failed to persist the {} of client {}, error message: {}",
+            id, client_id, e
+        );
+    }
+}
+
+pub fn release_message_id(
+    idempotent_manager: &Arc<IdempotentManager>,
+    client_id: &str,
+    message_id: &Option<String>,
+) {
+    if let Some(id) = message_id.as_deref().map(message_idempotent_id) {
+        idempotent_manager.release(client_id, &id);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
 
     use common_base::config::broker_mqtt::init_broker_mqtt_conf_by_path;
     use grpc_clients::pool::ClientPool;
+    use idempotent_message::persist::start_idempotent_persist_thread;
+    use idempotent_message::IdempotentManager;
+    use protocol::mqtt::common::PublishProperties;
+    use storage_adapter::memory::MemoryStorageAdapter;
+    use storage_adapter::storage::StorageAdapter;
+    use storage_adapter::StorageType;
+    use tokio::sync::broadcast;
 
-    use super::{pkid_delete, pkid_exists, pkid_save};
+    use super::{
+        commit_message_id, idempotent_storage_adapter, is_duplicate_message_id, pkid_delete,
+        pkid_exists, pkid_save, publish_message_id, release_message_id,
+    };
     use crate::handler::cache::CacheManager;
 
+    async fn build_idempotent_manager(
+        stop_send: &broadcast::Sender<bool>,
+    ) -> Arc<IdempotentManager> {
+        let idempotent_manager = Arc::new(IdempotentManager::new(
+            "test".to_string(),
+            "1".to_string(),
+            100,
+            Arc::new(MemoryStorageAdapter::new()),
+        ));
+        idempotent_manager.init().await.unwrap();
+        start_idempotent_persist_thread(idempotent_manager.clone(), stop_send.clone());
+        idempotent_manager
+    }
+
+    #[test]
+    fn idempotent_storage_adapter_test() {
+        let client_pool = Arc::new(ClientPool::new(1));
+        let message_storage_adapter = Arc::new(MemoryStorageAdapter::new());
+        let shared: Arc<dyn StorageAdapter + Sync + Send> = message_storage_adapter.clone();
+
+        // the memory storage is local to the node, the state goes to the placement center
+        let adapter = idempotent_storage_adapter(
+            &StorageType::Memory,
+            &message_storage_adapter,
+            &client_pool,
+            vec!["127.0.0.1:1228".to_string()],
+        );
+        assert!(!Arc::ptr_eq(&adapter, &shared));
+
+        let adapter = idempotent_storage_adapter(
+            &StorageType::Journal,
+            &message_storage_adapter,
+            &client_pool,
+            vec!["127.0.0.1:1228".to_string()],
+        );
+        assert!(Arc::ptr_eq(&adapter, &shared));
+    }
+
+    #[tokio::test]
+    async fn duplicate_message_id_test() {
+        let (stop_send, _) = broadcast::channel(2);
+        let idempotent_manager = build_idempotent_manager(&stop_send).await;
+
+        assert!(!is_duplicate_message_id(&idempotent_manager, "c1", &None).unwrap());
+
+        let properties = Some(PublishProperties {
+            user_properties: vec![("message-id".to_string(), "m1".to_string())],
+            ..Default::default()
+        });
+        let message_id = publish_message_id(&properties);
+        assert_eq!(message_id, Some("m1".to_string()));
+        assert!(!is_duplicate_message_id(&idempotent_manager, "c1", &message_id).unwrap());
+        // the first message is still being stored
+        assert!(is_duplicate_message_id(&idempotent_manager, "c1", &message_id).is_err());
+
+        // storing the first message failed, the retry of the client is stored
+        release_message_id(&idempotent_manager, "c1", &message_id);
+        assert!(!is_duplicate_message_id(&idempotent_manager, "c1", &message_id).unwrap());
+        commit_message_id(&idempotent_manager, "c1", &message_id).await;
+        assert!(is_duplicate_message_id(&idempotent_manager, "c1", &message_id).unwrap());
+
+        assert!(!is_duplicate_message_id(&idempotent_manager, "c2", &message_id).unwrap());
+    }
+
     #[tokio::test]
     #[ignore]
     pub async fn pkid_test() {
@@ -136,27 +269,29 @@ pub async fn pkid_test() {
         let cluster_name = "test".to_string();
         let client_pool = Arc::new(ClientPool::new(10));
         let cache_manager = Arc::new(CacheManager::new(client_pool.clone(), cluster_name));
+        let (stop_send, _) = broadcast::channel(2);
+        let idempotent_manager = build_idempotent_manager(&stop_send).await;
         let client_id = "test".to_string();
         let pkid = 15;
-        let flag = pkid_exists(&cache_manager, &client_pool, &client_id, pkid)
+        let flag = pkid_exists(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
         assert!(!flag);
 
-        pkid_save(&cache_manager, &client_pool, &client_id, pkid)
+        pkid_save(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
 
-        let flag = pkid_exists(&cache_manager, &client_pool, &client_id, pkid)
+        let flag = pkid_exists(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
         assert!(flag);
 
-        pkid_delete(&cache_manager, &client_pool, &client_id, pkid)
+        pkid_delete(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
 
-        let flag = pkid_exists(&cache_manager, &client_pool, &client_id, pkid)
+        let flag = pkid_exists(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
         assert!(!flag);
@@ -164,25 +299,25 @@ pub async fn pkid_test() {
         cluset_info.protocol.client_pkid_persistent = true;
         cache_manager.set_cluster_info(cluset_info);
 
-        let flag = pkid_exists(&cache_manager, &client_pool, &client_id, pkid)
+        let flag = pkid_exists(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
         assert!(!flag);
 
-        pkid_save(&cache_manager, &client_pool, &client_id, pkid)
+        pkid_save(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
 
-        let flag = pkid_exists(&cache_manager, &client_pool, &client_id, pkid)
+        let flag = pkid_exists(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
         assert!(flag);
 
-        pkid_delete(&cache_manager, &client_pool, &client_id, pkid)
+        pkid_delete(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
 
-        let flag = pkid_exists(&cache_manager, &client_pool, &client_id, pkid)
+        let flag = pkid_exists(&cache_manager, &idempotent_manager, &client_id, pkid)
             .await
             .unwrap();
         assert!(!flag);
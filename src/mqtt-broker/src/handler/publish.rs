@@ -0,0 +1,389 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use delay_message::DelayMessageManager;
+use grpc_clients::pool::ClientPool;
+use idempotent_message::IdempotentManager;
+use metadata_struct::acl::mqtt_acl::MqttAclAction;
+use metadata_struct::mqtt::connection::MQTTConnection;
+use protocol::mqtt::common::{Publish, PublishProperties};
+use schema_register::schema::SchemaRegisterManager;
+use storage_adapter::storage::StorageAdapter;
+
+use super::cache::CacheManager;
+use super::error::MqttBrokerError;
+use super::offline_message::save_message;
+use super::pkid::{
+    commit_message_id, is_duplicate_message_id, publish_message_id, release_message_id,
+};
+use super::retain::save_retain_message;
+use super::tenant::{
+    check_tenant_topic, check_tenant_topic_quota, is_tenant_publish_rate_exceeded,
+};
+use super::topic::{check_modified_publish_topic, try_init_topic};
+use crate::bridge::manager::ConnectorManager;
+use crate::hook::hook_manager;
+use crate::rule_engine::RuleEngine;
+use crate::security::acl::auth::is_allow_acl;
+use crate::subscribe::subscribe_manager::SubscribeManager;
+use crate::wasm_plugin::process_wasm_plugins;
+
+// Why a message was refused, the client publish maps it to the reason code of the ack.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PublishErrorKind {
+    // The ACL denies the publish.
+    NotAuthorized,
+    // A quota of the tenant is exceeded, publishing it again later may succeed.
+    QuotaExceeded,
+    // The message does not match the schema of the topic.
+    Invalid,
+    // A hook or a WASM plugin refused the message.
+    Plugin,
+    // Storing the message failed, publishing it again later may succeed.
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct PublishError {
+    pub kind: PublishErrorKind,
+    pub error: MqttBrokerError,
+}
+
+impl PublishError {
+    pub fn new(kind: PublishErrorKind, error: MqttBrokerError) -> Self {
+        PublishError { kind, error }
+    }
+
+    // The message will never be accepted, so it should not be published again.
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self.kind,
+            PublishErrorKind::NotAuthorized | PublishErrorKind::Invalid | PublishErrorKind::Plugin
+        )
+    }
+}
+
+// A topic changed by a hook or a WASM plugin that the client may not publish to is rejected
+// like the original topic, the other failures are failures of the plugin.
+fn plugin_error(error: MqttBrokerError) -> PublishError {
+    match error {
+        MqttBrokerError::PublishNotAuthorized(_) => {
+            PublishError::new(PublishErrorKind::NotAuthorized, error)
+        }
+        _ => PublishError::new(PublishErrorKind::Plugin, error),
+    }
+}
+
+impl From<MqttBrokerError> for PublishError {
+    fn from(error: MqttBrokerError) -> Self {
+        PublishError::new(PublishErrorKind::Failed, error)
+    }
+}
+
+// The message as it was stored, the hooks and the WASM plugins may have changed its topic,
+// payload and properties.
+pub struct PublishResult {
+    pub topic_name: String,
+    pub publish: Publish,
+    pub publish_properties: Option<PublishProperties>,
+    // The offsets the message was written at, None when it is a delay message, a duplicate,
+    // dropped by a WASM plugin, or discarded because nobody subscribes to the topic.
+    pub offset: Option<String>,
+}
+
+// The publish path shared by the client publish and the messages the broker publishes on
+// behalf of someone else, e.g. source connectors. The message is checked against the ACL,
+// the hooks, the WASM plugins, the tenant quotas and the schema of the topic, stored together
+// with its retained copy, then evaluated by the rules.
+#[derive(Clone)]
+pub struct MessagePublisher<S> {
+    cache_manager: Arc<CacheManager>,
+    message_storage_adapter: Arc<S>,
+    delay_message_manager: Arc<DelayMessageManager<S>>,
+    idempotent_manager: Arc<IdempotentManager>,
+    subscribe_manager: Arc<SubscribeManager>,
+    schema_manager: Arc<SchemaRegisterManager>,
+    client_pool: Arc<ClientPool>,
+    rule_engine: Arc<RuleEngine<S>>,
+}
+
+impl<S> MessagePublisher<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cache_manager: Arc<CacheManager>,
+        message_storage_adapter: Arc<S>,
+        delay_message_manager: Arc<DelayMessageManager<S>>,
+        idempotent_manager: Arc<IdempotentManager>,
+        subscribe_manager: Arc<SubscribeManager>,
+        schema_manager: Arc<SchemaRegisterManager>,
+        client_pool: Arc<ClientPool>,
+        connector_manager: Arc<ConnectorManager>,
+    ) -> Self {
+        let rule_engine = Arc::new(RuleEngine::new(
+            cache_manager.clone(),
+            connector_manager,
+            message_storage_adapter.clone(),
+        ));
+        MessagePublisher {
+            cache_manager,
+            message_storage_adapter,
+            delay_message_manager,
+            idempotent_manager,
+            subscribe_manager,
+            schema_manager,
+            client_pool,
+            rule_engine,
+        }
+    }
+
+    // Publishes the message as the connection. The topic name must already be mounted,
+    // see mount_publish_topic.
+    pub async fn publish(
+        &self,
+        connection: &MQTTConnection,
+        topic_name: String,
+        publish: Publish,
+        publish_properties: Option<PublishProperties>,
+    ) -> Result<PublishResult, PublishError> {
+        let message_id = publish_message_id(&publish_properties);
+        self.publish_message(
+            connection,
+            topic_name,
+            publish,
+            publish_properties,
+            message_id,
+            true,
+        )
+        .await
+    }
+
+    // Publishes a message deduplicated by a message id of the broker, which is not sent to the
+    // subscribers like the message id of the user properties.
+    pub async fn publish_with_message_id(
+        &self,
+        connection: &MQTTConnection,
+        topic_name: String,
+        publish: Publish,
+        publish_properties: Option<PublishProperties>,
+        message_id: String,
+    ) -> Result<PublishResult, PublishError> {
+        self.publish_message(
+            connection,
+            topic_name,
+            publish,
+            publish_properties,
+            Some(message_id),
+            true,
+        )
+        .await
+    }
+
+    // Publishes a message of a rule action, it does not run the rules again so that a rule
+    // cannot trigger itself.
+    pub async fn publish_without_rules(
+        &self,
+        connection: &MQTTConnection,
+        topic_name: String,
+        publish: Publish,
+        publish_properties: Option<PublishProperties>,
+    ) -> Result<PublishResult, PublishError> {
+        let message_id = publish_message_id(&publish_properties);
+        self.publish_message(
+            connection,
+            topic_name,
+            publish,
+            publish_properties,
+            message_id,
+            false,
+        )
+        .await
+    }
+
+    async fn publish_message(
+        &self,
+        connection: &MQTTConnection,
+        topic_name: String,
+        publish: Publish,
+        publish_properties: Option<PublishProperties>,
+        message_id: Option<String>,
+        run_rules: bool,
+    ) -> Result<PublishResult, PublishError> {
+        if !is_allow_acl(
+            &self.cache_manager,
+            connection,
+            &topic_name,
+            MqttAclAction::Publish,
+            publish.retain,
+            publish.qos,
+        ) {
+            return Err(PublishError::new(
+                PublishErrorKind::NotAuthorized,
+                MqttBrokerError::PublishNotAuthorized(topic_name),
+            ));
+        }
+
+        // A message dropped by a hook or a plugin is filtered on purpose, it counts as
+        // published without being stored so that the publisher does not send it again.
+        let hook_result = hook_manager()
+            .on_message_publish(
+                connection,
+                topic_name.clone(),
+                publish.clone(),
+                &publish_properties,
+            )
+            .await
+            .map_err(plugin_error)?;
+        let Some((hook_topic_name, publish)) = hook_result else {
+            return Ok(PublishResult {
+                topic_name,
+                publish,
+                publish_properties,
+                offset: None,
+            });
+        };
+        if hook_topic_name != topic_name {
+            check_modified_publish_topic(
+                &self.cache_manager,
+                connection,
+                &hook_topic_name,
+                &publish,
+            )
+            .map_err(plugin_error)?;
+        }
+        let topic_name = hook_topic_name;
+
+        let wasm_result = process_wasm_plugins(
+            &self.cache_manager,
+            connection,
+            topic_name.clone(),
+            publish.clone(),
+            publish_properties.clone(),
+        )
+        .await
+        .map_err(plugin_error)?;
+        let Some((topic_name, publish, publish_properties)) = wasm_result else {
+            return Ok(PublishResult {
+                topic_name,
+                publish,
+                publish_properties,
+                offset: None,
+            });
+        };
+
+        check_tenant_topic(&self.cache_manager, &connection.tenant, &topic_name)
+            .map_err(|e| PublishError::new(PublishErrorKind::NotAuthorized, e))?;
+
+        // tenant quota check
+        if self.cache_manager.get_topic_by_name(&topic_name).is_none() {
+            check_tenant_topic_quota(&self.cache_manager, &connection.tenant)
+                .map_err(|e| PublishError::new(PublishErrorKind::QuotaExceeded, e))?;
+        }
+        if is_tenant_publish_rate_exceeded(&self.cache_manager, &connection.tenant) {
+            return Err(PublishError::new(
+                PublishErrorKind::QuotaExceeded,
+                MqttBrokerError::TenantPublishRateExceeded(connection.tenant.clone()),
+            ));
+        }
+
+        let topic = try_init_topic(
+            &topic_name,
+            &connection.tenant,
+            &self.cache_manager,
+            &self.message_storage_adapter,
+            &self.client_pool,
+        )
+        .await?;
+
+        if self.schema_manager.is_check_schema(&topic_name) {
+            match self.schema_manager.validate(&topic_name, &publish.payload) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Err(PublishError::new(
+                        PublishErrorKind::Invalid,
+                        MqttBrokerError::PayloadNotMatchSchema(topic_name),
+                    ))
+                }
+                Err(e) => return Err(PublishError::new(PublishErrorKind::Invalid, e.into())),
+            }
+        }
+
+        let client_id = &connection.client_id;
+
+        // A message that repeats a message id of the client is not stored again. The id is
+        // only recorded once the message is stored.
+        if is_duplicate_message_id(&self.idempotent_manager, client_id, &message_id)? {
+            return Ok(PublishResult {
+                topic_name,
+                publish,
+                publish_properties,
+                offset: None,
+            });
+        }
+
+        if let Err(e) = save_retain_message(
+            &self.cache_manager,
+            &self.client_pool,
+            topic_name.clone(),
+            client_id,
+            &publish,
+            &publish_properties,
+        )
+        .await
+        {
+            release_message_id(&self.idempotent_manager, client_id, &message_id);
+            return Err(e.into());
+        }
+
+        let offset = match save_message(
+            &self.message_storage_adapter,
+            &self.delay_message_manager,
+            &self.cache_manager,
+            &self.client_pool,
+            &publish,
+            &publish_properties,
+            &self.subscribe_manager,
+            client_id,
+            &topic,
+        )
+        .await
+        {
+            Ok(offset) => offset,
+            Err(e) => {
+                release_message_id(&self.idempotent_manager, client_id, &message_id);
+                return Err(e.into());
+            }
+        };
+
+        commit_message_id(&self.idempotent_manager, client_id, &message_id).await;
+
+        if run_rules {
+            let rule_matches =
+                self.rule_engine
+                    .evaluate(connection, &topic_name, &publish, &publish_properties);
+            self.rule_engine.run_actions(self.clone(), rule_matches);
+        }
+
+        Ok(PublishResult {
+            topic_name,
+            publish,
+            publish_properties,
+            offset,
+        })
+    }
+}
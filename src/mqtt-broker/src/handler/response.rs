@@ -126,6 +126,33 @@ pub fn response_packet_mqtt_connect_fail(
     )
 }
 
+pub fn response_packet_mqtt_connect_redirect(
+    protocol: &MqttProtocol,
+    code: ConnectReturnCode,
+    server_reference: Option<String>,
+) -> MqttPacket {
+    if !protocol.is_mqtt5() {
+        return MqttPacket::ConnAck(
+            ConnAck {
+                session_present: false,
+                code: ConnectReturnCode::ServiceUnavailable,
+            },
+            None,
+        );
+    }
+    let properties = ConnAckProperties {
+        server_reference,
+        ..Default::default()
+    };
+    MqttPacket::ConnAck(
+        ConnAck {
+            session_present: false,
+            code,
+        },
+        Some(properties),
+    )
+}
+
 pub fn response_packet_mqtt_distinct(
     protocol: &MqttProtocol,
     code: Option<DisconnectReasonCode>,
@@ -157,6 +184,27 @@ pub fn response_packet_mqtt_distinct_by_reason(
     )
 }
 
+pub fn response_packet_mqtt_distinct_by_server_reference(
+    protocol: &MqttProtocol,
+    code: DisconnectReasonCode,
+    server_reference: Option<String>,
+) -> MqttPacket {
+    if !protocol.is_mqtt5() {
+        return MqttPacket::Disconnect(Disconnect { reason_code: None }, None);
+    }
+
+    let properties = DisconnectProperties {
+        server_reference,
+        ..Default::default()
+    };
+    MqttPacket::Disconnect(
+        Disconnect {
+            reason_code: Some(code),
+        },
+        Some(properties),
+    )
+}
+
 pub fn response_packet_mqtt_puback_success(
     protocol: &MqttProtocol,
     reason: PubAckReason,
@@ -15,18 +15,16 @@
 use std::sync::Arc;
 
 use bytes::Bytes;
-use common_base::tools::now_second;
 use dashmap::DashMap;
 use grpc_clients::pool::ClientPool;
-use log::info;
+use log::{error, info};
 use metadata_struct::mqtt::message::MqttMessage;
 use protocol::mqtt::common::{
-    MqttProtocol, Publish, PublishProperties, QoS, RetainForwardRule, Subscribe,
-    SubscribeProperties,
+    MqttProtocol, Publish, PublishProperties, RetainForwardRule, Subscribe, SubscribeProperties,
 };
 use tokio::sync::broadcast::{self};
 
-use super::cache::{CacheManager, QosAckPacketInfo};
+use super::cache::CacheManager;
 use super::constant::{SUB_RETAIN_MESSAGE_PUSH_FLAG, SUB_RETAIN_MESSAGE_PUSH_FLAG_VALUE};
 use super::error::MqttBrokerError;
 use super::message::build_message_expire;
@@ -35,10 +33,8 @@
 };
 use crate::server::connection_manager::ConnectionManager;
 use crate::storage::topic::TopicStorage;
-use crate::subscribe::exclusive_push::{
-    exclusive_publish_message_qos1, exclusive_publish_message_qos2,
-};
-use crate::subscribe::sub_common::{get_pkid, get_sub_topic_id_list, min_qos, publish_message_qos};
+use crate::subscribe::exclusive_push::exclusive_publish_message;
+use crate::subscribe::sub_common::{get_pkid, get_sub_topic_id_list, min_qos};
 use crate::subscribe::subscribe_manager::SubscribeManager;
 use crate::subscribe::subscriber::SubPublishParam;
 use crate::subscribe::subscriber::Subscriber;
@@ -227,58 +223,19 @@ async fn send_retain_message(
                 pkid,
             );
 
-            match qos {
-                QoS::AtMostOnce => {
-                    publish_message_qos(cache_manager, connection_manager, &sub_pub_param, stop_sx)
-                        .await;
-                }
-
-                QoS::AtLeastOnce => {
-                    let (wait_puback_sx, _) = broadcast::channel(1);
-                    cache_manager.add_ack_packet(
-                        client_id,
-                        pkid,
-                        QosAckPacketInfo {
-                            sx: wait_puback_sx.clone(),
-                            create_time: now_second(),
-                        },
-                    );
-
-                    exclusive_publish_message_qos1(
-                        cache_manager,
-                        connection_manager,
-                        &sub_pub_param,
-                        stop_sx,
-                        &wait_puback_sx,
-                    )
-                    .await;
-
-                    cache_manager.remove_ack_packet(client_id, pkid);
-                }
-
-                QoS::ExactlyOnce => {
-                    let (wait_ack_sx, _) = broadcast::channel(1);
-                    cache_manager.add_ack_packet(
-                        client_id,
-                        pkid,
-                        QosAckPacketInfo {
-                            sx: wait_ack_sx.clone(),
-                            create_time: now_second(),
-                        },
-                    );
-
-                    exclusive_publish_message_qos2(
-                        cache_manager,
-                        connection_manager,
-                        &sub_pub_param,
-                        stop_sx,
-                        &wait_ack_sx,
-                    )
-                    .await;
-
-                    cache_manager.remove_ack_packet(client_id, pkid);
-                }
-            };
+            if let Err(e) = exclusive_publish_message(
+                cache_manager,
+                connection_manager,
+                &sub_pub_param,
+                stop_sx,
+            )
+            .await
+            {
+                error!(
+                    "Failed to send retain message to client {}, error: {}",
+                    client_id, e
+                );
+            }
 
             record_retain_sent_metrics(qos);
             info!(
@@ -0,0 +1,76 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+use protocol::mqtt::common::{qos, Publish};
+use storage_adapter::storage::StorageAdapter;
+
+use super::cache::CacheManager;
+use super::error::MqttBrokerError;
+use super::publish::MessagePublisher;
+use super::topic::mount_publish_topic;
+use crate::subscribe::sub_common::get_pkid;
+
+// The scheduled messages are published with the client id "$scheduler/<name>".
+pub fn scheduled_message_client_id(name: &str) -> String {
+    format!("$scheduler/{}", name)
+}
+
+// Called when the scheduler of the placement center fires a tick of the scheduled message.
+// The scheduler sends a tick until it is acknowledged, the time of the tick is the message id
+// of the publish so that a tick sent more than once is only stored once. The message id is not
+// added to the user properties, so the subscribers do not get it.
+pub async fn publish_scheduled_message<S>(
+    cache_manager: &Arc<CacheManager>,
+    message_publisher: &MessagePublisher<S>,
+    message: &MqttScheduledMessage,
+) -> Result<(), MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let publish = Publish {
+        dup: false,
+        qos: qos(message.qos).ok_or_else(|| {
+            MqttBrokerError::ScheduledMessageError(
+                message.name.clone(),
+                format!("invalid qos {}", message.qos),
+            )
+        })?,
+        pkid: get_pkid(),
+        retain: message.retain,
+        topic: Bytes::from(message.topic.clone()),
+        payload: Bytes::from(message.payload.clone()),
+    };
+    let connection = MQTTConnection {
+        client_id: scheduled_message_client_id(&message.name),
+        is_login: true,
+        ..Default::default()
+    };
+    let topic_name = mount_publish_topic(cache_manager, "", message.topic.clone())?;
+    message_publisher
+        .publish_with_message_id(
+            &connection,
+            topic_name,
+            publish,
+            None,
+            message.last_fire_time.to_string(),
+        )
+        .await
+        .map_err(|e| e.error)?;
+    Ok(())
+}
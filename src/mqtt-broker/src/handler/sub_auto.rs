@@ -20,7 +20,13 @@
 
 use crate::subscribe::subscribe_manager::SubscribeManager;
 
-use super::{cache::CacheManager, error::MqttBrokerError, subscribe::save_subscribe};
+use super::{
+    cache::CacheManager,
+    error::MqttBrokerError,
+    mountpoint::mount_sub_path,
+    subscribe::save_subscribe,
+    tenant::{login_tenant, tenant_mountpoint},
+};
 
 pub async fn start_auto_subscribe(
     client_id: String,
@@ -30,9 +36,12 @@ pub async fn start_auto_subscribe(
     cache_manager: &Arc<CacheManager>,
     subscribe_manager: &Arc<SubscribeManager>,
 ) -> Result<(), MqttBrokerError> {
+    // The rules of a tenant only apply to the clients of that tenant
+    let tenant = login_tenant(cache_manager, login);
     let auto_subscribe_rules: Vec<MqttAutoSubscribeRule> = cache_manager
         .auto_subscribe_rule
         .iter()
+        .filter(|entry| entry.value().tenant.is_empty() || entry.value().tenant == tenant)
         .map(|entry| entry.value().clone())
         .collect();
 
@@ -50,8 +59,9 @@ pub async fn start_auto_subscribe(
             path = path.replace("${username}", &username);
         }
 
+        // the clients of a tenant only subscribe to the topics of the tenant
         filters.push(Filter {
-            path,
+            path: mount_sub_path(&tenant_mountpoint(&tenant), &path),
             qos: auto_subscribe_rule.qos,
             nolocal: auto_subscribe_rule.no_local,
             preserve_retain: auto_subscribe_rule.retain_as_published,
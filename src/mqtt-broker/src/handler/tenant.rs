@@ -0,0 +1,563 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_base::config::broker_mqtt::broker_mqtt_conf;
+use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
+use log::{debug, error};
+use metadata_struct::mqtt::tenant::MqttTenantUsage;
+use protocol::mqtt::common::Login;
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+
+use super::cache::CacheManager;
+use super::error::MqttBrokerError;
+use crate::observability::metrics::tenant::{
+    get_tenant_publish_message_num, metrics_tenant_publish_message_inc,
+    metrics_tenant_quota_exceeded_inc,
+};
+use crate::storage::tenant::TenantStorage;
+use crate::subscribe::sub_common::{
+    decode_queue_info, decode_share_info, is_queue_sub, is_share_sub,
+};
+
+pub const TENANT_QUOTA_CONNECTION: &str = "connection";
+pub const TENANT_QUOTA_TOPIC: &str = "topic";
+pub const TENANT_QUOTA_PUBLISH_RATE: &str = "publish_rate";
+
+// The topics of a tenant are mounted under `$tenant/<tenant>/`
+pub const TENANT_TOPIC_PREFIX: &str = "$tenant/";
+
+const TENANT_USAGE_REPORT_INTERVAL_SECS: u64 = 3;
+const TENANT_USAGE_EXPIRE_SECS: u64 = TENANT_USAGE_REPORT_INTERVAL_SECS * 3;
+
+pub fn login_tenant(cache_manager: &Arc<CacheManager>, login: &Option<Login>) -> String {
+    login
+        .as_ref()
+        .and_then(|login| cache_manager.user_info.get(&login.username))
+        .map(|user| user.tenant.clone())
+        .unwrap_or_default()
+}
+
+// An empty tenant in an admin request is the cluster administrator, who manages the resources of
+// every tenant.
+pub fn tenant_scope_match(scope: &str, tenant_name: &str) -> bool {
+    scope.is_empty() || scope == tenant_name
+}
+
+pub fn check_tenant_exists(
+    cache_manager: &Arc<CacheManager>,
+    tenant_name: &str,
+) -> Result<(), MqttBrokerError> {
+    if tenant_name.is_empty() || cache_manager.get_tenant(tenant_name).is_some() {
+        return Ok(());
+    }
+    Err(MqttBrokerError::TenantNotExists(tenant_name.to_string()))
+}
+
+pub fn check_tenant_owner(
+    scope: &str,
+    tenant_name: &str,
+    resource: &str,
+) -> Result<(), MqttBrokerError> {
+    if tenant_scope_match(scope, tenant_name) {
+        return Ok(());
+    }
+    Err(MqttBrokerError::TenantResourceNotOwned(
+        resource.to_string(),
+        scope.to_string(),
+    ))
+}
+
+// A tenant name is a level of the topics of the tenant
+pub fn tenant_name_validator(tenant_name: &str) -> Result<(), MqttBrokerError> {
+    if !tenant_name.is_empty()
+        && tenant_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Ok(());
+    }
+    Err(MqttBrokerError::TenantNameInvalid(tenant_name.to_string()))
+}
+
+pub fn tenant_mountpoint(tenant_name: &str) -> String {
+    if tenant_name.is_empty() {
+        return String::new();
+    }
+    format!("{}{}/", TENANT_TOPIC_PREFIX, tenant_name)
+}
+
+// Splits a topic name or a topic filter into its tenant and the topic inside the tenant, the
+// tenant is empty for a topic of the default tenant. The topic filter of a shared subscription
+// starts with `/` once its group is removed.
+pub fn split_tenant_topic(topic_name: &str) -> (&str, &str) {
+    topic_name
+        .trim_start_matches('/')
+        .strip_prefix(TENANT_TOPIC_PREFIX)
+        .and_then(|topic| topic.split_once('/'))
+        .unwrap_or(("", topic_name))
+}
+
+// A client can only publish to and subscribe to the topics of its own tenant. The topics of a
+// tenant client are always mounted under its tenant, this also rejects the clients of the default
+// tenant that name the topics of a tenant, and the topics a tenant created before it was mounted.
+pub fn check_tenant_topic(
+    cache_manager: &Arc<CacheManager>,
+    tenant_name: &str,
+    topic_name: &str,
+) -> Result<(), MqttBrokerError> {
+    let (topic_tenant, _) = split_tenant_topic(topic_name);
+    let owner = cache_manager
+        .get_topic_by_name(topic_name)
+        .map(|topic| topic.tenant)
+        .unwrap_or_else(|| topic_tenant.to_string());
+    if topic_tenant == tenant_name && owner == tenant_name {
+        return Ok(());
+    }
+    Err(MqttBrokerError::TenantTopicNotOwned(
+        topic_name.to_string(),
+        tenant_name.to_string(),
+    ))
+}
+
+// Shared and queue subscriptions are checked with their topic filter
+pub fn check_tenant_sub_path(
+    cache_manager: &Arc<CacheManager>,
+    tenant_name: &str,
+    sub_path: &str,
+) -> Result<(), MqttBrokerError> {
+    let topic_filter = if is_share_sub(sub_path) {
+        decode_share_info(sub_path).1
+    } else if is_queue_sub(sub_path) {
+        decode_queue_info(sub_path)
+    } else {
+        return check_tenant_topic(cache_manager, tenant_name, sub_path);
+    };
+    check_tenant_topic(
+        cache_manager,
+        tenant_name,
+        topic_filter.strip_prefix('/').unwrap_or(&topic_filter),
+    )
+}
+
+fn tenant_local_connection_num(cache_manager: &Arc<CacheManager>, tenant_name: &str) -> u64 {
+    cache_manager
+        .connection_info
+        .iter()
+        .filter(|conn| conn.tenant == tenant_name)
+        .count() as u64
+}
+
+// The usage reported by the other broker nodes, the usage of a node that stopped reporting is
+// ignored so the quota of a failed node is released.
+fn tenant_node_usage<F>(cache_manager: &Arc<CacheManager>, usage: F) -> u64
+where
+    F: Fn(&MqttTenantUsage) -> u64,
+{
+    let expire_time = now_second().saturating_sub(TENANT_USAGE_EXPIRE_SECS);
+    cache_manager
+        .tenant_node_usage
+        .iter()
+        .filter(|entry| entry.value().report_time >= expire_time)
+        .map(|entry| usage(entry.value()))
+        .sum()
+}
+
+// The connections of the tenant on the whole cluster, the connections of the other nodes are the
+// ones they reported in the last interval.
+pub fn tenant_connection_num(cache_manager: &Arc<CacheManager>, tenant_name: &str) -> u64 {
+    tenant_local_connection_num(cache_manager, tenant_name)
+        + tenant_node_usage(cache_manager, |usage| {
+            usage
+                .connection_num
+                .get(tenant_name)
+                .copied()
+                .unwrap_or_default()
+        })
+}
+
+// The topics are cached on every node, so max_topics is a limit of the cluster.
+pub fn tenant_topic_num(cache_manager: &Arc<CacheManager>, tenant_name: &str) -> u64 {
+    cache_manager
+        .topic_info
+        .iter()
+        .filter(|topic| topic.tenant == tenant_name)
+        .count() as u64
+}
+
+pub fn check_tenant_connection_quota(
+    cache_manager: &Arc<CacheManager>,
+    tenant_name: &str,
+) -> Result<(), MqttBrokerError> {
+    if tenant_name.is_empty() {
+        return Ok(());
+    }
+
+    let Some(tenant) = cache_manager.get_tenant(tenant_name) else {
+        return Err(MqttBrokerError::TenantNotExists(tenant_name.to_string()));
+    };
+
+    if tenant.max_connections > 0
+        && tenant_connection_num(cache_manager, tenant_name) >= tenant.max_connections
+    {
+        metrics_tenant_quota_exceeded_inc(tenant_name, TENANT_QUOTA_CONNECTION);
+        return Err(MqttBrokerError::TenantConnectionQuotaExceeded(
+            tenant_name.to_string(),
+            tenant.max_connections,
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_tenant_topic_quota(
+    cache_manager: &Arc<CacheManager>,
+    tenant_name: &str,
+) -> Result<(), MqttBrokerError> {
+    if tenant_name.is_empty() {
+        return Ok(());
+    }
+
+    let Some(tenant) = cache_manager.get_tenant(tenant_name) else {
+        return Err(MqttBrokerError::TenantNotExists(tenant_name.to_string()));
+    };
+
+    if tenant.max_topics > 0 && tenant_topic_num(cache_manager, tenant_name) >= tenant.max_topics {
+        metrics_tenant_quota_exceeded_inc(tenant_name, TENANT_QUOTA_TOPIC);
+        return Err(MqttBrokerError::TenantTopicQuotaExceeded(
+            tenant_name.to_string(),
+            tenant.max_topics,
+        ));
+    }
+    Ok(())
+}
+
+// Counts the message into the publish rate of the current second, the rate of the other nodes is
+// the average rate they reported in the last interval.
+pub fn is_tenant_publish_rate_exceeded(
+    cache_manager: &Arc<CacheManager>,
+    tenant_name: &str,
+) -> bool {
+    if tenant_name.is_empty() {
+        return false;
+    }
+
+    let max_publish_rate = cache_manager
+        .get_tenant(tenant_name)
+        .map(|tenant| tenant.max_publish_rate)
+        .unwrap_or_default();
+
+    let now = now_second();
+    let mut rate = cache_manager
+        .tenant_publish_rate
+        .entry(tenant_name.to_string())
+        .or_insert((now, 0));
+    if rate.0 != now {
+        *rate = (now, 0);
+    }
+
+    let node_rate = tenant_node_usage(cache_manager, |usage| {
+        usage
+            .publish_rate
+            .get(tenant_name)
+            .copied()
+            .unwrap_or_default()
+    });
+    if max_publish_rate > 0 && rate.1 + node_rate >= max_publish_rate {
+        metrics_tenant_quota_exceeded_inc(tenant_name, TENANT_QUOTA_PUBLISH_RATE);
+        return true;
+    }
+
+    rate.1 += 1;
+    metrics_tenant_publish_message_inc(tenant_name);
+    false
+}
+
+pub async fn report_tenant_usage(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    // (tenant_name, message num) at the last report, to compute the publish rate of the interval
+    let mut last_publish_num = HashMap::new();
+    loop {
+        let mut stop_recv = stop_send.subscribe();
+        select! {
+            val = stop_recv.recv() =>{
+                if let Ok(flag) = val {
+                    if flag {
+                        debug!("{}","Tenant usage reporting thread exited successfully");
+                        break;
+                    }
+                }
+            }
+            res = sync_tenant_usage(cache_manager, client_pool, &mut last_publish_num) => {
+                if let Err(e) = res {
+                    error!("Failed to sync the tenant usage of the cluster, error: {}", e);
+                }
+                sleep(Duration::from_secs(TENANT_USAGE_REPORT_INTERVAL_SECS)).await;
+            }
+        }
+    }
+}
+
+async fn sync_tenant_usage(
+    cache_manager: &Arc<CacheManager>,
+    client_pool: &Arc<ClientPool>,
+    last_publish_num: &mut HashMap<String, u64>,
+) -> Result<(), MqttBrokerError> {
+    let broker_id = broker_mqtt_conf().broker_id;
+    let usage = local_tenant_usage(cache_manager, broker_id, last_publish_num);
+
+    let tenant_storage = TenantStorage::new(client_pool.clone());
+    tenant_storage.report_usage(&usage).await?;
+
+    let node_usage = tenant_storage.list_usage().await?;
+    cache_manager
+        .tenant_node_usage
+        .retain(|id, _| node_usage.iter().any(|usage| usage.broker_id == *id));
+    for usage in node_usage {
+        if usage.broker_id != broker_id {
+            cache_manager
+                .tenant_node_usage
+                .insert(usage.broker_id, usage);
+        }
+    }
+    Ok(())
+}
+
+fn local_tenant_usage(
+    cache_manager: &Arc<CacheManager>,
+    broker_id: u64,
+    last_publish_num: &mut HashMap<String, u64>,
+) -> MqttTenantUsage {
+    let mut usage = MqttTenantUsage {
+        broker_id,
+        report_time: now_second(),
+        ..Default::default()
+    };
+    for tenant in cache_manager.tenant_info.iter() {
+        let tenant_name = tenant.key();
+        usage.connection_num.insert(
+            tenant_name.clone(),
+            tenant_local_connection_num(cache_manager, tenant_name),
+        );
+
+        let publish_num = get_tenant_publish_message_num(tenant_name);
+        let last_num = last_publish_num
+            .insert(tenant_name.clone(), publish_num)
+            .unwrap_or(publish_num);
+        usage.publish_rate.insert(
+            tenant_name.clone(),
+            publish_num.saturating_sub(last_num) / TENANT_USAGE_REPORT_INTERVAL_SECS,
+        );
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use common_base::tools::{now_second, unique_id};
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::mqtt::connection::MQTTConnection;
+    use metadata_struct::mqtt::session::MqttSession;
+    use metadata_struct::mqtt::tenant::{MqttTenant, MqttTenantUsage};
+    use metadata_struct::mqtt::topic::MqttTopic;
+    use metadata_struct::mqtt::user::MqttUser;
+    use protocol::mqtt::common::Login;
+
+    use super::{
+        check_tenant_connection_quota, check_tenant_owner, check_tenant_sub_path,
+        check_tenant_topic, check_tenant_topic_quota, is_tenant_publish_rate_exceeded,
+        local_tenant_usage, login_tenant, tenant_connection_num,
+    };
+    use crate::handler::cache::CacheManager;
+    use crate::handler::mountpoint::{connection_mountpoint, mount_sub_path, mount_topic};
+    use crate::observability::metrics::tenant::get_tenant_publish_message_num;
+    use crate::subscribe::sub_common::path_regex_match;
+
+    fn build_cache_manager() -> Arc<CacheManager> {
+        let client_pool = Arc::new(ClientPool::new(1));
+        let cache_manager = Arc::new(CacheManager::new(client_pool, "test".to_string()));
+        cache_manager.add_tenant(MqttTenant {
+            tenant_name: "tenant-a".to_string(),
+            max_connections: 1,
+            max_topics: 1,
+            max_publish_rate: 2,
+            ..Default::default()
+        });
+        cache_manager
+    }
+
+    #[test]
+    fn tenant_connection_quota_test() {
+        let cache_manager = build_cache_manager();
+        assert!(check_tenant_connection_quota(&cache_manager, "").is_ok());
+        assert!(check_tenant_connection_quota(&cache_manager, "tenant-b").is_err());
+        assert!(check_tenant_connection_quota(&cache_manager, "tenant-a").is_ok());
+
+        let client_id = unique_id();
+        cache_manager.add_session(
+            client_id.clone(),
+            MqttSession {
+                client_id: client_id.clone(),
+                ..Default::default()
+            },
+        );
+        cache_manager.add_connection(
+            1,
+            MQTTConnection {
+                client_id,
+                tenant: "tenant-a".to_string(),
+                ..Default::default()
+            },
+        );
+        assert!(check_tenant_connection_quota(&cache_manager, "tenant-a").is_err());
+
+        cache_manager.remove_connection(1);
+        assert!(check_tenant_connection_quota(&cache_manager, "tenant-a").is_ok());
+    }
+
+    #[test]
+    fn tenant_topic_quota_test() {
+        let cache_manager = build_cache_manager();
+        assert!(check_tenant_topic_quota(&cache_manager, "tenant-a").is_ok());
+
+        let mut topic = MqttTopic::new(unique_id(), "test".to_string(), "a/b".to_string());
+        topic.tenant = "tenant-a".to_string();
+        cache_manager.add_topic(&topic.topic_name, &topic);
+        assert!(check_tenant_topic_quota(&cache_manager, "tenant-a").is_err());
+    }
+
+    #[test]
+    fn tenant_publish_rate_test() {
+        let cache_manager = build_cache_manager();
+        assert!(!is_tenant_publish_rate_exceeded(&cache_manager, ""));
+        assert!(!is_tenant_publish_rate_exceeded(&cache_manager, "tenant-a"));
+        assert!(!is_tenant_publish_rate_exceeded(&cache_manager, "tenant-a"));
+        assert!(is_tenant_publish_rate_exceeded(&cache_manager, "tenant-a"));
+        assert!(get_tenant_publish_message_num("tenant-a") >= 2);
+    }
+
+    #[test]
+    fn tenant_cluster_usage_test() {
+        let cache_manager = build_cache_manager();
+        let mut usage = MqttTenantUsage {
+            broker_id: 2,
+            report_time: now_second(),
+            ..Default::default()
+        };
+        usage.connection_num.insert("tenant-a".to_string(), 1);
+        usage.publish_rate.insert("tenant-a".to_string(), 2);
+        cache_manager.tenant_node_usage.insert(2, usage.clone());
+
+        // The connections and messages of the other nodes count into the quotas
+        assert_eq!(tenant_connection_num(&cache_manager, "tenant-a"), 1);
+        assert!(check_tenant_connection_quota(&cache_manager, "tenant-a").is_err());
+        assert!(is_tenant_publish_rate_exceeded(&cache_manager, "tenant-a"));
+
+        // The usage of a node that stopped reporting is ignored
+        usage.report_time = 0;
+        cache_manager.tenant_node_usage.insert(2, usage);
+        assert_eq!(tenant_connection_num(&cache_manager, "tenant-a"), 0);
+        assert!(check_tenant_connection_quota(&cache_manager, "tenant-a").is_ok());
+
+        let mut last_publish_num = HashMap::new();
+        let local_usage = local_tenant_usage(&cache_manager, 1, &mut last_publish_num);
+        assert_eq!(local_usage.broker_id, 1);
+        assert_eq!(local_usage.connection_num.get("tenant-a"), Some(&0));
+        assert_eq!(local_usage.publish_rate.get("tenant-a"), Some(&0));
+    }
+
+    #[test]
+    fn tenant_owner_test() {
+        assert!(check_tenant_owner("", "tenant-a", "Schema s1").is_ok());
+        assert!(check_tenant_owner("tenant-a", "tenant-a", "Schema s1").is_ok());
+        assert!(check_tenant_owner("tenant-b", "tenant-a", "Schema s1").is_err());
+        assert!(check_tenant_owner("tenant-b", "", "Schema s1").is_err());
+    }
+
+    #[test]
+    fn tenant_isolation_test() {
+        let cache_manager = build_cache_manager();
+        for tenant in ["tenant_a", "tenant_b"] {
+            cache_manager.add_user(MqttUser {
+                username: "admin".to_string(),
+                password: "pwd".to_string(),
+                is_superuser: true,
+                mountpoint: "".to_string(),
+                tenant: tenant.to_string(),
+            });
+        }
+        // the two tenants each have their own user admin
+        assert_eq!(cache_manager.user_info.len(), 2);
+
+        let login = |username: &str| {
+            Some(Login {
+                username: username.to_string(),
+                password: "pwd".to_string(),
+            })
+        };
+        let login_a = login("tenant_a:admin");
+        let login_b = login("tenant_b:admin");
+        assert_eq!(login_tenant(&cache_manager, &login_a), "tenant_a");
+        assert_eq!(login_tenant(&cache_manager, &login_b), "tenant_b");
+        assert_eq!(login_tenant(&cache_manager, &login("admin")), "");
+
+        // the same topic name is a different topic in each tenant
+        let mountpoint_a = connection_mountpoint(&cache_manager, &None, "c1", &login_a).unwrap();
+        let mountpoint_b = connection_mountpoint(&cache_manager, &None, "c1", &login_b).unwrap();
+        assert_eq!(mountpoint_a, "$tenant/tenant_a/");
+        let topic_a = mount_topic(&mountpoint_a, "sensor/1");
+        let topic_b = mount_topic(&mountpoint_b, "sensor/1");
+        assert_ne!(topic_a, topic_b);
+
+        let mut topic = MqttTopic::new(unique_id(), "test".to_string(), topic_a.clone());
+        topic.tenant = "tenant_a".to_string();
+        cache_manager.add_topic(&topic_a, &topic);
+
+        // a client can only publish to the topics of its tenant
+        assert!(check_tenant_topic(&cache_manager, "tenant_a", &topic_a).is_ok());
+        assert!(check_tenant_topic(&cache_manager, "tenant_b", &topic_b).is_ok());
+        assert!(check_tenant_topic(&cache_manager, "tenant_b", &topic_a).is_err());
+        assert!(check_tenant_topic(&cache_manager, "", &topic_a).is_err());
+
+        // and only subscribe to them
+        let sub_b = mount_sub_path(&mountpoint_b, "sensor/1");
+        assert!(check_tenant_sub_path(&cache_manager, "tenant_b", &sub_b).is_ok());
+        assert!(!path_regex_match(&topic_a, &sub_b));
+        assert!(path_regex_match(
+            &topic_a,
+            &mount_sub_path(&mountpoint_a, "sensor/1")
+        ));
+        let share_a = format!("$share/g1/{}", topic_a);
+        assert!(check_tenant_sub_path(&cache_manager, "tenant_b", &share_a).is_err());
+        assert!(check_tenant_sub_path(&cache_manager, "tenant_b", "$tenant/+/sensor/1").is_err());
+
+        // a topic a tenant created before it was mounted stays with the tenant
+        let mut topic = MqttTopic::new(unique_id(), "test".to_string(), "sensor/1".to_string());
+        topic.tenant = "tenant_a".to_string();
+        cache_manager.add_topic("sensor/1", &topic);
+        assert!(check_tenant_topic(&cache_manager, "", "sensor/1").is_err());
+        assert!(check_tenant_topic(&cache_manager, "", "sensor/2").is_ok());
+    }
+}
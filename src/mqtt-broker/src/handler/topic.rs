@@ -18,6 +18,8 @@
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use common_base::tools::unique_id;
 use grpc_clients::pool::ClientPool;
+use metadata_struct::acl::mqtt_acl::MqttAclAction;
+use metadata_struct::mqtt::connection::MQTTConnection;
 use metadata_struct::mqtt::topic::MqttTopic;
 use protocol::mqtt::common::{Publish, PublishProperties};
 use regex::Regex;
@@ -25,7 +27,9 @@
 
 use super::error::MqttBrokerError;
 use crate::handler::cache::CacheManager;
+use crate::handler::mountpoint::{mount_topic, unmount_topic};
 use crate::handler::topic_rewrite::process_publish_topic_rewrite;
+use crate::security::acl::auth::is_allow_acl;
 use crate::storage::message::cluster_name;
 use crate::storage::topic::TopicStorage;
 use crate::subscribe::sub_common::{
@@ -94,28 +98,77 @@ pub fn get_topic_name(
         return Err(MqttBrokerError::TopicNameIsEmpty);
     }
 
+    let mountpoint = metadata_cache
+        .get_connection(connect_id)
+        .map(|connection| connection.mountpoint)
+        .unwrap_or_default();
+
+    // topic alias records the mounted topic name
     let topic_name = if topic.is_empty() {
         if let Some(tn) = metadata_cache.get_topic_alias(connect_id, topic_alias.unwrap()) {
-            tn
+            unmount_topic(&mountpoint, &tn)
         } else {
             return Err(MqttBrokerError::TopicNameInvalid());
         }
     } else {
         topic
     };
+    mount_publish_topic(metadata_cache, &mountpoint, topic_name)
+}
+
+// Validates the topic name a message is published to, and returns the name it is stored
+// under once the mountpoint and the topic rewrite rules are applied.
+pub fn mount_publish_topic(
+    metadata_cache: &Arc<CacheManager>,
+    mountpoint: &str,
+    topic_name: String,
+) -> Result<String, MqttBrokerError> {
     topic_name_validator(&topic_name)?;
+    let topic_name = mount_topic(mountpoint, &topic_name);
     // topic rewrite
     let rewrite_topic_name =
         process_publish_topic_rewrite(topic_name.clone(), &metadata_cache.topic_rewrite_rule)?;
     if let Some(val) = rewrite_topic_name {
-        topic_name_validator(val.as_str())?;
+        topic_name_validator(&unmount_topic(mountpoint, &val))?;
         return Ok(val);
     }
     Ok(topic_name)
 }
 
+// A hook or a WASM plugin that changes the topic of a message must keep it a valid topic name
+// under the mountpoint of the client, and the client must be allowed to publish to it.
+pub fn check_modified_publish_topic(
+    metadata_cache: &Arc<CacheManager>,
+    connection: &MQTTConnection,
+    topic_name: &str,
+    publish: &Publish,
+) -> Result<(), MqttBrokerError> {
+    if !topic_name.starts_with(&connection.mountpoint) {
+        return Err(MqttBrokerError::TopicNameOutOfMountpoint(
+            topic_name.to_string(),
+            connection.mountpoint.clone(),
+        ));
+    }
+    topic_name_validator(&unmount_topic(&connection.mountpoint, topic_name))?;
+
+    if !is_allow_acl(
+        metadata_cache,
+        connection,
+        topic_name,
+        MqttAclAction::Publish,
+        publish.retain,
+        publish.qos,
+    ) {
+        return Err(MqttBrokerError::PublishNotAuthorized(
+            topic_name.to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub async fn try_init_topic<S>(
     topic_name: &str,
+    tenant: &str,
     metadata_cache: &Arc<CacheManager>,
     message_storage_adapter: &Arc<S>,
     client_pool: &Arc<ClientPool>,
@@ -135,7 +188,9 @@ pub async fn try_init_topic<S>(
         let topic = if let Some(topic) = topic_storage.get_topic(topic_name).await? {
             topic
         } else {
-            let topic = MqttTopic::new(topic_id, conf.cluster_name.clone(), topic_name.to_owned());
+            let mut topic =
+                MqttTopic::new(topic_id, conf.cluster_name.clone(), topic_name.to_owned());
+            topic.tenant = tenant.to_owned();
             topic_storage.save_topic(topic.clone()).await?;
             topic
         };
@@ -188,9 +243,69 @@ pub fn gen_rewrite_topic(input: &str, pattern: &str, template: &str) -> Option<S
 
 #[cfg(test)]
 mod test {
-    use super::topic_name_validator;
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::acl::mqtt_acl::{
+        MqttAcl, MqttAclAction, MqttAclPermission, MqttAclResourceType,
+    };
+    use metadata_struct::mqtt::connection::MQTTConnection;
+    use protocol::mqtt::common::Publish;
+
+    use super::{check_modified_publish_topic, topic_name_validator};
+    use crate::handler::cache::CacheManager;
     use crate::handler::error::MqttBrokerError;
 
+    #[test]
+    fn check_modified_publish_topic_test() {
+        let client_pool = Arc::new(ClientPool::new(1));
+        let cache_manager = Arc::new(CacheManager::new(client_pool, "test".to_string()));
+        cache_manager.add_acl(MqttAcl {
+            resource_type: MqttAclResourceType::User,
+            resource_name: "u1".to_string(),
+            topic: "tenant_a/t/secret".to_string(),
+            ip: "*".to_string(),
+            action: MqttAclAction::Publish,
+            permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
+        });
+        let connection = MQTTConnection {
+            login_user: "u1".to_string(),
+            mountpoint: "tenant_a/".to_string(),
+            ..Default::default()
+        };
+        let publish = Publish {
+            topic: Bytes::from("tenant_a/t/1"),
+            ..Default::default()
+        };
+
+        assert!(check_modified_publish_topic(
+            &cache_manager,
+            &connection,
+            "tenant_a/t/2",
+            &publish
+        )
+        .is_ok());
+        assert!(matches!(
+            check_modified_publish_topic(&cache_manager, &connection, "tenant_b/t/2", &publish),
+            Err(MqttBrokerError::TopicNameOutOfMountpoint(_, _))
+        ));
+        assert!(matches!(
+            check_modified_publish_topic(&cache_manager, &connection, "tenant_a/t 2", &publish),
+            Err(MqttBrokerError::TopicNameIncorrectlyFormatted(_))
+        ));
+        assert!(matches!(
+            check_modified_publish_topic(
+                &cache_manager,
+                &connection,
+                "tenant_a/t/secret",
+                &publish
+            ),
+            Err(MqttBrokerError::PublishNotAuthorized(_))
+        ));
+    }
+
     #[test]
     pub fn topic_name_validator_test() {
         let topic_name = "".to_string();
@@ -0,0 +1,199 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::Bytes;
+use lru::LruCache;
+use protocol::mqtt::common::{Publish, PublishProperties};
+
+struct TopicAliasEntry {
+    alias: u16,
+    // Whether a Publish packet carrying both the topic name and the alias has been sent,
+    // only after that can the topic name be omitted.
+    established: bool,
+    // The Publish packets using the alias that are being written. The alias of a topic with
+    // packets in flight is not given to another topic, so a packet that omits the topic name
+    // cannot reach the client after the alias was reassigned.
+    in_flight: u32,
+}
+
+// Topic aliases used by the server when sending Publish packets to the client.
+// The number of aliases is limited by the Topic Alias Maximum in the client's CONNECT packet,
+// and the least recently used topic gives up its alias when the limit is reached.
+pub struct OutboundTopicAlias {
+    max: u16,
+    next_alias: u16,
+    topics: LruCache<String, TopicAliasEntry>,
+}
+
+impl OutboundTopicAlias {
+    pub fn new(max: u16) -> Self {
+        OutboundTopicAlias {
+            max,
+            next_alias: 1,
+            topics: LruCache::unbounded(),
+        }
+    }
+
+    // Returns the alias of the topic, and whether the topic name needs to be sent with it.
+    // Each assigned alias has to be released once the packet is written.
+    pub fn assign(&mut self, topic_name: &str) -> Option<(u16, bool)> {
+        if self.max == 0 || topic_name.is_empty() {
+            return None;
+        }
+
+        if let Some(entry) = self.topics.get_mut(topic_name) {
+            entry.in_flight += 1;
+            return Some((entry.alias, !entry.established));
+        }
+
+        let alias = if self.next_alias <= self.max {
+            let alias = self.next_alias;
+            self.next_alias += 1;
+            alias
+        } else {
+            self.evict()?
+        };
+
+        self.topics.put(
+            topic_name.to_owned(),
+            TopicAliasEntry {
+                alias,
+                established: false,
+                in_flight: 1,
+            },
+        );
+        Some((alias, true))
+    }
+
+    // Called after the packet using the alias of the topic is written, or failed to be written.
+    pub fn release(&mut self, topic_name: &str, written: bool) {
+        if let Some(entry) = self.topics.peek_mut(topic_name) {
+            entry.in_flight = entry.in_flight.saturating_sub(1);
+            entry.established |= written;
+        }
+    }
+
+    // Takes the alias of the least recently used topic. None when that topic still has
+    // packets in flight, the packet is then sent without an alias.
+    fn evict(&mut self) -> Option<u16> {
+        let (_, entry) = self.topics.peek_lru()?;
+        if entry.in_flight > 0 {
+            return None;
+        }
+        self.topics.pop_lru().map(|(_, entry)| entry.alias)
+    }
+}
+
+// Sets the topic alias of the Publish packet, and adds the properties to a packet that has none.
+// The topic name is kept when the alias has not been established on the client yet. The topic
+// name is returned so that the alias can be released after sending.
+pub fn apply_outbound_topic_alias(
+    topic_alias: &mut OutboundTopicAlias,
+    publish: &mut Publish,
+    properties: &mut Option<PublishProperties>,
+) -> Option<String> {
+    let topic_name = String::from_utf8(publish.topic.to_vec()).ok()?;
+    let (alias, send_topic_name) = topic_alias.assign(&topic_name)?;
+    properties.get_or_insert_with(Default::default).topic_alias = Some(alias);
+    if !send_topic_name {
+        publish.topic = Bytes::new();
+    }
+    Some(topic_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use protocol::mqtt::common::{Publish, PublishProperties};
+
+    use super::{apply_outbound_topic_alias, OutboundTopicAlias};
+
+    #[test]
+    fn outbound_topic_alias_assign_test() {
+        let mut topic_alias = OutboundTopicAlias::new(2);
+        assert_eq!(topic_alias.assign("/a"), Some((1, true)));
+        assert_eq!(topic_alias.assign("/a"), Some((1, true)));
+        topic_alias.release("/a", true);
+        topic_alias.release("/a", true);
+        assert_eq!(topic_alias.assign("/a"), Some((1, false)));
+        topic_alias.release("/a", true);
+
+        // the topic name is sent again when the packet failed to be written
+        assert_eq!(topic_alias.assign("/b"), Some((2, true)));
+        topic_alias.release("/b", false);
+        assert_eq!(topic_alias.assign("/b"), Some((2, true)));
+        topic_alias.release("/b", true);
+
+        // "/a" was used more recently than "/b", so "/b" gives up its alias
+        assert_eq!(topic_alias.assign("/a"), Some((1, false)));
+        topic_alias.release("/a", true);
+        assert_eq!(topic_alias.assign("/c"), Some((2, true)));
+        assert_eq!(topic_alias.assign("/b"), Some((1, true)));
+        topic_alias.release("/b", true);
+
+        // "/c" is the least recently used topic but its packet is still in flight
+        assert_eq!(topic_alias.assign("/d"), None);
+        topic_alias.release("/c", true);
+        assert_eq!(topic_alias.assign("/d"), Some((2, true)));
+
+        let mut topic_alias = OutboundTopicAlias::new(0);
+        assert_eq!(topic_alias.assign("/a"), None);
+    }
+
+    #[test]
+    fn apply_outbound_topic_alias_test() {
+        let mut topic_alias = OutboundTopicAlias::new(10);
+        let mut publish = Publish {
+            topic: Bytes::from("/very/long/topic/name"),
+            ..Default::default()
+        };
+        let mut properties = Some(PublishProperties::default());
+        let res = apply_outbound_topic_alias(&mut topic_alias, &mut publish, &mut properties);
+        assert_eq!(res, Some("/very/long/topic/name".to_string()));
+        assert_eq!(properties.unwrap().topic_alias, Some(1));
+        assert_eq!(publish.topic, Bytes::from("/very/long/topic/name"));
+
+        topic_alias.release("/very/long/topic/name", true);
+        let mut publish = Publish {
+            topic: Bytes::from("/very/long/topic/name"),
+            ..Default::default()
+        };
+        let mut properties = Some(PublishProperties::default());
+        let res = apply_outbound_topic_alias(&mut topic_alias, &mut publish, &mut properties);
+        assert_eq!(res, Some("/very/long/topic/name".to_string()));
+        assert_eq!(properties.unwrap().topic_alias, Some(1));
+        assert!(publish.topic.is_empty());
+    }
+
+    #[test]
+    fn apply_outbound_topic_alias_without_properties_test() {
+        // a message published without properties, such as one from an MQTT 3 client
+        let mut topic_alias = OutboundTopicAlias::new(10);
+        let mut publish = Publish {
+            topic: Bytes::from("/a/b"),
+            ..Default::default()
+        };
+        let mut properties = None;
+        let res = apply_outbound_topic_alias(&mut topic_alias, &mut publish, &mut properties);
+        assert_eq!(res, Some("/a/b".to_string()));
+        assert_eq!(properties.unwrap().topic_alias, Some(1));
+
+        // no alias is left, the packet is sent without properties
+        let mut topic_alias = OutboundTopicAlias::new(0);
+        let mut properties = None;
+        let res = apply_outbound_topic_alias(&mut topic_alias, &mut publish, &mut properties);
+        assert_eq!(res, None);
+        assert!(properties.is_none());
+    }
+}
@@ -12,8 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_base::config::broker_mqtt::Listener;
 use futures_util::SinkExt;
-use grpc_clients::pool::ClientPool;
+use idempotent_message::IdempotentManager;
 use log::error;
 use metadata_struct::mqtt::cluster::MqttClusterDynamicConfig;
 use metadata_struct::mqtt::connection::MQTTConnection;
@@ -35,6 +36,7 @@
 use super::flow_control::{
     is_connection_rate_exceeded, is_qos_message, is_subscribe_rate_exceeded,
 };
+use super::mountpoint::{mount_sub_path, mount_subscribe};
 use super::pkid::pkid_exists;
 use super::response::{
     response_packet_mqtt_connect_fail, response_packet_mqtt_distinct_by_reason,
@@ -42,6 +44,7 @@
     response_packet_mqtt_suback, response_packet_mqtt_unsuback,
 };
 use super::sub_exclusive::check_exclusive_subscribe;
+use super::tenant::check_tenant_sub_path;
 use super::topic::topic_name_validator;
 use crate::security::AuthDriver;
 use crate::server::connection_manager::ConnectionManager;
@@ -50,11 +53,12 @@
 
 pub async fn tcp_establish_connection_check(
     addr: &SocketAddr,
+    listener: &Listener,
     connection_manager: &Arc<ConnectionManager>,
     write_frame_stream: &mut FramedWrite<WriteHalf<TcpStream>, MqttCodec>,
 ) -> bool {
     if let Some(value) =
-        handle_tpc_connection_overflow(addr, connection_manager, write_frame_stream).await
+        handle_tpc_connection_overflow(addr, listener, connection_manager, write_frame_stream).await
     {
         return value;
     }
@@ -67,6 +71,7 @@ pub async fn tcp_establish_connection_check(
 
 pub async fn tcp_tls_establish_connection_check(
     addr: &SocketAddr,
+    listener: &Listener,
     connection_manager: &Arc<ConnectionManager>,
     write_frame_stream: &mut FramedWrite<
         WriteHalf<tokio_rustls::server::TlsStream<TcpStream>>,
@@ -74,7 +79,7 @@ pub async fn tcp_tls_establish_connection_check(
     >,
 ) -> bool {
     if let Some(value) =
-        handle_tpc_connection_overflow(addr, connection_manager, write_frame_stream).await
+        handle_tpc_connection_overflow(addr, listener, connection_manager, write_frame_stream).await
     {
         return value;
     }
@@ -88,13 +93,16 @@ pub async fn tcp_tls_establish_connection_check(
 
 async fn handle_tpc_connection_overflow<T>(
     addr: &SocketAddr,
+    listener: &Listener,
     connection_manager: &Arc<ConnectionManager>,
     write_frame_stream: &mut FramedWrite<WriteHalf<T>, MqttCodec>,
 ) -> Option<bool>
 where
     T: AsyncWriteExt + AsyncWrite,
 {
-    if connection_manager.tcp_connect_num_check() {
+    if connection_manager.tcp_connect_num_check()
+        || connection_manager.listener_connect_num_check(listener)
+    {
         let packet_wrapper = MqttPacketWrapper {
             protocol_version: MqttProtocol::Mqtt5.into(),
             packet: response_packet_mqtt_distinct_by_reason(
@@ -267,7 +275,7 @@ pub fn connect_validator(
 pub async fn publish_validator(
     protocol: &MqttProtocol,
     cache_manager: &Arc<CacheManager>,
-    client_pool: &Arc<ClientPool>,
+    idempotent_manager: &Arc<IdempotentManager>,
     connection: &MQTTConnection,
     publish: &Publish,
     publish_properties: &Option<PublishProperties>,
@@ -277,7 +285,7 @@ pub async fn publish_validator(
     if publish.qos == QoS::ExactlyOnce {
         match pkid_exists(
             cache_manager,
-            client_pool,
+            idempotent_manager,
             &connection.client_id,
             publish.pkid,
         )
@@ -430,6 +438,22 @@ pub async fn subscribe_validator(
         ));
     }
 
+    // exclusive and acl checks see the mounted topic filters
+    let mut subscribe = subscribe.clone();
+    mount_subscribe(&connection.mountpoint, &mut subscribe);
+
+    if subscribe.filters.iter().any(|filter| {
+        check_tenant_sub_path(metadata_cache, &connection.tenant, &filter.path).is_err()
+    }) {
+        return Some(response_packet_mqtt_suback(
+            protocol,
+            connection,
+            subscribe.packet_identifier,
+            vec![SubscribeReasonCode::NotAuthorized],
+            None,
+        ));
+    }
+
     if is_subscribe_rate_exceeded() {
         return Some(response_packet_mqtt_suback(
             protocol,
@@ -440,7 +464,7 @@ pub async fn subscribe_validator(
         ));
     }
 
-    if !check_exclusive_subscribe(metadata_cache, subscribe_manager, subscribe) {
+    if !check_exclusive_subscribe(metadata_cache, subscribe_manager, &subscribe) {
         return Some(response_packet_mqtt_suback(
             protocol,
             connection,
@@ -450,7 +474,7 @@ pub async fn subscribe_validator(
         ));
     }
 
-    if !auth_driver.allow_subscribe(connection, subscribe).await {
+    if !auth_driver.allow_subscribe(connection, &subscribe).await {
         return Some(response_packet_mqtt_suback(
             protocol,
             connection,
@@ -486,7 +510,11 @@ pub async fn un_subscribe_validator(
     }
 
     for path in un_subscribe.filters.clone() {
-        if subscribe_manager.get_subscribe(client_id, &path).is_none() {
+        let mounted_path = mount_sub_path(&connection.mountpoint, &path);
+        if subscribe_manager
+            .get_subscribe(client_id, &mounted_path)
+            .is_none()
+        {
             return Some(response_packet_mqtt_unsuback(
                 connection,
                 un_subscribe.pkid,
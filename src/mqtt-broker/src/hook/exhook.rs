@@ -0,0 +1,631 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common_base::config::broker_mqtt::{broker_mqtt_conf, ExHookServer};
+use common_base::error::common::CommonError;
+use grpc_clients::mqtt::exhook::call::{
+    mqtt_exhook_on_client_authenticate, mqtt_exhook_on_client_connect,
+    mqtt_exhook_on_client_connected, mqtt_exhook_on_client_disconnected,
+    mqtt_exhook_on_client_subscribe, mqtt_exhook_on_client_unsubscribe,
+    mqtt_exhook_on_message_acked, mqtt_exhook_on_message_delivered, mqtt_exhook_on_message_publish,
+    mqtt_exhook_on_provider_loaded,
+};
+use grpc_clients::pool::ClientPool;
+use log::{error, info, warn};
+use metadata_struct::mqtt::connection::MQTTConnection;
+use protocol::broker_mqtt::broker_mqtt_exhook::{
+    BrokerInfo, ClientAuthenticateRequest, ClientConnectRequest, ClientConnectedRequest,
+    ClientDisconnectedRequest, ClientInfo, ClientSubscribeRequest, ClientUnsubscribeRequest,
+    HookReply, HookResultType, Message, MessageAckedRequest, MessageDeliveredRequest,
+    MessagePublishRequest, ProviderLoadedRequest, TopicFilter,
+};
+use protocol::mqtt::common::{
+    qos, Connect, Filter, Login, Publish, PublishProperties, Subscribe, Unsubscribe,
+};
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, timeout};
+
+use super::{hook_manager, BrokerHook, HookResult};
+use crate::handler::error::MqttBrokerError;
+use crate::subscribe::sub_common::path_regex_match;
+
+pub const EXHOOK_FAILURE_POLICY_DENY: &str = "deny";
+
+// A hook backed by an external HookProviderService. Only the hooks declared by the service in
+// on_provider_loaded are called, the message hooks can be limited to a list of topic filters.
+pub struct ExHook {
+    name: String,
+    server: ExHookServer,
+    // hook name -> topic filters, an empty list matches all topics
+    hooks: HashMap<String, Vec<String>>,
+    client_pool: Arc<ClientPool>,
+}
+
+impl ExHook {
+    pub fn new(
+        server: ExHookServer,
+        hooks: HashMap<String, Vec<String>>,
+        client_pool: Arc<ClientPool>,
+    ) -> Self {
+        ExHook {
+            name: format!("exhook:{}", server.name),
+            server,
+            hooks,
+            client_pool,
+        }
+    }
+
+    fn is_declared(&self, hook: &str) -> bool {
+        self.hooks.contains_key(hook)
+    }
+
+    fn is_match_topic(&self, hook: &str, topic_name: &str) -> bool {
+        match self.hooks.get(hook) {
+            Some(topics) => {
+                topics.is_empty()
+                    || topics
+                        .iter()
+                        .any(|topic| path_regex_match(topic_name, topic))
+            }
+            None => false,
+        }
+    }
+
+    async fn call<T>(
+        &self,
+        fut: impl Future<Output = Result<T, CommonError>>,
+    ) -> Result<T, MqttBrokerError> {
+        call_with_timeout(self.server.timeout_ms, fut).await
+    }
+
+    // Applies the failure policy when the service cannot be called or the call times out.
+    fn failure<T>(&self, hook: &str, e: MqttBrokerError) -> HookResult<T> {
+        warn!(
+            "ExHook {} failed to call {} on {}, error message: {}",
+            self.server.name, hook, self.server.addr, e
+        );
+        if self.server.failure_policy == EXHOOK_FAILURE_POLICY_DENY {
+            return HookResult::Deny(format!("hook service is unavailable: {}", e));
+        }
+        HookResult::Allow
+    }
+
+    // Notifications do not wait for the reply of the service.
+    fn notify<F>(&self, hook: &'static str, call: impl FnOnce(Arc<ClientPool>, String) -> F)
+    where
+        F: Future<Output = Result<(), CommonError>> + Send + 'static,
+    {
+        let fut = call(self.client_pool.clone(), self.server.addr.clone());
+        let timeout_ms = self.server.timeout_ms;
+        let name = self.server.name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = call_with_timeout(timeout_ms, fut).await {
+                warn!(
+                    "ExHook {} failed to call {}, error message: {}",
+                    name, hook, e
+                );
+            }
+        });
+    }
+}
+
+async fn call_with_timeout<T>(
+    timeout_ms: u64,
+    fut: impl Future<Output = Result<T, CommonError>>,
+) -> Result<T, MqttBrokerError> {
+    match timeout(Duration::from_millis(timeout_ms), fut).await {
+        Ok(reply) => Ok(reply?),
+        Err(_) => Err(MqttBrokerError::CommonError(format!(
+            "call timed out after {}ms",
+            timeout_ms
+        ))),
+    }
+}
+
+fn build_client_info(connection: &MQTTConnection) -> ClientInfo {
+    ClientInfo {
+        client_id: connection.client_id.clone(),
+        username: connection.login_user.clone(),
+        source_ip_addr: connection.source_ip_addr.clone(),
+        tenant: connection.tenant.clone(),
+        connect_id: connection.connect_id,
+    }
+}
+
+fn build_message(topic_name: &str, publish: &Publish) -> Message {
+    Message {
+        topic: topic_name.to_string(),
+        payload: publish.payload.to_vec(),
+        qos: publish.qos as u32,
+        retain: publish.retain,
+    }
+}
+
+fn reply_result<T>(
+    reply: HookReply,
+    modify: impl FnOnce(HookReply) -> Result<T, MqttBrokerError>,
+) -> Result<HookResult<T>, MqttBrokerError> {
+    Ok(match reply.result() {
+        HookResultType::Allow => HookResult::Allow,
+        HookResultType::Deny => HookResult::Deny(reply.reason),
+        HookResultType::Modify => HookResult::Modify(modify(reply)?),
+        HookResultType::Drop => HookResult::Drop,
+    })
+}
+
+fn modify_publish(publish: &Publish, reply: HookReply) -> Result<Publish, MqttBrokerError> {
+    let Some(message) = reply.message else {
+        return Err(MqttBrokerError::CommonError(
+            "the modified message is empty".to_string(),
+        ));
+    };
+    let Some(qos) = qos(message.qos as u8) else {
+        return Err(MqttBrokerError::CommonError(format!(
+            "invalid qos {} of the modified message",
+            message.qos
+        )));
+    };
+    let mut publish = publish.clone();
+    publish.topic = message.topic.into();
+    publish.payload = message.payload.into();
+    publish.qos = qos;
+    publish.retain = message.retain;
+    Ok(publish)
+}
+
+// Subscription options that the service does not carry are kept for the filters that are not
+// changed, the new filters use the default options.
+fn modify_subscribe(subscribe: &Subscribe, reply: HookReply) -> Result<Subscribe, MqttBrokerError> {
+    let mut filters = Vec::new();
+    for topic_filter in reply.topic_filters {
+        let Some(qos) = qos(topic_filter.qos as u8) else {
+            return Err(MqttBrokerError::CommonError(format!(
+                "invalid qos {} of the modified topic filter",
+                topic_filter.qos
+            )));
+        };
+        let mut filter = subscribe
+            .filters
+            .iter()
+            .find(|raw| raw.path == topic_filter.topic)
+            .cloned()
+            .unwrap_or_default();
+        filter.path = topic_filter.topic;
+        filter.qos = qos;
+        filters.push(filter);
+    }
+    Ok(Subscribe {
+        packet_identifier: subscribe.packet_identifier,
+        filters,
+    })
+}
+
+fn build_topic_filters(filters: &[Filter]) -> Vec<TopicFilter> {
+    filters
+        .iter()
+        .map(|filter| TopicFilter {
+            topic: filter.path.clone(),
+            qos: filter.qos as u32,
+        })
+        .collect()
+}
+
+#[async_trait]
+impl BrokerHook for ExHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn on_client_connect(
+        &self,
+        connection: &MQTTConnection,
+        connect: &Connect,
+    ) -> HookResult<()> {
+        let hook = "on_client_connect";
+        if !self.is_declared(hook) {
+            return HookResult::Allow;
+        }
+        let request = ClientConnectRequest {
+            client: Some(build_client_info(connection)),
+            keep_alive: connect.keep_alive as u32,
+            clean_session: connect.clean_session,
+        };
+        match self
+            .call(mqtt_exhook_on_client_connect(
+                &self.client_pool,
+                &[&self.server.addr],
+                request,
+            ))
+            .await
+            .and_then(|reply| reply_result(reply, |_| Ok(())))
+        {
+            Ok(HookResult::Modify(_)) => HookResult::Allow,
+            Ok(result) => result,
+            Err(e) => self.failure(hook, e),
+        }
+    }
+
+    async fn on_client_authenticate(
+        &self,
+        connection: &MQTTConnection,
+        login: &Option<Login>,
+    ) -> HookResult<bool> {
+        let hook = "on_client_authenticate";
+        if !self.is_declared(hook) {
+            return HookResult::Allow;
+        }
+        let (username, password) = match login {
+            Some(login) => (login.username.clone(), login.password.clone()),
+            None => (String::new(), String::new()),
+        };
+        let request = ClientAuthenticateRequest {
+            client: Some(build_client_info(connection)),
+            username,
+            password,
+        };
+        match self
+            .call(mqtt_exhook_on_client_authenticate(
+                &self.client_pool,
+                &[&self.server.addr],
+                request,
+            ))
+            .await
+            .and_then(|reply| reply_result(reply, |reply| Ok(reply.authenticated)))
+        {
+            Ok(result) => result,
+            Err(e) => self.failure(hook, e),
+        }
+    }
+
+    async fn on_client_connected(&self, connection: &MQTTConnection) {
+        if !self.is_declared("on_client_connected") {
+            return;
+        }
+        let request = ClientConnectedRequest {
+            client: Some(build_client_info(connection)),
+        };
+        self.notify("on_client_connected", |client_pool, addr| async move {
+            mqtt_exhook_on_client_connected(&client_pool, &[addr], request)
+                .await
+                .map(|_| ())
+        });
+    }
+
+    async fn on_client_disconnected(&self, connection: &MQTTConnection) {
+        if !self.is_declared("on_client_disconnected") {
+            return;
+        }
+        let request = ClientDisconnectedRequest {
+            client: Some(build_client_info(connection)),
+        };
+        self.notify("on_client_disconnected", |client_pool, addr| async move {
+            mqtt_exhook_on_client_disconnected(&client_pool, &[addr], request)
+                .await
+                .map(|_| ())
+        });
+    }
+
+    async fn on_client_subscribe(
+        &self,
+        connection: &MQTTConnection,
+        subscribe: &Subscribe,
+    ) -> HookResult<Subscribe> {
+        let hook = "on_client_subscribe";
+        if !self.is_declared(hook) {
+            return HookResult::Allow;
+        }
+        let request = ClientSubscribeRequest {
+            client: Some(build_client_info(connection)),
+            topic_filters: build_topic_filters(&subscribe.filters),
+        };
+        match self
+            .call(mqtt_exhook_on_client_subscribe(
+                &self.client_pool,
+                &[&self.server.addr],
+                request,
+            ))
+            .await
+            .and_then(|reply| reply_result(reply, |reply| modify_subscribe(subscribe, reply)))
+        {
+            Ok(result) => result,
+            Err(e) => self.failure(hook, e),
+        }
+    }
+
+    async fn on_client_unsubscribe(
+        &self,
+        connection: &MQTTConnection,
+        unsubscribe: &Unsubscribe,
+    ) -> HookResult<Unsubscribe> {
+        let hook = "on_client_unsubscribe";
+        if !self.is_declared(hook) {
+            return HookResult::Allow;
+        }
+        let request = ClientUnsubscribeRequest {
+            client: Some(build_client_info(connection)),
+            topic_filters: unsubscribe
+                .filters
+                .iter()
+                .map(|topic| TopicFilter {
+                    topic: topic.clone(),
+                    qos: 0,
+                })
+                .collect(),
+        };
+        match self
+            .call(mqtt_exhook_on_client_unsubscribe(
+                &self.client_pool,
+                &[&self.server.addr],
+                request,
+            ))
+            .await
+            .and_then(|reply| {
+                reply_result(reply, |reply| {
+                    Ok(Unsubscribe {
+                        pkid: unsubscribe.pkid,
+                        filters: reply.topic_filters.into_iter().map(|f| f.topic).collect(),
+                    })
+                })
+            }) {
+            Ok(result) => result,
+            Err(e) => self.failure(hook, e),
+        }
+    }
+
+    async fn on_message_publish(
+        &self,
+        connection: &MQTTConnection,
+        topic_name: &str,
+        publish: &Publish,
+        _publish_properties: &Option<PublishProperties>,
+    ) -> HookResult<Publish> {
+        let hook = "on_message_publish";
+        if !self.is_match_topic(hook, topic_name) {
+            return HookResult::Allow;
+        }
+        let request = MessagePublishRequest {
+            client: Some(build_client_info(connection)),
+            message: Some(build_message(topic_name, publish)),
+        };
+        match self
+            .call(mqtt_exhook_on_message_publish(
+                &self.client_pool,
+                &[&self.server.addr],
+                request,
+            ))
+            .await
+            .and_then(|reply| reply_result(reply, |reply| modify_publish(publish, reply)))
+        {
+            Ok(result) => result,
+            Err(e) => self.failure(hook, e),
+        }
+    }
+
+    async fn on_message_delivered(&self, client_id: &str, publish: &Publish) {
+        let topic_name = String::from_utf8_lossy(&publish.topic).to_string();
+        if !self.is_match_topic("on_message_delivered", &topic_name) {
+            return;
+        }
+        let request = MessageDeliveredRequest {
+            client_id: client_id.to_string(),
+            message: Some(build_message(&topic_name, publish)),
+        };
+        self.notify("on_message_delivered", |client_pool, addr| async move {
+            mqtt_exhook_on_message_delivered(&client_pool, &[addr], request)
+                .await
+                .map(|_| ())
+        });
+    }
+
+    async fn on_message_acked(&self, connection: &MQTTConnection, pkid: u16) {
+        if !self.is_declared("on_message_acked") {
+            return;
+        }
+        let request = MessageAckedRequest {
+            client: Some(build_client_info(connection)),
+            pkid: pkid as u32,
+        };
+        self.notify("on_message_acked", |client_pool, addr| async move {
+            mqtt_exhook_on_message_acked(&client_pool, &[addr], request)
+                .await
+                .map(|_| ())
+        });
+    }
+}
+
+// Loads the hook services in the config. A service that is not reachable is retried every
+// 5 seconds until it is loaded, the hook is registered when the service has declared its hooks.
+// A loaded service is reloaded every reload_interval_ms, so the hooks and topic filters it
+// declares can change without restarting the broker. When a reload fails the hooks declared by
+// the last load are kept, the calls to an unavailable service follow the failure policy.
+pub fn start_exhook_providers(client_pool: Arc<ClientPool>, stop_send: broadcast::Sender<bool>) {
+    let conf = broker_mqtt_conf();
+    for server in conf.hook.exhook.clone() {
+        let client_pool = client_pool.clone();
+        let mut stop_rx = stop_send.subscribe();
+        tokio::spawn(async move {
+            let mut loaded_hooks: Option<HashMap<String, Vec<String>>> = None;
+            loop {
+                let interval = match load_exhook_provider(&client_pool, &server).await {
+                    Ok(hook) => {
+                        if loaded_hooks.as_ref() != Some(&hook.hooks) {
+                            info!(
+                                "ExHook {} loaded from {}, hooks: {:?}",
+                                server.name,
+                                server.addr,
+                                hook.hooks.keys().collect::<Vec<_>>()
+                            );
+                            loaded_hooks = Some(hook.hooks.clone());
+                            hook_manager().register(Arc::new(hook));
+                        }
+                        Duration::from_millis(server.reload_interval_ms)
+                    }
+                    Err(e) => {
+                        error!(
+                            "ExHook {} failed to load from {}, error message: {}",
+                            server.name, server.addr, e
+                        );
+                        Duration::from_secs(5)
+                    }
+                };
+
+                select! {
+                    val = stop_rx.recv() => {
+                        if let Ok(flag) = val {
+                            if flag {
+                                break;
+                            }
+                        }
+                    }
+                    _ = sleep(interval) => {}
+                }
+            }
+        });
+    }
+}
+
+async fn load_exhook_provider(
+    client_pool: &Arc<ClientPool>,
+    server: &ExHookServer,
+) -> Result<ExHook, MqttBrokerError> {
+    let conf = broker_mqtt_conf();
+    let request = ProviderLoadedRequest {
+        broker: Some(BrokerInfo {
+            cluster_name: conf.cluster_name.clone(),
+            broker_id: conf.broker_id,
+        }),
+    };
+    let reply = call_with_timeout(
+        server.timeout_ms,
+        mqtt_exhook_on_provider_loaded(client_pool, &[&server.addr], request),
+    )
+    .await?;
+    let hooks = reply
+        .hooks
+        .into_iter()
+        .map(|spec| (spec.name, spec.topics))
+        .collect();
+    Ok(ExHook::new(server.clone(), hooks, client_pool.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use common_base::config::broker_mqtt::ExHookServer;
+    use grpc_clients::pool::ClientPool;
+    use metadata_struct::mqtt::connection::MQTTConnection;
+    use protocol::broker_mqtt::broker_mqtt_exhook::{HookReply, HookResultType, TopicFilter};
+    use protocol::mqtt::common::{Filter, QoS, Subscribe};
+
+    use super::{modify_subscribe, ExHook};
+    use crate::hook::{BrokerHook, HookResult};
+
+    #[test]
+    fn exhook_topic_match_test() {
+        let mut hooks = HashMap::new();
+        hooks.insert(
+            "on_message_publish".to_string(),
+            vec!["sensor/+/temp".to_string()],
+        );
+        hooks.insert("on_message_delivered".to_string(), Vec::new());
+        let server = ExHookServer {
+            name: "business".to_string(),
+            addr: "127.0.0.1:9000".to_string(),
+            timeout_ms: 1000,
+            failure_policy: "ignore".to_string(),
+            reload_interval_ms: 60000,
+        };
+        let hook = ExHook::new(server, hooks, Arc::new(ClientPool::new(1)));
+
+        assert_eq!(hook.name(), "exhook:business");
+        assert!(hook.is_match_topic("on_message_publish", "sensor/1/temp"));
+        assert!(!hook.is_match_topic("on_message_publish", "sensor/1/humidity"));
+        assert!(hook.is_match_topic("on_message_delivered", "any/topic"));
+        assert!(!hook.is_match_topic("on_client_subscribe", "any/topic"));
+    }
+
+    #[tokio::test]
+    async fn exhook_failure_policy_test() {
+        let mut server = ExHookServer {
+            name: "business".to_string(),
+            addr: "127.0.0.1:1".to_string(),
+            timeout_ms: 1000,
+            failure_policy: "ignore".to_string(),
+            reload_interval_ms: 60000,
+        };
+        let mut hooks = HashMap::new();
+        hooks.insert("on_client_subscribe".to_string(), Vec::new());
+        let connection = MQTTConnection::default();
+        let subscribe = Subscribe {
+            packet_identifier: 1,
+            filters: Vec::new(),
+        };
+
+        let hook = ExHook::new(server.clone(), hooks.clone(), Arc::new(ClientPool::new(1)));
+        assert!(matches!(
+            hook.on_client_subscribe(&connection, &subscribe).await,
+            HookResult::Allow
+        ));
+
+        server.failure_policy = "deny".to_string();
+        let hook = ExHook::new(server, hooks, Arc::new(ClientPool::new(1)));
+        assert!(matches!(
+            hook.on_client_subscribe(&connection, &subscribe).await,
+            HookResult::Deny(_)
+        ));
+    }
+
+    #[test]
+    fn exhook_modify_subscribe_test() {
+        let subscribe = Subscribe {
+            packet_identifier: 1,
+            filters: vec![Filter {
+                path: "t1".to_string(),
+                qos: QoS::AtLeastOnce,
+                nolocal: true,
+                ..Default::default()
+            }],
+        };
+        let reply = HookReply {
+            result: HookResultType::Modify as i32,
+            topic_filters: vec![
+                TopicFilter {
+                    topic: "t1".to_string(),
+                    qos: 0,
+                },
+                TopicFilter {
+                    topic: "t2".to_string(),
+                    qos: 2,
+                },
+            ],
+            ..Default::default()
+        };
+        let data = modify_subscribe(&subscribe, reply).unwrap();
+        assert_eq!(data.packet_identifier, 1);
+        assert_eq!(data.filters.len(), 2);
+        assert!(data.filters[0].nolocal);
+        assert_eq!(data.filters[0].qos, QoS::AtMostOnce);
+        assert_eq!(data.filters[1].path, "t2");
+        assert_eq!(data.filters[1].qos, QoS::ExactlyOnce);
+    }
+}
@@ -0,0 +1,463 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, OnceLock, RwLock};
+
+use async_trait::async_trait;
+use log::debug;
+use metadata_struct::mqtt::connection::MQTTConnection;
+use protocol::mqtt::common::{Connect, Login, Publish, PublishProperties, Subscribe, Unsubscribe};
+
+use crate::handler::error::MqttBrokerError;
+
+pub mod exhook;
+pub mod webhook;
+
+pub enum HookResult<T> {
+    // Continue with the next hook and the default processing.
+    Allow,
+    // Stop processing and reject the request with the reason.
+    Deny(String),
+    // Replace the request and continue with the next hook.
+    Modify(T),
+    // Acknowledge the message without storing it, like a message dropped by a WASM plugin. Only
+    // on_message_publish supports it, the other events treat it as Allow.
+    Drop,
+}
+
+// Hooks are called in the broker process on the client and message lifecycle events. All
+// methods have a default implementation, so a hook only implements the events it handles.
+#[async_trait]
+pub trait BrokerHook: Send + Sync {
+    fn name(&self) -> &str;
+
+    // Called when the CONNECT packet is received, before the client is authenticated.
+    async fn on_client_connect(
+        &self,
+        _connection: &MQTTConnection,
+        _connect: &Connect,
+    ) -> HookResult<()> {
+        HookResult::Allow
+    }
+
+    // Called before the built-in authentication, Modify(true) authenticates the client without
+    // the built-in authentication and Modify(false) rejects it.
+    async fn on_client_authenticate(
+        &self,
+        _connection: &MQTTConnection,
+        _login: &Option<Login>,
+    ) -> HookResult<bool> {
+        HookResult::Allow
+    }
+
+    async fn on_client_connected(&self, _connection: &MQTTConnection) {}
+
+    async fn on_client_disconnected(&self, _connection: &MQTTConnection) {}
+
+    async fn on_client_subscribe(
+        &self,
+        _connection: &MQTTConnection,
+        _subscribe: &Subscribe,
+    ) -> HookResult<Subscribe> {
+        HookResult::Allow
+    }
+
+    async fn on_client_unsubscribe(
+        &self,
+        _connection: &MQTTConnection,
+        _unsubscribe: &Unsubscribe,
+    ) -> HookResult<Unsubscribe> {
+        HookResult::Allow
+    }
+
+    // Called after the subscription has been saved.
+    async fn on_client_subscribed(&self, _connection: &MQTTConnection, _subscribe: &Subscribe) {}
+
+    // Called after the subscription has been removed.
+    async fn on_client_unsubscribed(
+        &self,
+        _connection: &MQTTConnection,
+        _unsubscribe: &Unsubscribe,
+    ) {
+    }
+
+    // Called before the message is stored, a modified topic replaces the topic of the message.
+    // The modified topic is checked again against the topic rules, the mountpoint and the ACL of
+    // the client.
+    async fn on_message_publish(
+        &self,
+        _connection: &MQTTConnection,
+        _topic_name: &str,
+        _publish: &Publish,
+        _publish_properties: &Option<PublishProperties>,
+    ) -> HookResult<Publish> {
+        HookResult::Allow
+    }
+
+    // Called after the message has been written to the connection of the subscriber.
+    async fn on_message_delivered(&self, _client_id: &str, _publish: &Publish) {}
+
+    // Called when the subscriber acknowledges a QoS 1 or QoS 2 message.
+    async fn on_message_acked(&self, _connection: &MQTTConnection, _pkid: u16) {}
+
+    // Called when a message is not pushed to the subscriber, such as an expired message or a
+    // message moved to the dead letter topic.
+    async fn on_message_dropped(&self, _client_id: &str, _topic_name: &str, _reason: &str) {}
+}
+
+#[derive(Default)]
+pub struct HookManager {
+    order: RwLock<Vec<String>>,
+    hooks: RwLock<Vec<Arc<dyn BrokerHook>>>,
+}
+
+impl HookManager {
+    pub fn new() -> Self {
+        HookManager::default()
+    }
+
+    pub fn set_order(&self, order: Vec<String>) {
+        *self.order.write().unwrap() = order;
+        self.sort_hooks();
+    }
+
+    // A hook with the same name as a registered hook replaces it.
+    pub fn register(&self, hook: Arc<dyn BrokerHook>) {
+        {
+            let mut hooks = self.hooks.write().unwrap();
+            hooks.retain(|raw| raw.name() != hook.name());
+            hooks.push(hook);
+        }
+        self.sort_hooks();
+    }
+
+    pub fn unregister(&self, name: &str) {
+        self.hooks.write().unwrap().retain(|raw| raw.name() != name);
+    }
+
+    pub fn hook_names(&self) -> Vec<String> {
+        self.hooks
+            .read()
+            .unwrap()
+            .iter()
+            .map(|hook| hook.name().to_string())
+            .collect()
+    }
+
+    fn sort_hooks(&self) {
+        let order = self.order.read().unwrap();
+        let position = |name: &str| {
+            order
+                .iter()
+                .position(|raw| raw == name)
+                .unwrap_or(order.len())
+        };
+        // the sort is stable, so the unlisted hooks keep the registration order
+        self.hooks
+            .write()
+            .unwrap()
+            .sort_by_key(|hook| position(hook.name()));
+    }
+
+    fn hooks(&self) -> Vec<Arc<dyn BrokerHook>> {
+        self.hooks.read().unwrap().clone()
+    }
+
+    pub async fn on_client_connect(
+        &self,
+        connection: &MQTTConnection,
+        connect: &Connect,
+    ) -> Result<(), MqttBrokerError> {
+        for hook in self.hooks() {
+            if let HookResult::Deny(reason) = hook.on_client_connect(connection, connect).await {
+                return Err(MqttBrokerError::HookDenied(hook.name().to_string(), reason));
+            }
+        }
+        Ok(())
+    }
+
+    // Returns true when a hook has authenticated the client, the built-in authentication is
+    // skipped in this case.
+    pub async fn on_client_authenticate(
+        &self,
+        connection: &MQTTConnection,
+        login: &Option<Login>,
+    ) -> Result<bool, MqttBrokerError> {
+        for hook in self.hooks() {
+            match hook.on_client_authenticate(connection, login).await {
+                HookResult::Allow | HookResult::Drop => {}
+                HookResult::Modify(true) => return Ok(true),
+                HookResult::Modify(false) => {
+                    return Err(MqttBrokerError::HookDenied(
+                        hook.name().to_string(),
+                        "authentication failed".to_string(),
+                    ));
+                }
+                HookResult::Deny(reason) => {
+                    return Err(MqttBrokerError::HookDenied(hook.name().to_string(), reason));
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    pub async fn on_client_connected(&self, connection: &MQTTConnection) {
+        for hook in self.hooks() {
+            hook.on_client_connected(connection).await;
+        }
+    }
+
+    pub async fn on_client_disconnected(&self, connection: &MQTTConnection) {
+        for hook in self.hooks() {
+            hook.on_client_disconnected(connection).await;
+        }
+    }
+
+    pub async fn on_client_subscribe(
+        &self,
+        connection: &MQTTConnection,
+        mut subscribe: Subscribe,
+    ) -> Result<Subscribe, MqttBrokerError> {
+        for hook in self.hooks() {
+            match hook.on_client_subscribe(connection, &subscribe).await {
+                HookResult::Allow | HookResult::Drop => {}
+                HookResult::Modify(data) => subscribe = data,
+                HookResult::Deny(reason) => {
+                    return Err(MqttBrokerError::HookDenied(hook.name().to_string(), reason));
+                }
+            }
+        }
+        Ok(subscribe)
+    }
+
+    pub async fn on_client_unsubscribe(
+        &self,
+        connection: &MQTTConnection,
+        mut unsubscribe: Unsubscribe,
+    ) -> Result<Unsubscribe, MqttBrokerError> {
+        for hook in self.hooks() {
+            match hook.on_client_unsubscribe(connection, &unsubscribe).await {
+                HookResult::Allow | HookResult::Drop => {}
+                HookResult::Modify(data) => unsubscribe = data,
+                HookResult::Deny(reason) => {
+                    return Err(MqttBrokerError::HookDenied(hook.name().to_string(), reason));
+                }
+            }
+        }
+        Ok(unsubscribe)
+    }
+
+    pub async fn on_client_subscribed(&self, connection: &MQTTConnection, subscribe: &Subscribe) {
+        for hook in self.hooks() {
+            hook.on_client_subscribed(connection, subscribe).await;
+        }
+    }
+
+    pub async fn on_client_unsubscribed(
+        &self,
+        connection: &MQTTConnection,
+        unsubscribe: &Unsubscribe,
+    ) {
+        for hook in self.hooks() {
+            hook.on_client_unsubscribed(connection, unsubscribe).await;
+        }
+    }
+
+    // Returns the topic name and the message after all hooks have run, or None when a hook
+    // drops the message.
+    pub async fn on_message_publish(
+        &self,
+        connection: &MQTTConnection,
+        mut topic_name: String,
+        mut publish: Publish,
+        publish_properties: &Option<PublishProperties>,
+    ) -> Result<Option<(String, Publish)>, MqttBrokerError> {
+        for hook in self.hooks() {
+            match hook
+                .on_message_publish(connection, &topic_name, &publish, publish_properties)
+                .await
+            {
+                HookResult::Allow => {}
+                HookResult::Modify(data) => {
+                    if !data.topic.is_empty() && data.topic != topic_name.as_bytes() {
+                        topic_name = String::from_utf8(data.topic.to_vec())?;
+                    }
+                    publish = data;
+                }
+                HookResult::Deny(reason) => {
+                    return Err(MqttBrokerError::HookDenied(hook.name().to_string(), reason));
+                }
+                HookResult::Drop => {
+                    debug!("Hook {} dropped the message", hook.name());
+                    return Ok(None);
+                }
+            }
+        }
+        Ok(Some((topic_name, publish)))
+    }
+
+    pub async fn on_message_delivered(&self, client_id: &str, publish: &Publish) {
+        for hook in self.hooks() {
+            hook.on_message_delivered(client_id, publish).await;
+        }
+    }
+
+    pub async fn on_message_acked(&self, connection: &MQTTConnection, pkid: u16) {
+        for hook in self.hooks() {
+            hook.on_message_acked(connection, pkid).await;
+        }
+    }
+
+    pub async fn on_message_dropped(&self, client_id: &str, topic_name: &str, reason: &str) {
+        for hook in self.hooks() {
+            hook.on_message_dropped(client_id, topic_name, reason).await;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.read().unwrap().is_empty()
+    }
+}
+
+static HOOK_MANAGER: OnceLock<HookManager> = OnceLock::new();
+
+// The hooks are registered in the broker process, before or after the broker is started.
+pub fn hook_manager() -> &'static HookManager {
+    HOOK_MANAGER.get_or_init(HookManager::new)
+}
+
+pub fn register_hook(hook: Arc<dyn BrokerHook>) {
+    hook_manager().register(hook);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use metadata_struct::mqtt::connection::MQTTConnection;
+    use protocol::mqtt::common::{Publish, PublishProperties};
+
+    use super::{BrokerHook, HookManager, HookResult};
+    use crate::handler::error::MqttBrokerError;
+
+    struct TestHook {
+        name: String,
+        result: fn(&Publish) -> HookResult<Publish>,
+    }
+
+    #[async_trait]
+    impl BrokerHook for TestHook {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn on_message_publish(
+            &self,
+            _connection: &MQTTConnection,
+            _topic_name: &str,
+            publish: &Publish,
+            _publish_properties: &Option<PublishProperties>,
+        ) -> HookResult<Publish> {
+            (self.result)(publish)
+        }
+    }
+
+    fn build_hook(name: &str, result: fn(&Publish) -> HookResult<Publish>) -> Arc<TestHook> {
+        Arc::new(TestHook {
+            name: name.to_string(),
+            result,
+        })
+    }
+
+    #[test]
+    fn hook_order_test() {
+        let manager = HookManager::new();
+        manager.register(build_hook("h1", |_| HookResult::Allow));
+        manager.register(build_hook("h2", |_| HookResult::Allow));
+        manager.register(build_hook("h3", |_| HookResult::Allow));
+        assert_eq!(manager.hook_names(), vec!["h1", "h2", "h3"]);
+
+        manager.set_order(vec!["h3".to_string(), "h2".to_string()]);
+        assert_eq!(manager.hook_names(), vec!["h3", "h2", "h1"]);
+
+        manager.register(build_hook("h2", |_| HookResult::Allow));
+        manager.unregister("h3");
+        assert_eq!(manager.hook_names(), vec!["h2", "h1"]);
+    }
+
+    #[tokio::test]
+    async fn hook_publish_test() {
+        let manager = HookManager::new();
+        let connection = MQTTConnection::default();
+        let publish = Publish {
+            topic: Bytes::from("t1"),
+            payload: Bytes::from("p1"),
+            ..Default::default()
+        };
+
+        let (topic_name, data) = manager
+            .on_message_publish(&connection, "t1".to_string(), publish.clone(), &None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(topic_name, "t1");
+        assert_eq!(data, publish);
+
+        manager.register(build_hook("enrich", |publish| {
+            let mut publish = publish.clone();
+            publish.topic = Bytes::from("t2");
+            publish.payload = Bytes::from(format!("{}-enriched", publish.payload.len()));
+            HookResult::Modify(publish)
+        }));
+        let (topic_name, data) = manager
+            .on_message_publish(&connection, "t1".to_string(), publish.clone(), &None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(topic_name, "t2");
+        assert_eq!(data.payload, Bytes::from("2-enriched"));
+
+        manager.register(build_hook("audit", |publish| {
+            if publish.topic == "t2" {
+                return HookResult::Deny("forbidden".to_string());
+            }
+            HookResult::Allow
+        }));
+        match manager
+            .on_message_publish(&connection, "t1".to_string(), publish.clone(), &None)
+            .await
+        {
+            Err(MqttBrokerError::HookDenied(name, reason)) => {
+                assert_eq!(name, "audit");
+                assert_eq!(reason, "forbidden");
+            }
+            _ => panic!("the message should be denied by the audit hook"),
+        }
+
+        manager.unregister("audit");
+        manager.register(build_hook("filter", |publish| {
+            if publish.payload.starts_with(b"2-") {
+                return HookResult::Drop;
+            }
+            HookResult::Allow
+        }));
+        assert!(manager
+            .on_message_publish(&connection, "t1".to_string(), publish.clone(), &None)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,439 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common_base::config::broker_mqtt::{broker_mqtt_conf, WebHookServer};
+use common_base::tools::{get_local_ip, now_mills};
+use log::{error, info, warn};
+use metadata_struct::mqtt::connection::MQTTConnection;
+use protocol::mqtt::common::{Publish, Subscribe, Unsubscribe};
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{interval, sleep};
+
+use super::{hook_manager, BrokerHook};
+use crate::handler::error::MqttBrokerError;
+
+pub const WEBHOOK_EVENT_CLIENT_CONNECTED: &str = "client.connected";
+pub const WEBHOOK_EVENT_CLIENT_DISCONNECTED: &str = "client.disconnected";
+pub const WEBHOOK_EVENT_CLIENT_SUBSCRIBED: &str = "client.subscribed";
+pub const WEBHOOK_EVENT_CLIENT_UNSUBSCRIBED: &str = "client.unsubscribed";
+pub const WEBHOOK_EVENT_MESSAGE_DELIVERED: &str = "message.delivered";
+pub const WEBHOOK_EVENT_MESSAGE_ACKED: &str = "message.acked";
+pub const WEBHOOK_EVENT_MESSAGE_DROPPED: &str = "message.dropped";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebHookEvent {
+    pub event: String,
+    pub node: String,
+    pub ts: u128,
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qos: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pkid: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+// A hook that forwards the lifecycle events to an HTTP endpoint. The events are only queued
+// here, they are batched and sent by the sender thread of the endpoint, so a slow endpoint
+// never blocks the client. When the queue is full, the event is discarded.
+pub struct WebHook {
+    name: String,
+    node: String,
+    events: Vec<String>,
+    sender: mpsc::Sender<WebHookEvent>,
+}
+
+impl WebHook {
+    pub fn new(server: &WebHookServer, sender: mpsc::Sender<WebHookEvent>) -> Self {
+        WebHook {
+            name: format!("webhook:{}", server.name),
+            node: get_local_ip(),
+            events: server.events.clone(),
+            sender,
+        }
+    }
+
+    fn is_enabled(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|raw| raw == event)
+    }
+
+    fn build_event(&self, event: &str, client_id: &str) -> WebHookEvent {
+        WebHookEvent {
+            event: event.to_string(),
+            node: self.node.clone(),
+            ts: now_mills(),
+            client_id: client_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn build_connection_event(&self, event: &str, connection: &MQTTConnection) -> WebHookEvent {
+        WebHookEvent {
+            username: Some(connection.login_user.clone()),
+            ip_address: Some(connection.source_ip_addr.clone()),
+            ..self.build_event(event, &connection.client_id)
+        }
+    }
+
+    fn report(&self, event: WebHookEvent) {
+        if let Err(e) = self.sender.try_send(event) {
+            warn!(
+                "{} failed to queue the event, the event is discarded, error message: {}",
+                self.name, e
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl BrokerHook for WebHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn on_client_connected(&self, connection: &MQTTConnection) {
+        if self.is_enabled(WEBHOOK_EVENT_CLIENT_CONNECTED) {
+            self.report(self.build_connection_event(WEBHOOK_EVENT_CLIENT_CONNECTED, connection));
+        }
+    }
+
+    async fn on_client_disconnected(&self, connection: &MQTTConnection) {
+        if self.is_enabled(WEBHOOK_EVENT_CLIENT_DISCONNECTED) {
+            self.report(self.build_connection_event(WEBHOOK_EVENT_CLIENT_DISCONNECTED, connection));
+        }
+    }
+
+    async fn on_client_subscribed(&self, connection: &MQTTConnection, subscribe: &Subscribe) {
+        if !self.is_enabled(WEBHOOK_EVENT_CLIENT_SUBSCRIBED) {
+            return;
+        }
+        for filter in subscribe.filters.iter() {
+            self.report(WebHookEvent {
+                topic: Some(filter.path.clone()),
+                qos: Some(filter.qos.into()),
+                ..self.build_connection_event(WEBHOOK_EVENT_CLIENT_SUBSCRIBED, connection)
+            });
+        }
+    }
+
+    async fn on_client_unsubscribed(&self, connection: &MQTTConnection, unsubscribe: &Unsubscribe) {
+        if !self.is_enabled(WEBHOOK_EVENT_CLIENT_UNSUBSCRIBED) {
+            return;
+        }
+        for path in unsubscribe.filters.iter() {
+            self.report(WebHookEvent {
+                topic: Some(path.clone()),
+                ..self.build_connection_event(WEBHOOK_EVENT_CLIENT_UNSUBSCRIBED, connection)
+            });
+        }
+    }
+
+    async fn on_message_delivered(&self, client_id: &str, publish: &Publish) {
+        if self.is_enabled(WEBHOOK_EVENT_MESSAGE_DELIVERED) {
+            self.report(WebHookEvent {
+                topic: Some(String::from_utf8_lossy(&publish.topic).to_string()),
+                qos: Some(publish.qos.into()),
+                pkid: Some(publish.pkid),
+                ..self.build_event(WEBHOOK_EVENT_MESSAGE_DELIVERED, client_id)
+            });
+        }
+    }
+
+    async fn on_message_acked(&self, connection: &MQTTConnection, pkid: u16) {
+        if self.is_enabled(WEBHOOK_EVENT_MESSAGE_ACKED) {
+            self.report(WebHookEvent {
+                pkid: Some(pkid),
+                ..self.build_event(WEBHOOK_EVENT_MESSAGE_ACKED, &connection.client_id)
+            });
+        }
+    }
+
+    async fn on_message_dropped(&self, client_id: &str, topic_name: &str, reason: &str) {
+        if self.is_enabled(WEBHOOK_EVENT_MESSAGE_DROPPED) {
+            self.report(WebHookEvent {
+                topic: Some(topic_name.to_string()),
+                reason: Some(reason.to_string()),
+                ..self.build_event(WEBHOOK_EVENT_MESSAGE_DROPPED, client_id)
+            });
+        }
+    }
+}
+
+// Registers a webhook for each endpoint in the config and starts its sender thread.
+pub fn start_webhooks(stop_send: broadcast::Sender<bool>) {
+    let conf = broker_mqtt_conf();
+    for server in conf.hook.webhook.clone() {
+        let (sender, receiver) = mpsc::channel(server.queue_size.max(1));
+        hook_manager().register(Arc::new(WebHook::new(&server, sender)));
+        info!("WebHook {} registered, url: {}", server.name, server.url);
+
+        let stop_send = stop_send.clone();
+        tokio::spawn(async move {
+            run_webhook_sender(server, receiver, stop_send).await;
+        });
+    }
+}
+
+// Collects the queued events into batches. A batch is sent when it is full or the batch
+// interval has elapsed, the remaining events are sent when the broker stops.
+pub async fn run_webhook_sender(
+    server: WebHookServer,
+    mut receiver: mpsc::Receiver<WebHookEvent>,
+    stop_send: broadcast::Sender<bool>,
+) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_millis(server.timeout_ms))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            error!(
+                "WebHook {} failed to build the http client, error message: {}",
+                server.name, e
+            );
+            return;
+        }
+    };
+
+    let batch_size = server.batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut ticker = interval(Duration::from_millis(server.batch_interval_ms.max(1)));
+    let mut stop_rx = stop_send.subscribe();
+    loop {
+        select! {
+            val = stop_rx.recv() => {
+                if let Ok(flag) = val {
+                    if flag {
+                        while let Ok(event) = receiver.try_recv() {
+                            batch.push(event);
+                        }
+                        flush_batch(&client, &server, &mut batch).await;
+                        break;
+                    }
+                }
+            }
+            val = receiver.recv() => {
+                match val {
+                    Some(event) => {
+                        batch.push(event);
+                        if batch.len() >= batch_size {
+                            flush_batch(&client, &server, &mut batch).await;
+                        }
+                    }
+                    None => {
+                        flush_batch(&client, &server, &mut batch).await;
+                        break;
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                flush_batch(&client, &server, &mut batch).await;
+            }
+        }
+    }
+}
+
+async fn flush_batch(
+    client: &reqwest::Client,
+    server: &WebHookServer,
+    batch: &mut Vec<WebHookEvent>,
+) {
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = send_batch(client, server, batch).await {
+        error!(
+            "WebHook {} discarded {} events, error message: {}",
+            server.name,
+            batch.len(),
+            e
+        );
+    }
+    batch.clear();
+}
+
+// Sends the batch, a failed request is retried with an exponential backoff until max_retries
+// is exhausted.
+async fn send_batch(
+    client: &reqwest::Client,
+    server: &WebHookServer,
+    batch: &[WebHookEvent],
+) -> Result<(), MqttBrokerError> {
+    let mut attempts = 0;
+    loop {
+        match post_events(client, server, batch).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                attempts += 1;
+                if attempts > server.max_retries {
+                    return Err(e);
+                }
+                warn!(
+                    "WebHook {} request failed, attempts: {}, error message: {}",
+                    server.name, attempts, e
+                );
+                sleep(webhook_backoff(server.retry_backoff_ms, attempts)).await;
+            }
+        }
+    }
+}
+
+async fn post_events(
+    client: &reqwest::Client,
+    server: &WebHookServer,
+    batch: &[WebHookEvent],
+) -> Result<(), MqttBrokerError> {
+    let mut request = client.post(&server.url).json(batch);
+    for (key, value) in server.headers.iter() {
+        request = request.header(key, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| MqttBrokerError::WebHookRequestFailed(server.name.clone(), e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(MqttBrokerError::WebHookRequestFailed(
+            server.name.clone(),
+            format!("response status {}", response.status()),
+        ));
+    }
+    Ok(())
+}
+
+pub fn webhook_backoff(retry_backoff_ms: u64, attempts: u32) -> Duration {
+    let factor = 1u64 << attempts.saturating_sub(1).min(10);
+    Duration::from_millis(retry_backoff_ms.saturating_mul(factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use common_base::config::broker_mqtt::WebHookServer;
+    use metadata_struct::mqtt::connection::MQTTConnection;
+    use tokio::net::TcpListener;
+    use tokio::sync::{broadcast, mpsc};
+    use tokio::time::sleep;
+
+    use super::{
+        run_webhook_sender, webhook_backoff, WebHook, WebHookEvent, WEBHOOK_EVENT_CLIENT_CONNECTED,
+        WEBHOOK_EVENT_CLIENT_DISCONNECTED,
+    };
+    use crate::hook::BrokerHook;
+
+    #[derive(Clone, Default)]
+    struct Endpoint {
+        requests: Arc<AtomicU32>,
+        batches: Arc<Mutex<Vec<Vec<WebHookEvent>>>>,
+    }
+
+    // The first request fails, so the batch is only received after a retry.
+    async fn receive_events(
+        State(endpoint): State<Endpoint>,
+        Json(events): Json<Vec<WebHookEvent>>,
+    ) -> StatusCode {
+        if endpoint.requests.fetch_add(1, Ordering::SeqCst) == 0 {
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+        endpoint.batches.lock().unwrap().push(events);
+        StatusCode::OK
+    }
+
+    #[test]
+    fn webhook_backoff_test() {
+        assert_eq!(webhook_backoff(100, 1), Duration::from_millis(100));
+        assert_eq!(webhook_backoff(100, 3), Duration::from_millis(400));
+        assert_eq!(webhook_backoff(100, 30), Duration::from_millis(102400));
+    }
+
+    #[tokio::test]
+    async fn webhook_batch_test() {
+        let endpoint = Endpoint::default();
+        let app = Router::new()
+            .route("/events", post(receive_events))
+            .with_state(endpoint.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let server = WebHookServer {
+            name: "registry".to_string(),
+            url: format!("http://{}/events", addr),
+            events: vec![WEBHOOK_EVENT_CLIENT_CONNECTED.to_string()],
+            batch_size: 2,
+            batch_interval_ms: 60000,
+            max_retries: 3,
+            retry_backoff_ms: 10,
+            timeout_ms: 1000,
+            queue_size: 10,
+            ..Default::default()
+        };
+        let (sender, receiver) = mpsc::channel(server.queue_size);
+        let (stop_send, _) = broadcast::channel(1);
+        let hook = WebHook::new(&server, sender);
+        tokio::spawn(run_webhook_sender(server, receiver, stop_send.clone()));
+
+        let connection = MQTTConnection {
+            client_id: "c1".to_string(),
+            login_user: "u1".to_string(),
+            ..Default::default()
+        };
+        hook.on_client_connected(&connection).await;
+        // filtered out by the events of the endpoint
+        hook.on_client_disconnected(&connection).await;
+        hook.on_client_connected(&connection).await;
+
+        let mut batches = Vec::new();
+        for _ in 0..100 {
+            batches = endpoint.batches.lock().unwrap().clone();
+            if !batches.is_empty() {
+                break;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(endpoint.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 2);
+        assert!(batches[0]
+            .iter()
+            .all(|event| event.event == WEBHOOK_EVENT_CLIENT_CONNECTED
+                && event.client_id == "c1"
+                && event.username == Some("u1".to_string())));
+        assert!(batches[0]
+            .iter()
+            .all(|event| event.event != WEBHOOK_EVENT_CLIENT_DISCONNECTED));
+        stop_send.send(true).unwrap();
+    }
+}
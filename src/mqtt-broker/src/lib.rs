@@ -27,10 +27,22 @@
 use handler::acl::UpdateAclCache;
 use handler::cache::CacheManager;
 use handler::cache_update::load_metadata_cache;
+use handler::command::Command;
+use handler::drain::{drain_node, NodeDrain};
 use handler::heartbreat::{register_node, report_heartbeat};
 use handler::keep_alive::ClientKeepAlive;
+use handler::pkid::idempotent_storage_adapter;
+use handler::publish::MessagePublisher;
 use handler::sub_parse_topic::start_parse_subscribe_by_new_topic_thread;
+use handler::tenant::report_tenant_usage;
 use handler::user::{init_system_user, UpdateUserCache};
+use hook::exhook::start_exhook_providers;
+use hook::hook_manager;
+use hook::webhook::start_webhooks;
+use idempotent_message::persist::{
+    start_idempotent_expire_thread, start_idempotent_persist_thread, start_idempotent_sync_thread,
+};
+use idempotent_message::IdempotentManager;
 use lazy_static::lazy_static;
 use log::{error, info};
 use observability::start_opservability;
@@ -38,14 +50,12 @@
 use security::AuthDriver;
 use server::connection_manager::ConnectionManager;
 use server::grpc::server::GrpcServer;
-use server::tcp::server::start_tcp_server;
-use server::websocket::server::{websocket_server, websockets_server, WebSocketServerState};
+use server::listener::ListenerManager;
 use storage::cluster::ClusterStorage;
 use storage_adapter::memory::MemoryStorageAdapter;
 // use storage_adapter::mysql::MySQLStorageAdapter;
 // use storage_adapter::rocksdb::RocksDBStorageAdapter;
 use crate::handler::flapping_detect::UpdateFlappingDetectCache;
-use crate::server::quic::server::start_quic_server;
 use storage_adapter::storage::StorageAdapter;
 use storage_adapter::StorageType;
 use subscribe::exclusive_push::ExclusivePush;
@@ -64,14 +74,18 @@
 pub mod admin;
 pub mod bridge;
 pub mod handler;
+pub mod hook;
 pub mod observability;
+pub mod rule_engine;
 pub mod security;
 pub mod server;
 pub mod storage;
 mod subscribe;
+pub mod wasm_plugin;
 
 pub fn start_mqtt_broker_server(stop_send: broadcast::Sender<bool>) {
     let conf = broker_mqtt_conf();
+    hook_manager().set_order(conf.hook.order.clone());
     let client_pool: Arc<ClientPool> = Arc::new(ClientPool::new(5));
     let metadata_cache = Arc::new(CacheManager::new(
         client_pool.clone(),
@@ -123,7 +137,10 @@ pub struct MqttBroker<S> {
     connector_manager: Arc<ConnectorManager>,
     auth_driver: Arc<AuthDriver>,
     delay_message_manager: Arc<DelayMessageManager<S>>,
+    idempotent_manager: Arc<IdempotentManager>,
     schema_manager: Arc<SchemaRegisterManager>,
+    listener_manager: Arc<ListenerManager<S>>,
+    message_publisher: MessagePublisher<S>,
 }
 
 impl<S> MqttBroker<S>
@@ -136,6 +153,8 @@ pub fn new(
         cache_manager: Arc<CacheManager>,
     ) -> Self {
         let conf = broker_mqtt_conf();
+        let storage_type = StorageType::from_str(conf.storage.storage_type.as_str())
+            .expect("Storage type not supported");
         let runtime = create_runtime(
             "storage-engine-server-runtime",
             conf.system.runtime_worker_threads,
@@ -150,7 +169,47 @@ pub fn new(
             3,
             message_storage_adapter.clone(),
         ));
+        let idempotent_manager = Arc::new(IdempotentManager::new(
+            conf.cluster_name.clone(),
+            conf.broker_id.to_string(),
+            conf.idempotent_message.ttl_sec,
+            idempotent_storage_adapter(
+                &storage_type,
+                &message_storage_adapter,
+                &client_pool,
+                conf.placement_center.clone(),
+            ),
+        ));
         let schema_manager = Arc::new(SchemaRegisterManager::new());
+        let message_publisher = MessagePublisher::new(
+            cache_manager.clone(),
+            message_storage_adapter.clone(),
+            delay_message_manager.clone(),
+            idempotent_manager.clone(),
+            subscribe_manager.clone(),
+            schema_manager.clone(),
+            client_pool.clone(),
+            connector_manager.clone(),
+        );
+        let command = Command::new(
+            cache_manager.clone(),
+            message_storage_adapter.clone(),
+            delay_message_manager.clone(),
+            idempotent_manager.clone(),
+            subscribe_manager.clone(),
+            client_pool.clone(),
+            connection_manager.clone(),
+            schema_manager.clone(),
+            auth_driver.clone(),
+            connector_manager.clone(),
+        );
+        let listener_manager = Arc::new(ListenerManager::new(
+            command,
+            cache_manager.clone(),
+            connection_manager.clone(),
+            subscribe_manager.clone(),
+            client_pool.clone(),
+        ));
         MqttBroker {
             runtime,
             cache_manager,
@@ -161,7 +220,10 @@ pub fn new(
             connection_manager,
             auth_driver,
             delay_message_manager,
+            idempotent_manager,
             schema_manager,
+            listener_manager,
+            message_publisher,
         }
     }
 
@@ -170,20 +232,22 @@ pub fn start(&self, stop_send: broadcast::Sender<bool>) {
 
         self.register_node();
         self.start_cluster_heartbeat_report(stop_send.clone());
+        self.start_tenant_usage_report(stop_send.clone());
 
         self.start_push_server(stop_send.clone());
 
         self.start_grpc_server();
 
         self.start_mqtt_server(stop_send.clone());
-        self.start_quic_server(stop_send.clone());
-        self.start_websocket_server(stop_send.clone());
         self.start_keep_alive_thread(stop_send.clone());
         self.start_delay_message_thread();
+        self.start_idempotent_message_thread(stop_send.clone());
         self.start_update_cache_thread(stop_send.clone());
         self.start_system_topic_thread(stop_send.clone());
         self.start_prometheus();
         self.start_connector_thread(stop_send.clone());
+        self.start_exhook_providers(stop_send.clone());
+        self.start_webhooks(stop_send.clone());
         self.awaiting_stop(stop_send);
     }
 
@@ -193,28 +257,9 @@ fn start_tracer_provider(&self) {
         });
     }
     fn start_mqtt_server(&self, stop_send: broadcast::Sender<bool>) {
-        let cache = self.cache_manager.clone();
-        let message_storage_adapter = self.message_storage_adapter.clone();
-        let subscribe_manager = self.subscribe_manager.clone();
-        let client_pool = self.client_pool.clone();
-        let connection_manager = self.connection_manager.clone();
-        let auth_driver = self.auth_driver.clone();
-        let delay_message_manager = self.delay_message_manager.clone();
-        let schema_manager = self.schema_manager.clone();
-
+        let listener_manager = self.listener_manager.clone();
         self.runtime.spawn(async move {
-            start_tcp_server(
-                subscribe_manager,
-                cache,
-                connection_manager,
-                message_storage_adapter,
-                delay_message_manager,
-                schema_manager,
-                client_pool,
-                stop_send,
-                auth_driver,
-            )
-            .await
+            listener_manager.start_all(stop_send).await;
         });
     }
 
@@ -227,31 +272,6 @@ fn start_prometheus(&self) {
         }
     }
 
-    fn start_quic_server(&self, stop_send: broadcast::Sender<bool>) {
-        let cache = self.cache_manager.clone();
-        let message_storage_adapter = self.message_storage_adapter.clone();
-        let subscribe_manager = self.subscribe_manager.clone();
-        let client_pool = self.client_pool.clone();
-        let connection_manager = self.connection_manager.clone();
-        let auth_driver = self.auth_driver.clone();
-        let delay_message_manager = self.delay_message_manager.clone();
-        let schema_manager = self.schema_manager.clone();
-        self.runtime.spawn(async move {
-            start_quic_server(
-                subscribe_manager,
-                cache,
-                connection_manager,
-                message_storage_adapter,
-                delay_message_manager,
-                client_pool,
-                stop_send,
-                auth_driver,
-                schema_manager,
-            )
-            .await
-        });
-    }
-
     fn start_grpc_server(&self) {
         let conf = broker_mqtt_conf();
         let server = GrpcServer::new(
@@ -263,6 +283,9 @@ fn start_grpc_server(&self) {
             self.schema_manager.clone(),
             self.client_pool.clone(),
             self.message_storage_adapter.clone(),
+            self.listener_manager.clone(),
+            self.delay_message_manager.clone(),
+            self.message_publisher.clone(),
         );
         self.runtime.spawn(async move {
             match server.start().await {
@@ -274,37 +297,6 @@ fn start_grpc_server(&self) {
         });
     }
 
-    fn start_websocket_server(&self, stop_send: broadcast::Sender<bool>) {
-        let ws_state = WebSocketServerState::new(
-            self.subscribe_manager.clone(),
-            self.cache_manager.clone(),
-            self.connection_manager.clone(),
-            self.message_storage_adapter.clone(),
-            self.delay_message_manager.clone(),
-            self.schema_manager.clone(),
-            self.client_pool.clone(),
-            self.auth_driver.clone(),
-            stop_send.clone(),
-        );
-        self.runtime
-            .spawn(async move { websocket_server(ws_state).await });
-
-        let ws_state = WebSocketServerState::new(
-            self.subscribe_manager.clone(),
-            self.cache_manager.clone(),
-            self.connection_manager.clone(),
-            self.message_storage_adapter.clone(),
-            self.delay_message_manager.clone(),
-            self.schema_manager.clone(),
-            self.client_pool.clone(),
-            self.auth_driver.clone(),
-            stop_send.clone(),
-        );
-
-        self.runtime
-            .spawn(async move { websockets_server(ws_state).await });
-    }
-
     fn start_cluster_heartbeat_report(&self, stop_send: broadcast::Sender<bool>) {
         let client_pool = self.client_pool.clone();
         self.runtime.spawn(async move {
@@ -312,14 +304,43 @@ fn start_cluster_heartbeat_report(&self, stop_send: broadcast::Sender<bool>) {
         });
     }
 
+    fn start_tenant_usage_report(&self, stop_send: broadcast::Sender<bool>) {
+        let cache_manager = self.cache_manager.clone();
+        let client_pool = self.client_pool.clone();
+        self.runtime.spawn(async move {
+            report_tenant_usage(&cache_manager, &client_pool, stop_send).await;
+        });
+    }
+
     fn start_connector_thread(&self, stop_send: broadcast::Sender<bool>) {
+        let cache_manager = self.cache_manager.clone();
+        let client_pool = self.client_pool.clone();
         let message_storage = self.message_storage_adapter.clone();
         let connector_manager = self.connector_manager.clone();
+        let message_publisher = self.message_publisher.clone();
         self.runtime.spawn(async move {
-            start_connector_thread(message_storage, connector_manager, stop_send).await;
+            start_connector_thread(
+                cache_manager,
+                client_pool,
+                message_storage,
+                connector_manager,
+                message_publisher,
+                stop_send,
+            )
+            .await;
         });
     }
 
+    fn start_exhook_providers(&self, stop_send: broadcast::Sender<bool>) {
+        let _guard = self.runtime.enter();
+        start_exhook_providers(self.client_pool.clone(), stop_send);
+    }
+
+    fn start_webhooks(&self, stop_send: broadcast::Sender<bool>) {
+        let _guard = self.runtime.enter();
+        start_webhooks(stop_send);
+    }
+
     fn start_push_server(&self, stop_send: broadcast::Sender<bool>) {
         let subscribe_manager = self.subscribe_manager.clone();
         let client_pool = self.client_pool.clone();
@@ -340,6 +361,7 @@ fn start_push_server(&self, stop_send: broadcast::Sender<bool>) {
             self.cache_manager.clone(),
             self.subscribe_manager.clone(),
             self.connection_manager.clone(),
+            self.client_pool.clone(),
         );
 
         self.runtime.spawn(async move {
@@ -351,6 +373,7 @@ fn start_push_server(&self, stop_send: broadcast::Sender<bool>) {
             self.message_storage_adapter.clone(),
             self.connection_manager.clone(),
             self.cache_manager.clone(),
+            self.client_pool.clone(),
         );
 
         self.runtime.spawn(async move {
@@ -413,6 +436,22 @@ fn start_delay_message_thread(&self) {
         });
     }
 
+    fn start_idempotent_message_thread(&self, stop_send: broadcast::Sender<bool>) {
+        let idempotent_manager = self.idempotent_manager.clone();
+        self.runtime.spawn(async move {
+            if let Err(e) = idempotent_manager.init().await {
+                panic!("{}", e.to_string());
+            }
+            start_idempotent_persist_thread(idempotent_manager.clone(), stop_send.clone());
+            start_idempotent_sync_thread(
+                idempotent_manager.clone(),
+                broker_mqtt_conf().idempotent_message.sync_interval_ms,
+                stop_send.clone(),
+            );
+            start_idempotent_expire_thread(idempotent_manager, stop_send);
+        });
+    }
+
     fn start_update_cache_thread(&self, stop_send: broadcast::Sender<bool>) {
         let update_user_cache = UpdateUserCache::new(stop_send.clone(), self.auth_driver.clone());
 
@@ -457,12 +496,18 @@ pub fn awaiting_stop(&self, stop_send: broadcast::Sender<bool>) {
         // Wait for the stop signal
         self.runtime.block_on(async move {
             signal::ctrl_c().await.expect("failed to listen for event");
+            info!(
+                "{}",
+                "When ctrl + c is received, the service starts to stop"
+            );
+
+            // The clients are drained while the rest of the broker still runs, the listeners
+            // stop accepting first so that the load balancer moves new clients elsewhere.
+            self.listener_manager.stop_accepting();
+            self.drain_connections().await;
+
             match stop_send.send(true) {
                 Ok(_) => {
-                    info!(
-                        "{}",
-                        "When ctrl + c is received, the service starts to stop"
-                    );
                     self.stop_server().await;
                 }
                 Err(_) => {
@@ -511,6 +556,47 @@ async fn stop_server(&self) {
                 error!("{}", e.to_string());
             }
         }
-        self.connection_manager.close_all_connect().await;
+    }
+
+    async fn drain_connections(&self) {
+        let config = broker_mqtt_conf();
+        let drain = match NodeDrain::build(
+            &self.client_pool,
+            false,
+            &config.drain.server_reference,
+            config.drain.batch_size,
+            config.drain.batch_interval_ms,
+        )
+        .await
+        {
+            Ok(drain) => drain,
+            Err(e) => {
+                // Still disconnect the clients in batches, just without a server reference
+                error!(
+                    "Failed to get the server reference of the node drain, error message: {}",
+                    e
+                );
+                NodeDrain::new(
+                    false,
+                    Vec::new(),
+                    config.drain.batch_size,
+                    config.drain.batch_interval_ms,
+                )
+            }
+        };
+        // A drain requested through the admin API is cancelled and replaced by the drain of
+        // the shutdown.
+        if let Some(running) = self.cache_manager.remove_node_drain() {
+            running.cancel();
+        }
+        self.cache_manager.try_set_node_drain(drain.clone());
+        drain_node(
+            &self.cache_manager,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &self.client_pool,
+            drain,
+        )
+        .await;
     }
 }
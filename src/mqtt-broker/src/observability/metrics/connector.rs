@@ -0,0 +1,114 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct ConnectorLabels {
+    connector: String,
+}
+
+common_base::register_counter_metric!(
+    CONNECTOR_SUCCESS_NUM,
+    "connector_success_num",
+    "The number of records delivered by the connector",
+    ConnectorLabels
+);
+
+common_base::register_counter_metric!(
+    CONNECTOR_ERROR_NUM,
+    "connector_error_num",
+    "The number of failed delivery attempts of the connector",
+    ConnectorLabels
+);
+
+common_base::register_counter_metric!(
+    CONNECTOR_DEAD_LETTER_NUM,
+    "connector_dead_letter_num",
+    "The number of records the connector wrote to its dead letter topic",
+    ConnectorLabels
+);
+
+common_base::register_gauge_metric!(
+    CONNECTOR_DELIVERY_DELAY_SECONDS,
+    "connector_delivery_delay_seconds",
+    "The seconds between the newest delivered record being written and being delivered",
+    ConnectorLabels
+);
+
+common_base::register_gauge_metric!(
+    CONNECTOR_LAG,
+    "connector_lag",
+    "The number of records of the topic of the connector after its committed offset",
+    ConnectorLabels
+);
+
+pub fn metrics_connector_success_inc(connector: &str, num: u64) {
+    let labels = ConnectorLabels {
+        connector: connector.to_string(),
+    };
+    common_base::counter_metric_inc_by!(CONNECTOR_SUCCESS_NUM, labels, num)
+}
+
+pub fn metrics_connector_error_inc(connector: &str) {
+    let labels = ConnectorLabels {
+        connector: connector.to_string(),
+    };
+    common_base::counter_metric_inc!(CONNECTOR_ERROR_NUM, labels)
+}
+
+pub fn metrics_connector_dead_letter_inc(connector: &str) {
+    let labels = ConnectorLabels {
+        connector: connector.to_string(),
+    };
+    common_base::counter_metric_inc!(CONNECTOR_DEAD_LETTER_NUM, labels)
+}
+
+pub fn metrics_connector_delivery_delay_set(connector: &str, delay: u64) {
+    let labels = ConnectorLabels {
+        connector: connector.to_string(),
+    };
+    common_base::gauge_metric_set!(CONNECTOR_DELIVERY_DELAY_SECONDS, labels, delay as i64);
+}
+
+pub fn metrics_connector_lag_set(connector: &str, lag: u64) {
+    let labels = ConnectorLabels {
+        connector: connector.to_string(),
+    };
+    common_base::gauge_metric_set!(CONNECTOR_LAG, labels, lag as i64);
+}
+
+// (success, error, dead_letter, delivery_delay, lag) of the connector on this node
+pub fn get_connector_metrics(connector: &str) -> (u64, u64, u64, u64, u64) {
+    let labels = ConnectorLabels {
+        connector: connector.to_string(),
+    };
+    let mut success = 0;
+    common_base::counter_metric_get!(CONNECTOR_SUCCESS_NUM, labels, success);
+    let mut error = 0;
+    common_base::counter_metric_get!(CONNECTOR_ERROR_NUM, labels, error);
+    let mut dead_letter = 0;
+    common_base::counter_metric_get!(CONNECTOR_DEAD_LETTER_NUM, labels, dead_letter);
+    let mut delivery_delay = 0;
+    common_base::gauge_metric_get!(CONNECTOR_DELIVERY_DELAY_SECONDS, labels, delivery_delay);
+    let mut lag = 0;
+    common_base::gauge_metric_get!(CONNECTOR_LAG, labels, lag);
+    (
+        success,
+        error,
+        dead_letter,
+        delivery_delay.max(0) as u64,
+        lag.max(0) as u64,
+    )
+}
@@ -496,6 +496,7 @@ fn calc_mqtt_packet_test() {
             connection_stop_sx: None,
             connection_id: 100,
             protocol: Some(MqttProtocol::Mqtt3),
+            listener: None,
         };
         let ty = NetworkConnectionType::Tcp;
         record_received_metrics(&nc, &mp, &ty);
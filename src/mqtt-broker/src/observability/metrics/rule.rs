@@ -0,0 +1,97 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct RuleLabels {
+    rule: String,
+}
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct RuleActionLabels {
+    rule: String,
+    action: String,
+}
+
+common_base::register_counter_metric!(
+    RULE_MATCHED_NUM,
+    "rule_matched_num",
+    "The number of messages whose topic matched the FROM of the rule",
+    RuleLabels
+);
+
+common_base::register_counter_metric!(
+    RULE_PASSED_NUM,
+    "rule_passed_num",
+    "The number of messages that passed the WHERE of the rule",
+    RuleLabels
+);
+
+common_base::register_counter_metric!(
+    RULE_FAILED_NUM,
+    "rule_failed_num",
+    "The number of messages that failed to be evaluated by the rule",
+    RuleLabels
+);
+
+common_base::register_counter_metric!(
+    RULE_ACTION_FAILED_NUM,
+    "rule_action_failed_num",
+    "The number of failed actions of the rule",
+    RuleActionLabels
+);
+
+pub fn metrics_rule_matched_inc(rule: &str) {
+    let labels = RuleLabels {
+        rule: rule.to_string(),
+    };
+    common_base::counter_metric_inc!(RULE_MATCHED_NUM, labels)
+}
+
+pub fn metrics_rule_passed_inc(rule: &str) {
+    let labels = RuleLabels {
+        rule: rule.to_string(),
+    };
+    common_base::counter_metric_inc!(RULE_PASSED_NUM, labels)
+}
+
+pub fn metrics_rule_failed_inc(rule: &str) {
+    let labels = RuleLabels {
+        rule: rule.to_string(),
+    };
+    common_base::counter_metric_inc!(RULE_FAILED_NUM, labels)
+}
+
+pub fn metrics_rule_action_failed_inc(rule: &str, action: &str) {
+    let labels = RuleActionLabels {
+        rule: rule.to_string(),
+        action: action.to_string(),
+    };
+    common_base::counter_metric_inc!(RULE_ACTION_FAILED_NUM, labels)
+}
+
+// (matched, passed, failed) of the rule on this node
+pub fn get_rule_metrics(rule: &str) -> (u64, u64, u64) {
+    let labels = RuleLabels {
+        rule: rule.to_string(),
+    };
+    let mut matched = 0;
+    common_base::counter_metric_get!(RULE_MATCHED_NUM, labels, matched);
+    let mut passed = 0;
+    common_base::counter_metric_get!(RULE_PASSED_NUM, labels, passed);
+    let mut failed = 0;
+    common_base::counter_metric_get!(RULE_FAILED_NUM, labels, failed);
+    (matched, passed, failed)
+}
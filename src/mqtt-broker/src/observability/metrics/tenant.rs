@@ -0,0 +1,78 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct TenantLabels {
+    tenant: String,
+}
+
+#[derive(Eq, Hash, Clone, EncodeLabelSet, Debug, PartialEq)]
+struct TenantQuotaLabels {
+    tenant: String,
+    quota: String,
+}
+
+common_base::register_gauge_metric!(
+    TENANT_CONNECTION_NUM,
+    "tenant_connection_num",
+    "The number of connections of the tenant",
+    TenantLabels
+);
+
+common_base::register_counter_metric!(
+    TENANT_PUBLISH_MESSAGE_NUM,
+    "tenant_publish_message_num",
+    "The number of messages published by the tenant",
+    TenantLabels
+);
+
+common_base::register_counter_metric!(
+    TENANT_QUOTA_EXCEEDED_NUM,
+    "tenant_quota_exceeded_num",
+    "The number of requests rejected because the tenant exceeded a quota",
+    TenantQuotaLabels
+);
+
+pub fn metrics_tenant_connection_num(tenant: &str, num: i64) {
+    let labels = TenantLabels {
+        tenant: tenant.to_string(),
+    };
+    common_base::gauge_metric_inc_by!(TENANT_CONNECTION_NUM, labels, num);
+}
+
+pub fn metrics_tenant_publish_message_inc(tenant: &str) {
+    let labels = TenantLabels {
+        tenant: tenant.to_string(),
+    };
+    common_base::counter_metric_inc!(TENANT_PUBLISH_MESSAGE_NUM, labels)
+}
+
+pub fn metrics_tenant_quota_exceeded_inc(tenant: &str, quota: &str) {
+    let labels = TenantQuotaLabels {
+        tenant: tenant.to_string(),
+        quota: quota.to_string(),
+    };
+    common_base::counter_metric_inc!(TENANT_QUOTA_EXCEEDED_NUM, labels)
+}
+
+pub fn get_tenant_publish_message_num(tenant: &str) -> u64 {
+    let labels = TenantLabels {
+        tenant: tenant.to_string(),
+    };
+    let mut res = 0;
+    common_base::counter_metric_get!(TENANT_PUBLISH_MESSAGE_NUM, labels, res);
+    res
+}
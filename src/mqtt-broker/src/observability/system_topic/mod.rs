@@ -109,6 +109,7 @@ pub async fn try_init_system_topic(&self) {
             let new_topic_name = replace_topic_name(topic_name);
             match try_init_topic(
                 &new_topic_name,
+                "",
                 &self.metadata_cache,
                 &self.message_storage_adapter,
                 &self.client_pool,
@@ -156,6 +157,7 @@ pub(crate) async fn write_topic_data<S>(
 {
     match try_init_topic(
         &topic_name,
+        "",
         &metadata_cache.clone(),
         &message_storage_adapter.clone(),
         &client_pool.clone(),
@@ -0,0 +1,250 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use metadata_struct::adapter::record::Record;
+use metadata_struct::mqtt::bridge::connector_type::ConnectorType;
+use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::message::MqttMessage;
+use metadata_struct::mqtt::rule::MqttRuleAction;
+use protocol::mqtt::common::{qos, Publish, QoS};
+use serde_json::Value;
+use storage_adapter::storage::StorageAdapter;
+
+use super::eval::{get_path, value_to_string};
+use super::sql::PathSegment;
+use crate::bridge::manager::ConnectorManager;
+use crate::bridge::runtime::{init_rule_output_shard, rule_output_shard_name};
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::message::build_message_expire;
+use crate::handler::publish::MessagePublisher;
+use crate::handler::tenant::tenant_mountpoint;
+use crate::handler::topic::mount_publish_topic;
+use crate::storage::message::MessageStorage;
+
+// Replaces the ${field} placeholders with the fields of the rule output,
+// nested fields are separated by dots, e.g. ${payload.device}.
+pub fn render_template(template: &str, output: &Value) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let path: Vec<PathSegment> = rest[start + 2..start + len]
+            .split('.')
+            .map(|key| PathSegment::Key(key.to_string()))
+            .collect();
+        if let Some(value) = get_path(output, &path) {
+            result.push_str(&value_to_string(value));
+        }
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+// Renders a JSON template so that the result stays valid JSON: a placeholder inside a string
+// is replaced with the escaped text of the field, a placeholder outside of a string with the
+// field as a JSON value, null when the field does not exist.
+// e.g. {"device":"${clientid}","data":${payload}}
+pub fn render_json_template(template: &str, output: &Value) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut rest = template;
+    while let Some(ch) = rest.chars().next() {
+        if ch == '$' && !escaped && rest.starts_with("${") {
+            if let Some(len) = rest.find('}') {
+                let path: Vec<PathSegment> = rest[2..len]
+                    .split('.')
+                    .map(|key| PathSegment::Key(key.to_string()))
+                    .collect();
+                let value = get_path(output, &path);
+                if in_string {
+                    let text = value.map(value_to_string).unwrap_or_default();
+                    let quoted = Value::String(text).to_string();
+                    result.push_str(&quoted[1..quoted.len() - 1]);
+                } else {
+                    result.push_str(&value.cloned().unwrap_or(Value::Null).to_string());
+                }
+                rest = &rest[len + 1..];
+                continue;
+            }
+        }
+
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+        } else if ch == '"' {
+            in_string = true;
+        }
+        result.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    result
+}
+
+fn build_publish(topic_name: &str, qos: QoS, retain: bool, output: &Value) -> Publish {
+    Publish {
+        dup: false,
+        qos,
+        pkid: 0,
+        retain,
+        topic: Bytes::from(topic_name.to_owned()),
+        payload: Bytes::from(output.to_string()),
+    }
+}
+
+// Publishes the rule output as JSON to the target topic through the publish path of the
+// clients, as the client that published the original message: its ACL and tenant apply, and
+// retained messages are stored. The message is not evaluated by the rule engine again.
+pub async fn republish<S>(
+    cache_manager: &Arc<CacheManager>,
+    message_publisher: &MessagePublisher<S>,
+    connection: &MQTTConnection,
+    action: &MqttRuleAction,
+    output: &Value,
+) -> Result<(), MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    // the target is set by the rule, so it is not mounted under the mountpoint of the client,
+    // only under its tenant
+    let topic_name = mount_publish_topic(
+        cache_manager,
+        &tenant_mountpoint(&connection.tenant),
+        render_template(&action.target, output),
+    )?;
+    let publish = build_publish(
+        &topic_name,
+        qos(action.qos).unwrap_or(QoS::AtMostOnce),
+        action.retain,
+        output,
+    );
+    message_publisher
+        .publish_without_rules(connection, topic_name, publish, None)
+        .await
+        .map_err(|e| e.error)?;
+    Ok(())
+}
+
+// Writes the rule output into the rule output shard of the connector, which the sink reads
+// next to the topic of the connector.
+pub async fn send_to_connector<S>(
+    cache_manager: &Arc<CacheManager>,
+    connector_manager: &Arc<ConnectorManager>,
+    message_storage_adapter: &Arc<S>,
+    client_id: &str,
+    action: &MqttRuleAction,
+    output: &Value,
+) -> Result<(), MqttBrokerError>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    let Some(connector) = connector_manager.get_connector(&action.target) else {
+        return Err(MqttBrokerError::ConnectorNotExists(action.target.clone()));
+    };
+    if connector.connector_type.is_source() || connector.connector_type == ConnectorType::S3 {
+        return Err(MqttBrokerError::CommonError(format!(
+            "Connector {} of type {} does not receive the output of rules",
+            connector.connector_name, connector.connector_type
+        )));
+    }
+
+    let topic_name = cache_manager
+        .topic_id_name
+        .get(&connector.topic_id)
+        .map(|name| name.clone())
+        .unwrap_or_default();
+    let record = build_record(cache_manager, client_id, &topic_name, output)?;
+
+    let shard_name = rule_output_shard_name(&connector.connector_name);
+    let message_storage = MessageStorage::new(message_storage_adapter.clone());
+    if message_storage
+        .append_topic_message(&shard_name, vec![record.clone()])
+        .await
+        .is_err()
+    {
+        // the shard is created when the connector starts, it may not be running yet
+        init_rule_output_shard(message_storage_adapter, &connector.connector_name).await?;
+        message_storage
+            .append_topic_message(&shard_name, vec![record])
+            .await?;
+    }
+    Ok(())
+}
+
+fn build_record(
+    cache_manager: &Arc<CacheManager>,
+    client_id: &str,
+    topic_name: &str,
+    output: &Value,
+) -> Result<Record, MqttBrokerError> {
+    let publish = build_publish(topic_name, QoS::AtMostOnce, false, output);
+    let message_expire = build_message_expire(cache_manager, &None);
+    MqttMessage::build_record(client_id, &publish, &None, message_expire)
+        .ok_or(MqttBrokerError::FailedToBuildMessage)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{render_json_template, render_template};
+
+    #[test]
+    fn render_template_test() {
+        let output = json!({"t": 31, "clientid": "c1", "payload": {"room": "r1"}});
+        assert_eq!(render_template("alarm/${clientid}", &output), "alarm/c1");
+        assert_eq!(
+            render_template("alarm/${payload.room}/${t}", &output),
+            "alarm/r1/31"
+        );
+        assert_eq!(render_template("alarm/${missing}/x", &output), "alarm//x");
+        assert_eq!(
+            render_template("alarm/${clientid", &output),
+            "alarm/${clientid"
+        );
+        assert_eq!(render_template("alarm", &output), "alarm");
+    }
+
+    #[test]
+    fn render_json_template_test() {
+        let output = json!({"clientid": "c\"1", "payload": "say \"hi\"\n", "t": 31});
+        let body = render_json_template(
+            r#"{"device":"${clientid}","msg":"${payload}","data":${payload},"t":${t},"x":${missing}}"#,
+            &output,
+        );
+        assert_eq!(
+            body,
+            r#"{"device":"c\"1","msg":"say \"hi\"\n","data":"say \"hi\"\n","t":31,"x":null}"#
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&body).is_ok());
+
+        assert_eq!(
+            render_json_template(r#"{"k":"a\"${t}"}"#, &output),
+            r#"{"k":"a\"31"}"#
+        );
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+
+use serde_json::{Map, Number, Value};
+
+use super::function::call_function;
+use super::sql::{BinaryOp, Expr, PathSegment, RuleSql, SelectField};
+use crate::handler::error::MqttBrokerError;
+
+fn eval_error(msg: String) -> MqttBrokerError {
+    MqttBrokerError::RuleEvalError(msg)
+}
+
+// Returns None when the WHERE condition does not hold, otherwise the selected fields.
+pub fn eval_rule_sql(sql: &RuleSql, context: &Value) -> Result<Option<Value>, MqttBrokerError> {
+    if let Some(condition) = &sql.condition {
+        if !is_true(&eval_expr(condition, context)?)? {
+            return Ok(None);
+        }
+    }
+
+    let mut output = Map::new();
+    for field in sql.fields.iter() {
+        match field {
+            SelectField::All => {
+                if let Value::Object(map) = context {
+                    output.extend(map.clone());
+                }
+            }
+            SelectField::Expr { expr, alias } => {
+                output.insert(alias.clone(), eval_expr(expr, context)?);
+            }
+        }
+    }
+    Ok(Some(Value::Object(output)))
+}
+
+pub fn eval_expr(expr: &Expr, context: &Value) -> Result<Value, MqttBrokerError> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Field(path) => Ok(get_path(context, path).cloned().unwrap_or(Value::Null)),
+        Expr::Not(expr) => Ok(Value::Bool(!is_true(&eval_expr(expr, context)?)?)),
+        Expr::Neg(expr) => match eval_expr(expr, context)? {
+            Value::Null => Ok(Value::Null),
+            Value::Number(number) => {
+                if let Some(value) = number.as_i64().and_then(|v| v.checked_neg()) {
+                    return Ok(Value::from(value));
+                }
+                Ok(float_value(-number.as_f64().unwrap_or_default()))
+            }
+            value => Err(eval_error(format!("cannot negate {}", value))),
+        },
+        Expr::Binary(BinaryOp::And, left, right) => {
+            if !is_true(&eval_expr(left, context)?)? {
+                return Ok(Value::Bool(false));
+            }
+            Ok(Value::Bool(is_true(&eval_expr(right, context)?)?))
+        }
+        Expr::Binary(BinaryOp::Or, left, right) => {
+            if is_true(&eval_expr(left, context)?)? {
+                return Ok(Value::Bool(true));
+            }
+            Ok(Value::Bool(is_true(&eval_expr(right, context)?)?))
+        }
+        Expr::Binary(op, left, right) => {
+            let left = eval_expr(left, context)?;
+            let right = eval_expr(right, context)?;
+            match op {
+                BinaryOp::Eq => Ok(Value::Bool(is_equal(&left, &right))),
+                BinaryOp::NotEq => Ok(Value::Bool(!is_equal(&left, &right))),
+                BinaryOp::Gt | BinaryOp::Gte | BinaryOp::Lt | BinaryOp::Lte => {
+                    compare(*op, &left, &right)
+                }
+                _ => arithmetic(*op, &left, &right),
+            }
+        }
+        Expr::Call(name, args) => {
+            let mut values = Vec::with_capacity(args.len());
+            for arg in args.iter() {
+                values.push(eval_expr(arg, context)?);
+            }
+            call_function(name, values)
+        }
+    }
+}
+
+pub fn get_path<'a>(value: &'a Value, path: &[PathSegment]) -> Option<&'a Value> {
+    let mut current = value;
+    for segment in path.iter() {
+        current = match (segment, current) {
+            (PathSegment::Key(key), Value::Object(map)) => map.get(key)?,
+            (PathSegment::Index(index), Value::Array(list)) => list.get(*index)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+// Null is false, other values than booleans can not be used as a condition.
+pub fn is_true(value: &Value) -> Result<bool, MqttBrokerError> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        Value::Null => Ok(false),
+        value => Err(eval_error(format!("{} is not a boolean", value))),
+    }
+}
+
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => "".to_string(),
+        value => value.to_string(),
+    }
+}
+
+pub fn float_value(value: f64) -> Value {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+fn is_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn compare(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, MqttBrokerError> {
+    let ordering = match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => return Ok(Value::Bool(false)),
+        (Value::Number(left), Value::Number(right)) => left.as_f64().partial_cmp(&right.as_f64()),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => {
+            return Err(eval_error(format!(
+                "cannot compare {} with {}",
+                left, right
+            )))
+        }
+    };
+
+    let Some(ordering) = ordering else {
+        return Ok(Value::Bool(false));
+    };
+    let result = match op {
+        BinaryOp::Gt => ordering == Ordering::Greater,
+        BinaryOp::Gte => ordering != Ordering::Less,
+        BinaryOp::Lt => ordering == Ordering::Less,
+        _ => ordering != Ordering::Greater,
+    };
+    Ok(Value::Bool(result))
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, MqttBrokerError> {
+    let (left, right) = match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => return Ok(Value::Null),
+        (Value::String(_), _) | (_, Value::String(_)) if op == BinaryOp::Add => {
+            return Ok(Value::String(format!(
+                "{}{}",
+                value_to_string(left),
+                value_to_string(right)
+            )));
+        }
+        (Value::Number(left), Value::Number(right)) => (left, right),
+        _ => {
+            return Err(eval_error(format!(
+                "unsupported operands {} and {}",
+                left, right
+            )))
+        }
+    };
+
+    // Integers stay integers as long as the result fits
+    if let (Some(l), Some(r)) = (left.as_i64(), right.as_i64()) {
+        let result = match op {
+            BinaryOp::Add => l.checked_add(r),
+            BinaryOp::Sub => l.checked_sub(r),
+            BinaryOp::Mul => l.checked_mul(r),
+            BinaryOp::Div if r != 0 && l % r == 0 => l.checked_div(r),
+            BinaryOp::Mod if r != 0 => l.checked_rem(r),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(Value::from(result));
+        }
+    }
+
+    let l = left.as_f64().unwrap_or_default();
+    let r = right.as_f64().unwrap_or_default();
+    if r == 0.0 && matches!(op, BinaryOp::Div | BinaryOp::Mod) {
+        return Err(eval_error("division by zero".to_string()));
+    }
+    let result = match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div => l / r,
+        _ => l % r,
+    };
+    Ok(float_value(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{eval_expr, eval_rule_sql};
+    use crate::rule_engine::sql::{RuleSql, SelectField};
+
+    fn eval(expr: &str, context: &serde_json::Value) -> serde_json::Value {
+        let sql = RuleSql::parse(&format!("SELECT {} AS v FROM \"t\"", expr)).unwrap();
+        let SelectField::Expr { expr, .. } = &sql.fields[0] else {
+            panic!("field should be an expression");
+        };
+        eval_expr(expr, context).unwrap()
+    }
+
+    #[test]
+    fn eval_rule_sql_test() {
+        let context = json!({
+            "clientid": "c1",
+            "topic": "sensors/room1",
+            "payload": {"temp": 32.5, "hum": 40},
+        });
+        let sql = RuleSql::parse(
+            r#"SELECT payload.temp AS t, clientid FROM "sensors/#" WHERE payload.temp > 30"#,
+        )
+        .unwrap();
+        let output = eval_rule_sql(&sql, &context).unwrap();
+        assert_eq!(output, Some(json!({"t": 32.5, "clientid": "c1"})));
+
+        let context = json!({"clientid": "c1", "payload": {"temp": 20}});
+        assert_eq!(eval_rule_sql(&sql, &context).unwrap(), None);
+
+        // a missing field is null and the condition does not hold
+        let context = json!({"clientid": "c1", "payload": "not json"});
+        assert_eq!(eval_rule_sql(&sql, &context).unwrap(), None);
+
+        let sql = RuleSql::parse(r#"SELECT * FROM "sensors/#""#).unwrap();
+        let context = json!({"clientid": "c1", "qos": 1});
+        assert_eq!(eval_rule_sql(&sql, &context).unwrap(), Some(context));
+    }
+
+    #[test]
+    fn eval_expr_test() {
+        let context = json!({"a": 7, "b": 2, "f": 1.5, "s": "x", "list": [1, 2, 3]});
+        assert_eq!(eval("a + b * 2", &context), json!(11));
+        assert_eq!(eval("a / b", &context), json!(3.5));
+        assert_eq!(eval("a % b", &context), json!(1));
+        assert_eq!(eval("-a + f", &context), json!(-5.5));
+        assert_eq!(eval("s + a", &context), json!("x7"));
+        assert_eq!(eval("list[2] = 3", &context), json!(true));
+        assert_eq!(eval("a > b and not (s = 'y')", &context), json!(true));
+        assert_eq!(eval("missing > 1 or a <> 7", &context), json!(false));
+        assert_eq!(eval("missing = null", &context), json!(true));
+        assert_eq!(eval("missing + 1", &context), json!(null));
+        assert_eq!(eval("a >= 7.0", &context), json!(true));
+    }
+
+    #[test]
+    fn eval_error_test() {
+        let context = json!({"a": 1, "s": "x"});
+        let sql = RuleSql::parse("SELECT * FROM \"t\" WHERE a").unwrap();
+        assert!(eval_rule_sql(&sql, &context).is_err());
+
+        let sql = RuleSql::parse("SELECT a / 0 FROM \"t\"").unwrap();
+        assert!(eval_rule_sql(&sql, &context).is_err());
+
+        let sql = RuleSql::parse("SELECT * FROM \"t\" WHERE s > 1").unwrap();
+        assert!(eval_rule_sql(&sql, &context).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, SecondsFormat, Utc};
+use common_base::tools::now_mills;
+use regex::Regex;
+use serde_json::Value;
+
+use super::eval::{float_value, value_to_string};
+use crate::handler::error::MqttBrokerError;
+
+const BUILTIN_FUNCTIONS: [&str; 25] = [
+    // json
+    "json_encode",
+    "json_decode",
+    // string
+    "lower",
+    "upper",
+    "trim",
+    "ltrim",
+    "rtrim",
+    "strlen",
+    "concat",
+    "substr",
+    "replace",
+    "split",
+    "contains",
+    "regex_match",
+    // conversion
+    "str",
+    "int",
+    "float",
+    "coalesce",
+    "is_null",
+    "abs",
+    // time
+    "now_timestamp",
+    "now_rfc3339",
+    "unix_ts_to_rfc3339",
+    "rfc3339_to_unix_ts",
+    "format_date",
+];
+
+const UNIT_SECOND: &str = "second";
+const UNIT_MILLISECOND: &str = "millisecond";
+
+pub fn is_builtin_function(name: &str) -> bool {
+    BUILTIN_FUNCTIONS.contains(&name)
+}
+
+fn function_error(name: &str, msg: &str) -> MqttBrokerError {
+    MqttBrokerError::RuleEvalError(format!("{}: {}", name, msg))
+}
+
+fn check_args(name: &str, args: &[Value], min: usize, max: usize) -> Result<(), MqttBrokerError> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max {
+            format!("{}", min)
+        } else {
+            format!("{} to {}", min, max)
+        };
+        return Err(function_error(
+            name,
+            &format!("expected {} arguments, got {}", expected, args.len()),
+        ));
+    }
+    Ok(())
+}
+
+fn arg_i64(name: &str, value: &Value) -> Result<i64, MqttBrokerError> {
+    let result = match value {
+        Value::Number(number) => number
+            .as_i64()
+            .or_else(|| number.as_f64().map(|value| value as i64)),
+        Value::String(value) => value.trim().parse::<i64>().ok(),
+        Value::Bool(value) => Some(*value as i64),
+        _ => None,
+    };
+    result.ok_or_else(|| function_error(name, &format!("{} is not an integer", value)))
+}
+
+fn arg_f64(name: &str, value: &Value) -> Result<f64, MqttBrokerError> {
+    let result = match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(value) => value.trim().parse::<f64>().ok(),
+        Value::Bool(value) => Some(*value as i64 as f64),
+        _ => None,
+    };
+    result.ok_or_else(|| function_error(name, &format!("{} is not a number", value)))
+}
+
+// The time unit argument, millisecond by default
+fn arg_unit(name: &str, args: &[Value], index: usize) -> Result<bool, MqttBrokerError> {
+    match args.get(index).map(value_to_string) {
+        None => Ok(true),
+        Some(unit) if unit == UNIT_MILLISECOND => Ok(true),
+        Some(unit) if unit == UNIT_SECOND => Ok(false),
+        Some(unit) => Err(function_error(name, &format!("unknown time unit {}", unit))),
+    }
+}
+
+pub fn call_function(name: &str, args: Vec<Value>) -> Result<Value, MqttBrokerError> {
+    match name {
+        "json_encode" => {
+            check_args(name, &args, 1, 1)?;
+            Ok(Value::String(args[0].to_string()))
+        }
+        "json_decode" => {
+            check_args(name, &args, 1, 1)?;
+            serde_json::from_str(&value_to_string(&args[0]))
+                .map_err(|e| function_error(name, &e.to_string()))
+        }
+        "lower" | "upper" | "trim" | "ltrim" | "rtrim" | "strlen" => {
+            check_args(name, &args, 1, 1)?;
+            let value = value_to_string(&args[0]);
+            Ok(match name {
+                "lower" => Value::String(value.to_lowercase()),
+                "upper" => Value::String(value.to_uppercase()),
+                "trim" => Value::String(value.trim().to_string()),
+                "ltrim" => Value::String(value.trim_start().to_string()),
+                "rtrim" => Value::String(value.trim_end().to_string()),
+                _ => Value::from(value.chars().count()),
+            })
+        }
+        "concat" => Ok(Value::String(args.iter().map(value_to_string).collect())),
+        "substr" => {
+            check_args(name, &args, 2, 3)?;
+            let value = value_to_string(&args[0]);
+            let start = arg_i64(name, &args[1])?.max(0) as usize;
+            let chars = value.chars().skip(start);
+            let result = if let Some(len) = args.get(2) {
+                chars.take(arg_i64(name, len)?.max(0) as usize).collect()
+            } else {
+                chars.collect()
+            };
+            Ok(Value::String(result))
+        }
+        "replace" => {
+            check_args(name, &args, 3, 3)?;
+            let value = value_to_string(&args[0]);
+            Ok(Value::String(value.replace(
+                &value_to_string(&args[1]),
+                &value_to_string(&args[2]),
+            )))
+        }
+        "split" => {
+            check_args(name, &args, 2, 2)?;
+            let value = value_to_string(&args[0]);
+            let separator = value_to_string(&args[1]);
+            if separator.is_empty() {
+                return Err(function_error(name, "separator cannot be empty"));
+            }
+            Ok(Value::Array(
+                value
+                    .split(separator.as_str())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            ))
+        }
+        "contains" => {
+            check_args(name, &args, 2, 2)?;
+            let result = match &args[0] {
+                Value::Array(list) => list.contains(&args[1]),
+                value => value_to_string(value).contains(&value_to_string(&args[1])),
+            };
+            Ok(Value::Bool(result))
+        }
+        "regex_match" => {
+            check_args(name, &args, 2, 2)?;
+            let regex = Regex::new(&value_to_string(&args[1]))
+                .map_err(|e| function_error(name, &e.to_string()))?;
+            Ok(Value::Bool(regex.is_match(&value_to_string(&args[0]))))
+        }
+        "str" => {
+            check_args(name, &args, 1, 1)?;
+            Ok(Value::String(value_to_string(&args[0])))
+        }
+        "int" => {
+            check_args(name, &args, 1, 1)?;
+            if let Value::String(value) = &args[0] {
+                if let Ok(value) = value.trim().parse::<f64>() {
+                    return Ok(Value::from(value as i64));
+                }
+            }
+            Ok(Value::from(arg_i64(name, &args[0])?))
+        }
+        "float" => {
+            check_args(name, &args, 1, 1)?;
+            Ok(float_value(arg_f64(name, &args[0])?))
+        }
+        "abs" => {
+            check_args(name, &args, 1, 1)?;
+            if let Some(value) = args[0].as_i64() {
+                return Ok(Value::from(value.saturating_abs()));
+            }
+            Ok(float_value(arg_f64(name, &args[0])?.abs()))
+        }
+        "coalesce" => Ok(args
+            .into_iter()
+            .find(|value| !value.is_null())
+            .unwrap_or(Value::Null)),
+        "is_null" => {
+            check_args(name, &args, 1, 1)?;
+            Ok(Value::Bool(args[0].is_null()))
+        }
+        "now_timestamp" => {
+            check_args(name, &args, 0, 1)?;
+            let now = now_mills() as i64;
+            if arg_unit(name, &args, 0)? {
+                return Ok(Value::from(now));
+            }
+            Ok(Value::from(now / 1000))
+        }
+        "now_rfc3339" => {
+            check_args(name, &args, 0, 1)?;
+            let millisecond = arg_unit(name, &args, 0)?;
+            Ok(Value::String(format_rfc3339(
+                name,
+                now_mills() as i64,
+                millisecond,
+            )?))
+        }
+        "unix_ts_to_rfc3339" => {
+            check_args(name, &args, 1, 2)?;
+            let millisecond = arg_unit(name, &args, 1)?;
+            let ts = arg_i64(name, &args[0])?;
+            let ms = if millisecond {
+                ts
+            } else {
+                ts.saturating_mul(1000)
+            };
+            Ok(Value::String(format_rfc3339(name, ms, millisecond)?))
+        }
+        "rfc3339_to_unix_ts" => {
+            check_args(name, &args, 1, 2)?;
+            let millisecond = arg_unit(name, &args, 1)?;
+            let value = value_to_string(&args[0]);
+            let ms = DateTime::parse_from_rfc3339(&value)
+                .map_err(|_| function_error(name, &format!("{} is not a RFC3339 time", value)))?
+                .timestamp_millis();
+            if millisecond {
+                return Ok(Value::from(ms));
+            }
+            Ok(Value::from(ms.div_euclid(1000)))
+        }
+        "format_date" => {
+            // format_date(timestamp, format[, unit]), format is a strftime format in UTC
+            check_args(name, &args, 2, 3)?;
+            let millisecond = arg_unit(name, &args, 2)?;
+            let ts = arg_i64(name, &args[0])?;
+            let ms = if millisecond {
+                ts
+            } else {
+                ts.saturating_mul(1000)
+            };
+            Ok(Value::String(format_date(
+                name,
+                ms,
+                &value_to_string(&args[1]),
+            )?))
+        }
+        _ => Err(function_error(name, "unknown function")),
+    }
+}
+
+fn datetime(name: &str, ms: i64) -> Result<DateTime<Utc>, MqttBrokerError> {
+    DateTime::from_timestamp_millis(ms)
+        .ok_or_else(|| function_error(name, &format!("{} is out of the time range", ms)))
+}
+
+fn format_date(name: &str, ms: i64, format: &str) -> Result<String, MqttBrokerError> {
+    let items = StrftimeItems::new(format)
+        .parse()
+        .map_err(|_| function_error(name, &format!("invalid date format {}", format)))?;
+    Ok(datetime(name, ms)?
+        .format_with_items(items.iter())
+        .to_string())
+}
+
+// UTC time, with milliseconds if the unit is millisecond
+fn format_rfc3339(name: &str, ms: i64, millisecond: bool) -> Result<String, MqttBrokerError> {
+    let seconds_format = if millisecond {
+        SecondsFormat::Millis
+    } else {
+        SecondsFormat::Secs
+    };
+    Ok(datetime(name, ms)?.to_rfc3339_opts(seconds_format, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{call_function, is_builtin_function};
+
+    fn call(name: &str, args: Vec<Value>) -> Value {
+        call_function(name, args).unwrap()
+    }
+
+    #[test]
+    fn string_function_test() {
+        assert_eq!(call("lower", vec![json!("AbC")]), json!("abc"));
+        assert_eq!(call("upper", vec![json!("AbC")]), json!("ABC"));
+        assert_eq!(call("trim", vec![json!("  a ")]), json!("a"));
+        assert_eq!(call("strlen", vec![json!("温度")]), json!(2));
+        assert_eq!(
+            call("concat", vec![json!("a"), json!(1), json!(null)]),
+            json!("a1")
+        );
+        assert_eq!(
+            call("substr", vec![json!("abcdef"), json!(2), json!(3)]),
+            json!("cde")
+        );
+        assert_eq!(
+            call("replace", vec![json!("a/b/c"), json!("/"), json!(".")]),
+            json!("a.b.c")
+        );
+        assert_eq!(
+            call("split", vec![json!("a,b"), json!(",")]),
+            json!(["a", "b"])
+        );
+        assert_eq!(call("contains", vec![json!([1, 2]), json!(2)]), json!(true));
+        assert_eq!(
+            call("regex_match", vec![json!("room12"), json!("^room[0-9]+$")]),
+            json!(true)
+        );
+        assert!(call_function("lower", vec![]).is_err());
+        assert!(call_function("split", vec![json!("a"), json!("")]).is_err());
+    }
+
+    #[test]
+    fn json_function_test() {
+        assert_eq!(
+            call("json_decode", vec![json!("{\"a\":1}")]),
+            json!({"a": 1})
+        );
+        assert_eq!(
+            call("json_encode", vec![json!({"a": 1})]),
+            json!("{\"a\":1}")
+        );
+        assert!(call_function("json_decode", vec![json!("{")]).is_err());
+
+        assert_eq!(call("int", vec![json!("12.7")]), json!(12));
+        assert_eq!(call("float", vec![json!("1.5")]), json!(1.5));
+        assert_eq!(call("str", vec![json!(1)]), json!("1"));
+        assert_eq!(call("abs", vec![json!(-3)]), json!(3));
+        assert_eq!(call("coalesce", vec![json!(null), json!("x")]), json!("x"));
+        assert_eq!(call("is_null", vec![json!(null)]), json!(true));
+    }
+
+    #[test]
+    fn time_function_test() {
+        assert_eq!(
+            call(
+                "unix_ts_to_rfc3339",
+                vec![json!(1700000000), json!("second")]
+            ),
+            json!("2023-11-14T22:13:20Z")
+        );
+        assert_eq!(
+            call("unix_ts_to_rfc3339", vec![json!(1700000000123_i64)]),
+            json!("2023-11-14T22:13:20.123Z")
+        );
+        assert_eq!(
+            call(
+                "rfc3339_to_unix_ts",
+                vec![json!("2023-11-14T22:13:20.123Z")]
+            ),
+            json!(1700000000123_i64)
+        );
+        assert_eq!(
+            call(
+                "rfc3339_to_unix_ts",
+                vec![json!("2023-11-15T06:13:20+08:00"), json!("second")]
+            ),
+            json!(1700000000)
+        );
+        assert_eq!(
+            call(
+                "unix_ts_to_rfc3339",
+                vec![json!(951782400), json!("second")]
+            ),
+            json!("2000-02-29T00:00:00Z")
+        );
+        assert_eq!(
+            call(
+                "format_date",
+                vec![json!(1700000000), json!("%Y/%m/%d %H:%M"), json!("second")]
+            ),
+            json!("2023/11/14 22:13")
+        );
+        assert_eq!(
+            call(
+                "format_date",
+                vec![json!(1700000000123_i64), json!("%H:%M:%S%.3f")]
+            ),
+            json!("22:13:20.123")
+        );
+        assert!(call_function("format_date", vec![json!(0), json!("%Q")]).is_err());
+        assert!(call_function("rfc3339_to_unix_ts", vec![json!("2023-11-14")]).is_err());
+        assert!(call_function("now_timestamp", vec![json!("hour")]).is_err());
+
+        let now = call("now_timestamp", vec![json!("second")]);
+        assert!(now.as_i64().unwrap() > 1700000000);
+        assert!(is_builtin_function("now_rfc3339"));
+        assert!(!is_builtin_function("sleep"));
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::tools::now_mills;
+use log::warn;
+use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::rule::{MqttRule, MqttRuleActionType};
+use protocol::mqtt::common::{Publish, PublishProperties};
+use serde_json::{json, Map, Value};
+use storage_adapter::storage::StorageAdapter;
+
+use self::action::{republish, send_to_connector};
+use self::eval::eval_rule_sql;
+use self::sql::RuleSql;
+use crate::bridge::manager::ConnectorManager;
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::publish::MessagePublisher;
+use crate::observability::metrics::rule::{
+    metrics_rule_action_failed_inc, metrics_rule_failed_inc, metrics_rule_matched_inc,
+    metrics_rule_passed_inc,
+};
+use crate::subscribe::sub_common::path_regex_match;
+
+pub mod action;
+pub mod eval;
+pub mod function;
+pub mod sql;
+
+pub struct Rule {
+    pub info: MqttRule,
+    pub sql: RuleSql,
+}
+
+impl Rule {
+    pub fn new(info: MqttRule) -> Result<Self, MqttBrokerError> {
+        let sql = RuleSql::parse(&info.sql)?;
+        Ok(Rule { info, sql })
+    }
+
+    pub fn is_match_topic(&self, topic_name: &str) -> bool {
+        self.sql
+            .from
+            .iter()
+            .any(|filter| path_regex_match(topic_name, filter))
+    }
+}
+
+pub fn build_rule_context(
+    connection: &MQTTConnection,
+    topic_name: &str,
+    publish: &Publish,
+    publish_properties: &Option<PublishProperties>,
+) -> Value {
+    let payload = serde_json::from_slice::<Value>(&publish.payload)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&publish.payload).to_string()));
+
+    let mut user_properties = Map::new();
+    if let Some(properties) = publish_properties {
+        for (key, value) in properties.user_properties.iter() {
+            user_properties.insert(key.clone(), Value::String(value.clone()));
+        }
+    }
+
+    json!({
+        "clientid": connection.client_id,
+        "username": connection.login_user,
+        "topic": topic_name,
+        "qos": publish.qos as u8,
+        "retain": publish.retain,
+        "payload": payload,
+        "timestamp": now_mills() as u64,
+        "user_properties": user_properties,
+    })
+}
+
+// The rules a message passed, with their outputs.
+#[derive(Default)]
+pub struct RuleMatches {
+    connection: MQTTConnection,
+    outputs: Vec<(Arc<Rule>, Value)>,
+}
+
+#[derive(Clone)]
+pub struct RuleEngine<S> {
+    cache_manager: Arc<CacheManager>,
+    connector_manager: Arc<ConnectorManager>,
+    message_storage_adapter: Arc<S>,
+}
+
+impl<S> RuleEngine<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        cache_manager: Arc<CacheManager>,
+        connector_manager: Arc<ConnectorManager>,
+        message_storage_adapter: Arc<S>,
+    ) -> Self {
+        RuleEngine {
+            cache_manager,
+            connector_manager,
+            message_storage_adapter,
+        }
+    }
+
+    // Evaluates the rules whose FROM matches the topic, once the message is stored.
+    pub fn evaluate(
+        &self,
+        connection: &MQTTConnection,
+        topic_name: &str,
+        publish: &Publish,
+        publish_properties: &Option<PublishProperties>,
+    ) -> RuleMatches {
+        let rules: Vec<Arc<Rule>> = self
+            .cache_manager
+            .rule_info
+            .iter()
+            .filter(|rule| rule.is_match_topic(topic_name))
+            .map(|rule| rule.clone())
+            .collect();
+        if rules.is_empty() {
+            return RuleMatches::default();
+        }
+
+        let context = build_rule_context(connection, topic_name, publish, publish_properties);
+        let mut outputs = Vec::new();
+        for rule in rules {
+            let rule_name = &rule.info.rule_name;
+            metrics_rule_matched_inc(rule_name);
+            match eval_rule_sql(&rule.sql, &context) {
+                Ok(Some(output)) => {
+                    metrics_rule_passed_inc(rule_name);
+                    outputs.push((rule, output));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    metrics_rule_failed_inc(rule_name);
+                    warn!(
+                        "Rule {} failed to evaluate, error message: {}",
+                        rule_name, e
+                    );
+                }
+            }
+        }
+        if outputs.is_empty() {
+            return RuleMatches::default();
+        }
+
+        RuleMatches {
+            connection: connection.clone(),
+            outputs,
+        }
+    }
+
+    // Runs the actions of the rules the message passed. Actions run in the background so
+    // they never delay the publish ack.
+    pub fn run_actions(&self, message_publisher: MessagePublisher<S>, matches: RuleMatches) {
+        if matches.outputs.is_empty() {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            for (rule, output) in matches.outputs.iter() {
+                engine
+                    .run_rule_actions(&message_publisher, &matches.connection, rule, output)
+                    .await;
+            }
+        });
+    }
+
+    async fn run_rule_actions(
+        &self,
+        message_publisher: &MessagePublisher<S>,
+        connection: &MQTTConnection,
+        rule: &Rule,
+        output: &Value,
+    ) {
+        let rule_name = &rule.info.rule_name;
+        for action in rule.info.actions.iter() {
+            let result = match action.action_type {
+                MqttRuleActionType::Republish => {
+                    republish(
+                        &self.cache_manager,
+                        message_publisher,
+                        connection,
+                        action,
+                        output,
+                    )
+                    .await
+                }
+                MqttRuleActionType::Connector => {
+                    send_to_connector(
+                        &self.cache_manager,
+                        &self.connector_manager,
+                        &self.message_storage_adapter,
+                        &connection.client_id,
+                        action,
+                        output,
+                    )
+                    .await
+                }
+                // the output is discarded, the remaining actions are skipped
+                MqttRuleActionType::Drop => break,
+            };
+            if let Err(e) = result {
+                let action_name = format!("{:?}", action.action_type);
+                metrics_rule_action_failed_inc(rule_name, &action_name);
+                warn!(
+                    "Rule {} failed to run action {} with target {}, error message: {}",
+                    rule_name, action_name, action.target, e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use metadata_struct::mqtt::connection::MQTTConnection;
+    use metadata_struct::mqtt::rule::MqttRule;
+    use protocol::mqtt::common::{Publish, PublishProperties, QoS};
+
+    use super::{build_rule_context, Rule};
+
+    #[test]
+    fn rule_match_topic_test() {
+        let rule = Rule::new(MqttRule {
+            rule_name: "r1".to_string(),
+            sql: "SELECT * FROM \"sensor/+/temp\", \"alarm/#\"".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(rule.is_match_topic("sensor/s1/temp"));
+        assert!(rule.is_match_topic("alarm/a/b"));
+        assert!(!rule.is_match_topic("sensor/s1/hum"));
+
+        assert!(Rule::new(MqttRule {
+            rule_name: "r2".to_string(),
+            sql: "SELECT FROM".to_string(),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn build_rule_context_test() {
+        let connection = MQTTConnection {
+            client_id: "c1".to_string(),
+            login_user: "u1".to_string(),
+            ..Default::default()
+        };
+        let publish = Publish {
+            dup: false,
+            qos: QoS::AtLeastOnce,
+            pkid: 1,
+            retain: false,
+            topic: Bytes::from("sensor/s1/temp"),
+            payload: Bytes::from(r#"{"t": 31}"#),
+        };
+        let properties = PublishProperties {
+            user_properties: vec![("k".to_string(), "v".to_string())],
+            ..Default::default()
+        };
+
+        let context =
+            build_rule_context(&connection, "sensor/s1/temp", &publish, &Some(properties));
+        assert_eq!(context["clientid"], "c1");
+        assert_eq!(context["username"], "u1");
+        assert_eq!(context["qos"], 1);
+        assert_eq!(context["payload"]["t"], 31);
+        assert_eq!(context["user_properties"]["k"], "v");
+
+        let publish = Publish {
+            payload: Bytes::from("plain"),
+            ..publish
+        };
+        let context = build_rule_context(&connection, "sensor/s1/temp", &publish, &None);
+        assert_eq!(context["payload"], "plain");
+    }
+}
@@ -0,0 +1,702 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::{Number, Value};
+
+use super::function::is_builtin_function;
+use crate::handler::error::MqttBrokerError;
+
+const RESERVED_KEYWORDS: [&str; 7] = ["SELECT", "FROM", "WHERE", "AS", "AND", "OR", "NOT"];
+
+// SELECT <fields> FROM "<topic filter>"[, "<topic filter>"] [WHERE <condition>]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleSql {
+    pub fields: Vec<SelectField>,
+    pub from: Vec<String>,
+    pub condition: Option<Expr>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectField {
+    All,
+    Expr { expr: Expr, alias: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Field(Vec<PathSegment>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    And,
+    Or,
+    Eq,
+    NotEq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(Number),
+    // 'single quoted'
+    Str(String),
+    // "double quoted"
+    QuotedStr(String),
+    Comma,
+    Dot,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Star,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Eq,
+    NotEq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+fn syntax_error(msg: String) -> MqttBrokerError {
+    MqttBrokerError::RuleSqlSyntaxError(msg)
+}
+
+// Returns the tokens with the byte range of each token in the sql.
+fn tokenize(sql: &str) -> Result<Vec<(Token, usize, usize)>, MqttBrokerError> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let byte_pos = |i: usize| chars.get(i).map(|(pos, _)| *pos).unwrap_or(sql.len());
+    let next_is = |i: usize, c: char| chars.get(i + 1).map(|(_, n)| *n == c).unwrap_or(false);
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let token = match c {
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '*' => Token::Star,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '/' => Token::Slash,
+            '%' => Token::Percent,
+            '=' => Token::Eq,
+            '!' if next_is(i, '=') => {
+                i += 1;
+                Token::NotEq
+            }
+            '<' if next_is(i, '=') => {
+                i += 1;
+                Token::Lte
+            }
+            '<' if next_is(i, '>') => {
+                i += 1;
+                Token::NotEq
+            }
+            '<' => Token::Lt,
+            '>' if next_is(i, '=') => {
+                i += 1;
+                Token::Gte
+            }
+            '>' => Token::Gt,
+            '\'' | '"' => {
+                // A quote inside the string is escaped by doubling it
+                let mut value = String::new();
+                let mut closed = false;
+                i += 1;
+                while i < chars.len() {
+                    let ch = chars[i].1;
+                    if ch == c {
+                        if next_is(i, c) {
+                            value.push(c);
+                            i += 1;
+                        } else {
+                            closed = true;
+                            break;
+                        }
+                    } else {
+                        value.push(ch);
+                    }
+                    i += 1;
+                }
+                if !closed {
+                    return Err(syntax_error(format!(
+                        "unterminated string starting at {}",
+                        start
+                    )));
+                }
+                if c == '\'' {
+                    Token::Str(value)
+                } else {
+                    Token::QuotedStr(value)
+                }
+            }
+            c if c.is_ascii_digit() => {
+                let mut end = i;
+                while end + 1 < chars.len() && chars[end + 1].1.is_ascii_digit() {
+                    end += 1;
+                }
+                let is_float = next_is(end, '.')
+                    && chars
+                        .get(end + 2)
+                        .map(|(_, n)| n.is_ascii_digit())
+                        .unwrap_or(false);
+                if is_float {
+                    end += 1;
+                    while end + 1 < chars.len() && chars[end + 1].1.is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+                let text = &sql[start..byte_pos(end + 1)];
+                i = end;
+                let number = if is_float {
+                    text.parse::<f64>().ok().and_then(Number::from_f64)
+                } else {
+                    text.parse::<i64>().ok().map(Number::from)
+                };
+                match number {
+                    Some(number) => Token::Number(number),
+                    None => return Err(syntax_error(format!("invalid number {}", text))),
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = i;
+                while end + 1 < chars.len()
+                    && (chars[end + 1].1.is_alphanumeric() || chars[end + 1].1 == '_')
+                {
+                    end += 1;
+                }
+                let text = &sql[start..byte_pos(end + 1)];
+                i = end;
+                Token::Ident(text.to_string())
+            }
+            _ => {
+                return Err(syntax_error(format!(
+                    "unexpected character '{}' at {}",
+                    c, start
+                )));
+            }
+        };
+        i += 1;
+        tokens.push((token, start, byte_pos(i)));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    sql: &'a str,
+    tokens: Vec<(Token, usize, usize)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(token, _, _)| token.clone());
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn start_offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(_, start, _)| *start)
+            .unwrap_or(self.sql.len())
+    }
+
+    fn end_offset(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .and_then(|pos| self.tokens.get(pos))
+            .map(|(_, _, end)| *end)
+            .unwrap_or(0)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), MqttBrokerError> {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            return Ok(());
+        }
+        Err(self.unexpected(keyword))
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), MqttBrokerError> {
+        if self.peek() == Some(&token) {
+            self.pos += 1;
+            return Ok(());
+        }
+        Err(self.unexpected(&format!("{:?}", token)))
+    }
+
+    fn unexpected(&self, expected: &str) -> MqttBrokerError {
+        match self.peek() {
+            Some(token) => syntax_error(format!(
+                "expected {} but found {:?} at {}",
+                expected,
+                token,
+                self.start_offset()
+            )),
+            None => syntax_error(format!("expected {} but reached the end", expected)),
+        }
+    }
+
+    fn parse(&mut self) -> Result<RuleSql, MqttBrokerError> {
+        self.expect_keyword("SELECT")?;
+        let fields = self.parse_fields()?;
+
+        self.expect_keyword("FROM")?;
+        let from = self.parse_from()?;
+
+        let condition = if self.is_keyword("WHERE") {
+            self.pos += 1;
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        if self.peek().is_some() {
+            return Err(self.unexpected("the end of the sql"));
+        }
+
+        Ok(RuleSql {
+            fields,
+            from,
+            condition,
+        })
+    }
+
+    fn parse_fields(&mut self) -> Result<Vec<SelectField>, MqttBrokerError> {
+        let mut fields = Vec::new();
+        loop {
+            if self.peek() == Some(&Token::Star) {
+                self.pos += 1;
+                fields.push(SelectField::All);
+            } else {
+                let start = self.start_offset();
+                let expr = self.parse_expr()?;
+                let end = self.end_offset();
+
+                let alias = if self.is_keyword("AS") {
+                    self.pos += 1;
+                    match self.next() {
+                        Some(Token::Ident(alias))
+                        | Some(Token::Str(alias))
+                        | Some(Token::QuotedStr(alias)) => alias,
+                        _ => return Err(syntax_error("expected an alias after AS".to_string())),
+                    }
+                } else {
+                    default_alias(&expr, &self.sql[start..end])
+                };
+                fields.push(SelectField::Expr { expr, alias });
+            }
+
+            if self.peek() != Some(&Token::Comma) {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(fields)
+    }
+
+    fn parse_from(&mut self) -> Result<Vec<String>, MqttBrokerError> {
+        let mut from = Vec::new();
+        loop {
+            match self.next() {
+                Some(Token::QuotedStr(topic)) | Some(Token::Str(topic)) if !topic.is_empty() => {
+                    from.push(topic)
+                }
+                _ => {
+                    return Err(syntax_error(
+                        "expected a quoted topic filter after FROM".to_string(),
+                    ))
+                }
+            }
+
+            if self.peek() != Some(&Token::Comma) {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(from)
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, MqttBrokerError> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, MqttBrokerError> {
+        let mut left = self.parse_and()?;
+        while self.is_keyword("OR") {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, MqttBrokerError> {
+        let mut left = self.parse_not()?;
+        while self.is_keyword("AND") {
+            self.pos += 1;
+            let right = self.parse_not()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, MqttBrokerError> {
+        if self.is_keyword("NOT") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, MqttBrokerError> {
+        let left = self.parse_additive()?;
+        let op = match self.peek() {
+            Some(Token::Eq) => BinaryOp::Eq,
+            Some(Token::NotEq) => BinaryOp::NotEq,
+            Some(Token::Gt) => BinaryOp::Gt,
+            Some(Token::Gte) => BinaryOp::Gte,
+            Some(Token::Lt) => BinaryOp::Lt,
+            Some(Token::Lte) => BinaryOp::Lte,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.parse_additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, MqttBrokerError> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinaryOp::Add,
+                Some(Token::Minus) => BinaryOp::Sub,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, MqttBrokerError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinaryOp::Mul,
+                Some(Token::Slash) => BinaryOp::Div,
+                Some(Token::Percent) => BinaryOp::Mod,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, MqttBrokerError> {
+        if self.peek() == Some(&Token::Minus) {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, MqttBrokerError> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Literal(Value::Number(number))),
+            Some(Token::Str(value)) | Some(Token::QuotedStr(value)) => {
+                Ok(Expr::Literal(Value::String(value)))
+            }
+            Some(Token::LParen) => {
+                let expr = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) => {
+                let upper = ident.to_uppercase();
+                match upper.as_str() {
+                    "TRUE" => return Ok(Expr::Literal(Value::Bool(true))),
+                    "FALSE" => return Ok(Expr::Literal(Value::Bool(false))),
+                    "NULL" => return Ok(Expr::Literal(Value::Null)),
+                    _ => {}
+                }
+                if RESERVED_KEYWORDS.contains(&upper.as_str()) {
+                    return Err(syntax_error(format!("unexpected keyword {}", ident)));
+                }
+
+                if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    return self.parse_call(ident.to_lowercase());
+                }
+                self.parse_path(ident)
+            }
+            Some(token) => Err(syntax_error(format!("unexpected token {:?}", token))),
+            None => Err(syntax_error("unexpected end of the sql".to_string())),
+        }
+    }
+
+    fn parse_call(&mut self, name: String) -> Result<Expr, MqttBrokerError> {
+        if !is_builtin_function(&name) {
+            return Err(syntax_error(format!("unknown function {}", name)));
+        }
+
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Ok(Expr::Call(name, args));
+        }
+        loop {
+            args.push(self.parse_expr()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => break,
+                _ => {
+                    return Err(syntax_error(format!(
+                        "expected ',' or ')' in the arguments of {}",
+                        name
+                    )))
+                }
+            }
+        }
+        Ok(Expr::Call(name, args))
+    }
+
+    fn parse_path(&mut self, first: String) -> Result<Expr, MqttBrokerError> {
+        let mut path = vec![PathSegment::Key(first)];
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.pos += 1;
+                    match self.next() {
+                        Some(Token::Ident(key)) | Some(Token::QuotedStr(key)) => {
+                            path.push(PathSegment::Key(key))
+                        }
+                        _ => {
+                            return Err(syntax_error("expected a field name after '.'".to_string()))
+                        }
+                    }
+                }
+                Some(Token::LBracket) => {
+                    self.pos += 1;
+                    let segment = match self.next() {
+                        Some(Token::Number(number)) => number
+                            .as_u64()
+                            .map(|index| PathSegment::Index(index as usize)),
+                        Some(Token::Str(key)) | Some(Token::QuotedStr(key)) => {
+                            Some(PathSegment::Key(key))
+                        }
+                        _ => None,
+                    };
+                    let Some(segment) = segment else {
+                        return Err(syntax_error(
+                            "expected an index or a quoted key in '[]'".to_string(),
+                        ));
+                    };
+                    path.push(segment);
+                    self.expect(Token::RBracket)?;
+                }
+                _ => break,
+            }
+        }
+        Ok(Expr::Field(path))
+    }
+}
+
+// Fields are named after the last key of their path, other expressions after their text.
+fn default_alias(expr: &Expr, text: &str) -> String {
+    if let Expr::Field(path) = expr {
+        if let Some(PathSegment::Key(key)) = path.last() {
+            return key.clone();
+        }
+    }
+    text.trim().to_string()
+}
+
+impl RuleSql {
+    pub fn parse(sql: &str) -> Result<RuleSql, MqttBrokerError> {
+        let tokens = tokenize(sql)?;
+        let mut parser = Parser {
+            sql,
+            tokens,
+            pos: 0,
+        };
+        parser.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{BinaryOp, Expr, PathSegment, RuleSql, SelectField};
+
+    fn field(path: &[&str]) -> Expr {
+        Expr::Field(
+            path.iter()
+                .map(|key| PathSegment::Key(key.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_select_test() {
+        let sql = RuleSql::parse(
+            r#"SELECT payload.temp AS t, clientid FROM "sensors/#" WHERE payload.temp > 30"#,
+        )
+        .unwrap();
+        assert_eq!(
+            sql.fields,
+            vec![
+                SelectField::Expr {
+                    expr: field(&["payload", "temp"]),
+                    alias: "t".to_string(),
+                },
+                SelectField::Expr {
+                    expr: field(&["clientid"]),
+                    alias: "clientid".to_string(),
+                },
+            ]
+        );
+        assert_eq!(sql.from, vec!["sensors/#".to_string()]);
+        assert_eq!(
+            sql.condition,
+            Some(Expr::Binary(
+                BinaryOp::Gt,
+                Box::new(field(&["payload", "temp"])),
+                Box::new(Expr::Literal(json!(30))),
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_expression_test() {
+        let sql = RuleSql::parse(
+            "select *, upper(clientid), payload.values[1] * 2 + 1 as v from 'a/+', \"b/#\" \
+             where not payload.ok = true and (qos >= 1 or topic <> 'a/b')",
+        )
+        .unwrap();
+        assert_eq!(sql.fields.len(), 3);
+        assert_eq!(sql.fields[0], SelectField::All);
+        assert_eq!(
+            sql.fields[1],
+            SelectField::Expr {
+                expr: Expr::Call("upper".to_string(), vec![field(&["clientid"])]),
+                alias: "upper(clientid)".to_string(),
+            }
+        );
+        assert_eq!(
+            sql.fields[2],
+            SelectField::Expr {
+                expr: Expr::Binary(
+                    BinaryOp::Add,
+                    Box::new(Expr::Binary(
+                        BinaryOp::Mul,
+                        Box::new(Expr::Field(vec![
+                            PathSegment::Key("payload".to_string()),
+                            PathSegment::Key("values".to_string()),
+                            PathSegment::Index(1),
+                        ])),
+                        Box::new(Expr::Literal(json!(2))),
+                    )),
+                    Box::new(Expr::Literal(json!(1))),
+                ),
+                alias: "v".to_string(),
+            }
+        );
+        assert_eq!(sql.from, vec!["a/+".to_string(), "b/#".to_string()]);
+
+        let Some(Expr::Binary(BinaryOp::And, left, right)) = sql.condition else {
+            panic!("condition should be an AND expression");
+        };
+        assert!(matches!(*left, Expr::Not(_)));
+        assert!(matches!(*right, Expr::Binary(BinaryOp::Or, _, _)));
+
+        let sql = RuleSql::parse("SELECT 'it''s', -1.5, null FROM \"t\"").unwrap();
+        let literals: Vec<Value> = sql
+            .fields
+            .into_iter()
+            .map(|field| match field {
+                SelectField::Expr { expr, .. } => match expr {
+                    Expr::Literal(value) => value,
+                    Expr::Neg(expr) => match *expr {
+                        Expr::Literal(value) => json!(-value.as_f64().unwrap()),
+                        _ => Value::Null,
+                    },
+                    _ => Value::Null,
+                },
+                SelectField::All => Value::Null,
+            })
+            .collect();
+        assert_eq!(literals, vec![json!("it's"), json!(-1.5), Value::Null]);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert!(RuleSql::parse("").is_err());
+        assert!(RuleSql::parse("SELECT * FROM").is_err());
+        assert!(RuleSql::parse("SELECT * FROM sensors").is_err());
+        assert!(RuleSql::parse("SELECT FROM \"t\"").is_err());
+        assert!(RuleSql::parse("SELECT * FROM \"t\" WHERE").is_err());
+        assert!(RuleSql::parse("SELECT * FROM \"t\" WHERE a > 1 b").is_err());
+        assert!(RuleSql::parse("SELECT unknown_fn(a) FROM \"t\"").is_err());
+        assert!(RuleSql::parse("SELECT 'abc FROM \"t\"").is_err());
+        assert!(RuleSql::parse("SELECT a # b FROM \"t\"").is_err());
+    }
+}
@@ -168,7 +168,8 @@ fn is_acl_deny(
         .get(&connection.login_user)
     {
         for raw in acl_list.clone() {
-            if topic_match(topic_name, &raw.topic)
+            if tenant_match(&connection.tenant, &raw.tenant)
+                && topic_match(topic_name, &raw.topic)
                 && ip_match(&connection.source_ip_addr, &raw.ip)
                 && (raw.action == action || raw.action == MqttAclAction::All)
                 && raw.permission == MqttAclPermission::Deny
@@ -185,7 +186,8 @@ fn is_acl_deny(
         .get(&connection.client_id)
     {
         for raw in client_id_list.clone() {
-            if topic_match(topic_name, &raw.topic)
+            if tenant_match(&connection.tenant, &raw.tenant)
+                && topic_match(topic_name, &raw.topic)
                 && ip_match(&connection.source_ip_addr, &raw.ip)
                 && (raw.action == action || raw.action == MqttAclAction::All)
                 && raw.permission == MqttAclPermission::Deny
@@ -197,6 +199,12 @@ fn is_acl_deny(
     false
 }
 
+// An acl of a tenant only applies to the clients of that tenant, an acl without tenant applies to
+// all clients.
+fn tenant_match(tenant: &str, acl_tenant: &str) -> bool {
+    acl_tenant.is_empty() || tenant == acl_tenant
+}
+
 fn topic_match(topic_name: &str, match_topic_name: &str) -> bool {
     if match_topic_name == WILDCARD_RESOURCE {
         return true;
@@ -232,7 +240,7 @@ mod test {
     use metadata_struct::mqtt::connection::{ConnectionConfig, MQTTConnection};
     use metadata_struct::mqtt::user::MqttUser;
 
-    use super::{ip_match, is_acl_deny, is_blacklist, is_super_user, topic_match};
+    use super::{ip_match, is_acl_deny, is_blacklist, is_super_user, tenant_match, topic_match};
     use crate::handler::cache::CacheManager;
     use crate::handler::constant::WILDCARD_RESOURCE;
 
@@ -246,6 +254,8 @@ pub async fn check_super_user_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         cache_manager.add_user(user.clone());
 
@@ -261,6 +271,8 @@ pub async fn check_super_user_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: false,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         cache_manager.add_user(user.clone());
         assert!(!is_super_user(&cache_manager, &user.username));
@@ -275,6 +287,8 @@ pub async fn check_black_list_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         cache_manager.add_user(user.clone());
@@ -284,6 +298,7 @@ pub async fn check_black_list_test() {
             receive_maximum: 3,
             max_packet_size: 3,
             topic_alias_max: 3,
+            client_topic_alias_max: 0,
             request_problem_info: 1,
             keep_alive: 2,
             source_ip_addr: "127.0.0.1".to_string(),
@@ -362,6 +377,8 @@ pub async fn check_empty_acl_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         cache_manager.add_user(user.clone());
@@ -371,6 +388,7 @@ pub async fn check_empty_acl_test() {
             receive_maximum: 3,
             max_packet_size: 3,
             topic_alias_max: 3,
+            client_topic_alias_max: 0,
             request_problem_info: 1,
             keep_alive: 2,
             source_ip_addr: "127.0.0.1".to_string(),
@@ -403,6 +421,8 @@ pub async fn check_user_wildcard_acl_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         cache_manager.add_user(user.clone());
@@ -412,6 +432,7 @@ pub async fn check_user_wildcard_acl_test() {
             receive_maximum: 3,
             max_packet_size: 3,
             topic_alias_max: 3,
+            client_topic_alias_max: 0,
             request_problem_info: 1,
             keep_alive: 2,
             source_ip_addr: "127.0.0.1".to_string(),
@@ -426,6 +447,7 @@ pub async fn check_user_wildcard_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -449,6 +471,7 @@ pub async fn check_user_wildcard_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Subscribe,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -469,6 +492,8 @@ pub async fn check_user_match_acl_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         cache_manager.add_user(user.clone());
@@ -478,6 +503,7 @@ pub async fn check_user_match_acl_test() {
             receive_maximum: 3,
             max_packet_size: 3,
             topic_alias_max: 3,
+            client_topic_alias_max: 0,
             request_problem_info: 1,
             keep_alive: 2,
             source_ip_addr: "127.0.0.1".to_string(),
@@ -492,6 +518,7 @@ pub async fn check_user_match_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -515,6 +542,7 @@ pub async fn check_user_match_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Subscribe,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -535,6 +563,8 @@ pub async fn check_client_id_wildcard_acl_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         cache_manager.add_user(user.clone());
@@ -544,6 +574,7 @@ pub async fn check_client_id_wildcard_acl_test() {
             receive_maximum: 3,
             max_packet_size: 3,
             topic_alias_max: 3,
+            client_topic_alias_max: 0,
             request_problem_info: 1,
             keep_alive: 2,
             source_ip_addr: "127.0.0.1".to_string(),
@@ -558,6 +589,7 @@ pub async fn check_client_id_wildcard_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -581,6 +613,7 @@ pub async fn check_client_id_wildcard_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Subscribe,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -601,6 +634,8 @@ pub async fn check_client_id_match_acl_test() {
             username: "loboxu".to_string(),
             password: "lobo_123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
 
         cache_manager.add_user(user.clone());
@@ -610,6 +645,7 @@ pub async fn check_client_id_match_acl_test() {
             receive_maximum: 3,
             max_packet_size: 3,
             topic_alias_max: 3,
+            client_topic_alias_max: 0,
             request_problem_info: 1,
             keep_alive: 2,
             source_ip_addr: "127.0.0.1".to_string(),
@@ -624,6 +660,7 @@ pub async fn check_client_id_match_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -647,6 +684,7 @@ pub async fn check_client_id_match_acl_test() {
             ip: WILDCARD_RESOURCE.to_string(),
             action: MqttAclAction::Subscribe,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
         cache_manager.add_acl(acl);
         assert!(is_acl_deny(
@@ -666,6 +704,15 @@ pub async fn topic_match_test() {
         assert!(!topic_match(topic_name, "v1"));
     }
 
+    #[tokio::test]
+    pub async fn tenant_match_test() {
+        assert!(tenant_match("", ""));
+        assert!(tenant_match("tenant-a", ""));
+        assert!(tenant_match("tenant-a", "tenant-a"));
+        assert!(!tenant_match("tenant-b", "tenant-a"));
+        assert!(!tenant_match("", "tenant-a"));
+    }
+
     #[tokio::test]
     pub async fn ip_match_test() {
         let source_ip = "127.0.0.1";
@@ -20,6 +20,7 @@
 use metadata_struct::acl::mqtt_acl::{MqttAcl, MqttAclResourceType};
 use metadata_struct::acl::mqtt_blacklist::{MqttAclBlackList, MqttAclBlackListType};
 use metadata_struct::mqtt::cluster::MqttClusterDynamicFlappingDetect;
+use metadata_struct::mqtt::tenant::tenant_scoped_name;
 
 #[derive(Clone)]
 pub struct AclMetadata {
@@ -112,11 +113,13 @@ pub fn parse_mqtt_acl(&self, acl: MqttAcl) {
                         .insert(acl.resource_name.clone(), vec![acl]);
                 }
             }
+            // The acls of a user are keyed by the name the clients of the user log in with
             MqttAclResourceType::User => {
-                if let Some(mut raw) = self.acl_user.get_mut(&acl.resource_name) {
+                let login_name = tenant_scoped_name(&acl.tenant, &acl.resource_name);
+                if let Some(mut raw) = self.acl_user.get_mut(&login_name) {
                     raw.push(acl);
                 } else {
-                    self.acl_user.insert(acl.resource_name.clone(), vec![acl]);
+                    self.acl_user.insert(login_name, vec![acl]);
                 }
             }
         }
@@ -129,7 +132,8 @@ pub fn remove_mqtt_acl(&self, acl: MqttAcl) {
                 self.acl_client_id.remove(&resource_name);
             }
             MqttAclResourceType::User => {
-                self.acl_user.remove(&resource_name);
+                self.acl_user
+                    .remove(&tenant_scoped_name(&acl.tenant, &resource_name));
             }
         }
     }
@@ -298,6 +302,7 @@ pub async fn parse_mqtt_acl_test() {
             ip: "".to_string(),
             action: MqttAclAction::All,
             permission: MqttAclPermission::Allow,
+            tenant: "".to_string(),
         };
         acl_metadata.parse_mqtt_acl(client_id_acl.clone());
 
@@ -319,6 +324,7 @@ pub async fn parse_mqtt_acl_test() {
             ip: "".to_string(),
             action: MqttAclAction::All,
             permission: MqttAclPermission::Allow,
+            tenant: "".to_string(),
         };
         acl_metadata.parse_mqtt_acl(user_acl.clone());
 
@@ -21,6 +21,11 @@
 pub mod psk;
 pub mod x509;
 
+// Authentication methods that can be configured on a listener
+pub const AUTH_METHOD_ANONYMOUS: &str = "anonymous";
+pub const AUTH_METHOD_PASSWORD: &str = "password";
+pub const AUTH_METHOD_X509: &str = "x509";
+
 #[async_trait]
 pub trait Authentication {
     async fn apply(&self) -> Result<bool, MqttBrokerError>;
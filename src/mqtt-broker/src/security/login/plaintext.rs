@@ -76,6 +76,8 @@ pub async fn plaintext_test() {
             username: username.clone(),
             password: password.clone(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         cache_manager.add_user(user);
 
@@ -11,3 +11,136 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use common_base::config::broker_mqtt::Listener;
+use protocol::mqtt::common::Login;
+use x509_parser::extensions::GeneralName;
+use x509_parser::parse_x509_certificate;
+
+use super::AUTH_METHOD_X509;
+
+// The names a client certificate identifies its client with: the common names of the subject
+// followed by the DNS names and email addresses of the subject alternative name.
+pub fn cert_identities(der: &[u8]) -> Vec<String> {
+    let Ok((_, cert)) = parse_x509_certificate(der) else {
+        return Vec::new();
+    };
+
+    let mut identities: Vec<String> = cert
+        .subject()
+        .iter_common_name()
+        .filter_map(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string())
+        .collect();
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in san.value.general_names.iter() {
+            match name {
+                GeneralName::DNSName(name) | GeneralName::RFC822Name(name) => {
+                    identities.push(name.to_string())
+                }
+                _ => {}
+            }
+        }
+    }
+    identities
+}
+
+// A client of an x509 listener that sends no username logs in as the first name of its
+// certificate, so the ACLs, tenant and mountpoint of that user apply to it.
+pub fn x509_login(
+    login: &Option<Login>,
+    listener: &Option<Listener>,
+    cert_identities: &[String],
+) -> Option<Login> {
+    let has_username = login
+        .as_ref()
+        .is_some_and(|login| !login.username.is_empty());
+    let x509_listener = listener.as_ref().is_some_and(|listener| {
+        listener
+            .authentication
+            .iter()
+            .any(|method| method == AUTH_METHOD_X509)
+    });
+    if has_username || !x509_listener {
+        return login.clone();
+    }
+
+    cert_identities.first().map(|username| Login {
+        username: username.clone(),
+        password: String::new(),
+    })
+}
+
+// The username of the client has to be one of the names of its certificate, a client can not log
+// in as another user with a certificate issued to itself.
+pub fn x509_check_login(login: &Option<Login>, cert_identities: &[String]) -> bool {
+    login
+        .as_ref()
+        .is_some_and(|login| cert_identities.contains(&login.username))
+}
+
+#[cfg(test)]
+mod tests {
+    use common_base::config::broker_mqtt::Listener;
+    use protocol::mqtt::common::Login;
+    use rcgen::{CertificateParams, DnType, KeyPair};
+
+    use super::{cert_identities, x509_check_login, x509_login};
+
+    fn build_cert_der() -> Vec<u8> {
+        let mut params = CertificateParams::new(vec!["device-1.example.com".to_string()]).unwrap();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "device-1");
+        let key = KeyPair::generate().unwrap();
+        params.self_signed(&key).unwrap().der().to_vec()
+    }
+
+    fn login(username: &str) -> Option<Login> {
+        Some(Login {
+            username: username.to_string(),
+            password: "".to_string(),
+        })
+    }
+
+    #[test]
+    fn cert_identities_test() {
+        let identities = cert_identities(&build_cert_der());
+        assert_eq!(
+            identities,
+            vec!["device-1".to_string(), "device-1.example.com".to_string()]
+        );
+        assert!(cert_identities(b"not a certificate").is_empty());
+    }
+
+    #[test]
+    fn x509_login_test() {
+        let identities = cert_identities(&build_cert_der());
+        let listener = Some(Listener {
+            authentication: vec!["x509".to_string()],
+            ..Default::default()
+        });
+
+        // the certificate name is the username of a client without one
+        let res = x509_login(&None, &listener, &identities);
+        assert_eq!(res.unwrap().username, "device-1");
+        let res = x509_login(&login(""), &listener, &identities);
+        assert_eq!(res.unwrap().username, "device-1");
+
+        // the username of the client is kept and checked against the certificate
+        let res = x509_login(&login("device-2"), &listener, &identities);
+        assert_eq!(res.as_ref().unwrap().username, "device-2");
+        assert!(!x509_check_login(&res, &identities));
+
+        assert!(x509_check_login(&login("device-1"), &identities));
+        assert!(x509_check_login(
+            &login("device-1.example.com"),
+            &identities
+        ));
+        assert!(!x509_check_login(&None, &identities));
+        assert!(!x509_check_login(&login("device-1"), &[]));
+
+        // a listener without x509 does not map the certificate
+        assert!(x509_login(&None, &None, &identities).is_none());
+    }
+}
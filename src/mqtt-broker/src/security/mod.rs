@@ -19,12 +19,14 @@
 
 use acl::auth::is_allow_acl;
 use axum::async_trait;
-use common_base::config::broker_mqtt::broker_mqtt_conf;
+use common_base::config::broker_mqtt::{broker_mqtt_conf, Listener};
 use common_base::config::common::Auth;
 use dashmap::DashMap;
 use grpc_clients::pool::ClientPool;
+use log::warn;
 use login::plaintext::Plaintext;
-use login::Authentication;
+use login::x509::x509_check_login;
+use login::{Authentication, AUTH_METHOD_ANONYMOUS, AUTH_METHOD_PASSWORD, AUTH_METHOD_X509};
 use metadata_struct::acl::mqtt_acl::{MqttAcl, MqttAclAction, MqttAclResourceType};
 use metadata_struct::acl::mqtt_blacklist::MqttAclBlackList;
 use metadata_struct::mqtt::connection::MQTTConnection;
@@ -37,6 +39,7 @@
 use crate::handler::cache::CacheManager;
 use crate::handler::error::MqttBrokerError;
 use crate::security::acl::auth::is_blacklist;
+use crate::server::listener::LISTENER_PROTOCOL_TLS;
 use crate::subscribe::sub_common::get_sub_topic_id_list;
 
 pub mod acl;
@@ -107,8 +110,7 @@ pub async fn read_all_blacklist(&self) -> Result<Vec<MqttAclBlackList>, MqttBrok
     }
 
     pub async fn save_user(&self, user_info: MqttUser) -> Result<(), MqttBrokerError> {
-        let username = user_info.username.clone();
-        if let Some(_user) = self.cache_manager.user_info.get(&username) {
+        if let Some(_user) = self.cache_manager.user_info.get(&user_info.login_name()) {
             return Err(MqttBrokerError::UserAlreadyExist);
         }
         self.cache_manager.add_user(user_info.clone());
@@ -144,7 +146,17 @@ pub async fn check_login_auth(
         login: &Option<Login>,
         _: &Option<ConnectProperties>,
         _: &SocketAddr,
+        listener: &Option<Listener>,
+        cert_identities: &[String],
     ) -> Result<bool, MqttBrokerError> {
+        if let Some(listener) = listener {
+            if !listener.authentication.is_empty() {
+                return Ok(self
+                    .listener_check_login_auth(listener, login, cert_identities)
+                    .await);
+            }
+        }
+
         let cluster = self.cache_manager.get_cluster_info();
 
         if cluster.security.secret_free_login {
@@ -160,6 +172,47 @@ pub async fn check_login_auth(
         Ok(false)
     }
 
+    // Tries the authentication methods of the listener in order, the first one that passes wins.
+    async fn listener_check_login_auth(
+        &self,
+        listener: &Listener,
+        login: &Option<Login>,
+        cert_identities: &[String],
+    ) -> bool {
+        for method in listener.authentication.iter() {
+            match method.as_str() {
+                AUTH_METHOD_ANONYMOUS => return true,
+                AUTH_METHOD_PASSWORD => {
+                    if let Some(info) = login {
+                        if let Ok(true) = self
+                            .plaintext_check_login(&info.username, &info.password)
+                            .await
+                        {
+                            return true;
+                        }
+                    }
+                }
+                // The client certificate has already been verified during the TLS handshake, the
+                // username has to be one of its names
+                AUTH_METHOD_X509 => {
+                    if listener.protocol == LISTENER_PROTOCOL_TLS
+                        && !listener.ca_cert.is_empty()
+                        && x509_check_login(login, cert_identities)
+                    {
+                        return true;
+                    }
+                }
+                _ => {
+                    warn!(
+                        "Listener {} has an unknown authentication method {}",
+                        listener.name, method
+                    );
+                }
+            }
+        }
+        false
+    }
+
     pub async fn save_acl(&self, acl: MqttAcl) -> Result<(), MqttBrokerError> {
         self.cache_manager.add_acl(acl.clone());
         self.driver.save_acl(acl).await
@@ -66,6 +66,8 @@ async fn read_all_user(&self) -> Result<DashMap<String, MqttUser>, MqttBrokerErr
                 username: raw.0.clone(),
                 password: raw.1.clone(),
                 is_superuser: raw.3 == 1,
+                mountpoint: "".to_string(),
+                tenant: "".to_string(),
             };
             results.insert(raw.0.clone(), user);
         }
@@ -106,6 +108,7 @@ async fn read_all_acl(&self) -> Result<Vec<MqttAcl>, MqttBrokerError> {
                     5 => MqttAclAction::Qos,
                     _ => return Err(MqttBrokerError::InvalidAclAction),
                 },
+                tenant: "".to_string(),
             };
             results.push(acl);
         }
@@ -129,6 +132,8 @@ async fn get_user(&self, username: String) -> Result<Option<MqttUser>, MqttBroke
                 username: value.0.clone(),
                 password: value.1.clone(),
                 is_superuser: value.3 == 1,
+                mountpoint: "".to_string(),
+                tenant: "".to_string(),
             }));
         }
         return Ok(None);
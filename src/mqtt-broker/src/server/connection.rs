@@ -53,6 +53,11 @@ pub struct NetworkConnection {
     pub connection_id: u64,
     pub protocol: Option<MqttProtocol>,
     pub addr: SocketAddr,
+    // Name of the listener that accepted the connection
+    pub listener: Option<String>,
+    // Common names and subject alternative names of the client certificate
+    #[serde(default)]
+    pub cert_identities: Vec<String>,
     #[serde(skip_serializing, skip_deserializing)]
     pub connection_stop_sx: Option<mpsc::Sender<bool>>,
 }
@@ -69,6 +74,8 @@ pub fn new(
             connection_id,
             protocol: None,
             addr,
+            listener: None,
+            cert_identities: Vec::new(),
             connection_stop_sx,
         }
     }
@@ -77,6 +84,14 @@ pub fn connection_id(&self) -> u64 {
         self.connection_id
     }
 
+    pub fn set_listener(&mut self, listener: String) {
+        self.listener = Some(listener);
+    }
+
+    pub fn set_cert_identities(&mut self, cert_identities: Vec<String>) {
+        self.cert_identities = cert_identities;
+    }
+
     pub fn set_protocol(&mut self, protocol: MqttProtocol) {
         self.protocol = Some(protocol);
     }
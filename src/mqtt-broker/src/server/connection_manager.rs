@@ -16,18 +16,21 @@
 use std::time::Duration;
 
 use axum::extract::ws::{Message, WebSocket};
+use common_base::config::broker_mqtt::Listener;
 use dashmap::DashMap;
 use futures::stream::SplitSink;
 use futures::SinkExt;
 use log::{debug, info};
 use protocol::mqtt::codec::{MqttCodec, MqttPacketWrapper};
 use protocol::mqtt::common::MqttProtocol;
+use tokio::sync::Mutex;
 use tokio::time::sleep;
 use tokio_util::codec::FramedWrite;
 
 use super::connection::{NetworkConnection, NetworkConnectionType};
 use crate::handler::cache::CacheManager;
 use crate::handler::error::MqttBrokerError;
+use crate::handler::topic_alias::OutboundTopicAlias;
 use crate::observability::metrics::packets::record_sent_metrics;
 use crate::server::quic::quic_stream_wrapper::QuicFramedWriteStream;
 
@@ -44,6 +47,9 @@ pub struct ConnectionManager {
     >,
     websocket_write_list: DashMap<u64, SplitSink<WebSocket, Message>>,
     quic_write_list: DashMap<u64, QuicFramedWriteStream>,
+    outbound_topic_alias: DashMap<u64, Arc<Mutex<OutboundTopicAlias>>>,
+    // (listener name, connection num)
+    listener_connection_num: DashMap<String, usize>,
     cache_manager: Arc<CacheManager>,
 }
 
@@ -54,6 +60,8 @@ pub fn new(cache_manager: Arc<CacheManager>) -> ConnectionManager {
         let tcp_tls_write_list = DashMap::with_capacity(64);
         let websocket_write_list = DashMap::with_capacity(64);
         let quic_write_list = DashMap::with_capacity(64);
+        let outbound_topic_alias = DashMap::with_capacity(64);
+        let listener_connection_num = DashMap::with_capacity(2);
         ConnectionManager {
             connections,
             tcp_write_list,
@@ -61,15 +69,30 @@ pub fn new(cache_manager: Arc<CacheManager>) -> ConnectionManager {
             cache_manager,
             websocket_write_list,
             quic_write_list,
+            outbound_topic_alias,
+            listener_connection_num,
         }
     }
 
     pub fn add_connection(&self, connection: NetworkConnection) -> u64 {
         let connection_id = connection.connection_id();
+        if let Some(listener) = &connection.listener {
+            *self
+                .listener_connection_num
+                .entry(listener.clone())
+                .or_insert(0) += 1;
+        }
         self.connections.insert(connection_id, connection);
         connection_id
     }
 
+    pub fn listener_connection_num(&self, listener: &str) -> usize {
+        if let Some(num) = self.listener_connection_num.get(listener) {
+            return *num;
+        }
+        0
+    }
+
     pub fn list_connect(&self) -> DashMap<u64, NetworkConnection> {
         self.connections.clone()
     }
@@ -105,17 +128,43 @@ pub fn add_quic_write(
             .insert(connection_id, quic_framed_write_stream);
     }
 
-    pub async fn close_all_connect(&self) {
-        for (connect_id, _) in self.connections.clone() {
-            self.close_connect(connect_id).await;
+    // Topic aliases used when sending Publish packets to the client, None if the client does not accept topic aliases.
+    pub fn get_outbound_topic_alias(
+        &self,
+        connection_id: u64,
+    ) -> Option<Arc<Mutex<OutboundTopicAlias>>> {
+        if let Some(topic_alias) = self.outbound_topic_alias.get(&connection_id) {
+            return Some(topic_alias.clone());
+        }
+
+        let connection = self.cache_manager.get_connection(connection_id)?;
+        if connection.client_topic_alias_max == 0 {
+            return None;
         }
+
+        let topic_alias = self
+            .outbound_topic_alias
+            .entry(connection_id)
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(OutboundTopicAlias::new(
+                    connection.client_topic_alias_max,
+                )))
+            });
+        Some(topic_alias.clone())
     }
 
     pub async fn close_connect(&self, connection_id: u64) {
         if let Some((_, connection)) = self.connections.remove(&connection_id) {
+            if let Some(listener) = &connection.listener {
+                if let Some(mut num) = self.listener_connection_num.get_mut(listener) {
+                    *num = num.saturating_sub(1);
+                }
+            }
             connection.stop_connection().await;
         }
 
+        self.outbound_topic_alias.remove(&connection_id);
+
         if let Some((id, mut stream)) = self.tcp_write_list.remove(&connection_id) {
             if stream.close().await.is_ok() {
                 debug!(
@@ -346,6 +395,11 @@ pub fn tcp_connect_num_check(&self) -> bool {
         false
     }
 
+    pub fn listener_connect_num_check(&self, listener: &Listener) -> bool {
+        listener.max_connections > 0
+            && self.listener_connection_num(&listener.name) >= listener.max_connections
+    }
+
     pub fn get_connect(&self, connect_id: u64) -> Option<NetworkConnection> {
         if let Some(connect) = self.connections.get(&connect_id) {
             return Some(connect.clone());
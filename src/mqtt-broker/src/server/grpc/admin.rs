@@ -20,8 +20,20 @@
 };
 use crate::admin::connector::{
     create_connector_by_req, delete_connector_by_req, list_connector_by_req,
+    pause_connector_by_req, reset_connector_offset_by_req, resume_connector_by_req,
     update_connector_by_req,
 };
+use crate::admin::dead_letter::{
+    list_dead_letter_message_by_req, redrive_dead_letter_message_by_req,
+};
+use crate::admin::delay_message::{
+    cancel_delay_message_by_req, get_delay_message_by_req, list_delay_message_by_req,
+};
+use crate::admin::listener::{list_listener_by_req, start_listener_by_req, stop_listener_by_req};
+use crate::admin::rule::{create_rule_by_req, delete_rule_by_req, list_rule_by_req};
+use crate::admin::scheduled_message::{
+    create_scheduled_message_by_req, delete_scheduled_message_by_req, list_scheduled_message_by_req,
+};
 use crate::admin::schema::{
     bind_schema_by_req, create_schema_by_req, delete_schema_by_req, list_bind_schema_by_req,
     list_schema_by_req, unbind_schema_by_req, update_schema_by_req,
@@ -29,62 +41,106 @@
 use crate::admin::subscribe::{
     delete_auto_subscribe_rule, list_auto_subscribe_rule_by_req, set_auto_subscribe_rule,
 };
+use crate::admin::tenant::{create_tenant_by_req, delete_tenant_by_req, list_tenant_by_req};
 use crate::admin::topic::{
     create_topic_rewrite_rule_by_req, delete_topic_rewrite_rule_by_req, list_topic_by_req,
 };
 use crate::admin::user::{create_user_by_req, delete_user_by_req, list_user_by_req};
+use crate::admin::wasm_plugin::{
+    create_wasm_plugin_by_req, delete_wasm_plugin_by_req, list_wasm_plugin_by_req,
+};
 use crate::admin::{
-    cluster_status_by_req, enable_flapping_detect_by_req, enable_slow_subscribe_by_req,
-    list_connection_by_req, list_slow_subscribe_by_req,
+    cluster_status_by_req, drain_node_by_req, enable_flapping_detect_by_req,
+    enable_slow_subscribe_by_req, list_connection_by_req, list_slow_subscribe_by_req,
+    undrain_node_by_req,
 };
+use crate::bridge::manager::ConnectorManager;
 use crate::handler::cache::CacheManager;
 use crate::server::connection_manager::ConnectionManager;
+use crate::server::listener::ListenerManager;
+use crate::subscribe::subscribe_manager::SubscribeManager;
+use delay_message::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use protocol::broker_mqtt::broker_mqtt_admin::mqtt_broker_admin_service_server::MqttBrokerAdminService;
 use protocol::broker_mqtt::broker_mqtt_admin::{
-    ClusterStatusReply, ClusterStatusRequest, CreateAclReply, CreateAclRequest,
-    CreateBlacklistReply, CreateBlacklistRequest, CreateTopicRewriteRuleReply,
-    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, DeleteAclReply,
-    DeleteAclRequest, DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest,
-    DeleteBlacklistReply, DeleteBlacklistRequest, DeleteTopicRewriteRuleReply,
-    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, EnableFlappingDetectReply,
+    CancelDelayMessageReply, CancelDelayMessageRequest, ClusterStatusReply, ClusterStatusRequest,
+    CreateAclReply, CreateAclRequest, CreateBlacklistReply, CreateBlacklistRequest,
+    CreateRuleReply, CreateRuleRequest, CreateScheduledMessageReply, CreateScheduledMessageRequest,
+    CreateTenantReply, CreateTenantRequest, CreateTopicRewriteRuleReply,
+    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply,
+    DeleteAutoSubscribeRuleRequest, DeleteBlacklistReply, DeleteBlacklistRequest, DeleteRuleReply,
+    DeleteRuleRequest, DeleteScheduledMessageReply, DeleteScheduledMessageRequest,
+    DeleteTenantReply, DeleteTenantRequest, DeleteTopicRewriteRuleReply,
+    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, DeleteWasmPluginReply,
+    DeleteWasmPluginRequest, DrainNodeReply, DrainNodeRequest, EnableFlappingDetectReply,
     EnableFlappingDetectRequest, EnableSlowSubScribeReply, EnableSlowSubscribeRequest,
-    ListAclReply, ListAclRequest, ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest,
-    ListBlacklistReply, ListBlacklistRequest, ListConnectionReply, ListConnectionRequest,
-    ListSlowSubscribeReply, ListSlowSubscribeRequest, ListTopicReply, ListTopicRequest,
-    ListUserReply, ListUserRequest, MqttBindSchemaReply, MqttBindSchemaRequest,
-    MqttCreateConnectorReply, MqttCreateConnectorRequest, MqttCreateSchemaReply,
-    MqttCreateSchemaRequest, MqttDeleteConnectorReply, MqttDeleteConnectorRequest,
-    MqttDeleteSchemaReply, MqttDeleteSchemaRequest, MqttListBindSchemaReply,
-    MqttListBindSchemaRequest, MqttListConnectorReply, MqttListConnectorRequest,
-    MqttListSchemaReply, MqttListSchemaRequest, MqttUnbindSchemaReply, MqttUnbindSchemaRequest,
-    MqttUpdateConnectorReply, MqttUpdateConnectorRequest, MqttUpdateSchemaReply,
-    MqttUpdateSchemaRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
+    GetDelayMessageReply, GetDelayMessageRequest, ListAclReply, ListAclRequest,
+    ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest, ListBlacklistReply,
+    ListBlacklistRequest, ListConnectionReply, ListConnectionRequest, ListDeadLetterMessageReply,
+    ListDeadLetterMessageRequest, ListDelayMessageReply, ListDelayMessageRequest,
+    ListListenerReply, ListListenerRequest, ListRuleReply, ListRuleRequest,
+    ListScheduledMessageReply, ListScheduledMessageRequest, ListSlowSubscribeReply,
+    ListSlowSubscribeRequest, ListTenantReply, ListTenantRequest, ListTopicReply, ListTopicRequest,
+    ListUserReply, ListUserRequest, ListWasmPluginReply, ListWasmPluginRequest,
+    MqttBindSchemaReply, MqttBindSchemaRequest, MqttCreateConnectorReply,
+    MqttCreateConnectorRequest, MqttCreateSchemaReply, MqttCreateSchemaRequest,
+    MqttDeleteConnectorReply, MqttDeleteConnectorRequest, MqttDeleteSchemaReply,
+    MqttDeleteSchemaRequest, MqttListBindSchemaReply, MqttListBindSchemaRequest,
+    MqttListConnectorReply, MqttListConnectorRequest, MqttListSchemaReply, MqttListSchemaRequest,
+    MqttPauseConnectorReply, MqttPauseConnectorRequest, MqttResetConnectorOffsetReply,
+    MqttResetConnectorOffsetRequest, MqttResumeConnectorReply, MqttResumeConnectorRequest,
+    MqttUnbindSchemaReply, MqttUnbindSchemaRequest, MqttUpdateConnectorReply,
+    MqttUpdateConnectorRequest, MqttUpdateSchemaReply, MqttUpdateSchemaRequest,
+    RedriveDeadLetterMessageReply, RedriveDeadLetterMessageRequest, ReloadListenerCertReply,
+    ReloadListenerCertRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
+    StartListenerReply, StartListenerRequest, StopListenerReply, StopListenerRequest,
+    UndrainNodeReply, UndrainNodeRequest,
 };
+use storage_adapter::storage::StorageAdapter;
 use tonic::{Request, Response, Status};
 
-pub struct GrpcAdminServices {
+pub struct GrpcAdminServices<S> {
     client_pool: Arc<ClientPool>,
     cache_manager: Arc<CacheManager>,
     connection_manager: Arc<ConnectionManager>,
+    subscribe_manager: Arc<SubscribeManager>,
+    message_storage_adapter: Arc<S>,
+    listener_manager: Arc<ListenerManager<S>>,
+    connector_manager: Arc<ConnectorManager>,
+    delay_message_manager: Arc<DelayMessageManager<S>>,
 }
 
-impl GrpcAdminServices {
+impl<S> GrpcAdminServices<S> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         client_pool: Arc<ClientPool>,
         cache_manager: Arc<CacheManager>,
         connection_manager: Arc<ConnectionManager>,
+        subscribe_manager: Arc<SubscribeManager>,
+        message_storage_adapter: Arc<S>,
+        listener_manager: Arc<ListenerManager<S>>,
+        connector_manager: Arc<ConnectorManager>,
+        delay_message_manager: Arc<DelayMessageManager<S>>,
     ) -> Self {
         GrpcAdminServices {
             client_pool,
             cache_manager,
             connection_manager,
+            subscribe_manager,
+            message_storage_adapter,
+            listener_manager,
+            connector_manager,
+            delay_message_manager,
         }
     }
 }
 
 #[tonic::async_trait]
-impl MqttBrokerAdminService for GrpcAdminServices {
+impl<S> MqttBrokerAdminService for GrpcAdminServices<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
     // --- cluster ---
     async fn cluster_status(
         &self,
@@ -96,6 +152,56 @@ async fn cluster_status(
         }
     }
 
+    async fn mqtt_broker_drain_node(
+        &self,
+        request: Request<DrainNodeRequest>,
+    ) -> Result<Response<DrainNodeReply>, Status> {
+        drain_node_by_req(
+            &self.cache_manager,
+            &self.connection_manager,
+            &self.subscribe_manager,
+            &self.client_pool,
+            request,
+        )
+        .await
+    }
+
+    async fn mqtt_broker_undrain_node(
+        &self,
+        request: Request<UndrainNodeRequest>,
+    ) -> Result<Response<UndrainNodeReply>, Status> {
+        undrain_node_by_req(&self.cache_manager, request)
+    }
+
+    // --- listener ---
+    async fn mqtt_broker_list_listener(
+        &self,
+        request: Request<ListListenerRequest>,
+    ) -> Result<Response<ListListenerReply>, Status> {
+        list_listener_by_req(&self.listener_manager, request).await
+    }
+
+    async fn mqtt_broker_start_listener(
+        &self,
+        request: Request<StartListenerRequest>,
+    ) -> Result<Response<StartListenerReply>, Status> {
+        start_listener_by_req(&self.listener_manager, request).await
+    }
+
+    async fn mqtt_broker_stop_listener(
+        &self,
+        request: Request<StopListenerRequest>,
+    ) -> Result<Response<StopListenerReply>, Status> {
+        stop_listener_by_req(&self.listener_manager, request).await
+    }
+
+    async fn mqtt_broker_reload_listener_cert(
+        &self,
+        request: Request<ReloadListenerCertRequest>,
+    ) -> Result<Response<ReloadListenerCertReply>, Status> {
+        reload_listener_cert_by_req(&self.listener_manager, request).await
+    }
+
     // --- user ---
     async fn mqtt_broker_create_user(
         &self,
@@ -113,16 +219,106 @@ async fn mqtt_broker_delete_user(
 
     async fn mqtt_broker_list_user(
         &self,
-        _: Request<ListUserRequest>,
+        request: Request<ListUserRequest>,
     ) -> Result<Response<ListUserReply>, Status> {
-        list_user_by_req(&self.cache_manager, &self.client_pool).await
+        list_user_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_create_tenant(
+        &self,
+        request: Request<CreateTenantRequest>,
+    ) -> Result<Response<CreateTenantReply>, Status> {
+        create_tenant_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_delete_tenant(
+        &self,
+        request: Request<DeleteTenantRequest>,
+    ) -> Result<Response<DeleteTenantReply>, Status> {
+        delete_tenant_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_list_tenant(
+        &self,
+        request: Request<ListTenantRequest>,
+    ) -> Result<Response<ListTenantReply>, Status> {
+        list_tenant_by_req(&self.cache_manager, request)
+    }
+
+    async fn mqtt_broker_create_rule(
+        &self,
+        request: Request<CreateRuleRequest>,
+    ) -> Result<Response<CreateRuleReply>, Status> {
+        create_rule_by_req(
+            &self.cache_manager,
+            &self.connector_manager,
+            &self.client_pool,
+            request,
+        )
+        .await
+    }
+
+    async fn mqtt_broker_delete_rule(
+        &self,
+        request: Request<DeleteRuleRequest>,
+    ) -> Result<Response<DeleteRuleReply>, Status> {
+        delete_rule_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_list_rule(
+        &self,
+        request: Request<ListRuleRequest>,
+    ) -> Result<Response<ListRuleReply>, Status> {
+        list_rule_by_req(&self.cache_manager, request)
+    }
+
+    async fn mqtt_broker_create_wasm_plugin(
+        &self,
+        request: Request<CreateWasmPluginRequest>,
+    ) -> Result<Response<CreateWasmPluginReply>, Status> {
+        create_wasm_plugin_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_delete_wasm_plugin(
+        &self,
+        request: Request<DeleteWasmPluginRequest>,
+    ) -> Result<Response<DeleteWasmPluginReply>, Status> {
+        delete_wasm_plugin_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_list_wasm_plugin(
+        &self,
+        request: Request<ListWasmPluginRequest>,
+    ) -> Result<Response<ListWasmPluginReply>, Status> {
+        list_wasm_plugin_by_req(&self.cache_manager, request)
+    }
+
+    async fn mqtt_broker_create_scheduled_message(
+        &self,
+        request: Request<CreateScheduledMessageRequest>,
+    ) -> Result<Response<CreateScheduledMessageReply>, Status> {
+        create_scheduled_message_by_req(&self.cache_manager, &self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_delete_scheduled_message(
+        &self,
+        request: Request<DeleteScheduledMessageRequest>,
+    ) -> Result<Response<DeleteScheduledMessageReply>, Status> {
+        delete_scheduled_message_by_req(&self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_list_scheduled_message(
+        &self,
+        request: Request<ListScheduledMessageRequest>,
+    ) -> Result<Response<ListScheduledMessageReply>, Status> {
+        list_scheduled_message_by_req(&self.client_pool, request).await
     }
 
     async fn mqtt_broker_list_acl(
         &self,
-        _: Request<ListAclRequest>,
+        request: Request<ListAclRequest>,
     ) -> Result<Response<ListAclReply>, Status> {
-        list_acl_by_req(&self.cache_manager, &self.client_pool).await
+        list_acl_by_req(&self.cache_manager, &self.client_pool, request).await
     }
 
     async fn mqtt_broker_create_acl(
@@ -221,7 +417,7 @@ async fn mqtt_broker_create_connector(
         &self,
         request: Request<MqttCreateConnectorRequest>,
     ) -> Result<Response<MqttCreateConnectorReply>, Status> {
-        create_connector_by_req(&self.client_pool, request).await
+        create_connector_by_req(&self.cache_manager, &self.client_pool, request).await
     }
 
     async fn mqtt_broker_delete_connector(
@@ -238,6 +434,28 @@ async fn mqtt_broker_update_connector(
         update_connector_by_req(&self.client_pool, request).await
     }
 
+    async fn mqtt_broker_pause_connector(
+        &self,
+        request: Request<MqttPauseConnectorRequest>,
+    ) -> Result<Response<MqttPauseConnectorReply>, Status> {
+        pause_connector_by_req(&self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_resume_connector(
+        &self,
+        request: Request<MqttResumeConnectorRequest>,
+    ) -> Result<Response<MqttResumeConnectorReply>, Status> {
+        resume_connector_by_req(&self.client_pool, request).await
+    }
+
+    async fn mqtt_broker_reset_connector_offset(
+        &self,
+        request: Request<MqttResetConnectorOffsetRequest>,
+    ) -> Result<Response<MqttResetConnectorOffsetReply>, Status> {
+        reset_connector_offset_by_req(&self.client_pool, &self.message_storage_adapter, request)
+            .await
+    }
+
     // --- schema ---
     async fn mqtt_broker_list_schema(
         &self,
@@ -250,7 +468,7 @@ async fn mqtt_broker_create_schema(
         &self,
         request: Request<MqttCreateSchemaRequest>,
     ) -> Result<Response<MqttCreateSchemaReply>, Status> {
-        create_schema_by_req(&self.client_pool, request).await
+        create_schema_by_req(&self.cache_manager, &self.client_pool, request).await
     }
 
     async fn mqtt_broker_update_schema(
@@ -304,8 +522,52 @@ async fn mqtt_broker_delete_auto_subscribe_rule(
 
     async fn mqtt_broker_list_auto_subscribe_rule(
         &self,
-        _request: Request<ListAutoSubscribeRuleRequest>,
+        request: Request<ListAutoSubscribeRuleRequest>,
     ) -> Result<Response<ListAutoSubscribeRuleReply>, Status> {
-        list_auto_subscribe_rule_by_req(&self.cache_manager)
+        list_auto_subscribe_rule_by_req(&self.cache_manager, request)
+    }
+
+    // --- dead letter ---
+    async fn mqtt_broker_list_dead_letter_message(
+        &self,
+        request: Request<ListDeadLetterMessageRequest>,
+    ) -> Result<Response<ListDeadLetterMessageReply>, Status> {
+        list_dead_letter_message_by_req(&self.cache_manager, &self.message_storage_adapter, request)
+            .await
+    }
+
+    async fn mqtt_broker_redrive_dead_letter_message(
+        &self,
+        request: Request<RedriveDeadLetterMessageRequest>,
+    ) -> Result<Response<RedriveDeadLetterMessageReply>, Status> {
+        redrive_dead_letter_message_by_req(
+            &self.cache_manager,
+            &self.connection_manager,
+            &self.message_storage_adapter,
+            request,
+        )
+        .await
+    }
+
+    // delay message
+    async fn mqtt_broker_list_delay_message(
+        &self,
+        request: Request<ListDelayMessageRequest>,
+    ) -> Result<Response<ListDelayMessageReply>, Status> {
+        list_delay_message_by_req(&self.delay_message_manager, request).await
+    }
+
+    async fn mqtt_broker_get_delay_message(
+        &self,
+        request: Request<GetDelayMessageRequest>,
+    ) -> Result<Response<GetDelayMessageReply>, Status> {
+        get_delay_message_by_req(&self.delay_message_manager, request).await
+    }
+
+    async fn mqtt_broker_cancel_delay_message(
+        &self,
+        request: Request<CancelDelayMessageRequest>,
+    ) -> Result<Response<CancelDelayMessageReply>, Status> {
+        cancel_delay_message_by_req(&self.delay_message_manager, request).await
     }
 }
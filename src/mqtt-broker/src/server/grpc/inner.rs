@@ -18,10 +18,12 @@
 use grpc_clients::pool::ClientPool;
 use log::{debug, info};
 use metadata_struct::mqtt::lastwill::LastWillData;
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
 use protocol::broker_mqtt::broker_mqtt_inner::mqtt_broker_inner_service_server::MqttBrokerInnerService;
 use protocol::broker_mqtt::broker_mqtt_inner::{
     DeleteSessionReply, DeleteSessionRequest, SendLastWillMessageReply, SendLastWillMessageRequest,
-    UpdateMqttCacheReply, UpdateMqttCacheRequest,
+    SendScheduledMessageReply, SendScheduledMessageRequest, UpdateMqttCacheReply,
+    UpdateMqttCacheRequest,
 };
 use schema_register::schema::SchemaRegisterManager;
 use storage_adapter::storage::StorageAdapter;
@@ -31,6 +33,8 @@
 use crate::handler::cache::CacheManager;
 use crate::handler::cache_update::update_cache_metadata;
 use crate::handler::lastwill::send_last_will_message;
+use crate::handler::publish::MessagePublisher;
+use crate::handler::scheduled_message::publish_scheduled_message;
 use crate::subscribe::subscribe_manager::SubscribeManager;
 
 pub struct GrpcInnerServices<S> {
@@ -40,6 +44,7 @@ pub struct GrpcInnerServices<S> {
     schema_manager: Arc<SchemaRegisterManager>,
     client_pool: Arc<ClientPool>,
     message_storage_adapter: Arc<S>,
+    message_publisher: MessagePublisher<S>,
 }
 
 impl<S> GrpcInnerServices<S> {
@@ -50,6 +55,7 @@ pub fn new(
         schema_manager: Arc<SchemaRegisterManager>,
         client_pool: Arc<ClientPool>,
         message_storage_adapter: Arc<S>,
+        message_publisher: MessagePublisher<S>,
     ) -> Self {
         GrpcInnerServices {
             cache_manager,
@@ -58,6 +64,7 @@ pub fn new(
             client_pool,
             message_storage_adapter,
             schema_manager,
+            message_publisher,
         }
     }
 }
@@ -143,4 +150,31 @@ async fn send_last_will_message(
             }
         }
     }
+
+    async fn send_scheduled_message(
+        &self,
+        request: Request<SendScheduledMessageRequest>,
+    ) -> Result<Response<SendScheduledMessageReply>, Status> {
+        let req = request.into_inner();
+        if self.cache_manager.cluster_name != req.cluster_name {
+            return Err(Status::cancelled("Cluster name does not match".to_string()));
+        }
+        let message = match serde_json::from_slice::<MqttScheduledMessage>(&req.message) {
+            Ok(message) => message,
+            Err(e) => {
+                return Err(Status::cancelled(e.to_string()));
+            }
+        };
+        debug!(
+            "Received scheduled message {} from placement center, topic: {}",
+            message.name, message.topic
+        );
+
+        match publish_scheduled_message(&self.cache_manager, &self.message_publisher, &message)
+            .await
+        {
+            Ok(()) => Ok(Response::new(SendScheduledMessageReply::default())),
+            Err(e) => Err(Status::internal(e.to_string())),
+        }
+    }
 }
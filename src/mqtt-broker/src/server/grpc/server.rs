@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use common_base::error::common::CommonError;
+use delay_message::DelayMessageManager;
 use grpc_clients::pool::ClientPool;
 use log::info;
 use protocol::broker_mqtt::broker_mqtt_admin::mqtt_broker_admin_service_server::MqttBrokerAdminServiceServer;
@@ -26,8 +27,10 @@
 use super::inner::GrpcInnerServices;
 use crate::bridge::manager::ConnectorManager;
 use crate::handler::cache::CacheManager;
+use crate::handler::publish::MessagePublisher;
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::grpc::admin::GrpcAdminServices;
+use crate::server::listener::ListenerManager;
 use crate::subscribe::subscribe_manager::SubscribeManager;
 
 pub struct GrpcServer<S> {
@@ -39,6 +42,9 @@ pub struct GrpcServer<S> {
     schema_manager: Arc<SchemaRegisterManager>,
     client_pool: Arc<ClientPool>,
     message_storage_adapter: Arc<S>,
+    listener_manager: Arc<ListenerManager<S>>,
+    delay_message_manager: Arc<DelayMessageManager<S>>,
+    message_publisher: MessagePublisher<S>,
 }
 
 impl<S> GrpcServer<S>
@@ -55,6 +61,9 @@ pub fn new(
         schema_manager: Arc<SchemaRegisterManager>,
         client_pool: Arc<ClientPool>,
         message_storage_adapter: Arc<S>,
+        listener_manager: Arc<ListenerManager<S>>,
+        delay_message_manager: Arc<DelayMessageManager<S>>,
+        message_publisher: MessagePublisher<S>,
     ) -> Self {
         Self {
             port,
@@ -65,6 +74,9 @@ pub fn new(
             client_pool,
             message_storage_adapter,
             schema_manager,
+            listener_manager,
+            delay_message_manager,
+            message_publisher,
         }
     }
     pub async fn start(&self) -> Result<(), CommonError> {
@@ -77,11 +89,17 @@ pub async fn start(&self) -> Result<(), CommonError> {
             self.schema_manager.clone(),
             self.client_pool.clone(),
             self.message_storage_adapter.clone(),
+            self.message_publisher.clone(),
         );
         let admin_handler = GrpcAdminServices::new(
             self.client_pool.clone(),
             self.metadata_cache.clone(),
             self.connection_manager.clone(),
+            self.subscribe_manager.clone(),
+            self.message_storage_adapter.clone(),
+            self.listener_manager.clone(),
+            self.connector_manager.clone(),
+            self.delay_message_manager.clone(),
         );
         Server::builder()
             .accept_http1(true)
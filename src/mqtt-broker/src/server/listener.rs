@@ -0,0 +1,574 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use common_base::config::broker_mqtt::{broker_mqtt_conf, BrokerMqttConfig, Listener};
+use dashmap::DashMap;
+use grpc_clients::pool::ClientPool;
+use log::{error, info, warn};
+use storage_adapter::storage::StorageAdapter;
+use tokio::sync::broadcast;
+
+use crate::handler::cache::CacheManager;
+use crate::handler::command::Command;
+use crate::handler::connection::disconnect_connection;
+use crate::handler::error::MqttBrokerError;
+use crate::security::login::AUTH_METHOD_X509;
+use crate::server::connection_manager::ConnectionManager;
+use crate::server::quic::server::start_quic_server;
+use crate::server::tcp::server::{ProcessorConfig, TcpServer};
+use crate::server::tcp::tls_cert::{
+    start_tls_cert_reload_thread, ReloadableTlsAcceptor, ReloadableTlsCert,
+};
+use crate::server::websocket::server::{websocket_server, websockets_server, WebSocketServerState};
+use crate::subscribe::subscribe_manager::SubscribeManager;
+
+pub const LISTENER_PROTOCOL_TCP: &str = "tcp";
+pub const LISTENER_PROTOCOL_TLS: &str = "tls";
+pub const LISTENER_PROTOCOL_WS: &str = "ws";
+pub const LISTENER_PROTOCOL_WSS: &str = "wss";
+pub const LISTENER_PROTOCOL_QUIC: &str = "quic";
+
+#[derive(Debug, Clone)]
+pub struct ListenerState {
+    pub listener: Listener,
+    pub running: bool,
+    pub connection_num: usize,
+}
+
+// Owns the TCP/TLS/WebSocket/QUIC listeners of the broker, each listener can be stopped and started at runtime
+// without affecting the others.
+pub struct ListenerManager<S> {
+    command: Command<S>,
+    proc_config: ProcessorConfig,
+    cache_manager: Arc<CacheManager>,
+    connection_manager: Arc<ConnectionManager>,
+    subscribe_manager: Arc<SubscribeManager>,
+    client_pool: Arc<ClientPool>,
+    // (listener name, stop sender of the running listener)
+    running: DashMap<String, broadcast::Sender<bool>>,
+    // (listener name, stop sender of the acceptors of the running listener)
+    accepting: DashMap<String, broadcast::Sender<bool>>,
+    // (listener name, certificates of the running TLS and QUIC listener)
+    tls_certs: DashMap<String, Arc<dyn ReloadableTlsCert>>,
+}
+
+impl<S> ListenerManager<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        command: Command<S>,
+        cache_manager: Arc<CacheManager>,
+        connection_manager: Arc<ConnectionManager>,
+        subscribe_manager: Arc<SubscribeManager>,
+        client_pool: Arc<ClientPool>,
+    ) -> Self {
+        let conf = broker_mqtt_conf();
+        let listeners = build_listeners(conf);
+        if let Err(e) = validate_listeners(&listeners) {
+            panic!("Invalid listener configuration, error message: {}", e);
+        }
+        for listener in listeners {
+            cache_manager.add_listener(listener);
+        }
+
+        let proc_config = ProcessorConfig {
+            accept_thread_num: conf.tcp_thread.accept_thread_num,
+            handler_process_num: conf.tcp_thread.handler_thread_num,
+            response_process_num: conf.tcp_thread.response_thread_num,
+        };
+
+        ListenerManager {
+            command,
+            proc_config,
+            cache_manager,
+            connection_manager,
+            subscribe_manager,
+            client_pool,
+            running: DashMap::with_capacity(2),
+            accepting: DashMap::with_capacity(2),
+            tls_certs: DashMap::with_capacity(2),
+        }
+    }
+
+    pub async fn start_all(&self, stop_sx: broadcast::Sender<bool>) {
+        let mut names: Vec<String> = self
+            .cache_manager
+            .listener_info
+            .iter()
+            .filter(|listener| listener.enable)
+            .map(|listener| listener.name.clone())
+            .collect();
+        names.sort();
+
+        for name in names {
+            if let Err(e) = self.start_listener(&name).await {
+                panic!("Failed to start listener {}, error message: {}", name, e);
+            }
+        }
+
+        let mut stop_rx = stop_sx.subscribe();
+        loop {
+            if let Ok(flag) = stop_rx.recv().await {
+                if flag {
+                    break;
+                }
+            }
+        }
+
+        self.stop_accepting();
+        for raw in self.running.iter() {
+            if let Err(e) = raw.value().send(true) {
+                error!(
+                    "Failed to stop listener {}, error message: {}",
+                    raw.key(),
+                    e
+                );
+            }
+        }
+        self.running.clear();
+    }
+
+    pub async fn start_listener(&self, name: &str) -> Result<(), MqttBrokerError> {
+        let Some(listener) = self.cache_manager.get_listener(name) else {
+            return Err(MqttBrokerError::ListenerNotExists(name.to_string()));
+        };
+
+        if self.running.contains_key(name) {
+            return Err(MqttBrokerError::ListenerAlreadyRunning(name.to_string()));
+        }
+
+        let (stop_sx, _) = broadcast::channel::<bool>(2);
+        let (accept_stop_sx, _) = broadcast::channel::<bool>(2);
+        let mut server = TcpServer::<S>::new(
+            self.command.clone(),
+            self.proc_config,
+            stop_sx.clone(),
+            accept_stop_sx.clone(),
+            self.connection_manager.clone(),
+            self.subscribe_manager.clone(),
+            self.cache_manager.clone(),
+            self.client_pool.clone(),
+        );
+
+        let tls_cert: Option<Arc<dyn ReloadableTlsCert>> = match listener.protocol.as_str() {
+            LISTENER_PROTOCOL_TCP => {
+                server.start(&listener).await?;
+                None
+            }
+            LISTENER_PROTOCOL_TLS => {
+                let tls_acceptor = Arc::new(ReloadableTlsAcceptor::new(&listener)?);
+                server.start_tls(&listener, tls_acceptor.clone()).await?;
+                Some(tls_acceptor)
+            }
+            LISTENER_PROTOCOL_WS => {
+                let state = WebSocketServerState::new(
+                    self.command.clone(),
+                    self.connection_manager.clone(),
+                    &listener,
+                    stop_sx.clone(),
+                );
+                websocket_server(state, accept_stop_sx.clone()).await?;
+                None
+            }
+            LISTENER_PROTOCOL_WSS => {
+                let state = WebSocketServerState::new(
+                    self.command.clone(),
+                    self.connection_manager.clone(),
+                    &listener,
+                    stop_sx.clone(),
+                );
+                let tls_acceptor = Arc::new(ReloadableTlsAcceptor::new(&listener)?);
+                websockets_server(state, tls_acceptor.clone(), accept_stop_sx.clone()).await?;
+                Some(tls_acceptor)
+            }
+            LISTENER_PROTOCOL_QUIC => start_quic_server(
+                self.command.clone(),
+                &listener,
+                self.proc_config,
+                self.connection_manager.clone(),
+                self.cache_manager.clone(),
+                self.subscribe_manager.clone(),
+                self.client_pool.clone(),
+                stop_sx.clone(),
+                accept_stop_sx.clone(),
+            )
+            .await?
+            .map(|quic_tls| quic_tls as Arc<dyn ReloadableTlsCert>),
+            _ => {
+                return Err(MqttBrokerError::ListenerProtocolNotSupported(
+                    listener.name,
+                    listener.protocol,
+                ));
+            }
+        };
+        if let Some(tls_cert) = tls_cert {
+            start_tls_cert_reload_thread(
+                tls_cert.clone(),
+                broker_mqtt_conf().network.tls_reload_interval_sec,
+                stop_sx.clone(),
+            );
+            self.tls_certs.insert(name.to_string(), tls_cert);
+        }
+
+        self.running.insert(name.to_string(), stop_sx);
+        self.accepting.insert(name.to_string(), accept_stop_sx);
+        Ok(())
+    }
+
+    // Closes the sockets of all listeners, the connected clients are still served
+    // until they are disconnected. Used on shutdown before the connections are drained.
+    pub fn stop_accepting(&self) {
+        for raw in self.accepting.iter() {
+            if let Err(e) = raw.value().send(true) {
+                warn!(
+                    "Failed to stop accepting connections on listener {}, error message: {}",
+                    raw.key(),
+                    e
+                );
+            }
+        }
+        self.accepting.clear();
+    }
+
+    // Stops accepting new connections on the listener and disconnects the clients connected through it.
+    pub async fn stop_listener(&self, name: &str) -> Result<usize, MqttBrokerError> {
+        if self.cache_manager.get_listener(name).is_none() {
+            return Err(MqttBrokerError::ListenerNotExists(name.to_string()));
+        }
+
+        let Some((_, stop_sx)) = self.running.remove(name) else {
+            return Err(MqttBrokerError::ListenerNotRunning(name.to_string()));
+        };
+        if let Some((_, accept_stop_sx)) = self.accepting.remove(name) {
+            if let Err(e) = accept_stop_sx.send(true) {
+                warn!("Failed to stop listener {}, error message: {}", name, e);
+            }
+        }
+        if let Err(e) = stop_sx.send(true) {
+            warn!("Failed to stop listener {}, error message: {}", name, e);
+        }
+        self.tls_certs.remove(name);
+
+        let mut connection_num = 0;
+        for (connect_id, connection) in self.connection_manager.list_connect() {
+            if connection.listener.as_deref() != Some(name) {
+                continue;
+            }
+
+            if let Some(mqtt_connection) = self.cache_manager.get_connection(connect_id) {
+                if let Err(e) = disconnect_connection(
+                    &mqtt_connection.client_id,
+                    connect_id,
+                    &self.cache_manager,
+                    &self.client_pool,
+                    &self.connection_manager,
+                    &self.subscribe_manager,
+                    false,
+                )
+                .await
+                {
+                    warn!(
+                        "Failed to disconnect client {} when stopping listener {}, error message: {}",
+                        mqtt_connection.client_id, name, e
+                    );
+                }
+            } else {
+                self.connection_manager.close_connect(connect_id).await;
+            }
+            connection_num += 1;
+        }
+
+        info!(
+            "Listener {} stopped, {} connections were disconnected",
+            name, connection_num
+        );
+        Ok(connection_num)
+    }
+
+    // Reloads the certificates of the given TLS, WebSocket TLS or QUIC listener, or of all running ones if the
+    // name is empty. Every listener is reloaded even if some of them fail, the error lists all
+    // the listeners that failed.
+    pub fn reload_tls_cert(&self, name: &str) -> Result<Vec<String>, MqttBrokerError> {
+        if name.is_empty() {
+            let mut tls_certs: Vec<Arc<dyn ReloadableTlsCert>> = self
+                .tls_certs
+                .iter()
+                .map(|raw| raw.value().clone())
+                .collect();
+            tls_certs.sort_by(|a, b| a.listener_name().cmp(b.listener_name()));
+
+            let mut names = Vec::new();
+            let mut failures = Vec::new();
+            for tls_cert in tls_certs {
+                match tls_cert.reload() {
+                    Ok(()) => names.push(tls_cert.listener_name().to_string()),
+                    Err(e) => failures.push(format!("{}: {}", tls_cert.listener_name(), e)),
+                }
+            }
+            if !failures.is_empty() {
+                return Err(MqttBrokerError::TlsCertReloadFailed(
+                    failures.join("; "),
+                    names.join(", "),
+                ));
+            }
+            return Ok(names);
+        }
+
+        let Some(listener) = self.cache_manager.get_listener(name) else {
+            return Err(MqttBrokerError::ListenerNotExists(name.to_string()));
+        };
+        if listener.tls_cert.is_empty()
+            || (listener.protocol != LISTENER_PROTOCOL_TLS
+                && listener.protocol != LISTENER_PROTOCOL_WSS
+                && listener.protocol != LISTENER_PROTOCOL_QUIC)
+        {
+            return Err(MqttBrokerError::ListenerNotTls(name.to_string()));
+        }
+        let Some(tls_cert) = self.tls_certs.get(name) else {
+            return Err(MqttBrokerError::ListenerNotRunning(name.to_string()));
+        };
+        tls_cert.reload()?;
+        Ok(vec![name.to_string()])
+    }
+
+    pub fn list_listener(&self) -> Vec<ListenerState> {
+        let mut results: Vec<ListenerState> = self
+            .cache_manager
+            .listener_info
+            .iter()
+            .map(|listener| ListenerState {
+                listener: listener.clone(),
+                running: self.running.contains_key(listener.key()),
+                connection_num: self
+                    .connection_manager
+                    .listener_connection_num(listener.key()),
+            })
+            .collect();
+        results.sort_by(|a, b| a.listener.name.cmp(&b.listener.name));
+        results
+    }
+}
+
+// Without any [[listeners]] configured, the broker keeps listening on the ports of [network].
+pub fn build_listeners(conf: &BrokerMqttConfig) -> Vec<Listener> {
+    if !conf.listeners.is_empty() {
+        return conf.listeners.clone();
+    }
+
+    vec![
+        Listener {
+            name: LISTENER_PROTOCOL_TCP.to_string(),
+            protocol: LISTENER_PROTOCOL_TCP.to_string(),
+            bind: format!("0.0.0.0:{}", conf.network.tcp_port),
+            enable: true,
+            ..Default::default()
+        },
+        Listener {
+            name: LISTENER_PROTOCOL_TLS.to_string(),
+            protocol: LISTENER_PROTOCOL_TLS.to_string(),
+            bind: format!("0.0.0.0:{}", conf.network.tcps_port),
+            enable: true,
+            tls_cert: conf.network.tls_cert.clone(),
+            tls_key: conf.network.tls_key.clone(),
+            ..Default::default()
+        },
+        Listener {
+            name: LISTENER_PROTOCOL_WS.to_string(),
+            protocol: LISTENER_PROTOCOL_WS.to_string(),
+            bind: format!("0.0.0.0:{}", conf.network.websocket_port),
+            enable: true,
+            ..Default::default()
+        },
+        Listener {
+            name: LISTENER_PROTOCOL_WSS.to_string(),
+            protocol: LISTENER_PROTOCOL_WSS.to_string(),
+            bind: format!("0.0.0.0:{}", conf.network.websockets_port),
+            enable: true,
+            tls_cert: conf.network.tls_cert.clone(),
+            tls_key: conf.network.tls_key.clone(),
+            ..Default::default()
+        },
+        Listener {
+            name: LISTENER_PROTOCOL_QUIC.to_string(),
+            protocol: LISTENER_PROTOCOL_QUIC.to_string(),
+            bind: format!("0.0.0.0:{}", conf.network.quic_port),
+            enable: true,
+            tls_cert: conf.network.tls_cert.clone(),
+            tls_key: conf.network.tls_key.clone(),
+            ..Default::default()
+        },
+    ]
+}
+
+// A listener with an unknown protocol is rejected at startup instead of being silently skipped.
+// The PROXY protocol header is only read by the tcp and tls listeners.
+pub fn validate_listeners(listeners: &[Listener]) -> Result<(), MqttBrokerError> {
+    let mut names = HashSet::new();
+    for listener in listeners {
+        // the admin operations find the listener by its name
+        if !names.insert(listener.name.as_str()) {
+            return Err(MqttBrokerError::ListenerNameDuplicated(
+                listener.name.clone(),
+            ));
+        }
+
+        let protocol = listener.protocol.as_str();
+        if ![
+            LISTENER_PROTOCOL_TCP,
+            LISTENER_PROTOCOL_TLS,
+            LISTENER_PROTOCOL_WS,
+            LISTENER_PROTOCOL_WSS,
+            LISTENER_PROTOCOL_QUIC,
+        ]
+        .contains(&protocol)
+        {
+            return Err(MqttBrokerError::ListenerProtocolNotSupported(
+                listener.name.clone(),
+                listener.protocol.clone(),
+            ));
+        }
+
+        if listener.proxy_protocol
+            && protocol != LISTENER_PROTOCOL_TCP
+            && protocol != LISTENER_PROTOCOL_TLS
+        {
+            return Err(MqttBrokerError::ListenerProxyProtocolNotSupported(
+                listener.name.clone(),
+                listener.protocol.clone(),
+            ));
+        }
+
+        // Only the tls listener reads the names of the client certificate, on the other
+        // listeners x509 would reject every client.
+        if listener
+            .authentication
+            .iter()
+            .any(|method| method == AUTH_METHOD_X509)
+            && (protocol != LISTENER_PROTOCOL_TLS || listener.ca_cert.is_empty())
+        {
+            return Err(MqttBrokerError::ListenerX509AuthNotSupported(
+                listener.name.clone(),
+                listener.protocol.clone(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use common_base::config::broker_mqtt::{BrokerMqttConfig, Listener};
+
+    use super::{build_listeners, validate_listeners};
+
+    #[test]
+    fn build_listeners_test() {
+        let mut conf = BrokerMqttConfig::default();
+        conf.network.tcp_port = 1883;
+        conf.network.tcps_port = 1885;
+        conf.network.tls_cert = "./config/example/certs/cert.pem".to_string();
+
+        conf.network.websocket_port = 8083;
+        conf.network.websockets_port = 8084;
+        conf.network.quic_port = 9083;
+
+        let listeners = build_listeners(&conf);
+        assert_eq!(listeners.len(), 5);
+        assert_eq!(listeners[0].name, "tcp");
+        assert_eq!(listeners[0].bind, "0.0.0.0:1883");
+        assert_eq!(listeners[1].protocol, "tls");
+        assert_eq!(listeners[1].bind, "0.0.0.0:1885");
+        assert_eq!(listeners[1].tls_cert, "./config/example/certs/cert.pem");
+        assert_eq!(listeners[2].protocol, "ws");
+        assert_eq!(listeners[2].bind, "0.0.0.0:8083");
+        assert_eq!(listeners[3].protocol, "wss");
+        assert_eq!(listeners[3].bind, "0.0.0.0:8084");
+        assert_eq!(listeners[3].tls_cert, "./config/example/certs/cert.pem");
+        assert_eq!(listeners[4].protocol, "quic");
+        assert_eq!(listeners[4].bind, "0.0.0.0:9083");
+
+        conf.listeners = vec![Listener {
+            name: "internal".to_string(),
+            protocol: "tcp".to_string(),
+            bind: "127.0.0.1:11883".to_string(),
+            enable: true,
+            ..Default::default()
+        }];
+        let listeners = build_listeners(&conf);
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].name, "internal");
+    }
+
+    #[test]
+    fn validate_listeners_test() {
+        let mut listeners = build_listeners(&BrokerMqttConfig::default());
+        assert!(validate_listeners(&listeners).is_ok());
+
+        listeners.push(Listener {
+            name: "mqtt-sn".to_string(),
+            protocol: "udp".to_string(),
+            bind: "0.0.0.0:1884".to_string(),
+            enable: true,
+            ..Default::default()
+        });
+        assert!(validate_listeners(&listeners).is_err());
+        listeners.pop();
+
+        for (protocol, allowed) in [
+            ("tcp", true),
+            ("tls", true),
+            ("wss", false),
+            ("quic", false),
+        ] {
+            listeners.push(Listener {
+                name: format!("proxy-{}", protocol),
+                protocol: protocol.to_string(),
+                bind: "0.0.0.0:11883".to_string(),
+                enable: true,
+                proxy_protocol: true,
+                ..Default::default()
+            });
+            assert_eq!(validate_listeners(&listeners).is_ok(), allowed);
+            listeners.pop();
+        }
+
+        for (protocol, ca_cert, allowed) in [
+            ("tls", "./config/example/certs/ca.pem", true),
+            ("tls", "", false),
+            ("wss", "./config/example/certs/ca.pem", false),
+            ("quic", "./config/example/certs/ca.pem", false),
+        ] {
+            listeners.push(Listener {
+                name: format!("x509-{}", protocol),
+                protocol: protocol.to_string(),
+                bind: "0.0.0.0:11883".to_string(),
+                enable: true,
+                ca_cert: ca_cert.to_string(),
+                authentication: vec!["x509".to_string(), "password".to_string()],
+                ..Default::default()
+            });
+            assert_eq!(validate_listeners(&listeners).is_ok(), allowed);
+            listeners.pop();
+        }
+
+        let mut duplicated = listeners[0].clone();
+        duplicated.bind = "0.0.0.0:11883".to_string();
+        listeners.push(duplicated);
+        assert!(validate_listeners(&listeners).is_err());
+    }
+}
@@ -21,9 +21,10 @@
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::packet::RequestPackage;
 use crate::server::quic::quic_stream_wrapper::{QuicFramedReadStream, QuicFramedWriteStream};
+use common_base::config::broker_mqtt::Listener;
 use log::{debug, error, info};
 use protocol::mqtt::codec::MqttCodec;
-use quinn::Endpoint;
+use quinn::{Endpoint, VarInt};
 use std::sync::Arc;
 use tokio::select;
 use tokio::sync::broadcast;
@@ -35,6 +36,7 @@ pub(crate) async fn acceptor_process(
     connection_manager: Arc<ConnectionManager>,
     stop_sx: broadcast::Sender<bool>,
     endpoint_arc: Arc<Endpoint>,
+    listener: Arc<Listener>,
     request_queue_sx: Sender<RequestPackage>,
     cache_manager: Arc<CacheManager>,
     network_connection_type: NetworkConnectionType,
@@ -46,6 +48,7 @@ pub(crate) async fn acceptor_process(
         let raw_request_queue_sx = request_queue_sx.clone();
         let network_type = network_connection_type.clone();
         let cache_manager = cache_manager.clone();
+        let listener = listener.clone();
         tokio::spawn(async move {
             debug!("Quic Server acceptor thread {} start successfully.", index);
             loop {
@@ -66,6 +69,13 @@ pub(crate) async fn acceptor_process(
                                 Ok(connection) => {
                                         info!("accept quic connection:{:?}",connection.remote_address());
                                         let client_addr = connection.remote_address();
+                                        if connection_manager.tcp_connect_num_check()
+                                            || connection_manager.listener_connect_num_check(&listener)
+                                        {
+                                            info!("quic connection from {} is refused, listener {} has too many connections", client_addr, listener.name);
+                                            connection.close(VarInt::from_u32(0), b"quota exceeded");
+                                            continue;
+                                        }
                                         match connection.accept_bi().await {
                                             Ok((w_stream, r_stream)) => {
                                                     let codec = MqttCodec::new(None);
@@ -74,11 +84,12 @@ pub(crate) async fn acceptor_process(
                                                     // todo we need to add quic_establish_connection_check
 
                                                 let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                                let connection = NetworkConnection::new(
+                                                let mut connection = NetworkConnection::new(
                                                     NetworkConnectionType::Quic,
                                                     client_addr,
                                                     Some(connection_stop_sx.clone())
                                                 );
+                                                connection.set_listener(listener.name.clone());
                                                 connection_manager.add_connection(connection.clone());
                                                 connection_manager.add_quic_write(connection.connection_id, quic_framed_write_stream);
                                                 read_frame_process(quic_framed_read_stream, connection.clone(), raw_request_queue_sx.clone(),connection_stop_rx, network_type.clone(), cache_manager.clone())
@@ -94,7 +105,8 @@ pub(crate) async fn acceptor_process(
                                 }
                             },
                             None => {
-                                error!("Quic Server acceptor thread {} stopped unexpectedly.",index);
+                                debug!("Quic Server acceptor thread {} stopped, the endpoint was closed.",index);
+                                break;
                             }
 
                         }
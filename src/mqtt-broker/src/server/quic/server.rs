@@ -15,25 +15,27 @@
 use crate::handler::cache::CacheManager;
 use crate::handler::command::Command;
 use crate::handler::error::MqttBrokerError;
-use crate::security::AuthDriver;
 use crate::server::connection::NetworkConnectionType;
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::packet::{RequestPackage, ResponsePackage};
 use crate::server::quic::handler::handler_process;
 use crate::server::quic::quic_server_handler::acceptor_process;
 use crate::server::quic::response::response_process;
+use crate::server::tcp::server::ProcessorConfig;
+use crate::server::tcp::tls_cert::{
+    build_tls_server_config, listener_cert_files, CertFileWatcher, ReloadableTlsCert,
+};
 use crate::subscribe::subscribe_manager::SubscribeManager;
-use common_base::config::broker_mqtt::broker_mqtt_conf;
-use delay_message::DelayMessageManager;
+use common_base::config::broker_mqtt::Listener;
 use grpc_clients::pool::ClientPool;
 use log::info;
 use quinn::{Connection, Endpoint, ServerConfig, VarInt};
 use rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer};
 use rustls_pki_types::PrivateKeyDer;
-use schema_register::schema::SchemaRegisterManager;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use storage_adapter::storage::StorageAdapter;
+use tokio::select;
 use tokio::sync::{broadcast, mpsc};
 
 pub fn generate_self_signed_cert() -> (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) {
@@ -42,56 +44,111 @@ pub fn generate_self_signed_cert() -> (Vec<CertificateDer<'static>>, PrivateKeyD
     let priv_key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
     (vec![cert_der.clone()], priv_key.into())
 }
+
+pub fn build_quic_server_config(listener: &Listener) -> Result<ServerConfig, MqttBrokerError> {
+    let tls_config = build_tls_server_config(listener)?;
+    let crypto = match quinn::crypto::rustls::QuicServerConfig::try_from(tls_config) {
+        Ok(data) => data,
+        Err(e) => return Err(MqttBrokerError::CommonError(e.to_string())),
+    };
+    Ok(ServerConfig::with_crypto(Arc::new(crypto)))
+}
+
+// The server config of the QUIC endpoint. Reloading replaces the config used by new
+// connections, the connections that are already established keep their session.
+pub struct ReloadableQuicServerConfig {
+    listener: Listener,
+    endpoint: Endpoint,
+    watcher: Mutex<CertFileWatcher>,
+}
+
+impl ReloadableQuicServerConfig {
+    pub fn new(listener: &Listener, endpoint: Endpoint) -> Self {
+        ReloadableQuicServerConfig {
+            listener: listener.clone(),
+            endpoint,
+            watcher: Mutex::new(CertFileWatcher::new(listener_cert_files(listener))),
+        }
+    }
+}
+
+impl ReloadableTlsCert for ReloadableQuicServerConfig {
+    fn listener_name(&self) -> &str {
+        &self.listener.name
+    }
+
+    fn watcher(&self) -> &Mutex<CertFileWatcher> {
+        &self.watcher
+    }
+
+    fn reload(&self) -> Result<(), MqttBrokerError> {
+        let config = build_quic_server_config(&self.listener)?;
+        self.endpoint.set_server_config(Some(config));
+        self.watcher.lock().unwrap().refresh();
+        info!(
+            "The TLS certificates of listener {} were reloaded",
+            self.listener.name
+        );
+        Ok(())
+    }
+}
+
+// Serves MQTT over QUIC on the bind address of a quic listener. A self-signed certificate is
+// used when the listener has no certificate, otherwise the reloadable certificates are returned.
 #[allow(clippy::too_many_arguments)]
-pub async fn start_quic_server<S>(
-    subscribe_manager: Arc<SubscribeManager>,
-    cache_manager: Arc<CacheManager>,
+pub(crate) async fn start_quic_server<S>(
+    command: Command<S>,
+    listener: &Listener,
+    proc_config: ProcessorConfig,
     connection_manager: Arc<ConnectionManager>,
-    message_storage_adapter: Arc<S>,
-    delay_message_manager: Arc<DelayMessageManager<S>>,
+    cache_manager: Arc<CacheManager>,
+    subscribe_manager: Arc<SubscribeManager>,
     client_pool: Arc<ClientPool>,
     stop_sx: broadcast::Sender<bool>,
-    auth_driver: Arc<AuthDriver>,
-    schema_register_manager: Arc<SchemaRegisterManager>,
-) where
+    accept_stop_sx: broadcast::Sender<bool>,
+) -> Result<Option<Arc<ReloadableQuicServerConfig>>, MqttBrokerError>
+where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
-    let conf = broker_mqtt_conf();
-    let command = Command::new(
-        cache_manager.clone(),
-        message_storage_adapter.clone(),
-        delay_message_manager.clone(),
-        subscribe_manager.clone(),
-        client_pool.clone(),
-        connection_manager.clone(),
-        schema_register_manager.clone(),
-        auth_driver.clone(),
-    );
-
-    let mut server = QuicServer::new(SocketAddr::new(
-        IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
-        conf.network.quic_port as u16,
-    ));
+    let addr: SocketAddr = match listener.bind.parse() {
+        Ok(addr) => addr,
+        Err(e) => return Err(MqttBrokerError::CommonError(e.to_string())),
+    };
+    let mut server = if listener.tls_cert.is_empty() {
+        QuicServer::new(addr)
+    } else {
+        QuicServer::with_server_config(addr, build_quic_server_config(listener)?)
+    };
     server.start();
 
     let quic_endpoint = server.get_endpoint();
+    let quic_tls = if listener.tls_cert.is_empty() {
+        None
+    } else {
+        Some(Arc::new(ReloadableQuicServerConfig::new(
+            listener,
+            quic_endpoint.clone(),
+        )))
+    };
+    start_endpoint_stop_thread(
+        quic_endpoint.clone(),
+        stop_sx.clone(),
+        accept_stop_sx.clone(),
+    );
 
     let (request_queue_sx, request_queue_rx) = mpsc::channel::<RequestPackage>(1000);
     let (response_queue_sx, response_queue_rx) = mpsc::channel::<ResponsePackage>(1000);
 
     let arc_quic_endpoint = Arc::new(quic_endpoint);
 
-    let accept_thread_num = conf.tcp_thread.accept_thread_num;
-    let handler_process_num = conf.tcp_thread.handler_thread_num;
-    let response_thread_num = conf.tcp_thread.response_thread_num;
-
     let connection_type = NetworkConnectionType::Quic;
 
     acceptor_process(
-        accept_thread_num,
+        proc_config.accept_thread_num,
         connection_manager.clone(),
-        stop_sx.clone(),
+        accept_stop_sx,
         arc_quic_endpoint.clone(),
+        Arc::new(listener.clone()),
         request_queue_sx,
         cache_manager.clone(),
         connection_type,
@@ -99,7 +156,7 @@ pub async fn start_quic_server<S>(
     .await;
 
     handler_process(
-        handler_process_num,
+        proc_config.handler_process_num,
         request_queue_rx,
         connection_manager.clone(),
         response_queue_sx,
@@ -109,7 +166,7 @@ pub async fn start_quic_server<S>(
     .await;
 
     response_process(
-        response_thread_num,
+        proc_config.response_process_num,
         connection_manager.clone(),
         cache_manager.clone(),
         subscribe_manager.clone(),
@@ -120,9 +177,45 @@ pub async fn start_quic_server<S>(
     .await;
 
     info!(
-        "MQTT Quic Server started successfully, listening port: {}",
-        server.local_addr().port()
+        "MQTT Quic Server started successfully, listener: {}, bind: {}",
+        listener.name,
+        server.local_addr()
     );
+    Ok(quic_tls)
+}
+
+// Refuses new connections once the acceptors are stopped, and closes the endpoint with all its
+// connections when the listener is stopped.
+fn start_endpoint_stop_thread(
+    endpoint: Endpoint,
+    stop_sx: broadcast::Sender<bool>,
+    accept_stop_sx: broadcast::Sender<bool>,
+) {
+    let mut stop_rx = stop_sx.subscribe();
+    let mut accept_stop_rx = accept_stop_sx.subscribe();
+    tokio::spawn(async move {
+        let mut accepting = true;
+        loop {
+            select! {
+                val = accept_stop_rx.recv(), if accepting => {
+                    if let Ok(true) = val {
+                        endpoint.set_server_config(None);
+                    }
+                    accepting = matches!(val, Ok(false));
+                }
+                val = stop_rx.recv() => {
+                    match val {
+                        Ok(true) => {
+                            endpoint.close(VarInt::from_u32(0), b"listener stopped");
+                            break;
+                        }
+                        Ok(false) => {}
+                        Err(_) => break,
+                    }
+                }
+            }
+        }
+    });
 }
 
 #[derive(Clone, Debug)]
@@ -176,6 +269,16 @@ pub fn new(addr: SocketAddr) -> Self {
         }
     }
 
+    pub fn with_server_config(addr: SocketAddr, server_config: ServerConfig) -> Self {
+        QuicServer {
+            quic_server_config: QuicServerConfig {
+                server_config,
+                bind_addr: addr,
+            },
+            endpoint: None,
+        }
+    }
+
     pub fn start(&mut self) {
         let endpoint = self.create_quinn_endpoint_as_a_quic_server();
         self.bind_address_for_quic_server_config(endpoint);
@@ -13,7 +13,9 @@
 // limitations under the License.
 
 mod handler;
+pub mod proxy_protocol;
 mod response;
 pub mod server;
 mod tcp_server;
-mod tls_server;
+pub mod tls_cert;
+pub mod tls_server;
@@ -0,0 +1,199 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::timeout;
+
+use crate::handler::error::MqttBrokerError;
+
+const PROXY_V1_PREFIX: &[u8] = b"PROXY";
+const PROXY_V1_MAX_LEN: usize = 107;
+const PROXY_V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Same as read_proxy_header, but gives up when the peer does not send the complete header in time,
+// so a connection that sends nothing is dropped instead of being held open.
+pub async fn read_proxy_header_timeout<T>(
+    stream: &mut T,
+) -> Result<Option<SocketAddr>, MqttBrokerError>
+where
+    T: AsyncRead + Unpin,
+{
+    match timeout(PROXY_HEADER_TIMEOUT, read_proxy_header(stream)).await {
+        Ok(result) => result,
+        Err(_) => Err(MqttBrokerError::ProxyProtocolHeaderInvalid(format!(
+            "header was not received within {}s",
+            PROXY_HEADER_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+// Reads the PROXY protocol header (v1 or v2) in front of the connection, and returns the source
+// address of the client. None is returned when the proxy does not know the source address.
+// Only the header is read from the stream, the MQTT packets that follow are left untouched.
+pub async fn read_proxy_header<T>(stream: &mut T) -> Result<Option<SocketAddr>, MqttBrokerError>
+where
+    T: AsyncRead + Unpin,
+{
+    let mut prefix = [0u8; 5];
+    stream.read_exact(&mut prefix).await?;
+
+    if prefix == PROXY_V1_PREFIX {
+        let mut line = prefix.to_vec();
+        while !line.ends_with(b"\r\n") {
+            if line.len() >= PROXY_V1_MAX_LEN {
+                return Err(MqttBrokerError::ProxyProtocolHeaderInvalid(
+                    "v1 header is too long".to_string(),
+                ));
+            }
+            line.push(stream.read_u8().await?);
+        }
+        return parse_proxy_v1(&line);
+    }
+
+    if prefix == PROXY_V2_SIGNATURE[..5] {
+        let mut header = [0u8; 16];
+        header[..5].copy_from_slice(&prefix);
+        stream.read_exact(&mut header[5..]).await?;
+        let len = u16::from_be_bytes([header[14], header[15]]) as usize;
+        let mut addr = vec![0u8; len];
+        stream.read_exact(&mut addr).await?;
+        return parse_proxy_v2(&header, &addr);
+    }
+
+    Err(MqttBrokerError::ProxyProtocolHeaderInvalid(
+        "missing PROXY protocol signature".to_string(),
+    ))
+}
+
+// PROXY TCP4 192.168.0.1 192.168.0.11 56324 1883\r\n
+fn parse_proxy_v1(line: &[u8]) -> Result<Option<SocketAddr>, MqttBrokerError> {
+    let line = std::str::from_utf8(line)
+        .map_err(|e| MqttBrokerError::ProxyProtocolHeaderInvalid(e.to_string()))?;
+    let parts: Vec<&str> = line.trim_end().split(' ').collect();
+    match parts.get(1) {
+        Some(&"UNKNOWN") => Ok(None),
+        Some(&"TCP4") | Some(&"TCP6") if parts.len() == 6 => {
+            let ip = parts[2]
+                .parse::<IpAddr>()
+                .map_err(|e| MqttBrokerError::ProxyProtocolHeaderInvalid(e.to_string()))?;
+            let port = parts[4]
+                .parse::<u16>()
+                .map_err(|e| MqttBrokerError::ProxyProtocolHeaderInvalid(e.to_string()))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(MqttBrokerError::ProxyProtocolHeaderInvalid(
+            line.to_string(),
+        )),
+    }
+}
+
+fn parse_proxy_v2(header: &[u8; 16], addr: &[u8]) -> Result<Option<SocketAddr>, MqttBrokerError> {
+    if header[..12] != *PROXY_V2_SIGNATURE {
+        return Err(MqttBrokerError::ProxyProtocolHeaderInvalid(
+            "invalid v2 signature".to_string(),
+        ));
+    }
+
+    let version = header[12] >> 4;
+    let command = header[12] & 0x0F;
+    if version != 2 {
+        return Err(MqttBrokerError::ProxyProtocolHeaderInvalid(format!(
+            "unsupported version {}",
+            version
+        )));
+    }
+
+    // LOCAL command, the connection was established by the proxy itself
+    if command == 0 {
+        return Ok(None);
+    }
+
+    match header[13] >> 4 {
+        // AF_INET
+        1 if addr.len() >= 12 => {
+            let ip = Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]);
+            let port = u16::from_be_bytes([addr[8], addr[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        // AF_INET6
+        2 if addr.len() >= 36 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&addr[..16]);
+            let ip = Ipv6Addr::from(octets);
+            let port = u16::from_be_bytes([addr[32], addr[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(ip), port)))
+        }
+        // AF_UNSPEC or AF_UNIX
+        0 | 3 => Ok(None),
+        family => Err(MqttBrokerError::ProxyProtocolHeaderInvalid(format!(
+            "unsupported address family {}",
+            family
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::{read_proxy_header, PROXY_V2_SIGNATURE};
+
+    #[tokio::test]
+    async fn proxy_v1_test() {
+        let mut data: &[u8] = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 1883\r\n\x10";
+        let addr = read_proxy_header(&mut data).await.unwrap();
+        assert_eq!(
+            addr,
+            Some("192.168.0.1:56324".parse::<SocketAddr>().unwrap())
+        );
+        // the MQTT data after the header is not consumed
+        assert_eq!(data, b"\x10");
+
+        let mut data: &[u8] = b"PROXY TCP6 ::1 ::1 56324 1883\r\n";
+        let addr = read_proxy_header(&mut data).await.unwrap();
+        assert_eq!(addr, Some("[::1]:56324".parse::<SocketAddr>().unwrap()));
+
+        let mut data: &[u8] = b"PROXY UNKNOWN\r\n";
+        assert_eq!(read_proxy_header(&mut data).await.unwrap(), None);
+
+        let mut data: &[u8] = b"\x10\x00\x00\x00\x00\x00";
+        assert!(read_proxy_header(&mut data).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn proxy_v2_test() {
+        let mut data = PROXY_V2_SIGNATURE.to_vec();
+        // version 2, PROXY command, AF_INET + STREAM, 12 bytes of address
+        data.extend_from_slice(&[0x21, 0x11, 0x00, 0x0C]);
+        data.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+        data.extend_from_slice(&56324u16.to_be_bytes());
+        data.extend_from_slice(&1883u16.to_be_bytes());
+        data.push(0x10);
+
+        let mut reader: &[u8] = &data;
+        let addr = read_proxy_header(&mut reader).await.unwrap();
+        assert_eq!(addr, Some("10.0.0.1:56324".parse::<SocketAddr>().unwrap()));
+        assert_eq!(reader, b"\x10");
+
+        // LOCAL command
+        let mut data = PROXY_V2_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+        let mut reader: &[u8] = &data;
+        assert_eq!(read_proxy_header(&mut reader).await.unwrap(), None);
+    }
+}
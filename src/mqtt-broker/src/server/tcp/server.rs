@@ -14,85 +14,29 @@
 
 use std::sync::Arc;
 
-use common_base::config::broker_mqtt::broker_mqtt_conf;
-use delay_message::DelayMessageManager;
+use common_base::config::broker_mqtt::Listener;
 use grpc_clients::pool::ClientPool;
 use log::info;
-use schema_register::schema::SchemaRegisterManager;
 use storage_adapter::storage::StorageAdapter;
 use tokio::net::TcpListener;
 use tokio::sync::{broadcast, mpsc};
 
 use crate::handler::cache::CacheManager;
 use crate::handler::command::Command;
-use crate::security::AuthDriver;
+use crate::handler::error::MqttBrokerError;
 use crate::server::connection::NetworkConnectionType;
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::packet::{RequestPackage, ResponsePackage};
 use crate::server::tcp::handler::handler_process;
 use crate::server::tcp::response::response_process;
 use crate::server::tcp::tcp_server::acceptor_process;
+use crate::server::tcp::tls_cert::ReloadableTlsAcceptor;
 use crate::server::tcp::tls_server::acceptor_tls_process;
 use crate::subscribe::subscribe_manager::SubscribeManager;
 
-#[allow(clippy::too_many_arguments)]
-pub async fn start_tcp_server<S>(
-    subscribe_manager: Arc<SubscribeManager>,
-    cache_manager: Arc<CacheManager>,
-    connection_manager: Arc<ConnectionManager>,
-    message_storage_adapter: Arc<S>,
-    delay_message_manager: Arc<DelayMessageManager<S>>,
-    schema_manager: Arc<SchemaRegisterManager>,
-    client_pool: Arc<ClientPool>,
-    stop_sx: broadcast::Sender<bool>,
-    auth_driver: Arc<AuthDriver>,
-) where
-    S: StorageAdapter + Sync + Send + 'static + Clone,
-{
-    let conf = broker_mqtt_conf();
-    let command = Command::new(
-        cache_manager.clone(),
-        message_storage_adapter.clone(),
-        delay_message_manager.clone(),
-        subscribe_manager.clone(),
-        client_pool.clone(),
-        connection_manager.clone(),
-        schema_manager.clone(),
-        auth_driver.clone(),
-    );
-
-    let proc_config = ProcessorConfig {
-        accept_thread_num: conf.tcp_thread.accept_thread_num,
-        handler_process_num: conf.tcp_thread.handler_thread_num,
-        response_process_num: conf.tcp_thread.response_thread_num,
-    };
-
-    let mut server = TcpServer::<S>::new(
-        command.clone(),
-        proc_config,
-        stop_sx.clone(),
-        connection_manager.clone(),
-        subscribe_manager.clone(),
-        cache_manager.clone(),
-        client_pool.clone(),
-    );
-    server.start(conf.network.tcp_port).await;
-
-    let mut server = TcpServer::<S>::new(
-        command,
-        proc_config,
-        stop_sx.clone(),
-        connection_manager,
-        subscribe_manager.clone(),
-        cache_manager,
-        client_pool,
-    );
-    server.start_tls(conf.network.tcps_port).await;
-}
-
 // U: codec: encoder + decoder
 // S: message storage adapter
-struct TcpServer<S> {
+pub(crate) struct TcpServer<S> {
     command: Command<S>,
     connection_manager: Arc<ConnectionManager>,
     cache_manager: Arc<CacheManager>,
@@ -102,11 +46,13 @@ struct TcpServer<S> {
     handler_process_num: usize,
     response_process_num: usize,
     stop_sx: broadcast::Sender<bool>,
+    // Stops only the acceptors, the connected clients are still served
+    accept_stop_sx: broadcast::Sender<bool>,
     network_connection_type: NetworkConnectionType,
 }
 
 #[derive(Debug, Clone, Copy)]
-struct ProcessorConfig {
+pub(crate) struct ProcessorConfig {
     pub accept_thread_num: usize,
     pub handler_process_num: usize,
     pub response_process_num: usize,
@@ -120,6 +66,7 @@ pub fn new(
         command: Command<S>,
         proc_config: ProcessorConfig,
         stop_sx: broadcast::Sender<bool>,
+        accept_stop_sx: broadcast::Sender<bool>,
         connection_manager: Arc<ConnectionManager>,
         subscribe_manager: Arc<SubscribeManager>,
         cache_manager: Arc<CacheManager>,
@@ -134,18 +81,14 @@ pub fn new(
             handler_process_num: proc_config.handler_process_num,
             response_process_num: proc_config.response_process_num,
             stop_sx,
+            accept_stop_sx,
             network_connection_type: NetworkConnectionType::Tcp,
             subscribe_manager,
         }
     }
 
-    pub async fn start(&mut self, port: u32) {
-        let listener = match TcpListener::bind(format!("0.0.0.0:{}", port)).await {
-            Ok(tl) => tl,
-            Err(e) => {
-                panic!("{}", e.to_string());
-            }
-        };
+    pub async fn start(&mut self, listener_conf: &Listener) -> Result<(), MqttBrokerError> {
+        let listener = TcpListener::bind(&listener_conf.bind).await?;
         let (request_queue_sx, request_queue_rx) = mpsc::channel::<RequestPackage>(1000);
         let (response_queue_sx, response_queue_rx) = mpsc::channel::<ResponsePackage>(1000);
 
@@ -154,8 +97,9 @@ pub async fn start(&mut self, port: u32) {
         acceptor_process(
             self.accept_thread_num,
             self.connection_manager.clone(),
-            self.stop_sx.clone(),
+            self.accept_stop_sx.clone(),
             arc_listener.clone(),
+            Arc::new(listener_conf.clone()),
             request_queue_sx,
             self.cache_manager.clone(),
             self.network_connection_type.clone(),
@@ -184,16 +128,19 @@ pub async fn start(&mut self, port: u32) {
         .await;
 
         self.network_connection_type = NetworkConnectionType::Tcp;
-        info!("MQTT TCP Server started successfully, listening port: {port}");
+        info!(
+            "MQTT TCP Server started successfully, listener: {}, bind: {}",
+            listener_conf.name, listener_conf.bind
+        );
+        Ok(())
     }
 
-    pub async fn start_tls(&mut self, port: u32) {
-        let listener = match TcpListener::bind(format!("0.0.0.0:{}", port)).await {
-            Ok(tl) => tl,
-            Err(e) => {
-                panic!("{}", e.to_string());
-            }
-        };
+    pub async fn start_tls(
+        &mut self,
+        listener_conf: &Listener,
+        tls_acceptor: Arc<ReloadableTlsAcceptor>,
+    ) -> Result<(), MqttBrokerError> {
+        let listener = TcpListener::bind(&listener_conf.bind).await?;
         let (request_queue_sx, request_queue_rx) = mpsc::channel::<RequestPackage>(1000);
         let (response_queue_sx, response_queue_rx) = mpsc::channel::<ResponsePackage>(1000);
 
@@ -202,10 +149,12 @@ pub async fn start_tls(&mut self, port: u32) {
         acceptor_tls_process(
             self.accept_thread_num,
             arc_listener.clone(),
-            self.stop_sx.clone(),
-            self.network_connection_type.clone(),
+            Arc::new(listener_conf.clone()),
+            self.accept_stop_sx.clone(),
+            NetworkConnectionType::Tls,
             self.connection_manager.clone(),
             request_queue_sx,
+            tls_acceptor,
         )
         .await;
 
@@ -230,6 +179,10 @@ pub async fn start_tls(&mut self, port: u32) {
         )
         .await;
         self.network_connection_type = NetworkConnectionType::Tls;
-        info!("MQTT TCP TLS Server started successfully, listening port: {port}");
+        info!(
+            "MQTT TCP TLS Server started successfully, listener: {}, bind: {}",
+            listener_conf.name, listener_conf.bind
+        );
+        Ok(())
     }
 }
@@ -12,13 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use common_base::config::broker_mqtt::Listener;
 use futures_util::StreamExt;
 use log::{debug, error, info};
 use protocol::mqtt::codec::MqttCodec;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::time::sleep;
@@ -34,6 +36,7 @@
 use crate::server::connection::{NetworkConnection, NetworkConnectionType};
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::packet::RequestPackage;
+use crate::server::tcp::proxy_protocol::read_proxy_header_timeout;
 
 /// The `acceptor_process` function is responsible for accepting incoming TCP connections
 /// in an asynchronous manner. It utilizes multiple threads to handle the incoming connections
@@ -44,15 +47,18 @@
 /// - `connection_manager`: An `Arc`-wrapped `ConnectionManager` instance for managing all network connections.
 /// - `stop_sx`: A `broadcast::Sender` used to send stop signals to all acceptor threads.
 /// - `listener_arc`: An `Arc`-wrapped `TcpListener` for listening to and accepting TCP connections.
+/// - `listener_conf`: The configuration of the named listener that owns the socket.
 /// - `request_queue_sx`: A `Sender` for sending `RequestPackage` instances to a processing queue.
 /// - `cache_manager`: An `Arc`-wrapped `CacheManager` for managing cache operations.
 /// - `network_connection_type`: An enum indicating the type of network connection.
 ///
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn acceptor_process(
     accept_thread_num: usize,
     connection_manager: Arc<ConnectionManager>,
     stop_sx: broadcast::Sender<bool>,
     listener_arc: Arc<TcpListener>,
+    listener_conf: Arc<Listener>,
     request_queue_sx: Sender<RequestPackage>,
     cache_manager: Arc<CacheManager>,
     network_connection_type: NetworkConnectionType,
@@ -64,6 +70,7 @@ pub(crate) async fn acceptor_process(
         let raw_request_queue_sx = request_queue_sx.clone();
         let network_type = network_connection_type.clone();
         let cache_manager = cache_manager.clone();
+        let listener_conf = listener_conf.clone();
         tokio::spawn(async move {
             debug!("TCP Server acceptor thread {} start successfully.", index);
             loop {
@@ -81,27 +88,17 @@ pub(crate) async fn acceptor_process(
                         match val{
                             Ok((stream, addr)) => {
                                 info!("accept tcp connection:{:?}",addr);
-
-                                let (r_stream, w_stream) = io::split(stream);
-                                let codec = MqttCodec::new(None);
-                                let read_frame_stream = FramedRead::new(r_stream, codec.clone());
-                                let mut  write_frame_stream = FramedWrite::new(w_stream, codec.clone());
-
-                                if !tcp_establish_connection_check(&addr,&connection_manager,&mut write_frame_stream).await{
-                                    continue;
-                                }
-
-                                let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                let connection = NetworkConnection::new(
-                                    NetworkConnectionType::Tcp,
+                                // The connection is set up in its own task, so a peer that is slow to send the
+                                // PROXY protocol header does not hold up the acceptor.
+                                tokio::spawn(establish_connection(
+                                    stream,
                                     addr,
-                                    Some(connection_stop_sx.clone())
-                                );
-
-                                connection_manager.add_connection(connection.clone());
-                                connection_manager.add_tcp_write(connection.connection_id, write_frame_stream);
-
-                                read_frame_process(read_frame_stream,connection,raw_request_queue_sx.clone(),connection_stop_rx,network_type.clone(),cache_manager.clone());
+                                    listener_conf.clone(),
+                                    connection_manager.clone(),
+                                    raw_request_queue_sx.clone(),
+                                    network_type.clone(),
+                                    cache_manager.clone(),
+                                ));
                             }
                             Err(e) => {
                                 error!("TCP accept failed to create connection with error message :{:?}",e);
@@ -114,6 +111,66 @@ pub(crate) async fn acceptor_process(
     }
 }
 
+async fn establish_connection(
+    mut stream: TcpStream,
+    mut addr: SocketAddr,
+    listener_conf: Arc<Listener>,
+    connection_manager: Arc<ConnectionManager>,
+    request_queue_sx: Sender<RequestPackage>,
+    network_type: NetworkConnectionType,
+    cache_manager: Arc<CacheManager>,
+) {
+    if listener_conf.proxy_protocol {
+        match read_proxy_header_timeout(&mut stream).await {
+            Ok(Some(source)) => addr = source,
+            Ok(None) => {}
+            Err(e) => {
+                error!(
+                    "Listener {} failed to read PROXY protocol header from {addr}, error message: {e}",
+                    listener_conf.name
+                );
+                return;
+            }
+        }
+    }
+
+    let (r_stream, w_stream) = io::split(stream);
+    let codec = MqttCodec::new(None);
+    let read_frame_stream = FramedRead::new(r_stream, codec.clone());
+    let mut write_frame_stream = FramedWrite::new(w_stream, codec.clone());
+
+    if !tcp_establish_connection_check(
+        &addr,
+        &listener_conf,
+        &connection_manager,
+        &mut write_frame_stream,
+    )
+    .await
+    {
+        return;
+    }
+
+    let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
+    let mut connection = NetworkConnection::new(
+        NetworkConnectionType::Tcp,
+        addr,
+        Some(connection_stop_sx.clone()),
+    );
+    connection.set_listener(listener_conf.name.clone());
+
+    connection_manager.add_connection(connection.clone());
+    connection_manager.add_tcp_write(connection.connection_id, write_frame_stream);
+
+    read_frame_process(
+        read_frame_stream,
+        connection,
+        request_queue_sx,
+        connection_stop_rx,
+        network_type,
+        cache_manager,
+    );
+}
+
 fn read_frame_process(
     mut read_frame_stream: FramedRead<io::ReadHalf<tokio::net::TcpStream>, MqttCodec>,
     connection: NetworkConnection,
@@ -0,0 +1,357 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use common_base::config::broker_mqtt::Listener;
+use log::{debug, error, info};
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+use tokio_rustls::rustls::crypto::ring::sign::any_supported_type;
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+use crate::handler::error::MqttBrokerError;
+use crate::server::tcp::tls_server::{load_certs, load_key};
+
+// Picks the certificate by the SNI of the client, falling back to the default certificate
+// when the client sends no SNI or no certificate matches.
+#[derive(Debug)]
+pub struct SniCertResolver {
+    default: Arc<CertifiedKey>,
+    // (server name, certificate), wildcard names are kept as *.example.com
+    sni: HashMap<String, Arc<CertifiedKey>>,
+}
+
+impl SniCertResolver {
+    pub fn new(default: Arc<CertifiedKey>, sni: HashMap<String, Arc<CertifiedKey>>) -> Self {
+        SniCertResolver { default, sni }
+    }
+
+    pub fn resolve_by_name(&self, server_name: Option<&str>) -> Arc<CertifiedKey> {
+        if let Some(name) = server_name {
+            let name = name.to_lowercase();
+            if let Some(key) = self.sni.get(&name) {
+                return key.clone();
+            }
+
+            if let Some((_, parent)) = name.split_once('.') {
+                if let Some(key) = self.sni.get(&format!("*.{}", parent)) {
+                    return key.clone();
+                }
+            }
+        }
+        self.default.clone()
+    }
+}
+
+impl ResolvesServerCert for SniCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.resolve_by_name(client_hello.server_name()))
+    }
+}
+
+pub fn load_certified_key(
+    cert_path: &str,
+    key_path: &str,
+) -> Result<Arc<CertifiedKey>, MqttBrokerError> {
+    let certs = load_certs(Path::new(cert_path))?;
+    let key = load_key(Path::new(key_path))?;
+    let signing_key = match any_supported_type(&key) {
+        Ok(data) => data,
+        Err(e) => return Err(MqttBrokerError::CommonError(e.to_string())),
+    };
+    let certified_key = CertifiedKey::new(certs, signing_key);
+    // CertifiedKey::new does not check the key against the certificate, a key that does not
+    // match would only fail the handshakes after the certificates were swapped.
+    if let Err(e) = certified_key.keys_match() {
+        return Err(MqttBrokerError::CommonError(format!(
+            "The private key {} does not match the certificate {}: {}",
+            key_path, cert_path, e
+        )));
+    }
+    Ok(Arc::new(certified_key))
+}
+
+pub fn build_tls_server_config(listener: &Listener) -> Result<ServerConfig, MqttBrokerError> {
+    let default = load_certified_key(&listener.tls_cert, &listener.tls_key)?;
+    let mut sni = HashMap::new();
+    for sni_cert in listener.sni_certs.iter() {
+        sni.insert(
+            sni_cert.server_name.to_lowercase(),
+            load_certified_key(&sni_cert.tls_cert, &sni_cert.tls_key)?,
+        );
+    }
+    let resolver = Arc::new(SniCertResolver::new(default, sni));
+
+    let builder = if listener.ca_cert.is_empty() {
+        ServerConfig::builder().with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(Path::new(&listener.ca_cert))? {
+            if let Err(e) = roots.add(cert) {
+                return Err(MqttBrokerError::CommonError(e.to_string()));
+            }
+        }
+        let verifier = match WebPkiClientVerifier::builder(Arc::new(roots)).build() {
+            Ok(data) => data,
+            Err(e) => return Err(MqttBrokerError::CommonError(e.to_string())),
+        };
+        ServerConfig::builder().with_client_cert_verifier(verifier)
+    };
+
+    Ok(builder.with_cert_resolver(resolver))
+}
+
+// Remembers the modification time of the certificate files, so that a rotation
+// (including a symlink swap done by cert-manager) can be detected by polling.
+pub struct CertFileWatcher {
+    files: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+}
+
+impl CertFileWatcher {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let modified = files.iter().map(|file| file_modified(file)).collect();
+        CertFileWatcher { files, modified }
+    }
+
+    pub fn changed(&self) -> bool {
+        self.files
+            .iter()
+            .zip(self.modified.iter())
+            .any(|(file, modified)| file_modified(file) != *modified)
+    }
+
+    pub fn refresh(&mut self) {
+        self.modified = self.files.iter().map(|file| file_modified(file)).collect();
+    }
+}
+
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+pub fn listener_cert_files(listener: &Listener) -> Vec<PathBuf> {
+    let mut files = vec![
+        PathBuf::from(&listener.tls_cert),
+        PathBuf::from(&listener.tls_key),
+    ];
+    if !listener.ca_cert.is_empty() {
+        files.push(PathBuf::from(&listener.ca_cert));
+    }
+    for sni_cert in listener.sni_certs.iter() {
+        files.push(PathBuf::from(&sni_cert.tls_cert));
+        files.push(PathBuf::from(&sni_cert.tls_key));
+    }
+    files
+}
+
+// The certificates of a listener that can be reloaded without restarting the listener.
+pub trait ReloadableTlsCert: Send + Sync {
+    fn listener_name(&self) -> &str;
+
+    fn watcher(&self) -> &Mutex<CertFileWatcher>;
+
+    fn reload(&self) -> Result<(), MqttBrokerError>;
+
+    fn reload_if_changed(&self) -> Result<bool, MqttBrokerError> {
+        if !self.watcher().lock().unwrap().changed() {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+}
+
+// The TLS acceptor of a listener. Reloading swaps the acceptor used by new handshakes,
+// the connections that are already established keep their session.
+pub struct ReloadableTlsAcceptor {
+    listener: Listener,
+    acceptor: RwLock<TlsAcceptor>,
+    watcher: Mutex<CertFileWatcher>,
+}
+
+impl ReloadableTlsAcceptor {
+    pub fn new(listener: &Listener) -> Result<Self, MqttBrokerError> {
+        let config = build_tls_server_config(listener)?;
+        Ok(ReloadableTlsAcceptor {
+            listener: listener.clone(),
+            acceptor: RwLock::new(TlsAcceptor::from(Arc::new(config))),
+            watcher: Mutex::new(CertFileWatcher::new(listener_cert_files(listener))),
+        })
+    }
+
+    pub fn acceptor(&self) -> TlsAcceptor {
+        self.acceptor.read().unwrap().clone()
+    }
+}
+
+impl ReloadableTlsCert for ReloadableTlsAcceptor {
+    fn listener_name(&self) -> &str {
+        &self.listener.name
+    }
+
+    fn watcher(&self) -> &Mutex<CertFileWatcher> {
+        &self.watcher
+    }
+
+    fn reload(&self) -> Result<(), MqttBrokerError> {
+        let config = build_tls_server_config(&self.listener)?;
+        *self.acceptor.write().unwrap() = TlsAcceptor::from(Arc::new(config));
+        self.watcher.lock().unwrap().refresh();
+        info!(
+            "The TLS certificates of listener {} were reloaded",
+            self.listener.name
+        );
+        Ok(())
+    }
+}
+
+pub fn start_tls_cert_reload_thread(
+    tls_cert: Arc<dyn ReloadableTlsCert>,
+    interval_sec: u64,
+    stop_sx: broadcast::Sender<bool>,
+) {
+    if interval_sec == 0 {
+        return;
+    }
+
+    let mut stop_rx = stop_sx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            select! {
+                val = stop_rx.recv() => {
+                    if let Ok(flag) = val {
+                        if flag {
+                            debug!("TLS certificate reload thread of listener {} stopped successfully.", tls_cert.listener_name());
+                            break;
+                        }
+                    }
+                }
+                _ = sleep(Duration::from_secs(interval_sec)) => {
+                    // Keep the previous certificates if the new files can not be loaded,
+                    // such as when they are only partly written.
+                    if let Err(e) = tls_cert.reload_if_changed() {
+                        error!(
+                            "Failed to reload the TLS certificates of listener {}, error message: {}",
+                            tls_cert.listener_name(), e
+                        );
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+
+    use common_base::tools::unique_id;
+    use tokio_rustls::rustls::crypto::ring::sign::any_supported_type;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer};
+    use tokio_rustls::rustls::sign::CertifiedKey;
+
+    use super::{load_certified_key, CertFileWatcher, SniCertResolver};
+
+    fn self_signed(name: &str) -> Arc<CertifiedKey> {
+        let cert = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+        let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()).into();
+        Arc::new(CertifiedKey::new(
+            vec![CertificateDer::from(cert.cert)],
+            any_supported_type(&key).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn sni_resolve_test() {
+        let default = self_signed("localhost");
+        let exact = self_signed("mqtt.example.com");
+        let wildcard = self_signed("*.example.com");
+        let mut sni = HashMap::new();
+        sni.insert("mqtt.example.com".to_string(), exact.clone());
+        sni.insert("*.example.com".to_string(), wildcard.clone());
+        let resolver = SniCertResolver::new(default.clone(), sni);
+
+        assert!(Arc::ptr_eq(&resolver.resolve_by_name(None), &default));
+        assert!(Arc::ptr_eq(
+            &resolver.resolve_by_name(Some("MQTT.example.com")),
+            &exact
+        ));
+        assert!(Arc::ptr_eq(
+            &resolver.resolve_by_name(Some("iot.example.com")),
+            &wildcard
+        ));
+        assert!(Arc::ptr_eq(
+            &resolver.resolve_by_name(Some("a.b.example.com")),
+            &default
+        ));
+        assert!(Arc::ptr_eq(
+            &resolver.resolve_by_name(Some("robustmq.com")),
+            &default
+        ));
+    }
+
+    #[test]
+    fn load_certified_key_test() {
+        let dir = format!("/tmp/robustmq-cert-{}", unique_id());
+        fs::create_dir_all(&dir).unwrap();
+        let mut files = Vec::new();
+        for name in ["a", "b"] {
+            let cert = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+            let cert_path = format!("{}/{}.pem", dir, name);
+            let key_path = format!("{}/{}.key", dir, name);
+            fs::write(&cert_path, cert.cert.pem()).unwrap();
+            fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+            files.push((cert_path, key_path));
+        }
+
+        assert!(load_certified_key(&files[0].0, &files[0].1).is_ok());
+        assert!(load_certified_key(&files[0].0, &files[1].1).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cert_file_watcher_test() {
+        let path = PathBuf::from(format!("/tmp/robustmq-cert-{}.pem", unique_id()));
+        fs::write(&path, "cert").unwrap();
+
+        let mut watcher = CertFileWatcher::new(vec![path.clone()]);
+        assert!(!watcher.changed());
+
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert!(watcher.changed());
+
+        watcher.refresh();
+        assert!(!watcher.changed());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.changed());
+    }
+}
@@ -14,33 +14,35 @@
 
 use std::fs::File;
 use std::io::{self, BufReader, ErrorKind};
+use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
-use common_base::config::broker_mqtt::broker_mqtt_conf;
+use common_base::config::broker_mqtt::Listener;
 use futures_util::StreamExt;
 use log::{debug, error, info};
 use protocol::mqtt::codec::MqttCodec;
 use rustls_pemfile::{certs, private_key};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::select;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{broadcast, mpsc};
 use tokio::time::sleep;
 
 use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
-use tokio_rustls::rustls::ServerConfig;
-use tokio_rustls::TlsAcceptor;
 use tokio_util::codec::{FramedRead, FramedWrite};
 
 use crate::handler::validator::tcp_tls_establish_connection_check;
 use crate::observability::metrics::packets::{
     record_received_error_metrics, record_received_metrics,
 };
+use crate::security::login::x509::cert_identities;
 use crate::server::connection::{NetworkConnection, NetworkConnectionType};
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::packet::RequestPackage;
+use crate::server::tcp::proxy_protocol::read_proxy_header_timeout;
+use crate::server::tcp::tls_cert::ReloadableTlsAcceptor;
 
 pub(crate) fn load_certs(path: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
     certs(&mut BufReader::new(File::open(path)?)).collect()
@@ -55,47 +57,25 @@ pub(crate) fn load_key(path: &Path) -> io::Result<PrivateKeyDer<'static>> {
         ))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn acceptor_tls_process(
     accept_thread_num: usize,
     listener_arc: Arc<TcpListener>,
+    listener_conf: Arc<Listener>,
     stop_sx: broadcast::Sender<bool>,
     network_connection_type: NetworkConnectionType,
     connection_manager: Arc<ConnectionManager>,
     request_queue_sx: Sender<RequestPackage>,
+    tls_acceptor: Arc<ReloadableTlsAcceptor>,
 ) {
-    let conf = broker_mqtt_conf();
-    let certs = match load_certs(Path::new(&conf.network.tls_cert)) {
-        Ok(data) => data,
-        Err(e) => {
-            panic!("load certs: {}", e);
-        }
-    };
-
-    let key = match load_key(Path::new(&conf.network.tls_key)) {
-        Ok(data) => data,
-        Err(e) => {
-            panic!("load key: {}", e);
-        }
-    };
-
-    let config = match ServerConfig::builder()
-        .with_no_client_auth()
-        .with_single_cert(certs, key)
-    {
-        Ok(data) => data,
-        Err(e) => {
-            panic!("ssl build cert:{}", e);
-        }
-    };
-    let tls_acceptor = TlsAcceptor::from(Arc::new(config));
-
     for index in 1..=accept_thread_num {
         let listener = listener_arc.clone();
         let connection_manager = connection_manager.clone();
         let mut stop_rx = stop_sx.subscribe();
         let raw_request_queue_sx = request_queue_sx.clone();
-        let raw_tls_acceptor = tls_acceptor.clone();
+        let tls_acceptor = tls_acceptor.clone();
         let network_type = network_connection_type.clone();
+        let listener_conf = listener_conf.clone();
         tokio::spawn(async move {
             debug!("TCP Server acceptor thread {} start successfully.", index);
             loop {
@@ -112,32 +92,17 @@ pub(crate) async fn acceptor_tls_process(
                         match val{
                             Ok((stream, addr)) => {
                                 info!("accept tcp tls connection:{:?}",addr);
-                                let stream = match raw_tls_acceptor.accept(stream).await{
-                                    Ok(da) => da,
-                                    Err(e) => {
-                                        error!("Tls Accepter failed to read Stream with error message :{e:?}");
-                                        continue;
-                                    }
-                                };
-                                let (r_stream, w_stream) = tokio::io::split(stream);
-                                let codec = MqttCodec::new(None);
-                                let read_frame_stream = FramedRead::new(r_stream, codec.clone());
-                                let mut  write_frame_stream = FramedWrite::new(w_stream, codec.clone());
-
-                                if !tcp_tls_establish_connection_check(&addr,&connection_manager,&mut write_frame_stream).await{
-                                    continue;
-                                }
-
-                                let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
-                                let connection = NetworkConnection::new(
-                                    crate::server::connection::NetworkConnectionType::Tls,
+                                // The PROXY protocol header and the TLS handshake are read in their own task,
+                                // so a peer that is slow to send them does not hold up the acceptor.
+                                tokio::spawn(establish_tls_connection(
+                                    stream,
                                     addr,
-                                    Some(connection_stop_sx.clone())
-                                );
-                                connection_manager.add_connection(connection.clone());
-                                connection_manager.add_tcp_tls_write(connection.connection_id, write_frame_stream);
-
-                                read_tls_frame_process(read_frame_stream,connection,raw_request_queue_sx.clone(),connection_stop_rx, network_type.clone());
+                                    listener_conf.clone(),
+                                    connection_manager.clone(),
+                                    raw_request_queue_sx.clone(),
+                                    network_type.clone(),
+                                    tls_acceptor.clone(),
+                                ));
                             }
                             Err(e) => {
                                 error!("TCP accept failed to create connection with error message :{:?}",e);
@@ -150,6 +115,79 @@ pub(crate) async fn acceptor_tls_process(
     }
 }
 
+async fn establish_tls_connection(
+    mut stream: TcpStream,
+    mut addr: SocketAddr,
+    listener_conf: Arc<Listener>,
+    connection_manager: Arc<ConnectionManager>,
+    request_queue_sx: Sender<RequestPackage>,
+    network_type: NetworkConnectionType,
+    tls_acceptor: Arc<ReloadableTlsAcceptor>,
+) {
+    if listener_conf.proxy_protocol {
+        match read_proxy_header_timeout(&mut stream).await {
+            Ok(Some(source)) => addr = source,
+            Ok(None) => {}
+            Err(e) => {
+                error!(
+                    "Listener {} failed to read PROXY protocol header from {addr}, error message: {e}",
+                    listener_conf.name
+                );
+                return;
+            }
+        }
+    }
+
+    let stream = match tls_acceptor.acceptor().accept(stream).await {
+        Ok(da) => da,
+        Err(e) => {
+            error!("Tls Accepter failed to read Stream with error message :{e:?}");
+            return;
+        }
+    };
+    let cert_identities = stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .map(|cert| cert_identities(cert.as_ref()))
+        .unwrap_or_default();
+    let (r_stream, w_stream) = tokio::io::split(stream);
+    let codec = MqttCodec::new(None);
+    let read_frame_stream = FramedRead::new(r_stream, codec.clone());
+    let mut write_frame_stream = FramedWrite::new(w_stream, codec.clone());
+
+    if !tcp_tls_establish_connection_check(
+        &addr,
+        &listener_conf,
+        &connection_manager,
+        &mut write_frame_stream,
+    )
+    .await
+    {
+        return;
+    }
+
+    let (connection_stop_sx, connection_stop_rx) = mpsc::channel::<bool>(1);
+    let mut connection = NetworkConnection::new(
+        NetworkConnectionType::Tls,
+        addr,
+        Some(connection_stop_sx.clone()),
+    );
+    connection.set_listener(listener_conf.name.clone());
+    connection.set_cert_identities(cert_identities);
+    connection_manager.add_connection(connection.clone());
+    connection_manager.add_tcp_tls_write(connection.connection_id, write_frame_stream);
+
+    read_tls_frame_process(
+        read_frame_stream,
+        connection,
+        request_queue_sx,
+        connection_stop_rx,
+        network_type,
+    );
+}
+
 pub(crate) fn read_tls_frame_process(
     mut read_frame_stream: FramedRead<
         tokio::io::ReadHalf<tokio_rustls::server::TlsStream<tokio::net::TcpStream>>,
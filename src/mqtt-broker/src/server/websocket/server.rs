@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
 use std::net::SocketAddr;
-use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
@@ -23,126 +24,184 @@
 use axum::Router;
 use axum_extra::headers::UserAgent;
 use axum_extra::TypedHeader;
-use axum_server::tls_rustls::RustlsConfig;
+use axum_server::accept::Accept;
+use axum_server::Handle;
 use bytes::{BufMut, BytesMut};
-use common_base::config::broker_mqtt::broker_mqtt_conf;
-use delay_message::DelayMessageManager;
+use common_base::config::broker_mqtt::Listener;
+use futures_util::future::BoxFuture;
 use futures_util::stream::StreamExt;
-use grpc_clients::pool::ClientPool;
 use log::{error, info};
 use protocol::mqtt::codec::{MqttCodec, MqttPacketWrapper};
 use protocol::mqtt::common::{MqttPacket, MqttProtocol};
-use schema_register::schema::SchemaRegisterManager;
 use storage_adapter::storage::StorageAdapter;
+use tokio::net::TcpStream;
 use tokio::select;
 use tokio::sync::broadcast::{self};
+use tokio::time::timeout;
+use tokio_rustls::server::TlsStream;
 
-use crate::handler::cache::CacheManager;
 use crate::handler::command::Command;
-use crate::security::AuthDriver;
-use crate::server::connection::NetworkConnection;
+use crate::handler::error::MqttBrokerError;
+use crate::server::connection::{NetworkConnection, NetworkConnectionType};
 use crate::server::connection_manager::ConnectionManager;
-use crate::subscribe::subscribe_manager::SubscribeManager;
+use crate::server::listener::LISTENER_PROTOCOL_WSS;
+use crate::server::tcp::tls_cert::ReloadableTlsAcceptor;
 
 pub const ROUTE_ROOT: &str = "/mqtt";
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Clone)]
 pub struct WebSocketServerState<S> {
-    sucscribe_manager: Arc<SubscribeManager>,
-    cache_manager: Arc<CacheManager>,
-    message_storage_adapter: Arc<S>,
-    delay_message_manager: Arc<DelayMessageManager<S>>,
-    client_pool: Arc<ClientPool>,
-    stop_sx: broadcast::Sender<bool>,
+    command: Command<S>,
     connection_manager: Arc<ConnectionManager>,
-    schema_manager: Arc<SchemaRegisterManager>,
-    auth_driver: Arc<AuthDriver>,
+    listener: Arc<Listener>,
+    stop_sx: broadcast::Sender<bool>,
 }
 
 impl<S> WebSocketServerState<S>
 where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
-    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        sucscribe_manager: Arc<SubscribeManager>,
-        cache_manager: Arc<CacheManager>,
+        command: Command<S>,
         connection_manager: Arc<ConnectionManager>,
-        message_storage_adapter: Arc<S>,
-        delay_message_manager: Arc<DelayMessageManager<S>>,
-        schema_manager: Arc<SchemaRegisterManager>,
-        client_pool: Arc<ClientPool>,
-        auth_driver: Arc<AuthDriver>,
+        listener: &Listener,
         stop_sx: broadcast::Sender<bool>,
     ) -> Self {
         Self {
-            sucscribe_manager,
-            cache_manager,
+            command,
             connection_manager,
-            message_storage_adapter,
-            delay_message_manager,
-            schema_manager,
-            client_pool,
-            auth_driver,
+            listener: Arc::new(listener.clone()),
             stop_sx,
         }
     }
 }
 
-pub async fn websocket_server<S>(state: WebSocketServerState<S>)
+// Serves MQTT over WebSocket on the bind address of a ws listener.
+pub async fn websocket_server<S>(
+    state: WebSocketServerState<S>,
+    accept_stop_sx: broadcast::Sender<bool>,
+) -> Result<(), MqttBrokerError>
 where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
-    let config = broker_mqtt_conf();
-    let ip: SocketAddr = format!("0.0.0.0:{}", config.network.websocket_port)
-        .parse()
-        .unwrap();
+    let listener = state.listener.clone();
+    let tcp_listener = bind_listener(&listener)?;
+    let handle = Handle::new();
+    start_accept_stop_thread(handle.clone(), accept_stop_sx);
+
     let app = routes_v1(state);
+    let server = axum_server::from_tcp(tcp_listener).handle(handle);
+    let name = listener.name.clone();
+    tokio::spawn(async move {
+        if let Err(e) = server
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+        {
+            error!(
+                "WebSocket listener {} stopped unexpectedly, error message: {}",
+                name, e
+            );
+        }
+    });
+
     info!(
-        "Broker WebSocket Server start success. port:{}",
-        config.network.websocket_port
+        "MQTT WebSocket Server started successfully, listener: {}, bind: {}",
+        listener.name, listener.bind
     );
-    match axum_server::bind(ip)
-        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-        .await
-    {
-        Ok(()) => {}
-        Err(e) => panic!("{}", e.to_string()),
-    }
+    Ok(())
 }
 
-pub async fn websockets_server<S>(state: WebSocketServerState<S>)
+// Serves MQTT over WebSocket with TLS on the bind address of a wss listener. The handshakes use
+// the TLS acceptor of the listener, so the certificates are picked by SNI and reloaded like the
+// ones of a tls listener.
+pub async fn websockets_server<S>(
+    state: WebSocketServerState<S>,
+    tls_acceptor: Arc<ReloadableTlsAcceptor>,
+    accept_stop_sx: broadcast::Sender<bool>,
+) -> Result<(), MqttBrokerError>
 where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
-    let config = broker_mqtt_conf();
-    let ip: SocketAddr = format!("0.0.0.0:{}", config.network.websockets_port)
-        .parse()
-        .unwrap();
-    let app = routes_v1(state);
+    let listener = state.listener.clone();
+    let tcp_listener = bind_listener(&listener)?;
 
-    let tls_config = match RustlsConfig::from_pem_file(
-        PathBuf::from(config.network.tls_cert.clone()),
-        PathBuf::from(config.network.tls_key.clone()),
-    )
-    .await
-    {
-        Ok(cf) => cf,
-        Err(e) => {
-            panic!("{}", e.to_string());
+    let handle = Handle::new();
+    start_accept_stop_thread(handle.clone(), accept_stop_sx);
+
+    let app = routes_v1(state);
+    let server = axum_server::from_tcp(tcp_listener)
+        .acceptor(WebSocketTlsAcceptor { tls_acceptor })
+        .handle(handle);
+    let name = listener.name.clone();
+    tokio::spawn(async move {
+        if let Err(e) = server
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+        {
+            error!(
+                "WebSocket TLS listener {} stopped unexpectedly, error message: {}",
+                name, e
+            );
         }
-    };
+    });
 
     info!(
-        "Broker WebSocket TLS Server start success. port:{}",
-        config.network.websockets_port
+        "MQTT WebSocket TLS Server started successfully, listener: {}, bind: {}",
+        listener.name, listener.bind
     );
-    match axum_server::bind_rustls(ip, tls_config)
-        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-        .await
-    {
-        Ok(()) => {}
-        Err(e) => panic!("{}", e.to_string()),
+    Ok(())
+}
+
+fn bind_listener(listener: &Listener) -> Result<std::net::TcpListener, MqttBrokerError> {
+    let tcp_listener = std::net::TcpListener::bind(&listener.bind)?;
+    tcp_listener.set_nonblocking(true)?;
+    Ok(tcp_listener)
+}
+
+// Closes the socket of the listener when its acceptors are stopped, the WebSocket connections
+// that are already established are kept until the listener is stopped.
+fn start_accept_stop_thread(handle: Handle, accept_stop_sx: broadcast::Sender<bool>) {
+    let mut stop_rx = accept_stop_sx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match stop_rx.recv().await {
+                Ok(true) => {
+                    handle.graceful_shutdown(None);
+                    break;
+                }
+                Ok(false) => {}
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+#[derive(Clone)]
+struct WebSocketTlsAcceptor {
+    tls_acceptor: Arc<ReloadableTlsAcceptor>,
+}
+
+impl<S> Accept<TcpStream, S> for WebSocketTlsAcceptor
+where
+    S: Send + 'static,
+{
+    type Stream = TlsStream<TcpStream>;
+    type Service = S;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        // Takes the acceptor of the current certificates, a reload only affects new handshakes
+        let acceptor = self.tls_acceptor.acceptor();
+        Box::pin(async move {
+            match timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(stream) => Ok((stream?, service)),
+                Err(_) => Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "TLS handshake timed out",
+                )),
+            }
+        })
     }
 }
 
@@ -171,16 +230,7 @@ async fn ws_handler<S>(
         String::from("Unknown Source")
     };
     info!("websocket `{user_agent}` at {addr} connected.");
-    let command = Command::new(
-        state.cache_manager.clone(),
-        state.message_storage_adapter.clone(),
-        state.delay_message_manager.clone(),
-        state.sucscribe_manager.clone(),
-        state.client_pool.clone(),
-        state.connection_manager.clone(),
-        state.schema_manager.clone(),
-        state.auth_driver.clone(),
-    );
+    let command = state.command.clone();
     let codec = MqttCodec::new(None);
     ws.protocols(["mqtt", "mqttv3.1"])
         .on_upgrade(move |socket| {
@@ -190,6 +240,7 @@ async fn ws_handler<S>(
                 command,
                 codec,
                 state.connection_manager.clone(),
+                state.listener.clone(),
                 state.stop_sx.clone(),
             )
         })
@@ -201,16 +252,29 @@ async fn handle_socket<S>(
     mut command: Command<S>,
     mut codec: MqttCodec,
     connection_manager: Arc<ConnectionManager>,
+    listener: Arc<Listener>,
     stop_sx: broadcast::Sender<bool>,
 ) where
     S: StorageAdapter + Sync + Send + 'static + Clone,
 {
+    if connection_manager.tcp_connect_num_check()
+        || connection_manager.listener_connect_num_check(&listener)
+    {
+        info!(
+            "websocket connection from {} is refused, listener {} has too many connections",
+            addr, listener.name
+        );
+        return;
+    }
+
     let (sender, mut receiver) = socket.split();
-    let mut tcp_connection = NetworkConnection::new(
-        crate::server::connection::NetworkConnectionType::WebSocket,
-        addr,
-        None,
-    );
+    let connection_type = if listener.protocol == LISTENER_PROTOCOL_WSS {
+        NetworkConnectionType::WebSockets
+    } else {
+        NetworkConnectionType::WebSocket
+    };
+    let mut tcp_connection = NetworkConnection::new(connection_type, addr, None);
+    tcp_connection.set_listener(listener.name.clone());
 
     connection_manager.add_websocket_write(tcp_connection.connection_id, sender);
     connection_manager.add_connection(tcp_connection.clone());
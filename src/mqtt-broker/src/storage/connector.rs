@@ -12,17 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use grpc_clients::{
-    placement::mqtt::call::{
-        placement_connector_heartbeat, placement_create_connector, placement_delete_connector,
-        placement_list_connector, placement_update_connector,
+    placement::{
+        kv::call::{placement_delete, placement_get, placement_set},
+        mqtt::call::{
+            placement_connector_heartbeat, placement_create_connector, placement_delete_connector,
+            placement_list_connector, placement_update_connector,
+        },
     },
     pool::ClientPool,
 };
-use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+use metadata_struct::mqtt::bridge::{connector::MQTTConnector, status::ConnectorRuntimeStatus};
+use protocol::placement_center::placement_center_kv::{DeleteRequest, GetRequest, SetRequest};
 use protocol::placement_center::placement_center_mqtt::{
     ConnectorHeartbeatRaw, ConnectorHeartbeatRequest, CreateConnectorRequest,
     DeleteConnectorRequest, ListConnectorRequest, UpdateConnectorRequest,
@@ -109,4 +113,107 @@ pub async fn connector_heartbeat(
         placement_connector_heartbeat(&self.client_pool, &config.placement_center, request).await?;
         Ok(())
     }
+
+    // The position of a source connector in the external system, e.g. the next offset of
+    // each Kafka partition. It is kept in the placement center so that the connector resumes
+    // from it when it is started on another broker.
+    pub async fn get_source_offset(
+        &self,
+        connector_name: &str,
+    ) -> Result<HashMap<String, u64>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = GetRequest {
+            key: source_offset_key(&config.cluster_name, connector_name),
+        };
+        let reply = placement_get(&self.client_pool, &config.placement_center, request).await?;
+        if reply.value.is_empty() {
+            return Ok(HashMap::new());
+        }
+        Ok(serde_json::from_str(&reply.value)?)
+    }
+
+    pub async fn set_source_offset(
+        &self,
+        connector_name: &str,
+        offset: &HashMap<String, u64>,
+    ) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = SetRequest {
+            key: source_offset_key(&config.cluster_name, connector_name),
+            value: serde_json::to_string(offset)?,
+        };
+        placement_set(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_source_offset(
+        &self,
+        cluster_name: &str,
+        connector_name: &str,
+    ) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = DeleteRequest {
+            key: source_offset_key(cluster_name, connector_name),
+        };
+        placement_delete(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    // The runtime status reported by the broker running the connector.
+    pub async fn get_runtime_status(
+        &self,
+        cluster_name: &str,
+        connector_name: &str,
+    ) -> Result<Option<ConnectorRuntimeStatus>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = GetRequest {
+            key: runtime_status_key(cluster_name, connector_name),
+        };
+        let reply = placement_get(&self.client_pool, &config.placement_center, request).await?;
+        if reply.value.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&reply.value)?))
+    }
+
+    pub async fn set_runtime_status(
+        &self,
+        connector_name: &str,
+        status: &ConnectorRuntimeStatus,
+    ) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = SetRequest {
+            key: runtime_status_key(&config.cluster_name, connector_name),
+            value: serde_json::to_string(status)?,
+        };
+        placement_set(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_runtime_status(
+        &self,
+        cluster_name: &str,
+        connector_name: &str,
+    ) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = DeleteRequest {
+            key: runtime_status_key(cluster_name, connector_name),
+        };
+        placement_delete(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+}
+
+fn runtime_status_key(cluster_name: &str, connector_name: &str) -> String {
+    format!(
+        "/mqtt/connector-runtime-status/{}/{}",
+        cluster_name, connector_name
+    )
+}
+
+fn source_offset_key(cluster_name: &str, connector_name: &str) -> String {
+    format!(
+        "/mqtt/connector-source-offset/{}/{}",
+        cluster_name, connector_name
+    )
 }
@@ -88,6 +88,38 @@ pub async fn get_group_offset(&self, group_id: &str) -> Result<u64, CommonError>
         Ok(0)
     }
 
+    // The offset of the first record of the topic written at or after the timestamp in
+    // seconds, None if there is no such record.
+    pub async fn get_offset_by_timestamp(
+        &self,
+        topic_id: &str,
+        timestamp: u64,
+    ) -> Result<Option<u64>, CommonError> {
+        let shard_name = topic_id;
+        let namespace = cluster_name();
+        let offset = self
+            .storage_adapter
+            .get_offset_by_timestamp(namespace, shard_name.to_owned(), timestamp)
+            .await?;
+        Ok(offset.map(|offset| offset.offset))
+    }
+
+    // The offset after the last record of the topic, the records are read from the offset
+    // up to the end of the topic.
+    pub async fn get_end_offset(&self, topic_id: &str, offset: u64) -> Result<u64, CommonError> {
+        let mut end_offset = offset;
+        loop {
+            let records = self.read_topic_message(topic_id, end_offset, 1000).await?;
+            let Some(last) = records.last() else {
+                return Ok(end_offset);
+            };
+            end_offset = last
+                .offset
+                .map(|last| last + 1)
+                .unwrap_or(end_offset + records.len() as u64);
+        }
+    }
+
     pub async fn commit_group_offset(
         &self,
         group_id: &str,
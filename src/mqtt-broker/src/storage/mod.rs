@@ -18,6 +18,8 @@
 pub mod cluster;
 pub mod connector;
 pub mod message;
+pub mod scheduled_message;
 pub mod session;
+pub mod tenant;
 pub mod topic;
 pub mod user;
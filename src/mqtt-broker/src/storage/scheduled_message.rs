@@ -0,0 +1,84 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::config::broker_mqtt::broker_mqtt_conf;
+use grpc_clients::placement::mqtt::call::{
+    placement_create_scheduled_message, placement_delete_scheduled_message,
+    placement_list_scheduled_message,
+};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+use protocol::placement_center::placement_center_mqtt::{
+    CreateScheduledMessageRequest, DeleteScheduledMessageRequest, ListScheduledMessageRequest,
+};
+
+use crate::handler::error::MqttBrokerError;
+
+pub struct ScheduledMessageStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl ScheduledMessageStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        ScheduledMessageStorage { client_pool }
+    }
+
+    pub async fn save_scheduled_message(
+        &self,
+        message: MqttScheduledMessage,
+    ) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = CreateScheduledMessageRequest {
+            cluster_name: config.cluster_name.clone(),
+            name: message.name.clone(),
+            content: message.encode(),
+        };
+        placement_create_scheduled_message(&self.client_pool, &config.placement_center, request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_scheduled_message(&self, name: String) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = DeleteScheduledMessageRequest {
+            cluster_name: config.cluster_name.clone(),
+            name,
+        };
+        placement_delete_scheduled_message(&self.client_pool, &config.placement_center, request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list_scheduled_message(
+        &self,
+        name: String,
+    ) -> Result<Vec<MqttScheduledMessage>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = ListScheduledMessageRequest {
+            cluster_name: config.cluster_name.clone(),
+            name,
+        };
+        let reply =
+            placement_list_scheduled_message(&self.client_pool, &config.placement_center, request)
+                .await?;
+
+        let mut results = Vec::new();
+        for raw in reply.messages {
+            results.push(serde_json::from_slice::<MqttScheduledMessage>(&raw)?);
+        }
+        Ok(results)
+    }
+}
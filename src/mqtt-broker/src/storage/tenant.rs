@@ -0,0 +1,110 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::config::broker_mqtt::broker_mqtt_conf;
+use grpc_clients::placement::kv::call::{placement_get_prefix, placement_set};
+use grpc_clients::placement::mqtt::call::{
+    placement_create_tenant, placement_delete_tenant, placement_list_tenant,
+};
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::tenant::{MqttTenant, MqttTenantUsage};
+use protocol::placement_center::placement_center_kv::{GetPrefixRequest, SetRequest};
+use protocol::placement_center::placement_center_mqtt::{
+    CreateTenantRequest, DeleteTenantRequest, ListTenantRequest,
+};
+
+use crate::handler::error::MqttBrokerError;
+
+pub struct TenantStorage {
+    client_pool: Arc<ClientPool>,
+}
+
+impl TenantStorage {
+    pub fn new(client_pool: Arc<ClientPool>) -> Self {
+        TenantStorage { client_pool }
+    }
+
+    pub async fn save_tenant(&self, tenant: MqttTenant) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = CreateTenantRequest {
+            cluster_name: config.cluster_name.clone(),
+            tenant_name: tenant.tenant_name.clone(),
+            content: tenant.encode(),
+        };
+        placement_create_tenant(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_tenant(&self, tenant_name: String) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = DeleteTenantRequest {
+            cluster_name: config.cluster_name.clone(),
+            tenant_name,
+        };
+        placement_delete_tenant(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn list_tenant(&self) -> Result<Vec<MqttTenant>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = ListTenantRequest {
+            cluster_name: config.cluster_name.clone(),
+            ..Default::default()
+        };
+        let reply =
+            placement_list_tenant(&self.client_pool, &config.placement_center, request).await?;
+
+        let mut results = Vec::new();
+        for raw in reply.tenants {
+            results.push(serde_json::from_slice::<MqttTenant>(&raw)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn report_usage(&self, usage: &MqttTenantUsage) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = SetRequest {
+            key: tenant_usage_key(&config.cluster_name, usage.broker_id),
+            value: serde_json::to_string(usage)?,
+        };
+        placement_set(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    // The tenant usage reported by every broker node of the cluster
+    pub async fn list_usage(&self) -> Result<Vec<MqttTenantUsage>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = GetPrefixRequest {
+            prefix: tenant_usage_key_prefix(&config.cluster_name),
+        };
+        let reply =
+            placement_get_prefix(&self.client_pool, &config.placement_center, request).await?;
+
+        let mut results = Vec::new();
+        for raw in reply.values {
+            results.push(serde_json::from_str::<MqttTenantUsage>(&raw)?);
+        }
+        Ok(results)
+    }
+}
+
+fn tenant_usage_key(cluster_name: &str, broker_id: u64) -> String {
+    format!("/mqtt/tenant-usage/{}/{}", cluster_name, broker_id)
+}
+
+fn tenant_usage_key_prefix(cluster_name: &str) -> String {
+    format!("/mqtt/tenant-usage/{}/", cluster_name)
+}
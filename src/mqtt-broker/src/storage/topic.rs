@@ -17,17 +17,22 @@
 use common_base::config::broker_mqtt::broker_mqtt_conf;
 use dashmap::DashMap;
 use grpc_clients::placement::mqtt::call::{
-    placement_create_topic, placement_create_topic_rewrite_rule, placement_delete_topic,
-    placement_delete_topic_rewrite_rule, placement_list_topic, placement_list_topic_rewrite_rule,
+    placement_create_rule, placement_create_topic, placement_create_topic_rewrite_rule,
+    placement_create_wasm_plugin, placement_delete_rule, placement_delete_topic,
+    placement_delete_topic_rewrite_rule, placement_delete_wasm_plugin, placement_list_rule,
+    placement_list_topic, placement_list_topic_rewrite_rule, placement_list_wasm_plugin,
     placement_set_topic_retain_message,
 };
 use grpc_clients::pool::ClientPool;
 use metadata_struct::mqtt::message::MqttMessage;
+use metadata_struct::mqtt::rule::MqttRule;
 use metadata_struct::mqtt::topic::MqttTopic;
 use metadata_struct::mqtt::topic_rewrite_rule::MqttTopicRewriteRule;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 use protocol::placement_center::placement_center_mqtt::{
-    CreateTopicRequest, CreateTopicRewriteRuleRequest, DeleteTopicRequest,
-    DeleteTopicRewriteRuleRequest, ListTopicRequest, ListTopicRewriteRuleRequest,
+    CreateRuleRequest, CreateTopicRequest, CreateTopicRewriteRuleRequest, CreateWasmPluginRequest,
+    DeleteRuleRequest, DeleteTopicRequest, DeleteTopicRewriteRuleRequest, DeleteWasmPluginRequest,
+    ListRuleRequest, ListTopicRequest, ListTopicRewriteRuleRequest, ListWasmPluginRequest,
     SetTopicRetainMessageRequest,
 };
 
@@ -196,4 +201,75 @@ pub async fn delete_topic_rewrite_rule(
             .await?;
         Ok(())
     }
+
+    pub async fn list_rule(&self) -> Result<Vec<MqttRule>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = ListRuleRequest {
+            cluster_name: config.cluster_name.clone(),
+        };
+        let reply =
+            placement_list_rule(&self.client_pool, &config.placement_center, request).await?;
+        let mut results = Vec::with_capacity(8);
+        for raw in reply.rules {
+            results.push(serde_json::from_slice::<MqttRule>(&raw)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn create_rule(&self, rule: MqttRule) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = CreateRuleRequest {
+            cluster_name: config.cluster_name.clone(),
+            rule_name: rule.rule_name.clone(),
+            content: rule.encode(),
+        };
+        placement_create_rule(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_rule(&self, rule_name: String) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = DeleteRuleRequest {
+            cluster_name: config.cluster_name.clone(),
+            rule_name,
+        };
+        placement_delete_rule(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn list_wasm_plugin(&self) -> Result<Vec<MqttWasmPlugin>, MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = ListWasmPluginRequest {
+            cluster_name: config.cluster_name.clone(),
+        };
+        let reply =
+            placement_list_wasm_plugin(&self.client_pool, &config.placement_center, request)
+                .await?;
+        let mut results = Vec::with_capacity(8);
+        for raw in reply.plugins {
+            results.push(serde_json::from_slice::<MqttWasmPlugin>(&raw)?);
+        }
+        Ok(results)
+    }
+
+    pub async fn create_wasm_plugin(&self, plugin: MqttWasmPlugin) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = CreateWasmPluginRequest {
+            cluster_name: config.cluster_name.clone(),
+            plugin_name: plugin.plugin_name.clone(),
+            content: plugin.encode(),
+        };
+        placement_create_wasm_plugin(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
+
+    pub async fn delete_wasm_plugin(&self, plugin_name: String) -> Result<(), MqttBrokerError> {
+        let config = broker_mqtt_conf();
+        let request = DeleteWasmPluginRequest {
+            cluster_name: config.cluster_name.clone(),
+            plugin_name,
+        };
+        placement_delete_wasm_plugin(&self.client_pool, &config.placement_center, request).await?;
+        Ok(())
+    }
 }
@@ -39,7 +39,7 @@ pub async fn save_user(&self, user_info: MqttUser) -> Result<(), MqttBrokerError
         let config = broker_mqtt_conf();
         let request = CreateUserRequest {
             cluster_name: config.cluster_name.clone(),
-            user_name: user_info.username.clone(),
+            user_name: user_info.login_name(),
             content: user_info.encode(),
         };
         placement_create_user(&self.client_pool, &config.placement_center, request).await?;
@@ -87,7 +87,7 @@ pub async fn user_list(&self) -> Result<DashMap<String, MqttUser>, MqttBrokerErr
         let results = DashMap::with_capacity(2);
         for raw in reply.users {
             let data = serde_json::from_slice::<MqttUser>(&raw)?;
-            results.insert(data.username.clone(), data);
+            results.insert(data.login_name(), data);
         }
         Ok(results)
     }
@@ -0,0 +1,361 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use common_base::config::broker_mqtt::{broker_mqtt_conf, DeadLetter};
+use grpc_clients::pool::ClientPool;
+use log::warn;
+use metadata_struct::adapter::record::Record;
+use metadata_struct::mqtt::message::MqttMessage;
+use protocol::mqtt::common::{Publish, PublishProperties};
+use storage_adapter::storage::StorageAdapter;
+
+use super::subscriber::SubPublishParam;
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::message::build_message_expire;
+use crate::handler::topic::try_init_topic;
+use crate::hook::hook_manager;
+use crate::storage::message::MessageStorage;
+
+pub const DEAD_LETTER_REASON: &str = "dlq-reason";
+pub const DEAD_LETTER_CLIENT_ID: &str = "dlq-client-id";
+pub const DEAD_LETTER_ATTEMPTS: &str = "dlq-attempts";
+pub const DEAD_LETTER_ORIGINAL_TOPIC: &str = "dlq-original-topic";
+
+#[derive(Clone, Debug, Default)]
+pub struct RetryPolicy {
+    pub enable: bool,
+    pub max_retry_times: u32,
+    pub retry_backoff_ms: u64,
+    pub max_retry_backoff_ms: u64,
+}
+
+impl RetryPolicy {
+    pub fn new(conf: &DeadLetter) -> Self {
+        RetryPolicy {
+            enable: conf.enable,
+            max_retry_times: conf.max_retry_times,
+            retry_backoff_ms: conf.retry_backoff_ms,
+            max_retry_backoff_ms: conf.max_retry_backoff_ms,
+        }
+    }
+
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        self.enable && attempts >= self.max_retry_times.max(1)
+    }
+
+    // Exponential backoff, retry_backoff_ms * 2^(attempts-1), limited by max_retry_backoff_ms.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let exp = attempts.saturating_sub(1).min(16);
+        let mut ms = self.retry_backoff_ms.saturating_mul(1 << exp);
+        if self.max_retry_backoff_ms > 0 {
+            ms = ms.min(self.max_retry_backoff_ms);
+        }
+        Duration::from_millis(ms)
+    }
+}
+
+pub fn dead_letter_topic_name(prefix: &str, topic_name: &str) -> String {
+    if topic_name.starts_with('/') {
+        format!("{}{}", prefix, topic_name)
+    } else {
+        format!("{}/{}", prefix, topic_name)
+    }
+}
+
+pub fn is_dead_letter_topic(prefix: &str, topic_name: &str) -> bool {
+    !prefix.is_empty() && topic_name.starts_with(&format!("{}/", prefix))
+}
+
+// Messages that could not be delivered to a subscriber after the retry policy is exhausted
+// are written to the dead letter topic, so that they can be inspected and re-driven later.
+#[derive(Clone)]
+pub struct DeadLetterQueue<S> {
+    cache_manager: Arc<CacheManager>,
+    client_pool: Arc<ClientPool>,
+    message_storage_adapter: Arc<S>,
+}
+
+impl<S> DeadLetterQueue<S>
+where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    pub fn new(
+        cache_manager: Arc<CacheManager>,
+        client_pool: Arc<ClientPool>,
+        message_storage_adapter: Arc<S>,
+    ) -> Self {
+        DeadLetterQueue {
+            cache_manager,
+            client_pool,
+            message_storage_adapter,
+        }
+    }
+
+    pub(crate) async fn send(
+        &self,
+        sub_pub_param: &SubPublishParam,
+        reason: &str,
+        attempts: u32,
+    ) -> Result<(), MqttBrokerError> {
+        let conf = broker_mqtt_conf();
+        let prefix = &conf.dead_letter.topic_prefix;
+        let original_topic = &sub_pub_param.subscribe.topic_name;
+
+        // Messages that cannot be delivered from the dead letter topic itself are discarded,
+        // otherwise they would be nested into the dead letter topic again and again.
+        if is_dead_letter_topic(prefix, original_topic) {
+            warn!(
+                "Message dropping: message of dead letter topic {} could not be pushed to client {}, and is discarded",
+                original_topic, sub_pub_param.subscribe.client_id
+            );
+            hook_manager()
+                .on_message_dropped(&sub_pub_param.subscribe.client_id, original_topic, reason)
+                .await;
+            return Ok(());
+        }
+
+        let topic_name = dead_letter_topic_name(prefix, original_topic);
+        let topic = try_init_topic(
+            &topic_name,
+            "",
+            &self.cache_manager,
+            &self.message_storage_adapter,
+            &self.client_pool,
+        )
+        .await?;
+
+        let message_expire = build_message_expire(&self.cache_manager, &None);
+        let record =
+            build_dead_letter_record(sub_pub_param, &topic_name, reason, attempts, message_expire)?;
+
+        let message_storage = MessageStorage::new(self.message_storage_adapter.clone());
+        message_storage
+            .append_topic_message(&topic.topic_id, vec![record])
+            .await?;
+
+        warn!(
+            "Message to client {} of topic {} was moved to dead letter topic {} after {} attempts, reason: {}",
+            sub_pub_param.subscribe.client_id, original_topic, topic_name, attempts, reason
+        );
+        hook_manager()
+            .on_message_dropped(&sub_pub_param.subscribe.client_id, original_topic, reason)
+            .await;
+        Ok(())
+    }
+}
+
+pub(crate) fn build_dead_letter_record(
+    sub_pub_param: &SubPublishParam,
+    topic_name: &str,
+    reason: &str,
+    attempts: u32,
+    message_expire: u64,
+) -> Result<Record, MqttBrokerError> {
+    let mut publish = sub_pub_param.publish.clone();
+    publish.topic = Bytes::from(topic_name.to_owned());
+    publish.dup = false;
+    publish.retain = false;
+
+    let mut properties = sub_pub_param.properties.clone().unwrap_or_default();
+    properties.topic_alias = None;
+    properties.message_expiry_interval = None;
+    properties.subscription_identifiers = Vec::new();
+    properties
+        .user_properties
+        .push((DEAD_LETTER_REASON.to_string(), reason.to_string()));
+    properties.user_properties.push((
+        DEAD_LETTER_CLIENT_ID.to_string(),
+        sub_pub_param.subscribe.client_id.clone(),
+    ));
+    properties
+        .user_properties
+        .push((DEAD_LETTER_ATTEMPTS.to_string(), attempts.to_string()));
+    properties.user_properties.push((
+        DEAD_LETTER_ORIGINAL_TOPIC.to_string(),
+        sub_pub_param.subscribe.topic_name.clone(),
+    ));
+
+    if let Some(record) = MqttMessage::build_record(
+        &sub_pub_param.subscribe.client_id,
+        &publish,
+        &Some(properties),
+        message_expire,
+    ) {
+        return Ok(record);
+    }
+    Err(MqttBrokerError::FailedToBuildMessage)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DeadLetterMessage {
+    pub offset: u64,
+    pub original_topic: String,
+    pub client_id: String,
+    pub reason: String,
+    pub attempts: u32,
+    pub message: MqttMessage,
+}
+
+impl DeadLetterMessage {
+    pub fn decode(record: Record) -> Result<Self, MqttBrokerError> {
+        let offset = record.offset.unwrap_or_default();
+        let message = MqttMessage::decode_record(record)?;
+        let mut dead_letter = DeadLetterMessage {
+            offset,
+            ..Default::default()
+        };
+
+        for (key, value) in message.user_properties.iter() {
+            match key.as_str() {
+                DEAD_LETTER_REASON => dead_letter.reason = value.clone(),
+                DEAD_LETTER_CLIENT_ID => dead_letter.client_id = value.clone(),
+                DEAD_LETTER_ATTEMPTS => dead_letter.attempts = value.parse()?,
+                DEAD_LETTER_ORIGINAL_TOPIC => dead_letter.original_topic = value.clone(),
+                _ => {}
+            }
+        }
+
+        if dead_letter.original_topic.is_empty() {
+            return Err(MqttBrokerError::CommonError(format!(
+                "Message at offset {} is not a dead letter message, original topic is missing",
+                offset
+            )));
+        }
+
+        dead_letter.message = message;
+        Ok(dead_letter)
+    }
+
+    // Restores the publish of the original topic, dropping the dead letter user properties.
+    pub fn build_redrive_publish(&self) -> (Publish, Option<PublishProperties>) {
+        let message = &self.message;
+        let publish = Publish {
+            dup: false,
+            qos: message.qos,
+            pkid: message.pkid,
+            retain: false,
+            topic: Bytes::from(self.original_topic.clone()),
+            payload: message.payload.clone(),
+        };
+        let mut user_properties = message.user_properties.clone();
+        user_properties.retain(|(key, _)| {
+            key != DEAD_LETTER_REASON
+                && key != DEAD_LETTER_CLIENT_ID
+                && key != DEAD_LETTER_ATTEMPTS
+                && key != DEAD_LETTER_ORIGINAL_TOPIC
+        });
+        let properties = PublishProperties {
+            payload_format_indicator: message.format_indicator,
+            response_topic: message.response_topic.clone(),
+            correlation_data: message.correlation_data.clone(),
+            user_properties,
+            content_type: message.content_type.clone(),
+            ..Default::default()
+        };
+        (publish, Some(properties))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use common_base::tools::now_second;
+    use protocol::mqtt::common::{Publish, PublishProperties, QoS};
+
+    use super::{
+        build_dead_letter_record, dead_letter_topic_name, is_dead_letter_topic, DeadLetterMessage,
+        RetryPolicy, DEAD_LETTER_REASON,
+    };
+    use crate::subscribe::subscriber::{SubPublishParam, Subscriber};
+
+    #[test]
+    fn retry_policy_test() {
+        let policy = RetryPolicy {
+            enable: true,
+            max_retry_times: 3,
+            retry_backoff_ms: 100,
+            max_retry_backoff_ms: 300,
+        };
+        assert!(!policy.is_exhausted(2));
+        assert!(policy.is_exhausted(3));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+
+        let policy = RetryPolicy::default();
+        assert!(!policy.is_exhausted(100));
+    }
+
+    #[test]
+    fn dead_letter_topic_name_test() {
+        assert_eq!(dead_letter_topic_name("$DLQ", "/a/b"), "$DLQ/a/b");
+        assert_eq!(dead_letter_topic_name("$DLQ", "a/b"), "$DLQ/a/b");
+        assert!(is_dead_letter_topic("$DLQ", "$DLQ/a/b"));
+        assert!(!is_dead_letter_topic("$DLQ", "/a/b"));
+        assert!(!is_dead_letter_topic("", "/a/b"));
+    }
+
+    #[test]
+    fn dead_letter_record_test() {
+        let sub_pub_param = SubPublishParam {
+            subscribe: Subscriber {
+                client_id: "client-1".to_string(),
+                topic_name: "/a/b".to_string(),
+                ..Default::default()
+            },
+            publish: Publish {
+                qos: QoS::AtLeastOnce,
+                topic: Bytes::from("/a/b"),
+                payload: Bytes::from("payload"),
+                ..Default::default()
+            },
+            properties: Some(PublishProperties {
+                user_properties: vec![("k".to_string(), "v".to_string())],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let expire = now_second() + 10;
+        let mut record =
+            build_dead_letter_record(&sub_pub_param, "$DLQ/a/b", "timeout", 3, expire).unwrap();
+        record.offset = Some(5);
+
+        let dead_letter = DeadLetterMessage::decode(record).unwrap();
+        assert_eq!(dead_letter.offset, 5);
+        assert_eq!(dead_letter.original_topic, "/a/b");
+        assert_eq!(dead_letter.client_id, "client-1");
+        assert_eq!(dead_letter.reason, "timeout");
+        assert_eq!(dead_letter.attempts, 3);
+        assert_eq!(dead_letter.message.topic, Bytes::from("$DLQ/a/b"));
+
+        let (publish, properties) = dead_letter.build_redrive_publish();
+        let properties = properties.unwrap();
+        assert_eq!(publish.topic, Bytes::from("/a/b"));
+        assert_eq!(publish.payload, Bytes::from("payload"));
+        assert_eq!(publish.qos, QoS::AtLeastOnce);
+        assert_eq!(properties.user_properties.len(), 1);
+        assert!(!properties
+            .user_properties
+            .iter()
+            .any(|(k, _)| k == DEAD_LETTER_REASON));
+    }
+}
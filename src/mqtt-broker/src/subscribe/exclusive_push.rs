@@ -17,6 +17,7 @@
 
 use bytes::Bytes;
 use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
 use log::{error, info, warn};
 use metadata_struct::adapter::record::Record;
 use metadata_struct::mqtt::message::MqttMessage;
@@ -26,6 +27,7 @@
 use tokio::sync::broadcast::{self};
 use tokio::time::sleep;
 
+use super::dead_letter::DeadLetterQueue;
 use super::sub_common::{
     get_pkid, loop_commit_offset, min_qos, publish_message_qos, qos2_send_pubrel, wait_pub_ack,
     wait_pub_comp, wait_pub_rec,
@@ -35,6 +37,7 @@
 use crate::handler::cache::{CacheManager, QosAckPackageData, QosAckPacketInfo};
 use crate::handler::error::MqttBrokerError;
 use crate::handler::message::is_message_expire;
+use crate::hook::hook_manager;
 use crate::server::connection_manager::ConnectionManager;
 use crate::storage::message::MessageStorage;
 use crate::subscribe::subscriber::SubPublishParam;
@@ -44,6 +47,7 @@ pub struct ExclusivePush<S> {
     subscribe_manager: Arc<SubscribeManager>,
     connection_manager: Arc<ConnectionManager>,
     message_storage: Arc<S>,
+    client_pool: Arc<ClientPool>,
 }
 
 impl<S> ExclusivePush<S>
@@ -55,12 +59,14 @@ pub fn new(
         cache_manager: Arc<CacheManager>,
         subscribe_manager: Arc<SubscribeManager>,
         connection_manager: Arc<ConnectionManager>,
+        client_pool: Arc<ClientPool>,
     ) -> Self {
         ExclusivePush {
             message_storage,
             cache_manager,
             subscribe_manager,
             connection_manager,
+            client_pool,
         }
     }
 
@@ -116,6 +122,11 @@ async fn start_push_thread(&self) {
             let cache_manager = self.cache_manager.clone();
             let connection_manager = self.connection_manager.clone();
             let subscribe_manager = self.subscribe_manager.clone();
+            let dead_letter_queue = DeadLetterQueue::new(
+                self.cache_manager.clone(),
+                self.client_pool.clone(),
+                self.message_storage.clone(),
+            );
 
             // Subscribe to the data push thread
             self.subscribe_manager
@@ -162,6 +173,7 @@ async fn start_push_thread(&self) {
                                 &connection_manager,
                                 &message_storage,
                                 &cache_manager,
+                                &dead_letter_queue,
                                 &subscriber,
                                 &group_id,
                                 &qos,
@@ -200,6 +212,7 @@ async fn pub_message<S>(
     connection_manager: &Arc<ConnectionManager>,
     message_storage: &MessageStorage<S>,
     cache_manager: &Arc<CacheManager>,
+    dead_letter_queue: &DeadLetterQueue<S>,
     subscriber: &Subscriber,
     group_id: &str,
     qos: &QoS,
@@ -256,7 +269,7 @@ async fn pub_message<S>(
                     },
                 );
 
-                exclusive_publish_message_qos1(
+                let res = exclusive_publish_message_qos1(
                     cache_manager,
                     connection_manager,
                     &sub_pub_param,
@@ -266,6 +279,13 @@ async fn pub_message<S>(
                 .await;
 
                 cache_manager.remove_ack_packet(&client_id, pkid);
+                try_send_dead_letter(
+                    dead_letter_queue,
+                    &sub_pub_param,
+                    res,
+                    "PubAck not received",
+                )
+                .await;
             }
 
             QoS::ExactlyOnce => {
@@ -279,7 +299,7 @@ async fn pub_message<S>(
                     },
                 );
 
-                exclusive_publish_message_qos2(
+                let res = exclusive_publish_message_qos2(
                     cache_manager,
                     connection_manager,
                     &sub_pub_param,
@@ -289,6 +309,13 @@ async fn pub_message<S>(
                 .await;
 
                 cache_manager.remove_ack_packet(&client_id, pkid);
+                try_send_dead_letter(
+                    dead_letter_queue,
+                    &sub_pub_param,
+                    res,
+                    "PubRec not received",
+                )
+                .await;
             }
         }
 
@@ -316,6 +343,9 @@ async fn build_pub_message(
 
     if is_message_expire(&msg) {
         warn!("Message dropping: message expires, is not pushed to the client, and is discarded");
+        hook_manager()
+            .on_message_dropped(&subscriber.client_id, &subscriber.topic_name, "expired")
+            .await;
         return Ok(None);
     }
 
@@ -367,6 +397,56 @@ async fn build_pub_message(
     Ok(Some(sub_pub_param))
 }
 
+// Sends the message to a single client and waits for the acks of its QoS. The pkid is
+// registered while waiting so that the acks of the client reach this task.
+pub async fn exclusive_publish_message(
+    metadata_cache: &Arc<CacheManager>,
+    connection_manager: &Arc<ConnectionManager>,
+    sub_pub_param: &SubPublishParam,
+    stop_sx: &broadcast::Sender<bool>,
+) -> Result<(), MqttBrokerError> {
+    let client_id = &sub_pub_param.subscribe.client_id;
+    let pkid = sub_pub_param.pkid;
+    let qos = sub_pub_param.publish.qos;
+    if qos == QoS::AtMostOnce {
+        publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx).await;
+        return Ok(());
+    }
+
+    let (wait_ack_sx, _) = broadcast::channel(1);
+    metadata_cache.add_ack_packet(
+        client_id,
+        pkid,
+        QosAckPacketInfo {
+            sx: wait_ack_sx.clone(),
+            create_time: now_second(),
+        },
+    );
+
+    let result = if qos == QoS::AtLeastOnce {
+        exclusive_publish_message_qos1(
+            metadata_cache,
+            connection_manager,
+            sub_pub_param,
+            stop_sx,
+            &wait_ack_sx,
+        )
+        .await
+    } else {
+        exclusive_publish_message_qos2(
+            metadata_cache,
+            connection_manager,
+            sub_pub_param,
+            stop_sx,
+            &wait_ack_sx,
+        )
+        .await
+    };
+
+    metadata_cache.remove_ack_packet(client_id, pkid);
+    result
+}
+
 // When the subscribed QOS is 1, we need to keep retrying to send the message to the client.
 // To avoid messages that are not successfully pushed to the client. When the client Session expires,
 // the push thread will exit automatically and will not attempt to push again.
@@ -376,7 +456,7 @@ pub async fn exclusive_publish_message_qos1(
     sub_pub_param: &SubPublishParam,
     stop_sx: &broadcast::Sender<bool>,
     wait_puback_sx: &broadcast::Sender<QosAckPackageData>,
-) {
+) -> Result<(), MqttBrokerError> {
     // 1. send Publish to Client
     publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx).await;
 
@@ -388,7 +468,7 @@ pub async fn exclusive_publish_message_qos1(
         stop_sx,
         wait_puback_sx,
     )
-    .await;
+    .await
 }
 
 // send publish message
@@ -401,7 +481,7 @@ pub async fn exclusive_publish_message_qos2(
     sub_pub_param: &SubPublishParam,
     stop_sx: &broadcast::Sender<bool>,
     wait_ack_sx: &broadcast::Sender<QosAckPackageData>,
-) {
+) -> Result<(), MqttBrokerError> {
     // 1. send Publish to Client
     publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx).await;
 
@@ -413,7 +493,7 @@ pub async fn exclusive_publish_message_qos2(
         stop_sx,
         wait_ack_sx,
     )
-    .await;
+    .await?;
 
     // 3. send PubRel to Client
     qos2_send_pubrel(metadata_cache, sub_pub_param, connection_manager, stop_sx).await;
@@ -427,6 +507,30 @@ pub async fn exclusive_publish_message_qos2(
         wait_ack_sx,
     )
     .await;
+    Ok(())
+}
+
+// When the retry policy is exhausted, the message is moved to the dead letter topic
+// and the offset is committed as usual, so the push thread is not blocked by this message.
+async fn try_send_dead_letter<S>(
+    dead_letter_queue: &DeadLetterQueue<S>,
+    sub_pub_param: &SubPublishParam,
+    res: Result<(), MqttBrokerError>,
+    reason: &str,
+) where
+    S: StorageAdapter + Sync + Send + 'static + Clone,
+{
+    if let Err(MqttBrokerError::SubPublishRetryExhausted(_, attempts)) = res {
+        if let Err(e) = dead_letter_queue
+            .send(sub_pub_param, reason, attempts)
+            .await
+        {
+            error!(
+                "Failed to move message to dead letter topic, client_id: {}, topic: {}, error: {}",
+                sub_pub_param.subscribe.client_id, sub_pub_param.subscribe.topic_name, e
+            );
+        }
+    }
 }
 
 fn build_group_name(subscriber: &Subscriber) -> String {
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod dead_letter;
 pub mod exclusive_push;
 pub mod share_follower_resub;
 pub mod share_leader_push;
@@ -16,7 +16,9 @@
 use std::time::Duration;
 
 use bytes::Bytes;
+use common_base::config::broker_mqtt::broker_mqtt_conf;
 use common_base::tools::now_second;
+use grpc_clients::pool::ClientPool;
 use log::{error, info};
 use metadata_struct::mqtt::message::MqttMessage;
 use protocol::mqtt::common::{MqttPacket, MqttProtocol, Publish, PublishProperties, QoS};
@@ -25,6 +27,7 @@
 use tokio::sync::broadcast::{self, Sender};
 use tokio::time::sleep;
 
+use super::dead_letter::{DeadLetterQueue, RetryPolicy};
 use super::sub_common::{
     get_pkid, loop_commit_offset, min_qos, publish_message_qos, publish_message_to_client,
     qos2_send_pubrel, wait_packet_ack,
@@ -44,6 +47,7 @@ pub struct ShareLeaderPush<S> {
     message_storage: Arc<S>,
     connection_manager: Arc<ConnectionManager>,
     cache_manager: Arc<CacheManager>,
+    client_pool: Arc<ClientPool>,
 }
 
 impl<S> ShareLeaderPush<S>
@@ -55,12 +59,14 @@ pub fn new(
         message_storage: Arc<S>,
         connection_manager: Arc<ConnectionManager>,
         cache_manager: Arc<CacheManager>,
+        client_pool: Arc<ClientPool>,
     ) -> Self {
         ShareLeaderPush {
             subscribe_manager,
             message_storage,
             connection_manager,
             cache_manager,
+            client_pool,
         }
     }
 
@@ -148,6 +154,11 @@ async fn push_by_round_robin(
         let connection_manager = self.connection_manager.clone();
         let cache_manager = self.cache_manager.clone();
         let subscribe_manager = self.subscribe_manager.clone();
+        let dead_letter_queue = DeadLetterQueue::new(
+            self.cache_manager.clone(),
+            self.client_pool.clone(),
+            self.message_storage.clone(),
+        );
 
         tokio::spawn(async move {
             info!(
@@ -172,6 +183,7 @@ async fn push_by_round_robin(
                         &connection_manager,
                         &cache_manager,
                         &message_storage,
+                        &dead_letter_queue,
                         &subscribe_manager,
                         &share_leader_key,
                         &sub_data,
@@ -213,6 +225,7 @@ async fn read_message_process<S>(
     connection_manager: &Arc<ConnectionManager>,
     cache_manager: &Arc<CacheManager>,
     message_storage: &MessageStorage<S>,
+    dead_letter_queue: &DeadLetterQueue<S>,
     subscribe_manager: &Arc<SubscribeManager>,
     share_leader_key: &str,
     sub_data: &ShareLeaderSubscribeData,
@@ -231,6 +244,7 @@ async fn read_message_process<S>(
         return Ok(None);
     }
 
+    let policy = RetryPolicy::new(&broker_mqtt_conf().dead_letter);
     for record in results.iter() {
         let msg = MqttMessage::decode_record(record.clone())?;
 
@@ -238,6 +252,7 @@ async fn read_message_process<S>(
             continue;
         }
 
+        let mut attempts = 0;
         loop {
             let subscribe = if let Some(subscrbie) =
                 get_subscribe(subscribe_manager, share_leader_key, record.offset.unwrap())
@@ -271,7 +286,7 @@ async fn read_message_process<S>(
                     connection_manager,
                     cache_manager,
                     message_storage,
-                    sub_pub_param,
+                    sub_pub_param.clone(),
                     record.offset.unwrap(),
                     stop_sx,
                 )
@@ -279,6 +294,29 @@ async fn read_message_process<S>(
                 {
                     break;
                 }
+
+                // When the members of the shared subscription keep failing to ack the message,
+                // it is moved to the dead letter topic instead of blocking the whole group.
+                attempts += 1;
+                if policy.is_exhausted(attempts) {
+                    if let Err(e) = dead_letter_queue
+                        .send(
+                            &sub_pub_param,
+                            "Shared subscription members failed to ack",
+                            attempts,
+                        )
+                        .await
+                    {
+                        error!(
+                            "Failed to move message to dead letter topic, group: {}, topic: {}, error: {}",
+                            sub_data.group_name, sub_data.topic_name, e
+                        );
+                    }
+                    break;
+                }
+                if policy.enable {
+                    sleep(policy.backoff(attempts)).await;
+                }
             }
         }
 
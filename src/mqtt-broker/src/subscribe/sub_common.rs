@@ -34,9 +34,14 @@
 use tokio::sync::broadcast::{self, Sender};
 use tokio::time::{sleep, timeout};
 
+use super::dead_letter::RetryPolicy;
 use super::subscriber::SubPublishParam;
 use crate::handler::cache::{CacheManager, QosAckPackageData, QosAckPackageType};
 use crate::handler::error::MqttBrokerError;
+use crate::handler::mountpoint::unmount_publish;
+use crate::handler::tenant::split_tenant_topic;
+use crate::handler::topic_alias::apply_outbound_topic_alias;
+use crate::hook::hook_manager;
 use crate::observability::slow::sub::{record_slow_sub_data, SlowSubData};
 use crate::server::connection_manager::ConnectionManager;
 use crate::server::packet::ResponsePackage;
@@ -92,6 +97,15 @@ pub fn path_regex_match(topic_name: &str, sub_path: &str) -> bool {
         topic_name.to_owned()
     };
 
+    // The topics of a tenant are only matched by the topic filters of the same tenant, the
+    // tenant level is matched as a plain level and not by a wildcard
+    let (topic_tenant, tenant_topic) = split_tenant_topic(&topic);
+    let (path_tenant, tenant_path) = split_tenant_topic(&path);
+    if topic_tenant != path_tenant {
+        return false;
+    }
+    let (topic, path) = (tenant_topic.to_owned(), tenant_path.to_owned());
+
     // Path perfect matching
     if topic == path {
         return true;
@@ -193,12 +207,42 @@ pub async fn publish_message_to_client(
     metadata_cache: &Arc<CacheManager>,
 ) -> Result<(), MqttBrokerError> {
     if let Some(protocol) = connection_manager.get_connect_protocol(resp.connection_id) {
+        let mut packet = resp.packet;
+
+        // strip the mountpoint of the subscriber before the topic reaches the client
+        if let MqttPacket::Publish(publish, _) = &mut packet {
+            if let Some(connection) = metadata_cache.get_connection(resp.connection_id) {
+                unmount_publish(&connection.mountpoint, publish);
+            }
+        }
+
+        // The topic alias is released once the packet is written. Until then the alias is not
+        // given to another topic, and the topic name is sent with the alias until a packet
+        // carrying both has been written.
+        let topic_alias = if protocol == MqttProtocol::Mqtt5 {
+            connection_manager.get_outbound_topic_alias(resp.connection_id)
+        } else {
+            None
+        };
+        let mut alias_topic_name = None;
+        if let Some(topic_alias) = topic_alias.as_ref() {
+            if let MqttPacket::Publish(publish, properties) = &mut packet {
+                alias_topic_name =
+                    apply_outbound_topic_alias(&mut *topic_alias.lock().await, publish, properties);
+            }
+        }
+
+        let delivered = match &packet {
+            MqttPacket::Publish(publish, _) if !hook_manager().is_empty() => Some(publish.clone()),
+            _ => None,
+        };
+
         let response: MqttPacketWrapper = MqttPacketWrapper {
             protocol_version: protocol.clone().into(),
-            packet: resp.packet,
+            packet,
         };
 
-        if connection_manager.is_websocket(resp.connection_id) {
+        let write_result = if connection_manager.is_websocket(resp.connection_id) {
             let mut codec = MqttCodec::new(Some(protocol.into()));
             let mut buff = BytesMut::new();
             match codec.encode_data(response.clone(), &mut buff) {
@@ -209,11 +253,25 @@ pub async fn publish_message_to_client(
             }
             connection_manager
                 .write_websocket_frame(resp.connection_id, response, Message::Binary(buff.to_vec()))
-                .await?;
+                .await
         } else {
             connection_manager
                 .write_tcp_frame(resp.connection_id, response)
-                .await?
+                .await
+        };
+
+        if let (Some(topic_alias), Some(topic_name)) = (topic_alias.as_ref(), alias_topic_name) {
+            topic_alias
+                .lock()
+                .await
+                .release(&topic_name, write_result.is_ok());
+        }
+        write_result?;
+
+        if let Some(publish) = delivered {
+            hook_manager()
+                .on_message_delivered(&sub_pub_param.subscribe.client_id, &publish)
+                .await;
         }
 
         // record slow sub data
@@ -231,14 +289,16 @@ pub async fn publish_message_to_client(
     Ok(())
 }
 
+// Wait for the client to return PubAck. Each timeout re-sends the Publish packet, and when the
+// dead letter retry policy is enabled, an error is returned once the retry times are exhausted.
 pub async fn wait_pub_ack(
     metadata_cache: &Arc<CacheManager>,
     connection_manager: &Arc<ConnectionManager>,
     sub_pub_param: &SubPublishParam,
     stop_sx: &broadcast::Sender<bool>,
     wait_ack_sx: &broadcast::Sender<QosAckPackageData>,
-) {
-    let wait_pub_rec_fn = || async {
+) -> Result<(), MqttBrokerError> {
+    let wait_pub_ack_fn = || async {
         match timeout(Duration::from_secs(30), wait_packet_ack(wait_ack_sx)).await {
             Ok(Some(data)) => {
                 if data.ack_type == QosAckPackageType::PubAck && data.pkid == sub_pub_param.pkid {
@@ -246,15 +306,10 @@ pub async fn wait_pub_ack(
                 }
             }
             Ok(None) => {}
-            Err(e) => {
-                publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx)
-                    .await;
-                return Err(MqttBrokerError::CommonError(
-                    format!(
-                        "Push QOS1 Publish message to client {}, wait PubAck timeout, more than 30s, error message :{:?}",
-                        sub_pub_param.subscribe.client_id,
-                        e
-                    )
+            Err(_) => {
+                return Err(MqttBrokerError::SubPublishWaitAckTimeout(
+                    sub_pub_param.subscribe.client_id.clone(),
+                    "PubAck".to_string(),
                 ));
             }
         };
@@ -265,35 +320,55 @@ pub async fn wait_pub_ack(
         ))
     };
 
+    let policy = RetryPolicy::new(&broker_mqtt_conf().dead_letter);
+    let mut attempts = 0;
     let mut stop_recv = stop_sx.subscribe();
     loop {
         select! {
             val = stop_recv.recv() => {
                 if let Ok(flag) = val {
                     if flag {
-                        return;
+                        return Ok(());
                     }
                 }
             }
-            val = wait_pub_rec_fn() => {
-                if let Err(e) = val {
-                    error!("{:?}",e);
-                    sleep(Duration::from_secs(1)).await;
-                    continue;
+            val = wait_pub_ack_fn() => {
+                match val {
+                    Ok(()) => return Ok(()),
+                    Err(MqttBrokerError::SubPublishWaitAckTimeout(client_id, _)) => {
+                        attempts += 1;
+                        if policy.is_exhausted(attempts) {
+                            return Err(MqttBrokerError::SubPublishRetryExhausted(client_id, attempts));
+                        }
+                        error!(
+                            "Push QOS1 Publish message to client {}, wait PubAck timeout, attempts: {}",
+                            client_id, attempts
+                        );
+                        if policy.enable {
+                            sleep(policy.backoff(attempts)).await;
+                        }
+                        publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx)
+                            .await;
+                    }
+                    Err(e) => {
+                        error!("{:?}",e);
+                        sleep(Duration::from_secs(1)).await;
+                    }
                 }
-                break;
             }
         }
     }
 }
 
+// Wait for the client to return PubRec. Without the dead letter retry policy, the wait gives up
+// after the first timeout, otherwise the Publish packet is re-sent until the retry times are exhausted.
 pub async fn wait_pub_rec(
     metadata_cache: &Arc<CacheManager>,
     connection_manager: &Arc<ConnectionManager>,
     sub_pub_param: &SubPublishParam,
     stop_sx: &broadcast::Sender<bool>,
     wait_ack_sx: &broadcast::Sender<QosAckPackageData>,
-) {
+) -> Result<(), MqttBrokerError> {
     let wait_pub_rec_fn = || async {
         match timeout(Duration::from_secs(30), wait_packet_ack(wait_ack_sx)).await {
             Ok(Some(data)) => {
@@ -302,15 +377,10 @@ pub async fn wait_pub_rec(
                 }
             }
             Ok(None) => {}
-            Err(e) => {
-                publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx)
-                    .await;
-                return Err(MqttBrokerError::CommonError(
-                    format!(
-                        "Push QOS2 Publish message to client {}, wait pubrec timeout, more than 30s, error message :{:?}",
-                        sub_pub_param.subscribe.client_id,
-                        e
-                    )
+            Err(_) => {
+                return Err(MqttBrokerError::SubPublishWaitAckTimeout(
+                    sub_pub_param.subscribe.client_id.clone(),
+                    "PubRec".to_string(),
                 ));
             }
         };
@@ -321,26 +391,44 @@ pub async fn wait_pub_rec(
         ))
     };
 
+    let policy = RetryPolicy::new(&broker_mqtt_conf().dead_letter);
+    let mut attempts = 0;
     let mut stop_recv = stop_sx.subscribe();
     loop {
         select! {
             val = stop_recv.recv() => {
                 if let Ok(flag) = val {
                     if flag {
-                        return;
+                        return Ok(());
                     }
                 }
             }
             val = wait_pub_rec_fn() => {
-                if let Err(e) = val {
-                    error!("{:?}",e);
-                    if e.to_string().contains("wait pubrec timeout"){
-                        break;
+                match val {
+                    Ok(()) => return Ok(()),
+                    Err(MqttBrokerError::SubPublishWaitAckTimeout(client_id, _)) => {
+                        attempts += 1;
+                        error!(
+                            "Push QOS2 Publish message to client {}, wait PubRec timeout, attempts: {}",
+                            client_id, attempts
+                        );
+                        if !policy.enable {
+                            publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx)
+                                .await;
+                            return Ok(());
+                        }
+                        if policy.is_exhausted(attempts) {
+                            return Err(MqttBrokerError::SubPublishRetryExhausted(client_id, attempts));
+                        }
+                        sleep(policy.backoff(attempts)).await;
+                        publish_message_qos(metadata_cache, connection_manager, sub_pub_param, stop_sx)
+                            .await;
+                    }
+                    Err(e) => {
+                        error!("{:?}",e);
+                        sleep(Duration::from_secs(1)).await;
                     }
-                    sleep(Duration::from_secs(1)).await;
-                    continue;
                 }
-                break;
             }
         }
     }
@@ -689,6 +777,19 @@ fn path_regex_match_test() {
         let topic_name = r"y/a/z/b".to_string();
         let sub_regex = r"y/+/z/#".to_string();
         assert!(path_regex_match(&topic_name, &sub_regex));
+
+        // the topics of a tenant are only matched by the filters of the tenant
+        let topic_name = r"$tenant/tenant_a/sensor/1".to_string();
+        assert!(path_regex_match(&topic_name, r"$tenant/tenant_a/sensor/+"));
+        assert!(path_regex_match(&topic_name, r"$tenant/tenant_a/#"));
+        assert!(path_regex_match(
+            &topic_name,
+            r"$share/groupname/$tenant/tenant_a/sensor/#"
+        ));
+        assert!(!path_regex_match(&topic_name, r"$tenant/tenant_b/#"));
+        assert!(!path_regex_match(&topic_name, r"$tenant/+/sensor/1"));
+        assert!(!path_regex_match(&topic_name, r"#"));
+        assert!(!path_regex_match(&topic_name, r"+/+/sensor/1"));
     }
 
     #[test]
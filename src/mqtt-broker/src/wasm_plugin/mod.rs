@@ -0,0 +1,417 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, OnceLock};
+
+use bytes::Bytes;
+use log::debug;
+use metadata_struct::mqtt::connection::MQTTConnection;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
+use protocol::mqtt::common::{Publish, PublishProperties};
+use wasmtime::{
+    Caller, Config, Engine, InstancePre, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+use crate::handler::cache::CacheManager;
+use crate::handler::error::MqttBrokerError;
+use crate::handler::topic::check_modified_publish_topic;
+use crate::subscribe::sub_common::path_regex_match;
+
+// The host functions are imported by the module from this namespace.
+pub const WASM_HOST_MODULE: &str = "robustmq";
+// The module exports `on_publish() -> i32`, it returns 0 to keep the message and 1 to drop it.
+pub const WASM_ON_PUBLISH: &str = "on_publish";
+pub const WASM_MEMORY: &str = "memory";
+
+pub const WASM_RESULT_CONTINUE: i32 = 0;
+pub const WASM_RESULT_DROP: i32 = 1;
+
+pub const DEFAULT_WASM_MAX_FUEL: u64 = 10_000_000;
+pub const DEFAULT_WASM_MAX_MEMORY_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WasmMessage {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub user_properties: Vec<(String, String)>,
+}
+
+struct WasmHostState {
+    message: WasmMessage,
+    limits: StoreLimits,
+}
+
+pub struct WasmPlugin {
+    pub info: MqttWasmPlugin,
+    instance_pre: InstancePre<WasmHostState>,
+}
+
+static WASM_ENGINE: OnceLock<Engine> = OnceLock::new();
+
+// Fuel is consumed by every executed instruction, so a module cannot run longer than its fuel.
+fn wasm_engine() -> &'static Engine {
+    WASM_ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).unwrap()
+    })
+}
+
+impl WasmPlugin {
+    pub fn new(info: MqttWasmPlugin) -> Result<Self, MqttBrokerError> {
+        let plugin_error = |e: wasmtime::Error| {
+            MqttBrokerError::WasmPluginError(info.plugin_name.clone(), e.to_string())
+        };
+
+        let module = Module::new(wasm_engine(), &info.module).map_err(plugin_error)?;
+        for name in [WASM_ON_PUBLISH, WASM_MEMORY] {
+            if module.get_export(name).is_none() {
+                return Err(MqttBrokerError::WasmPluginError(
+                    info.plugin_name.clone(),
+                    format!("the module does not export {}", name),
+                ));
+            }
+        }
+
+        let mut linker = Linker::new(wasm_engine());
+        register_host_functions(&mut linker).map_err(plugin_error)?;
+        // Fails when the module imports functions that the host does not provide.
+        let instance_pre = linker.instantiate_pre(&module).map_err(plugin_error)?;
+        Ok(WasmPlugin { info, instance_pre })
+    }
+
+    pub fn is_match(&self, tenant: &str, topic_name: &str) -> bool {
+        if !self.info.tenant.is_empty() && self.info.tenant != tenant {
+            return false;
+        }
+        self.info
+            .topics
+            .iter()
+            .any(|filter| path_regex_match(topic_name, filter))
+    }
+
+    // Runs the module on the message in a new instance, returns None when the module drops it.
+    pub fn call(&self, message: WasmMessage) -> Result<Option<WasmMessage>, MqttBrokerError> {
+        let plugin_error = |e: wasmtime::Error| {
+            MqttBrokerError::WasmPluginError(self.info.plugin_name.clone(), e.to_string())
+        };
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.info.max_memory_bytes as usize)
+            .instances(1)
+            .build();
+        let mut store = Store::new(wasm_engine(), WasmHostState { message, limits });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.info.max_fuel).map_err(plugin_error)?;
+
+        let instance = self
+            .instance_pre
+            .instantiate(&mut store)
+            .map_err(plugin_error)?;
+        let on_publish = instance
+            .get_typed_func::<(), i32>(&mut store, WASM_ON_PUBLISH)
+            .map_err(plugin_error)?;
+        match on_publish.call(&mut store, ()).map_err(plugin_error)? {
+            WASM_RESULT_CONTINUE => Ok(Some(store.into_data().message)),
+            WASM_RESULT_DROP => Ok(None),
+            code => Err(MqttBrokerError::WasmPluginError(
+                self.info.plugin_name.clone(),
+                format!("unknown result {} of {}", code, WASM_ON_PUBLISH),
+            )),
+        }
+    }
+}
+
+// Host API, all strings are UTF-8. The get functions copy the value into the guest memory at ptr
+// when it fits into cap bytes and return its length, so the guest can call again with a larger
+// buffer. get_user_property returns -1 when the property does not exist.
+fn register_host_functions(linker: &mut Linker<WasmHostState>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        WASM_HOST_MODULE,
+        "get_topic",
+        |mut caller: Caller<'_, WasmHostState>, ptr: i32, cap: i32| -> wasmtime::Result<i32> {
+            let data = caller.data().message.topic.clone().into_bytes();
+            write_guest(&mut caller, ptr, cap, &data)
+        },
+    )?;
+    linker.func_wrap(
+        WASM_HOST_MODULE,
+        "set_topic",
+        |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+            let topic = read_guest_string(&mut caller, ptr, len)?;
+            caller.data_mut().message.topic = topic;
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        WASM_HOST_MODULE,
+        "get_payload",
+        |mut caller: Caller<'_, WasmHostState>, ptr: i32, cap: i32| -> wasmtime::Result<i32> {
+            let data = caller.data().message.payload.clone();
+            write_guest(&mut caller, ptr, cap, &data)
+        },
+    )?;
+    linker.func_wrap(
+        WASM_HOST_MODULE,
+        "set_payload",
+        |mut caller: Caller<'_, WasmHostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+            let payload = read_guest(&mut caller, ptr, len)?;
+            caller.data_mut().message.payload = payload;
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        WASM_HOST_MODULE,
+        "get_user_property",
+        |mut caller: Caller<'_, WasmHostState>,
+         key_ptr: i32,
+         key_len: i32,
+         ptr: i32,
+         cap: i32|
+         -> wasmtime::Result<i32> {
+            let key = read_guest_string(&mut caller, key_ptr, key_len)?;
+            let value = caller
+                .data()
+                .message
+                .user_properties
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone().into_bytes());
+            match value {
+                Some(data) => write_guest(&mut caller, ptr, cap, &data),
+                None => Ok(-1),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        WASM_HOST_MODULE,
+        "set_user_property",
+        |mut caller: Caller<'_, WasmHostState>,
+         key_ptr: i32,
+         key_len: i32,
+         ptr: i32,
+         len: i32|
+         -> wasmtime::Result<()> {
+            let key = read_guest_string(&mut caller, key_ptr, key_len)?;
+            let value = read_guest_string(&mut caller, ptr, len)?;
+            let properties = &mut caller.data_mut().message.user_properties;
+            properties.retain(|(k, _)| *k != key);
+            properties.push((key, value));
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn guest_memory(caller: &mut Caller<'_, WasmHostState>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export(WASM_MEMORY)
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("the module does not export memory"))
+}
+
+fn read_guest(
+    caller: &mut Caller<'_, WasmHostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<Vec<u8>> {
+    if ptr < 0 || len < 0 {
+        return Err(wasmtime::Error::msg("invalid memory range"));
+    }
+    let memory = guest_memory(caller)?;
+    let mut data = vec![0; len as usize];
+    memory.read(&*caller, ptr as usize, &mut data)?;
+    Ok(data)
+}
+
+fn read_guest_string(
+    caller: &mut Caller<'_, WasmHostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<String> {
+    Ok(String::from_utf8(read_guest(caller, ptr, len)?)?)
+}
+
+fn write_guest(
+    caller: &mut Caller<'_, WasmHostState>,
+    ptr: i32,
+    cap: i32,
+    data: &[u8],
+) -> wasmtime::Result<i32> {
+    if ptr < 0 || cap < 0 {
+        return Err(wasmtime::Error::msg("invalid memory range"));
+    }
+    if data.len() <= cap as usize {
+        let memory = guest_memory(caller)?;
+        memory.write(&mut *caller, ptr as usize, data)?;
+    }
+    Ok(data.len() as i32)
+}
+
+// Runs the plugins matching the topic in the order of their names, returns None when a plugin
+// drops the message. The modules run on the blocking thread pool, since a module may run up to
+// its max_fuel instructions and would otherwise hold up the other tasks of the runtime worker.
+pub async fn process_wasm_plugins(
+    cache_manager: &Arc<CacheManager>,
+    connection: &MQTTConnection,
+    topic_name: String,
+    mut publish: Publish,
+    mut publish_properties: Option<PublishProperties>,
+) -> Result<Option<(String, Publish, Option<PublishProperties>)>, MqttBrokerError> {
+    if cache_manager.wasm_plugin_info.is_empty() {
+        return Ok(Some((topic_name, publish, publish_properties)));
+    }
+    let mut plugins: Vec<Arc<WasmPlugin>> = cache_manager
+        .wasm_plugin_info
+        .iter()
+        .filter(|plugin| plugin.is_match(&connection.tenant, &topic_name))
+        .map(|plugin| plugin.clone())
+        .collect();
+    if plugins.is_empty() {
+        return Ok(Some((topic_name, publish, publish_properties)));
+    }
+    plugins.sort_by(|a, b| a.info.plugin_name.cmp(&b.info.plugin_name));
+
+    let message = WasmMessage {
+        topic: topic_name.clone(),
+        payload: publish.payload.to_vec(),
+        user_properties: publish_properties
+            .as_ref()
+            .map(|properties| properties.user_properties.clone())
+            .unwrap_or_default(),
+    };
+    let result = tokio::task::spawn_blocking(move || call_wasm_plugins(&plugins, message))
+        .await
+        .map_err(|e| MqttBrokerError::CommonError(e.to_string()))??;
+    let Some(message) = result else {
+        return Ok(None);
+    };
+
+    if message.topic != topic_name {
+        check_modified_publish_topic(cache_manager, connection, &message.topic, &publish)?;
+        publish.topic = Bytes::from(message.topic.clone());
+    }
+    publish.payload = Bytes::from(message.payload);
+    match publish_properties.as_mut() {
+        Some(properties) => properties.user_properties = message.user_properties,
+        None if !message.user_properties.is_empty() => {
+            publish_properties = Some(PublishProperties {
+                user_properties: message.user_properties,
+                ..Default::default()
+            });
+        }
+        None => {}
+    }
+    Ok(Some((message.topic, publish, publish_properties)))
+}
+
+fn call_wasm_plugins(
+    plugins: &[Arc<WasmPlugin>],
+    mut message: WasmMessage,
+) -> Result<Option<WasmMessage>, MqttBrokerError> {
+    for plugin in plugins {
+        match plugin.call(message)? {
+            Some(data) => message = data,
+            None => {
+                debug!(
+                    "WASM plugin {} dropped the message",
+                    plugin.info.plugin_name
+                );
+                return Ok(None);
+            }
+        }
+    }
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
+
+    use super::{WasmMessage, WasmPlugin, DEFAULT_WASM_MAX_FUEL, DEFAULT_WASM_MAX_MEMORY_BYTES};
+    use crate::handler::error::MqttBrokerError;
+
+    // Prefixes the payload with "v:" and sets the user property "decoded" to "1", drops the
+    // messages with an empty payload.
+    const TRANSFORM_WAT: &str = r#"
+        (module
+            (import "robustmq" "get_payload" (func $get_payload (param i32 i32) (result i32)))
+            (import "robustmq" "set_payload" (func $set_payload (param i32 i32)))
+            (import "robustmq" "set_user_property" (func $set_user_property (param i32 i32 i32 i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "decoded1")
+            (data (i32.const 16) "v:")
+            (func (export "on_publish") (result i32)
+                (local $len i32)
+                (local.set $len (call $get_payload (i32.const 18) (i32.const 1024)))
+                (if (i32.eqz (local.get $len)) (then (return (i32.const 1))))
+                (call $set_payload (i32.const 16) (i32.add (local.get $len) (i32.const 2)))
+                (call $set_user_property (i32.const 0) (i32.const 7) (i32.const 7) (i32.const 1))
+                (i32.const 0)))
+    "#;
+
+    const LOOP_WAT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "on_publish") (result i32)
+                (loop $l (br $l))
+                (i32.const 0)))
+    "#;
+
+    fn build_plugin(name: &str, wat: &str) -> Result<WasmPlugin, MqttBrokerError> {
+        WasmPlugin::new(MqttWasmPlugin {
+            plugin_name: name.to_string(),
+            topics: vec!["sensor/+".to_string()],
+            module: wat.as_bytes().to_vec(),
+            max_fuel: DEFAULT_WASM_MAX_FUEL,
+            max_memory_bytes: DEFAULT_WASM_MAX_MEMORY_BYTES,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn wasm_plugin_transform_test() {
+        let plugin = build_plugin("decode", TRANSFORM_WAT).unwrap();
+        assert!(plugin.is_match("", "sensor/1"));
+        assert!(!plugin.is_match("", "device/1"));
+
+        let message = WasmMessage {
+            topic: "sensor/1".to_string(),
+            payload: b"raw".to_vec(),
+            user_properties: Vec::new(),
+        };
+        let data = plugin.call(message).unwrap().unwrap();
+        assert_eq!(data.payload, b"v:raw".to_vec());
+        assert_eq!(
+            data.user_properties,
+            vec![("decoded".to_string(), "1".to_string())]
+        );
+
+        let message = WasmMessage {
+            topic: "sensor/1".to_string(),
+            ..Default::default()
+        };
+        assert!(plugin.call(message).unwrap().is_none());
+    }
+
+    #[test]
+    fn wasm_plugin_limit_test() {
+        let plugin = build_plugin("loop", LOOP_WAT).unwrap();
+        assert!(plugin.call(WasmMessage::default()).is_err());
+
+        // the module does not export on_publish
+        assert!(build_plugin("invalid", r#"(module (memory (export "memory") 1))"#).is_err());
+    }
+}
@@ -145,4 +145,7 @@ pub enum PlacementCenterError {
 
     #[error("Schema [{0}] already exist")]
     SchemaAlreadyExist(String),
+
+    #[error("Tenant [{0}] still owns {1} resources and cannot be deleted")]
+    TenantHasResources(String, usize),
 }
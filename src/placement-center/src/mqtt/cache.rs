@@ -79,16 +79,16 @@ pub fn remove_topic(&self, cluster_name: &str, topic_name: &str) {
     // User
     pub fn add_user(&self, cluster_name: &str, user: MqttUser) {
         if let Some(data) = self.user_list.get_mut(cluster_name) {
-            data.insert(user.username.clone(), user);
+            data.insert(user.login_name(), user);
         } else {
             let data = DashMap::with_capacity(8);
-            data.insert(user.username.clone(), user);
+            data.insert(user.login_name(), user);
             self.user_list.insert(cluster_name.to_owned(), data);
         }
     }
 
     pub fn remove_user(&self, cluster_name: &str, user_name: &str) {
-        if let Some(data) = self.topic_list.get_mut(cluster_name) {
+        if let Some(data) = self.user_list.get_mut(cluster_name) {
             data.remove(user_name);
         }
     }
@@ -100,6 +100,13 @@ async fn check_heartbeat(
             continue;
         };
 
+        // A paused connector no longer reports heartbeats and must not be rescheduled.
+        if connector.status == MQTTStatus::Paused {
+            mqtt_cache
+                .remove_connector_heartbeat(&heartbeat.cluster_name, &heartbeat.connector_name);
+            continue;
+        }
+
         if now_second() - heartbeat.last_heartbeat > config.heartbeat.heartbeat_timeout_ms / 1000 {
             info!(
                 "cluster:{},Connector {} heartbeat expired, rescheduled, new node: {}",
@@ -128,6 +135,11 @@ async fn start_stop_connector_thread(
     placement_cache: &Arc<PlacementCacheManager>,
 ) -> Result<(), PlacementCenterError> {
     for mut connector in mqtt_cache.get_all_connector() {
+        // A paused connector is not assigned to any broker until it is resumed.
+        if connector.status == MQTTStatus::Paused {
+            continue;
+        }
+
         if connector.broker_id.is_none() && connector.status == MQTTStatus::Running {
             warn!("Connector {} has an abnormal state, which is Running, but the execution node is empty.", connector.cluster_name);
         }
@@ -21,10 +21,13 @@
 use log::warn;
 use log::{debug, error, info};
 use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+use metadata_struct::mqtt::rule::MqttRule;
 use metadata_struct::mqtt::session::MqttSession;
 use metadata_struct::mqtt::subscribe_data::MqttSubscribe;
+use metadata_struct::mqtt::tenant::MqttTenant;
 use metadata_struct::mqtt::topic::MqttTopic;
 use metadata_struct::mqtt::user::MqttUser;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 use metadata_struct::placement::node::BrokerNode;
 use metadata_struct::schema::{SchemaData, SchemaResourceBind};
 use protocol::broker_mqtt::broker_mqtt_inner::MqttBrokerUpdateCacheResourceType;
@@ -309,6 +312,108 @@ pub async fn update_cache_by_delete_user(
     Ok(())
 }
 
+pub async fn update_cache_by_add_rule(
+    cluster_name: &str,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    rule: MqttRule,
+) -> Result<(), PlacementCenterError> {
+    let data = serde_json::to_string(&rule)?;
+    let message = MQTTInnerCallMessage {
+        action_type: MqttBrokerUpdateCacheActionType::Set,
+        resource_type: MqttBrokerUpdateCacheResourceType::Rule,
+        cluster_name: cluster_name.to_string(),
+        data,
+    };
+    add_call_message(call_manager, cluster_name, client_pool, message).await?;
+    Ok(())
+}
+
+pub async fn update_cache_by_delete_rule(
+    cluster_name: &str,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    rule: MqttRule,
+) -> Result<(), PlacementCenterError> {
+    let data = serde_json::to_string(&rule)?;
+    let message = MQTTInnerCallMessage {
+        action_type: MqttBrokerUpdateCacheActionType::Delete,
+        resource_type: MqttBrokerUpdateCacheResourceType::Rule,
+        cluster_name: cluster_name.to_string(),
+        data,
+    };
+    add_call_message(call_manager, cluster_name, client_pool, message).await?;
+    Ok(())
+}
+
+pub async fn update_cache_by_add_wasm_plugin(
+    cluster_name: &str,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    plugin: MqttWasmPlugin,
+) -> Result<(), PlacementCenterError> {
+    let data = serde_json::to_string(&plugin)?;
+    let message = MQTTInnerCallMessage {
+        action_type: MqttBrokerUpdateCacheActionType::Set,
+        resource_type: MqttBrokerUpdateCacheResourceType::WasmPlugin,
+        cluster_name: cluster_name.to_string(),
+        data,
+    };
+    add_call_message(call_manager, cluster_name, client_pool, message).await?;
+    Ok(())
+}
+
+pub async fn update_cache_by_delete_wasm_plugin(
+    cluster_name: &str,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    plugin: MqttWasmPlugin,
+) -> Result<(), PlacementCenterError> {
+    let data = serde_json::to_string(&plugin)?;
+    let message = MQTTInnerCallMessage {
+        action_type: MqttBrokerUpdateCacheActionType::Delete,
+        resource_type: MqttBrokerUpdateCacheResourceType::WasmPlugin,
+        cluster_name: cluster_name.to_string(),
+        data,
+    };
+    add_call_message(call_manager, cluster_name, client_pool, message).await?;
+    Ok(())
+}
+
+pub async fn update_cache_by_add_tenant(
+    cluster_name: &str,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    tenant: MqttTenant,
+) -> Result<(), PlacementCenterError> {
+    let data = serde_json::to_string(&tenant)?;
+    let message = MQTTInnerCallMessage {
+        action_type: MqttBrokerUpdateCacheActionType::Set,
+        resource_type: MqttBrokerUpdateCacheResourceType::Tenant,
+        cluster_name: cluster_name.to_string(),
+        data,
+    };
+    add_call_message(call_manager, cluster_name, client_pool, message).await?;
+    Ok(())
+}
+
+pub async fn update_cache_by_delete_tenant(
+    cluster_name: &str,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    tenant: MqttTenant,
+) -> Result<(), PlacementCenterError> {
+    let data = serde_json::to_string(&tenant)?;
+    let message = MQTTInnerCallMessage {
+        action_type: MqttBrokerUpdateCacheActionType::Delete,
+        resource_type: MqttBrokerUpdateCacheResourceType::Tenant,
+        cluster_name: cluster_name.to_string(),
+        data,
+    };
+    add_call_message(call_manager, cluster_name, client_pool, message).await?;
+    Ok(())
+}
+
 pub async fn update_cache_by_add_subscribe(
     cluster_name: &str,
     call_manager: &Arc<MQTTInnerCallManager>,
@@ -19,6 +19,7 @@
 use grpc_clients::pool::ClientPool;
 use log::info;
 use message_expire::MessageExpire;
+use scheduled_message::ScheduledMessageScheduler;
 use session_expire::SessionExpire;
 use tokio::select;
 use tokio::sync::broadcast;
@@ -26,10 +27,12 @@
 
 use crate::core::cache::PlacementCacheManager;
 use crate::mqtt::cache::MqttCacheManager;
+use crate::route::apply::RaftMachineApply;
 use crate::storage::rocksdb::RocksDBEngine;
 
 pub mod call_broker;
 pub mod message_expire;
+pub mod scheduled_message;
 pub mod session_expire;
 
 pub struct MqttController {
@@ -37,6 +40,7 @@ pub struct MqttController {
     placement_center_cache: Arc<PlacementCacheManager>,
     mqtt_cache_manager: Arc<MqttCacheManager>,
     client_pool: Arc<ClientPool>,
+    raft_machine_apply: Arc<RaftMachineApply>,
     thread_running_info: DashMap<String, bool>,
     stop_send: broadcast::Sender<bool>,
 }
@@ -47,6 +51,7 @@ pub fn new(
         placement_center_cache: Arc<PlacementCacheManager>,
         mqtt_cache_manager: Arc<MqttCacheManager>,
         client_pool: Arc<ClientPool>,
+        raft_machine_apply: Arc<RaftMachineApply>,
         stop_send: broadcast::Sender<bool>,
     ) -> MqttController {
         MqttController {
@@ -54,6 +59,7 @@ pub fn new(
             placement_center_cache,
             mqtt_cache_manager,
             client_pool,
+            raft_machine_apply,
             thread_running_info: DashMap::with_capacity(2),
             stop_send,
         }
@@ -180,6 +186,30 @@ pub async fn check_start_thread(&self) {
                     }
                 }
             });
+            // Fires the scheduled messages when their ticks are due
+            let scheduler = ScheduledMessageScheduler::new(
+                cluster_name.clone(),
+                self.rocksdb_engine_handler.clone(),
+                self.placement_center_cache.clone(),
+                self.raft_machine_apply.clone(),
+                self.client_pool.clone(),
+            );
+            let mut stop_recv = self.stop_send.subscribe();
+            tokio::spawn(async move {
+                loop {
+                    select! {
+                        val = stop_recv.recv() =>{
+                            if let Ok(flag) = val {
+                                if flag {
+                                    break;
+                                }
+                            }
+                        }
+                        _ = scheduler.fire_scheduled_messages() => {
+                        }
+                    }
+                }
+            });
             self.thread_running_info.insert(cluster_name.clone(), true);
         }
         sleep(Duration::from_secs(1)).await;
@@ -0,0 +1,203 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_base::error::common::CommonError;
+use common_base::tools::now_second;
+use futures::future::join_all;
+use grpc_clients::mqtt::inner::call::send_scheduled_message;
+use grpc_clients::pool::ClientPool;
+use log::{debug, error};
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+use protocol::broker_mqtt::broker_mqtt_inner::SendScheduledMessageRequest;
+use tokio::time::{sleep, timeout};
+
+use crate::core::cache::PlacementCacheManager;
+use crate::core::error::PlacementCenterError;
+use crate::route::apply::RaftMachineApply;
+use crate::route::data::{StorageData, StorageDataType};
+use crate::storage::mqtt::scheduled_message::MqttScheduledMessageStorage;
+use crate::storage::rocksdb::RocksDBEngine;
+
+// A tick that is not acknowledged in time is sent again in the next round.
+const SCHEDULED_MESSAGE_SEND_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Fires the scheduled messages of a cluster. The controller only runs on the leader of the
+// placement center, so each tick is fired by a single scheduler however many brokers there are.
+pub struct ScheduledMessageScheduler {
+    cluster_name: String,
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+    placement_cache_manager: Arc<PlacementCacheManager>,
+    raft_machine_apply: Arc<RaftMachineApply>,
+    client_pool: Arc<ClientPool>,
+}
+
+impl ScheduledMessageScheduler {
+    pub fn new(
+        cluster_name: String,
+        rocksdb_engine_handler: Arc<RocksDBEngine>,
+        placement_cache_manager: Arc<PlacementCacheManager>,
+        raft_machine_apply: Arc<RaftMachineApply>,
+        client_pool: Arc<ClientPool>,
+    ) -> Self {
+        ScheduledMessageScheduler {
+            cluster_name,
+            rocksdb_engine_handler,
+            placement_cache_manager,
+            raft_machine_apply,
+            client_pool,
+        }
+    }
+
+    pub async fn fire_scheduled_messages(&self) {
+        let storage = MqttScheduledMessageStorage::new(self.rocksdb_engine_handler.clone());
+        match storage.list(&self.cluster_name) {
+            Ok(messages) => {
+                let now = now_second();
+                let mut fires = Vec::new();
+                for message in messages {
+                    match is_fire_due(&message, now) {
+                        Ok(true) => fires.push(self.fire(&storage, message, now)),
+                        Ok(false) => {}
+                        Err(e) => {
+                            error!(
+                                "Scheduled message {} has an invalid schedule, error message: {}",
+                                message.name, e
+                            );
+                        }
+                    }
+                }
+
+                // The messages are fired together, so a broker that does not answer does not
+                // hold back the other messages.
+                for result in join_all(fires).await {
+                    if let Err(e) = result {
+                        error!("Failed to fire scheduled message, error message: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("{}", e);
+            }
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+
+    // The tick is committed before it is sent, so the next leader does not fire it again.
+    // It is sent until the broker acknowledges it, a tick sent more than once is only stored
+    // once because the broker drops the ticks it has already stored.
+    async fn fire(
+        &self,
+        storage: &MqttScheduledMessageStorage,
+        mut message: MqttScheduledMessage,
+        now: u64,
+    ) -> Result<(), PlacementCenterError> {
+        let node_addr = self
+            .placement_cache_manager
+            .get_broker_node_addr_by_cluster(&self.cluster_name);
+        if node_addr.is_empty() {
+            debug!(
+                "Cluster {} has no broker node available, scheduled message {} is not fired.",
+                self.cluster_name, message.name
+            );
+            return Ok(());
+        }
+
+        if !message.is_delivery_pending() {
+            message.last_fire_time = now;
+            message.fire_count += 1;
+            self.save_fire_state(&message).await?;
+        }
+
+        // The message may have been cancelled after it was listed.
+        if storage.get(&self.cluster_name, &message.name)?.is_none() {
+            return Ok(());
+        }
+
+        let request = SendScheduledMessageRequest {
+            cluster_name: self.cluster_name.clone(),
+            message: message.encode(),
+        };
+        timeout(
+            SCHEDULED_MESSAGE_SEND_TIMEOUT,
+            send_scheduled_message(&self.client_pool, &node_addr, request),
+        )
+        .await??;
+
+        message.delivered_fire_time = message.last_fire_time;
+        self.save_fire_state(&message).await
+    }
+
+    async fn save_fire_state(
+        &self,
+        message: &MqttScheduledMessage,
+    ) -> Result<(), PlacementCenterError> {
+        let data = StorageData::new(
+            StorageDataType::MqttSetScheduledMessageFireState,
+            message.fire_state().encode(),
+        );
+        self.raft_machine_apply.client_write(data).await?;
+        Ok(())
+    }
+}
+
+// A fired tick that the broker has not acknowledged is sent again. When several ticks were
+// missed, for example while the placement center had no leader, the message is fired once and
+// the schedule continues from the current time.
+pub fn is_fire_due(message: &MqttScheduledMessage, now: u64) -> Result<bool, CommonError> {
+    if message.is_delivery_pending() {
+        return Ok(true);
+    }
+    Ok(matches!(message.pending_fire_time()?, Some(time) if time <= now))
+}
+
+#[cfg(test)]
+mod tests {
+    use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+
+    use super::is_fire_due;
+
+    #[test]
+    fn is_fire_due_test() {
+        let mut message = MqttScheduledMessage {
+            interval_sec: 60,
+            start_time: 1000,
+            end_time: 2000,
+            ..Default::default()
+        };
+        assert!(!is_fire_due(&message, 999).unwrap());
+        assert!(is_fire_due(&message, 1000).unwrap());
+
+        message.last_fire_time = 1000;
+        message.delivered_fire_time = 1000;
+        assert!(!is_fire_due(&message, 1059).unwrap());
+        assert!(is_fire_due(&message, 1060).unwrap());
+
+        // missed ticks are fired once
+        assert!(is_fire_due(&message, 1500).unwrap());
+        message.last_fire_time = 1500;
+        message.delivered_fire_time = 1500;
+        assert!(!is_fire_due(&message, 1500).unwrap());
+        assert!(is_fire_due(&message, 1540).unwrap());
+
+        // a tick that is not acknowledged is sent again
+        message.last_fire_time = 1960;
+        assert!(is_fire_due(&message, 1961).unwrap());
+        message.delivered_fire_time = 1960;
+        assert!(!is_fire_due(&message, 1961).unwrap());
+        assert!(!is_fire_due(&message, 3000).unwrap());
+    }
+}
@@ -14,8 +14,10 @@
 
 pub mod acl;
 pub mod connector;
+pub mod scheduled_message;
 pub mod session;
 pub mod share_sub;
 pub mod subscribe;
+pub mod tenant;
 pub mod topic;
 pub mod user;
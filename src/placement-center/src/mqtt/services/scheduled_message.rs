@@ -0,0 +1,94 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+use prost::Message;
+use protocol::placement_center::placement_center_mqtt::{
+    CreateScheduledMessageReply, CreateScheduledMessageRequest, DeleteScheduledMessageReply,
+    DeleteScheduledMessageRequest, ListScheduledMessageReply, ListScheduledMessageRequest,
+};
+use rocksdb_engine::RocksDBEngine;
+use tonic::{Request, Response, Status};
+
+use crate::route::apply::RaftMachineApply;
+use crate::route::data::{StorageData, StorageDataType};
+use crate::storage::mqtt::scheduled_message::MqttScheduledMessageStorage;
+
+pub fn list_scheduled_message_by_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<ListScheduledMessageRequest>,
+) -> Result<Response<ListScheduledMessageReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttScheduledMessageStorage::new(rocksdb_engine_handler.clone());
+
+    if !req.name.is_empty() {
+        let messages = storage
+            .get(&req.cluster_name, &req.name)?
+            .map(|message| vec![message.encode()])
+            .unwrap_or_default();
+        return Ok(Response::new(ListScheduledMessageReply { messages }));
+    }
+
+    let messages = storage
+        .list(&req.cluster_name)?
+        .iter()
+        .map(|message| message.encode())
+        .collect();
+    Ok(Response::new(ListScheduledMessageReply { messages }))
+}
+
+// Scheduled messages are only read by the scheduler of the placement center, so they are not
+// pushed to the broker caches.
+pub async fn create_scheduled_message_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    request: Request<CreateScheduledMessageRequest>,
+) -> Result<Response<CreateScheduledMessageReply>, Status> {
+    let req = request.into_inner();
+
+    let message = match serde_json::from_slice::<MqttScheduledMessage>(&req.content) {
+        Ok(message) => message,
+        Err(e) => {
+            return Err(Status::cancelled(e.to_string()));
+        }
+    };
+    if let Err(e) = message.validate() {
+        return Err(Status::cancelled(e.to_string()));
+    }
+
+    let data = StorageData::new(
+        StorageDataType::MqttSetScheduledMessage,
+        CreateScheduledMessageRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(CreateScheduledMessageReply::default()))
+}
+
+pub async fn delete_scheduled_message_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    request: Request<DeleteScheduledMessageRequest>,
+) -> Result<Response<DeleteScheduledMessageReply>, Status> {
+    let req = request.into_inner();
+    let data = StorageData::new(
+        StorageDataType::MqttDeleteScheduledMessage,
+        DeleteScheduledMessageRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(DeleteScheduledMessageReply::default()))
+}
@@ -0,0 +1,124 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::tenant::MqttTenant;
+use prost::Message;
+use protocol::placement_center::placement_center_mqtt::{
+    CreateTenantReply, CreateTenantRequest, DeleteTenantReply, DeleteTenantRequest,
+    ListTenantReply, ListTenantRequest,
+};
+use rocksdb_engine::RocksDBEngine;
+use tonic::{Request, Response, Status};
+
+use crate::core::error::PlacementCenterError;
+use crate::mqtt::controller::call_broker::{
+    update_cache_by_add_tenant, update_cache_by_delete_tenant, MQTTInnerCallManager,
+};
+use crate::route::apply::RaftMachineApply;
+use crate::route::data::{StorageData, StorageDataType};
+use crate::storage::mqtt::tenant::MqttTenantStorage;
+
+pub fn list_tenant_by_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<ListTenantRequest>,
+) -> Result<Response<ListTenantReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttTenantStorage::new(rocksdb_engine_handler.clone());
+
+    if !req.tenant_name.is_empty() {
+        let tenants = storage
+            .get(&req.cluster_name, &req.tenant_name)?
+            .map(|tenant| vec![tenant.encode()])
+            .unwrap_or_default();
+        return Ok(Response::new(ListTenantReply { tenants }));
+    }
+
+    let tenants = storage
+        .list(&req.cluster_name)?
+        .iter()
+        .map(|tenant| tenant.encode())
+        .collect();
+    Ok(Response::new(ListTenantReply { tenants }))
+}
+
+pub async fn create_tenant_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateTenantRequest>,
+) -> Result<Response<CreateTenantReply>, Status> {
+    let req = request.into_inner();
+
+    let tenant = match serde_json::from_slice::<MqttTenant>(&req.content) {
+        Ok(tenant) => tenant,
+        Err(e) => {
+            return Err(Status::cancelled(e.to_string()));
+        }
+    };
+
+    let data = StorageData::new(
+        StorageDataType::MqttSetTenant,
+        CreateTenantRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+
+    if let Err(e) =
+        update_cache_by_add_tenant(&req.cluster_name, call_manager, client_pool, tenant).await
+    {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(CreateTenantReply::default()))
+}
+
+pub async fn delete_tenant_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<DeleteTenantRequest>,
+) -> Result<Response<DeleteTenantReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttTenantStorage::new(rocksdb_engine_handler.clone());
+    let tenant = storage.get(&req.cluster_name, &req.tenant_name)?;
+
+    let resources = storage.list_resource(&req.cluster_name, &req.tenant_name)?;
+    if !resources.is_empty() {
+        return Err(Status::cancelled(
+            PlacementCenterError::TenantHasResources(req.tenant_name, resources.len()).to_string(),
+        ));
+    }
+
+    let data = StorageData::new(
+        StorageDataType::MqttDeleteTenant,
+        DeleteTenantRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+
+    if let Some(tenant) = tenant {
+        if let Err(e) =
+            update_cache_by_delete_tenant(&req.cluster_name, call_manager, client_pool, tenant)
+                .await
+        {
+            return Err(Status::cancelled(e.to_string()));
+        };
+    }
+    Ok(Response::new(DeleteTenantReply::default()))
+}
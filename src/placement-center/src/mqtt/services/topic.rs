@@ -14,20 +14,27 @@
 
 use crate::core::error::PlacementCenterError;
 use crate::mqtt::controller::call_broker::{
-    update_cache_by_add_topic, update_cache_by_delete_topic, MQTTInnerCallManager,
+    update_cache_by_add_rule, update_cache_by_add_topic, update_cache_by_add_wasm_plugin,
+    update_cache_by_delete_rule, update_cache_by_delete_topic, update_cache_by_delete_wasm_plugin,
+    MQTTInnerCallManager,
 };
 use crate::route::apply::RaftMachineApply;
 use crate::route::data::{StorageData, StorageDataType};
 use crate::storage::mqtt::topic::MqttTopicStorage;
 use grpc_clients::pool::ClientPool;
+use metadata_struct::mqtt::rule::MqttRule;
 use metadata_struct::mqtt::topic::MqttTopic;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 use prost::Message;
 use protocol::placement_center::placement_center_mqtt::{
-    CreateTopicReply, CreateTopicRequest, CreateTopicRewriteRuleReply,
-    CreateTopicRewriteRuleRequest, DeleteTopicReply, DeleteTopicRequest,
-    DeleteTopicRewriteRuleReply, DeleteTopicRewriteRuleRequest, ListTopicReply, ListTopicRequest,
-    ListTopicRewriteRuleReply, ListTopicRewriteRuleRequest, SaveLastWillMessageReply,
-    SaveLastWillMessageRequest, SetTopicRetainMessageReply, SetTopicRetainMessageRequest,
+    CreateRuleReply, CreateRuleRequest, CreateTopicReply, CreateTopicRequest,
+    CreateTopicRewriteRuleReply, CreateTopicRewriteRuleRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteRuleReply, DeleteRuleRequest, DeleteTopicReply,
+    DeleteTopicRequest, DeleteTopicRewriteRuleReply, DeleteTopicRewriteRuleRequest,
+    DeleteWasmPluginReply, DeleteWasmPluginRequest, ListRuleReply, ListRuleRequest, ListTopicReply,
+    ListTopicRequest, ListTopicRewriteRuleReply, ListTopicRewriteRuleRequest, ListWasmPluginReply,
+    ListWasmPluginRequest, SaveLastWillMessageReply, SaveLastWillMessageRequest,
+    SetTopicRetainMessageReply, SetTopicRetainMessageRequest,
 };
 use rocksdb_engine::RocksDBEngine;
 use std::sync::Arc;
@@ -259,3 +266,162 @@ pub fn list_topic_rewrite_rule_by_req(
         Err(e) => Err(Status::cancelled(e.to_string())),
     }
 }
+
+pub fn list_rule_by_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<ListRuleRequest>,
+) -> Result<Response<ListRuleReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttTopicStorage::new(rocksdb_engine_handler.clone());
+    match storage.list_rule(&req.cluster_name) {
+        Ok(data) => {
+            let rules = data.iter().map(|rule| rule.encode()).collect();
+            Ok(Response::new(ListRuleReply { rules }))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn create_rule_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateRuleRequest>,
+) -> Result<Response<CreateRuleReply>, Status> {
+    let req = request.into_inner();
+    let rule = match serde_json::from_slice::<MqttRule>(&req.content) {
+        Ok(rule) => rule,
+        Err(e) => {
+            return Err(Status::cancelled(e.to_string()));
+        }
+    };
+
+    let data = StorageData::new(
+        StorageDataType::MqttSetRule,
+        CreateRuleRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+
+    if let Err(e) =
+        update_cache_by_add_rule(&req.cluster_name, call_manager, client_pool, rule).await
+    {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(CreateRuleReply::default()))
+}
+
+pub async fn delete_rule_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<DeleteRuleRequest>,
+) -> Result<Response<DeleteRuleReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttTopicStorage::new(rocksdb_engine_handler.clone());
+    let rule = match storage.get_rule(&req.cluster_name, &req.rule_name) {
+        Ok(rule) => rule,
+        Err(e) => {
+            return Err(Status::cancelled(e.to_string()));
+        }
+    };
+
+    let data = StorageData::new(
+        StorageDataType::MqttDeleteRule,
+        DeleteRuleRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+
+    if let Some(rule) = rule {
+        if let Err(e) =
+            update_cache_by_delete_rule(&req.cluster_name, call_manager, client_pool, rule).await
+        {
+            return Err(Status::cancelled(e.to_string()));
+        };
+    }
+    Ok(Response::new(DeleteRuleReply::default()))
+}
+
+pub fn list_wasm_plugin_by_req(
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<ListWasmPluginRequest>,
+) -> Result<Response<ListWasmPluginReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttTopicStorage::new(rocksdb_engine_handler.clone());
+    match storage.list_wasm_plugin(&req.cluster_name) {
+        Ok(data) => {
+            let plugins = data.iter().map(|plugin| plugin.encode()).collect();
+            Ok(Response::new(ListWasmPluginReply { plugins }))
+        }
+        Err(e) => Err(Status::cancelled(e.to_string())),
+    }
+}
+
+pub async fn create_wasm_plugin_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    request: Request<CreateWasmPluginRequest>,
+) -> Result<Response<CreateWasmPluginReply>, Status> {
+    let req = request.into_inner();
+    let plugin = match serde_json::from_slice::<MqttWasmPlugin>(&req.content) {
+        Ok(plugin) => plugin,
+        Err(e) => {
+            return Err(Status::cancelled(e.to_string()));
+        }
+    };
+
+    let data = StorageData::new(
+        StorageDataType::MqttSetWasmPlugin,
+        CreateWasmPluginRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+
+    if let Err(e) =
+        update_cache_by_add_wasm_plugin(&req.cluster_name, call_manager, client_pool, plugin).await
+    {
+        return Err(Status::cancelled(e.to_string()));
+    };
+    Ok(Response::new(CreateWasmPluginReply::default()))
+}
+
+pub async fn delete_wasm_plugin_by_req(
+    raft_machine_apply: &Arc<RaftMachineApply>,
+    call_manager: &Arc<MQTTInnerCallManager>,
+    client_pool: &Arc<ClientPool>,
+    rocksdb_engine_handler: &Arc<RocksDBEngine>,
+    request: Request<DeleteWasmPluginRequest>,
+) -> Result<Response<DeleteWasmPluginReply>, Status> {
+    let req = request.into_inner();
+    let storage = MqttTopicStorage::new(rocksdb_engine_handler.clone());
+    let plugin = match storage.get_wasm_plugin(&req.cluster_name, &req.plugin_name) {
+        Ok(plugin) => plugin,
+        Err(e) => {
+            return Err(Status::cancelled(e.to_string()));
+        }
+    };
+
+    let data = StorageData::new(
+        StorageDataType::MqttDeleteWasmPlugin,
+        DeleteWasmPluginRequest::encode_to_vec(&req),
+    );
+    if let Err(e) = raft_machine_apply.client_write(data).await {
+        return Err(Status::cancelled(e.to_string()));
+    };
+
+    if let Some(plugin) = plugin {
+        if let Err(e) =
+            update_cache_by_delete_wasm_plugin(&req.cluster_name, call_manager, client_pool, plugin)
+                .await
+        {
+            return Err(Status::cancelled(e.to_string()));
+        };
+    }
+    Ok(Response::new(DeleteWasmPluginReply::default()))
+}
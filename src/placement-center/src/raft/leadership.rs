@@ -96,6 +96,7 @@ pub fn start_controller(
         cluster_cache.clone(),
         mqtt_cache.clone(),
         client_pool.clone(),
+        raft_machine_apply.clone(),
         stop_send.clone(),
     );
     tokio::spawn(async move {
@@ -87,4 +87,13 @@ pub enum StorageDataType {
     MqttDeleteConnector,
     MqttSetAutoSubscribeRule,
     MqttDeleteAutoSubscribeRule,
+    MqttSetTenant,
+    MqttDeleteTenant,
+    MqttSetRule,
+    MqttDeleteRule,
+    MqttSetWasmPlugin,
+    MqttDeleteWasmPlugin,
+    MqttSetScheduledMessage,
+    MqttDeleteScheduledMessage,
+    MqttSetScheduledMessageFireState,
 }
@@ -230,6 +230,37 @@ pub async fn route(
                     .delete_topic_rewrite_rule(storage_data.value)?;
                 Ok(None)
             }
+            StorageDataType::MqttSetRule => {
+                self.route_mqtt.create_rule(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttDeleteRule => {
+                self.route_mqtt.delete_rule(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttSetWasmPlugin => {
+                self.route_mqtt.create_wasm_plugin(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttDeleteWasmPlugin => {
+                self.route_mqtt.delete_wasm_plugin(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttSetScheduledMessage => {
+                self.route_mqtt
+                    .create_scheduled_message(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttDeleteScheduledMessage => {
+                self.route_mqtt
+                    .delete_scheduled_message(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttSetScheduledMessageFireState => {
+                self.route_mqtt
+                    .set_scheduled_message_fire_state(storage_data.value)?;
+                Ok(None)
+            }
             StorageDataType::MqttSetSubscribe => {
                 self.route_mqtt.set_subscribe(storage_data.value)?;
                 Ok(None)
@@ -258,6 +289,16 @@ pub async fn route(
                     .delete_auto_subscribe_rule(storage_data.value)?;
                 Ok(None)
             }
+
+            // tenant
+            StorageDataType::MqttSetTenant => {
+                self.route_mqtt.create_tenant(storage_data.value)?;
+                Ok(None)
+            }
+            StorageDataType::MqttDeleteTenant => {
+                self.route_mqtt.delete_tenant(storage_data.value)?;
+                Ok(None)
+            }
         }
     }
 
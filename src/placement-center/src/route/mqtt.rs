@@ -19,20 +19,28 @@
 use metadata_struct::acl::mqtt_blacklist::MqttAclBlackList;
 use metadata_struct::mqtt::auto_subscribe_rule::MqttAutoSubscribeRule;
 use metadata_struct::mqtt::bridge::connector::MQTTConnector;
+use metadata_struct::mqtt::rule::MqttRule;
+use metadata_struct::mqtt::scheduled_message::{
+    MqttScheduledMessage, MqttScheduledMessageFireState,
+};
 use metadata_struct::mqtt::session::MqttSession;
 use metadata_struct::mqtt::subscribe_data::MqttSubscribe;
+use metadata_struct::mqtt::tenant::MqttTenant;
 use metadata_struct::mqtt::topic::MqttTopic;
 use metadata_struct::mqtt::topic_rewrite_rule::MqttTopicRewriteRule;
 use metadata_struct::mqtt::user::MqttUser;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 use prost::Message as _;
 use protocol::mqtt::common::{qos, retain_forward_rule, Error, QoS, RetainForwardRule};
 use protocol::placement_center::placement_center_mqtt::{
-    CreateAclRequest, CreateBlacklistRequest, CreateConnectorRequest, CreateSessionRequest,
-    CreateTopicRequest, CreateTopicRewriteRuleRequest, CreateUserRequest, DeleteAclRequest,
+    CreateAclRequest, CreateBlacklistRequest, CreateConnectorRequest, CreateRuleRequest,
+    CreateScheduledMessageRequest, CreateSessionRequest, CreateTenantRequest, CreateTopicRequest,
+    CreateTopicRewriteRuleRequest, CreateUserRequest, CreateWasmPluginRequest, DeleteAclRequest,
     DeleteAutoSubscribeRuleRequest, DeleteBlacklistRequest, DeleteConnectorRequest,
-    DeleteSessionRequest, DeleteSubscribeRequest, DeleteTopicRequest,
-    DeleteTopicRewriteRuleRequest, DeleteUserRequest, SaveLastWillMessageRequest,
-    SetAutoSubscribeRuleRequest, SetSubscribeRequest, UpdateSessionRequest,
+    DeleteRuleRequest, DeleteScheduledMessageRequest, DeleteSessionRequest, DeleteSubscribeRequest,
+    DeleteTenantRequest, DeleteTopicRequest, DeleteTopicRewriteRuleRequest, DeleteUserRequest,
+    DeleteWasmPluginRequest, SaveLastWillMessageRequest, SetAutoSubscribeRuleRequest,
+    SetSubscribeRequest, UpdateSessionRequest,
 };
 
 use crate::core::error::PlacementCenterError;
@@ -41,8 +49,10 @@
 use crate::storage::mqtt::blacklist::MqttBlackListStorage;
 use crate::storage::mqtt::connector::MqttConnectorStorage;
 use crate::storage::mqtt::lastwill::MqttLastWillStorage;
+use crate::storage::mqtt::scheduled_message::MqttScheduledMessageStorage;
 use crate::storage::mqtt::session::MqttSessionStorage;
 use crate::storage::mqtt::subscribe::MqttSubscribeStorage;
+use crate::storage::mqtt::tenant::MqttTenantStorage;
 use crate::storage::mqtt::topic::MqttTopicStorage;
 use crate::storage::mqtt::user::MqttUserStorage;
 use crate::storage::rocksdb::RocksDBEngine;
@@ -82,6 +92,22 @@ pub fn delete_user(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
         Ok(())
     }
 
+    // Tenant
+    pub fn create_tenant(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = CreateTenantRequest::decode(value.as_ref())?;
+        let storage = MqttTenantStorage::new(self.rocksdb_engine_handler.clone());
+        let tenant = serde_json::from_slice::<MqttTenant>(&req.content)?;
+        storage.save(&req.cluster_name, &req.tenant_name, tenant)?;
+        Ok(())
+    }
+
+    pub fn delete_tenant(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = DeleteTenantRequest::decode(value.as_ref())?;
+        let storage = MqttTenantStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete(&req.cluster_name, &req.tenant_name)?;
+        Ok(())
+    }
+
     // Topic
     pub fn create_topic(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
         let req = CreateTopicRequest::decode(value.as_ref())?;
@@ -186,6 +212,67 @@ pub fn delete_topic_rewrite_rule(&self, value: Vec<u8>) -> Result<(), PlacementC
         storage.delete_topic_rewrite_rule(&req.cluster_name, &req.action, &req.source_topic)
     }
 
+    // Rule
+    pub fn create_rule(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = CreateRuleRequest::decode(value.as_ref())?;
+        let storage = MqttTopicStorage::new(self.rocksdb_engine_handler.clone());
+        let rule = serde_json::from_slice::<MqttRule>(&req.content)?;
+        storage.save_rule(&req.cluster_name, &req.rule_name, rule)
+    }
+
+    pub fn delete_rule(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = DeleteRuleRequest::decode(value.as_ref())?;
+        let storage = MqttTopicStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete_rule(&req.cluster_name, &req.rule_name)
+    }
+
+    // Wasm Plugin
+    pub fn create_wasm_plugin(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = CreateWasmPluginRequest::decode(value.as_ref())?;
+        let storage = MqttTopicStorage::new(self.rocksdb_engine_handler.clone());
+        let plugin = serde_json::from_slice::<MqttWasmPlugin>(&req.content)?;
+        storage.save_wasm_plugin(&req.cluster_name, &req.plugin_name, plugin)
+    }
+
+    pub fn delete_wasm_plugin(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = DeleteWasmPluginRequest::decode(value.as_ref())?;
+        let storage = MqttTopicStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete_wasm_plugin(&req.cluster_name, &req.plugin_name)
+    }
+
+    // Scheduled Message
+    pub fn create_scheduled_message(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = CreateScheduledMessageRequest::decode(value.as_ref())?;
+        let storage = MqttScheduledMessageStorage::new(self.rocksdb_engine_handler.clone());
+        let message = serde_json::from_slice::<MqttScheduledMessage>(&req.content)?;
+        storage.save(&req.cluster_name, &req.name, message)?;
+        Ok(())
+    }
+
+    pub fn delete_scheduled_message(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
+        let req = DeleteScheduledMessageRequest::decode(value.as_ref())?;
+        let storage = MqttScheduledMessageStorage::new(self.rocksdb_engine_handler.clone());
+        storage.delete(&req.cluster_name, &req.name)?;
+        Ok(())
+    }
+
+    // A scheduled message deleted after the tick was fired is not created again.
+    pub fn set_scheduled_message_fire_state(
+        &self,
+        value: Vec<u8>,
+    ) -> Result<(), PlacementCenterError> {
+        let state = serde_json::from_slice::<MqttScheduledMessageFireState>(&value)?;
+        let storage = MqttScheduledMessageStorage::new(self.rocksdb_engine_handler.clone());
+        let Some(mut message) = storage.get(&state.cluster_name, &state.name)? else {
+            return Ok(());
+        };
+        message.last_fire_time = state.last_fire_time;
+        message.fire_count = state.fire_count;
+        message.delivered_fire_time = state.delivered_fire_time;
+        storage.save(&state.cluster_name, &state.name, message)?;
+        Ok(())
+    }
+
     // Subscribe
     pub fn set_subscribe(&self, value: Vec<u8>) -> Result<(), PlacementCenterError> {
         let storage = MqttSubscribeStorage::new(self.rocksdb_engine_handler.clone());
@@ -291,6 +378,7 @@ pub fn set_auto_subscribe_rule(&self, value: Vec<u8>) -> Result<(), PlacementCen
             retained_handling: _retained_handling.ok_or(PlacementCenterError::CommonError(
                 Error::InvalidRetainForwardRule(req.retained_handling as u8).to_string(),
             ))?,
+            tenant: req.tenant.clone(),
         };
         storage.save_auto_subscribe_rule(&req.cluster_name, &req.topic, auto_subscribe_rule)
     }
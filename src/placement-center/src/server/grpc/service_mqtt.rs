@@ -23,6 +23,9 @@
     connector_heartbeat_by_req, create_connector_by_req, delete_connector_by_req,
     list_connectors_by_req, update_connector_by_req,
 };
+use crate::mqtt::services::scheduled_message::{
+    create_scheduled_message_by_req, delete_scheduled_message_by_req, list_scheduled_message_by_req,
+};
 use crate::mqtt::services::session::{
     create_session_by_req, delete_session_by_req, list_session_by_req, update_session_by_req,
 };
@@ -31,9 +34,14 @@
     delete_auto_subscribe_rule_by_req, delete_subscribe_by_req, list_auto_subscribe_rule_by_req,
     list_subscribe_by_req, set_auto_subscribe_rule_by_req, set_subscribe_by_req,
 };
+use crate::mqtt::services::tenant::{
+    create_tenant_by_req, delete_tenant_by_req, list_tenant_by_req,
+};
 use crate::mqtt::services::topic::{
-    create_topic_by_req, create_topic_rewrite_rule_by_req, delete_topic_by_req,
-    delete_topic_rewrite_rule_by_req, list_topic_by_req, list_topic_rewrite_rule_by_req,
+    create_rule_by_req, create_topic_by_req, create_topic_rewrite_rule_by_req,
+    create_wasm_plugin_by_req, delete_rule_by_req, delete_topic_by_req,
+    delete_topic_rewrite_rule_by_req, delete_wasm_plugin_by_req, list_rule_by_req,
+    list_topic_by_req, list_topic_rewrite_rule_by_req, list_wasm_plugin_by_req,
     save_last_will_message_by_req, set_topic_retain_message_by_req,
 };
 use crate::mqtt::services::user::{create_user_by_req, delete_user_by_req, list_user_by_req};
@@ -44,18 +52,24 @@
 use protocol::placement_center::placement_center_mqtt::{
     ConnectorHeartbeatReply, ConnectorHeartbeatRequest, CreateAclReply, CreateAclRequest,
     CreateBlacklistReply, CreateBlacklistRequest, CreateConnectorReply, CreateConnectorRequest,
-    CreateSessionReply, CreateSessionRequest, CreateTopicReply, CreateTopicRequest,
-    CreateTopicRewriteRuleReply, CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest,
-    DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply, DeleteAutoSubscribeRuleRequest,
-    DeleteBlacklistReply, DeleteBlacklistRequest, DeleteConnectorReply, DeleteConnectorRequest,
-    DeleteSessionReply, DeleteSessionRequest, DeleteSubscribeReply, DeleteSubscribeRequest,
-    DeleteTopicReply, DeleteTopicRequest, DeleteTopicRewriteRuleReply,
-    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, GetShareSubLeaderReply,
-    GetShareSubLeaderRequest, ListAclReply, ListAclRequest, ListAutoSubscribeRuleReply,
-    ListAutoSubscribeRuleRequest, ListBlacklistReply, ListBlacklistRequest, ListConnectorReply,
-    ListConnectorRequest, ListSessionReply, ListSessionRequest, ListSubscribeReply,
-    ListSubscribeRequest, ListTopicReply, ListTopicRequest, ListTopicRewriteRuleReply,
-    ListTopicRewriteRuleRequest, ListUserReply, ListUserRequest, SaveLastWillMessageReply,
+    CreateRuleReply, CreateRuleRequest, CreateScheduledMessageReply, CreateScheduledMessageRequest,
+    CreateSessionReply, CreateSessionRequest, CreateTenantReply, CreateTenantRequest,
+    CreateTopicReply, CreateTopicRequest, CreateTopicRewriteRuleReply,
+    CreateTopicRewriteRuleRequest, CreateUserReply, CreateUserRequest, CreateWasmPluginReply,
+    CreateWasmPluginRequest, DeleteAclReply, DeleteAclRequest, DeleteAutoSubscribeRuleReply,
+    DeleteAutoSubscribeRuleRequest, DeleteBlacklistReply, DeleteBlacklistRequest,
+    DeleteConnectorReply, DeleteConnectorRequest, DeleteRuleReply, DeleteRuleRequest,
+    DeleteScheduledMessageReply, DeleteScheduledMessageRequest, DeleteSessionReply,
+    DeleteSessionRequest, DeleteSubscribeReply, DeleteSubscribeRequest, DeleteTenantReply,
+    DeleteTenantRequest, DeleteTopicReply, DeleteTopicRequest, DeleteTopicRewriteRuleReply,
+    DeleteTopicRewriteRuleRequest, DeleteUserReply, DeleteUserRequest, DeleteWasmPluginReply,
+    DeleteWasmPluginRequest, GetShareSubLeaderReply, GetShareSubLeaderRequest, ListAclReply,
+    ListAclRequest, ListAutoSubscribeRuleReply, ListAutoSubscribeRuleRequest, ListBlacklistReply,
+    ListBlacklistRequest, ListConnectorReply, ListConnectorRequest, ListRuleReply, ListRuleRequest,
+    ListScheduledMessageReply, ListScheduledMessageRequest, ListSessionReply, ListSessionRequest,
+    ListSubscribeReply, ListSubscribeRequest, ListTenantReply, ListTenantRequest, ListTopicReply,
+    ListTopicRequest, ListTopicRewriteRuleReply, ListTopicRewriteRuleRequest, ListUserReply,
+    ListUserRequest, ListWasmPluginReply, ListWasmPluginRequest, SaveLastWillMessageReply,
     SaveLastWillMessageRequest, SetAutoSubscribeRuleReply, SetAutoSubscribeRuleRequest,
     SetSubscribeReply, SetSubscribeRequest, SetTopicRetainMessageReply,
     SetTopicRetainMessageRequest, UpdateConnectorReply, UpdateConnectorRequest, UpdateSessionReply,
@@ -130,6 +144,63 @@ async fn delete_user(
         .await
     }
 
+    // Tenant
+    async fn list_tenant(
+        &self,
+        request: Request<ListTenantRequest>,
+    ) -> Result<Response<ListTenantReply>, Status> {
+        list_tenant_by_req(&self.rocksdb_engine_handler, request)
+    }
+
+    async fn create_tenant(
+        &self,
+        request: Request<CreateTenantRequest>,
+    ) -> Result<Response<CreateTenantReply>, Status> {
+        create_tenant_by_req(
+            &self.raft_machine_apply,
+            &self.mqtt_call_manager,
+            &self.client_pool,
+            request,
+        )
+        .await
+    }
+
+    async fn delete_tenant(
+        &self,
+        request: Request<DeleteTenantRequest>,
+    ) -> Result<Response<DeleteTenantReply>, Status> {
+        delete_tenant_by_req(
+            &self.raft_machine_apply,
+            &self.mqtt_call_manager,
+            &self.client_pool,
+            &self.rocksdb_engine_handler,
+            request,
+        )
+        .await
+    }
+
+    // Scheduled Message
+    async fn list_scheduled_message(
+        &self,
+        request: Request<ListScheduledMessageRequest>,
+    ) -> Result<Response<ListScheduledMessageReply>, Status> {
+        list_scheduled_message_by_req(&self.rocksdb_engine_handler, request)
+    }
+
+    async fn create_scheduled_message(
+        &self,
+        request: Request<CreateScheduledMessageRequest>,
+    ) -> Result<Response<CreateScheduledMessageReply>, Status> {
+        create_scheduled_message_by_req(&self.raft_machine_apply, request).await
+    }
+
+    async fn delete_scheduled_message(
+        &self,
+        request: Request<DeleteScheduledMessageRequest>,
+    ) -> Result<Response<DeleteScheduledMessageReply>, Status> {
+        delete_scheduled_message_by_req(&self.raft_machine_apply, request).await
+    }
+
     // Session
     async fn list_session(
         &self,
@@ -308,6 +379,76 @@ async fn list_topic_rewrite_rule(
         list_topic_rewrite_rule_by_req(&self.rocksdb_engine_handler, request)
     }
 
+    // Rule
+    async fn list_rule(
+        &self,
+        request: Request<ListRuleRequest>,
+    ) -> Result<Response<ListRuleReply>, Status> {
+        list_rule_by_req(&self.rocksdb_engine_handler, request)
+    }
+
+    async fn create_rule(
+        &self,
+        request: Request<CreateRuleRequest>,
+    ) -> Result<Response<CreateRuleReply>, Status> {
+        create_rule_by_req(
+            &self.raft_machine_apply,
+            &self.mqtt_call_manager,
+            &self.client_pool,
+            request,
+        )
+        .await
+    }
+
+    async fn delete_rule(
+        &self,
+        request: Request<DeleteRuleRequest>,
+    ) -> Result<Response<DeleteRuleReply>, Status> {
+        delete_rule_by_req(
+            &self.raft_machine_apply,
+            &self.mqtt_call_manager,
+            &self.client_pool,
+            &self.rocksdb_engine_handler,
+            request,
+        )
+        .await
+    }
+
+    // Wasm Plugin
+    async fn list_wasm_plugin(
+        &self,
+        request: Request<ListWasmPluginRequest>,
+    ) -> Result<Response<ListWasmPluginReply>, Status> {
+        list_wasm_plugin_by_req(&self.rocksdb_engine_handler, request)
+    }
+
+    async fn create_wasm_plugin(
+        &self,
+        request: Request<CreateWasmPluginRequest>,
+    ) -> Result<Response<CreateWasmPluginReply>, Status> {
+        create_wasm_plugin_by_req(
+            &self.raft_machine_apply,
+            &self.mqtt_call_manager,
+            &self.client_pool,
+            request,
+        )
+        .await
+    }
+
+    async fn delete_wasm_plugin(
+        &self,
+        request: Request<DeleteWasmPluginRequest>,
+    ) -> Result<Response<DeleteWasmPluginReply>, Status> {
+        delete_wasm_plugin_by_req(
+            &self.raft_machine_apply,
+            &self.mqtt_call_manager,
+            &self.client_pool,
+            &self.rocksdb_engine_handler,
+            request,
+        )
+        .await
+    }
+
     // Subscribe
     async fn list_subscribe(
         &self,
@@ -151,6 +151,30 @@ pub fn storage_key_mqtt_user_cluster_prefix(cluster_name: &str) -> String {
     format!("/mqtt/user/{}/", cluster_name)
 }
 
+pub fn storage_key_mqtt_tenant(cluster_name: &str, tenant_name: &str) -> String {
+    format!("/mqtt/tenant/{}/{}", cluster_name, tenant_name)
+}
+
+pub fn storage_key_mqtt_tenant_cluster_prefix(cluster_name: &str) -> String {
+    format!("/mqtt/tenant/{}/", cluster_name)
+}
+
+pub fn storage_key_mqtt_tenant_resource(
+    cluster_name: &str,
+    tenant_name: &str,
+    resource_type: &str,
+    resource_name: &str,
+) -> String {
+    format!(
+        "/mqtt/tenant_resource/{}/{}/{}/{}",
+        cluster_name, tenant_name, resource_type, resource_name
+    )
+}
+
+pub fn storage_key_mqtt_tenant_resource_prefix(cluster_name: &str, tenant_name: &str) -> String {
+    format!("/mqtt/tenant_resource/{}/{}/", cluster_name, tenant_name)
+}
+
 pub fn storage_key_mqtt_topic(cluster_name: &str, user_name: &str) -> String {
     format!("/mqtt/topic/{}/{}", cluster_name, user_name)
 }
@@ -273,6 +297,30 @@ pub fn storage_key_mqtt_topic_rewrite_rule_prefix(cluster_name: &str) -> String
     format!("/mqtt/topic_rewrite_rule/{}/", cluster_name)
 }
 
+pub fn storage_key_mqtt_rule(cluster_name: &str, rule_name: &str) -> String {
+    format!("/mqtt/rule/{}/{}", cluster_name, rule_name)
+}
+
+pub fn storage_key_mqtt_rule_prefix(cluster_name: &str) -> String {
+    format!("/mqtt/rule/{}/", cluster_name)
+}
+
+pub fn storage_key_mqtt_wasm_plugin(cluster_name: &str, plugin_name: &str) -> String {
+    format!("/mqtt/wasm_plugin/{}/{}", cluster_name, plugin_name)
+}
+
+pub fn storage_key_mqtt_wasm_plugin_prefix(cluster_name: &str) -> String {
+    format!("/mqtt/wasm_plugin/{}/", cluster_name)
+}
+
+pub fn storage_key_mqtt_scheduled_message(cluster_name: &str, name: &str) -> String {
+    format!("/mqtt/scheduled_message/{}/{}", cluster_name, name)
+}
+
+pub fn storage_key_mqtt_scheduled_message_prefix(cluster_name: &str) -> String {
+    format!("/mqtt/scheduled_message/{}/", cluster_name)
+}
+
 pub fn storage_key_mqtt_auto_subscribe_rule(cluster_name: &str, topic: &str) -> String {
     format!("/mqtt/auto_subscribe_rule/{}/{}", cluster_name, topic)
 }
@@ -16,11 +16,13 @@
 
 use common_base::error::common::CommonError;
 use metadata_struct::acl::mqtt_acl::MqttAcl;
+use metadata_struct::mqtt::tenant::tenant_scoped_name;
 
 use crate::storage::engine::{
     engine_get_by_cluster, engine_prefix_list_by_cluster, engine_save_by_cluster,
 };
 use crate::storage::keys::{storage_key_mqtt_acl, storage_key_mqtt_acl_prefix};
+use crate::storage::mqtt::tenant::{MqttTenantStorage, TENANT_RESOURCE_ACL};
 use crate::storage::rocksdb::RocksDBEngine;
 
 pub struct AclStorage {
@@ -37,6 +39,7 @@ pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
     pub fn save(&self, cluster_name: &str, acl: MqttAcl) -> Result<(), CommonError> {
         let mut acl_list = self.get(
             cluster_name,
+            &acl.tenant,
             &acl.resource_type.to_string(),
             &acl.resource_name,
         )?;
@@ -46,11 +49,12 @@ pub fn save(&self, cluster_name: &str, acl: MqttAcl) -> Result<(), CommonError>
         }
 
         acl_list.push(acl.clone());
+        self.save_tenant_resource(cluster_name, &acl)?;
 
         let key = storage_key_mqtt_acl(
             cluster_name,
             &acl.resource_type.to_string(),
-            &acl.resource_name,
+            &tenant_scoped_name(&acl.tenant, &acl.resource_name),
         );
         engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, acl_list)
     }
@@ -69,6 +73,7 @@ pub fn list(&self, cluster_name: &str) -> Result<Vec<MqttAcl>, CommonError> {
     pub fn delete(&self, cluster_name: &str, delete_acl: &MqttAcl) -> Result<(), CommonError> {
         let acl_list = self.get(
             cluster_name,
+            &delete_acl.tenant,
             &delete_acl.resource_type.to_string(),
             &delete_acl.resource_name,
         )?;
@@ -87,27 +92,58 @@ pub fn delete(&self, cluster_name: &str, delete_acl: &MqttAcl) -> Result<(), Com
                 new_acl_list.push(raw);
             }
         }
+
+        // The tenant keeps the acls of the resource as long as one of them is left
+        if new_acl_list.is_empty() {
+            MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).delete_resource(
+                cluster_name,
+                &delete_acl.tenant,
+                TENANT_RESOURCE_ACL,
+                &Self::tenant_resource_name(delete_acl),
+            )?;
+        }
+
         let key = storage_key_mqtt_acl(
             cluster_name,
             &delete_acl.resource_type.to_string(),
-            &delete_acl.resource_name,
+            &tenant_scoped_name(&delete_acl.tenant, &delete_acl.resource_name),
         );
         engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, new_acl_list)
     }
 
+    // The acls of a resource are kept per tenant, the same resource name in two tenants is two
+    // different resources.
     pub fn get(
         &self,
         cluster_name: &str,
+        tenant: &str,
         resource_type: &str,
         resource_name: &str,
     ) -> Result<Vec<MqttAcl>, CommonError> {
-        let key = storage_key_mqtt_acl(cluster_name, resource_type, resource_name);
+        let key = storage_key_mqtt_acl(
+            cluster_name,
+            resource_type,
+            &tenant_scoped_name(tenant, resource_name),
+        );
         if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
             return Ok(serde_json::from_str::<Vec<MqttAcl>>(&data.data)?);
         }
         Ok(Vec::new())
     }
 
+    fn save_tenant_resource(&self, cluster_name: &str, acl: &MqttAcl) -> Result<(), CommonError> {
+        MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).save_resource(
+            cluster_name,
+            &acl.tenant,
+            TENANT_RESOURCE_ACL,
+            &Self::tenant_resource_name(acl),
+        )
+    }
+
+    fn tenant_resource_name(acl: &MqttAcl) -> String {
+        format!("{}/{}", acl.resource_type, acl.resource_name)
+    }
+
     fn acl_exists(&self, acl_list: &[MqttAcl], acl: &MqttAcl) -> bool {
         for raw in acl_list {
             if raw.permission == acl.permission
@@ -163,6 +199,7 @@ async fn acl_storage_test() {
             ip: ip.clone(),
             action: action.clone(),
             permission: permission.clone(),
+            tenant: "".to_string(),
         };
 
         acl_storage.save(&cluster_name, acl.clone()).unwrap();
@@ -180,6 +217,7 @@ async fn acl_storage_test() {
             ip: "localhost2".to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
 
         acl_storage.save(&cluster_name, acl2.clone()).unwrap();
@@ -190,6 +228,7 @@ async fn acl_storage_test() {
         let res = acl_storage
             .get(
                 &cluster_name,
+                "",
                 resource_type.to_string().as_str(),
                 &resource_name,
             )
@@ -202,6 +241,7 @@ async fn acl_storage_test() {
         let res = acl_storage
             .get(
                 &cluster_name,
+                "",
                 resource_type.to_string().as_str(),
                 &resource_name,
             )
@@ -216,6 +256,7 @@ async fn acl_storage_test() {
         let res = acl_storage
             .get(
                 &cluster_name,
+                "",
                 resource_type.to_string().as_str(),
                 "test_resource2",
             )
@@ -226,4 +267,49 @@ async fn acl_storage_test() {
         let res = acl_storage.list(&cluster_name).unwrap();
         assert_eq!(res.len(), 0);
     }
+
+    #[tokio::test]
+    async fn acl_storage_tenant_test() {
+        let config = placement_center_test_conf();
+        let rs = Arc::new(RocksDBEngine::new(
+            &test_temp_dir(),
+            config.rocksdb.max_open_files.unwrap(),
+            column_family_list(),
+        ));
+        let acl_storage = AclStorage::new(rs);
+        let cluster_name = "test_cluster".to_string();
+
+        let acl_a = MqttAcl {
+            resource_type: MqttAclResourceType::User,
+            resource_name: "admin".to_string(),
+            topic: "test_topic".to_string(),
+            ip: "*".to_string(),
+            action: MqttAclAction::Publish,
+            permission: MqttAclPermission::Deny,
+            tenant: "tenant_a".to_string(),
+        };
+        let acl_b = MqttAcl {
+            tenant: "tenant_b".to_string(),
+            ..acl_a.clone()
+        };
+        acl_storage.save(&cluster_name, acl_a.clone()).unwrap();
+        acl_storage.save(&cluster_name, acl_b.clone()).unwrap();
+
+        // the same user name in two tenants keeps two acl lists
+        assert_eq!(acl_storage.list(&cluster_name).unwrap().len(), 2);
+        let res = acl_storage
+            .get(&cluster_name, "tenant_a", "User", "admin")
+            .unwrap();
+        assert_eq!(res, vec![acl_a.clone()]);
+
+        acl_storage.delete(&cluster_name, &acl_a).unwrap();
+        let res = acl_storage
+            .get(&cluster_name, "tenant_b", "User", "admin")
+            .unwrap();
+        assert_eq!(res, vec![acl_b]);
+        assert!(acl_storage
+            .get(&cluster_name, "tenant_a", "User", "admin")
+            .unwrap()
+            .is_empty());
+    }
 }
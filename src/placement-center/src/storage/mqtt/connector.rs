@@ -34,6 +34,7 @@
     engine_save_by_cluster,
 };
 use crate::storage::keys::{storage_key_mqtt_connector, storage_key_mqtt_connector_prefix};
+use crate::storage::mqtt::tenant::{MqttTenantStorage, TENANT_RESOURCE_CONNECTOR};
 use crate::storage::rocksdb::RocksDBEngine;
 
 pub struct MqttConnectorStorage {
@@ -52,6 +53,16 @@ pub fn save(
         connector_name: &str,
         connector: &MQTTConnector,
     ) -> Result<(), CommonError> {
+        let old_tenant = self
+            .get(cluster_name, connector_name)?
+            .map(|connector| connector.tenant);
+        MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).update_resource(
+            cluster_name,
+            old_tenant.as_deref(),
+            &connector.tenant,
+            TENANT_RESOURCE_CONNECTOR,
+            connector_name,
+        )?;
         let key = storage_key_mqtt_connector(cluster_name, connector_name);
         engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, connector)
     }
@@ -80,6 +91,14 @@ pub fn get(
     }
 
     pub fn delete(&self, cluster_name: &str, connector_name: &str) -> Result<(), CommonError> {
+        if let Some(connector) = self.get(cluster_name, connector_name)? {
+            MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).delete_resource(
+                cluster_name,
+                &connector.tenant,
+                TENANT_RESOURCE_CONNECTOR,
+                connector_name,
+            )?;
+        }
         let key = storage_key_mqtt_connector(cluster_name, connector_name);
         engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)
     }
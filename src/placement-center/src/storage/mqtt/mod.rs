@@ -28,7 +28,9 @@
 pub mod blacklist;
 pub mod connector;
 pub mod lastwill;
+pub mod scheduled_message;
 pub mod session;
 pub mod subscribe;
+pub mod tenant;
 pub mod topic;
 pub mod user;
@@ -0,0 +1,123 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+
+use crate::storage::engine::{
+    engine_delete_by_cluster, engine_get_by_cluster, engine_prefix_list_by_cluster,
+    engine_save_by_cluster,
+};
+use crate::storage::keys::{
+    storage_key_mqtt_scheduled_message, storage_key_mqtt_scheduled_message_prefix,
+};
+use crate::storage::rocksdb::RocksDBEngine;
+
+pub struct MqttScheduledMessageStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl MqttScheduledMessageStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        MqttScheduledMessageStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(
+        &self,
+        cluster_name: &str,
+        name: &str,
+        message: MqttScheduledMessage,
+    ) -> Result<(), CommonError> {
+        let key = storage_key_mqtt_scheduled_message(cluster_name, name);
+        engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, message)
+    }
+
+    pub fn list(&self, cluster_name: &str) -> Result<Vec<MqttScheduledMessage>, CommonError> {
+        let prefix_key = storage_key_mqtt_scheduled_message_prefix(cluster_name);
+        let data = engine_prefix_list_by_cluster(self.rocksdb_engine_handler.clone(), prefix_key)?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_str::<MqttScheduledMessage>(&raw.data)?);
+        }
+        Ok(results)
+    }
+
+    pub fn get(
+        &self,
+        cluster_name: &str,
+        name: &str,
+    ) -> Result<Option<MqttScheduledMessage>, CommonError> {
+        let key = storage_key_mqtt_scheduled_message(cluster_name, name);
+        if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            return Ok(Some(serde_json::from_str::<MqttScheduledMessage>(
+                &data.data,
+            )?));
+        }
+        Ok(None)
+    }
+
+    pub fn delete(&self, cluster_name: &str, name: &str) -> Result<(), CommonError> {
+        let key = storage_key_mqtt_scheduled_message(cluster_name, name);
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_base::config::placement_center::placement_center_test_conf;
+    use common_base::utils::file_utils::test_temp_dir;
+    use metadata_struct::mqtt::scheduled_message::MqttScheduledMessage;
+
+    use crate::storage::mqtt::scheduled_message::MqttScheduledMessageStorage;
+    use crate::storage::rocksdb::{column_family_list, RocksDBEngine};
+
+    #[tokio::test]
+    async fn scheduled_message_storage_test() {
+        let config = placement_center_test_conf();
+
+        let rs = Arc::new(RocksDBEngine::new(
+            &test_temp_dir(),
+            config.rocksdb.max_open_files.unwrap(),
+            column_family_list(),
+        ));
+        let storage = MqttScheduledMessageStorage::new(rs);
+        let cluster_name = "test_cluster".to_string();
+
+        for name in ["daily-config", "heartbeat"] {
+            let message = MqttScheduledMessage {
+                name: name.to_string(),
+                topic: "/config".to_string(),
+                interval_sec: 60,
+                ..Default::default()
+            };
+            storage.save(&cluster_name, name, message).unwrap();
+        }
+
+        let res = storage.list(&cluster_name).unwrap();
+        assert_eq!(res.len(), 2);
+
+        let res = storage.get(&cluster_name, "heartbeat").unwrap();
+        assert_eq!(res.unwrap().interval_sec, 60);
+
+        storage.delete(&cluster_name, "heartbeat").unwrap();
+        let res = storage.get(&cluster_name, "heartbeat").unwrap();
+        assert!(res.is_none());
+    }
+}
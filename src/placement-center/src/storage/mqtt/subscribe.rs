@@ -40,6 +40,7 @@
     storage_key_mqtt_subscribe, storage_key_mqtt_subscribe_client_id_prefix,
     storage_key_mqtt_subscribe_cluster_prefix,
 };
+use crate::storage::mqtt::tenant::{MqttTenantStorage, TENANT_RESOURCE_AUTO_SUBSCRIBE_RULE};
 use crate::storage::rocksdb::RocksDBEngine;
 
 pub struct MqttSubscribeStorage {
@@ -134,6 +135,16 @@ pub fn save_auto_subscribe_rule(
         topic: &str,
         auto_subscribe_rule: MqttAutoSubscribeRule,
     ) -> Result<(), PlacementCenterError> {
+        let old_tenant = self
+            .get_auto_subscribe_rule(cluster_name, topic)?
+            .map(|rule| rule.tenant);
+        MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).update_resource(
+            cluster_name,
+            old_tenant.as_deref(),
+            &auto_subscribe_rule.tenant,
+            TENANT_RESOURCE_AUTO_SUBSCRIBE_RULE,
+            topic,
+        )?;
         let key = storage_key_mqtt_auto_subscribe_rule(cluster_name, topic);
         engine_save_by_cluster(
             self.rocksdb_engine_handler.clone(),
@@ -143,11 +154,33 @@ pub fn save_auto_subscribe_rule(
         Ok(())
     }
 
+    pub fn get_auto_subscribe_rule(
+        &self,
+        cluster_name: &str,
+        topic: &str,
+    ) -> Result<Option<MqttAutoSubscribeRule>, PlacementCenterError> {
+        let key = storage_key_mqtt_auto_subscribe_rule(cluster_name, topic);
+        if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            return Ok(Some(serde_json::from_str::<MqttAutoSubscribeRule>(
+                &data.data,
+            )?));
+        }
+        Ok(None)
+    }
+
     pub fn delete_auto_subscribe_rule(
         &self,
         cluster_name: &str,
         topic: &str,
     ) -> Result<(), PlacementCenterError> {
+        if let Some(rule) = self.get_auto_subscribe_rule(cluster_name, topic)? {
+            MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).delete_resource(
+                cluster_name,
+                &rule.tenant,
+                TENANT_RESOURCE_AUTO_SUBSCRIBE_RULE,
+                topic,
+            )?;
+        }
         let key = storage_key_mqtt_auto_subscribe_rule(cluster_name, topic);
         engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)?;
         Ok(())
@@ -279,6 +312,7 @@ async fn auto_subscribe_rule_storage_basic_ops() {
             no_local: true,
             retain_as_published: false,
             retained_handling: RetainForwardRule::OnEverySubscribe,
+            tenant: "".to_string(),
         };
 
         storage
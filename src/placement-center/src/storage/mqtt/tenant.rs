@@ -0,0 +1,274 @@
+// Copyright 2023 RobustMQ Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_base::error::common::CommonError;
+use metadata_struct::mqtt::tenant::{MqttTenant, MqttTenantResource};
+
+use crate::storage::engine::{
+    engine_delete_by_cluster, engine_get_by_cluster, engine_prefix_list_by_cluster,
+    engine_save_by_cluster,
+};
+use crate::storage::keys::{
+    storage_key_mqtt_tenant, storage_key_mqtt_tenant_cluster_prefix,
+    storage_key_mqtt_tenant_resource, storage_key_mqtt_tenant_resource_prefix,
+};
+use crate::storage::rocksdb::RocksDBEngine;
+
+pub const TENANT_RESOURCE_USER: &str = "user";
+pub const TENANT_RESOURCE_ACL: &str = "acl";
+pub const TENANT_RESOURCE_TOPIC: &str = "topic";
+pub const TENANT_RESOURCE_SCHEMA: &str = "schema";
+pub const TENANT_RESOURCE_CONNECTOR: &str = "connector";
+pub const TENANT_RESOURCE_AUTO_SUBSCRIBE_RULE: &str = "auto_subscribe_rule";
+
+pub struct MqttTenantStorage {
+    rocksdb_engine_handler: Arc<RocksDBEngine>,
+}
+
+impl MqttTenantStorage {
+    pub fn new(rocksdb_engine_handler: Arc<RocksDBEngine>) -> Self {
+        MqttTenantStorage {
+            rocksdb_engine_handler,
+        }
+    }
+
+    pub fn save(
+        &self,
+        cluster_name: &str,
+        tenant_name: &str,
+        tenant: MqttTenant,
+    ) -> Result<(), CommonError> {
+        let key = storage_key_mqtt_tenant(cluster_name, tenant_name);
+        engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, tenant)
+    }
+
+    pub fn list(&self, cluster_name: &str) -> Result<Vec<MqttTenant>, CommonError> {
+        let prefix_key = storage_key_mqtt_tenant_cluster_prefix(cluster_name);
+        let data = engine_prefix_list_by_cluster(self.rocksdb_engine_handler.clone(), prefix_key)?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_str::<MqttTenant>(&raw.data)?);
+        }
+        Ok(results)
+    }
+
+    pub fn get(
+        &self,
+        cluster_name: &str,
+        tenant_name: &str,
+    ) -> Result<Option<MqttTenant>, CommonError> {
+        let key = storage_key_mqtt_tenant(cluster_name, tenant_name);
+        if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            return Ok(Some(serde_json::from_str::<MqttTenant>(&data.data)?));
+        }
+        Ok(None)
+    }
+
+    pub fn delete(&self, cluster_name: &str, tenant_name: &str) -> Result<(), CommonError> {
+        let key = storage_key_mqtt_tenant(cluster_name, tenant_name);
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)
+    }
+
+    // Records that the resource belongs to the tenant, the resources of the default tenant are
+    // not recorded.
+    pub fn save_resource(
+        &self,
+        cluster_name: &str,
+        tenant_name: &str,
+        resource_type: &str,
+        resource_name: &str,
+    ) -> Result<(), CommonError> {
+        if tenant_name.is_empty() {
+            return Ok(());
+        }
+        let key = storage_key_mqtt_tenant_resource(
+            cluster_name,
+            tenant_name,
+            resource_type,
+            resource_name,
+        );
+        let resource = MqttTenantResource {
+            resource_type: resource_type.to_string(),
+            resource_name: resource_name.to_string(),
+        };
+        engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, resource)
+    }
+
+    pub fn delete_resource(
+        &self,
+        cluster_name: &str,
+        tenant_name: &str,
+        resource_type: &str,
+        resource_name: &str,
+    ) -> Result<(), CommonError> {
+        if tenant_name.is_empty() {
+            return Ok(());
+        }
+        let key = storage_key_mqtt_tenant_resource(
+            cluster_name,
+            tenant_name,
+            resource_type,
+            resource_name,
+        );
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)
+    }
+
+    // Moves the resource from the tenant it belonged to, if any, to its current tenant
+    pub fn update_resource(
+        &self,
+        cluster_name: &str,
+        old_tenant_name: Option<&str>,
+        tenant_name: &str,
+        resource_type: &str,
+        resource_name: &str,
+    ) -> Result<(), CommonError> {
+        if let Some(old_tenant_name) = old_tenant_name {
+            if old_tenant_name != tenant_name {
+                self.delete_resource(cluster_name, old_tenant_name, resource_type, resource_name)?;
+            }
+        }
+        self.save_resource(cluster_name, tenant_name, resource_type, resource_name)
+    }
+
+    pub fn list_resource(
+        &self,
+        cluster_name: &str,
+        tenant_name: &str,
+    ) -> Result<Vec<MqttTenantResource>, CommonError> {
+        let prefix_key = storage_key_mqtt_tenant_resource_prefix(cluster_name, tenant_name);
+        let data = engine_prefix_list_by_cluster(self.rocksdb_engine_handler.clone(), prefix_key)?;
+        let mut results = Vec::new();
+        for raw in data {
+            results.push(serde_json::from_str::<MqttTenantResource>(&raw.data)?);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_base::config::placement_center::placement_center_test_conf;
+    use common_base::utils::file_utils::test_temp_dir;
+    use metadata_struct::mqtt::tenant::MqttTenant;
+
+    use crate::storage::mqtt::tenant::{
+        MqttTenantStorage, TENANT_RESOURCE_CONNECTOR, TENANT_RESOURCE_TOPIC,
+    };
+    use crate::storage::rocksdb::{column_family_list, RocksDBEngine};
+
+    #[tokio::test]
+    async fn tenant_storage_test() {
+        let config = placement_center_test_conf();
+
+        let rs = Arc::new(RocksDBEngine::new(
+            &test_temp_dir(),
+            config.rocksdb.max_open_files.unwrap(),
+            column_family_list(),
+        ));
+        let tenant_storage = MqttTenantStorage::new(rs);
+        let cluster_name = "test_cluster".to_string();
+
+        for tenant_name in ["tenant-a", "tenant-b"] {
+            let tenant = MqttTenant {
+                tenant_name: tenant_name.to_string(),
+                max_connections: 100,
+                ..Default::default()
+            };
+            tenant_storage
+                .save(&cluster_name, tenant_name, tenant)
+                .unwrap();
+        }
+
+        let res = tenant_storage.list(&cluster_name).unwrap();
+        assert_eq!(res.len(), 2);
+
+        let res = tenant_storage.get(&cluster_name, "tenant-a").unwrap();
+        assert_eq!(res.unwrap().max_connections, 100);
+
+        tenant_storage.delete(&cluster_name, "tenant-a").unwrap();
+        let res = tenant_storage.get(&cluster_name, "tenant-a").unwrap();
+        assert!(res.is_none());
+    }
+
+    #[tokio::test]
+    async fn tenant_resource_storage_test() {
+        let config = placement_center_test_conf();
+
+        let rs = Arc::new(RocksDBEngine::new(
+            &test_temp_dir(),
+            config.rocksdb.max_open_files.unwrap(),
+            column_family_list(),
+        ));
+        let tenant_storage = MqttTenantStorage::new(rs);
+        let cluster_name = "test_cluster".to_string();
+
+        tenant_storage
+            .save_resource(&cluster_name, "tenant-a", TENANT_RESOURCE_TOPIC, "a/b")
+            .unwrap();
+        tenant_storage
+            .save_resource(&cluster_name, "", TENANT_RESOURCE_TOPIC, "c/d")
+            .unwrap();
+        tenant_storage
+            .save_resource(&cluster_name, "tenant-ab", TENANT_RESOURCE_TOPIC, "e/f")
+            .unwrap();
+        let res = tenant_storage
+            .list_resource(&cluster_name, "tenant-a")
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].resource_name, "a/b");
+
+        tenant_storage
+            .update_resource(
+                &cluster_name,
+                Some("tenant-a"),
+                "tenant-b",
+                TENANT_RESOURCE_CONNECTOR,
+                "c1",
+            )
+            .unwrap();
+        tenant_storage
+            .update_resource(
+                &cluster_name,
+                Some("tenant-a"),
+                "tenant-b",
+                TENANT_RESOURCE_TOPIC,
+                "a/b",
+            )
+            .unwrap();
+        assert!(tenant_storage
+            .list_resource(&cluster_name, "tenant-a")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            tenant_storage
+                .list_resource(&cluster_name, "tenant-b")
+                .unwrap()
+                .len(),
+            2
+        );
+
+        tenant_storage
+            .delete_resource(&cluster_name, "tenant-b", TENANT_RESOURCE_TOPIC, "a/b")
+            .unwrap();
+        let res = tenant_storage
+            .list_resource(&cluster_name, "tenant-b")
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].resource_type, TENANT_RESOURCE_CONNECTOR);
+    }
+}
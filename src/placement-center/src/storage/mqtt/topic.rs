@@ -14,8 +14,10 @@
 
 use std::sync::Arc;
 
+use metadata_struct::mqtt::rule::MqttRule;
 use metadata_struct::mqtt::topic::MqttTopic;
 use metadata_struct::mqtt::topic_rewrite_rule::MqttTopicRewriteRule;
+use metadata_struct::mqtt::wasm_plugin::MqttWasmPlugin;
 
 use crate::core::error::PlacementCenterError;
 use crate::storage::engine::{
@@ -23,9 +25,12 @@
     engine_save_by_cluster,
 };
 use crate::storage::keys::{
-    storage_key_mqtt_topic, storage_key_mqtt_topic_cluster_prefix,
-    storage_key_mqtt_topic_rewrite_rule, storage_key_mqtt_topic_rewrite_rule_prefix,
+    storage_key_mqtt_rule, storage_key_mqtt_rule_prefix, storage_key_mqtt_topic,
+    storage_key_mqtt_topic_cluster_prefix, storage_key_mqtt_topic_rewrite_rule,
+    storage_key_mqtt_topic_rewrite_rule_prefix, storage_key_mqtt_wasm_plugin,
+    storage_key_mqtt_wasm_plugin_prefix,
 };
+use crate::storage::mqtt::tenant::{MqttTenantStorage, TENANT_RESOURCE_TOPIC};
 use crate::storage::rocksdb::RocksDBEngine;
 
 pub struct MqttTopicStorage {
@@ -45,6 +50,16 @@ pub fn save(
         topic_name: &str,
         topic: MqttTopic,
     ) -> Result<(), PlacementCenterError> {
+        let old_tenant = self
+            .get(cluster_name, topic_name)?
+            .map(|topic| topic.tenant);
+        MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).update_resource(
+            cluster_name,
+            old_tenant.as_deref(),
+            &topic.tenant,
+            TENANT_RESOURCE_TOPIC,
+            topic_name,
+        )?;
         let key = storage_key_mqtt_topic(cluster_name, topic_name);
         engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, topic)?;
         Ok(())
@@ -76,6 +91,14 @@ pub fn get(
     }
 
     pub fn delete(&self, cluster_name: &str, topic_name: &str) -> Result<(), PlacementCenterError> {
+        if let Some(topic) = self.get(cluster_name, topic_name)? {
+            MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).delete_resource(
+                cluster_name,
+                &topic.tenant,
+                TENANT_RESOURCE_TOPIC,
+                topic_name,
+            )?;
+        }
         let key: String = storage_key_mqtt_topic(cluster_name, topic_name);
         engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)?;
         Ok(())
@@ -117,6 +140,99 @@ pub fn list_topic_rewrite_rule(
         }
         Ok(results)
     }
+
+    pub fn save_rule(
+        &self,
+        cluster_name: &str,
+        rule_name: &str,
+        rule: MqttRule,
+    ) -> Result<(), PlacementCenterError> {
+        let key = storage_key_mqtt_rule(cluster_name, rule_name);
+        engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, rule)?;
+        Ok(())
+    }
+
+    pub fn get_rule(
+        &self,
+        cluster_name: &str,
+        rule_name: &str,
+    ) -> Result<Option<MqttRule>, PlacementCenterError> {
+        let key = storage_key_mqtt_rule(cluster_name, rule_name);
+        if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            let rule = serde_json::from_str::<MqttRule>(&data.data)?;
+            return Ok(Some(rule));
+        }
+        Ok(None)
+    }
+
+    pub fn delete_rule(
+        &self,
+        cluster_name: &str,
+        rule_name: &str,
+    ) -> Result<(), PlacementCenterError> {
+        let key = storage_key_mqtt_rule(cluster_name, rule_name);
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)?;
+        Ok(())
+    }
+
+    pub fn list_rule(&self, cluster_name: &str) -> Result<Vec<MqttRule>, PlacementCenterError> {
+        let prefix_key = storage_key_mqtt_rule_prefix(cluster_name);
+        let data = engine_prefix_list_by_cluster(self.rocksdb_engine_handler.clone(), prefix_key)?;
+        let mut results = Vec::new();
+        for raw in data {
+            let rule = serde_json::from_str::<MqttRule>(&raw.data)?;
+            results.push(rule);
+        }
+        Ok(results)
+    }
+
+    pub fn save_wasm_plugin(
+        &self,
+        cluster_name: &str,
+        plugin_name: &str,
+        plugin: MqttWasmPlugin,
+    ) -> Result<(), PlacementCenterError> {
+        let key = storage_key_mqtt_wasm_plugin(cluster_name, plugin_name);
+        engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, plugin)?;
+        Ok(())
+    }
+
+    pub fn get_wasm_plugin(
+        &self,
+        cluster_name: &str,
+        plugin_name: &str,
+    ) -> Result<Option<MqttWasmPlugin>, PlacementCenterError> {
+        let key = storage_key_mqtt_wasm_plugin(cluster_name, plugin_name);
+        if let Some(data) = engine_get_by_cluster(self.rocksdb_engine_handler.clone(), key)? {
+            let plugin = serde_json::from_str::<MqttWasmPlugin>(&data.data)?;
+            return Ok(Some(plugin));
+        }
+        Ok(None)
+    }
+
+    pub fn delete_wasm_plugin(
+        &self,
+        cluster_name: &str,
+        plugin_name: &str,
+    ) -> Result<(), PlacementCenterError> {
+        let key = storage_key_mqtt_wasm_plugin(cluster_name, plugin_name);
+        engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)?;
+        Ok(())
+    }
+
+    pub fn list_wasm_plugin(
+        &self,
+        cluster_name: &str,
+    ) -> Result<Vec<MqttWasmPlugin>, PlacementCenterError> {
+        let prefix_key = storage_key_mqtt_wasm_plugin_prefix(cluster_name);
+        let data = engine_prefix_list_by_cluster(self.rocksdb_engine_handler.clone(), prefix_key)?;
+        let mut results = Vec::new();
+        for raw in data {
+            let plugin = serde_json::from_str::<MqttWasmPlugin>(&raw.data)?;
+            results.push(plugin);
+        }
+        Ok(results)
+    }
 }
 
 #[cfg(test)]
@@ -150,6 +266,7 @@ async fn topic_storage_test() {
             retain_message: None,
             retain_message_expired_at: None,
             create_time: now_second(),
+            tenant: "".to_string(),
         };
         topic_storage
             .save(&cluster_name, &topic_name, topic)
@@ -163,6 +280,7 @@ async fn topic_storage_test() {
             retain_message: None,
             retain_message_expired_at: None,
             create_time: now_second(),
+            tenant: "".to_string(),
         };
         topic_storage
             .save(&cluster_name, &topic_name, topic)
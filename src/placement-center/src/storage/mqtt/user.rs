@@ -22,6 +22,7 @@
     engine_save_by_cluster,
 };
 use crate::storage::keys::{storage_key_mqtt_user, storage_key_mqtt_user_cluster_prefix};
+use crate::storage::mqtt::tenant::{MqttTenantStorage, TENANT_RESOURCE_USER};
 use crate::storage::rocksdb::RocksDBEngine;
 
 pub struct MqttUserStorage {
@@ -41,6 +42,14 @@ pub fn save(
         user_name: &str,
         user: MqttUser,
     ) -> Result<(), CommonError> {
+        let old_tenant = self.get(cluster_name, user_name)?.map(|user| user.tenant);
+        MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).update_resource(
+            cluster_name,
+            old_tenant.as_deref(),
+            &user.tenant,
+            TENANT_RESOURCE_USER,
+            user_name,
+        )?;
         let key = storage_key_mqtt_user(cluster_name, user_name);
         engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, user)
     }
@@ -64,6 +73,14 @@ pub fn get(&self, cluster_name: &str, username: &str) -> Result<Option<MqttUser>
     }
 
     pub fn delete(&self, cluster_name: &str, user_name: &str) -> Result<(), CommonError> {
+        if let Some(user) = self.get(cluster_name, user_name)? {
+            MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).delete_resource(
+                cluster_name,
+                &user.tenant,
+                TENANT_RESOURCE_USER,
+                user_name,
+            )?;
+        }
         let key: String = storage_key_mqtt_user(cluster_name, user_name);
         engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)
     }
@@ -96,6 +113,8 @@ async fn user_storage_test() {
             username: username.clone(),
             password: "pwd123".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         user_storage.save(&cluster_name, &username, user).unwrap();
 
@@ -104,6 +123,8 @@ async fn user_storage_test() {
             username: username.clone(),
             password: "pwd1231".to_string(),
             is_superuser: true,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         user_storage.save(&cluster_name, &username, user).unwrap();
 
@@ -22,6 +22,7 @@
     storage_key_mqtt_schema_bind_prefix_by_cluster,
     storage_key_mqtt_schema_bind_prefix_by_resource, storage_key_mqtt_schema_prefix,
 };
+use crate::storage::mqtt::tenant::{MqttTenantStorage, TENANT_RESOURCE_SCHEMA};
 use crate::storage::rocksdb::RocksDBEngine;
 use metadata_struct::schema::{SchemaData, SchemaResourceBind};
 use std::sync::Arc;
@@ -43,6 +44,16 @@ pub fn save(
         schema_name: &str,
         schema: &SchemaData,
     ) -> Result<(), PlacementCenterError> {
+        let old_tenant = self
+            .get(cluster_name, schema_name)?
+            .map(|schema| schema.tenant);
+        MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).update_resource(
+            cluster_name,
+            old_tenant.as_deref(),
+            &schema.tenant,
+            TENANT_RESOURCE_SCHEMA,
+            schema_name,
+        )?;
         let key = storage_key_mqtt_schema(cluster_name, schema_name);
         engine_save_by_cluster(self.rocksdb_engine_handler.clone(), key, schema)?;
         Ok(())
@@ -78,6 +89,14 @@ pub fn delete(
         cluster_name: &str,
         schema_name: &str,
     ) -> Result<(), PlacementCenterError> {
+        if let Some(schema) = self.get(cluster_name, schema_name)? {
+            MqttTenantStorage::new(self.rocksdb_engine_handler.clone()).delete_resource(
+                cluster_name,
+                &schema.tenant,
+                TENANT_RESOURCE_SCHEMA,
+                schema_name,
+            )?;
+        }
         let key: String = storage_key_mqtt_schema(cluster_name, schema_name);
         engine_delete_by_cluster(self.rocksdb_engine_handler.clone(), key)?;
         Ok(())
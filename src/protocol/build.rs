@@ -12,7 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{env, fs, path::PathBuf};
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     robustmq_proto_build::setup()?;
+
+    // The broker MQTT and placement center MQTT services are defined here, they are generated
+    // into their own directory so that the packages of robustmq-proto do not shadow them.
+    let vendored_dir = PathBuf::from(env::var("OUT_DIR")?).join("vendored");
+    fs::create_dir_all(&vendored_dir)?;
+    let vendored_protos = [
+        "proto/broker_mqtt/admin.proto",
+        "proto/broker_mqtt/inner.proto",
+        "proto/exhook.proto",
+        "proto/placement_center/mqtt.proto",
+    ];
+    tonic_build::configure()
+        .build_server(true)
+        .out_dir(&vendored_dir)
+        .compile_protos(&vendored_protos, &["proto"])?;
+    for proto in vendored_protos {
+        println!("cargo:rerun-if-changed={}", proto);
+    }
     Ok(())
 }
@@ -16,9 +16,13 @@
 #![allow(clippy::all)]
 
 pub mod broker_mqtt_admin {
-    tonic::include_proto!("broker.mqtt.admin");
+    include!(concat!(env!("OUT_DIR"), "/vendored/broker.mqtt.admin.rs"));
 }
 
 pub mod broker_mqtt_inner {
-    tonic::include_proto!("broker.mqtt.inner");
+    include!(concat!(env!("OUT_DIR"), "/vendored/broker.mqtt.inner.rs"));
+}
+
+pub mod broker_mqtt_exhook {
+    include!(concat!(env!("OUT_DIR"), "/vendored/broker.mqtt.exhook.rs"));
 }
@@ -28,7 +28,7 @@ pub mod placement_center_kv {
 }
 
 pub mod placement_center_mqtt {
-    tonic::include_proto!("placement.center.mqtt");
+    include!(concat!(env!("OUT_DIR"), "/vendored/placement.center.mqtt.rs"));
 }
 
 pub mod placement_center_openraft {
@@ -81,6 +81,7 @@ pub fn protofish_example_test() {
             schema_type: SchemaType::PROTOBUF,
             desc: "".to_string(),
             schema: schema.to_string(),
+            tenant: "".to_string(),
         };
 
         let res = protobuf_validate(&schema_data, b"\x0a\x05Perch", "Proto.Request");
@@ -126,6 +127,7 @@ pub fn protofish_schema_test() {
             schema_type: SchemaType::PROTOBUF,
             desc: "".to_string(),
             schema: schema.to_string(),
+            tenant: "".to_string(),
         };
 
         // ----- Experience -----
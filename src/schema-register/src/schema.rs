@@ -151,6 +151,7 @@ pub fn json_schema_test() {
             schema: schema_json_content.to_string(),
             schema_type: SchemaType::JSON,
             desc: "test".to_string(),
+            tenant: "".to_string(),
         });
 
         let topic_name = "t1".to_string();
@@ -232,6 +233,7 @@ pub fn avro_schema_test() {
             schema: schema_avro_content.to_string(),
             schema_type: SchemaType::AVRO,
             desc: "test".to_string(),
+            tenant: "".to_string(),
         });
 
         let topic_name = "t1".to_string();
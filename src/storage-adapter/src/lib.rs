@@ -49,6 +49,14 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
     }
 }
 
+impl StorageType {
+    // Whether the data written by one node is read by the other nodes of the cluster. The memory
+    // and the local RocksDB storage keep it on the node that wrote it.
+    pub fn is_shared(&self) -> bool {
+        !matches!(self, StorageType::Memory | StorageType::RocksDB)
+    }
+}
+
 // pub fn storage_is_journal(storage_type: &str) -> bool {
 
 // }
@@ -110,4 +118,13 @@ fn storage_type_from_str() {
         );
         assert_eq!(StorageType::from_str("minio").unwrap(), StorageType::MinIO);
     }
+
+    #[test]
+    fn storage_type_is_shared() {
+        assert!(!StorageType::Memory.is_shared());
+        assert!(!StorageType::RocksDB.is_shared());
+        assert!(StorageType::Journal.is_shared());
+        assert!(StorageType::Mysql.is_shared());
+        assert!(StorageType::Placement.is_shared());
+    }
 }
@@ -26,6 +26,8 @@
 pub struct MemoryStorageAdapter {
     pub shard_info: DashMap<String, ShardInfo>,
     pub shard_data: DashMap<String, Vec<Record>>,
+    // the offset of the first record of shard_data, the records below it are trimmed
+    pub shard_start_offset: DashMap<String, u64>,
     //group, (namespace_shard_name,offset)
     pub group_data: DashMap<String, DashMap<String, u64>>,
 }
@@ -40,6 +42,7 @@ impl MemoryStorageAdapter {
     pub fn new() -> Self {
         MemoryStorageAdapter {
             shard_data: DashMap::with_capacity(256),
+            shard_start_offset: DashMap::with_capacity(256),
             group_data: DashMap::with_capacity(256),
             shard_info: DashMap::with_capacity(2),
         }
@@ -48,6 +51,19 @@ pub fn new() -> Self {
     pub fn shard_key(&self, namespace: &str, shard_name: &str) -> String {
         format!("{}_{}", namespace, shard_name)
     }
+
+    // The position of the record with the offset in shard_data. It must be called while
+    // shard_data of the shard is locked, so that it is not trimmed meanwhile.
+    fn record_index(&self, shard_key: &str, offset: u64) -> u64 {
+        offset.saturating_sub(self.start_offset(shard_key))
+    }
+
+    fn start_offset(&self, shard_key: &str) -> u64 {
+        self.shard_start_offset
+            .get(shard_key)
+            .map(|raw| *raw)
+            .unwrap_or(0)
+    }
 }
 
 impl MemoryStorageAdapter {}
@@ -57,6 +73,7 @@ impl StorageAdapter for MemoryStorageAdapter {
     async fn create_shard(&self, shard: ShardInfo) -> Result<(), CommonError> {
         let key = self.shard_key(&shard.namespace, &shard.shard_name);
         self.shard_data.insert(key.clone(), Vec::new());
+        self.shard_start_offset.remove(&key);
         self.shard_info.insert(key.clone(), shard);
         return Ok(());
     }
@@ -77,8 +94,9 @@ async fn list_shard(
     }
 
     async fn delete_shard(&self, namespace: String, shard_name: String) -> Result<(), CommonError> {
-        self.shard_data
-            .remove(&self.shard_key(&namespace, &shard_name));
+        let shard_key = self.shard_key(&namespace, &shard_name);
+        self.shard_data.remove(&shard_key);
+        self.shard_start_offset.remove(&shard_key);
         return Ok(());
     }
 
@@ -92,7 +110,7 @@ async fn batch_write(
         let mut offset_res = Vec::new();
 
         if let Some(mut data_list) = self.shard_data.get_mut(&shard_key) {
-            let mut start_offset = data_list.len();
+            let mut start_offset = self.start_offset(&shard_key) as usize + data_list.len();
             for mut msg in messages {
                 offset_res.push(start_offset as u64);
                 msg.offset = Some(start_offset as u64);
@@ -122,7 +140,7 @@ async fn write(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         let offset = if let Some(mut data_list) = self.shard_data.get_mut(&shard_key) {
-            let start_offset = data_list.len();
+            let start_offset = self.start_offset(&shard_key) as usize + data_list.len();
 
             data.offset = Some(start_offset as u64);
             data_list.push(data);
@@ -147,12 +165,13 @@ async fn read_by_offset(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(data_list) = self.shard_data.get(&shard_key) {
-            if data_list.len() < offset as usize {
+            let index = self.record_index(&shard_key, offset);
+            if data_list.len() < index as usize {
                 return Ok(Vec::new());
             }
 
             let mut result = Vec::new();
-            for i in offset..(offset + read_config.max_record_num) {
+            for i in index..(index + read_config.max_record_num) {
                 if let Some(value) = data_list.get(i as usize) {
                     result.push(value.clone());
                 } else {
@@ -176,12 +195,13 @@ async fn read_by_tag(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(record_list) = self.shard_data.get(&shard_key) {
-            if record_list.len() < offset as usize {
+            let index = self.record_index(&shard_key, offset);
+            if record_list.len() < index as usize {
                 return Ok(Vec::new());
             }
             let mut result = Vec::new();
 
-            for i in offset..(offset + read_config.max_record_num) {
+            for i in index..(index + read_config.max_record_num) {
                 if let Some(value) = record_list.get(i as usize) {
                     if value.tags.contains(&tag) {
                         result.push(value.clone());
@@ -207,12 +227,13 @@ async fn read_by_key(
         let shard_key = self.shard_key(&namespace, &shard_name);
 
         if let Some(record_list) = self.shard_data.get(&shard_key) {
-            if record_list.len() < offset as usize {
+            let index = self.record_index(&shard_key, offset);
+            if record_list.len() < index as usize {
                 return Ok(Vec::new());
             }
             let mut result = Vec::new();
 
-            for i in offset..(offset + read_config.max_record_num) {
+            for i in index..(index + read_config.max_record_num) {
                 if let Some(value) = record_list.get(i as usize) {
                     if value.key == key {
                         result.push(value.clone());
@@ -291,6 +312,28 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn trim_shard(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let shard_key = self.shard_key(&namespace, &shard_name);
+
+        if let Some(mut data_list) = self.shard_data.get_mut(&shard_key) {
+            let start_offset = self.start_offset(&shard_key);
+            if offset <= start_offset {
+                return Ok(());
+            }
+            let count = ((offset - start_offset) as usize).min(data_list.len());
+            data_list.drain(..count);
+            self.shard_start_offset
+                .insert(shard_key, start_offset + count as u64);
+        }
+
+        Ok(())
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         Ok(())
     }
@@ -464,4 +507,90 @@ async fn stream_read_write() {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn trim_shard_test() {
+        let storage_adapter = MemoryStorageAdapter::new();
+        let namespace = unique_id();
+        let shard_name = "test-trim".to_string();
+
+        let mut data = Vec::new();
+        for i in 0..5 {
+            let mut record = Record::build_str(format!("data{}", i));
+            record.set_key(format!("key{}", i));
+            data.push(record);
+        }
+        storage_adapter
+            .batch_write(namespace.clone(), shard_name.clone(), data)
+            .await
+            .unwrap();
+
+        let read_config = ReadConfig {
+            max_record_num: 100,
+            max_size: u64::MAX,
+        };
+
+        storage_adapter
+            .trim_shard(namespace.clone(), shard_name.clone(), 3)
+            .await
+            .unwrap();
+
+        let records = storage_adapter
+            .read_by_offset(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records.first().unwrap().offset, Some(3));
+
+        let records = storage_adapter
+            .read_by_key(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "key1".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+
+        let records = storage_adapter
+            .read_by_key(
+                namespace.clone(),
+                shard_name.clone(),
+                4,
+                "key4".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.first().unwrap().offset, Some(4));
+
+        // the offsets keep growing after the shard is trimmed
+        storage_adapter
+            .trim_shard(namespace.clone(), shard_name.clone(), 10)
+            .await
+            .unwrap();
+        let offset = storage_adapter
+            .write(
+                namespace.clone(),
+                shard_name.clone(),
+                Record::build_str("data5".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(offset, 5);
+
+        let records = storage_adapter
+            .read_by_offset(namespace, shard_name, 5, read_config)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records.first().unwrap().offset, Some(5));
+    }
 }
@@ -32,6 +32,28 @@
 
 use crate::storage::{ShardInfo, ShardOffset, StorageAdapter};
 
+// Builds the operator of an S3 compatible storage such as MinIO, the region is detected
+// by the storage if it is empty.
+pub fn build_s3_operator(
+    endpoint: &str,
+    bucket: &str,
+    root: &str,
+    access_key_id: &str,
+    secret_access_key: &str,
+    region: &str,
+) -> Result<Operator, CommonError> {
+    let mut builder = S3::default()
+        .root(root)
+        .bucket(bucket)
+        .endpoint(endpoint)
+        .access_key_id(access_key_id)
+        .secret_access_key(secret_access_key);
+    if !region.is_empty() {
+        builder = builder.region(region);
+    }
+    Ok(Operator::new(builder)?.finish())
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 struct WriteThreadData {
@@ -74,14 +96,15 @@ struct MinIoStorageAdapter {
 #[allow(dead_code)]
 impl MinIoStorageAdapter {
     pub fn new(data_dir: impl AsRef<str>, bucket: impl AsRef<str>) -> Result<Self, CommonError> {
-        let builder = S3::default()
-            .root(data_dir.as_ref())
-            .bucket(bucket.as_ref())
-            .endpoint("http://127.0.0.1:9000")
-            .access_key_id("minioadmin")
-            .secret_access_key("minioadmin");
         Ok(Self {
-            op: Operator::new(builder)?.finish(),
+            op: build_s3_operator(
+                "http://127.0.0.1:9000",
+                bucket.as_ref(),
+                data_dir.as_ref(),
+                "minioadmin",
+                "minioadmin",
+                "",
+            )?,
             write_handles: DashMap::with_capacity(2),
         })
     }
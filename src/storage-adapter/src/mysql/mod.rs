@@ -635,6 +635,39 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn trim_shard(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        let mut conn = self.pool.get_conn()?;
+
+        let delete_records_sql = format!(
+            "DELETE FROM `{}` WHERE `offset` < :offset",
+            Self::record_table_name(&namespace, &shard_name)
+        );
+
+        conn.exec_drop(delete_records_sql, params! { "offset" => offset })?;
+
+        let delete_tags_sql = format!(
+            "DELETE FROM `{}`
+            WHERE namespace = :namespace AND shard = :shard AND m_offset < :offset",
+            Self::tags_table_name()
+        );
+
+        conn.exec_drop(
+            delete_tags_sql,
+            params! {
+                "namespace" => namespace,
+                "shard" => shard_name,
+                "offset" => offset,
+            },
+        )?;
+
+        Ok(())
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         self.stop_send.send(true).await.map_err(|err| {
             CommonError::CommonError(format!("Failed to send stop signal: {}", err))
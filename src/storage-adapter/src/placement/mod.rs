@@ -689,6 +689,77 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn trim_shard(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        // the records below the last trimmed offset are already deleted
+        for i in (0..offset).rev() {
+            let shard_record_key = Self::shard_record_key(&namespace, &shard_name, i);
+            let reply = placement_get(
+                &self.client_pool,
+                &self.addrs,
+                GetRequest {
+                    key: shard_record_key.clone(),
+                },
+            )
+            .await?;
+
+            if reply.value.is_empty() {
+                break;
+            }
+
+            let record = serde_json::from_str::<Record>(&reply.value)?;
+
+            if !record.key.is_empty() {
+                let key_offset_key = Self::key_offset_key(&namespace, &shard_name, &record.key);
+                let reply = placement_get(
+                    &self.client_pool,
+                    &self.addrs,
+                    GetRequest {
+                        key: key_offset_key.clone(),
+                    },
+                )
+                .await?;
+
+                if serde_json::from_str::<u64>(&reply.value).ok() == Some(i) {
+                    placement_delete(
+                        &self.client_pool,
+                        &self.addrs,
+                        DeleteRequest {
+                            key: key_offset_key,
+                        },
+                    )
+                    .await?;
+                }
+            }
+
+            for tag in record.tags.iter() {
+                placement_delete(
+                    &self.client_pool,
+                    &self.addrs,
+                    DeleteRequest {
+                        key: Self::tag_offsets_key(&namespace, &shard_name, tag, i),
+                    },
+                )
+                .await?;
+            }
+
+            placement_delete(
+                &self.client_pool,
+                &self.addrs,
+                DeleteRequest {
+                    key: shard_record_key,
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         let write_handles = self.get_all_write_handles().await;
 
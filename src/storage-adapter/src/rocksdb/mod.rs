@@ -528,7 +528,7 @@ async fn read_by_key(
 
         match self.db.read::<u64>(cf.clone(), &key_offset_key)? {
             Some(key_offset) if key_offset >= offset && read_config.max_record_num >= 1 => {
-                let shard_record_key = Self::shard_record_key(&namespace, &shard_name, offset);
+                let shard_record_key = Self::shard_record_key(&namespace, &shard_name, key_offset);
                 let record = self
                     .db
                     .read::<Record>(cf.clone(), &shard_record_key)?
@@ -619,6 +619,43 @@ async fn commit_offset(
         Ok(())
     }
 
+    async fn trim_shard(
+        &self,
+        namespace: String,
+        shard_name: String,
+        offset: u64,
+    ) -> Result<(), CommonError> {
+        self.ensure_shard_exists(&namespace, &shard_name)?;
+
+        let cf = self.db.cf_handle(DB_COLUMN_FAMILY).unwrap();
+
+        // the records below the last trimmed offset are already deleted
+        for i in (0..offset).rev() {
+            let shard_record_key = Self::shard_record_key(&namespace, &shard_name, i);
+            let Some(record) = self.db.read::<Record>(cf.clone(), &shard_record_key)? else {
+                break;
+            };
+
+            if !record.key.is_empty() {
+                let key_offset_key = Self::key_offset_key(&namespace, &shard_name, &record.key);
+                if self.db.read::<u64>(cf.clone(), &key_offset_key)? == Some(i) {
+                    self.db.delete(cf.clone(), &key_offset_key)?;
+                }
+            }
+
+            for tag in record.tags.iter() {
+                self.db.delete(
+                    cf.clone(),
+                    &Self::tag_offsets_key(&namespace, &shard_name, tag, i),
+                )?;
+            }
+
+            self.db.delete(cf.clone(), &shard_record_key)?;
+        }
+
+        Ok(())
+    }
+
     async fn close(&self) -> Result<(), CommonError> {
         let write_handles = self.get_all_write_handles().await;
 
@@ -1049,4 +1086,118 @@ async fn concurrency_test() {
 
         let _ = std::fs::remove_dir_all(&db_path);
     }
+
+    #[tokio::test]
+    async fn trim_shard_test() {
+        let db_path = format!("/tmp/robustmq_{}", unique_id());
+
+        let storage_adapter = RocksDBStorageAdapter::new(db_path.as_str(), 100);
+        let namespace = unique_id();
+        let shard_name = "test-trim".to_string();
+
+        storage_adapter
+            .create_shard(ShardInfo {
+                namespace: namespace.clone(),
+                shard_name: shard_name.clone(),
+                replica_num: 1,
+            })
+            .await
+            .unwrap();
+
+        let mut data = Vec::new();
+        for i in 0..5 {
+            let mut record = Record::build_str(format!("data{}", i));
+            record.set_key(format!("key{}", i));
+            record.set_tags(vec!["tag".to_string()]);
+            data.push(record);
+        }
+        storage_adapter
+            .batch_write(namespace.clone(), shard_name.clone(), data)
+            .await
+            .unwrap();
+
+        let read_config = ReadConfig {
+            max_record_num: u64::MAX,
+            max_size: u64::MAX,
+        };
+
+        storage_adapter
+            .trim_shard(namespace.clone(), shard_name.clone(), 3)
+            .await
+            .unwrap();
+
+        let records = storage_adapter
+            .read_by_offset(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+
+        let records = storage_adapter
+            .read_by_offset(
+                namespace.clone(),
+                shard_name.clone(),
+                3,
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+
+        let records = storage_adapter
+            .read_by_key(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "key1".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+
+        let records = storage_adapter
+            .read_by_key(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "key4".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records.first().unwrap().offset, Some(4));
+
+        let records = storage_adapter
+            .read_by_tag(
+                namespace.clone(),
+                shard_name.clone(),
+                0,
+                "tag".to_string(),
+                read_config.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+
+        // trimming again only deletes the records above the last trim
+        storage_adapter
+            .trim_shard(namespace.clone(), shard_name.clone(), 4)
+            .await
+            .unwrap();
+        let records = storage_adapter
+            .read_by_offset(namespace.clone(), shard_name.clone(), 3, read_config)
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+
+        storage_adapter.close().await.unwrap();
+
+        let _ = std::fs::remove_dir_all(&db_path);
+    }
 }
@@ -106,5 +106,19 @@ async fn commit_offset(
         offset: HashMap<String, u64>,
     ) -> Result<(), CommonError>;
 
+    // Deletes the records of the shard below the offset, which are no longer needed by the
+    // reader. Fails on the storages that cannot delete single records, they keep them.
+    async fn trim_shard(
+        &self,
+        namespace: String,
+        shard_name: String,
+        _offset: u64,
+    ) -> Result<(), CommonError> {
+        Err(CommonError::CommonError(format!(
+            "Shard {} under namespace {} cannot be trimmed, the storage does not support it",
+            shard_name, namespace
+        )))
+    }
+
     async fn close(&self) -> Result<(), CommonError>;
 }
@@ -47,6 +47,7 @@ async fn acl_storage_test() {
             ip: "*".to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
 
         create_acl(
@@ -59,6 +60,7 @@ async fn acl_storage_test() {
 
         let list_request = ListAclRequest {
             cluster_name: cluster_name.clone(),
+            tenant: "".to_string(),
         };
         match mqtt_broker_list_acl(&client_pool, &grpc_addr, list_request.clone()).await {
             Ok(data) => {
@@ -146,6 +148,7 @@ async fn user_publish_authorization_test() {
             ip: "*".to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
 
         create_acl(
@@ -205,6 +208,7 @@ async fn client_publish_authorization_test() {
             ip: "*".to_string(),
             action: MqttAclAction::Publish,
             permission: MqttAclPermission::Deny,
+            tenant: "".to_string(),
         };
 
         create_acl(
@@ -334,6 +338,8 @@ async fn create_user(
             username,
             password,
             is_superuser: false,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         let res = mqtt_broker_create_user(&client_pool, &grpc_addr, user.clone()).await;
         assert!(res.is_ok());
@@ -105,6 +105,8 @@ async fn create_user(
             username,
             password,
             is_superuser: false,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         let res = mqtt_broker_create_user(&client_pool, &addrs, user.clone()).await;
         assert!(res.is_ok());
@@ -34,6 +34,8 @@ async fn user_test() {
             username: username.clone(),
             password: password.clone(),
             is_superuser,
+            mountpoint: "".to_string(),
+            tenant: "".to_string(),
         };
         user_storage.save_user(user_info).await.unwrap();
 